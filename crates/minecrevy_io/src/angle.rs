@@ -0,0 +1,84 @@
+//! The [`Angle`]s that entity rotations are sent as.
+
+use std::io;
+
+use crate::{McRead, McWrite};
+
+/// An angle in steps of 1/256 of a full turn, as the protocol sends entity
+/// rotations.
+///
+/// Encoded as an unsigned byte holding the number of steps.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    /// Returns the [`Angle`] closest to the given angle in degrees, rounding
+    /// down like vanilla does.
+    ///
+    /// Angles outside of `0.0..360.0` wrap around, so `-90.0` and `270.0` are
+    /// the same angle.
+    pub fn from_degrees(degrees: f32) -> Self {
+        // Truncating to a byte wraps the angle around.
+        Self((degrees * 256.0 / 360.0).floor() as i32 as u8)
+    }
+
+    /// Returns this angle in degrees, from `0.0` to less than `360.0`.
+    pub fn to_degrees(self) -> f32 {
+        f32::from(self.0) * 360.0 / 256.0
+    }
+}
+
+impl McRead for Angle {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        u8::read(reader, ()).map(Self)
+    }
+}
+
+impl McWrite for Angle {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(writer, ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Angle;
+    use crate::{McRead, McWrite};
+
+    #[test]
+    fn from_degrees_rounds_down() {
+        assert_eq!(Angle::from_degrees(0.0), Angle(0));
+        assert_eq!(Angle::from_degrees(1.0), Angle(0));
+        assert_eq!(Angle::from_degrees(1.40625), Angle(1));
+        assert_eq!(Angle::from_degrees(90.0), Angle(64));
+        assert_eq!(Angle::from_degrees(359.9), Angle(255));
+    }
+
+    #[test]
+    fn from_degrees_wraps_around() {
+        assert_eq!(Angle::from_degrees(360.0), Angle(0));
+        assert_eq!(Angle::from_degrees(450.0), Angle(64));
+        assert_eq!(Angle::from_degrees(-90.0), Angle(192));
+        assert_eq!(Angle::from_degrees(-90.0), Angle::from_degrees(270.0));
+        assert_eq!(Angle::from_degrees(-1.0), Angle(255));
+    }
+
+    #[test]
+    fn to_degrees() {
+        assert_eq!(Angle(0).to_degrees(), 0.0);
+        assert_eq!(Angle(64).to_degrees(), 90.0);
+        assert_eq!(Angle(192).to_degrees(), 270.0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = Vec::new();
+        Angle(200).write(&mut bytes, ()).unwrap();
+        assert_eq!(bytes, [200]);
+        assert_eq!(Angle::read(bytes.as_slice(), ()).unwrap(), Angle(200));
+    }
+}
@@ -0,0 +1,136 @@
+//! Structured errors for decoding Minecraft data types.
+
+use std::io;
+
+use thiserror::Error;
+
+/// An error that occurred while decoding a Minecraft data type.
+///
+/// Readers return [`io::Error`]s, which wrap a [`DecodeError`] for every
+/// failure other than the underlying reader's own errors. Converting an
+/// [`io::Error`] back with [`DecodeError::from`] recovers the original
+/// variant, so callers can tell failures apart without matching on messages.
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    /// The underlying reader failed.
+    #[error(transparent)]
+    Io(io::Error),
+    /// The input ended before the value was fully read.
+    #[error("unexpected end of input")]
+    Truncated,
+    /// A VarInt or VarLong was encoded with more bytes than its type allows.
+    #[error("{0} is too big")]
+    VarIntTooLong(&'static str),
+    /// A length or count was negative, or otherwise impossible.
+    #[error("invalid length: {0}")]
+    InvalidLength(i64),
+    /// A length or count was larger than allowed.
+    #[error("exceeded max length (max: {max}, actual: {actual})")]
+    LengthExceeded {
+        /// The maximum allowed length.
+        max: usize,
+        /// The length that was read.
+        actual: usize,
+    },
+    /// An enum discriminant didn't match any of the type's variants.
+    #[error("invalid {type_name} discriminant: {value}")]
+    InvalidDiscriminant {
+        /// The name of the type being read.
+        type_name: &'static str,
+        /// The discriminant that was read.
+        value: i64,
+    },
+    /// A string wasn't valid UTF-8.
+    #[error("string has invalid UTF-8 characters")]
+    InvalidUtf8,
+}
+
+impl DecodeError {
+    /// Returns an [`DecodeError::InvalidDiscriminant`] error for the type `T`.
+    pub fn invalid_discriminant<T>(value: impl Into<i64>) -> Self {
+        Self::InvalidDiscriminant {
+            type_name: std::any::type_name::<T>(),
+            value: value.into(),
+        }
+    }
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(error: DecodeError) -> Self {
+        match error {
+            DecodeError::Io(error) => error,
+            DecodeError::Truncated => io::Error::new(io::ErrorKind::UnexpectedEof, error),
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(error: io::Error) -> Self {
+        if error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<DecodeError>())
+        {
+            // Checked above, so neither can fail.
+            let inner = error.into_inner().unwrap();
+            return *inner.downcast::<DecodeError>().unwrap();
+        }
+
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => DecodeError::Truncated,
+            _ => DecodeError::Io(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::DecodeError;
+    use crate::prelude::ReadMinecraftExt;
+
+    #[test]
+    fn truncated_read() {
+        let error = [0x01].as_slice().read_i32().unwrap_err();
+        assert!(matches!(DecodeError::from(error), DecodeError::Truncated));
+
+        let error = [0x80].as_slice().read_var_i32().unwrap_err();
+        assert!(matches!(DecodeError::from(error), DecodeError::Truncated));
+    }
+
+    #[test]
+    fn invalid_varint() {
+        let error = [0xFF; 6].as_slice().read_var_i32().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            DecodeError::from(error),
+            DecodeError::VarIntTooLong(_)
+        ));
+    }
+
+    #[test]
+    fn io_error_kinds() {
+        let truncated = io::Error::from(DecodeError::Truncated);
+        assert_eq!(truncated.kind(), io::ErrorKind::UnexpectedEof);
+
+        let invalid = io::Error::from(DecodeError::invalid_discriminant::<bool>(2));
+        assert_eq!(invalid.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            DecodeError::from(invalid),
+            DecodeError::InvalidDiscriminant { value: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn io_errors_are_kept() {
+        let error = io::Error::new(io::ErrorKind::ConnectionReset, "reset");
+        let DecodeError::Io(error) = DecodeError::from(error) else {
+            panic!("expected an I/O error");
+        };
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+
+        let error = io::Error::from(DecodeError::Io(error));
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+    }
+}
@@ -3,9 +3,10 @@
 use std::io::{self, Read, Write};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use glam::IVec3;
 use uuid::Uuid;
 
-use crate::{packet::RawPacket, util::varint_bytes};
+use crate::{angle::Angle, error::DecodeError, packet::RawPacket, util::MAX_PREALLOC_BYTES};
 
 /// Extends [Read] with methods for reading [Minecraft protocol data types][1].
 ///
@@ -153,11 +154,42 @@ pub trait ReadMinecraftExt: Read {
         Ok(Uuid::from_u128(self.read_u128()?))
     }
 
+    /// Reads a block position packed into a 64 bit integer from the underlying reader.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying reader returns an error, this function will return that error.
+    #[inline]
+    fn read_position(&mut self) -> io::Result<IVec3> {
+        let v = self.read_i64()?;
+        // Shift each field to the top before shifting it back down, to sign-extend it.
+        Ok(IVec3::new(
+            (v >> 38) as i32,
+            (v << 52 >> 52) as i32,
+            (v << 26 >> 38) as i32,
+        ))
+    }
+
+    /// Reads an angle in steps of 1/256 of a full turn from the underlying reader, in degrees.
+    ///
+    /// See [`Angle::to_degrees`].
+    ///
+    /// # Errors
+    ///
+    /// If the underlying reader returns an error, this function will return that error.
+    #[inline]
+    fn read_angle(&mut self) -> io::Result<f32> {
+        Ok(Angle(self.read_u8()?).to_degrees())
+    }
+
     /// Reads a signed 32 bit integer from the underlying reader, using variable-length encoding.
     ///
     /// # Errors
     ///
     /// If the underlying reader returns an error, this function will return that error.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the VarInt is longer
+    /// than 5 bytes.
     fn read_var_i32(&mut self) -> io::Result<i32> {
         pub const SEGMENT: u8 = 0b0111_1111;
         pub const CONTINUE: u8 = 0b1000_0000;
@@ -170,13 +202,11 @@ pub trait ReadMinecraftExt: Read {
             value |= (i32::from(byte & SEGMENT)) << position;
             position += 7;
 
-            if position >= 32 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "VarInt is too big",
-                ))?;
-            } else if byte & CONTINUE != CONTINUE {
+            if byte & CONTINUE != CONTINUE {
                 break;
+            } else if position >= 32 {
+                // A continuation bit on the last allowed byte.
+                return Err(DecodeError::VarIntTooLong("VarInt").into());
             }
         }
 
@@ -190,12 +220,8 @@ pub trait ReadMinecraftExt: Read {
     /// If the underlying reader returns an error, this function will return that error.
     fn read_var_i32_len(&mut self) -> io::Result<usize> {
         let value = self.read_var_i32()?;
-        let value = usize::try_from(value).map_err(|_| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("invalid VarInt value as length: {}", value),
-            )
-        })?;
+        let value =
+            usize::try_from(value).map_err(|_| DecodeError::InvalidLength(i64::from(value)))?;
         Ok(value)
     }
 
@@ -204,6 +230,9 @@ pub trait ReadMinecraftExt: Read {
     /// # Errors
     ///
     /// If the underlying reader returns an error, this function will return that error.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the VarLong is longer
+    /// than 10 bytes.
     fn read_var_i64(&mut self) -> io::Result<i64> {
         pub const SEGMENT: u8 = 0b0111_1111;
         pub const CONTINUE: u8 = 0b1000_0000;
@@ -216,13 +245,11 @@ pub trait ReadMinecraftExt: Read {
             value |= (i64::from(byte & SEGMENT)) << position;
             position += 7;
 
-            if position >= 64 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "VarLong is too big",
-                ))?;
-            } else if byte & CONTINUE != CONTINUE {
+            if byte & CONTINUE != CONTINUE {
                 break;
+            } else if position >= 64 {
+                // A continuation bit on the last allowed byte.
+                return Err(DecodeError::VarIntTooLong("VarLong").into());
             }
         }
 
@@ -236,8 +263,24 @@ pub trait ReadMinecraftExt: Read {
     /// If the underlying reader returns an error, this function will return that error.
     fn read_bytes_var_i32(&mut self) -> io::Result<Vec<u8>> {
         let len = self.read_var_i32_len()?;
-        let mut bytes = vec![0; len];
-        self.read_exact(&mut bytes)?;
+        self.read_bytes_exact(len)
+    }
+
+    /// Reads exactly `len` bytes from the underlying reader.
+    ///
+    /// Unlike reading into a buffer of `len` bytes, memory is only allocated
+    /// for bytes that are actually read, so a bogus length can't exhaust it.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying reader ends before `len` bytes are read, this function
+    /// will return an [`io::ErrorKind::UnexpectedEof`] error.
+    fn read_bytes_exact(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len.min(MAX_PREALLOC_BYTES));
+        self.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(DecodeError::Truncated.into());
+        }
         Ok(bytes)
     }
 
@@ -259,12 +302,29 @@ pub trait ReadMinecraftExt: Read {
     /// If the underlying reader returns an error, this function will return that error.
     fn read_string(&mut self) -> io::Result<String> {
         let bytes = self.read_bytes_var_i32()?;
-        let string = String::from_utf8(bytes).map_err(|_| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "string has invalid utf8 characters",
-            )
-        })?;
+        let string = String::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)?;
+        Ok(string)
+    }
+
+    /// Reads a [String] of at most `max_len` bytes from the underlying reader, with a
+    /// variable-length 32 bit integer as the length prefix.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying reader returns an error, this function will return that error.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the length prefix exceeds `max_len`.
+    fn read_string_max_len(&mut self, max_len: usize) -> io::Result<String> {
+        let len = self.read_var_i32_len()?;
+        if len > max_len {
+            return Err(DecodeError::LengthExceeded {
+                max: max_len,
+                actual: len,
+            }
+            .into());
+        }
+        let bytes = self.read_bytes_exact(len)?;
+        let string = String::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)?;
         Ok(string)
     }
 
@@ -273,15 +333,30 @@ pub trait ReadMinecraftExt: Read {
     /// # Errors
     ///
     /// If the underlying reader returns an error, this function will return that error.
+    ///
+    /// Returns an [`io::ErrorKind::UnexpectedEof`] error if the whole packet
+    /// hasn't been received yet, and an [`io::ErrorKind::InvalidData`] error if
+    /// the packet is malformed.
     fn read_packet(&mut self) -> io::Result<RawPacket> {
         let len = self.read_var_i32_len()?;
-        let mut reader = self.take(len as u64);
-
-        let id = reader.read_var_i32()?;
+        if len == 0 {
+            return Err(DecodeError::InvalidLength(0).into());
+        } else if len > RawPacket::MAX_LEN {
+            return Err(DecodeError::LengthExceeded {
+                max: RawPacket::MAX_LEN,
+                actual: len,
+            }
+            .into());
+        }
 
-        let remaining_len = len - varint_bytes(id);
-        let mut body = Vec::with_capacity(remaining_len);
-        reader.read_to_end(&mut body)?;
+        let frame = self.read_bytes_exact(len)?;
+        let mut reader = &frame[..];
+        let id = reader.read_var_i32().map_err(|e| match e.kind() {
+            // The frame was read in full, so the ID itself is malformed.
+            io::ErrorKind::UnexpectedEof => DecodeError::InvalidLength(len as i64).into(),
+            _ => e,
+        })?;
+        let body = reader.to_vec();
 
         Ok(RawPacket { id, body })
     }
@@ -434,6 +509,34 @@ pub trait WriteMinecraftExt: Write {
         self.write_u128(v.as_u128())
     }
 
+    /// Writes a block position packed into a 64 bit integer to the underlying writer.
+    ///
+    /// Coordinates outside of the packed range (26 bits for X and Z, 12 bits for Y) wrap around.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying writer returns an error, this function will return that error.
+    #[inline]
+    fn write_position(&mut self, v: IVec3) -> io::Result<()> {
+        self.write_u64(
+            ((v.x as u64 & 0x03FF_FFFF) << 38)
+                | ((v.z as u64 & 0x03FF_FFFF) << 12)
+                | (v.y as u64 & 0xFFF),
+        )
+    }
+
+    /// Writes an angle in degrees to the underlying writer, in steps of 1/256 of a full turn.
+    ///
+    /// See [`Angle::from_degrees`].
+    ///
+    /// # Errors
+    ///
+    /// If the underlying writer returns an error, this function will return that error.
+    #[inline]
+    fn write_angle(&mut self, degrees: f32) -> io::Result<()> {
+        self.write_u8(Angle::from_degrees(degrees).0)
+    }
+
     /// Writes a signed 32 bit integer to the underlying writer, using variable-length encoding.
     ///
     /// # Errors
@@ -453,7 +556,7 @@ pub trait WriteMinecraftExt: Write {
             self.write_u16(w as u16)?;
         } else if (value & (MASK << 21)) == 0 {
             let w = (value & SEGMENT | CONTINUE) << 16
-                | ((value >> 17) & SEGMENT | CONTINUE) << 8
+                | ((value >> 7) & SEGMENT | CONTINUE) << 8
                 | (value >> 14);
             self.write_u24::<BigEndian>(w)?;
         } else if (value & (MASK << 28)) == 0 {
@@ -495,7 +598,7 @@ pub trait WriteMinecraftExt: Write {
     ///
     /// If the underlying writer returns an error, this function will return that error.
     fn write_var_i64(&mut self, v: i64) -> io::Result<()> {
-        let mut v = v as u32;
+        let mut v = v as u64;
         loop {
             if (v & !0x7F) == 0 {
                 self.write_u8(v as u8)?;
@@ -551,3 +654,238 @@ pub trait WriteMinecraftExt: Write {
 }
 
 impl<T: Write> WriteMinecraftExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use glam::IVec3;
+    use uuid::Uuid;
+
+    use super::{ReadMinecraftExt, WriteMinecraftExt};
+    use crate::{
+        args::{ListArgs, StringArgs},
+        nbt::Tag,
+        packet::RawPacket,
+        McRead,
+    };
+
+    /// Returns the given varint followed by the given bytes.
+    fn prefixed(len: i32, rest: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.write_var_i32(len).unwrap();
+        bytes.extend_from_slice(rest);
+        bytes
+    }
+
+    #[test]
+    fn huge_string_length_is_truncated() {
+        let bytes = prefixed(i32::MAX, b"abc");
+        let error = bytes.as_slice().read_string().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let args = StringArgs { max_len: None };
+        assert!(String::read(bytes.as_slice(), args).is_err());
+    }
+
+    #[test]
+    fn huge_list_length_is_truncated() {
+        let bytes = prefixed(i32::MAX, &[1, 2, 3]);
+        let error = Vec::<u64>::read(bytes.as_slice(), ListArgs::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn negative_length_is_invalid() {
+        let bytes = prefixed(-1, &[]);
+        let error = bytes.as_slice().read_string().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn empty_packet_is_invalid() {
+        let error = [0].as_slice().read_packet().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn oversized_packet_is_invalid() {
+        let bytes = prefixed(RawPacket::MAX_LEN as i32 + 1, &[0]);
+        let error = bytes.as_slice().read_packet().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn partial_packet_is_unexpected_eof() {
+        let bytes = prefixed(4, &[0x01, 0xAA]);
+        let error = bytes.as_slice().read_packet().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn packet_id_past_its_frame_is_invalid() {
+        // The frame is one byte long, but the ID's continuation bit is set.
+        let bytes = prefixed(1, &[0x80, 0x01]);
+        let error = bytes.as_slice().read_packet().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// Feeds pseudo-random bytes to each reader, which must return instead of
+    /// panicking or exhausting memory.
+    #[test]
+    fn random_input_does_not_panic() {
+        // xorshift64, so that failures are reproducible.
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..2000 {
+            let len = (next() % 64) as usize;
+            let bytes: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+
+            let _ = bytes.as_slice().read_packet();
+            let _ = bytes.as_slice().read_var_i32();
+            let _ = bytes.as_slice().read_var_i64();
+            let _ = String::read(bytes.as_slice(), StringArgs::default());
+            let _ = Vec::<String>::read(bytes.as_slice(), ListArgs::default());
+            let _ = Tag::read(bytes.as_slice(), ());
+        }
+    }
+
+    fn var_i32_bytes(value: i32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.write_var_i32(value).unwrap();
+        bytes
+    }
+
+    fn var_i64_bytes(value: i64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.write_var_i64(value).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn var_i32_boundaries() {
+        for (value, expected) in [
+            (0, &[0x00][..]),
+            (1, &[0x01]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (255, &[0xFF, 0x01]),
+            (25565, &[0xDD, 0xC7, 0x01]),
+            (2097151, &[0xFF, 0xFF, 0x7F]),
+            (i32::MAX, &[0xFF, 0xFF, 0xFF, 0xFF, 0x07]),
+            (-1, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+            (i32::MIN, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+        ] {
+            assert_eq!(var_i32_bytes(value), expected, "{value}");
+            assert_eq!(expected.len(), crate::util::varint_bytes(value), "{value}");
+            assert_eq!(expected.as_ref().read_var_i32().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn var_i32_rejects_six_bytes() {
+        let bytes = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+        let error = bytes.as_slice().read_var_i32().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn var_i64_boundaries() {
+        for (value, len) in [
+            (0, 1),
+            (i64::from(i32::MAX), 5),
+            (i64::MAX, 9),
+            (-1, 10),
+            (i64::MIN, 10),
+        ] {
+            let bytes = var_i64_bytes(value);
+            assert_eq!(bytes.len(), len, "{value}");
+            assert_eq!(bytes.as_slice().read_var_i64().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn var_i64_rejects_eleven_bytes() {
+        let mut bytes = vec![0xFF; 10];
+        bytes.push(0x00);
+        let error = bytes.as_slice().read_var_i64().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn position_layout() {
+        let position = IVec3::new(18357644, 831, -20882616);
+        let mut bytes = Vec::new();
+        bytes.write_position(position).unwrap();
+
+        assert_eq!(bytes, 0x4607_632C_15B4_833Fu64.to_be_bytes());
+        assert_eq!(bytes.as_slice().read_position().unwrap(), position);
+    }
+
+    #[test]
+    fn position_round_trip() {
+        for position in [
+            IVec3::ZERO,
+            IVec3::new(-1, -1, -1),
+            IVec3::new(1, -64, -1),
+            IVec3::new(33554431, 2047, 33554431),
+            IVec3::new(-33554432, -2048, -33554432),
+        ] {
+            let mut bytes = Vec::new();
+            bytes.write_position(position).unwrap();
+            assert_eq!(bytes.as_slice().read_position().unwrap(), position);
+        }
+    }
+
+    #[test]
+    fn angle_round_trip() {
+        for (degrees, step) in [(0.0, 0), (90.0, 64), (180.0, 128), (270.0, 192)] {
+            let mut bytes = Vec::new();
+            bytes.write_angle(degrees).unwrap();
+            assert_eq!(bytes, [step]);
+            assert_eq!(bytes.as_slice().read_angle().unwrap(), degrees);
+        }
+    }
+
+    #[test]
+    fn angle_wraps_and_rounds_down() {
+        let mut bytes = Vec::new();
+        bytes.write_angle(-90.0).unwrap();
+        bytes.write_angle(361.0).unwrap();
+        bytes.write_angle(-1.0).unwrap();
+        assert_eq!(bytes, [192, 0, 255]);
+    }
+
+    #[test]
+    fn uuid_round_trip() {
+        let uuid = Uuid::from_u128(0x0123_4567_89AB_CDEF_0123_4567_89AB_CDEF);
+        let mut bytes = Vec::new();
+        bytes.write_uuid(uuid).unwrap();
+
+        assert_eq!(bytes, uuid.as_bytes());
+        assert_eq!(bytes.as_slice().read_uuid().unwrap(), uuid);
+    }
+
+    #[test]
+    fn string_round_trip() {
+        let mut bytes = Vec::new();
+        bytes.write_string("héllo").unwrap();
+
+        assert_eq!(bytes[0], 6);
+        assert_eq!(bytes.as_slice().read_string().unwrap(), "héllo");
+        assert_eq!(bytes.as_slice().read_string_max_len(6).unwrap(), "héllo");
+    }
+
+    #[test]
+    fn string_max_len_is_checked_before_reading() {
+        let bytes = prefixed(6, b"abc");
+        let error = bytes.as_slice().read_string_max_len(5).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -2,7 +2,6 @@ pub mod alloc;
 pub mod core;
 pub mod glam;
 pub mod key;
-pub mod nbt;
 pub mod std;
 pub mod tuples;
 pub mod uuid;
@@ -2,7 +2,9 @@ use std::io::{self, Read, Write};
 
 use crate::{
     args::{ListArgs, ListLength, StringArgs},
+    error::DecodeError,
     prelude::{ReadMinecraftExt, WriteMinecraftExt},
+    util::prealloc_len,
     McRead, McWrite,
 };
 
@@ -14,25 +16,20 @@ impl McRead for String {
         let len = reader.read_var_i32_len()?;
         match args.max_len {
             Some(max_len) if len > max_len => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("exceeded max string length (max: {max_len}, actual: {len})"),
-                ))
+                return Err(DecodeError::LengthExceeded {
+                    max: max_len,
+                    actual: len,
+                }
+                .into())
             }
             _ => {}
         }
 
         // Read the actual string as bytes
-        let mut bytes = vec![0; len];
-        reader.read_exact(&mut bytes)?;
+        let bytes = reader.read_bytes_exact(len)?;
 
         // Try to convert the bytes into valid UTF-8
-        String::from_utf8(bytes).map_err(|_| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "string has invalid UTF-8 characters",
-            )
-        })
+        String::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8.into())
     }
 }
 
@@ -67,7 +64,7 @@ impl<T: McRead> McRead for Vec<T> {
         match args.length {
             ListLength::VarInt => {
                 let len = reader.read_var_i32_len()?;
-                let mut result = Vec::with_capacity(len);
+                let mut result = Vec::with_capacity(prealloc_len::<T>(len));
                 for _ in 0..len {
                     result.push(T::read(&mut reader, args.inner.clone())?);
                 }
@@ -75,13 +72,9 @@ impl<T: McRead> McRead for Vec<T> {
             }
             ListLength::Byte => {
                 let len = reader.read_i8()?;
-                let len = usize::try_from(len).map_err(|_| {
-                    io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("invalid list length: {}", len),
-                    )
-                })?;
-                let mut result = Vec::with_capacity(len);
+                let len =
+                    usize::try_from(len).map_err(|_| DecodeError::InvalidLength(i64::from(len)))?;
+                let mut result = Vec::with_capacity(prealloc_len::<T>(len));
                 for _ in 0..len {
                     result.push(T::read(&mut reader, args.inner.clone())?);
                 }
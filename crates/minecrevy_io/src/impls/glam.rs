@@ -1,9 +1,10 @@
 use std::io::{self, Read, Write};
 
-use glam::{DVec2, DVec3, IVec2, IVec3, Vec2, Vec3};
+use glam::{DVec2, DVec3, DVec4, IVec2, IVec3, IVec4, Vec2, Vec3, Vec4};
 
 use crate::{
     args::{ArrayArgs, IVec3Args},
+    prelude::{ReadMinecraftExt, WriteMinecraftExt},
     McRead, McWrite,
 };
 
@@ -31,19 +32,21 @@ macro_rules! impl_vec {
 
 impl_vec!(
     IVec2 as [i32; 2],
+    IVec4 as [i32; 4],
     Vec2 as [f32; 2],
     Vec3 as [f32; 3],
+    Vec4 as [f32; 4],
     DVec2 as [f64; 2],
     DVec3 as [f64; 3],
+    DVec4 as [f64; 4],
 );
 
 impl McRead for IVec3 {
     type Args = IVec3Args;
 
-    fn read(reader: impl Read, args: Self::Args) -> io::Result<Self> {
+    fn read(mut reader: impl Read, args: Self::Args) -> io::Result<Self> {
         if args.compressed {
-            let val = u64::read(reader, ())?;
-            Ok(uncompress_ivec3(val))
+            reader.read_position()
         } else {
             let [x, y, z] = <[i32; 3]>::read(reader, ArrayArgs::default())?;
             Ok(IVec3::new(x, y, z))
@@ -56,8 +59,7 @@ impl McWrite for IVec3 {
 
     fn write(&self, mut writer: impl Write, args: Self::Args) -> io::Result<()> {
         if args.compressed {
-            let val = compress_ivec3(*self);
-            val.write(&mut writer, ())?;
+            writer.write_position(*self)?;
         } else {
             self.to_array().write(writer, ArrayArgs::default())?;
         }
@@ -65,14 +67,54 @@ impl McWrite for IVec3 {
     }
 }
 
-fn uncompress_ivec3(v: u64) -> IVec3 {
-    IVec3::new(
-        (v >> 38) as i32,
-        (v & 0xFFF) as i32,
-        ((v >> 12) & 0x03FF_FFFF) as i32,
-    )
-}
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use glam::{DVec3, DVec4, IVec2, IVec3, IVec4, Vec4};
+
+    use crate::{args::IVec3Args, McRead, McWrite};
+
+    fn round_trip<T: McRead<Args = ()> + McWrite<Args = ()> + PartialEq + Debug>(
+        value: T,
+        len: usize,
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.write(&mut bytes, ()).unwrap();
+        assert_eq!(bytes.len(), len);
+        assert_eq!(T::read(bytes.as_slice(), ()).unwrap(), value);
+        bytes
+    }
+
+    #[test]
+    fn vec2_round_trip() {
+        let bytes = round_trip(IVec2::new(1, -2), 8);
+        assert_eq!(bytes, [0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFE]);
+    }
 
-fn compress_ivec3(v: IVec3) -> u64 {
-    ((v.x as u64 & 0x03FF_FFFF) << 38) | ((v.z as u64 & 0x03FF_FFFF) << 12) | (v.y as u64 & 0xFFF)
+    #[test]
+    fn vec3_round_trip() {
+        let bytes = round_trip(DVec3::new(0.5, -64.0, 1e9), 24);
+        assert_eq!(bytes[..8], 0.5f64.to_be_bytes());
+    }
+
+    #[test]
+    fn vec4_round_trip() {
+        let bytes = round_trip(Vec4::new(1.0, -2.5, 0.0, f32::MAX), 16);
+        assert_eq!(bytes[4..8], (-2.5f32).to_be_bytes());
+        round_trip(IVec4::new(i32::MIN, -1, 0, i32::MAX), 16);
+        round_trip(DVec4::new(0.25, -0.25, 1e-9, -1e9), 32);
+    }
+
+    #[test]
+    fn ivec3_round_trip() {
+        let position = IVec3::new(-5, 70, 12);
+        for (compressed, len) in [(true, 8), (false, 12)] {
+            let args = IVec3Args { compressed };
+            let mut bytes = Vec::new();
+            position.write(&mut bytes, args.clone()).unwrap();
+            assert_eq!(bytes.len(), len);
+            assert_eq!(IVec3::read(bytes.as_slice(), args).unwrap(), position);
+        }
+    }
 }
@@ -1,12 +1,14 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     hash::{BuildHasher, Hash},
     io::{self, Read, Write},
 };
 
 use crate::{
     args::{ListArgs, ListLength},
+    error::DecodeError,
     prelude::{ReadMinecraftExt, WriteMinecraftExt},
+    util::prealloc_len,
     McRead, McWrite,
 };
 
@@ -14,80 +16,234 @@ impl<K: McRead + Eq + Hash, V: McRead, S: BuildHasher + Default> McRead for Hash
     type Args = ListArgs<(K::Args, V::Args)>;
 
     fn read(mut reader: impl Read, args: Self::Args) -> io::Result<Self> {
-        let (k, v) = args.inner;
-        match args.length {
-            ListLength::VarInt => {
-                let len = reader.read_var_i32_len()?;
-                let mut result = HashMap::with_capacity_and_hasher(len, S::default());
-                for _ in 0..len {
-                    result.insert(
-                        K::read(&mut reader, k.clone())?,
-                        V::read(&mut reader, v.clone())?,
-                    );
-                }
-                Ok(result)
-            }
-            ListLength::Byte => {
-                let len = reader.read_i8()?;
-                let len = usize::try_from(len).map_err(|_| {
-                    io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("invalid list length: {}", len),
-                    )
-                })?;
-                let mut result = HashMap::with_capacity_and_hasher(len, S::default());
-                for _ in 0..len {
-                    result.insert(
-                        K::read(&mut reader, k.clone())?,
-                        V::read(&mut reader, v.clone())?,
-                    );
-                }
-                Ok(result)
-            }
-            ListLength::Remaining => {
-                let mut result = HashMap::with_hasher(S::default());
-                loop {
-                    match (
-                        K::read(&mut reader, k.clone()),
-                        V::read(&mut reader, v.clone()),
-                    ) {
-                        (Ok(k), Ok(v)) => {
-                            result.insert(k, v);
-                        }
-                        (Err(e), _) | (_, Err(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
-                            break
-                        }
-                        (Err(e), _) | (_, Err(e)) => return Err(e),
-                    }
-                }
-                Ok(result)
-            }
-        }
+        let len = read_len(&mut reader, args.length)?;
+        let mut result = HashMap::with_capacity_and_hasher(
+            prealloc_len::<(K, V)>(len.unwrap_or(0)),
+            S::default(),
+        );
+        read_entries(reader, len, args.inner, &mut result)?;
+        Ok(result)
     }
 }
 
 impl<K: McWrite, V: McWrite, S: BuildHasher> McWrite for HashMap<K, V, S> {
     type Args = ListArgs<(K::Args, V::Args)>;
 
-    fn write(&self, mut writer: impl Write, args: Self::Args) -> io::Result<()> {
-        let (k, v) = args.inner;
-        match args.length {
-            ListLength::VarInt => writer.write_var_i32_len(self.len())?,
-            ListLength::Byte => {
-                let len = i8::try_from(self.len()).map_err(|_| {
-                    io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("exceeded maximum list length: {}", self.len()),
-                    )
-                })?;
-                writer.write_i8(len)?;
+    /// Writes the entries in the map's iteration order, which isn't stable
+    /// across runs. Use a [`BTreeMap`] for reproducible output.
+    fn write(&self, writer: impl Write, args: Self::Args) -> io::Result<()> {
+        write_entries(writer, args, self.len(), self)
+    }
+}
+
+impl<K: McRead + Ord, V: McRead> McRead for BTreeMap<K, V> {
+    type Args = ListArgs<(K::Args, V::Args)>;
+
+    fn read(mut reader: impl Read, args: Self::Args) -> io::Result<Self> {
+        let len = read_len(&mut reader, args.length)?;
+        let mut result = BTreeMap::new();
+        read_entries(reader, len, args.inner, &mut result)?;
+        Ok(result)
+    }
+}
+
+impl<K: McWrite, V: McWrite> McWrite for BTreeMap<K, V> {
+    type Args = ListArgs<(K::Args, V::Args)>;
+
+    /// Writes the entries sorted by key, so the same map always produces the
+    /// same bytes.
+    fn write(&self, writer: impl Write, args: Self::Args) -> io::Result<()> {
+        write_entries(writer, args, self.len(), self)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: McRead + Eq + Hash, V: McRead, S: BuildHasher + Default> McRead
+    for indexmap::IndexMap<K, V, S>
+{
+    type Args = ListArgs<(K::Args, V::Args)>;
+
+    fn read(mut reader: impl Read, args: Self::Args) -> io::Result<Self> {
+        let len = read_len(&mut reader, args.length)?;
+        let mut result = indexmap::IndexMap::with_capacity_and_hasher(
+            prealloc_len::<(K, V)>(len.unwrap_or(0)),
+            S::default(),
+        );
+        read_entries(reader, len, args.inner, &mut result)?;
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: McWrite, V: McWrite, S: BuildHasher> McWrite for indexmap::IndexMap<K, V, S> {
+    type Args = ListArgs<(K::Args, V::Args)>;
+
+    /// Writes the entries in insertion order, so the same sequence of inserts
+    /// always produces the same bytes.
+    fn write(&self, writer: impl Write, args: Self::Args) -> io::Result<()> {
+        write_entries(writer, args, self.len(), self)
+    }
+}
+
+/// Reads the length prefix of a list of map entries, or [`None`] if the
+/// entries continue until the end of the stream.
+fn read_len(mut reader: impl Read, length: ListLength) -> io::Result<Option<usize>> {
+    match length {
+        ListLength::VarInt => Ok(Some(reader.read_var_i32_len()?)),
+        ListLength::Byte => {
+            let len = reader.read_i8()?;
+            let len =
+                usize::try_from(len).map_err(|_| DecodeError::InvalidLength(i64::from(len)))?;
+            Ok(Some(len))
+        }
+        ListLength::Remaining => Ok(None),
+    }
+}
+
+/// Reads `len` map entries into the given map, or until the end of the stream
+/// if `len` is [`None`].
+fn read_entries<K: McRead, V: McRead>(
+    mut reader: impl Read,
+    len: Option<usize>,
+    (k, v): (K::Args, V::Args),
+    map: &mut impl Extend<(K, V)>,
+) -> io::Result<()> {
+    let Some(len) = len else {
+        loop {
+            match (
+                K::read(&mut reader, k.clone()),
+                V::read(&mut reader, v.clone()),
+            ) {
+                (Ok(k), Ok(v)) => map.extend([(k, v)]),
+                (Err(e), _) | (_, Err(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(())
+                }
+                (Err(e), _) | (_, Err(e)) => return Err(e),
             }
-            ListLength::Remaining => { /* no length prefix since its inferred */ }
         }
-        for (key, value) in self {
-            key.write(&mut writer, k.clone())?;
-            value.write(&mut writer, v.clone())?;
+    };
+
+    for _ in 0..len {
+        map.extend([(
+            K::read(&mut reader, k.clone())?,
+            V::read(&mut reader, v.clone())?,
+        )]);
+    }
+    Ok(())
+}
+
+/// Writes a list of map entries in the given order.
+fn write_entries<'a, K: McWrite + 'a, V: McWrite + 'a>(
+    mut writer: impl Write,
+    args: ListArgs<(K::Args, V::Args)>,
+    len: usize,
+    entries: impl IntoIterator<Item = (&'a K, &'a V)>,
+) -> io::Result<()> {
+    let (k, v) = args.inner;
+    match args.length {
+        ListLength::VarInt => writer.write_var_i32_len(len)?,
+        ListLength::Byte => {
+            let len = i8::try_from(len).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("exceeded maximum list length: {}", len),
+                )
+            })?;
+            writer.write_i8(len)?;
+        }
+        ListLength::Remaining => { /* no length prefix since its inferred */ }
+    }
+    for (key, value) in entries {
+        key.write(&mut writer, k.clone())?;
+        value.write(&mut writer, v.clone())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::{
+        args::{ListArgs, ListLength},
+        McRead, McWrite,
+    };
+
+    fn write<T: McWrite<Args = ListArgs<((), ())>>>(map: &T, length: ListLength) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        map.write(
+            &mut bytes,
+            ListArgs {
+                length,
+                inner: ((), ()),
+            },
+        )
+        .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn btree_map_output_is_sorted_by_key() {
+        let forwards: BTreeMap<u8, u8> = (0..50).map(|k| (k, k * 2)).collect();
+        let backwards: BTreeMap<u8, u8> = (0..50).rev().map(|k| (k, k * 2)).collect();
+
+        let bytes = write(&forwards, ListLength::VarInt);
+        assert_eq!(bytes, write(&backwards, ListLength::VarInt));
+        assert_eq!(bytes[0], 50);
+        let keys: Vec<u8> = bytes[1..].iter().step_by(2).copied().collect();
+        assert_eq!(keys, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn btree_map_matches_sorted_hash_map_entries() {
+        let hash_map: HashMap<u8, u8> = HashMap::from([(3, 30), (1, 10), (2, 20)]);
+        let btree_map: BTreeMap<u8, u8> = hash_map.clone().into_iter().collect();
+
+        assert_eq!(
+            write(&btree_map, ListLength::Byte),
+            [3, 1, 10, 2, 20, 3, 30]
+        );
+        // The hash map has the same entries, in whichever order it iterates.
+        let bytes = write(&hash_map, ListLength::Byte);
+        let read = BTreeMap::<u8, u8>::read(
+            bytes.as_slice(),
+            ListArgs {
+                length: ListLength::Byte,
+                inner: ((), ()),
+            },
+        )
+        .unwrap();
+        assert_eq!(read, btree_map);
+    }
+
+    #[test]
+    fn btree_map_round_trip() {
+        let map = BTreeMap::from([(1u8, 2u8), (3, 4)]);
+        for length in [ListLength::VarInt, ListLength::Byte, ListLength::Remaining] {
+            let bytes = write(&map, length);
+            let args = ListArgs {
+                length,
+                inner: ((), ()),
+            };
+            assert_eq!(BTreeMap::read(bytes.as_slice(), args).unwrap(), map);
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_map_keeps_insertion_order() {
+        let map: indexmap::IndexMap<u8, u8> = [(3, 30), (1, 10), (2, 20)].into_iter().collect();
+
+        let bytes = write(&map, ListLength::VarInt);
+        assert_eq!(bytes, [3, 3, 30, 1, 10, 2, 20]);
+        for length in [ListLength::VarInt, ListLength::Byte, ListLength::Remaining] {
+            let bytes = write(&map, length);
+            let args = ListArgs {
+                length,
+                inner: ((), ()),
+            };
+            let read = indexmap::IndexMap::<u8, u8>::read(bytes.as_slice(), args).unwrap();
+            assert!(read.iter().eq(map.iter()));
         }
-        Ok(())
     }
 }
@@ -24,3 +24,26 @@ impl McWrite for Uuid {
         writer.write_u128(self.as_u128())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{McRead, McWrite};
+
+    #[test]
+    fn round_trip() {
+        let uuid = Uuid::parse_str("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap();
+
+        let mut bytes = Vec::new();
+        uuid.write(&mut bytes, ()).unwrap();
+        assert_eq!(
+            bytes,
+            [
+                0xF8, 0x1D, 0x4F, 0xAE, 0x7D, 0xEC, 0x11, 0xD0, 0xA7, 0x65, 0x00, 0xA0, 0xC9, 0x1E,
+                0x6B, 0xF6,
+            ]
+        );
+        assert_eq!(Uuid::read(bytes.as_slice(), ()).unwrap(), uuid);
+    }
+}
@@ -7,25 +7,36 @@
 //!
 //! #[derive(McRead, McWrite)]
 //! pub struct Handshake {
-//!     #[args(varint = true)]
+//!     #[options(varint = true)]
 //!     pub version: i32,
-//!     #[args(max_len = 255)]
+//!     #[options(max_len = Some(255))]
 //!     pub address: String,
 //!     pub port: u16,
-//!     #[args(varint = true)]
+//!     #[options(varint = true)]
 //!     pub next: i32,
 //! }
 //! ```
+//!
+//! Each `#[options(...)]` entry sets a field of the `Args` the field is read
+//! and written with. `#[options(default_on_eof)]` reads a missing trailing
+//! field as its default, for fields that older clients don't send.
 
 #![warn(missing_docs)]
 #![allow(clippy::module_name_repetitions)]
 
 use std::io;
 
+pub use minecrevy_io_derive::{McRead, McWrite};
+
+// Lets the derive macros refer to `::minecrevy_io` inside this crate too.
+extern crate self as minecrevy_io;
+
 pub mod prelude {
     //! Re-exports important traits, types, and functions.
 
     pub use crate::{
+        angle::Angle,
+        error::DecodeError,
         ext::{ReadMinecraftExt, WriteMinecraftExt},
         packet::RawPacket,
         util::varint_bytes,
@@ -33,9 +44,12 @@ pub mod prelude {
     };
 }
 
+pub mod angle;
 pub mod args;
+pub mod error;
 pub mod ext;
 mod impls;
+pub mod nbt;
 pub mod packet;
 pub mod util;
 
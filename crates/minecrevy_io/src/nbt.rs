@@ -0,0 +1,926 @@
+//! Reading and writing of network NBT, as used for registry data, chunk
+//! heightmaps, block entities, and text components.
+//!
+//! Network NBT is regular NBT whose root tag has no name. [`Tag`] holds NBT
+//! in memory, while [`CompoundWriter`] streams a compound straight to a writer.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+};
+
+use crate::{args::IntArgs, McRead, McWrite};
+
+/// The tag type that ends a compound.
+pub const TAG_END: u8 = 0;
+/// The tag type of a signed byte.
+pub const TAG_BYTE: u8 = 1;
+/// The tag type of a signed short.
+pub const TAG_SHORT: u8 = 2;
+/// The tag type of a signed int.
+pub const TAG_INT: u8 = 3;
+/// The tag type of a signed long.
+pub const TAG_LONG: u8 = 4;
+/// The tag type of a float.
+pub const TAG_FLOAT: u8 = 5;
+/// The tag type of a double.
+pub const TAG_DOUBLE: u8 = 6;
+/// The tag type of an array of signed bytes.
+pub const TAG_BYTE_ARRAY: u8 = 7;
+/// The tag type of a modified UTF-8 string.
+pub const TAG_STRING: u8 = 8;
+/// The tag type of a list of unnamed tags of a single type.
+pub const TAG_LIST: u8 = 9;
+/// The tag type of a compound of named tags.
+pub const TAG_COMPOUND: u8 = 10;
+/// The tag type of an array of signed ints.
+pub const TAG_INT_ARRAY: u8 = 11;
+/// The tag type of an array of signed longs.
+pub const TAG_LONG_ARRAY: u8 = 12;
+
+/// The maximum nesting depth of lists and compounds, matching vanilla.
+const MAX_DEPTH: usize = 512;
+
+/// The fields of an NBT compound, keyed by name.
+///
+/// Fields are kept sorted by name, so compounds with the same fields compare
+/// equal and are written as the same bytes, regardless of the order they were
+/// inserted in. This makes written NBT suitable as a cache key. Lists keep
+/// their order, as it's part of their contents.
+pub type Compound = BTreeMap<String, Tag>;
+
+/// An NBT tag and its payload.
+///
+/// Tags are read and written as nameless network NBT.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Tag {
+    /// A signed byte, also used for booleans.
+    Byte(i8),
+    /// A signed short.
+    Short(i16),
+    /// A signed int.
+    Int(i32),
+    /// A signed long.
+    Long(i64),
+    /// A float.
+    Float(f32),
+    /// A double.
+    Double(f64),
+    /// An array of signed bytes.
+    ByteArray(Vec<i8>),
+    /// A string.
+    String(String),
+    /// A list of tags, which must all be of the same type.
+    List(Vec<Tag>),
+    /// A compound of named tags.
+    Compound(Compound),
+    /// An array of signed ints.
+    IntArray(Vec<i32>),
+    /// An array of signed longs.
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Returns the tag type, such as [`TAG_COMPOUND`].
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Byte(_) => TAG_BYTE,
+            Self::Short(_) => TAG_SHORT,
+            Self::Int(_) => TAG_INT,
+            Self::Long(_) => TAG_LONG,
+            Self::Float(_) => TAG_FLOAT,
+            Self::Double(_) => TAG_DOUBLE,
+            Self::ByteArray(_) => TAG_BYTE_ARRAY,
+            Self::String(_) => TAG_STRING,
+            Self::List(_) => TAG_LIST,
+            Self::Compound(_) => TAG_COMPOUND,
+            Self::IntArray(_) => TAG_INT_ARRAY,
+            Self::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// Returns the string, if this is a [`Tag::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the fields, if this is a [`Tag::Compound`].
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Self::Compound(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if this is a [`Tag::List`].
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Self::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the exact number of bytes the tag takes as nameless network
+    /// NBT, computed from its structure without writing it.
+    ///
+    /// # Errors
+    ///
+    /// If the tag can't be written, such as a list whose elements aren't all
+    /// of the same type, this function will return that error.
+    pub fn serialized_len(&self) -> io::Result<usize> {
+        Ok(1 + payload_len(self)?)
+    }
+}
+
+impl From<bool> for Tag {
+    fn from(value: bool) -> Self {
+        Self::Byte(i8::from(value))
+    }
+}
+
+impl From<i32> for Tag {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for Tag {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Compound> for Tag {
+    fn from(value: Compound) -> Self {
+        Self::Compound(value)
+    }
+}
+
+/// Overlays the fields of `other` onto `base`, such as to apply overrides to
+/// default NBT.
+///
+/// Compounds present in both are merged recursively, while other fields of
+/// `other` replace those of `base`. If `merge_lists` is `true`, lists present
+/// in both with the same element type are concatenated rather than replaced.
+pub fn merge(base: &mut Compound, other: &Compound, merge_lists: bool) {
+    for (name, value) in other {
+        match (base.get_mut(name), value) {
+            (Some(Tag::Compound(base)), Tag::Compound(other)) => {
+                merge(base, other, merge_lists);
+            }
+            (Some(Tag::List(base)), Tag::List(other))
+                if merge_lists
+                    && base
+                        .first()
+                        .zip(other.first())
+                        .is_none_or(|(a, b)| a.id() == b.id()) =>
+            {
+                base.extend(other.iter().cloned());
+            }
+            _ => {
+                base.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+impl McRead for Tag {
+    type Args = ();
+
+    /// Reads a nameless network NBT tag, rejecting lists and compounds nested
+    /// more than 512 levels deep.
+    fn read(mut reader: impl Read, (): Self::Args) -> io::Result<Self> {
+        let tag = u8::read(&mut reader, ())?;
+        read_payload(&mut reader, tag, 0)
+    }
+}
+
+impl McWrite for Tag {
+    type Args = ();
+
+    /// Writes the tag as nameless network NBT.
+    ///
+    /// Lists whose elements aren't all of the same type are rejected.
+    fn write(&self, mut writer: impl Write, (): Self::Args) -> io::Result<()> {
+        self.id().write(&mut writer, ())?;
+        write_payload(&mut writer, self)
+    }
+}
+
+// The recursive functions take `&mut dyn Read` and `&mut dyn Write`, as a
+// generic reader or writer would be instantiated with ever deeper `&mut`
+// references.
+fn read_payload(reader: &mut dyn Read, tag: u8, depth: usize) -> io::Result<Tag> {
+    let read_len = |reader: &mut dyn Read| {
+        let len = i32::read(reader, IntArgs { varint: false })?;
+        usize::try_from(len).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
+    // Array lengths aren't trusted for preallocation, as they may be bogus.
+    Ok(match tag {
+        TAG_BYTE => Tag::Byte(i8::read(reader, ())?),
+        TAG_SHORT => Tag::Short(i16::read(reader, ())?),
+        TAG_INT => Tag::Int(i32::read(reader, IntArgs { varint: false })?),
+        TAG_LONG => Tag::Long(i64::read(reader, IntArgs { varint: false })?),
+        TAG_FLOAT => Tag::Float(f32::read(reader, ())?),
+        TAG_DOUBLE => Tag::Double(f64::read(reader, ())?),
+        TAG_BYTE_ARRAY => Tag::ByteArray(
+            (0..read_len(reader)?)
+                .map(|_| i8::read(&mut *reader, ()))
+                .collect::<io::Result<_>>()?,
+        ),
+        TAG_STRING => Tag::String(read_string(reader)?),
+        TAG_LIST => {
+            let depth = enter(depth)?;
+            let tag = u8::read(&mut *reader, ())?;
+            let len = read_len(reader)?;
+            if tag == TAG_END && len > 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "non-empty NBT list of end tags",
+                ));
+            }
+            Tag::List(
+                (0..len)
+                    .map(|_| read_payload(reader, tag, depth))
+                    .collect::<io::Result<_>>()?,
+            )
+        }
+        TAG_COMPOUND => {
+            let depth = enter(depth)?;
+            let mut fields = Compound::new();
+            loop {
+                let tag = u8::read(&mut *reader, ())?;
+                if tag == TAG_END {
+                    break Tag::Compound(fields);
+                }
+                let name = read_string(&mut *reader)?;
+                fields.insert(name, read_payload(reader, tag, depth)?);
+            }
+        }
+        TAG_INT_ARRAY => Tag::IntArray(
+            (0..read_len(reader)?)
+                .map(|_| i32::read(&mut *reader, IntArgs { varint: false }))
+                .collect::<io::Result<_>>()?,
+        ),
+        TAG_LONG_ARRAY => Tag::LongArray(
+            (0..read_len(reader)?)
+                .map(|_| i64::read(&mut *reader, IntArgs { varint: false }))
+                .collect::<io::Result<_>>()?,
+        ),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid NBT tag type: {tag}"),
+            ))
+        }
+    })
+}
+
+/// Returns the depth of a nested list or compound, or an error if it's
+/// nested too deeply.
+fn enter(depth: usize) -> io::Result<usize> {
+    if depth >= MAX_DEPTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "NBT is nested too deeply",
+        ));
+    }
+    Ok(depth + 1)
+}
+
+fn write_payload(writer: &mut dyn Write, tag: &Tag) -> io::Result<()> {
+    match tag {
+        Tag::Byte(v) => v.write(writer, ()),
+        Tag::Short(v) => v.write(writer, ()),
+        Tag::Int(v) => v.write(writer, IntArgs { varint: false }),
+        Tag::Long(v) => v.write(writer, IntArgs { varint: false }),
+        Tag::Float(v) => v.write(writer, ()),
+        Tag::Double(v) => v.write(writer, ()),
+        Tag::ByteArray(values) => {
+            write_array_len(&mut *writer, values.len())?;
+            values.iter().try_for_each(|v| v.write(&mut *writer, ()))
+        }
+        Tag::String(v) => write_string(writer, v),
+        Tag::List(values) => {
+            list_element_id(values)?.write(&mut *writer, ())?;
+            write_array_len(&mut *writer, values.len())?;
+            values
+                .iter()
+                .try_for_each(|value| write_payload(writer, value))
+        }
+        Tag::Compound(fields) => {
+            for (name, value) in fields {
+                write_field(&mut *writer, value.id(), name)?;
+                write_payload(writer, value)?;
+            }
+            TAG_END.write(writer, ())
+        }
+        Tag::IntArray(values) => {
+            write_array_len(&mut *writer, values.len())?;
+            values
+                .iter()
+                .try_for_each(|v| v.write(&mut *writer, IntArgs { varint: false }))
+        }
+        Tag::LongArray(values) => {
+            write_array_len(&mut *writer, values.len())?;
+            values
+                .iter()
+                .try_for_each(|v| v.write(&mut *writer, IntArgs { varint: false }))
+        }
+    }
+}
+
+/// Returns the number of bytes the payload of the given tag takes, which is
+/// everything [`write_payload`] writes.
+fn payload_len(tag: &Tag) -> io::Result<usize> {
+    Ok(match tag {
+        Tag::Byte(_) => 1,
+        Tag::Short(_) => 2,
+        Tag::Int(_) | Tag::Float(_) => 4,
+        Tag::Long(_) | Tag::Double(_) => 8,
+        Tag::ByteArray(values) => 4 + values.len(),
+        Tag::String(value) => 2 + string_len(value)?,
+        Tag::List(values) => {
+            list_element_id(values)?;
+            // The element type and the length, then the elements.
+            5 + values.iter().map(payload_len).sum::<io::Result<usize>>()?
+        }
+        Tag::Compound(fields) => {
+            let fields = fields
+                .iter()
+                .map(|(name, value)| Ok(3 + string_len(name)? + payload_len(value)?))
+                .sum::<io::Result<usize>>()?;
+            // The fields, then the end tag.
+            fields + 1
+        }
+        Tag::IntArray(values) => 4 + 4 * values.len(),
+        Tag::LongArray(values) => 4 + 8 * values.len(),
+    })
+}
+
+/// Returns the tag type of the elements of a list, or [`TAG_END`] if it's
+/// empty.
+///
+/// # Errors
+///
+/// If the elements aren't all of the same type, this function will return an
+/// error.
+fn list_element_id(values: &[Tag]) -> io::Result<u8> {
+    let id = values.first().map_or(TAG_END, Tag::id);
+    if values.iter().any(|value| value.id() != id) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "NBT lists must contain elements of a single type",
+        ));
+    }
+    Ok(id)
+}
+
+/// A writer for an NBT compound tag, which writes each field as it's added.
+///
+/// The compound must be closed with [`CompoundWriter::end`].
+pub struct CompoundWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> CompoundWriter<W> {
+    /// Starts writing a nameless root compound, as used in network NBT.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn root(mut writer: W) -> io::Result<Self> {
+        TAG_COMPOUND.write(&mut writer, ())?;
+        Ok(Self { writer })
+    }
+
+    /// Writes a byte field.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn byte(&mut self, name: &str, value: i8) -> io::Result<&mut Self> {
+        self.field(TAG_BYTE, name)?;
+        value.write(&mut self.writer, ())?;
+        Ok(self)
+    }
+
+    /// Writes a boolean field, which NBT stores as a byte.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn bool(&mut self, name: &str, value: bool) -> io::Result<&mut Self> {
+        self.byte(name, i8::from(value))
+    }
+
+    /// Writes an int field.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn int(&mut self, name: &str, value: i32) -> io::Result<&mut Self> {
+        self.field(TAG_INT, name)?;
+        value.write(&mut self.writer, IntArgs { varint: false })?;
+        Ok(self)
+    }
+
+    /// Writes a long field.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn long(&mut self, name: &str, value: i64) -> io::Result<&mut Self> {
+        self.field(TAG_LONG, name)?;
+        value.write(&mut self.writer, IntArgs { varint: false })?;
+        Ok(self)
+    }
+
+    /// Writes a float field.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn float(&mut self, name: &str, value: f32) -> io::Result<&mut Self> {
+        self.field(TAG_FLOAT, name)?;
+        value.write(&mut self.writer, ())?;
+        Ok(self)
+    }
+
+    /// Writes a double field.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn double(&mut self, name: &str, value: f64) -> io::Result<&mut Self> {
+        self.field(TAG_DOUBLE, name)?;
+        value.write(&mut self.writer, ())?;
+        Ok(self)
+    }
+
+    /// Writes a string field.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, or the string is longer than 65535
+    /// bytes once encoded, this function will return an error.
+    pub fn string(&mut self, name: &str, value: &str) -> io::Result<&mut Self> {
+        self.field(TAG_STRING, name)?;
+        write_string(&mut self.writer, value)?;
+        Ok(self)
+    }
+
+    /// Writes an int array field, such as a UUID converted with
+    /// [`uuid_to_intarray`](crate::util::uuid_to_intarray).
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn int_array(&mut self, name: &str, values: &[i32]) -> io::Result<&mut Self> {
+        self.field(TAG_INT_ARRAY, name)?;
+        write_array_len(&mut self.writer, values.len())?;
+        for value in values {
+            value.write(&mut self.writer, IntArgs { varint: false })?;
+        }
+        Ok(self)
+    }
+
+    /// Writes a long array field, such as a packed heightmap.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn long_array(&mut self, name: &str, values: &[i64]) -> io::Result<&mut Self> {
+        self.field(TAG_LONG_ARRAY, name)?;
+        write_array_len(&mut self.writer, values.len())?;
+        for value in values {
+            value.write(&mut self.writer, IntArgs { varint: false })?;
+        }
+        Ok(self)
+    }
+
+    /// Starts writing a nested compound field, which must be closed with
+    /// [`CompoundWriter::end`] before writing further fields.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn compound(&mut self, name: &str) -> io::Result<CompoundWriter<&mut W>> {
+        self.field(TAG_COMPOUND, name)?;
+        Ok(CompoundWriter {
+            writer: &mut self.writer,
+        })
+    }
+
+    /// Closes the compound.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn end(mut self) -> io::Result<()> {
+        TAG_END.write(&mut self.writer, ())
+    }
+
+    /// Writes the tag type and name of a field.
+    fn field(&mut self, tag: u8, name: &str) -> io::Result<()> {
+        write_field(&mut self.writer, tag, name)
+    }
+}
+
+/// Writes the tag type and name that start a field of a compound.
+///
+/// # Errors
+///
+/// If the writer returns an error, this function will return that error.
+pub fn write_field(mut writer: impl Write, tag: u8, name: &str) -> io::Result<()> {
+    tag.write(&mut writer, ())?;
+    write_string(writer, name)
+}
+
+/// Writes the length of an array or list.
+fn write_array_len(writer: impl Write, len: usize) -> io::Result<()> {
+    let len = i32::try_from(len).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    len.write(writer, IntArgs { varint: false })
+}
+
+/// Writes a string in the modified UTF-8 encoding used by NBT.
+///
+/// # Errors
+///
+/// If the writer returns an error, or the string is longer than 65535 bytes
+/// once encoded, this function will return an error.
+pub fn write_string(mut writer: impl Write, value: &str) -> io::Result<()> {
+    let len = u16::try_from(string_len(value)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    len.write(&mut writer, ())?;
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => writer.write_all(&[unit as u8])?,
+            0x0000 | 0x0080..=0x07FF => {
+                writer.write_all(&[0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8])?
+            }
+            _ => writer.write_all(&[
+                0xE0 | (unit >> 12) as u8,
+                0x80 | ((unit >> 6) & 0x3F) as u8,
+                0x80 | (unit & 0x3F) as u8,
+            ])?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the number of bytes the given string takes in the modified UTF-8
+/// encoding used by NBT, not counting its length prefix.
+///
+/// NUL takes two bytes, and supplementary characters take six, as they're
+/// encoded as a surrogate pair of three bytes each.
+///
+/// # Errors
+///
+/// If the string is longer than 65535 bytes once encoded, this function will
+/// return an error.
+pub fn string_len(value: &str) -> io::Result<usize> {
+    let len = value
+        .chars()
+        .map(|c| match u32::from(c) {
+            0x0001..=0x007F => 1,
+            0x0000 | 0x0080..=0x07FF => 2,
+            0x0800..=0xFFFF => 3,
+            _ => 6,
+        })
+        .sum();
+    if len > usize::from(u16::MAX) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("NBT string is {len} bytes long, more than {}", u16::MAX),
+        ));
+    }
+    Ok(len)
+}
+
+/// Reads a string in the modified UTF-8 encoding used by NBT.
+///
+/// # Errors
+///
+/// If the reader returns an error, or the string isn't valid modified UTF-8,
+/// this function will return an error.
+pub fn read_string(mut reader: impl Read) -> io::Result<String> {
+    let len = u16::read(&mut reader, ())?;
+    let mut bytes = vec![0; usize::from(len)];
+    reader.read_exact(&mut bytes)?;
+
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid modified UTF-8 string");
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.into_iter();
+    while let Some(b) = iter.next() {
+        let mut continuation = || match iter.next() {
+            Some(c) if c & 0xC0 == 0x80 => Ok(u16::from(c & 0x3F)),
+            _ => Err(invalid()),
+        };
+        let unit = match b {
+            0x01..=0x7F => u16::from(b),
+            0xC0..=0xDF => (u16::from(b & 0x1F) << 6) | continuation()?,
+            0xE0..=0xEF => (u16::from(b & 0x0F) << 12) | (continuation()? << 6) | continuation()?,
+            _ => return Err(invalid()),
+        };
+        units.push(unit);
+    }
+
+    String::from_utf16(&units).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{McRead, McWrite};
+
+    use super::{merge, read_string, write_string, Compound, CompoundWriter, Tag};
+
+    #[test]
+    fn tag_round_trip() {
+        let tag = Tag::Compound(Compound::from([
+            ("byte".to_owned(), Tag::Byte(-1)),
+            ("long".to_owned(), Tag::Long(i64::MIN)),
+            (
+                "list".to_owned(),
+                Tag::List(vec![Tag::from("a"), Tag::from("b")]),
+            ),
+            ("empty".to_owned(), Tag::List(Vec::new())),
+            ("ints".to_owned(), Tag::IntArray(vec![1, -2])),
+            (
+                "nested".to_owned(),
+                Tag::Compound(Compound::from([("x".to_owned(), Tag::Double(0.5))])),
+            ),
+        ]));
+
+        let mut bytes = Vec::new();
+        tag.write(&mut bytes, ()).unwrap();
+        assert_eq!(Tag::read(bytes.as_slice(), ()).unwrap(), tag);
+    }
+
+    #[test]
+    fn compound_equality_ignores_insertion_order() {
+        let mut first = Compound::new();
+        first.insert("a".to_owned(), Tag::Int(1));
+        first.insert(
+            "nested".to_owned(),
+            Tag::Compound(Compound::from([
+                ("x".to_owned(), Tag::Byte(1)),
+                ("y".to_owned(), Tag::Byte(2)),
+            ])),
+        );
+        first.insert("b".to_owned(), Tag::from("b"));
+
+        let mut second = Compound::new();
+        second.insert("b".to_owned(), Tag::from("b"));
+        second.insert(
+            "nested".to_owned(),
+            Tag::Compound(Compound::from([
+                ("y".to_owned(), Tag::Byte(2)),
+                ("x".to_owned(), Tag::Byte(1)),
+            ])),
+        );
+        second.insert("a".to_owned(), Tag::Int(1));
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn compound_is_written_in_name_order() {
+        let mut first = Compound::new();
+        first.insert("b".to_owned(), Tag::Int(2));
+        first.insert("a".to_owned(), Tag::Int(1));
+        let mut second = Compound::new();
+        second.insert("a".to_owned(), Tag::Int(1));
+        second.insert("b".to_owned(), Tag::Int(2));
+
+        let write = |fields: Compound| {
+            let mut bytes = Vec::new();
+            Tag::Compound(fields).write(&mut bytes, ()).unwrap();
+            bytes
+        };
+        let bytes = write(first);
+        assert_eq!(bytes, write(second));
+        assert_eq!(
+            bytes,
+            [
+                10, // root compound
+                3, 0, 1, b'a', 0, 0, 0, 1, // a: 1
+                3, 0, 1, b'b', 0, 0, 0, 2, // b: 2
+                0, // end
+            ]
+        );
+    }
+
+    #[test]
+    fn compound_equality_compares_contents() {
+        let base = Compound::from([
+            ("a".to_owned(), Tag::Int(1)),
+            ("list".to_owned(), Tag::List(vec![Tag::Int(1), Tag::Int(2)])),
+        ]);
+
+        let mut value = base.clone();
+        value.insert("a".to_owned(), Tag::Int(2));
+        assert_ne!(base, value);
+
+        let mut tag_type = base.clone();
+        tag_type.insert("a".to_owned(), Tag::Long(1));
+        assert_ne!(base, tag_type);
+
+        let mut extra = base.clone();
+        extra.insert("b".to_owned(), Tag::Int(1));
+        assert_ne!(base, extra);
+
+        // Lists are ordered, unlike compounds.
+        let mut list = base.clone();
+        list.insert("list".to_owned(), Tag::List(vec![Tag::Int(2), Tag::Int(1)]));
+        assert_ne!(base, list);
+    }
+
+    /// Returns the compound that [`merge`] is tested against.
+    fn defaults() -> Compound {
+        Compound::from([
+            ("count".to_owned(), Tag::Int(1)),
+            (
+                "display".to_owned(),
+                Tag::Compound(Compound::from([
+                    ("name".to_owned(), Tag::from("Stick")),
+                    ("color".to_owned(), Tag::Int(0)),
+                ])),
+            ),
+            ("lore".to_owned(), Tag::List(vec![Tag::from("a")])),
+        ])
+    }
+
+    #[test]
+    fn merge_overrides_scalars() {
+        let mut base = defaults();
+        merge(
+            &mut base,
+            &Compound::from([
+                ("count".to_owned(), Tag::Int(5)),
+                ("new".to_owned(), Tag::Byte(1)),
+            ]),
+            false,
+        );
+
+        assert_eq!(base["count"], Tag::Int(5));
+        assert_eq!(base["new"], Tag::Byte(1));
+        assert_eq!(base["lore"], defaults()["lore"]);
+    }
+
+    #[test]
+    fn merge_recurses_into_compounds() {
+        let mut base = defaults();
+        merge(
+            &mut base,
+            &Compound::from([(
+                "display".to_owned(),
+                Tag::Compound(Compound::from([("color".to_owned(), Tag::Int(7))])),
+            )]),
+            false,
+        );
+
+        assert_eq!(
+            base["display"],
+            Tag::Compound(Compound::from([
+                ("name".to_owned(), Tag::from("Stick")),
+                ("color".to_owned(), Tag::Int(7)),
+            ]))
+        );
+
+        // A compound replaces a field of another type.
+        let mut base = defaults();
+        let count = Tag::Compound(Compound::from([("x".to_owned(), Tag::Int(1))]));
+        merge(
+            &mut base,
+            &Compound::from([("count".to_owned(), count.clone())]),
+            false,
+        );
+        assert_eq!(base["count"], count);
+    }
+
+    #[test]
+    fn merge_replaces_or_concatenates_lists() {
+        let lore = Compound::from([("lore".to_owned(), Tag::List(vec![Tag::from("b")]))]);
+
+        let mut replaced = defaults();
+        merge(&mut replaced, &lore, false);
+        assert_eq!(replaced["lore"], Tag::List(vec![Tag::from("b")]));
+
+        let mut concatenated = defaults();
+        merge(&mut concatenated, &lore, true);
+        assert_eq!(
+            concatenated["lore"],
+            Tag::List(vec![Tag::from("a"), Tag::from("b")])
+        );
+
+        // Lists of different element types can't be concatenated.
+        let ints = Compound::from([("lore".to_owned(), Tag::List(vec![Tag::Int(1)]))]);
+        let mut mismatched = defaults();
+        merge(&mut mismatched, &ints, true);
+        assert_eq!(mismatched["lore"], ints["lore"]);
+    }
+
+    #[test]
+    fn serialized_len_matches_written_bytes() {
+        let tags = [
+            Tag::Byte(1),
+            Tag::Short(-2),
+            Tag::Int(3),
+            Tag::Long(-1),
+            Tag::Float(0.5),
+            Tag::Double(-0.25),
+            Tag::ByteArray(vec![1, -1, 0]),
+            Tag::from(""),
+            Tag::from("plain"),
+            Tag::from("\0\u{1F600}"),
+            Tag::from("é\u{FFFF}\u{10000}"),
+            Tag::List(Vec::new()),
+            Tag::List(vec![Tag::IntArray(vec![1, 2]), Tag::IntArray(Vec::new())]),
+            Tag::List(vec![Tag::List(vec![Tag::from("\0")])]),
+            Tag::Compound(Compound::new()),
+            Tag::Compound(Compound::from([(
+                "\0\u{1F600}".to_owned(),
+                Tag::Compound(Compound::from([("x".to_owned(), Tag::from("é"))])),
+            )])),
+            Tag::Compound(defaults()),
+            Tag::IntArray(vec![i32::MIN, 0, i32::MAX]),
+            Tag::LongArray(vec![i64::MIN]),
+        ];
+
+        for tag in tags {
+            let mut bytes = Vec::new();
+            tag.write(&mut bytes, ()).unwrap();
+            assert_eq!(tag.serialized_len().unwrap(), bytes.len(), "{tag:?}");
+        }
+
+        let mixed = Tag::List(vec![Tag::Int(1), Tag::from("a")]);
+        assert!(mixed.serialized_len().is_err());
+        let long = Tag::from("\0".repeat(32768));
+        assert!(long.serialized_len().is_err());
+        assert!(long.write(&mut Vec::new(), ()).is_err());
+    }
+
+    #[test]
+    fn tag_rejects_mixed_lists() {
+        let tag = Tag::List(vec![Tag::Int(1), Tag::from("a")]);
+        assert!(tag.write(&mut Vec::new(), ()).is_err());
+    }
+
+    #[test]
+    fn tag_rejects_deep_nesting() {
+        // 513 nested lists, each holding a single list.
+        let mut bytes = vec![9];
+        for _ in 0..513 {
+            bytes.extend([9, 0, 0, 0, 1]);
+        }
+        bytes.extend([0, 0, 0, 0, 0]);
+        assert!(Tag::read(bytes.as_slice(), ()).is_err());
+    }
+
+    #[test]
+    fn compound() {
+        let mut bytes = Vec::new();
+        let mut nbt = CompoundWriter::root(&mut bytes).unwrap();
+        nbt.bool("a", true).unwrap();
+        nbt.long_array("b", &[1]).unwrap();
+        nbt.end().unwrap();
+
+        assert_eq!(
+            bytes,
+            [
+                10, // root compound
+                1, 0, 1, b'a', 1, // a: 1b
+                12, 0, 1, b'b', 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, // b: [L; 1L]
+                0, // end
+            ]
+        );
+    }
+
+    #[test]
+    fn modified_utf8() {
+        for s in ["", "plain", "\0", "é", "\u{1F600}"] {
+            let mut bytes = Vec::new();
+            write_string(&mut bytes, s).unwrap();
+            assert_eq!(read_string(bytes.as_slice()).unwrap(), s);
+        }
+
+        // NUL is encoded as two bytes, and supplementary characters as
+        // surrogate pairs.
+        let mut bytes = Vec::new();
+        write_string(&mut bytes, "\0\u{1F600}").unwrap();
+        assert_eq!(
+            bytes,
+            [0, 8, 0xC0, 0x80, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]
+        );
+    }
+}
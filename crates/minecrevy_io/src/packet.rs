@@ -2,10 +2,13 @@
 
 use std::{
     fmt::{self, Write as _},
-    io::{Cursor, Read, Write},
+    io::{self, Cursor, Read, Write},
 };
 
-use crate::util::varint_bytes;
+use crate::{
+    prelude::{ReadMinecraftExt, WriteMinecraftExt},
+    util::varint_bytes,
+};
 
 /// A single packet in the Minecraft protocol.
 ///
@@ -26,6 +29,10 @@ pub struct RawPacket {
 }
 
 impl RawPacket {
+    /// The maximum length of a packet, including its ID, as enforced by the
+    /// vanilla client and server.
+    pub const MAX_LEN: usize = 2_097_151;
+
     /// Returns the length of the packet.
     ///
     /// This corresponds to the Length field in the packet format mentioned [here][RawPacket].
@@ -37,6 +44,31 @@ impl RawPacket {
         id_len + body_len
     }
 
+    /// Reads a single length-prefixed packet from the given reader.
+    ///
+    /// This is the same framing used on the wire when neither compression nor
+    /// encryption is enabled, which allows reading a raw packet stream without
+    /// going through the codec.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::UnexpectedEof`] error if the whole packet
+    /// isn't available yet, or an [`io::ErrorKind::InvalidData`] error if the
+    /// packet is malformed.
+    pub fn read(mut reader: impl Read) -> io::Result<Self> {
+        reader.read_packet()
+    }
+
+    /// Writes this packet to the given writer, prefixed with its
+    /// [length](RawPacket::len) and ID.
+    ///
+    /// # Errors
+    ///
+    /// If the writer returns an error, this function will return that error.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_packet(self)
+    }
+
     /// Returns an opaque [`Read`] for reading from this packet's body.
     #[must_use]
     pub fn reader(&self) -> impl Read + '_ {
@@ -71,6 +103,10 @@ pub mod codec {
         time::Duration,
     };
 
+    use aes::{
+        cipher::{generic_array::GenericArray, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+        Aes128,
+    };
     use bytes::Buf;
     use tokio_util::codec::{Decoder, Encoder};
 
@@ -103,8 +139,18 @@ pub mod codec {
         pub settings: Arc<PacketCodecSettings>,
         /// True if packets should be compressed/decompressed.
         pub compress: bool,
-        /// True if packets should be encrypted/decrypted.
-        pub encrypt: bool,
+        /// The ciphers packets are encrypted and decrypted with, if encryption
+        /// is enabled.
+        cipher: Option<Cipher>,
+    }
+
+    /// The AES/CFB8 stream ciphers of an encrypted connection.
+    struct Cipher {
+        encryptor: cfb8::Encryptor<Aes128>,
+        decryptor: cfb8::Decryptor<Aes128>,
+        /// The number of bytes at the start of the read buffer that have
+        /// already been decrypted.
+        decrypted: usize,
     }
 
     impl RawPacketCodec {
@@ -114,7 +160,7 @@ pub mod codec {
             Self {
                 settings,
                 compress: false,
-                encrypt: false,
+                cipher: None,
             }
         }
 
@@ -123,9 +169,21 @@ pub mod codec {
             self.compress = true;
         }
 
-        /// Enables encryption for this codec.
-        pub fn enable_encryption(&mut self) {
-            self.encrypt = true;
+        /// Enables encryption for this codec, with the given shared secret as
+        /// both the AES key and IV, as vanilla does.
+        ///
+        /// Bytes already in the read buffer are treated as encrypted.
+        pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+            self.cipher = Some(Cipher {
+                encryptor: cfb8::Encryptor::new(shared_secret.into(), shared_secret.into()),
+                decryptor: cfb8::Decryptor::new(shared_secret.into(), shared_secret.into()),
+                decrypted: 0,
+            });
+        }
+
+        /// Returns `true` if encryption is enabled.
+        pub fn is_encrypted(&self) -> bool {
+            self.cipher.is_some()
         }
     }
 
@@ -144,8 +202,13 @@ pub mod codec {
                 // TODO
             }
 
-            if self.encrypt {
-                // TODO
+            if let Some(cipher) = &mut self.cipher {
+                // CFB8 encrypts one byte at a time.
+                for byte in bytes.chunks_exact_mut(1) {
+                    cipher
+                        .encryptor
+                        .encrypt_block_mut(GenericArray::from_mut_slice(byte));
+                }
             }
 
             dst.extend_from_slice(&bytes);
@@ -159,12 +222,26 @@ pub mod codec {
         type Error = io::Error;
 
         fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-            // TODO: compression and encryption
+            // TODO: compression
+            if let Some(cipher) = &mut self.cipher {
+                // Decrypt only the bytes that arrived since the last call.
+                for byte in src[cipher.decrypted..].chunks_exact_mut(1) {
+                    cipher
+                        .decryptor
+                        .decrypt_block_mut(GenericArray::from_mut_slice(byte));
+                }
+                cipher.decrypted = src.len();
+            }
+
             let mut cursor = Cursor::<&[u8]>::new(src);
             match cursor.read_packet() {
                 Ok(packet) => {
                     // reading was successful, advance the outer buffer and return
-                    src.advance(cursor.position() as usize);
+                    let len = cursor.position() as usize;
+                    src.advance(len);
+                    if let Some(cipher) = &mut self.cipher {
+                        cipher.decrypted -= len;
+                    }
                     Ok(Some(packet))
                 }
                 Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
@@ -172,4 +249,101 @@ pub mod codec {
             }
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::Arc;
+
+        use bytes::BytesMut;
+        use tokio_util::codec::{Decoder, Encoder};
+
+        use super::RawPacketCodec;
+        use crate::packet::RawPacket;
+
+        #[test]
+        fn encryption_round_trip() {
+            let secret = [7; 16];
+            let mut server = RawPacketCodec::new(Arc::default());
+            let mut client = RawPacketCodec::new(Arc::default());
+            server.enable_encryption(&secret);
+            client.enable_encryption(&secret);
+
+            let packets = [
+                RawPacket {
+                    id: 0x02,
+                    body: b"hello".to_vec(),
+                },
+                RawPacket {
+                    id: 0x2C,
+                    body: vec![0xFF; 300],
+                },
+            ];
+            let mut buf = BytesMut::new();
+            for packet in packets.clone() {
+                server.encode(packet, &mut buf).unwrap();
+            }
+            assert_ne!(&buf[1..], &[0x02, b'h', b'e', b'l', b'l', b'o'][..]);
+
+            // Deliver the bytes in two parts, splitting the second packet.
+            let mut src = BytesMut::from(&buf[..10]);
+            assert_eq!(client.decode(&mut src).unwrap(), Some(packets[0].clone()));
+            assert_eq!(client.decode(&mut src).unwrap(), None);
+            src.extend_from_slice(&buf[10..]);
+            assert_eq!(client.decode(&mut src).unwrap(), Some(packets[1].clone()));
+            assert!(src.is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawPacket;
+
+    #[test]
+    fn read_framed_packets() {
+        // Two frames back to back: ID 0x00 with "hi", then ID 0x80 with no body.
+        let bytes = [0x03, 0x00, b'h', b'i', 0x02, 0x80, 0x01];
+        let mut reader = bytes.as_slice();
+
+        assert_eq!(
+            RawPacket::read(&mut reader).unwrap(),
+            RawPacket {
+                id: 0x00,
+                body: b"hi".to_vec(),
+            }
+        );
+        assert_eq!(
+            RawPacket::read(&mut reader).unwrap(),
+            RawPacket {
+                id: 0x80,
+                body: Vec::new(),
+            }
+        );
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn write_matches_read_bytes() {
+        let bytes = [0x04, 0x80, 0x01, 0xAB, 0xCD];
+        let packet = RawPacket::read(bytes.as_slice()).unwrap();
+        assert_eq!(packet.len(), 4);
+
+        let mut written = Vec::new();
+        packet.write(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+
+    #[test]
+    fn length_covers_id_and_body() {
+        let packet = RawPacket {
+            id: 0x2C,
+            body: vec![0; 200],
+        };
+        let mut written = Vec::new();
+        packet.write(&mut written).unwrap();
+
+        // 201 as a VarInt, then the ID and body.
+        assert_eq!(&written[..3], &[0xC9, 0x01, 0x2C]);
+        assert_eq!(written.len(), 2 + packet.len());
+    }
 }
@@ -1,6 +1,24 @@
 //! Utility functions for the `minecrevy_io` crate.
 
-use std::sync::OnceLock;
+use std::{
+    io::{self, Read},
+    sync::OnceLock,
+};
+
+use uuid::Uuid;
+
+use crate::{McRead, McWrite};
+
+/// The most memory to allocate up front for a collection whose length was
+/// read from untrusted input, so that a bogus length can't exhaust memory
+/// before the reader runs out of data.
+pub(crate) const MAX_PREALLOC_BYTES: usize = 64 * 1024;
+
+/// Returns the number of elements of `T` to allocate up front for a
+/// collection with the given length, read from untrusted input.
+pub(crate) fn prealloc_len<T>(len: usize) -> usize {
+    len.min(MAX_PREALLOC_BYTES / std::mem::size_of::<T>().max(1))
+}
 
 /// Returns the number of bytes required to encode the given value as a varint.
 pub fn varint_bytes(value: i32) -> usize {
@@ -17,3 +35,183 @@ pub fn varint_bytes(value: i32) -> usize {
         })
     })[value.leading_zeros() as usize]
 }
+
+/// Reads a `T`, or returns its default if the stream has already ended, for
+/// trailing fields that older clients don't send.
+///
+/// This is what `#[options(default_on_eof)]` fields are read with.
+///
+/// # Errors
+///
+/// If the value is cut short after some of it was read, or reading fails for
+/// any other reason, this function will return that error.
+pub fn read_default_on_eof<T: McRead + Default>(reader: impl Read, args: T::Args) -> io::Result<T> {
+    let mut reader = CountingReader {
+        inner: reader,
+        read: 0,
+    };
+    match T::read(&mut reader, args) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && reader.read == 0 => Ok(T::default()),
+        result => result,
+    }
+}
+
+/// An [`io::Read`] that counts the bytes read through it.
+struct CountingReader<R> {
+    inner: R,
+    read: usize,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n;
+        Ok(n)
+    }
+}
+
+/// Converts the given [`Uuid`] to the four ints NBT stores it as, most
+/// significant first.
+pub fn uuid_to_intarray(uuid: Uuid) -> [i32; 4] {
+    let bits = uuid.as_u128();
+    [
+        (bits >> 96) as i32,
+        (bits >> 64) as i32,
+        (bits >> 32) as i32,
+        bits as i32,
+    ]
+}
+
+/// Converts the four ints NBT stores a [`Uuid`] as, most significant first,
+/// back to the [`Uuid`].
+pub fn uuid_from_intarray(ints: [i32; 4]) -> Uuid {
+    Uuid::from_u128(
+        ints.iter()
+            .fold(0u128, |bits, &int| (bits << 32) | u128::from(int as u32)),
+    )
+}
+
+/// Returns the exact number of bytes the given value takes when written with
+/// the given arguments, without buffering the written bytes.
+///
+/// # Errors
+///
+/// If writing the value fails, such as a string exceeding its maximum length,
+/// this function will return that error.
+pub fn serialized_len<T: McWrite>(value: &T, args: T::Args) -> io::Result<usize> {
+    let mut counter = ByteCounter(0);
+    value.write(&mut counter, args)?;
+    Ok(counter.0)
+}
+
+/// An [`io::Write`] that discards its input, only counting the bytes written.
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use uuid::Uuid;
+
+    use super::{uuid_from_intarray, uuid_to_intarray};
+    use crate::{error::DecodeError, McRead, McWrite};
+
+    /// A packet whose trailing fields older clients don't send.
+    #[derive(McRead, McWrite, PartialEq, Debug)]
+    struct Tailed {
+        id: u8,
+        #[options(default_on_eof)]
+        tail: u16,
+        #[options(max_len = Some(4), default_on_eof = true)]
+        name: String,
+    }
+
+    #[test]
+    fn default_on_eof_reads_the_tail() {
+        let bytes = [7, 0x01, 0x02, 0x02, b'h', b'i'];
+        let packet = Tailed::read(bytes.as_slice(), ()).unwrap();
+        assert_eq!(
+            packet,
+            Tailed {
+                id: 7,
+                tail: 0x0102,
+                name: "hi".to_owned(),
+            }
+        );
+
+        let mut written = Vec::new();
+        packet.write(&mut written, ()).unwrap();
+        assert_eq!(written, bytes);
+    }
+
+    #[test]
+    fn default_on_eof_defaults_a_missing_tail() {
+        let packet = Tailed::read([7].as_slice(), ()).unwrap();
+        assert_eq!(
+            packet,
+            Tailed {
+                id: 7,
+                tail: 0,
+                name: String::new(),
+            }
+        );
+
+        // Only the last field is missing.
+        let packet = Tailed::read([7, 0x01, 0x02].as_slice(), ()).unwrap();
+        assert_eq!(packet.tail, 0x0102);
+        assert_eq!(packet.name, "");
+    }
+
+    #[test]
+    fn default_on_eof_rejects_a_partial_tail() {
+        // One of the two bytes of the tail.
+        let error = Tailed::read([7, 0x01].as_slice(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // The name's length, but not all of its bytes.
+        let error = Tailed::read([7, 0x01, 0x02, 0x02, b'h'].as_slice(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn default_on_eof_keeps_other_errors() {
+        // Missing fields before the tail are still errors.
+        let error = Tailed::read([].as_slice(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        // The tail's own options still apply.
+        let bytes = [7, 0x01, 0x02, 0x05, b'h', b'e', b'l', b'l', b'o'];
+        let error = Tailed::read(bytes.as_slice(), ()).unwrap_err();
+        assert!(matches!(
+            DecodeError::from(error),
+            DecodeError::LengthExceeded { max: 4, actual: 5 }
+        ));
+    }
+
+    #[test]
+    fn uuid_intarray_is_most_significant_first() {
+        let uuid = Uuid::parse_str("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap();
+        let ints = [-132296786, 2112623056, -1486552928, -920753162];
+
+        assert_eq!(uuid_to_intarray(uuid), ints);
+        assert_eq!(uuid_from_intarray(ints), uuid);
+    }
+
+    #[test]
+    fn uuid_intarray_round_trip() {
+        for uuid in [Uuid::nil(), Uuid::max(), Uuid::from_u128(1 << 127 | 1)] {
+            assert_eq!(uuid_from_intarray(uuid_to_intarray(uuid)), uuid);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Derive macros for the `McRead` and `McWrite` traits of `minecrevy_io`.
+//!
+//! Use them through the re-exports in `minecrevy_io`, as the generated code
+//! refers to that crate.
+
+#![warn(missing_docs)]
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, Fields, Ident, LitBool, Member,
+    Type,
+};
+
+/// Derives `McRead` for a struct, reading its fields in declaration order.
+///
+/// The struct's `Args` are `()`. Each field is read with the default `Args` of
+/// its type, with the fields set by its `#[options(...)]` attribute:
+///
+/// - `name = value` sets the `name` field of the field type's `Args` to
+///   `value`, such as `#[options(varint = true)]` for an
+///   `IntArgs { varint: true }`.
+/// - `default_on_eof` reads the field's default if the packet ends before it,
+///   for trailing fields that older clients don't send. It's only allowed on
+///   trailing fields, so every field after it must have it too. A field that's
+///   cut short partway through is still an error.
+#[proc_macro_derive(McRead, attributes(options))]
+pub fn derive_mc_read(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_read(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `McWrite` for a struct, writing its fields in declaration order.
+///
+/// The struct's `Args` are `()`. Each field is written with the default `Args`
+/// of its type, with the fields set by its `#[options(...)]` attribute, as for
+/// `McRead`. Fields with `default_on_eof` are always written.
+#[proc_macro_derive(McWrite, attributes(options))]
+pub fn derive_mc_write(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_write(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A field of the struct being derived, with its parsed options.
+struct Field<'a> {
+    /// The name or index of the field.
+    member: Member,
+    /// The type of the field.
+    ty: &'a Type,
+    /// The `name = value` options, in the order they were given.
+    args: Vec<(Ident, Expr)>,
+    /// Whether a missing field reads as its default.
+    default_on_eof: bool,
+}
+
+/// Parses the fields of the given struct and their options.
+///
+/// # Errors
+///
+/// If the input isn't a struct, an option is malformed, or `default_on_eof`
+/// is on a field that isn't trailing, this function will return an error.
+fn fields(input: &DeriveInput) -> syn::Result<(Vec<Field<'_>>, bool)> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "McRead and McWrite can only be derived for structs",
+        ));
+    };
+
+    let mut fields = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let mut parsed = Field {
+            member,
+            ty: &field.ty,
+            args: Vec::new(),
+            default_on_eof: false,
+        };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("options")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default_on_eof") {
+                    parsed.default_on_eof =
+                        if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+                            true
+                        } else {
+                            meta.value()?.parse::<LitBool>()?.value
+                        };
+                } else if let Some(ident) = meta.path.get_ident() {
+                    parsed.args.push((ident.clone(), meta.value()?.parse()?));
+                } else {
+                    return Err(meta.error("expected `name = value` or `default_on_eof`"));
+                }
+                Ok(())
+            })?;
+        }
+        fields.push(parsed);
+    }
+
+    // A field with `default_on_eof` must not be followed by one without it.
+    if let Some(last) = fields.iter().rposition(|f| !f.default_on_eof) {
+        if let Some(field) = fields[..last].iter().find(|f| f.default_on_eof) {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                "`default_on_eof` is only allowed on trailing fields",
+            ));
+        }
+    }
+
+    let named = matches!(data.fields, Fields::Named(_));
+    Ok((fields, named))
+}
+
+/// Returns an expression for the `Args` the given field is read or written
+/// with, where `Args` is the associated type of `trait_path`.
+fn args(field: &Field<'_>, trait_path: &TokenStream) -> TokenStream {
+    let ty = field.ty;
+    if field.args.is_empty() {
+        return quote!(::core::default::Default::default());
+    }
+    let (names, values): (Vec<_>, Vec<_>) = field.args.iter().cloned().unzip();
+    quote!({
+        let mut args: <#ty as #trait_path>::Args = ::core::default::Default::default();
+        #(args.#names = #values;)*
+        args
+    })
+}
+
+/// Expands `#[derive(McRead)]`.
+fn expand_read(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let (fields, named) = fields(input)?;
+    let trait_path = quote!(::minecrevy_io::McRead);
+
+    let reads = fields.iter().map(|field| {
+        let ty = field.ty;
+        let args = args(field, &trait_path);
+        if field.default_on_eof {
+            quote!(::minecrevy_io::util::read_default_on_eof::<#ty>(&mut reader, #args)?)
+        } else {
+            quote!(<#ty as #trait_path>::read(&mut reader, #args)?)
+        }
+    });
+    let body = if named {
+        let members = fields.iter().map(|f| &f.member);
+        quote!(Self { #(#members: #reads,)* })
+    } else if fields.is_empty() {
+        quote!(Self)
+    } else {
+        quote!(Self(#(#reads,)*))
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+            type Args = ();
+
+            #[allow(unused_mut, unused_variables, clippy::field_reassign_with_default)]
+            fn read(
+                mut reader: impl ::std::io::Read,
+                (): Self::Args,
+            ) -> ::std::io::Result<Self> {
+                ::std::io::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+/// Expands `#[derive(McWrite)]`.
+fn expand_write(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let (fields, _) = fields(input)?;
+    let trait_path = quote!(::minecrevy_io::McWrite);
+
+    let writes = fields.iter().map(|field| {
+        let ty = field.ty;
+        let member = &field.member;
+        let args = args(field, &trait_path);
+        quote!(<#ty as #trait_path>::write(&self.#member, &mut writer, #args)?;)
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+            type Args = ();
+
+            #[allow(unused_mut, unused_variables, clippy::field_reassign_with_default)]
+            fn write(
+                &self,
+                mut writer: impl ::std::io::Write,
+                (): Self::Args,
+            ) -> ::std::io::Result<()> {
+                #(#writes)*
+                ::std::io::Result::Ok(())
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::{expand_read, expand_write};
+
+    #[test]
+    fn default_on_eof_on_trailing_fields() {
+        let input = parse_quote! {
+            struct Packet {
+                id: u8,
+                #[options(default_on_eof)]
+                a: u16,
+                #[options(varint = true, default_on_eof = true)]
+                b: i32,
+            }
+        };
+        assert!(expand_read(&input).is_ok());
+        assert!(expand_write(&input).is_ok());
+    }
+
+    #[test]
+    fn default_on_eof_rejected_before_other_fields() {
+        let input = parse_quote! {
+            struct Packet {
+                #[options(default_on_eof)]
+                a: u16,
+                b: u8,
+            }
+        };
+        let error = expand_read(&input).unwrap_err();
+        assert!(error.to_string().contains("trailing fields"), "{error}");
+        assert!(expand_write(&input).is_err());
+    }
+
+    #[test]
+    fn enums_are_rejected() {
+        let input = parse_quote! {
+            enum Packet {
+                A,
+            }
+        };
+        assert!(expand_read(&input).is_err());
+    }
+}
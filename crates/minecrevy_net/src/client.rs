@@ -1,11 +1,18 @@
 //! This module contains the [`ClientPlugin`], which handles client communication, both client-side and server-side.
 
-use std::{io, net::SocketAddr};
+use std::{
+    io,
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use bevy::{
     ecs::{
-        component::ComponentId, entity::EntityHashMap, query::QueryEntityError,
-        system::SystemParam, world::DeferredWorld,
+        component::ComponentId,
+        entity::EntityHashMap,
+        query::QueryEntityError,
+        system::{Deferred, SystemBuffer, SystemMeta, SystemParam},
+        world::DeferredWorld,
     },
     prelude::*,
     utils::HashMap,
@@ -13,7 +20,13 @@ use bevy::{
 use minecrevy_io::{packet::RawPacket, McWrite};
 use tokio::sync::{mpsc::UnboundedSender, oneshot};
 
-use crate::packet::OutgoingPacketIds;
+#[cfg(feature = "metrics")]
+use crate::metrics::PacketMetrics;
+use crate::{
+    packet::{OutgoingPacketIds, PacketIdMap},
+    server::StateEnteredAt,
+    stats::NetworkStats,
+};
 
 /// [`Plugin`] for client-side network functionality.
 pub struct ClientPlugin;
@@ -24,10 +37,28 @@ impl Plugin for ClientPlugin {
     }
 }
 
-/// [`Resource`] that maps [`SocketAddr`] to [`Entity`] and vice versa.
+/// A unique identifier for a connection to the server, which incoming packets
+/// are routed by.
+///
+/// Unlike a [`SocketAddr`], which a PROXY protocol header can claim to be
+/// anything, a connection ID is assigned by the server and never reused.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ConnectionId(u64);
+
+impl ConnectionId {
+    /// Returns a new, unique [`ConnectionId`].
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// [`Resource`] that maps [`SocketAddr`] and [`ConnectionId`] to [`Entity`],
+/// and [`Entity`] to [`SocketAddr`].
 #[derive(Resource, Default)]
 pub struct ClientAddressIndex {
     addr_to_entity: HashMap<SocketAddr, Entity>,
+    id_to_entity: HashMap<ConnectionId, Entity>,
     entity_to_addr: EntityHashMap<SocketAddr>,
 }
 
@@ -38,19 +69,36 @@ impl ClientAddressIndex {
     }
 
     /// Returns the [`Entity`] of the given [`SocketAddr`].
+    ///
+    /// If more than one client claims the same address, this is the one that
+    /// connected first.
     pub fn entity(&self, addr: SocketAddr) -> Option<Entity> {
         self.addr_to_entity.get(&addr).copied()
     }
 
-    /// Inserts the given [`SocketAddr`] and [`Entity`] into the index.
-    pub(crate) fn insert(&mut self, addr: SocketAddr, entity: Entity) {
-        self.addr_to_entity.insert(addr, entity);
+    /// Returns the [`Entity`] of the given [`ConnectionId`].
+    pub fn entity_by_id(&self, id: ConnectionId) -> Option<Entity> {
+        self.id_to_entity.get(&id).copied()
+    }
+
+    /// Inserts the given [`SocketAddr`], [`ConnectionId`], and [`Entity`] into
+    /// the index.
+    ///
+    /// An address that's already in the index keeps its entity, so that a
+    /// client can't take over another's address.
+    pub(crate) fn insert(&mut self, addr: SocketAddr, id: ConnectionId, entity: Entity) {
+        self.addr_to_entity.entry(addr).or_insert(entity);
+        self.id_to_entity.insert(id, entity);
         self.entity_to_addr.insert(entity, addr);
     }
 
-    /// Removes the given [`SocketAddr`] and [`Entity`] from the index.
-    pub(crate) fn remove(&mut self, addr: SocketAddr, entity: Entity) {
-        self.addr_to_entity.remove(&addr);
+    /// Removes the given [`SocketAddr`], [`ConnectionId`], and [`Entity`] from
+    /// the index.
+    pub(crate) fn remove(&mut self, addr: SocketAddr, id: ConnectionId, entity: Entity) {
+        if self.addr_to_entity.get(&addr) == Some(&entity) {
+            self.addr_to_entity.remove(&addr);
+        }
+        self.id_to_entity.remove(&id);
         self.entity_to_addr.remove(&entity);
     }
 }
@@ -58,8 +106,20 @@ impl ClientAddressIndex {
 /// [`SystemParam`] for writing packets to clients.
 #[derive(SystemParam)]
 pub struct PacketWriter<'w, 's> {
-    clients: Query<'w, 's, (&'static Client, &'static mut ProtocolState)>,
+    clients: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Client,
+            &'static mut ProtocolState,
+            Option<&'static PacketIdMap>,
+        ),
+    >,
     outgoing_ids: Res<'w, OutgoingPacketIds>,
+    state_changes: Deferred<'s, StateChanges>,
+    #[cfg(feature = "metrics")]
+    metrics: Res<'w, PacketMetrics>,
 }
 
 impl PacketWriter<'_, '_> {
@@ -82,15 +142,30 @@ impl PacketWriter<'_, '_> {
         client: Entity,
     ) -> Result<ClientPacketWriter<'_>, QueryEntityError> {
         let outgoing_ids = &self.outgoing_ids;
+        let state_changes = &mut *self.state_changes;
+        #[cfg(feature = "metrics")]
+        let metrics = &*self.metrics;
         self.clients
             .get_mut(client)
-            .map(move |(client, state)| ClientPacketWriter {
+            .map(move |(entity, client, state, id_map)| ClientPacketWriter {
+                entity,
                 client,
                 state,
                 outgoing_ids,
+                id_map,
+                state_changes,
+                #[cfg(feature = "metrics")]
+                metrics,
             })
     }
 
+    /// Returns an iterator over all clients and their current [`ProtocolState`].
+    pub fn states(&self) -> impl Iterator<Item = (Entity, ProtocolState)> + '_ {
+        self.clients
+            .iter()
+            .map(|(entity, _, state, _)| (entity, *state))
+    }
+
     /// Sends the given packet to the given client.
     pub fn send<T: McWrite + 'static>(&mut self, client: Entity, packet: &T) -> &mut Self {
         let client = self.get_client(client).unwrap();
@@ -98,26 +173,91 @@ impl PacketWriter<'_, '_> {
         drop(client);
         self
     }
+
+    /// Sends the given packet to all clients in the given [`ProtocolState`].
+    pub fn broadcast<T: McWrite + 'static>(
+        &mut self,
+        state: ProtocolState,
+        packet: &T,
+    ) -> &mut Self {
+        self.broadcast_filtered(state, packet, |_| true)
+    }
+
+    /// Sends the given packet to all clients in the given [`ProtocolState`],
+    /// except for the given client.
+    pub fn broadcast_except<T: McWrite + 'static>(
+        &mut self,
+        state: ProtocolState,
+        except: Entity,
+        packet: &T,
+    ) -> &mut Self {
+        self.broadcast_filtered(state, packet, |client| client != except)
+    }
+
+    /// Sends the given packet to all clients in the given [`ProtocolState`]
+    /// that match the given filter.
+    pub fn broadcast_filtered<T: McWrite + 'static>(
+        &mut self,
+        state: ProtocolState,
+        packet: &T,
+        mut filter: impl FnMut(Entity) -> bool,
+    ) -> &mut Self {
+        let id = registered_id::<T>(&self.outgoing_ids, state);
+
+        for (entity, client, client_state, id_map) in &self.clients {
+            if *client_state != state || !filter(entity) {
+                continue;
+            }
+            let Some(id) = client_id::<T>(id_map, state, id) else {
+                continue;
+            };
+
+            #[cfg(feature = "metrics")]
+            let start = std::time::Instant::now();
+            let _len = client.send(id, packet);
+            #[cfg(feature = "metrics")]
+            self.metrics.record_outgoing::<T>(_len, start.elapsed());
+
+            let _ = client.outgoing.send(WriteOp::Flush);
+        }
+        self
+    }
 }
 
 /// A writer for sending packets to a client.
 pub struct ClientPacketWriter<'w> {
+    entity: Entity,
     client: &'w Client,
     state: Mut<'w, ProtocolState>,
     outgoing_ids: &'w OutgoingPacketIds,
+    id_map: Option<&'w PacketIdMap>,
+    state_changes: &'w mut StateChanges,
+    #[cfg(feature = "metrics")]
+    metrics: &'w PacketMetrics,
 }
 
 impl ClientPacketWriter<'_> {
     /// Sends the given packet to the client.
     pub fn send<T: McWrite + 'static>(&self, packet: &T) -> &Self {
-        let id = self.outgoing_ids.get::<T>(*self.state).unwrap_or_else(|| {
-            panic!(
-                "Packet {:?} is not registered for state {:?}",
-                std::any::type_name::<T>(),
-                self.state
-            )
-        });
-        self.client.send(id, packet);
+        let id = registered_id::<T>(self.outgoing_ids, *self.state);
+        let Some(id) = client_id::<T>(self.id_map, *self.state, id) else {
+            return self;
+        };
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        let _len = self.client.send(id, packet);
+        #[cfg(feature = "metrics")]
+        self.metrics.record_outgoing::<T>(_len, start.elapsed());
+        self
+    }
+
+    /// Enables encryption for the [`Client`] with the given shared secret,
+    /// starting with the next packet sent.
+    pub fn enable_encryption(&self, shared_secret: [u8; 16]) -> &Self {
+        let _ = self
+            .client
+            .outgoing
+            .send(WriteOp::EnableEncryption(shared_secret));
         self
     }
 
@@ -127,8 +267,16 @@ impl ClientPacketWriter<'_> {
     }
 
     /// Changes the [`Client`]'s [`ProtocolState`].
+    ///
+    /// A [`StateChanged`] event is triggered for the client once the calling
+    /// system's deferred changes are applied.
     pub fn set_state(&mut self, state: ProtocolState) {
-        *self.state = state;
+        let from = std::mem::replace(&mut *self.state, state);
+        if from != state {
+            self.state_changes
+                .0
+                .push((self.entity, StateChanged { from, to: state }));
+        }
     }
 }
 
@@ -138,74 +286,135 @@ impl Drop for ClientPacketWriter<'_> {
     }
 }
 
+/// Returns the registered ID of packet `T` in the given [`ProtocolState`].
+///
+/// # Panics
+///
+/// Panics if `T` isn't registered for the state.
+fn registered_id<T: McWrite + 'static>(ids: &OutgoingPacketIds, state: ProtocolState) -> i32 {
+    ids.get::<T>(state).unwrap_or_else(|| {
+        panic!(
+            "Packet {:?} is not registered for state {:?}",
+            std::any::type_name::<T>(),
+            state
+        )
+    })
+}
+
+/// Returns the ID to send packet `T` to a client with the given
+/// [`PacketIdMap`] with, or [`None`] if the client's protocol version doesn't
+/// have the packet.
+fn client_id<T: McWrite + 'static>(
+    id_map: Option<&PacketIdMap>,
+    state: ProtocolState,
+    id: i32,
+) -> Option<i32> {
+    let Some(id_map) = id_map else {
+        return Some(id);
+    };
+    let id = id_map.outgoing(state, id);
+    if id.is_none() {
+        debug!(
+            "Not sending packet {:?}, which the client doesn't have in state {:?}",
+            std::any::type_name::<T>(),
+            state
+        );
+    }
+    id
+}
+
 /// A client connected to the server.
 #[derive(Component)]
-#[require(ProtocolState)]
+#[require(ProtocolState, StateEnteredAt)]
 #[component(on_add = Self::on_add, on_remove = Self::on_remove)]
 pub struct Client {
+    /// The ID of the client's connection, which its incoming packets are
+    /// routed by.
+    id: ConnectionId,
     /// The address of the client.
     addr: SocketAddr,
     /// The [`UnboundedSender`] for outgoing packets.
     outgoing: UnboundedSender<WriteOp>,
     /// The [`Receiver`] for I/O errors.
     pub(crate) errors: oneshot::Receiver<io::Error>,
+    /// The [`NetworkStats`] updated by the client's I/O task, which are
+    /// inserted alongside the client when it's spawned.
+    pub(crate) stats: NetworkStats,
 }
 
 impl Client {
-    /// Creates a new [`Client`] with the given address, I/O task, and channels.
+    /// Creates a new [`Client`] with the given connection ID, address, and
+    /// channels.
     pub(crate) fn new(
+        id: ConnectionId,
         addr: SocketAddr,
         outgoing: UnboundedSender<WriteOp>,
         errors: oneshot::Receiver<io::Error>,
+        stats: NetworkStats,
     ) -> Self {
         Self {
+            id,
             addr,
             outgoing,
             errors,
+            stats,
         }
     }
 
+    /// Returns the ID of the client's connection.
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+
     /// Returns the address of the client.
     pub fn addr(&self) -> SocketAddr {
         self.addr
     }
 
-    /// Sends the given packet to the client.
+    /// Closes the client's connection, after writing the packets already
+    /// flushed to it.
+    pub fn disconnect(&self) {
+        let _ = self.outgoing.send(WriteOp::Disconnect);
+    }
+
+    /// Sends the given packet to the client, returning the size of its body.
     ///
     /// Prefer using [`PacketWriter`] or [`ClientPacketWriter`] instead.
-    fn send<T: McWrite + 'static>(&self, id: i32, packet: &T) {
+    fn send<T: McWrite + 'static>(&self, id: i32, packet: &T) -> usize {
         let mut body = Vec::new();
         packet.write_default(&mut body).unwrap();
+        let len = body.len();
 
         let _ = self.outgoing.send(WriteOp::Send(RawPacket { id, body }));
+        len
     }
 
     fn on_add(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
-        let Some(addr) = world.get::<Client>(entity).map(|c| c.addr()) else {
+        let Some((id, addr)) = world.get::<Client>(entity).map(|c| (c.id(), c.addr())) else {
             return;
         };
         let Some(mut index) = world.get_resource_mut::<ClientAddressIndex>() else {
             return;
         };
 
-        index.insert(addr, entity);
+        index.insert(addr, id, entity);
     }
 
     fn on_remove(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
-        let Some(addr) = world.get::<Client>(entity).map(|c| c.addr()) else {
+        let Some((id, addr)) = world.get::<Client>(entity).map(|c| (c.id(), c.addr())) else {
             return;
         };
         let Some(mut index) = world.get_resource_mut::<ClientAddressIndex>() else {
             return;
         };
 
-        index.remove(addr, entity);
+        index.remove(addr, id, entity);
     }
 }
 
 impl Drop for Client {
     fn drop(&mut self) {
-        let _ = self.outgoing.send(WriteOp::Disconnect);
+        self.disconnect();
     }
 }
 
@@ -218,8 +427,8 @@ pub enum WriteOp {
     Flush,
     /// Enables compression for the client.
     EnableCompression,
-    /// Enables encryption for the client.
-    EnableEncryption,
+    /// Enables encryption for the client with the given shared secret.
+    EnableEncryption([u8; 16]),
     /// Disconnects the client.
     Disconnect,
 }
@@ -244,3 +453,210 @@ pub enum ProtocolState {
     /// new network configuration.
     Config,
 }
+
+/// [`Event`] triggered for a [`Client`] after its [`ProtocolState`] changes.
+#[derive(Event)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StateChanged {
+    /// The previous protocol state.
+    pub from: ProtocolState,
+    /// The new protocol state.
+    pub to: ProtocolState,
+}
+
+/// [`SystemBuffer`] of [`StateChanged`] events to trigger once the system
+/// that changed the states applies its deferred changes.
+#[derive(Default)]
+struct StateChanges(Vec<(Entity, StateChanged)>);
+
+impl SystemBuffer for StateChanges {
+    fn apply(&mut self, _system_meta: &SystemMeta, world: &mut World) {
+        for (client, event) in self.0.drain(..) {
+            world.trigger_targets(event, client);
+        }
+    }
+
+    fn queue(&mut self, _system_meta: &SystemMeta, mut world: DeferredWorld) {
+        let mut commands = world.commands();
+        for (client, event) in self.0.drain(..) {
+            commands.trigger_targets(event, client);
+        }
+    }
+}
+
+impl ProtocolState {
+    /// Every protocol state, in the order a client usually moves through them.
+    pub const ALL: [Self; 5] = [
+        Self::Handshake,
+        Self::Status,
+        Self::Login,
+        Self::Config,
+        Self::Play,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        net::{Ipv4Addr, SocketAddr},
+        time::Duration,
+    };
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use super::{ClientAddressIndex, ConnectionId, PacketWriter, ProtocolState, StateChanged};
+    use crate::{
+        loopback::LoopbackClient,
+        packet::OutgoingPacketIds,
+        server::{Server, ServerPlugin},
+    };
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.init_resource::<OutgoingPacketIds>();
+        app.world_mut()
+            .resource_mut::<OutgoingPacketIds>()
+            .insert::<u8>(ProtocolState::Play, 0x01);
+        app
+    }
+
+    /// Connects a loopback client in the given state, which must be kept
+    /// alive so that the server doesn't despawn it for closing the connection.
+    fn connect(app: &mut App, state: ProtocolState) -> (Entity, LoopbackClient) {
+        let mut connection = app.world().resource::<Server>().connect_loopback();
+        connection.set_timeout(Duration::from_millis(100));
+        app.update();
+        let client = app
+            .world()
+            .resource::<ClientAddressIndex>()
+            .entity(connection.addr())
+            .unwrap();
+        app.world_mut()
+            .run_system_once(move |mut writer: PacketWriter| {
+                writer.client(client).set_state(state);
+            })
+            .unwrap();
+        (client, connection)
+    }
+
+    fn assert_nothing_received(connection: &mut LoopbackClient) {
+        let error = connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn broadcast_except_skips_the_excluded_client_and_other_states() {
+        let mut app = app();
+        let (excluded, mut excluded_connection) = connect(&mut app, ProtocolState::Play);
+        let (_, mut playing) = connect(&mut app, ProtocolState::Play);
+        let (_, mut configuring) = connect(&mut app, ProtocolState::Config);
+
+        app.world_mut()
+            .run_system_once(move |mut writer: PacketWriter| {
+                writer.broadcast_except(ProtocolState::Play, excluded, &7u8);
+            })
+            .unwrap();
+
+        // The packet is flushed without a ClientPacketWriter being dropped.
+        let packet = playing.recv_raw().unwrap();
+        assert_eq!((packet.id, packet.body), (0x01, vec![7]));
+        assert_nothing_received(&mut excluded_connection);
+        assert_nothing_received(&mut configuring);
+    }
+
+    #[test]
+    fn broadcast_reaches_every_client_in_the_state() {
+        let mut app = app();
+        let (_, mut first) = connect(&mut app, ProtocolState::Play);
+        let (_, mut second) = connect(&mut app, ProtocolState::Play);
+
+        app.world_mut()
+            .run_system_once(|mut writer: PacketWriter| {
+                writer.broadcast(ProtocolState::Play, &3u8);
+            })
+            .unwrap();
+
+        assert_eq!(first.recv_raw().unwrap().body, [3]);
+        assert_eq!(second.recv_raw().unwrap().body, [3]);
+    }
+
+    #[test]
+    #[should_panic = "is not registered for state"]
+    fn broadcast_of_unregistered_packet_panics() {
+        let mut app = app();
+        app.world_mut()
+            .run_system_once(|mut writer: PacketWriter| {
+                writer.broadcast(ProtocolState::Play, &3u16);
+            })
+            .unwrap();
+    }
+
+    /// [`Resource`] that collects every [`StateChanged`] event in order.
+    #[derive(Resource, Default)]
+    struct StateChanges(Vec<(Entity, StateChanged)>);
+
+    fn record_state_change(trigger: Trigger<StateChanged>, mut changes: ResMut<StateChanges>) {
+        changes.0.push((trigger.entity(), *trigger.event()));
+    }
+
+    #[test]
+    fn state_changed_is_triggered_for_each_transition() {
+        let mut app = app();
+        app.init_resource::<StateChanges>();
+        app.add_observer(record_state_change);
+
+        let (client, _connection) = connect(&mut app, ProtocolState::Login);
+        for state in [
+            ProtocolState::Config,
+            ProtocolState::Config,
+            ProtocolState::Play,
+            ProtocolState::Config,
+        ] {
+            app.world_mut()
+                .run_system_once(move |mut writer: PacketWriter| {
+                    writer.client(client).set_state(state);
+                })
+                .unwrap();
+        }
+
+        // Setting the state the client is already in doesn't trigger an event.
+        let changes = &app.world().resource::<StateChanges>().0;
+        assert!(changes.iter().all(|&(entity, _)| entity == client));
+        let transitions = changes
+            .iter()
+            .map(|(_, change)| (change.from, change.to))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transitions,
+            [
+                (ProtocolState::Handshake, ProtocolState::Login),
+                (ProtocolState::Login, ProtocolState::Config),
+                (ProtocolState::Config, ProtocolState::Play),
+                (ProtocolState::Play, ProtocolState::Config),
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_address_does_not_take_over_a_connection() {
+        let addr = SocketAddr::new(Ipv4Addr::new(192, 168, 0, 1).into(), 56324);
+        let (first_id, second_id) = (ConnectionId::next(), ConnectionId::next());
+        let (first, second) = (Entity::from_raw(1), Entity::from_raw(2));
+
+        let mut index = ClientAddressIndex::default();
+        index.insert(addr, first_id, first);
+        index.insert(addr, second_id, second);
+
+        assert_eq!(index.entity(addr), Some(first));
+        assert_eq!(index.entity_by_id(first_id), Some(first));
+        assert_eq!(index.entity_by_id(second_id), Some(second));
+
+        // Removing the second client leaves the first one's address in place.
+        index.remove(addr, second_id, second);
+        assert_eq!(index.entity(addr), Some(first));
+        assert_eq!(index.entity_by_id(second_id), None);
+        assert_eq!(index.address(second), None);
+    }
+}
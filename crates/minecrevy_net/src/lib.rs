@@ -10,13 +10,18 @@ use tokio::net::ToSocketAddrs;
 
 use crate::{
     client::ProtocolState,
-    packet::{IncomingPacketHandlers, OutgoingPacketIds},
-    server::{Server, ServerPlugin},
+    packet::{IncomingPacketHandlers, OutgoingPacketIds, PacketBatches, PacketRegistration},
+    server::{Server, ServerPlugin, ServerSets},
 };
 
 pub mod client;
+pub mod loopback;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod packet;
+pub mod proxy;
 pub mod server;
+pub mod stats;
 
 /// [`PluginGroup`] for the [`NetworkPlugin`] and [`ServerPlugin`].
 pub struct NetworkServerPlugins;
@@ -38,12 +43,34 @@ pub trait AppNetworkExt {
         id: i32,
     ) -> &mut Self;
 
+    /// Registers the given incoming packet type with the given [`ProtocolState`] and packet ID,
+    /// delivering it in a [`RecvBatch<T>`](packet::RecvBatch) once per tick rather than as a
+    /// [`Recv<T>`](packet::Recv) for each packet.
+    fn add_batched_incoming_packet<T: McRead + Send + Sync + 'static>(
+        &mut self,
+        state: ProtocolState,
+        id: i32,
+    ) -> &mut Self;
+
     /// Registers the given outgoing packet type with the given [`ProtocolState`] and packet ID.
     fn add_outgoing_packet<T: McWrite + Send + Sync + 'static>(
         &mut self,
         state: ProtocolState,
         id: i32,
     ) -> &mut Self;
+
+    /// Registers each of the given packet types with the given
+    /// [`ProtocolState`] and its paired packet ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two different packet types in the same direction are
+    /// registered with the same ID and [`ProtocolState`].
+    fn add_packets(
+        &mut self,
+        state: ProtocolState,
+        packets: &[(i32, PacketRegistration)],
+    ) -> &mut Self;
 }
 
 impl AppNetworkExt for App {
@@ -60,6 +87,27 @@ impl AppNetworkExt for App {
         self
     }
 
+    fn add_batched_incoming_packet<T: McRead + Send + Sync + 'static>(
+        &mut self,
+        state: ProtocolState,
+        id: i32,
+    ) -> &mut Self {
+        let mut handlers = self
+            .world_mut()
+            .get_resource_or_init::<IncomingPacketHandlers>();
+        handlers.insert_batched::<T>(state, id);
+
+        if !self.world().contains_resource::<PacketBatches<T>>() {
+            self.init_resource::<PacketBatches<T>>();
+            self.add_systems(
+                PreUpdate,
+                PacketBatches::<T>::trigger.in_set(ServerSets::EmitPacketBatches),
+            );
+        }
+
+        self
+    }
+
     fn add_outgoing_packet<T: McWrite + Send + Sync + 'static>(
         &mut self,
         state: ProtocolState,
@@ -70,6 +118,18 @@ impl AppNetworkExt for App {
 
         self
     }
+
+    fn add_packets(
+        &mut self,
+        state: ProtocolState,
+        packets: &[(i32, PacketRegistration)],
+    ) -> &mut Self {
+        for (id, packet) in packets {
+            packet.register(self, state, *id);
+        }
+
+        self
+    }
 }
 
 /// [`System`] supplier that tells the [`Server`](server::Server) to start listening for connections.
@@ -80,3 +140,88 @@ pub fn start_server(
         server.start(address.clone());
     }
 }
+
+/// [`System`] supplier that tells the [`Server`](server::Server) to start listening for connections
+/// on each of the given addresses, such as an IPv4 and an IPv6 address.
+pub fn start_server_all<A>(addresses: Vec<A>) -> impl FnMut(ResMut<Server>)
+where
+    A: ToSocketAddrs + Clone + fmt::Display + Send + 'static,
+{
+    move |mut server: ResMut<Server>| {
+        server.start_all(addresses.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::{
+        client::ProtocolState,
+        packet::{IncomingPacketHandlers, OutgoingPacketIds, PacketBatches, PacketRegistration},
+        AppNetworkExt,
+    };
+
+    #[test]
+    fn add_packets_registers_every_direction() {
+        let mut app = App::new();
+        app.add_packets(
+            ProtocolState::Play,
+            &[
+                (0x00, PacketRegistration::incoming::<u8>()),
+                (0x01, PacketRegistration::incoming_batched::<u16>()),
+                (0x00, PacketRegistration::outgoing::<u8>()),
+                (0x02, PacketRegistration::outgoing::<u32>()),
+            ],
+        );
+
+        let incoming = app.world().resource::<IncomingPacketHandlers>();
+        assert_eq!(incoming.ids(ProtocolState::Play), [0x00, 0x01]);
+        assert_eq!(incoming.name(ProtocolState::Play, 0x00), Some("u8"));
+        assert_eq!(incoming.name(ProtocolState::Play, 0x01), Some("u16"));
+        assert!(incoming.ids(ProtocolState::Config).is_empty());
+        assert!(app.world().contains_resource::<PacketBatches<u16>>());
+
+        let outgoing = app.world().resource::<OutgoingPacketIds>();
+        assert_eq!(outgoing.get::<u8>(ProtocolState::Play), Some(0x00));
+        assert_eq!(outgoing.get::<u32>(ProtocolState::Play), Some(0x02));
+        assert_eq!(outgoing.get::<u8>(ProtocolState::Config), None);
+    }
+
+    #[test]
+    fn add_packets_allows_registering_the_same_type_again() {
+        let mut app = App::new();
+        app.add_incoming_packet::<u8>(ProtocolState::Play, 0x00);
+        app.add_packets(
+            ProtocolState::Play,
+            &[(0x00, PacketRegistration::incoming::<u8>())],
+        );
+
+        let incoming = app.world().resource::<IncomingPacketHandlers>();
+        assert_eq!(incoming.ids(ProtocolState::Play), [0x00]);
+    }
+
+    #[test]
+    #[should_panic = "Incoming packet ID 0x01 in state Play is registered to both u8 and u16"]
+    fn add_packets_rejects_duplicate_incoming_ids() {
+        let mut app = App::new();
+        app.add_packets(
+            ProtocolState::Play,
+            &[
+                (0x01, PacketRegistration::incoming::<u8>()),
+                (0x01, PacketRegistration::incoming_batched::<u16>()),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic = "Outgoing packet ID 0x01 in state Config is registered to both u8 and u16"]
+    fn add_packets_rejects_duplicate_outgoing_ids() {
+        let mut app = App::new();
+        app.add_outgoing_packet::<u8>(ProtocolState::Config, 0x01);
+        app.add_packets(
+            ProtocolState::Config,
+            &[(0x01, PacketRegistration::outgoing::<u16>())],
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! An in-process transport for connecting a client to the [`Server`] without
+//! sockets, for driving packet flows in integration tests.
+
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use futures_util::{SinkExt, StreamExt};
+use minecrevy_io::{packet::codec::RawPacketCodec, packet::RawPacket, McRead, McWrite};
+use tokio::{io::DuplexStream, runtime::Handle};
+use tokio_util::codec::Framed;
+
+#[cfg(doc)]
+use crate::server::Server;
+
+/// The size of the in-memory buffer in each direction of a loopback connection.
+pub(crate) const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Returns a unique placeholder address for a loopback connection.
+///
+/// Loopback addresses are in `0.0.0.0/8` with port `0`, which real clients
+/// can't connect from, so they never collide with a TCP client's address.
+pub(crate) fn next_address() -> SocketAddr {
+    static NEXT: AtomicU32 = AtomicU32::new(1);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed) & 0x00FF_FFFF;
+    SocketAddr::new(Ipv4Addr::from(n).into(), 0)
+}
+
+/// The client end of an in-process connection to the [`Server`], created with
+/// [`Server::connect_loopback`].
+///
+/// Packets are sent and received in the same format as over TCP, but without
+/// any sockets. Each method blocks until its I/O completes, so it must not be
+/// called from within an async context. Receiving gives up after the client's
+/// timeout, so that a missing response fails a test instead of hanging it.
+pub struct LoopbackClient {
+    /// The address the server sees the client connected from.
+    addr: SocketAddr,
+    /// The framed client end of the connection.
+    stream: Framed<DuplexStream, RawPacketCodec>,
+    /// The [`Handle`] to the server's runtime, used to drive the I/O.
+    runtime: Handle,
+    /// How long to wait for a packet when receiving.
+    timeout: Duration,
+}
+
+impl LoopbackClient {
+    /// How long receiving waits for a packet by default.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Creates a new [`LoopbackClient`] for the given client end of a connection.
+    pub(crate) fn new(
+        addr: SocketAddr,
+        stream: Framed<DuplexStream, RawPacketCodec>,
+        runtime: Handle,
+    ) -> Self {
+        Self {
+            addr,
+            stream,
+            runtime,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Returns the address the server sees the client connected from.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Sets how long receiving waits for a packet, which is
+    /// [`LoopbackClient::DEFAULT_TIMEOUT`] by default.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sends the given packet to the server with the given packet ID.
+    pub fn send<T: McWrite>(&mut self, id: i32, packet: &T) -> io::Result<()> {
+        let mut body = Vec::new();
+        packet.write_default(&mut body)?;
+        self.send_raw(RawPacket { id, body })
+    }
+
+    /// Sends the given [`RawPacket`] to the server.
+    pub fn send_raw(&mut self, packet: RawPacket) -> io::Result<()> {
+        self.runtime.block_on(self.stream.send(packet))
+    }
+
+    /// Receives the next packet from the server, which must have the given
+    /// packet ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the packet has a different ID or fails to decode.
+    pub fn recv<T: McRead>(&mut self, id: i32) -> io::Result<T> {
+        let packet = self.recv_raw()?;
+        if packet.id != id {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Expected packet ID {id:#04x}, received {:#04x}", packet.id),
+            ));
+        }
+        T::read_default(packet.reader())
+    }
+
+    /// Receives the next [`RawPacket`] from the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server closed the connection, or an error of
+    /// kind [`io::ErrorKind::TimedOut`] if no packet arrived within the
+    /// client's timeout. Receiving can be retried after a timeout.
+    pub fn recv_raw(&mut self) -> io::Result<RawPacket> {
+        let timeout = self.timeout;
+        let next = self.stream.next();
+        match self
+            .runtime
+            .block_on(async { tokio::time::timeout(timeout, next).await })
+        {
+            Ok(Some(packet)) => packet,
+            Ok(None) => Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "Server closed the connection",
+            )),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("No packet received within {timeout:?}"),
+            )),
+        }
+    }
+
+    /// Enables compression for the connection, to match the server after it
+    /// sends Set Compression.
+    pub fn enable_compression(&mut self) {
+        self.stream.codec_mut().enable_compression();
+    }
+
+    /// Enables encryption for the connection with the given shared secret, to
+    /// match the server after the client sends Encryption Response.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        self.stream.codec_mut().enable_encryption(shared_secret);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::Duration};
+
+    use crate::server::Server;
+
+    #[test]
+    fn recv_times_out_without_packets() {
+        let server = Server::default();
+        let mut client = server.connect_loopback();
+        client.set_timeout(Duration::from_millis(10));
+
+        let error = client.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,210 @@
+//! This module contains [`PacketMetrics`], which records how long packets take
+//! to encode and decode, and how large they are.
+
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// [`Resource`] that accumulates [`PacketStats`] for each packet type, keyed
+/// by the packet's type name.
+///
+/// Stats are recorded through a shared reference, so that packets can be
+/// written while the resource is borrowed immutably.
+#[derive(Resource, Default)]
+pub struct PacketMetrics {
+    incoming: Mutex<HashMap<&'static str, PacketStats>>,
+    outgoing: Mutex<HashMap<&'static str, PacketStats>>,
+}
+
+impl PacketMetrics {
+    /// Returns a snapshot of the stats for decoded incoming packets.
+    pub fn incoming(&self) -> HashMap<&'static str, PacketStats> {
+        lock(&self.incoming).clone()
+    }
+
+    /// Returns a snapshot of the stats for encoded outgoing packets.
+    pub fn outgoing(&self) -> HashMap<&'static str, PacketStats> {
+        lock(&self.outgoing).clone()
+    }
+
+    /// Clears all recorded stats.
+    pub fn reset(&self) {
+        lock(&self.incoming).clear();
+        lock(&self.outgoing).clear();
+    }
+
+    /// Records the decoding of an incoming packet of type `T`.
+    pub fn record_incoming<T>(&self, bytes: usize, duration: Duration) {
+        lock(&self.incoming)
+            .entry(std::any::type_name::<T>())
+            .or_default()
+            .record(bytes, duration);
+    }
+
+    /// Records the encoding of an outgoing packet of type `T`.
+    pub fn record_outgoing<T>(&self, bytes: usize, duration: Duration) {
+        lock(&self.outgoing)
+            .entry(std::any::type_name::<T>())
+            .or_default()
+            .record(bytes, duration);
+    }
+}
+
+/// Locks the given stats, recovering them if a previous holder panicked.
+fn lock<'a>(
+    stats: &'a Mutex<HashMap<&'static str, PacketStats>>,
+) -> MutexGuard<'a, HashMap<&'static str, PacketStats>> {
+    stats.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The accumulated stats for a single packet type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct PacketStats {
+    /// The number of packets encoded or decoded.
+    pub count: u64,
+    /// The total size of the packet bodies, in bytes.
+    pub bytes: u64,
+    /// The total time spent encoding or decoding.
+    pub duration: Duration,
+}
+
+impl PacketStats {
+    /// Returns the average time spent encoding or decoding a single packet.
+    pub fn average_duration(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.duration / count,
+            Err(_) => Duration::from_secs_f64(self.duration.as_secs_f64() / self.count as f64),
+        }
+    }
+
+    /// Adds a single packet to the stats.
+    fn record(&mut self, bytes: usize, duration: Duration) {
+        self.count += 1;
+        self.bytes += bytes as u64;
+        self.duration += duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use super::{PacketMetrics, PacketStats};
+    use crate::{
+        client::{ClientAddressIndex, PacketWriter, ProtocolState},
+        server::{Server, ServerPlugin},
+        AppNetworkExt,
+    };
+
+    struct Ping;
+    struct Pong;
+
+    #[test]
+    fn record_accumulates_per_packet_type() {
+        let metrics = PacketMetrics::default();
+        metrics.record_incoming::<Ping>(10, Duration::from_micros(3));
+        metrics.record_incoming::<Ping>(30, Duration::from_micros(5));
+        metrics.record_incoming::<Pong>(1, Duration::from_micros(1));
+        metrics.record_outgoing::<Ping>(7, Duration::from_micros(2));
+
+        let incoming = metrics.incoming();
+        assert_eq!(
+            incoming[std::any::type_name::<Ping>()],
+            PacketStats {
+                count: 2,
+                bytes: 40,
+                duration: Duration::from_micros(8),
+            }
+        );
+        assert_eq!(incoming[std::any::type_name::<Pong>()].count, 1);
+
+        let outgoing = metrics.outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(
+            outgoing[std::any::type_name::<Ping>()],
+            PacketStats {
+                count: 1,
+                bytes: 7,
+                duration: Duration::from_micros(2),
+            }
+        );
+
+        metrics.reset();
+        assert!(metrics.incoming().is_empty());
+        assert!(metrics.outgoing().is_empty());
+    }
+
+    #[test]
+    fn average_duration() {
+        assert_eq!(PacketStats::default().average_duration(), Duration::ZERO);
+
+        let stats = PacketStats {
+            count: 4,
+            bytes: 0,
+            duration: Duration::from_micros(10),
+        };
+        assert_eq!(stats.average_duration(), Duration::from_nanos(2500));
+
+        // Counts too large for `Duration`'s division are averaged as floats.
+        let stats = PacketStats {
+            count: u64::from(u32::MAX) * 2,
+            bytes: 0,
+            duration: Duration::from_secs(u64::from(u32::MAX) * 2),
+        };
+        assert_eq!(stats.average_duration(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn server_records_packets_it_reads_and_writes() {
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.add_incoming_packet::<u16>(ProtocolState::Handshake, 0x00);
+        app.add_outgoing_packet::<u8>(ProtocolState::Handshake, 0x01);
+
+        let mut connection = app.world().resource::<Server>().connect_loopback();
+        app.update();
+        let client = app
+            .world()
+            .resource::<ClientAddressIndex>()
+            .entity(connection.addr())
+            .unwrap();
+
+        connection.send(0x00, &1u16).unwrap();
+        connection.send(0x00, &2u16).unwrap();
+        for _ in 0..500 {
+            app.update();
+            let metrics = app.world().resource::<PacketMetrics>();
+            if metrics
+                .incoming()
+                .get("u16")
+                .is_some_and(|stats| stats.count == 2)
+            {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        app.world_mut()
+            .run_system_once(move |mut writer: PacketWriter| {
+                writer.client(client).send(&7u8);
+                writer.broadcast(ProtocolState::Handshake, &8u8);
+            })
+            .unwrap();
+        assert_eq!(connection.recv_raw().unwrap().body, [7]);
+        assert_eq!(connection.recv_raw().unwrap().body, [8]);
+
+        let metrics = app.world().resource::<PacketMetrics>();
+        let incoming = metrics.incoming();
+        assert_eq!(incoming.len(), 1);
+        assert_eq!((incoming["u16"].count, incoming["u16"].bytes), (2, 4));
+        let outgoing = metrics.outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!((outgoing["u8"].count, outgoing["u8"].bytes), (2, 2));
+    }
+}
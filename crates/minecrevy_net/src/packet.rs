@@ -1,4 +1,10 @@
 //! Packet registration and event types.
+//!
+//! Packet directions are always from the server's point of view: incoming
+//! packets are serverbound and outgoing packets are clientbound. A test client
+//! or bot that reuses these registrations must swap the two, reading the
+//! server's outgoing packets and writing its incoming ones under the same IDs.
+//! [`PacketTable::flip`] does this for a snapshot of the registrations.
 
 use std::{
     any::TypeId,
@@ -8,7 +14,7 @@ use std::{
 use bevy::{prelude::*, utils::HashMap};
 use minecrevy_io::{packet::RawPacket, McRead, McWrite};
 
-use crate::client::ProtocolState;
+use crate::{client::ProtocolState, AppNetworkExt};
 
 /// [`Event`] emitted for each incoming packet.
 #[derive(Event)]
@@ -36,47 +42,643 @@ pub type PacketHandler = fn(&mut World, Entity, RawPacket);
 
 /// [`Resource`] that stores [`PacketHandler`]s for triggering [`Event`]s for incoming packets.
 #[derive(Resource, Default)]
-pub struct IncomingPacketHandlers(HashMap<(ProtocolState, i32), PacketHandler>);
+pub struct IncomingPacketHandlers(HashMap<(ProtocolState, i32), (PacketHandler, &'static str)>);
 
 impl IncomingPacketHandlers {
     /// Returns the [`PacketHandler`] for the given packet ID and
     /// [`ProtocolState`], if any.
     pub fn get(&self, state: ProtocolState, id: i32) -> Option<PacketHandler> {
-        self.0.get(&(state, id)).copied()
+        self.0.get(&(state, id)).map(|(handler, _)| *handler)
+    }
+
+    /// Returns the type name of the packet registered for the given packet ID
+    /// and [`ProtocolState`], if any.
+    pub fn name(&self, state: ProtocolState, id: i32) -> Option<&'static str> {
+        self.0.get(&(state, id)).map(|(_, name)| *name)
+    }
+
+    /// Returns the registered packet IDs for the given [`ProtocolState`], in
+    /// ascending order.
+    pub fn ids(&self, state: ProtocolState) -> Vec<i32> {
+        sorted_ids(self.0.keys(), state)
     }
 
     /// Inserts a [`PacketHandler`] for the given packet ID and
     /// [`ProtocolState`], which deserializes the [`RawPacket`] into the given
     /// type `T` and triggers a [`Recv<T>`] event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a different packet type is already registered with the same
+    /// ID and [`ProtocolState`].
     pub fn insert<T: McRead + Send + Sync + 'static>(&mut self, state: ProtocolState, id: i32) {
-        self.0.insert((state, id), |world, client, packet| {
-            let Ok(packet) = T::read_default(packet.reader()) else {
-                warn!(
-                    "Failed to read packet from client {client}: {:?}",
-                    std::any::type_name::<T>()
-                );
+        let name = std::any::type_name::<T>();
+        if let Some((_, existing)) = self.0.get(&(state, id)) {
+            assert!(
+                *existing == name,
+                "Incoming packet ID {id:#04x} in state {state:?} is registered to both {existing} and {name}",
+            );
+        }
+
+        let handler: PacketHandler = |world, client, packet| {
+            let Some(packet) = read_packet::<T>(world, client, packet) else {
                 return;
             };
 
             world.trigger_targets(Recv(packet), client);
-        });
+        };
+        self.0.insert((state, id), (handler, name));
+    }
+
+    /// Inserts a [`PacketHandler`] for the given packet ID and
+    /// [`ProtocolState`], which deserializes the [`RawPacket`] into the given
+    /// type `T` and adds it to the client's [`PacketBatches<T>`], to be
+    /// triggered later as a single [`RecvBatch<T>`] event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a different packet type is already registered with the same
+    /// ID and [`ProtocolState`].
+    pub fn insert_batched<T: McRead + Send + Sync + 'static>(
+        &mut self,
+        state: ProtocolState,
+        id: i32,
+    ) {
+        let name = std::any::type_name::<T>();
+        if let Some((_, existing)) = self.0.get(&(state, id)) {
+            assert!(
+                *existing == name,
+                "Incoming packet ID {id:#04x} in state {state:?} is registered to both {existing} and {name}",
+            );
+        }
+
+        let handler: PacketHandler = |world, client, packet| {
+            let Some(packet) = read_packet::<T>(world, client, packet) else {
+                return;
+            };
+
+            world
+                .get_resource_or_init::<PacketBatches<T>>()
+                .0
+                .entry(client)
+                .or_default()
+                .push(packet);
+        };
+        self.0.insert((state, id), (handler, name));
+    }
+}
+
+/// Deserializes the [`RawPacket`] into the given type `T`, logging a warning
+/// if it's malformed.
+fn read_packet<T: McRead + 'static>(world: &World, client: Entity, packet: RawPacket) -> Option<T> {
+    #[cfg(feature = "metrics")]
+    let (start, packet_len) = (std::time::Instant::now(), packet.body.len());
+    let packet = T::read_default(packet.reader());
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = world.get_resource::<crate::metrics::PacketMetrics>() {
+        metrics.record_incoming::<T>(packet_len, start.elapsed());
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = world;
+
+    match packet {
+        Ok(packet) => Some(packet),
+        Err(_) => {
+            warn!(
+                "Failed to read packet from client {client}: {:?}",
+                std::any::type_name::<T>()
+            );
+            None
+        }
+    }
+}
+
+/// [`Event`] emitted once per tick for each client with all the packets of
+/// type `T` it sent since the previous tick, in the order they were received.
+///
+/// Prefer this over [`Recv<T>`] for high-frequency packets such as movement,
+/// where handling each packet on its own costs more than the packet is worth
+/// and often only the latest one matters. Prefer [`Recv<T>`] for packets that
+/// change the client's [`ProtocolState`] or must be handled before the next
+/// packet is read, as batched packets are only delivered after every packet
+/// of the tick has been read.
+///
+/// Register batched packets with [`AppNetworkExt::add_batched_incoming_packet`].
+///
+/// [`AppNetworkExt::add_batched_incoming_packet`]: crate::AppNetworkExt::add_batched_incoming_packet
+#[derive(Event)]
+#[repr(transparent)]
+pub struct RecvBatch<T: McRead>(pub Vec<T>);
+
+impl<T: McRead> Deref for RecvBatch<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: McRead> DerefMut for RecvBatch<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// [`Resource`] that collects the incoming packets of type `T` for each client
+/// until they're triggered as a [`RecvBatch<T>`] event.
+#[derive(Resource)]
+pub struct PacketBatches<T: McRead>(HashMap<Entity, Vec<T>>);
+
+impl<T: McRead> Default for PacketBatches<T> {
+    fn default() -> Self {
+        Self(HashMap::default())
+    }
+}
+
+impl<T: McRead + Send + Sync + 'static> PacketBatches<T> {
+    /// [`System`] that triggers a [`RecvBatch<T>`] event for each client with
+    /// pending packets of type `T`.
+    pub fn trigger(world: &mut World) {
+        let batches = std::mem::take(&mut world.resource_mut::<Self>().0);
+        for (client, batch) in batches {
+            if world.get_entity(client).is_err() {
+                // The client disconnected since sending the packets.
+                continue;
+            }
+            world.trigger_targets(RecvBatch(batch), client);
+        }
+    }
+}
+
+/// A packet type to register with [`AppNetworkExt::add_packets`].
+///
+/// [`AppNetworkExt::add_packets`]: crate::AppNetworkExt::add_packets
+#[derive(Clone, Copy)]
+pub struct PacketRegistration {
+    register: fn(&mut App, ProtocolState, i32),
+}
+
+impl PacketRegistration {
+    /// Registers `T` as an incoming packet.
+    pub fn incoming<T: McRead + Send + Sync + 'static>() -> Self {
+        Self {
+            register: |app, state, id| {
+                app.world_mut()
+                    .get_resource_or_init::<IncomingPacketHandlers>()
+                    .insert::<T>(state, id);
+            },
+        }
+    }
+
+    /// Registers `T` as an incoming packet delivered in a [`RecvBatch<T>`].
+    pub fn incoming_batched<T: McRead + Send + Sync + 'static>() -> Self {
+        Self {
+            register: |app, state, id| {
+                app.add_batched_incoming_packet::<T>(state, id);
+            },
+        }
+    }
+
+    /// Registers `T` as an outgoing packet.
+    pub fn outgoing<T: McWrite + Send + Sync + 'static>() -> Self {
+        Self {
+            register: |app, state, id| {
+                app.world_mut()
+                    .get_resource_or_init::<OutgoingPacketIds>()
+                    .insert::<T>(state, id);
+            },
+        }
+    }
+
+    /// Registers the packet type with the given [`ProtocolState`] and packet ID.
+    pub fn register(&self, app: &mut App, state: ProtocolState, id: i32) {
+        (self.register)(app, state, id);
     }
 }
 
 /// [`Resource`] that stores the IDs for packets that are sent to the client,
 /// based on the packet type and [`ProtocolState`].
 #[derive(Resource, Default)]
-pub struct OutgoingPacketIds(HashMap<(ProtocolState, TypeId), i32>);
+pub struct OutgoingPacketIds {
+    ids: HashMap<(ProtocolState, TypeId), i32>,
+    names: HashMap<(ProtocolState, i32), &'static str>,
+}
 
 impl OutgoingPacketIds {
     /// Returns the ID of the given packet type `T` for the given
     /// [`ProtocolState`], if any.
     pub fn get<T: McWrite + 'static>(&self, state: ProtocolState) -> Option<i32> {
-        self.0.get(&(state, TypeId::of::<T>())).copied()
+        self.ids.get(&(state, TypeId::of::<T>())).copied()
+    }
+
+    /// Returns the type name of the packet registered for the given packet ID
+    /// and [`ProtocolState`], if any.
+    pub fn name(&self, state: ProtocolState, id: i32) -> Option<&'static str> {
+        self.names.get(&(state, id)).copied()
+    }
+
+    /// Returns the registered packet IDs for the given [`ProtocolState`], in
+    /// ascending order.
+    pub fn ids(&self, state: ProtocolState) -> Vec<i32> {
+        sorted_ids(self.names.keys(), state)
     }
 
     /// Inserts the packet ID for the given packet type `T` and [`ProtocolState`].
+    ///
+    /// If `T` was already registered with a different ID in the same state,
+    /// it's moved to the new ID, freeing the old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a different packet type is already registered with the same
+    /// ID and [`ProtocolState`].
     pub fn insert<T: McWrite + 'static>(&mut self, state: ProtocolState, id: i32) {
-        self.0.insert((state, TypeId::of::<T>()), id);
+        let name = std::any::type_name::<T>();
+        if let Some(existing) = self.names.get(&(state, id)) {
+            assert!(
+                *existing == name,
+                "Outgoing packet ID {id:#04x} in state {state:?} is registered to both {existing} and {name}",
+            );
+        }
+
+        if let Some(old) = self.ids.insert((state, TypeId::of::<T>()), id) {
+            self.names.remove(&(state, old));
+        }
+        self.names.insert((state, id), name);
+    }
+}
+
+/// [`Component`] that translates the packet IDs of a client whose protocol
+/// version numbers its packets differently from the IDs registered in
+/// [`IncomingPacketHandlers`] and [`OutgoingPacketIds`].
+///
+/// IDs without an entry are the same for the client as they're registered.
+/// Clients without this component use the registered IDs as-is.
+#[derive(Component)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PacketIdMap {
+    /// The registered ID for each of the client's incoming packet IDs, or
+    /// [`None`] if the client's ID isn't a registered packet.
+    incoming: HashMap<(ProtocolState, i32), Option<i32>>,
+    /// The client's ID for each registered outgoing packet ID, or [`None`] if
+    /// the client doesn't have the packet.
+    outgoing: HashMap<(ProtocolState, i32), Option<i32>>,
+}
+
+impl PacketIdMap {
+    /// Returns `true` if the map doesn't change any IDs.
+    pub fn is_empty(&self) -> bool {
+        self.incoming.is_empty() && self.outgoing.is_empty()
+    }
+
+    /// Returns the registered ID of the packet the client sent with the given
+    /// ID, or [`None`] if no registered packet has that ID for the client.
+    pub fn incoming(&self, state: ProtocolState, id: i32) -> Option<i32> {
+        self.incoming.get(&(state, id)).copied().unwrap_or(Some(id))
+    }
+
+    /// Returns the client's ID for the outgoing packet registered with the
+    /// given ID, or [`None`] if the client doesn't have the packet.
+    pub fn outgoing(&self, state: ProtocolState, id: i32) -> Option<i32> {
+        self.outgoing.get(&(state, id)).copied().unwrap_or(Some(id))
+    }
+
+    /// Maps the client's incoming packet ID to the given registered ID, or to
+    /// no packet if `registered` is [`None`].
+    pub fn insert_incoming(&mut self, state: ProtocolState, client: i32, registered: Option<i32>) {
+        self.incoming.insert((state, client), registered);
+    }
+
+    /// Maps the registered outgoing packet ID to the given client ID, or to no
+    /// packet if `client` is [`None`].
+    pub fn insert_outgoing(&mut self, state: ProtocolState, registered: i32, client: Option<i32>) {
+        self.outgoing.insert((state, registered), client);
+    }
+}
+
+/// Returns the packet IDs of the given keys that belong to `state`, in
+/// ascending order.
+fn sorted_ids<'a>(
+    keys: impl Iterator<Item = &'a (ProtocolState, i32)>,
+    state: ProtocolState,
+) -> Vec<i32> {
+    let mut ids: Vec<i32> = keys
+        .filter(|(s, _)| *s == state)
+        .map(|(_, id)| *id)
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// The direction of a packet, from the point of view of one side of the
+/// connection.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum PacketDirection {
+    /// Packets the side receives.
+    Incoming,
+    /// Packets the side sends.
+    Outgoing,
+}
+
+impl PacketDirection {
+    /// Returns the direction the same packets have from the other side of
+    /// the connection.
+    #[must_use]
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Incoming => Self::Outgoing,
+            Self::Outgoing => Self::Incoming,
+        }
+    }
+}
+
+/// A snapshot of the registered packet IDs and type names of each
+/// [`ProtocolState`] and [`PacketDirection`].
+///
+/// Build it from the server's registrations with [`PacketTable::from_world`],
+/// then [`PacketTable::flip`] it to get the table of a client talking to that
+/// server.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PacketTable(HashMap<(ProtocolState, PacketDirection, i32), &'static str>);
+
+impl PacketTable {
+    /// Returns the server's table of the packets registered in the
+    /// [`IncomingPacketHandlers`] and [`OutgoingPacketIds`] resources.
+    pub fn from_world(world: &World) -> Self {
+        let incoming = world.get_resource::<IncomingPacketHandlers>();
+        let outgoing = world.get_resource::<OutgoingPacketIds>();
+
+        let mut table = HashMap::default();
+        for state in ProtocolState::ALL {
+            if let Some(incoming) = incoming {
+                for id in incoming.ids(state) {
+                    let name = incoming.name(state, id).unwrap_or_default();
+                    table.insert((state, PacketDirection::Incoming, id), name);
+                }
+            }
+            if let Some(outgoing) = outgoing {
+                for id in outgoing.ids(state) {
+                    let name = outgoing.name(state, id).unwrap_or_default();
+                    table.insert((state, PacketDirection::Outgoing, id), name);
+                }
+            }
+        }
+        Self(table)
+    }
+
+    /// Returns the type name of the packet with the given ID, direction, and
+    /// [`ProtocolState`], if any.
+    pub fn get(
+        &self,
+        state: ProtocolState,
+        direction: PacketDirection,
+        id: i32,
+    ) -> Option<&'static str> {
+        self.0.get(&(state, direction, id)).copied()
+    }
+
+    /// Returns the packet IDs of the given direction and [`ProtocolState`], in
+    /// ascending order.
+    pub fn ids(&self, state: ProtocolState, direction: PacketDirection) -> Vec<i32> {
+        let mut ids: Vec<i32> = self
+            .0
+            .keys()
+            .filter(|(s, d, _)| *s == state && *d == direction)
+            .map(|(_, _, id)| *id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Returns the table from the other side of the connection, where every
+    /// incoming packet is outgoing and every outgoing packet is incoming,
+    /// under the same IDs.
+    ///
+    /// Flipping twice returns the original table.
+    #[must_use]
+    pub fn flip(&self) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|(&(state, direction, id), &name)| ((state, direction.flip(), id), name))
+                .collect(),
+        )
+    }
+}
+
+/// [`Plugin`] that logs every registered packet ID and type at startup, to
+/// help diagnose packets that aren't being handled.
+pub struct DebugPacketTablePlugin;
+
+impl Plugin for DebugPacketTablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, Self::log_packet_table);
+    }
+}
+
+impl DebugPacketTablePlugin {
+    /// [`System`] that logs the incoming and outgoing packet tables for each
+    /// [`ProtocolState`].
+    fn log_packet_table(world: &World) {
+        let table = PacketTable::from_world(world);
+        for state in ProtocolState::ALL {
+            for direction in [PacketDirection::Incoming, PacketDirection::Outgoing] {
+                let label = match direction {
+                    PacketDirection::Incoming => "incoming",
+                    PacketDirection::Outgoing => "outgoing",
+                };
+                for id in table.ids(state, direction) {
+                    let name = table.get(state, direction, id).unwrap_or_default();
+                    info!("{state:?} {label} {id:#04x}: {name}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use super::{
+        IncomingPacketHandlers, OutgoingPacketIds, PacketDirection, PacketTable, RecvBatch,
+    };
+    use crate::{
+        client::ProtocolState,
+        server::{Server, ServerPlugin},
+        AppNetworkExt,
+    };
+
+    /// [`Resource`] that collects every [`RecvBatch<u8>`] in order.
+    #[derive(Resource, Default)]
+    struct Batches(Vec<(Entity, Vec<u8>)>);
+
+    fn record_batch(trigger: Trigger<RecvBatch<u8>>, mut batches: ResMut<Batches>) {
+        let batch = trigger.event().0.clone();
+        batches.0.push((trigger.entity(), batch));
+    }
+
+    /// Waits until the server has read the given number of packets that
+    /// haven't been handled yet.
+    fn wait_for_incoming(app: &App, count: usize) {
+        let incoming = app.world().resource::<Server>().incoming();
+        for _ in 0..500 {
+            if incoming.len() >= count {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("timed out waiting for {count} incoming packets");
+    }
+
+    #[test]
+    fn packets_of_a_frame_arrive_as_one_batch() {
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.init_resource::<OutgoingPacketIds>();
+        app.init_resource::<Batches>();
+        app.add_batched_incoming_packet::<u8>(ProtocolState::Handshake, 0x00);
+        app.add_observer(record_batch);
+
+        let mut connection = app.world().resource::<Server>().connect_loopback();
+        app.update();
+
+        for byte in [3, 1, 2] {
+            connection.send(0x00, &byte).unwrap();
+        }
+        wait_for_incoming(&app, 3);
+        app.update();
+
+        connection.send(0x00, &4u8).unwrap();
+        wait_for_incoming(&app, 1);
+        app.update();
+
+        let batches = &app.world().resource::<Batches>().0;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].1, [3, 1, 2]);
+        assert_eq!(batches[1].1, [4]);
+        assert_eq!(batches[0].0, batches[1].0);
+    }
+
+    #[test]
+    fn incoming_ids_are_sorted_per_state() {
+        let mut handlers = IncomingPacketHandlers::default();
+        handlers.insert::<u8>(ProtocolState::Play, 0x10);
+        handlers.insert_batched::<u16>(ProtocolState::Play, 0x02);
+        handlers.insert::<u32>(ProtocolState::Config, 0x05);
+
+        assert_eq!(handlers.ids(ProtocolState::Play), [0x02, 0x10]);
+        assert_eq!(handlers.ids(ProtocolState::Config), [0x05]);
+        assert!(handlers.ids(ProtocolState::Login).is_empty());
+    }
+
+    #[test]
+    fn incoming_name_is_the_registered_type() {
+        let mut handlers = IncomingPacketHandlers::default();
+        handlers.insert::<u8>(ProtocolState::Play, 0x10);
+        handlers.insert_batched::<u16>(ProtocolState::Play, 0x02);
+
+        assert_eq!(handlers.name(ProtocolState::Play, 0x10), Some("u8"));
+        assert_eq!(handlers.name(ProtocolState::Play, 0x02), Some("u16"));
+        assert_eq!(handlers.name(ProtocolState::Play, 0x03), None);
+        assert_eq!(handlers.name(ProtocolState::Config, 0x10), None);
+    }
+
+    #[test]
+    fn reregistering_frees_old_id() {
+        let mut ids = OutgoingPacketIds::default();
+        ids.insert::<u8>(ProtocolState::Play, 0x01);
+        ids.insert::<u8>(ProtocolState::Play, 0x02);
+        ids.insert::<u16>(ProtocolState::Play, 0x01);
+
+        assert_eq!(ids.get::<u8>(ProtocolState::Play), Some(0x02));
+        assert_eq!(ids.get::<u16>(ProtocolState::Play), Some(0x01));
+        assert_eq!(ids.ids(ProtocolState::Play), [0x01, 0x02]);
+    }
+
+    #[test]
+    fn same_id_in_different_states_is_allowed() {
+        let mut ids = OutgoingPacketIds::default();
+        ids.insert::<u8>(ProtocolState::Config, 0x01);
+        ids.insert::<u16>(ProtocolState::Play, 0x01);
+
+        assert_eq!(ids.get::<u8>(ProtocolState::Config), Some(0x01));
+        assert_eq!(ids.get::<u16>(ProtocolState::Play), Some(0x01));
+    }
+
+    #[test]
+    #[should_panic = "is registered to both"]
+    fn duplicate_id_panics() {
+        let mut ids = OutgoingPacketIds::default();
+        ids.insert::<u8>(ProtocolState::Play, 0x01);
+        ids.insert::<u16>(ProtocolState::Play, 0x01);
+    }
+
+    #[test]
+    fn direction_flip_round_trips() {
+        for direction in [PacketDirection::Incoming, PacketDirection::Outgoing] {
+            assert_ne!(direction.flip(), direction);
+            assert_eq!(direction.flip().flip(), direction);
+        }
+    }
+
+    /// Returns an [`App`] with a few incoming and outgoing packets registered,
+    /// including IDs shared between directions and states.
+    fn registered_app() -> App {
+        let mut app = App::new();
+        app.add_incoming_packet::<u8>(ProtocolState::Handshake, 0x00);
+        app.add_incoming_packet::<u16>(ProtocolState::Play, 0x00);
+        app.add_incoming_packet::<i32>(ProtocolState::Play, 0x12);
+        app.add_outgoing_packet::<u32>(ProtocolState::Status, 0x00);
+        app.add_outgoing_packet::<u64>(ProtocolState::Play, 0x00);
+        app.add_outgoing_packet::<i64>(ProtocolState::Play, 0x27);
+        app
+    }
+
+    #[test]
+    fn table_flip_round_trips() {
+        let table = PacketTable::from_world(registered_app().world());
+
+        assert_ne!(table.flip(), table);
+        assert_eq!(table.flip().flip(), table);
+    }
+
+    #[test]
+    fn flipped_table_mirrors_registrations() {
+        let mut app = registered_app();
+        // Moving a packet to a new ID keeps the tables mirrored.
+        app.add_outgoing_packet::<i64>(ProtocolState::Play, 0x28);
+
+        let server = PacketTable::from_world(app.world());
+        let client = server.flip();
+
+        for state in ProtocolState::ALL {
+            for direction in [PacketDirection::Incoming, PacketDirection::Outgoing] {
+                let ids = server.ids(state, direction);
+                assert_eq!(client.ids(state, direction.flip()), ids);
+                for id in ids {
+                    assert_eq!(
+                        client.get(state, direction.flip(), id),
+                        server.get(state, direction, id),
+                    );
+                }
+            }
+        }
+
+        let name = std::any::type_name::<i64>();
+        assert_eq!(
+            client.get(ProtocolState::Play, PacketDirection::Incoming, 0x28),
+            Some(name)
+        );
+        assert_eq!(
+            client.get(ProtocolState::Play, PacketDirection::Incoming, 0x27),
+            None
+        );
+        assert_eq!(
+            client.ids(ProtocolState::Play, PacketDirection::Outgoing),
+            [0x00, 0x12]
+        );
     }
 }
@@ -0,0 +1,457 @@
+//! Support for the [PROXY protocol][1], which TCP proxies and load balancers
+//! use to pass on the real address of the clients they forward.
+//!
+//! [1]: https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+/// The prefix of a version 1 (text) header.
+const V1_PREFIX: &[u8] = b"PROXY ";
+/// The maximum length of a version 1 header, including the trailing CRLF.
+const V1_MAX_LEN: usize = 107;
+/// The signature that starts a version 2 (binary) header.
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+/// The length of a version 2 header before its addresses.
+const V2_HEADER_LEN: usize = 16;
+
+/// Whether the [`Server`](crate::server::Server) expects connections to start
+/// with a PROXY protocol header, as sent by proxies such as HAProxy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum ProxyProtocol {
+    /// Connections are read as-is, and clients are identified by the address
+    /// they connected from.
+    #[default]
+    Disabled,
+    /// Connections may start with a header, which replaces the address the
+    /// client connected from.
+    ///
+    /// Anyone who can reach the server directly can forge their address in
+    /// this mode, so prefer [`ProxyProtocol::Required`] where possible.
+    Optional,
+    /// Connections must start with a header, and are closed if they don't.
+    Required,
+}
+
+/// The result of parsing the start of a connection.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Parsed {
+    /// More bytes are needed to tell whether there's a header.
+    Incomplete,
+    /// The connection doesn't start with a header.
+    NoHeader,
+    /// The connection starts with a header of the given length, which carries
+    /// the client's address unless the proxy connected on its own behalf.
+    Header {
+        source: Option<SocketAddr>,
+        len: usize,
+    },
+}
+
+/// Reads the PROXY protocol header from the start of a connection, according
+/// to the given mode.
+///
+/// Returns the address of the client, which is `peer` if there's no header,
+/// and the stream to read the rest of the connection from.
+///
+/// # Errors
+///
+/// Returns an error if the header is malformed, if it's missing in
+/// [`ProxyProtocol::Required`] mode, or if it isn't received within `timeout`.
+pub(crate) async fn accept<S: AsyncRead + Unpin>(
+    mut stream: S,
+    peer: SocketAddr,
+    mode: ProxyProtocol,
+    timeout: Duration,
+) -> io::Result<(SocketAddr, PrefixedStream<S>)> {
+    if mode == ProxyProtocol::Disabled {
+        return Ok((peer, PrefixedStream::new(Vec::new(), stream)));
+    }
+
+    let read = async {
+        let mut buf = Vec::new();
+        let mut chunk = [0; 512];
+        loop {
+            match parse(&buf)? {
+                Parsed::Incomplete => {}
+                parsed => return Ok((parsed, buf)),
+            }
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    };
+    let (parsed, mut buf) = tokio::time::timeout(timeout, read).await.map_err(|_| {
+        io::Error::new(io::ErrorKind::TimedOut, "PROXY protocol header timed out")
+    })??;
+
+    match parsed {
+        Parsed::Header { source, len } => {
+            buf.drain(..len);
+            Ok((source.unwrap_or(peer), PrefixedStream::new(buf, stream)))
+        }
+        Parsed::NoHeader if mode == ProxyProtocol::Optional => {
+            Ok((peer, PrefixedStream::new(buf, stream)))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing PROXY protocol header",
+        )),
+    }
+}
+
+/// Parses a PROXY protocol header from the start of the given bytes.
+fn parse(buf: &[u8]) -> io::Result<Parsed> {
+    if buf.starts_with(V1_PREFIX) {
+        parse_v1(buf)
+    } else if buf.starts_with(V2_SIGNATURE) {
+        parse_v2(buf)
+    } else if V1_PREFIX.starts_with(buf) || V2_SIGNATURE.starts_with(buf) {
+        Ok(Parsed::Incomplete)
+    } else {
+        Ok(Parsed::NoHeader)
+    }
+}
+
+/// Parses a version 1 header, such as `PROXY TCP4 1.2.3.4 5.6.7.8 1234 25565\r\n`.
+fn parse_v1(buf: &[u8]) -> io::Result<Parsed> {
+    let Some(end) = buf[..buf.len().min(V1_MAX_LEN)]
+        .windows(2)
+        .position(|w| w == b"\r\n")
+    else {
+        if buf.len() >= V1_MAX_LEN {
+            return Err(invalid("PROXY protocol v1 header is too long"));
+        }
+        return Ok(Parsed::Incomplete);
+    };
+
+    let line = std::str::from_utf8(&buf[..end])
+        .map_err(|_| invalid("PROXY protocol v1 header isn't valid ASCII"))?;
+    let fields = line.split(' ').collect::<Vec<_>>();
+    let source = match fields[..] {
+        ["PROXY", "UNKNOWN", ..] => None,
+        ["PROXY", "TCP4" | "TCP6", src, _dst, src_port, _dst_port] => {
+            let ip = src
+                .parse::<IpAddr>()
+                .map_err(|_| invalid("invalid PROXY protocol v1 source address"))?;
+            let port = src_port
+                .parse::<u16>()
+                .map_err(|_| invalid("invalid PROXY protocol v1 source port"))?;
+            Some(SocketAddr::new(ip, port))
+        }
+        _ => return Err(invalid("malformed PROXY protocol v1 header")),
+    };
+
+    Ok(Parsed::Header {
+        source,
+        len: end + 2,
+    })
+}
+
+/// Parses a version 2 header, which is binary.
+fn parse_v2(buf: &[u8]) -> io::Result<Parsed> {
+    if buf.len() < V2_HEADER_LEN {
+        return Ok(Parsed::Incomplete);
+    }
+
+    let version = buf[12] >> 4;
+    let command = buf[12] & 0x0F;
+    let family = buf[13] >> 4;
+    let addr_len = usize::from(u16::from_be_bytes([buf[14], buf[15]]));
+    if version != 2 {
+        return Err(invalid("unsupported PROXY protocol version"));
+    }
+
+    let len = V2_HEADER_LEN + addr_len;
+    let Some(addrs) = buf.get(V2_HEADER_LEN..len) else {
+        return Ok(Parsed::Incomplete);
+    };
+
+    let source = match (command, family) {
+        // LOCAL: the proxy connected on its own behalf, such as for a health check.
+        (0x0, _) => None,
+        // PROXY over IPv4
+        (0x1, 0x1) if addrs.len() >= 12 => {
+            let ip = Ipv4Addr::from(<[u8; 4]>::try_from(&addrs[0..4]).unwrap());
+            let port = u16::from_be_bytes([addrs[8], addrs[9]]);
+            Some(SocketAddr::new(ip.into(), port))
+        }
+        // PROXY over IPv6
+        (0x1, 0x2) if addrs.len() >= 36 => {
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(&addrs[0..16]).unwrap());
+            let port = u16::from_be_bytes([addrs[32], addrs[33]]);
+            Some(SocketAddr::new(ip.into(), port))
+        }
+        (0x1, 0x1 | 0x2) => return Err(invalid("truncated PROXY protocol v2 addresses")),
+        // PROXY over an unspecified or non-IP family, which carries no usable address.
+        (0x1, _) => None,
+        _ => return Err(invalid("unsupported PROXY protocol v2 command")),
+    };
+
+    Ok(Parsed::Header { source, len })
+}
+
+/// Returns an [`io::ErrorKind::InvalidData`] error with the given message.
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// A stream that replays the bytes read past the PROXY protocol header
+/// before reading from the underlying stream.
+pub(crate) struct PrefixedStream<S> {
+    /// The bytes read ahead from the underlying stream.
+    prefix: Vec<u8>,
+    /// The number of bytes of the prefix that have been replayed.
+    pos: usize,
+    /// The underlying stream.
+    inner: S,
+}
+
+impl<S> PrefixedStream<S> {
+    /// Creates a new [`PrefixedStream`] that replays the given bytes first.
+    fn new(prefix: Vec<u8>, inner: S) -> Self {
+        Self {
+            prefix,
+            pos: 0,
+            inner,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for PrefixedStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.pos < this.prefix.len() {
+            let rest = &this.prefix[this.pos..];
+            let n = rest.len().min(buf.remaining());
+            buf.put_slice(&rest[..n]);
+            this.pos += n;
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for PrefixedStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+        time::Duration,
+    };
+
+    use tokio::io::AsyncReadExt;
+
+    use super::{accept, parse, Parsed, ProxyProtocol, V2_SIGNATURE};
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn peer() -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 40000)
+    }
+
+    /// Returns a version 2 header with the given command and family, followed
+    /// by the given addresses.
+    fn v2_header(command: u8, family: u8, addrs: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.push(0x20 | command);
+        header.push((family << 4) | 0x1);
+        header.extend_from_slice(&(addrs.len() as u16).to_be_bytes());
+        header.extend_from_slice(addrs);
+        header
+    }
+
+    #[test]
+    fn v1_tcp4_header() {
+        let header = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 25565\r\n";
+        let mut buf = header.to_vec();
+        buf.extend_from_slice(b"\x10\x00");
+
+        assert_eq!(
+            parse(&buf).unwrap(),
+            Parsed::Header {
+                source: Some(SocketAddr::new(Ipv4Addr::new(192, 168, 0, 1).into(), 56324)),
+                len: header.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn v1_tcp6_header() {
+        let header = b"PROXY TCP6 ::1 ::2 56324 25565\r\n";
+
+        assert_eq!(
+            parse(header).unwrap(),
+            Parsed::Header {
+                source: Some(SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 56324)),
+                len: header.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn v1_unknown_header_has_no_source() {
+        let header = b"PROXY UNKNOWN\r\n";
+
+        assert_eq!(
+            parse(header).unwrap(),
+            Parsed::Header {
+                source: None,
+                len: header.len(),
+            }
+        );
+    }
+
+    #[test]
+    fn v1_partial_header_is_incomplete() {
+        assert_eq!(parse(b"PRO").unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(b"PROXY TCP4 1.2.3.4").unwrap(), Parsed::Incomplete);
+    }
+
+    #[test]
+    fn v1_malformed_header_is_rejected() {
+        let error = parse(b"PROXY TCP4 1.2.3.4\r\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let error = parse(b"PROXY TCP4 1.2.3.4 5.6.7.8 99999 25565\r\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn v2_ipv4_header() {
+        let addrs = [192, 168, 0, 1, 192, 168, 0, 11, 0xDC, 0x04, 0x63, 0xDD];
+        let header = v2_header(0x1, 0x1, &addrs);
+
+        assert_eq!(
+            parse(&header).unwrap(),
+            Parsed::Header {
+                source: Some(SocketAddr::new(Ipv4Addr::new(192, 168, 0, 1).into(), 56324)),
+                len: 28,
+            }
+        );
+    }
+
+    #[test]
+    fn v2_ipv6_header() {
+        let mut addrs = Vec::new();
+        addrs.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        addrs.extend_from_slice(&Ipv6Addr::UNSPECIFIED.octets());
+        addrs.extend_from_slice(&[0xDC, 0x04, 0x63, 0xDD]);
+        let header = v2_header(0x1, 0x2, &addrs);
+
+        assert_eq!(
+            parse(&header).unwrap(),
+            Parsed::Header {
+                source: Some(SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 56324)),
+                len: 52,
+            }
+        );
+    }
+
+    #[test]
+    fn v2_local_header_has_no_source() {
+        let header = v2_header(0x0, 0x0, &[]);
+
+        assert_eq!(
+            parse(&header).unwrap(),
+            Parsed::Header {
+                source: None,
+                len: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn v2_partial_header_is_incomplete() {
+        let header = v2_header(0x1, 0x1, &[192, 168, 0, 1, 192, 168, 0, 11, 0, 0, 0, 0]);
+
+        assert_eq!(parse(&header[..10]).unwrap(), Parsed::Incomplete);
+        assert_eq!(parse(&header[..20]).unwrap(), Parsed::Incomplete);
+    }
+
+    #[test]
+    fn v2_truncated_addresses_are_rejected() {
+        let header = v2_header(0x1, 0x1, &[192, 168, 0, 1]);
+
+        let error = parse(&header).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn handshake_has_no_header() {
+        assert_eq!(parse(b"\x10\x00\xFF\x05").unwrap(), Parsed::NoHeader);
+    }
+
+    #[tokio::test]
+    async fn header_replaces_the_peer_address() {
+        let input = b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 25565\r\n\x10\x00";
+
+        let (addr, mut stream) = accept(&input[..], peer(), ProxyProtocol::Required, TIMEOUT)
+            .await
+            .unwrap();
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await.unwrap();
+
+        assert_eq!(
+            addr,
+            SocketAddr::new(Ipv4Addr::new(192, 168, 0, 1).into(), 56324)
+        );
+        assert_eq!(rest, b"\x10\x00");
+    }
+
+    #[tokio::test]
+    async fn optional_mode_without_header_keeps_the_peer_address() {
+        let input = b"\x10\x00\xFF\x05";
+
+        let (addr, mut stream) = accept(&input[..], peer(), ProxyProtocol::Optional, TIMEOUT)
+            .await
+            .unwrap();
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await.unwrap();
+
+        assert_eq!(addr, peer());
+        assert_eq!(rest, input);
+    }
+
+    #[tokio::test]
+    async fn required_mode_with_missing_header_is_rejected() {
+        let input = b"\x10\x00\xFF\x05";
+
+        let Err(error) = accept(&input[..], peer(), ProxyProtocol::Required, TIMEOUT).await else {
+            panic!("connection without a header was accepted");
+        };
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,6 +1,16 @@
 //! This module contains the [`ServerPlugin`], which handles server-side communication.
 
-use std::{fmt, io, net::SocketAddr, sync::Arc};
+use std::{
+    fmt,
+    future::Future,
+    io,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use bevy::prelude::*;
 use flume::{Receiver, Sender};
@@ -10,16 +20,20 @@ use minecrevy_io::packet::{
     RawPacket,
 };
 use tokio::{
-    net::{TcpListener, TcpStream, ToSocketAddrs},
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, ToSocketAddrs},
     runtime::Runtime,
     sync::{mpsc::UnboundedReceiver, oneshot},
-    task::JoinHandle,
+    task::{JoinHandle, JoinSet},
 };
 use tokio_util::codec::Framed;
 
 use crate::{
-    client::{Client, ClientAddressIndex, ProtocolState, WriteOp},
-    packet::IncomingPacketHandlers,
+    client::{Client, ClientAddressIndex, ConnectionId, ProtocolState, StateChanged, WriteOp},
+    loopback::{self, LoopbackClient},
+    packet::{IncomingPacketHandlers, PacketIdMap},
+    proxy::{self, ProxyProtocol},
+    stats::NetworkStats,
 };
 
 /// [`SystemSet`]s for the [`NetServerPlugin`].
@@ -32,6 +46,10 @@ pub enum ServerSets {
     /// The set of systems that read incoming packets from clients and trigger
     /// them as observer events.
     EmitPacketEvents,
+    /// The set of systems that trigger the incoming packets collected by
+    /// [`ServerSets::EmitPacketEvents`] as
+    /// [`RecvBatch`](crate::packet::RecvBatch) observer events.
+    EmitPacketBatches,
     /// The set of systems that despawn [`Client`]s that have errored.
     DespawnClients,
 }
@@ -44,10 +62,19 @@ impl Plugin for ServerPlugin {
         // Not listening by default.
         app.init_resource::<Server>();
         app.init_resource::<ClientAddressIndex>();
+        app.init_resource::<UnknownPacketPolicy>();
+        app.init_resource::<StateTimeout>();
+        #[cfg(feature = "metrics")]
+        app.init_resource::<crate::metrics::PacketMetrics>();
 
         app.configure_sets(
             PreUpdate,
-            (ServerSets::SpawnClients, ServerSets::EmitPacketEvents).chain(),
+            (
+                ServerSets::SpawnClients,
+                ServerSets::EmitPacketEvents,
+                ServerSets::EmitPacketBatches,
+            )
+                .chain(),
         );
 
         // ServerSets::SpawnClients
@@ -62,10 +89,13 @@ impl Plugin for ServerPlugin {
             Self::trigger_incoming_packets.in_set(ServerSets::EmitPacketEvents),
         );
 
+        app.add_observer(Self::on_state_changed);
+
         // ServerSets::DespawnClients
         app.add_systems(
             PostUpdate,
-            Self::despawn_errored_clients.in_set(ServerSets::DespawnClients),
+            (Self::despawn_errored_clients, Self::despawn_stalled_clients)
+                .in_set(ServerSets::DespawnClients),
         );
     }
 }
@@ -74,7 +104,8 @@ impl ServerPlugin {
     /// [`System`] that spawns new [`Client`]s as entities.
     fn spawn_clients(mut commands: Commands, server: Res<Server>) {
         for client in server.iter_new_clients() {
-            commands.spawn(client);
+            let stats = client.stats.clone();
+            commands.spawn((client, stats));
         }
     }
 
@@ -84,9 +115,9 @@ impl ServerPlugin {
         index: Res<ClientAddressIndex>,
         mut commands: Commands,
     ) {
-        for (addr, packet) in server.iter_incoming() {
-            let Some(client_entity) = index.entity(addr) else {
-                warn!("No client entity for {addr}");
+        for (id, packet) in server.iter_incoming() {
+            let Some(client_entity) = index.entity_by_id(id) else {
+                warn!("No client entity for connection {id:?}");
                 continue;
             };
 
@@ -97,11 +128,14 @@ impl ServerPlugin {
                     // The client may have disconnected.
                     return;
                 };
-                let Some(func) = world
-                    .resource::<IncomingPacketHandlers>()
-                    .get(state, packet.id)
+                let id = match world.get::<PacketIdMap>(client_entity) {
+                    Some(map) => map.incoming(state, packet.id),
+                    None => Some(packet.id),
+                };
+                let Some(func) =
+                    id.and_then(|id| world.resource::<IncomingPacketHandlers>().get(state, id))
                 else {
-                    warn!("No handler for packet {} in state {state:?}", packet.id);
+                    Self::on_unknown_packet(world, client_entity, state, packet.id);
                     return;
                 };
 
@@ -110,6 +144,85 @@ impl ServerPlugin {
         }
     }
 
+    /// Counts an incoming packet with no registered handler against the client,
+    /// logging it at most once per [`UnknownPacketPolicy::log_interval`], and
+    /// disconnecting the client once it reaches the policy's limit.
+    fn on_unknown_packet(world: &mut World, client: Entity, state: ProtocolState, id: i32) {
+        let policy = *world.resource::<UnknownPacketPolicy>();
+        let now = Instant::now();
+
+        if world.get::<UnknownPackets>(client).is_none() {
+            world.entity_mut(client).insert(UnknownPackets::default());
+        }
+        let mut unknown = world.get_mut::<UnknownPackets>(client).unwrap();
+        unknown.count += 1;
+        unknown.unlogged += 1;
+
+        let should_log = unknown
+            .last_logged
+            .is_none_or(|last| now.duration_since(last) >= policy.log_interval);
+        if should_log {
+            warn!(
+                "No handler for packet {id:#04x} in state {state:?} from client {client} \
+                 ({} unknown since last logged, {} total)",
+                unknown.unlogged, unknown.count,
+            );
+            unknown.last_logged = Some(now);
+            unknown.unlogged = 0;
+        }
+
+        if policy
+            .disconnect_after
+            .is_some_and(|limit| unknown.count >= limit)
+        {
+            warn!(
+                "Disconnecting client {client} after {} unknown packets",
+                unknown.count
+            );
+            Self::disconnect(world, client);
+        }
+    }
+
+    /// Closes the connection of the given [`Client`] and despawns it, which
+    /// is how every client the server rejects is disconnected.
+    fn disconnect(world: &mut World, client: Entity) {
+        if let Some(connection) = world.get::<Client>(client) {
+            connection.disconnect();
+        }
+        world.despawn(client);
+    }
+
+    /// [`Observer`] [`System`] that resets a [`Client`]'s [`StateEnteredAt`]
+    /// when its [`ProtocolState`] changes.
+    fn on_state_changed(trigger: Trigger<StateChanged>, mut clients: Query<&mut StateEnteredAt>) {
+        if let Ok(mut entered) = clients.get_mut(trigger.entity()) {
+            *entered = StateEnteredAt::default();
+        }
+    }
+
+    /// [`System`] that despawns [`Client`]s that have stayed in a
+    /// [`ProtocolState`] other than [`ProtocolState::Play`] for longer than
+    /// the [`StateTimeout`].
+    fn despawn_stalled_clients(
+        mut commands: Commands,
+        timeout: Res<StateTimeout>,
+        clients: Query<(Entity, &Client, &ProtocolState, &StateEnteredAt)>,
+    ) {
+        let Some(timeout) = timeout.timeout else {
+            return;
+        };
+
+        for (entity, client, &state, entered) in &clients {
+            if state != ProtocolState::Play && entered.elapsed() >= timeout {
+                warn!(
+                    "Disconnecting client {addr} after {timeout:?} in state {state:?}",
+                    addr = client.addr(),
+                );
+                commands.queue(move |world: &mut World| Self::disconnect(world, entity));
+            }
+        }
+    }
+
     /// [`System`] that despawns [`Client`]s that have errored.
     fn despawn_errored_clients(mut commands: Commands, mut clients: Query<(Entity, &mut Client)>) {
         for (entity, mut client) in clients.iter_mut() {
@@ -119,60 +232,272 @@ impl ServerPlugin {
                     addr = client.addr(),
                     error = error
                 );
-                commands.entity(entity).despawn();
+                commands.queue(move |world: &mut World| Self::disconnect(world, entity));
             }
         }
     }
 }
 
+/// [`Resource`] that configures how long a [`Client`] may stay in a
+/// [`ProtocolState`] other than [`ProtocolState::Play`], where keep-alives
+/// don't detect clients that stop progressing.
+#[derive(Resource)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct StateTimeout {
+    /// The longest time a client may stay in the same state before reaching
+    /// [`ProtocolState::Play`], or [`None`] to never disconnect clients for it.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for StateTimeout {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// [`Component`] that tracks when a [`Client`] entered its current
+/// [`ProtocolState`], for enforcing the [`StateTimeout`].
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct StateEnteredAt {
+    /// When the client entered its current state.
+    since: Instant,
+}
+
+impl Default for StateEnteredAt {
+    fn default() -> Self {
+        Self {
+            since: Instant::now(),
+        }
+    }
+}
+
+impl StateEnteredAt {
+    /// Returns how long the client has been in its current state.
+    pub fn elapsed(&self) -> Duration {
+        self.since.elapsed()
+    }
+}
+
+/// [`Resource`] that configures how incoming packets with no registered
+/// handler are treated, which may come from clients on the wrong protocol
+/// version or malicious clients.
+#[derive(Resource)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct UnknownPacketPolicy {
+    /// The minimum time between logging unknown packets from the same client.
+    pub log_interval: Duration,
+    /// The number of unknown packets after which a client is disconnected, or
+    /// [`None`] to never disconnect clients for them.
+    pub disconnect_after: Option<u32>,
+}
+
+impl Default for UnknownPacketPolicy {
+    fn default() -> Self {
+        Self {
+            log_interval: Duration::from_secs(10),
+            disconnect_after: None,
+        }
+    }
+}
+
+/// [`Component`] that counts the incoming packets with no registered handler
+/// that a client has sent.
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct UnknownPackets {
+    /// The total number of unknown packets.
+    pub count: u32,
+    /// The number of unknown packets since the last one was logged.
+    unlogged: u32,
+    /// When an unknown packet was last logged.
+    last_logged: Option<Instant>,
+}
+
+/// How the [`Server`] handles some of its addresses failing to bind when
+/// listening on more than one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum BindPolicy {
+    /// Log the addresses that failed to bind, and keep listening on the rest.
+    #[default]
+    SkipFailed,
+    /// Log the addresses that failed to bind, and don't listen on any of them.
+    RequireAll,
+}
+
 /// [`Resource`] for the network server.
 #[derive(Resource)]
 pub struct Server {
     /// The [`Runtime`] used to spawn the server and handle clients.
     runtime: Runtime,
-    /// The [`JoinHandle`] for the TCP network listener.
+    /// The [`JoinHandle`] for the task that owns the TCP network listeners.
     listener: Option<JoinHandle<()>>,
+    /// The addresses that the TCP network listeners are bound to.
+    local_addrs: Arc<Mutex<Vec<SocketAddr>>>,
+    /// The number of open TCP connections.
+    connections: Arc<AtomicUsize>,
+    /// The [`Sender`] for new clients.
+    new_clients_tx: Sender<Client>,
     /// The [`Receiver`] for new clients.
     new_clients: Receiver<Client>,
-    // The [`Sender`] for incoming packets.
-    incoming_tx: Sender<(SocketAddr, RawPacket)>,
-    // The [`Receiver`] for incoming packets.
-    incoming_rx: Receiver<(SocketAddr, RawPacket)>,
+    // The [`Sender`] for incoming packets, paired with their connection.
+    incoming_tx: Sender<(ConnectionId, RawPacket)>,
+    // The [`Receiver`] for incoming packets, paired with their connection.
+    incoming_rx: Receiver<(ConnectionId, RawPacket)>,
     /// The codec settings used for the server.
     pub codec: Arc<PacketCodecSettings>,
+    /// How to handle addresses that fail to bind.
+    pub bind_policy: BindPolicy,
+    /// Whether connections start with a PROXY protocol header, which should
+    /// only be enabled when the server is behind a proxy that sends one.
+    pub proxy_protocol: ProxyProtocol,
+    /// The most TCP connections to keep open at once, or [`None`] for no
+    /// limit. Connections beyond the limit are closed as soon as they're
+    /// accepted, before any packets are read.
+    pub max_connections: Option<usize>,
 }
 
 impl Default for Server {
     fn default() -> Self {
         let (incoming_tx, incoming_rx) = flume::unbounded();
+        let (new_clients_tx, new_clients) = flume::unbounded();
         // Off by default.
         Self {
             runtime: Runtime::new().unwrap(),
             listener: None,
-            new_clients: flume::unbounded().1,
+            local_addrs: Arc::default(),
+            connections: Arc::default(),
+            new_clients_tx,
+            new_clients,
             incoming_tx,
             incoming_rx,
             codec: Arc::new(PacketCodecSettings::default()),
+            bind_policy: BindPolicy::default(),
+            proxy_protocol: ProxyProtocol::default(),
+            max_connections: None,
         }
     }
 }
 
 impl Server {
+    /// How long a listener waits before accepting again after failing to
+    /// accept a connection.
+    const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
     /// Starts the server on the given address.
     pub fn start(&mut self, address: impl ToSocketAddrs + fmt::Display + Send + 'static) {
-        self.stop();
+        self.start_all([address]);
+    }
 
-        info!("Starting network server on {}", address);
+    /// Starts the server on each of the given addresses, such as an IPv4 and
+    /// an IPv6 address, or several ports.
+    ///
+    /// Addresses that fail to bind are handled according to the server's
+    /// [`BindPolicy`].
+    pub fn start_all<A>(&mut self, addresses: impl IntoIterator<Item = A>)
+    where
+        A: ToSocketAddrs + fmt::Display + Send + 'static,
+    {
+        self.stop();
 
+        let addresses = addresses.into_iter().collect::<Vec<_>>();
+        let policy = self.bind_policy;
+        let proxy_protocol = self.proxy_protocol;
+        let limit = ConnectionLimit {
+            open: self.connections.clone(),
+            max: self.max_connections,
+        };
+        let local_addrs = self.local_addrs.clone();
         let codec = self.codec.clone();
-        let (new_clients_tx, new_clients_rx) = flume::unbounded::<Client>();
+        let new_clients_tx = self.new_clients_tx.clone();
+        let incoming = self.incoming_tx.clone();
+
+        self.listener = Some(self.runtime.spawn(async move {
+            let Some(listeners) = Self::bind(addresses, policy).await else {
+                return;
+            };
+
+            *local_addrs.lock().unwrap() = listeners
+                .iter()
+                .filter_map(|listener| listener.local_addr().ok())
+                .collect();
+
+            // Dropping the set when the task is aborted aborts every listener.
+            let mut tasks = JoinSet::new();
+            for listener in listeners {
+                tasks.spawn(Self::listener(
+                    listener,
+                    new_clients_tx.clone(),
+                    incoming.clone(),
+                    codec.clone(),
+                    proxy_protocol,
+                    limit.clone(),
+                ));
+            }
+            while tasks.join_next().await.is_some() {}
+
+            local_addrs.lock().unwrap().clear();
+            info!("Network server stopped");
+        }));
+    }
+
+    /// Returns the number of open TCP connections, which excludes loopback
+    /// clients.
+    pub fn connection_count(&self) -> usize {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    /// Returns the addresses the server is listening on, which is empty until
+    /// its listeners have been bound.
+    ///
+    /// This is useful for finding the port chosen by the OS when starting the
+    /// server on port 0.
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        self.local_addrs.lock().unwrap().clone()
+    }
+
+    /// Connects a new in-process client to the server, without any sockets.
+    ///
+    /// The client is spawned as an entity like any TCP client, and works
+    /// whether or not the server is listening.
+    pub fn connect_loopback(&self) -> LoopbackClient {
+        let id = ConnectionId::next();
+        let addr = loopback::next_address();
+        let (client_stream, server_stream) = tokio::io::duplex(loopback::BUFFER_SIZE);
         let incoming = self.incoming_tx.clone();
+        let (outgoing_tx, outgoing_rx) = tokio::sync::mpsc::unbounded_channel::<WriteOp>();
+        let (errors_tx, errors_rx) = oneshot::channel::<io::Error>();
+        let stats = NetworkStats::default();
+        let codec = self.codec.clone();
+
+        let server_codec = codec.clone();
+        let server_stats = stats.clone();
+        self.runtime.spawn(async move {
+            Self::handle_client(
+                id,
+                server_stream,
+                server_codec,
+                server_stats,
+                incoming,
+                outgoing_rx,
+                errors_tx,
+            )
+            .await
+        });
 
-        self.listener =
-            Some(self.runtime.spawn(async move {
-                Self::listener(address, new_clients_tx, incoming, codec).await
-            }));
-        self.new_clients = new_clients_rx;
+        self.new_clients_tx
+            .try_send(Client::new(id, addr, outgoing_tx, errors_rx, stats))
+            .ok();
+
+        trace!("Loopback client {addr} connected");
+
+        LoopbackClient::new(
+            addr,
+            Framed::new(client_stream, RawPacketCodec::new(codec)),
+            self.runtime.handle().clone(),
+        )
     }
 
     /// Stops the server.
@@ -180,66 +505,154 @@ impl Server {
         if let Some(listener) = self.listener.take() {
             info!("Stopping network server");
             listener.abort();
+            self.local_addrs.lock().unwrap().clear();
         }
     }
 
+    /// Spawns the given future on the server's [`Runtime`], for I/O that
+    /// shouldn't block the app, such as HTTP requests.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.runtime.spawn(future)
+    }
+
     /// Returns an iterator over newly connected clients.
     pub fn iter_new_clients(&self) -> impl Iterator<Item = Client> + '_ {
         self.new_clients.try_iter()
     }
 
-    /// Returns the [`Receiver`] for incoming packets.
-    pub fn incoming(&self) -> Receiver<(SocketAddr, RawPacket)> {
+    /// Returns the [`Receiver`] for incoming packets, paired with the
+    /// [`ConnectionId`] of the client that sent them.
+    pub fn incoming(&self) -> Receiver<(ConnectionId, RawPacket)> {
         self.incoming_rx.clone()
     }
 
-    /// Returns an iterator over incoming packets.
-    pub fn iter_incoming(&self) -> impl Iterator<Item = (SocketAddr, RawPacket)> + '_ {
+    /// Returns an iterator over incoming packets, paired with the
+    /// [`ConnectionId`] of the client that sent them.
+    pub fn iter_incoming(&self) -> impl Iterator<Item = (ConnectionId, RawPacket)> + '_ {
         self.incoming_rx.try_iter()
     }
 
+    /// Binds a [`TcpListener`] to each of the given addresses, or returns
+    /// [`None`] if none should be listened on according to the [`BindPolicy`].
+    async fn bind(
+        addresses: Vec<impl ToSocketAddrs + fmt::Display>,
+        policy: BindPolicy,
+    ) -> Option<Vec<TcpListener>> {
+        let mut listeners = Vec::with_capacity(addresses.len());
+        let mut failed = false;
+        for addr in addresses {
+            let name = addr.to_string();
+            info!("Starting network server on {name}");
+            match TcpListener::bind(addr).await {
+                Ok(listener) => listeners.push(listener),
+                Err(e) => {
+                    error!("Failed to bind network server to {name}: {e}");
+                    failed = true;
+                }
+            }
+        }
+
+        if failed && policy == BindPolicy::RequireAll {
+            error!("Network server not started, as not every address could be bound");
+            return None;
+        }
+        if listeners.is_empty() {
+            error!("Network server not started, as no address could be bound");
+            return None;
+        }
+        Some(listeners)
+    }
+
     /// Processes incoming connections.
     async fn listener(
-        addr: impl ToSocketAddrs + fmt::Display,
+        listener: TcpListener,
         new_clients: Sender<Client>,
-        incoming: Sender<(SocketAddr, RawPacket)>,
+        incoming: Sender<(ConnectionId, RawPacket)>,
         codec: Arc<PacketCodecSettings>,
+        proxy_protocol: ProxyProtocol,
+        limit: ConnectionLimit,
     ) {
-        info!("Starting network server on {addr}");
-
-        let listener = TcpListener::bind(addr).await.unwrap();
+        let mut rejections = Rejections::default();
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Errors such as running out of file descriptors are
+                    // transient, so keep listening, but back off so that a
+                    // persistent error doesn't spin.
+                    error!("Failed to accept connection: {e}");
+                    tokio::time::sleep(Self::ACCEPT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let Some(permit) = limit.acquire() else {
+                if let Some(count) = rejections.record(Instant::now()) {
+                    warn!(
+                        "Rejected {count} connection(s), most recently from {peer}: \
+                         server is at its limit of {} connections",
+                        limit.max.unwrap_or_default(),
+                    );
+                }
+                drop(stream);
+                continue;
+            };
 
-        while let Ok((stream, addr)) = listener.accept().await {
+            let new_clients = new_clients.clone();
             let incoming = incoming.clone();
-            // Tokio's MPSC channels are cancel safe, so we use those instead for tokio::select! {}
-            let (outgoing_tx, outgoing_rx) = tokio::sync::mpsc::unbounded_channel::<WriteOp>();
-            let (errors_tx, errors_rx) = oneshot::channel::<io::Error>();
             let codec = codec.clone();
 
             tokio::spawn(async move {
-                Self::handle_client(addr, stream, codec, incoming, outgoing_rx, errors_tx).await
-            });
+                // Held until the connection closes.
+                let _permit = permit;
+
+                let (addr, stream) =
+                    match proxy::accept(stream, peer, proxy_protocol, codec.timeout).await {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            warn!("Rejected connection from {peer}: {e}");
+                            return;
+                        }
+                    };
 
-            new_clients
-                .try_send(Client::new(addr, outgoing_tx, errors_rx))
-                .ok();
+                // Packets are routed by connection ID rather than by address,
+                // so that a forged PROXY header can't take over another
+                // client's connection.
+                let id = ConnectionId::next();
 
-            trace!("Client {addr} connected");
-        }
+                // Tokio's MPSC channels are cancel safe, so we use those instead for tokio::select! {}
+                let (outgoing_tx, outgoing_rx) = tokio::sync::mpsc::unbounded_channel::<WriteOp>();
+                let (errors_tx, errors_rx) = oneshot::channel::<io::Error>();
+                let stats = NetworkStats::default();
 
-        info!("Network server stopped");
+                new_clients
+                    .try_send(Client::new(id, addr, outgoing_tx, errors_rx, stats.clone()))
+                    .ok();
+
+                trace!("Client {addr} connected");
+
+                Self::handle_client(id, stream, codec, stats, incoming, outgoing_rx, errors_tx)
+                    .await
+            });
+        }
     }
 
     /// Handles I/O for the given client.
     async fn handle_client(
-        addr: SocketAddr,
-        stream: TcpStream,
+        id: ConnectionId,
+        stream: impl AsyncRead + AsyncWrite + Unpin,
         codec: Arc<PacketCodecSettings>,
-        incoming: Sender<(SocketAddr, RawPacket)>,
+        stats: NetworkStats,
+        incoming: Sender<(ConnectionId, RawPacket)>,
         mut outgoing: UnboundedReceiver<WriteOp>,
         errors: oneshot::Sender<io::Error>,
     ) {
         let mut stream = Framed::new(stream, RawPacketCodec::new(Arc::clone(&codec)));
+        let disconnected =
+            || io::Error::new(io::ErrorKind::ConnectionAborted, "Client disconnected");
 
         loop {
             tokio::select! {
@@ -250,24 +663,33 @@ impl Server {
                         break;
                     } else {
                         // timed out
-                        errors.send(io::Error::new(io::ErrorKind::TimedOut, "Client timed out")).ok();
+                        errors
+                            .send(io::Error::new(io::ErrorKind::TimedOut, "Client timed out"))
+                            .ok();
                         break;
                     }
                 }
-                Some(packet) = stream.next() => {
+                packet = stream.next() => {
                     match packet {
-                        Ok(packet) => {
-                            incoming.try_send((addr, packet)).ok();
+                        Some(Ok(packet)) => {
+                            stats.record_received(&packet);
+                            incoming.try_send((id, packet)).ok();
                         }
-                        Err(e) => {
+                        Some(Err(e)) => {
                             errors.send(e).ok();
                             break;
                         }
+                        None => {
+                            // The client closed the connection.
+                            errors.send(disconnected()).ok();
+                            break;
+                        }
                     }
                 }
                 Some(op) = outgoing.recv() => {
                     match op {
                         WriteOp::Send(packet) => {
+                            stats.record_sent(&packet);
                             if let Err(e) = stream.feed(packet).await {
                                 errors.send(e).ok();
                                 break;
@@ -282,21 +704,349 @@ impl Server {
                         WriteOp::EnableCompression => {
                             stream.codec_mut().enable_compression();
                         }
-                        WriteOp::EnableEncryption => {
-                            stream.codec_mut().enable_encryption();
+                        WriteOp::EnableEncryption(shared_secret) => {
+                            stream.codec_mut().enable_encryption(&shared_secret);
                         }
                         WriteOp::Disconnect => {
-                            errors.send(io::Error::new(io::ErrorKind::ConnectionAborted, "Client disconnected")).ok();
+                            errors.send(disconnected()).ok();
                             break;
                         }
                     }
                 }
                 else => {
                     // I/O disconnected
-                    errors.send(io::Error::new(io::ErrorKind::ConnectionAborted, "Client disconnected")).ok();
+                    errors.send(disconnected()).ok();
                     break;
                 }
             }
         }
     }
 }
+
+/// The shared count of open TCP connections, and the most that may be open.
+#[derive(Clone)]
+struct ConnectionLimit {
+    open: Arc<AtomicUsize>,
+    max: Option<usize>,
+}
+
+impl ConnectionLimit {
+    /// Counts a new connection, or returns [`None`] if the limit is reached.
+    fn acquire(&self) -> Option<ConnectionPermit> {
+        let max = self.max.unwrap_or(usize::MAX);
+        self.open
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < max).then_some(open + 1)
+            })
+            .ok()?;
+        Some(ConnectionPermit(self.open.clone()))
+    }
+}
+
+/// Connections rejected for being over the [`ConnectionLimit`], which are
+/// logged at most once per [`Rejections::LOG_INTERVAL`] so that a flood of
+/// connections doesn't flood the log.
+#[derive(Default)]
+struct Rejections {
+    /// The number of rejections since the last one was logged.
+    unlogged: u32,
+    /// When a rejection was last logged.
+    last_logged: Option<Instant>,
+}
+
+impl Rejections {
+    /// The minimum time between logged rejections.
+    const LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Counts a rejected connection, returning the number of rejections to
+    /// log if it's time to log them.
+    fn record(&mut self, now: Instant) -> Option<u32> {
+        self.unlogged += 1;
+
+        let should_log = self
+            .last_logged
+            .is_none_or(|last| now.duration_since(last) >= Self::LOG_INTERVAL);
+        if !should_log {
+            return None;
+        }
+        self.last_logged = Some(now);
+        Some(std::mem::take(&mut self.unlogged))
+    }
+}
+
+/// A counted open connection, which stops being counted when dropped.
+struct ConnectionPermit(Arc<AtomicUsize>);
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Read},
+        net::{SocketAddr, TcpStream},
+        time::{Duration, Instant},
+    };
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+    use minecrevy_io::packet::RawPacket;
+
+    use super::{
+        Rejections, Server, ServerPlugin, StateTimeout, UnknownPacketPolicy, UnknownPackets,
+    };
+    use crate::{
+        client::{Client, PacketWriter, ProtocolState},
+        loopback::LoopbackClient,
+        packet::{IncomingPacketHandlers, OutgoingPacketIds},
+    };
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.init_resource::<OutgoingPacketIds>();
+        app.insert_resource(StateTimeout {
+            timeout: Some(TIMEOUT),
+        });
+        app
+    }
+
+    /// Connects a loopback client, which must be kept alive so that the
+    /// server doesn't despawn it for closing the connection.
+    fn connect(app: &mut App) -> (Entity, LoopbackClient) {
+        let client = app.world().resource::<Server>().connect_loopback();
+        app.update();
+        let entity = app
+            .world_mut()
+            .query_filtered::<Entity, With<Client>>()
+            .single(app.world());
+        (entity, client)
+    }
+
+    fn set_state(app: &mut App, client: Entity, state: ProtocolState) {
+        app.world_mut()
+            .run_system_once(move |mut writer: PacketWriter| {
+                writer.client(client).set_state(state);
+            })
+            .unwrap();
+    }
+
+    /// Returns an app whose clients are in [`ProtocolState::Play`], where only
+    /// packet `0x00` has a handler.
+    fn unknown_packet_app(policy: UnknownPacketPolicy) -> App {
+        let mut app = app();
+        app.insert_resource(policy);
+        app.insert_resource(StateTimeout { timeout: None });
+        app.init_resource::<IncomingPacketHandlers>();
+        app.world_mut()
+            .resource_mut::<IncomingPacketHandlers>()
+            .insert::<u8>(ProtocolState::Play, 0x00);
+        app
+    }
+
+    /// Updates the app until the client has sent the given number of unknown
+    /// packets, or has been despawned.
+    fn wait_for_unknown(app: &mut App, client: Entity, count: u32) -> Option<UnknownPackets> {
+        for _ in 0..500 {
+            app.update();
+            let unknown = app.world().get_entity(client).ok()?.get::<UnknownPackets>();
+            if unknown.is_some_and(|unknown| unknown.count >= count) {
+                return unknown.copied();
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("timed out waiting for {count} unknown packets");
+    }
+
+    #[test]
+    fn unknown_packets_are_counted_and_logged_once_per_interval() {
+        let mut app = unknown_packet_app(UnknownPacketPolicy {
+            log_interval: Duration::from_secs(3600),
+            disconnect_after: None,
+        });
+        let (client, mut connection) = connect(&mut app);
+        set_state(&mut app, client, ProtocolState::Play);
+
+        for id in [0x01, 0x00, 0x02, 0x01] {
+            connection.send(id, &1u8).unwrap();
+        }
+        wait_for_unknown(&mut app, client, 3);
+        for _ in 0..10 {
+            app.update();
+        }
+        let unknown = *app.world().get::<UnknownPackets>(client).unwrap();
+
+        // Only the first unknown packet is logged within the interval, and the
+        // known packet isn't counted.
+        assert_eq!(unknown.count, 3);
+        assert_eq!(unknown.unlogged, 2);
+        assert!(unknown.last_logged.is_some());
+    }
+
+    #[test]
+    fn unknown_packets_are_logged_again_after_the_interval() {
+        let mut app = unknown_packet_app(UnknownPacketPolicy {
+            log_interval: Duration::ZERO,
+            disconnect_after: None,
+        });
+        let (client, mut connection) = connect(&mut app);
+        set_state(&mut app, client, ProtocolState::Play);
+
+        for id in [0x01, 0x02] {
+            connection.send(id, &1u8).unwrap();
+        }
+        let unknown = wait_for_unknown(&mut app, client, 2).unwrap();
+
+        assert_eq!(unknown.count, 2);
+        assert_eq!(unknown.unlogged, 0);
+    }
+
+    #[test]
+    fn client_is_disconnected_after_too_many_unknown_packets() {
+        let mut app = unknown_packet_app(UnknownPacketPolicy {
+            log_interval: Duration::from_secs(3600),
+            disconnect_after: Some(2),
+        });
+        let (client, mut connection) = connect(&mut app);
+        set_state(&mut app, client, ProtocolState::Play);
+
+        connection.send(0x01, &1u8).unwrap();
+        assert_eq!(wait_for_unknown(&mut app, client, 1).unwrap().count, 1);
+        assert!(app.world().get_entity(client).is_ok());
+
+        connection.send(0x01, &1u8).unwrap();
+        assert_eq!(wait_for_unknown(&mut app, client, 2), None);
+
+        // The server closes the connection rather than leaving it open.
+        connection.set_timeout(Duration::from_secs(1));
+        let error = connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionAborted);
+    }
+
+    #[test]
+    fn stalled_client_is_despawned() {
+        let mut app = app();
+        let (client, _connection) = connect(&mut app);
+        set_state(&mut app, client, ProtocolState::Login);
+
+        std::thread::sleep(TIMEOUT);
+        app.update();
+
+        assert!(app.world().get_entity(client).is_err());
+    }
+
+    #[test]
+    fn progressing_client_is_kept() {
+        let mut app = app();
+        let (client, _connection) = connect(&mut app);
+        set_state(&mut app, client, ProtocolState::Login);
+
+        std::thread::sleep(TIMEOUT * 3 / 5);
+        set_state(&mut app, client, ProtocolState::Config);
+        std::thread::sleep(TIMEOUT * 3 / 5);
+        app.update();
+
+        assert!(app.world().get_entity(client).is_ok());
+    }
+
+    /// Starts the server on the given addresses, and waits until it has bound
+    /// them.
+    fn listen(server: &mut Server, addresses: Vec<&'static str>) -> Vec<SocketAddr> {
+        let count = addresses.len();
+        server.start_all(addresses);
+        for _ in 0..500 {
+            let addrs = server.local_addrs();
+            if addrs.len() == count {
+                return addrs;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("timed out waiting for the server to bind {count} addresses");
+    }
+
+    /// Waits until the given number of new clients have connected to the
+    /// server, and returns them.
+    fn wait_for_clients(server: &Server, count: usize) -> Vec<Client> {
+        let mut clients = Vec::new();
+        for _ in 0..500 {
+            clients.extend(server.iter_new_clients());
+            if clients.len() >= count {
+                return clients;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!(
+            "timed out waiting for {count} clients, got {}",
+            clients.len()
+        );
+    }
+
+    #[test]
+    fn listens_on_every_address() {
+        let mut server = Server::default();
+        let addrs = listen(&mut server, vec!["127.0.0.1:0", "127.0.0.1:0"]);
+        assert_ne!(addrs[0], addrs[1]);
+
+        let _first = TcpStream::connect(addrs[0]).unwrap();
+        let _second = TcpStream::connect(addrs[1]).unwrap();
+
+        let clients = wait_for_clients(&server, 2);
+        assert_eq!(clients.len(), 2);
+        assert_eq!(server.connection_count(), 2);
+    }
+
+    #[test]
+    fn connections_over_the_limit_are_closed() {
+        let mut server = Server {
+            max_connections: Some(1),
+            ..Server::default()
+        };
+        let addrs = listen(&mut server, vec!["127.0.0.1:0"]);
+
+        let mut first = TcpStream::connect(addrs[0]).unwrap();
+        let clients = wait_for_clients(&server, 1);
+
+        let mut second = TcpStream::connect(addrs[0]).unwrap();
+        second
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let closed = match second.read(&mut [0; 1]) {
+            Ok(n) => n == 0,
+            Err(e) => e.kind() == io::ErrorKind::ConnectionReset,
+        };
+        assert!(closed, "the second connection wasn't closed");
+        assert_eq!(server.connection_count(), 1);
+
+        // The first connection is still served.
+        let packet = RawPacket {
+            id: 0x00,
+            body: vec![7],
+        };
+        packet.write(&mut first).unwrap();
+        let (id, received) = server
+            .incoming()
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(id, clients[0].id());
+        assert_eq!(received, packet);
+    }
+
+    #[test]
+    fn rejections_are_logged_once_per_interval() {
+        let start = Instant::now();
+        let mut rejections = Rejections::default();
+
+        assert_eq!(rejections.record(start), Some(1));
+        assert_eq!(rejections.record(start + Duration::from_secs(1)), None);
+        assert_eq!(rejections.record(start + Duration::from_secs(2)), None);
+        assert_eq!(rejections.record(start + Rejections::LOG_INTERVAL), Some(3));
+        assert_eq!(
+            rejections.record(start + Rejections::LOG_INTERVAL * 2),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! This module contains the [`NetworkStats`] of each client, which count the
+//! bytes and packets sent to and received from it, and the
+//! [`TopTalkersPlugin`] for logging the busiest clients.
+
+use std::{
+    cmp::Reverse,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bevy::{ecs::entity::EntityHashMap, prelude::*, time::common_conditions::on_timer};
+use minecrevy_io::{packet::RawPacket, util::varint_bytes};
+
+use crate::client::Client;
+
+/// [`Component`] that counts the bytes and packets sent to and received from
+/// a [`Client`] since it connected.
+///
+/// The counters are updated by the client's I/O task as packets are encoded
+/// and decoded, so they're always current without any system running.
+#[derive(Component)]
+#[derive(Clone, Debug, Default)]
+pub struct NetworkStats(Arc<Counters>);
+
+/// The counters shared between a [`NetworkStats`] and its client's I/O task.
+#[derive(Debug, Default)]
+struct Counters {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    packets_sent: AtomicU64,
+    packets_received: AtomicU64,
+}
+
+impl NetworkStats {
+    /// Returns the number of bytes sent to the client, including each
+    /// packet's length prefix.
+    pub fn bytes_sent(&self) -> u64 {
+        self.0.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes received from the client, including each
+    /// packet's length prefix.
+    pub fn bytes_received(&self) -> u64 {
+        self.0.bytes_received.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of packets sent to the client.
+    pub fn packets_sent(&self) -> u64 {
+        self.0.packets_sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of packets received from the client.
+    pub fn packets_received(&self) -> u64 {
+        self.0.packets_received.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of bytes sent to and received from the client.
+    pub fn bytes_total(&self) -> u64 {
+        self.bytes_sent() + self.bytes_received()
+    }
+
+    /// Counts a packet sent to the client.
+    pub(crate) fn record_sent(&self, packet: &RawPacket) {
+        self.0
+            .bytes_sent
+            .fetch_add(framed_len(packet), Ordering::Relaxed);
+        self.0.packets_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a packet received from the client.
+    pub(crate) fn record_received(&self, packet: &RawPacket) {
+        self.0
+            .bytes_received
+            .fetch_add(framed_len(packet), Ordering::Relaxed);
+        self.0.packets_received.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the number of bytes the packet takes up on the wire, including its
+/// length prefix.
+fn framed_len(packet: &RawPacket) -> u64 {
+    let len = packet.len();
+    (varint_bytes(len as i32) + len) as u64
+}
+
+/// [`Plugin`] that periodically logs the [`Client`]s that sent and received
+/// the most bytes since the last time it logged.
+pub struct TopTalkersPlugin {
+    /// How often to log the top talkers.
+    pub interval: Duration,
+    /// How many clients to log.
+    pub count: usize,
+}
+
+impl Default for TopTalkersPlugin {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            count: 5,
+        }
+    }
+}
+
+impl Plugin for TopTalkersPlugin {
+    fn build(&self, app: &mut App) {
+        let count = self.count;
+        app.add_systems(
+            Update,
+            (move |clients: Query<(Entity, &Client, &NetworkStats)>,
+                   last: Local<EntityHashMap<u64>>| {
+                Self::log_top_talkers(clients, last, count);
+            })
+            .run_if(on_timer(self.interval)),
+        );
+    }
+}
+
+impl TopTalkersPlugin {
+    /// [`System`] that logs the `count` clients that transferred the most
+    /// bytes since the last time it ran.
+    fn log_top_talkers(
+        clients: Query<(Entity, &Client, &NetworkStats)>,
+        mut last: Local<EntityHashMap<u64>>,
+        count: usize,
+    ) {
+        let mut talkers = clients
+            .iter()
+            .map(|(entity, client, stats)| {
+                let total = stats.bytes_total();
+                let delta = total - last.get(&entity).copied().unwrap_or(0);
+                (entity, client.addr(), stats, total, delta)
+            })
+            .collect::<Vec<_>>();
+
+        // Forget disconnected clients, so their totals don't accumulate.
+        *last = talkers
+            .iter()
+            .map(|&(entity, _, _, total, _)| (entity, total))
+            .collect();
+
+        talkers.sort_unstable_by_key(|&(.., delta)| Reverse(delta));
+        for (entity, addr, stats, _, delta) in talkers.into_iter().take(count) {
+            if delta == 0 {
+                break;
+            }
+            info!(
+                "Client {entity} ({addr}): {delta} bytes since last report \
+                 ({} sent, {} received, {} packets sent, {} packets received in total)",
+                stats.bytes_sent(),
+                stats.bytes_received(),
+                stats.packets_sent(),
+                stats.packets_received(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+    use minecrevy_io::packet::RawPacket;
+
+    use super::NetworkStats;
+    use crate::{
+        client::{ClientAddressIndex, PacketWriter, ProtocolState},
+        packet::{IncomingPacketHandlers, OutgoingPacketIds},
+        server::{Server, ServerPlugin},
+    };
+
+    /// Updates the app until the given condition holds for the client's stats.
+    fn wait_for(app: &mut App, client: Entity, condition: impl Fn(&NetworkStats) -> bool) {
+        for _ in 0..500 {
+            app.update();
+            if condition(app.world().get::<NetworkStats>(client).unwrap()) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("timed out waiting for the client's stats");
+    }
+
+    #[test]
+    fn counts_framed_bytes_and_packets() {
+        let mut app = App::new();
+        app.add_plugins(ServerPlugin);
+        app.init_resource::<IncomingPacketHandlers>();
+        app.init_resource::<OutgoingPacketIds>();
+        app.world_mut()
+            .resource_mut::<OutgoingPacketIds>()
+            .insert::<u8>(ProtocolState::Handshake, 0x01);
+
+        let mut connection = app.world().resource::<Server>().connect_loopback();
+        app.update();
+        let client = app
+            .world()
+            .resource::<ClientAddressIndex>()
+            .entity(connection.addr())
+            .unwrap();
+
+        // 1 byte of length, 1 byte of ID, and 3 bytes of body.
+        for _ in 0..2 {
+            connection
+                .send_raw(RawPacket {
+                    id: 0x00,
+                    body: vec![1, 2, 3],
+                })
+                .unwrap();
+        }
+        wait_for(&mut app, client, |stats| stats.packets_received() == 2);
+
+        // 1 byte of length, 1 byte of ID, and 1 byte of body.
+        app.world_mut()
+            .run_system_once(move |mut writer: PacketWriter| {
+                writer.client(client).send(&7u8);
+            })
+            .unwrap();
+        connection.recv_raw().unwrap();
+
+        let stats = app.world().get::<NetworkStats>(client).unwrap();
+        assert_eq!(stats.packets_received(), 2);
+        assert_eq!(stats.bytes_received(), 10);
+        assert_eq!(stats.packets_sent(), 1);
+        assert_eq!(stats.bytes_sent(), 3);
+        assert_eq!(stats.bytes_total(), 13);
+    }
+
+    #[test]
+    fn framed_len_includes_the_length_prefix() {
+        let small = RawPacket {
+            id: 0x00,
+            body: vec![0; 126],
+        };
+        let large = RawPacket {
+            id: 0x00,
+            body: vec![0; 127],
+        };
+
+        // A length of 127 fits in one varint byte, but 128 needs two.
+        assert_eq!(super::framed_len(&small), 1 + 127);
+        assert_eq!(super::framed_len(&large), 2 + 128);
+    }
+}
@@ -0,0 +1,487 @@
+//! Brigadier command graphs, as sent to clients in the
+//! [`DeclareCommands`](crate::play::DeclareCommands) packet.
+
+use std::io;
+
+use minecrevy_io::{
+    args::{IntArgs, StringArgs},
+    McWrite,
+};
+use minecrevy_util::key::Key;
+
+use crate::play::DeclareCommands;
+
+/// Builder for a graph of [`CommandNode`]s, which computes the indices of each
+/// node in the flat array sent to clients.
+///
+/// The root node is always at index [`CommandTree::ROOT`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct CommandTree {
+    nodes: Vec<CommandNode>,
+}
+
+impl Default for CommandTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandTree {
+    /// The index of the root node.
+    pub const ROOT: i32 = 0;
+
+    /// Creates a new [`CommandTree`] containing only the root node.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![CommandNode::new(NodeKind::Root)],
+        }
+    }
+
+    /// Adds a literal node, such as `tp`, as a child of the given parent node,
+    /// returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent doesn't exist.
+    pub fn literal(&mut self, parent: i32, name: impl Into<String>) -> i32 {
+        self.add(parent, NodeKind::Literal(name.into()))
+    }
+
+    /// Adds an argument node, such as `<target>`, as a child of the given
+    /// parent node, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent doesn't exist.
+    pub fn argument(&mut self, parent: i32, name: impl Into<String>, parser: Parser) -> i32 {
+        self.add(
+            parent,
+            NodeKind::Argument {
+                name: name.into(),
+                parser,
+            },
+        )
+    }
+
+    /// Marks the given node as executable, meaning the command is complete
+    /// once it has been parsed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node doesn't exist.
+    pub fn executable(&mut self, node: i32) -> &mut Self {
+        self.node_mut(node).executable = true;
+        self
+    }
+
+    /// Redirects the given node to `target`, such that parsing continues with
+    /// the children of `target`, as used for command aliases.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either node doesn't exist.
+    pub fn redirect(&mut self, node: i32, target: i32) -> &mut Self {
+        self.node_mut(target);
+        self.node_mut(node).redirect = Some(target);
+        self
+    }
+
+    /// Makes the client ask the server for suggestions for the given argument
+    /// node, using the given suggestions type, such as `minecraft:ask_server`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node doesn't exist or isn't an argument node.
+    pub fn suggestions(&mut self, node: i32, suggestions: Key) -> &mut Self {
+        let node = self.node_mut(node);
+        assert!(
+            matches!(node.kind, NodeKind::Argument { .. }),
+            "only argument nodes can have suggestions, not {:?}",
+            node.kind,
+        );
+        node.suggestions = Some(suggestions);
+        self
+    }
+
+    /// Returns the node at the given index, if any.
+    pub fn get(&self, node: i32) -> Option<&CommandNode> {
+        usize::try_from(node).ok().and_then(|i| self.nodes.get(i))
+    }
+
+    /// Returns the number of nodes in the tree, including the root node.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree only contains the root node.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Builds the [`DeclareCommands`] packet for this tree.
+    pub fn build(&self) -> DeclareCommands {
+        DeclareCommands {
+            nodes: self.nodes.clone(),
+            root: Self::ROOT,
+        }
+    }
+
+    fn add(&mut self, parent: i32, kind: NodeKind) -> i32 {
+        let index = i32::try_from(self.nodes.len()).expect("too many command nodes");
+        self.node_mut(parent).children.push(index);
+        self.nodes.push(CommandNode::new(kind));
+        index
+    }
+
+    fn node_mut(&mut self, node: i32) -> &mut CommandNode {
+        usize::try_from(node)
+            .ok()
+            .and_then(|i| self.nodes.get_mut(i))
+            .unwrap_or_else(|| panic!("command node {node} does not exist"))
+    }
+}
+
+/// A single node of a command graph.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CommandNode {
+    /// The kind of node.
+    pub kind: NodeKind,
+    /// Whether the command is complete once this node has been parsed.
+    pub executable: bool,
+    /// The indices of the child nodes.
+    pub children: Vec<i32>,
+    /// The index of the node to continue parsing from, if any.
+    pub redirect: Option<i32>,
+    /// The suggestions type of an argument node, if the client should ask the
+    /// server for suggestions.
+    pub suggestions: Option<Key>,
+}
+
+impl CommandNode {
+    /// Creates a new, non-executable [`CommandNode`] without any children.
+    pub fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            executable: false,
+            children: Vec::new(),
+            redirect: None,
+            suggestions: None,
+        }
+    }
+}
+
+impl McWrite for CommandNode {
+    type Args = ();
+
+    /// # Errors
+    ///
+    /// Returns an error if a node other than an argument node has suggestions,
+    /// which clients can't read.
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        if self.suggestions.is_some() && !matches!(self.kind, NodeKind::Argument { .. }) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only argument nodes can have suggestions",
+            ));
+        }
+
+        let mut flags: u8 = match self.kind {
+            NodeKind::Root => 0,
+            NodeKind::Literal(_) => 1,
+            NodeKind::Argument { .. } => 2,
+        };
+        if self.executable {
+            flags |= 0x04;
+        }
+        if self.redirect.is_some() {
+            flags |= 0x08;
+        }
+        if self.suggestions.is_some() {
+            flags |= 0x10;
+        }
+        flags.write(&mut writer, ())?;
+
+        let len = i32::try_from(self.children.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        len.write(&mut writer, IntArgs { varint: true })?;
+        for child in &self.children {
+            child.write(&mut writer, IntArgs { varint: true })?;
+        }
+        if let Some(redirect) = self.redirect {
+            redirect.write(&mut writer, IntArgs { varint: true })?;
+        }
+
+        match &self.kind {
+            NodeKind::Root => {}
+            NodeKind::Literal(name) => {
+                name.write(
+                    &mut writer,
+                    StringArgs {
+                        max_len: Some(32767),
+                    },
+                )?;
+            }
+            NodeKind::Argument { name, parser } => {
+                name.write(
+                    &mut writer,
+                    StringArgs {
+                        max_len: Some(32767),
+                    },
+                )?;
+                parser.write(&mut writer, ())?;
+            }
+        }
+
+        if let Some(suggestions) = &self.suggestions {
+            suggestions.write(&mut writer, ())?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of a [`CommandNode`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum NodeKind {
+    /// The root of the command graph.
+    Root,
+    /// A literal word, such as a command name.
+    Literal(String),
+    /// An argument parsed by a [`Parser`].
+    Argument {
+        /// The name of the argument, as shown to players.
+        name: String,
+        /// The parser for the argument.
+        parser: Parser,
+    },
+}
+
+/// The parser of an argument [`CommandNode`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum Parser {
+    /// `brigadier:bool`
+    Bool,
+    /// `brigadier:float`, with optional bounds.
+    Float {
+        /// The minimum value.
+        min: Option<f32>,
+        /// The maximum value.
+        max: Option<f32>,
+    },
+    /// `brigadier:double`, with optional bounds.
+    Double {
+        /// The minimum value.
+        min: Option<f64>,
+        /// The maximum value.
+        max: Option<f64>,
+    },
+    /// `brigadier:integer`, with optional bounds.
+    Integer {
+        /// The minimum value.
+        min: Option<i32>,
+        /// The maximum value.
+        max: Option<i32>,
+    },
+    /// `brigadier:long`, with optional bounds.
+    Long {
+        /// The minimum value.
+        min: Option<i64>,
+        /// The maximum value.
+        max: Option<i64>,
+    },
+    /// `brigadier:string`
+    String(StringKind),
+    /// `minecraft:entity`
+    Entity {
+        /// Whether only a single entity may be selected.
+        single: bool,
+        /// Whether only players may be selected.
+        players_only: bool,
+    },
+    /// `minecraft:game_profile`
+    GameProfile,
+    /// `minecraft:block_pos`
+    BlockPos,
+    /// `minecraft:vec3`
+    Vec3,
+    /// `minecraft:message`
+    Message,
+}
+
+impl Parser {
+    /// Returns the protocol ID of this parser.
+    pub fn to_id(&self) -> i32 {
+        match self {
+            Self::Bool => 0,
+            Self::Float { .. } => 1,
+            Self::Double { .. } => 2,
+            Self::Integer { .. } => 3,
+            Self::Long { .. } => 4,
+            Self::String(_) => 5,
+            Self::Entity { .. } => 6,
+            Self::GameProfile => 7,
+            Self::BlockPos => 8,
+            Self::Vec3 => 10,
+            Self::Message => 19,
+        }
+    }
+}
+
+impl McWrite for Parser {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_id().write(&mut writer, IntArgs { varint: true })?;
+
+        match self {
+            Self::Float { min, max } => write_bounds(writer, *min, *max, ()),
+            Self::Double { min, max } => write_bounds(writer, *min, *max, ()),
+            Self::Integer { min, max } => {
+                write_bounds(writer, *min, *max, IntArgs { varint: false })
+            }
+            Self::Long { min, max } => write_bounds(writer, *min, *max, IntArgs { varint: false }),
+            Self::String(kind) => kind.to_id().write(writer, IntArgs { varint: true }),
+            Self::Entity {
+                single,
+                players_only,
+            } => {
+                let mut flags: u8 = 0;
+                if *single {
+                    flags |= 0x01;
+                }
+                if *players_only {
+                    flags |= 0x02;
+                }
+                flags.write(writer, ())
+            }
+            Self::Bool | Self::GameProfile | Self::BlockPos | Self::Vec3 | Self::Message => Ok(()),
+        }
+    }
+}
+
+/// Writes the flags and optional bounds of a numeric [`Parser`].
+fn write_bounds<T: McWrite>(
+    mut writer: impl io::Write,
+    min: Option<T>,
+    max: Option<T>,
+    args: T::Args,
+) -> io::Result<()>
+where
+    T::Args: Clone,
+{
+    let mut flags: u8 = 0;
+    if min.is_some() {
+        flags |= 0x01;
+    }
+    if max.is_some() {
+        flags |= 0x02;
+    }
+    flags.write(&mut writer, ())?;
+
+    if let Some(min) = min {
+        min.write(&mut writer, args.clone())?;
+    }
+    if let Some(max) = max {
+        max.write(&mut writer, args)?;
+    }
+    Ok(())
+}
+
+/// The kind of string parsed by a [`Parser::String`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum StringKind {
+    /// A single word.
+    SingleWord,
+    /// A single word, or a quoted phrase.
+    QuotablePhrase,
+    /// The rest of the command.
+    GreedyPhrase,
+}
+
+impl StringKind {
+    /// Returns the protocol ID of this string kind.
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::SingleWord => 0,
+            Self::QuotablePhrase => 1,
+            Self::GreedyPhrase => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::McWrite;
+    use minecrevy_util::key::Key;
+
+    use super::{CommandNode, CommandTree, NodeKind, Parser};
+
+    fn ask_server() -> Key {
+        Key::minecraft("ask_server").unwrap()
+    }
+
+    #[test]
+    fn argument_node_writes_suggestions() {
+        let mut tree = CommandTree::new();
+        let tp = tree.literal(CommandTree::ROOT, "tp");
+        let target = tree.argument(tp, "target", Parser::Bool);
+        tree.suggestions(target, ask_server());
+
+        let mut bytes = Vec::new();
+        tree.get(target).unwrap().write_default(&mut bytes).unwrap();
+        // An argument node with suggestions and no children.
+        assert_eq!(bytes[..2], [0x02 | 0x10, 0]);
+        assert!(bytes.ends_with(b"minecraft:ask_server"));
+    }
+
+    /// Writes the node at the given index of the tree.
+    fn node_bytes(tree: &CommandTree, node: i32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        tree.get(node).unwrap().write_default(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn node_flags_layout() {
+        let mut tree = CommandTree::new();
+        let teleport = tree.literal(CommandTree::ROOT, "teleport");
+        let target = tree.argument(teleport, "target", Parser::BlockPos);
+        tree.executable(target);
+        let tp = tree.literal(CommandTree::ROOT, "tp");
+        tree.redirect(tp, teleport);
+
+        #[rustfmt::skip]
+        assert_eq!(node_bytes(&tree, CommandTree::ROOT), [
+            // root, with two children
+            0x00, 0x02, 0x01, 0x03,
+        ]);
+        #[rustfmt::skip]
+        assert_eq!(node_bytes(&tree, target), [
+            // executable argument node, without children
+            0x02 | 0x04, 0x00,
+            // name, then the parser ID
+            0x06, b't', b'a', b'r', b'g', b'e', b't', 0x08,
+        ]);
+        #[rustfmt::skip]
+        assert_eq!(node_bytes(&tree, tp), [
+            // literal node with a redirect, without children
+            0x01 | 0x08, 0x00,
+            // redirect, then the name
+            0x01, 0x02, b't', b'p',
+        ]);
+    }
+
+    #[test]
+    #[should_panic = "only argument nodes can have suggestions"]
+    fn literal_node_rejects_suggestions() {
+        let mut tree = CommandTree::new();
+        let tp = tree.literal(CommandTree::ROOT, "tp");
+        tree.suggestions(tp, ask_server());
+    }
+
+    #[test]
+    fn literal_node_with_suggestions_fails_to_write() {
+        let mut node = CommandNode::new(NodeKind::Literal("tp".to_owned()));
+        node.suggestions = Some(ask_server());
+        assert!(node.write_default(&mut Vec::new()).is_err());
+    }
+}
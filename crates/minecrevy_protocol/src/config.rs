@@ -1 +1,237 @@
 //! Minecraft protocol packet definitions in the `Config` state.
+
+use std::io;
+
+use minecrevy_io::{
+    args::{IntArgs, ListArgs, StringArgs},
+    McRead, McWrite,
+};
+use minecrevy_util::key::Key;
+
+/// A packet sent by the server to send the entries of a single registry, such
+/// as the dimension types or biomes, to the client.
+///
+/// Only `RegistryData<Tag>` is registered, with each entry's data as a
+/// network NBT [`Tag`](minecrevy_io::nbt::Tag), since a packet ID can only
+/// belong to one type.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RegistryData<T> {
+    /// The ID of the registry, such as `minecraft:dimension_type`.
+    pub registry: Key,
+    /// The entries of the registry, in order of their numeric IDs.
+    ///
+    /// Entries without data are filled in by the client from its known packs.
+    pub entries: Vec<(Key, Option<T>)>,
+}
+
+impl<T: McWrite> McWrite for RegistryData<T> {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.registry.write(&mut writer, ())?;
+        let len = i32::try_from(self.entries.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        len.write(&mut writer, IntArgs { varint: true })?;
+        for (id, data) in &self.entries {
+            id.write(&mut writer, ())?;
+            data.write_default(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to finish the configuration state, after which
+/// the client acknowledges it with [`AcknowledgeFinishConfiguration`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct FinishConfiguration;
+
+impl McWrite for FinishConfiguration {
+    type Args = ();
+
+    fn write(&self, _: impl io::Write, (): Self::Args) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A packet sent by the client to acknowledge [`FinishConfiguration`], after
+/// which both sides are in the play state.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AcknowledgeFinishConfiguration;
+
+impl McRead for AcknowledgeFinishConfiguration {
+    type Args = ();
+
+    fn read(_: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// A packet sent by the server to enable feature flags, such as
+/// `minecraft:vanilla`, on the client.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FeatureFlags {
+    /// The feature flags to enable.
+    pub flags: Vec<Key>,
+}
+
+impl McWrite for FeatureFlags {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.flags.write(writer, ListArgs::default())
+    }
+}
+
+/// A data pack known to both the client and server, whose registry data
+/// doesn't need to be sent in [`RegistryData`].
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct KnownPack {
+    /// The namespace of the pack, such as `minecraft`.
+    pub namespace: String,
+    /// The ID of the pack, such as `core`.
+    pub id: String,
+    /// The version of the pack, such as `1.21`.
+    pub version: String,
+}
+
+impl KnownPack {
+    /// Returns the built-in `minecraft:core` pack of the given game version.
+    pub fn core(version: impl Into<String>) -> Self {
+        Self {
+            namespace: "minecraft".to_owned(),
+            id: "core".to_owned(),
+            version: version.into(),
+        }
+    }
+}
+
+impl McRead for KnownPack {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            namespace: String::read(&mut reader, StringArgs::default())?,
+            id: String::read(&mut reader, StringArgs::default())?,
+            version: String::read(reader, StringArgs::default())?,
+        })
+    }
+}
+
+impl McWrite for KnownPack {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.namespace.write(&mut writer, StringArgs::default())?;
+        self.id.write(&mut writer, StringArgs::default())?;
+        self.version.write(writer, StringArgs::default())
+    }
+}
+
+/// A packet sent by the server to list the [`KnownPack`]s it'd like to skip
+/// sending the registry data of.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ClientboundKnownPacks {
+    /// The packs known to the server.
+    pub packs: Vec<KnownPack>,
+}
+
+impl McWrite for ClientboundKnownPacks {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.packs.write(writer, ListArgs::default())
+    }
+}
+
+/// A packet sent by the client in response to [`ClientboundKnownPacks`],
+/// listing which of the server's packs it also knows.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServerboundKnownPacks {
+    /// The packs known to both the client and server.
+    pub packs: Vec<KnownPack>,
+}
+
+impl McRead for ServerboundKnownPacks {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            packs: Vec::read(reader, ListArgs::default())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::{ext::ReadMinecraftExt, nbt::Tag, McRead, McWrite};
+    use minecrevy_util::{dimension::DimensionType, key::Key};
+
+    use super::RegistryData;
+
+    fn encode(value: &impl McWrite<Args = ()>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.write(&mut bytes, ()).unwrap();
+        bytes
+    }
+
+    fn key(path: &str) -> Key {
+        Key::minecraft(path).unwrap()
+    }
+
+    #[test]
+    fn registry_data_layout() {
+        let packet = RegistryData::<Tag> {
+            registry: key("dimension_type"),
+            entries: vec![
+                (key("overworld"), Some(Tag::Int(1))),
+                (key("the_end"), None),
+            ],
+        };
+
+        #[rustfmt::skip]
+        let expected = [
+            &[24][..], b"minecraft:dimension_type", // registry
+            &[2],                                   // entry count
+            &[19], b"minecraft:overworld",          // key
+            &[1],                                   // present
+            &[3, 0, 0, 0, 1],                       // nameless int tag
+            &[17], b"minecraft:the_end",            // key
+            &[0],                                   // absent, filled in from the known packs
+        ]
+        .concat();
+        assert_eq!(encode(&packet), expected);
+    }
+
+    #[test]
+    fn typed_entries_encode_as_nbt() {
+        let packet = RegistryData {
+            registry: key("dimension_type"),
+            entries: vec![
+                (key("overworld"), Some(DimensionType::OVERWORLD)),
+                (key("the_nether"), Some(DimensionType::THE_NETHER)),
+            ],
+        };
+        let bytes = encode(&packet);
+
+        let mut reader = bytes.as_slice();
+        assert_eq!(Key::read_default(&mut reader).unwrap(), packet.registry);
+        assert_eq!(reader.read_var_i32().unwrap(), 2);
+        let mut entries = Vec::new();
+        for _ in 0..2 {
+            let key = Key::read_default(&mut reader).unwrap();
+            assert!(reader.read_bool().unwrap());
+            entries.push((key, Tag::read_default(&mut reader).unwrap()));
+        }
+        assert!(reader.is_empty(), "trailing bytes");
+
+        assert_eq!(entries[0].0, key("overworld"));
+        let overworld = entries[0].1.as_compound().unwrap();
+        assert_eq!(overworld["min_y"], Tag::Int(-64));
+        assert!(!overworld.contains_key("fixed_time"));
+
+        assert_eq!(entries[1].0, key("the_nether"));
+        let the_nether = entries[1].1.as_compound().unwrap();
+        assert_eq!(the_nether["fixed_time"], Tag::Long(18000));
+        assert_eq!(the_nether["ultrawarm"], Tag::Byte(1));
+    }
+}
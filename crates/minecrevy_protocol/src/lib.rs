@@ -3,16 +3,25 @@
 #![warn(missing_docs)]
 
 use bevy::prelude::*;
+use minecrevy_io::nbt::Tag;
 use minecrevy_net::{client::ProtocolState, AppNetworkExt};
 
+use crate::version::{ProtocolVersion, VersionedPackets};
+
+pub mod command;
 pub mod config;
 pub mod handshake;
 pub mod login;
 pub mod play;
 pub mod status;
+pub mod version;
 
 /// [`Plugin`] for automatically registering Minecraft protocol packets for
 /// server-side communication.
+///
+/// Packets are registered with their IDs in [`ProtocolVersion::LATEST`]. Add
+/// packets whose IDs differ in other supported versions to the
+/// [`VersionedPackets`] resource before adding this plugin.
 pub struct ServerProtocolPlugin {
     /// Whether to register [`handshake`] packets.
     pub handshake: bool,
@@ -63,8 +72,15 @@ impl Plugin for ServerProtocolPlugin {
             app.add_status_packets();
         }
         if self.config {
-            // TODO
+            app.add_config_packets();
+            app.add_systems(Update, apply_deferred.in_set(PacketHandlerSet::ConfigApply));
         }
+
+        // Packets whose IDs differ between versions are registered with their
+        // latest IDs, which clients on other versions are translated from.
+        app.init_resource::<VersionedPackets>();
+        let versioned = app.world().resource::<VersionedPackets>().clone();
+        versioned.register(app, ProtocolVersion::LATEST);
     }
 }
 
@@ -116,11 +132,87 @@ impl AppProtocolExt for App {
     }
 
     fn add_login_packets(&mut self) -> &mut Self {
-        self.add_outgoing_packet::<login::Disconnect>(ProtocolState::Login, 0x00)
+        self.add_incoming_packet::<login::LoginStart>(ProtocolState::Login, 0x00)
+            .add_incoming_packet::<login::EncryptionResponse>(ProtocolState::Login, 0x01)
+            .add_incoming_packet::<login::LoginAcknowledged>(ProtocolState::Login, 0x03)
+            .add_outgoing_packet::<login::Disconnect>(ProtocolState::Login, 0x00)
+            .add_outgoing_packet::<login::EncryptionRequest>(ProtocolState::Login, 0x01)
+            .add_outgoing_packet::<login::LoginSuccess>(ProtocolState::Login, 0x02)
     }
 
     fn add_play_packets(&mut self) -> &mut Self {
-        self
+        self.add_incoming_packet::<play::ConfirmTeleport>(ProtocolState::Play, 0x00)
+            .add_incoming_packet::<play::SetDifficulty>(ProtocolState::Play, 0x02)
+            .add_incoming_packet::<play::ChatCommand>(ProtocolState::Play, 0x04)
+            .add_incoming_packet::<play::PluginMessage>(ProtocolState::Play, 0x12)
+            .add_incoming_packet::<play::KeepAlive>(ProtocolState::Play, 0x18)
+            .add_incoming_packet::<play::LockDifficulty>(ProtocolState::Play, 0x19)
+            .add_incoming_packet::<play::SetPlayerPosition>(ProtocolState::Play, 0x1A)
+            .add_incoming_packet::<play::SetPlayerPositionAndRotation>(ProtocolState::Play, 0x1B)
+            .add_incoming_packet::<play::PlayerAction>(ProtocolState::Play, 0x24)
+            .add_incoming_packet::<play::EntityAction>(ProtocolState::Play, 0x25)
+            .add_incoming_packet::<play::Pong>(ProtocolState::Play, 0x27)
+            .add_incoming_packet::<play::ResourcePackResponse>(ProtocolState::Play, 0x2B)
+            .add_incoming_packet::<play::SetHeldItem>(ProtocolState::Play, 0x2F)
+            .add_incoming_packet::<play::ProgramCommandBlock>(ProtocolState::Play, 0x30)
+            .add_incoming_packet::<play::SetCreativeModeSlot>(ProtocolState::Play, 0x32)
+            .add_incoming_packet::<play::ProgramJigsawBlock>(ProtocolState::Play, 0x33)
+            .add_incoming_packet::<play::ProgramStructureBlock>(ProtocolState::Play, 0x34)
+            .add_incoming_packet::<play::UpdateSign>(ProtocolState::Play, 0x35)
+            .add_incoming_packet::<play::SwingArm>(ProtocolState::Play, 0x36)
+            .add_incoming_packet::<play::Spectate>(ProtocolState::Play, 0x37)
+            .add_incoming_packet::<play::UseItemOn>(ProtocolState::Play, 0x38)
+            .add_outgoing_packet::<play::SpawnEntity>(ProtocolState::Play, 0x01)
+            .add_outgoing_packet::<play::EntityAnimation>(ProtocolState::Play, 0x03)
+            .add_outgoing_packet::<play::StatisticsUpdate>(ProtocolState::Play, 0x04)
+            .add_outgoing_packet::<play::AckBlockChange>(ProtocolState::Play, 0x05)
+            .add_outgoing_packet::<play::BlockEntityData>(ProtocolState::Play, 0x07)
+            .add_outgoing_packet::<play::BlockUpdate>(ProtocolState::Play, 0x09)
+            .add_outgoing_packet::<play::BossBarUpdate>(ProtocolState::Play, 0x0A)
+            .add_outgoing_packet::<play::DifficultyUpdate>(ProtocolState::Play, 0x0B)
+            .add_outgoing_packet::<play::ClearTitles>(ProtocolState::Play, 0x0F)
+            .add_outgoing_packet::<play::DeclareCommands>(ProtocolState::Play, 0x11)
+            .add_outgoing_packet::<play::PluginMessage>(ProtocolState::Play, 0x19)
+            .add_outgoing_packet::<play::Disconnect>(ProtocolState::Play, 0x1D)
+            .add_outgoing_packet::<play::GameEvent>(ProtocolState::Play, 0x22)
+            .add_outgoing_packet::<play::CreateWorldBorder>(ProtocolState::Play, 0x25)
+            .add_outgoing_packet::<play::KeepAlive>(ProtocolState::Play, 0x26)
+            .add_outgoing_packet::<play::ChunkDataAndLight>(ProtocolState::Play, 0x27)
+            .add_outgoing_packet::<play::SpawnParticle>(ProtocolState::Play, 0x29)
+            .add_outgoing_packet::<play::UpdateLight>(ProtocolState::Play, 0x2A)
+            .add_outgoing_packet::<play::Login>(ProtocolState::Play, 0x2B)
+            .add_outgoing_packet::<play::MapUpdate>(ProtocolState::Play, 0x2C)
+            .add_outgoing_packet::<play::OpenSignEditor>(ProtocolState::Play, 0x34)
+            .add_outgoing_packet::<play::Ping>(ProtocolState::Play, 0x35)
+            .add_outgoing_packet::<play::TabListRemove>(ProtocolState::Play, 0x3D)
+            .add_outgoing_packet::<play::TabListUpdate>(ProtocolState::Play, 0x3E)
+            .add_outgoing_packet::<play::SyncPlayerPosition>(ProtocolState::Play, 0x40)
+            .add_outgoing_packet::<play::ResetScore>(ProtocolState::Play, 0x44)
+            .add_outgoing_packet::<play::AddResourcePack>(ProtocolState::Play, 0x46)
+            .add_outgoing_packet::<play::SetActionBar>(ProtocolState::Play, 0x4C)
+            .add_outgoing_packet::<play::WorldBorderCenter>(ProtocolState::Play, 0x4D)
+            .add_outgoing_packet::<play::WorldBorderLerpSize>(ProtocolState::Play, 0x4E)
+            .add_outgoing_packet::<play::WorldBorderSize>(ProtocolState::Play, 0x4F)
+            .add_outgoing_packet::<play::WorldBorderWarningDelay>(ProtocolState::Play, 0x50)
+            .add_outgoing_packet::<play::WorldBorderWarningReach>(ProtocolState::Play, 0x51)
+            .add_outgoing_packet::<play::SetCenterChunk>(ProtocolState::Play, 0x54)
+            .add_outgoing_packet::<play::SetDefaultSpawnPosition>(ProtocolState::Play, 0x56)
+            .add_outgoing_packet::<play::DisplayObjective>(ProtocolState::Play, 0x57)
+            .add_outgoing_packet::<play::EntityMetadata>(ProtocolState::Play, 0x58)
+            .add_outgoing_packet::<play::SetEntityVelocity>(ProtocolState::Play, 0x5A)
+            .add_outgoing_packet::<play::UpdateObjectives>(ProtocolState::Play, 0x5E)
+            .add_outgoing_packet::<play::TeamUpdate>(ProtocolState::Play, 0x60)
+            .add_outgoing_packet::<play::UpdateScore>(ProtocolState::Play, 0x61)
+            .add_outgoing_packet::<play::SetSubtitle>(ProtocolState::Play, 0x63)
+            .add_outgoing_packet::<play::SetTitle>(ProtocolState::Play, 0x65)
+            .add_outgoing_packet::<play::TitleTimes>(ProtocolState::Play, 0x66)
+            .add_outgoing_packet::<play::EntitySoundEffect>(ProtocolState::Play, 0x67)
+            .add_outgoing_packet::<play::SoundEffect>(ProtocolState::Play, 0x68)
+            .add_outgoing_packet::<play::StopSound>(ProtocolState::Play, 0x6A)
+            .add_outgoing_packet::<play::SystemChat>(ProtocolState::Play, 0x6C)
+            .add_outgoing_packet::<play::TabListHeaderAndFooter>(ProtocolState::Play, 0x6D)
+            .add_outgoing_packet::<play::UpdateAttributes>(ProtocolState::Play, 0x75)
+            .add_outgoing_packet::<play::EntityEffect>(ProtocolState::Play, 0x76)
     }
 
     fn add_status_packets(&mut self) -> &mut Self {
@@ -131,6 +223,50 @@ impl AppProtocolExt for App {
     }
 
     fn add_config_packets(&mut self) -> &mut Self {
-        self
+        self.add_incoming_packet::<config::AcknowledgeFinishConfiguration>(
+            ProtocolState::Config,
+            0x03,
+        )
+        .add_incoming_packet::<play::PluginMessage>(ProtocolState::Config, 0x02)
+        .add_incoming_packet::<config::ServerboundKnownPacks>(ProtocolState::Config, 0x07)
+        .add_outgoing_packet::<play::PluginMessage>(ProtocolState::Config, 0x01)
+        .add_outgoing_packet::<config::FinishConfiguration>(ProtocolState::Config, 0x03)
+        .add_outgoing_packet::<config::RegistryData<Tag>>(ProtocolState::Config, 0x07)
+        .add_outgoing_packet::<config::FeatureFlags>(ProtocolState::Config, 0x0C)
+        .add_outgoing_packet::<config::ClientboundKnownPacks>(ProtocolState::Config, 0x0E)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_net::{
+        client::ProtocolState,
+        packet::{IncomingPacketHandlers, OutgoingPacketIds},
+    };
+
+    use crate::{play::PluginMessage, ServerProtocolPlugin};
+
+    #[test]
+    fn plugin_messages_are_registered_in_config() {
+        let mut app = App::new();
+        app.add_plugins(ServerProtocolPlugin {
+            handshake: false,
+            login: false,
+            play: false,
+            status: false,
+            config: true,
+        });
+
+        let incoming = app.world().resource::<IncomingPacketHandlers>();
+        assert_eq!(
+            incoming.name(ProtocolState::Config, 0x02),
+            Some(std::any::type_name::<PluginMessage>())
+        );
+        let outgoing = app.world().resource::<OutgoingPacketIds>();
+        assert_eq!(
+            outgoing.get::<PluginMessage>(ProtocolState::Config),
+            Some(0x01)
+        );
     }
 }
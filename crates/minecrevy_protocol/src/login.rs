@@ -29,6 +29,52 @@ impl McRead for LoginStart {
     }
 }
 
+/// A packet sent by the server to start encrypting the connection, in online
+/// mode.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EncryptionRequest {
+    /// The server ID, which is empty for vanilla servers.
+    pub server_id: String,
+    /// The server's RSA public key, in ASN.1 DER format.
+    pub public_key: Vec<u8>,
+    /// A random token the client must encrypt and send back.
+    pub verify_token: Vec<u8>,
+    /// Whether the client should authenticate with Mojang before responding.
+    pub should_authenticate: bool,
+}
+
+impl McWrite for EncryptionRequest {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.server_id
+            .write(&mut writer, StringArgs { max_len: Some(20) })?;
+        self.public_key.write(&mut writer, ListArgs::default())?;
+        self.verify_token.write(&mut writer, ListArgs::default())?;
+        self.should_authenticate.write(writer, ())
+    }
+}
+
+/// A packet sent by the client in response to an [`EncryptionRequest`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct EncryptionResponse {
+    /// The shared secret, encrypted with the server's public key.
+    pub shared_secret: Vec<u8>,
+    /// The verify token, encrypted with the server's public key.
+    pub verify_token: Vec<u8>,
+}
+
+impl McRead for EncryptionResponse {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            shared_secret: Vec::read(&mut reader, ListArgs::default())?,
+            verify_token: Vec::read(reader, ListArgs::default())?,
+        })
+    }
+}
+
 /// A packet sent by the client to finish the login process.
 #[derive(Clone, PartialEq, Debug)]
 pub struct LoginAcknowledged;
@@ -50,6 +96,9 @@ pub struct LoginSuccess {
     pub username: String,
     /// The properties of the player, such as their skin.
     pub properties: Vec<Property>,
+    /// Whether the client should disconnect on any packet it fails to decode,
+    /// rather than skipping it.
+    pub strict_error_handling: bool,
 }
 
 impl McWrite for LoginSuccess {
@@ -60,12 +109,13 @@ impl McWrite for LoginSuccess {
         self.username
             .write(&mut writer, StringArgs { max_len: Some(16) })?;
         self.properties.write(
-            writer,
+            &mut writer,
             ListArgs {
                 length: ListLength::VarInt,
                 inner: (),
             },
         )?;
+        self.strict_error_handling.write(writer, ())?;
         Ok(())
     }
 }
@@ -81,6 +131,36 @@ pub struct Property {
     pub signature: Option<String>,
 }
 
+impl McRead for Property {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            name: String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+            value: String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+            signature: Option::<String>::read(
+                reader,
+                OptionArgs {
+                    tag: OptionTag::Bool,
+                    inner: StringArgs {
+                        max_len: Some(32767),
+                    },
+                },
+            )?,
+        })
+    }
+}
+
 impl McWrite for Property {
     type Args = ();
 
@@ -132,3 +212,29 @@ impl McWrite for Disconnect {
         self.reason.write_default(writer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::McWrite;
+    use uuid::Uuid;
+
+    use super::LoginSuccess;
+
+    #[test]
+    fn login_success_ends_with_strict_error_handling() {
+        let packet = LoginSuccess {
+            uuid: Uuid::nil(),
+            username: "Notch".to_owned(),
+            properties: Vec::new(),
+            strict_error_handling: true,
+        };
+        let mut bytes = Vec::new();
+        packet.write_default(&mut bytes).unwrap();
+
+        let mut expected = vec![0; 16];
+        expected.extend([5, b'N', b'o', b't', b'c', b'h']);
+        // No properties, then strict error handling.
+        expected.extend([0, 1]);
+        assert_eq!(bytes, expected);
+    }
+}
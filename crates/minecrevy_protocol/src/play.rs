@@ -1 +1,4540 @@
 //! Minecraft protocol packet definitions in the `Play` state.
+
+use std::{collections::BTreeMap, io};
+
+use glam::{DVec3, IVec3, Vec3};
+use minecrevy_io::{
+    angle::Angle,
+    args::{IVec3Args, IntArgs, ListArgs, ListLength, OptionArgs, OptionTag, StringArgs},
+    error::DecodeError,
+    nbt::{CompoundWriter, Tag, TAG_END},
+    prelude::{ReadMinecraftExt, WriteMinecraftExt},
+    McRead, McWrite,
+};
+use minecrevy_text::{Text, TextArgs};
+use minecrevy_util::{
+    attribute::AttributeModifier,
+    bitset::BitSet,
+    boss_bar::{BossBarColor, BossBarDivision},
+    color::NamedColor,
+    difficulty::Difficulty,
+    direction::Direction,
+    game_mode::{GameMode, PreviousGameMode},
+    hand::Hand,
+    item::ItemStack,
+    key::Key,
+    num::velocity_to_fixed,
+    pose::Pose,
+    potion::PotionEffect,
+    registry::Registry,
+    scoreboard::{DisplaySlot, ObjectiveRenderType},
+    sound::SoundCategory,
+    team::{CollisionRule, NameTagVisibility},
+    ticks::Ticks,
+};
+use uuid::Uuid;
+
+use crate::{
+    command::{CommandNode, NodeKind},
+    login::Property,
+};
+
+/// A packet sent by the server to ensure the client is still connected.
+///
+/// The client should respond with a [`KeepAlive`] packet containing the same ID.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeepAlive(pub i64);
+
+impl McRead for KeepAlive {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self(i64::read(reader, IntArgs { varint: false })?))
+    }
+}
+
+impl McWrite for KeepAlive {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        i64::write(&self.0, writer, IntArgs { varint: false })
+    }
+}
+
+/// A packet sent by the server to measure the client's latency.
+///
+/// The client should respond with a [`Pong`] packet containing the same ID.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ping(pub i32);
+
+impl McWrite for Ping {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        i32::write(&self.0, writer, IntArgs { varint: false })
+    }
+}
+
+/// A packet sent by the client in response to a [`Ping`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pong(pub i32);
+
+impl McRead for Pong {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self(i32::read(reader, IntArgs { varint: false })?))
+    }
+}
+
+/// A packet sent by the server to add or update entries in the client's tab list.
+///
+/// All entries in a single packet must have the same set of fields present,
+/// as the protocol only encodes one set of actions for the whole packet.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabListUpdate {
+    /// The entries to add or update.
+    pub entries: Vec<TabListEntry>,
+}
+
+impl McWrite for TabListUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let actions = self.entries.first().map_or(0, TabListEntry::actions);
+        if self.entries.iter().any(|entry| entry.actions() != actions) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "tab list entries must all have the same fields present",
+            ));
+        }
+
+        actions.write(&mut writer, ())?;
+        self.entries.write(writer, ListArgs::default())?;
+        Ok(())
+    }
+}
+
+/// A single player's entry in a [`TabListUpdate`] packet.
+///
+/// Each field that is [`Some`] corresponds to an action applied to the entry.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabListEntry {
+    /// The UUID of the player.
+    pub uuid: Uuid,
+    /// Adds the player to the tab list with the given profile.
+    pub add_player: Option<TabListAddPlayer>,
+    /// Updates the game mode of the player.
+    pub game_mode: Option<GameMode>,
+    /// Updates whether the player is listed in the tab list.
+    pub listed: Option<bool>,
+    /// Updates the latency of the player, in milliseconds.
+    pub latency: Option<i32>,
+    /// Updates the name shown for the player, where [`Some(None)`](Some)
+    /// shows their username.
+    pub display_name: Option<Option<Text>>,
+}
+
+impl TabListEntry {
+    const ADD_PLAYER: u8 = 0x01;
+    const UPDATE_GAME_MODE: u8 = 0x04;
+    const UPDATE_LISTED: u8 = 0x08;
+    const UPDATE_LATENCY: u8 = 0x10;
+    const UPDATE_DISPLAY_NAME: u8 = 0x20;
+
+    /// Creates a new [`TabListEntry`] for the given player with no actions.
+    pub fn new(uuid: Uuid) -> Self {
+        Self {
+            uuid,
+            add_player: None,
+            game_mode: None,
+            listed: None,
+            latency: None,
+            display_name: None,
+        }
+    }
+
+    /// Returns the bitmask of actions present in this entry.
+    fn actions(&self) -> u8 {
+        let mut actions = 0;
+        if self.add_player.is_some() {
+            actions |= Self::ADD_PLAYER;
+        }
+        if self.game_mode.is_some() {
+            actions |= Self::UPDATE_GAME_MODE;
+        }
+        if self.listed.is_some() {
+            actions |= Self::UPDATE_LISTED;
+        }
+        if self.latency.is_some() {
+            actions |= Self::UPDATE_LATENCY;
+        }
+        if self.display_name.is_some() {
+            actions |= Self::UPDATE_DISPLAY_NAME;
+        }
+        actions
+    }
+}
+
+impl McWrite for TabListEntry {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.uuid.write_default(&mut writer)?;
+        if let Some(add_player) = &self.add_player {
+            add_player.write(&mut writer, ())?;
+        }
+        if let Some(game_mode) = self.game_mode {
+            i32::from(game_mode.to_id()).write(&mut writer, IntArgs { varint: true })?;
+        }
+        if let Some(listed) = self.listed {
+            listed.write(&mut writer, ())?;
+        }
+        if let Some(latency) = self.latency {
+            latency.write(&mut writer, IntArgs { varint: true })?;
+        }
+        if let Some(display_name) = &self.display_name {
+            display_name.write(
+                &mut writer,
+                OptionArgs {
+                    tag: OptionTag::Bool,
+                    inner: TextArgs {
+                        nbt: true,
+                        ..Default::default()
+                    },
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The profile of a player being added to the tab list.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabListAddPlayer {
+    /// The username of the player.
+    pub username: String,
+    /// The properties of the player, such as their skin.
+    pub properties: Vec<Property>,
+}
+
+impl McWrite for TabListAddPlayer {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.username
+            .write(&mut writer, StringArgs { max_len: Some(16) })?;
+        self.properties.write(writer, ListArgs::default())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to remove players from the client's tab list.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabListRemove {
+    /// The UUIDs of the players to remove.
+    pub uuids: Vec<Uuid>,
+}
+
+impl McWrite for TabListRemove {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        (self.uuids.len() as i32).write(&mut writer, IntArgs { varint: true })?;
+        for uuid in &self.uuids {
+            uuid.write_default(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the text shown above and below the
+/// client's tab list.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabListHeaderAndFooter {
+    /// The text shown above the tab list, or empty text for none.
+    pub header: Text,
+    /// The text shown below the tab list, or empty text for none.
+    pub footer: Text,
+}
+
+impl McWrite for TabListHeaderAndFooter {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let args = TextArgs {
+            nbt: true,
+            ..Default::default()
+        };
+        self.header.write(&mut writer, args.clone())?;
+        self.footer.write(&mut writer, args)?;
+        Ok(())
+    }
+}
+
+/// A packet sent by either side to exchange arbitrary data on a named channel.
+///
+/// Used by mods and plugins, as well as by the vanilla `minecraft:brand` channel.
+/// The packet has the same layout in the configuration state, where it's also
+/// registered.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PluginMessage {
+    /// The channel the data is sent on.
+    pub channel: Key,
+    /// The channel-specific data.
+    pub data: Vec<u8>,
+}
+
+impl PluginMessage {
+    /// The maximum length of the data sent by clients.
+    pub const MAX_INCOMING_LEN: usize = 32767;
+}
+
+impl McRead for PluginMessage {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let channel = Key::read(&mut reader, ())?;
+        let data = reader.read_bytes_remaining()?;
+        if data.len() > Self::MAX_INCOMING_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "exceeded max plugin message length (max: {}, actual: {})",
+                    Self::MAX_INCOMING_LEN,
+                    data.len()
+                ),
+            ));
+        }
+
+        Ok(Self { channel, data })
+    }
+}
+
+impl McWrite for PluginMessage {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.channel.write(&mut writer, ())?;
+        writer.write_bytes_remaining(&self.data)?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to move the player, such as when teleporting.
+///
+/// The client should respond with a [`ConfirmTeleport`] packet containing the
+/// same teleport ID.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SyncPlayerPosition {
+    /// The position of the player.
+    pub position: DVec3,
+    /// The yaw of the player, in degrees.
+    pub yaw: f32,
+    /// The pitch of the player, in degrees.
+    pub pitch: f32,
+    /// A bitmask of which fields are relative to the player's current values,
+    /// rather than absolute.
+    ///
+    /// | Bit    | Field   |
+    /// |--------|---------|
+    /// | `0x01` | `x`     |
+    /// | `0x02` | `y`     |
+    /// | `0x04` | `z`     |
+    /// | `0x08` | `yaw`   |
+    /// | `0x10` | `pitch` |
+    pub relative: u8,
+    /// The ID of the teleport, which the client confirms.
+    pub teleport_id: i32,
+}
+
+impl McWrite for SyncPlayerPosition {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.position.write(&mut writer, ())?;
+        self.yaw.write(&mut writer, ())?;
+        self.pitch.write(&mut writer, ())?;
+        self.relative.write(&mut writer, ())?;
+        self.teleport_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the client to confirm a [`SyncPlayerPosition`] packet.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfirmTeleport {
+    /// The ID of the teleport being confirmed.
+    pub teleport_id: i32,
+}
+
+impl McRead for ConfirmTeleport {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            teleport_id: i32::read(reader, IntArgs { varint: true })?,
+        })
+    }
+}
+
+/// A packet sent by the client when the player moves.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetPlayerPosition {
+    /// The position of the player's feet.
+    pub position: DVec3,
+    /// Whether the player is on the ground.
+    pub on_ground: bool,
+}
+
+impl McRead for SetPlayerPosition {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            position: DVec3::read(&mut reader, ())?,
+            on_ground: bool::read(reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by the client when the player moves and turns.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetPlayerPositionAndRotation {
+    /// The position of the player's feet.
+    pub position: DVec3,
+    /// The yaw of the player, in degrees.
+    pub yaw: f32,
+    /// The pitch of the player, in degrees.
+    pub pitch: f32,
+    /// Whether the player is on the ground.
+    pub on_ground: bool,
+}
+
+impl McRead for SetPlayerPositionAndRotation {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            position: DVec3::read(&mut reader, ())?,
+            yaw: f32::read(&mut reader, ())?,
+            pitch: f32::read(&mut reader, ())?,
+            on_ground: bool::read(reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by spectating clients to teleport to the entity with the given UUID.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Spectate {
+    /// The UUID of the entity to teleport to.
+    pub target: Uuid,
+}
+
+impl McRead for Spectate {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            target: Uuid::read(reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by the client when it swings one of its arms.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SwingArm {
+    /// The hand that was swung.
+    pub hand: Hand,
+}
+
+impl McRead for SwingArm {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            hand: Hand::read(reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by the server to play an animation on an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntityAnimation {
+    /// The network ID of the entity.
+    pub entity_id: i32,
+    /// The animation to play.
+    pub animation: Animation,
+}
+
+impl McWrite for EntityAnimation {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.animation.to_id().write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// An animation played on an entity by an [`EntityAnimation`] packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Animation {
+    /// Swings the entity's main arm.
+    SwingMainArm,
+    /// Makes the entity leave its bed.
+    LeaveBed,
+    /// Swings the entity's off arm.
+    SwingOffArm,
+    /// Shows critical hit particles around the entity.
+    CriticalEffect,
+    /// Shows enchanted critical hit particles around the entity.
+    MagicCriticalEffect,
+}
+
+impl Animation {
+    /// Returns the swing animation for the given [`Hand`].
+    pub fn swing(hand: Hand) -> Self {
+        match hand {
+            Hand::MainHand => Self::SwingMainArm,
+            Hand::OffHand => Self::SwingOffArm,
+        }
+    }
+
+    /// Returns the protocol ID of this [`Animation`].
+    pub fn to_id(self) -> u8 {
+        match self {
+            Self::SwingMainArm => 0,
+            Self::LeaveBed => 2,
+            Self::SwingOffArm => 3,
+            Self::CriticalEffect => 4,
+            Self::MagicCriticalEffect => 5,
+        }
+    }
+}
+
+/// A packet sent by the server to set the values of a player's statistics,
+/// such as in response to the client opening its statistics screen.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct StatisticsUpdate {
+    /// The statistics to set.
+    pub statistics: Vec<Statistic>,
+}
+
+impl McWrite for StatisticsUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        write_len(&mut writer, self.statistics.len(), true)?;
+        for statistic in &self.statistics {
+            statistic.write(&mut writer, ())?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of one of a player's statistics, such as the number of stone
+/// blocks they've mined.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct Statistic {
+    /// The category of the statistic.
+    pub category: StatCategory,
+    /// The ID of the statistic in the registry of its category, such as the
+    /// block registry for [`StatCategory::Mined`].
+    pub statistic_id: i32,
+    /// The value of the statistic.
+    pub value: i32,
+}
+
+impl McWrite for Statistic {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.category
+            .to_id()
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.statistic_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.value.write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// The category of a [`Statistic`], which decides the registry its ID is
+/// from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum StatCategory {
+    /// The number of times a block was mined, keyed by block.
+    Mined,
+    /// The number of times an item was crafted, keyed by item.
+    Crafted,
+    /// The number of times an item was used, keyed by item.
+    Used,
+    /// The number of times an item broke, keyed by item.
+    Broken,
+    /// The number of times an item was picked up, keyed by item.
+    PickedUp,
+    /// The number of times an item was dropped, keyed by item.
+    Dropped,
+    /// The number of times an entity was killed, keyed by entity type.
+    Killed,
+    /// The number of times the player was killed by an entity, keyed by
+    /// entity type.
+    KilledBy,
+    /// Statistics that aren't tied to a registry, such as the distance walked,
+    /// keyed by the `minecraft:custom_stat` registry.
+    Custom,
+}
+
+impl StatCategory {
+    /// Returns the protocol ID of this [`StatCategory`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::Mined => 0,
+            Self::Crafted => 1,
+            Self::Used => 2,
+            Self::Broken => 3,
+            Self::PickedUp => 4,
+            Self::Dropped => 5,
+            Self::Killed => 6,
+            Self::KilledBy => 7,
+            Self::Custom => 8,
+        }
+    }
+}
+
+/// A packet sent by the client when it digs a block, or performs one of a few
+/// other item-related actions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayerAction {
+    /// The action being performed.
+    pub status: DigStatus,
+    /// The position of the block being acted on.
+    pub position: IVec3,
+    /// The face of the block being acted on.
+    pub face: Direction,
+    /// The sequence number of the action, which the server acknowledges with
+    /// an [`AckBlockChange`] packet.
+    pub sequence: i32,
+}
+
+impl McRead for PlayerAction {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let status = i32::read(&mut reader, IntArgs { varint: true })?;
+        let status = DigStatus::from_id(status)
+            .ok_or_else(|| DecodeError::invalid_discriminant::<DigStatus>(status))?;
+        let position = IVec3::read(&mut reader, IVec3Args { compressed: true })?;
+        let face = u8::read(&mut reader, ())?;
+        let face = Direction::from_id(i32::from(face))
+            .ok_or_else(|| DecodeError::invalid_discriminant::<Direction>(face))?;
+
+        Ok(Self {
+            status,
+            position,
+            face,
+            sequence: i32::read(&mut reader, IntArgs { varint: true })?,
+        })
+    }
+}
+
+/// The action performed by a [`PlayerAction`] packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum DigStatus {
+    /// The player started digging a block.
+    ///
+    /// Blocks are broken immediately by players in creative mode.
+    StartedDigging,
+    /// The player stopped digging a block before breaking it.
+    CancelledDigging,
+    /// The player finished digging a block.
+    FinishedDigging,
+    /// The player dropped their held item stack.
+    DropItemStack,
+    /// The player dropped a single item from their held item stack.
+    DropItem,
+    /// The player released the item they were using, such as a drawn bow.
+    ReleaseUseItem,
+    /// The player swapped the items in their main and off hands.
+    SwapItemInHand,
+}
+
+impl DigStatus {
+    /// Returns the [`DigStatus`] with the given protocol ID, or [`None`] if
+    /// the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::StartedDigging),
+            1 => Some(Self::CancelledDigging),
+            2 => Some(Self::FinishedDigging),
+            3 => Some(Self::DropItemStack),
+            4 => Some(Self::DropItem),
+            5 => Some(Self::ReleaseUseItem),
+            6 => Some(Self::SwapItemInHand),
+            _ => None,
+        }
+    }
+}
+
+/// A packet sent by the client when it uses its held item on a block, such as
+/// to place a block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UseItemOn {
+    /// The hand holding the item.
+    pub hand: Hand,
+    /// The position of the block being clicked.
+    pub position: IVec3,
+    /// The face of the block being clicked.
+    pub face: Direction,
+    /// The position of the cursor on the clicked face, from `0.0` to `1.0`.
+    pub cursor: Vec3,
+    /// Whether the player's head is inside of a block.
+    pub inside_block: bool,
+    /// The sequence number of the action, which the server acknowledges with
+    /// an [`AckBlockChange`] packet.
+    pub sequence: i32,
+}
+
+impl McRead for UseItemOn {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let hand = Hand::read(&mut reader, ())?;
+        let position = IVec3::read(&mut reader, IVec3Args { compressed: true })?;
+        let face = i32::read(&mut reader, IntArgs { varint: true })?;
+        let face = Direction::from_id(face)
+            .ok_or_else(|| DecodeError::invalid_discriminant::<Direction>(face))?;
+
+        Ok(Self {
+            hand,
+            position,
+            face,
+            cursor: Vec3::read(&mut reader, ())?,
+            inside_block: bool::read(&mut reader, ())?,
+            sequence: i32::read(&mut reader, IntArgs { varint: true })?,
+        })
+    }
+}
+
+/// A packet sent by the client when it selects a different hotbar slot.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetHeldItem {
+    /// The selected hotbar slot, from `0` to `8`.
+    pub slot: i16,
+}
+
+impl McRead for SetHeldItem {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            slot: i16::read(reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by clients in creative mode to set the item stack in one of
+/// their inventory slots.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetCreativeModeSlot {
+    /// The inventory slot being set, where `36` to `44` are the hotbar slots.
+    pub slot: i16,
+    /// The item stack put in the slot, which is empty to clear it.
+    pub item: ItemStack,
+}
+
+impl SetCreativeModeSlot {
+    /// The inventory slot of the first hotbar slot.
+    pub const HOTBAR_START: i16 = 36;
+}
+
+impl McRead for SetCreativeModeSlot {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            slot: i16::read(&mut reader, ())?,
+            item: ItemStack::read(reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by the server to change a single block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockUpdate {
+    /// The position of the block.
+    pub position: IVec3,
+    /// The new block state ID of the block.
+    pub block_state: i32,
+}
+
+impl McWrite for BlockUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.position
+            .write(&mut writer, IVec3Args { compressed: true })?;
+        self.block_state
+            .write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the data of a block entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockEntityData {
+    /// The position of the block entity.
+    pub position: IVec3,
+    /// The protocol ID of the block entity type.
+    pub kind: i32,
+    /// The block entity's data, as an NBT compound, or [`None`] to remove it.
+    pub data: Option<Tag>,
+}
+
+impl McWrite for BlockEntityData {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.position
+            .write(&mut writer, IVec3Args { compressed: true })?;
+        self.kind.write(&mut writer, IntArgs { varint: true })?;
+        match &self.data {
+            Some(data) => data.write(&mut writer, ()),
+            None => TAG_END.write(&mut writer, ()),
+        }
+    }
+}
+
+/// A packet sent by the server to open the sign editor for a sign the player
+/// placed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OpenSignEditor {
+    /// The position of the sign.
+    pub position: IVec3,
+    /// Whether to edit the front or back text of the sign.
+    pub is_front_text: bool,
+}
+
+impl McWrite for OpenSignEditor {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.position
+            .write(&mut writer, IVec3Args { compressed: true })?;
+        self.is_front_text.write(&mut writer, ())
+    }
+}
+
+/// A packet sent by the server to acknowledge all block changes made by the
+/// client up to the given sequence number.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AckBlockChange {
+    /// The sequence number being acknowledged.
+    pub sequence: i32,
+}
+
+impl McWrite for AckBlockChange {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.sequence.write(writer, IntArgs { varint: true })
+    }
+}
+
+/// A packet sent by the client when it performs an action, such as sneaking or
+/// sprinting.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntityAction {
+    /// The network ID of the player.
+    pub entity_id: i32,
+    /// The action being performed.
+    pub action: EntityActionKind,
+    /// The strength of a horse jump, from `0` to `100`, or `0` for other actions.
+    pub jump_boost: i32,
+}
+
+impl McRead for EntityAction {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let entity_id = i32::read(&mut reader, IntArgs { varint: true })?;
+        let action = i32::read(&mut reader, IntArgs { varint: true })?;
+        let action = EntityActionKind::from_id(action)
+            .ok_or_else(|| DecodeError::invalid_discriminant::<EntityActionKind>(action))?;
+
+        Ok(Self {
+            entity_id,
+            action,
+            jump_boost: i32::read(&mut reader, IntArgs { varint: true })?,
+        })
+    }
+}
+
+/// The action performed by an [`EntityAction`] packet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum EntityActionKind {
+    /// The player started sneaking.
+    StartSneaking,
+    /// The player stopped sneaking.
+    StopSneaking,
+    /// The player left their bed.
+    LeaveBed,
+    /// The player started sprinting.
+    StartSprinting,
+    /// The player stopped sprinting.
+    StopSprinting,
+    /// The player started charging a jump on their horse.
+    StartHorseJump,
+    /// The player released a jump on their horse.
+    StopHorseJump,
+    /// The player opened the inventory of their vehicle.
+    OpenVehicleInventory,
+    /// The player started gliding with an elytra.
+    StartFlyingWithElytra,
+}
+
+impl EntityActionKind {
+    /// Returns the [`EntityActionKind`] with the given protocol ID, or
+    /// [`None`] if the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::StartSneaking),
+            1 => Some(Self::StopSneaking),
+            2 => Some(Self::LeaveBed),
+            3 => Some(Self::StartSprinting),
+            4 => Some(Self::StopSprinting),
+            5 => Some(Self::StartHorseJump),
+            6 => Some(Self::StopHorseJump),
+            7 => Some(Self::OpenVehicleInventory),
+            8 => Some(Self::StartFlyingWithElytra),
+            _ => None,
+        }
+    }
+}
+
+/// A packet sent by the server to update some of the metadata of an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntityMetadata {
+    /// The network ID of the entity.
+    pub entity_id: i32,
+    /// The metadata entries being updated.
+    pub metadata: Metadata,
+}
+
+impl McRead for EntityMetadata {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            entity_id: i32::read(&mut reader, IntArgs { varint: true })?,
+            metadata: Metadata::read(&mut reader, ())?,
+        })
+    }
+}
+
+impl McWrite for EntityMetadata {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.metadata.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// The metadata entries of an entity, keyed by index.
+///
+/// The meaning of each index depends on the type of entity.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Metadata(pub BTreeMap<u8, MetadataValue>);
+
+impl Metadata {
+    /// The index of the entity flags [`MetadataValue::Byte`], shared by all entities.
+    ///
+    /// | Bit    | Flag            |
+    /// |--------|-----------------|
+    /// | `0x01` | On fire         |
+    /// | `0x02` | Crouching       |
+    /// | `0x08` | Sprinting       |
+    /// | `0x10` | Swimming        |
+    /// | `0x20` | Invisible       |
+    /// | `0x40` | Glowing         |
+    /// | `0x80` | Elytra flying   |
+    pub const FLAGS: u8 = 0;
+
+    /// The index of the [`MetadataValue::Pose`], shared by all entities.
+    pub const POSE: u8 = 6;
+
+    /// The index that terminates the list of entries on the wire.
+    const END: u8 = 0xFF;
+
+    /// Creates a new, empty [`Metadata`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value at the given index, returning the previous value.
+    pub fn insert(&mut self, index: u8, value: MetadataValue) -> Option<MetadataValue> {
+        self.0.insert(index, value)
+    }
+
+    /// Returns the value at the given index, if any.
+    pub fn get(&self, index: u8) -> Option<&MetadataValue> {
+        self.0.get(&index)
+    }
+}
+
+impl McRead for Metadata {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let mut metadata = Self::new();
+        loop {
+            let index = u8::read(&mut reader, ())?;
+            if index == Self::END {
+                return Ok(metadata);
+            }
+            metadata.insert(index, MetadataValue::read(&mut reader, ())?);
+        }
+    }
+}
+
+impl McWrite for Metadata {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        for (index, value) in &self.0 {
+            if *index == Self::END {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "metadata index 0xFF is reserved",
+                ));
+            }
+            index.write(&mut writer, ())?;
+            value.write(&mut writer, ())?;
+        }
+        Self::END.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// The value of a [`Metadata`] entry.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MetadataValue {
+    /// A signed byte, often used for bit flags.
+    Byte(i8),
+    /// A variable-length integer.
+    VarInt(i32),
+    /// A variable-length long.
+    VarLong(i64),
+    /// A floating point number.
+    Float(f32),
+    /// A string.
+    String(String),
+    /// A text component.
+    Text(Text),
+    /// An optional text component.
+    OptionalText(Option<Text>),
+    /// An item stack, which is empty when its count is `0`.
+    Slot(ItemStack),
+    /// A boolean.
+    Bool(bool),
+    /// The rotation around the X, Y, and Z axes, in degrees.
+    Rotations(Vec3),
+    /// A block position.
+    Position(IVec3),
+    /// An optional block position.
+    OptionalPosition(Option<IVec3>),
+    /// A [`Direction`].
+    Direction(Direction),
+    /// An optional UUID.
+    OptionalUuid(Option<Uuid>),
+    /// A block state ID.
+    BlockState(i32),
+    /// An optional block state ID, where `None` is air.
+    OptionalBlockState(Option<i32>),
+    /// The [`Pose`] of the entity.
+    Pose(Pose),
+}
+
+impl MetadataValue {
+    /// Returns the protocol ID of the type of this value.
+    pub fn type_id(&self) -> i32 {
+        match self {
+            Self::Byte(_) => 0,
+            Self::VarInt(_) => 1,
+            Self::VarLong(_) => 2,
+            Self::Float(_) => 3,
+            Self::String(_) => 4,
+            Self::Text(_) => 5,
+            Self::OptionalText(_) => 6,
+            Self::Slot(_) => 7,
+            Self::Bool(_) => 8,
+            Self::Rotations(_) => 9,
+            Self::Position(_) => 10,
+            Self::OptionalPosition(_) => 11,
+            Self::Direction(_) => 12,
+            Self::OptionalUuid(_) => 13,
+            Self::BlockState(_) => 14,
+            Self::OptionalBlockState(_) => 15,
+            Self::Pose(_) => 21,
+        }
+    }
+}
+
+/// Arguments for reading and writing text components in [`MetadataValue`]s.
+fn nbt_text() -> TextArgs {
+    TextArgs {
+        nbt: true,
+        ..Default::default()
+    }
+}
+
+impl McRead for MetadataValue {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let ty = i32::read(&mut reader, IntArgs { varint: true })?;
+        Ok(match ty {
+            0 => Self::Byte(i8::read(reader, ())?),
+            1 => Self::VarInt(i32::read(reader, IntArgs { varint: true })?),
+            2 => Self::VarLong(i64::read(reader, IntArgs { varint: true })?),
+            3 => Self::Float(f32::read(reader, ())?),
+            4 => Self::String(String::read(
+                reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?),
+            5 => Self::Text(Text::read(reader, nbt_text())?),
+            6 => Self::OptionalText(Option::<Text>::read(
+                reader,
+                OptionArgs {
+                    inner: nbt_text(),
+                    ..Default::default()
+                },
+            )?),
+            7 => Self::Slot(ItemStack::read(reader, ())?),
+            8 => Self::Bool(bool::read(reader, ())?),
+            9 => Self::Rotations(Vec3::read(reader, ())?),
+            10 => Self::Position(IVec3::read(reader, IVec3Args { compressed: true })?),
+            11 => Self::OptionalPosition(Option::<IVec3>::read(
+                reader,
+                OptionArgs {
+                    inner: IVec3Args { compressed: true },
+                    ..Default::default()
+                },
+            )?),
+            12 => {
+                let id = i32::read(reader, IntArgs { varint: true })?;
+                Self::Direction(
+                    Direction::from_id(id)
+                        .ok_or_else(|| DecodeError::invalid_discriminant::<Direction>(id))?,
+                )
+            }
+            13 => Self::OptionalUuid(Option::<Uuid>::read_default(reader)?),
+            14 => Self::BlockState(i32::read(reader, IntArgs { varint: true })?),
+            15 => {
+                let id = i32::read(reader, IntArgs { varint: true })?;
+                Self::OptionalBlockState((id != 0).then_some(id))
+            }
+            21 => {
+                let id = i32::read(reader, IntArgs { varint: true })?;
+                Self::Pose(
+                    Pose::from_id(id)
+                        .ok_or_else(|| DecodeError::invalid_discriminant::<Pose>(id))?,
+                )
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported metadata type: {ty}"),
+                ))
+            }
+        })
+    }
+}
+
+impl McWrite for MetadataValue {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.type_id()
+            .write(&mut writer, IntArgs { varint: true })?;
+        match self {
+            Self::Byte(v) => v.write(writer, ()),
+            Self::VarInt(v) => v.write(writer, IntArgs { varint: true }),
+            Self::VarLong(v) => v.write(writer, IntArgs { varint: true }),
+            Self::Float(v) => v.write(writer, ()),
+            Self::String(v) => v.write(
+                writer,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            ),
+            Self::Text(v) => v.write(writer, nbt_text()),
+            Self::OptionalText(v) => v.write(
+                writer,
+                OptionArgs {
+                    inner: nbt_text(),
+                    ..Default::default()
+                },
+            ),
+            Self::Slot(v) => v.write(writer, ()),
+            Self::Bool(v) => v.write(writer, ()),
+            Self::Rotations(v) => v.write(writer, ()),
+            Self::Position(v) => v.write(writer, IVec3Args { compressed: true }),
+            Self::OptionalPosition(v) => v.write(
+                writer,
+                OptionArgs {
+                    inner: IVec3Args { compressed: true },
+                    ..Default::default()
+                },
+            ),
+            Self::Direction(v) => v.to_id().write(writer, IntArgs { varint: true }),
+            Self::OptionalUuid(v) => v.write_default(writer),
+            Self::BlockState(v) => v.write(writer, IntArgs { varint: true }),
+            Self::OptionalBlockState(v) => v.unwrap_or(0).write(writer, IntArgs { varint: true }),
+            Self::Pose(v) => v.to_id().write(writer, IntArgs { varint: true }),
+        }
+    }
+}
+
+/// A packet sent by the client when it finishes editing the text of a sign.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpdateSign {
+    /// The position of the sign.
+    pub position: IVec3,
+    /// Whether the front or back text of the sign was edited.
+    pub is_front_text: bool,
+    /// The four lines of text.
+    pub lines: [String; 4],
+}
+
+impl UpdateSign {
+    /// The maximum length of a single line, in characters.
+    pub const MAX_LINE_LEN: usize = 384;
+}
+
+impl McRead for UpdateSign {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let position = IVec3::read(&mut reader, IVec3Args { compressed: true })?;
+        let is_front_text = bool::read(&mut reader, ())?;
+        let mut read_line = || {
+            String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(Self::MAX_LINE_LEN),
+                },
+            )
+        };
+
+        Ok(Self {
+            position,
+            is_front_text,
+            lines: [read_line()?, read_line()?, read_line()?, read_line()?],
+        })
+    }
+}
+
+/// A packet sent by the client to configure a command block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProgramCommandBlock {
+    /// The position of the command block.
+    pub position: IVec3,
+    /// The command to run.
+    pub command: String,
+    /// The mode of the command block.
+    ///
+    /// `0` for sequence, `1` for auto (repeating), `2` for redstone (impulse).
+    pub mode: i32,
+    /// A bitmask of the command block's flags.
+    ///
+    /// | Bit    | Flag                |
+    /// |--------|---------------------|
+    /// | `0x01` | Track output        |
+    /// | `0x02` | Conditional         |
+    /// | `0x04` | Always active       |
+    pub flags: u8,
+}
+
+impl McRead for ProgramCommandBlock {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            position: IVec3::read(&mut reader, IVec3Args { compressed: true })?,
+            command: String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+            mode: i32::read(&mut reader, IntArgs { varint: true })?,
+            flags: u8::read(&mut reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by the client to configure a jigsaw block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProgramJigsawBlock {
+    /// The position of the jigsaw block.
+    pub position: IVec3,
+    /// The name of this jigsaw block.
+    pub name: Key,
+    /// The name of the jigsaw block this one connects to.
+    pub target: Key,
+    /// The template pool to generate from.
+    pub pool: Key,
+    /// The block state this jigsaw block turns into once generated.
+    pub final_state: String,
+    /// How this jigsaw block joins its target, either `rollable` or `aligned`.
+    pub joint_type: String,
+    /// The priority of this jigsaw block when selecting connections.
+    pub selection_priority: i32,
+    /// The priority of this jigsaw block when placing pieces.
+    pub placement_priority: i32,
+}
+
+impl McRead for ProgramJigsawBlock {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            position: IVec3::read(&mut reader, IVec3Args { compressed: true })?,
+            name: Key::read(&mut reader, ())?,
+            target: Key::read(&mut reader, ())?,
+            pool: Key::read(&mut reader, ())?,
+            final_state: String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+            joint_type: String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+            selection_priority: i32::read(&mut reader, IntArgs { varint: true })?,
+            placement_priority: i32::read(&mut reader, IntArgs { varint: true })?,
+        })
+    }
+}
+
+/// A packet sent by the client to configure a structure block.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProgramStructureBlock {
+    /// The position of the structure block.
+    pub position: IVec3,
+    /// The action to perform.
+    ///
+    /// `0` to update the data, `1` to save, `2` to load, `3` to detect the size.
+    pub action: i32,
+    /// The mode of the structure block.
+    ///
+    /// `0` for save, `1` for load, `2` for corner, `3` for data.
+    pub mode: i32,
+    /// The name of the structure.
+    pub name: String,
+    /// The offset of the structure from the structure block, from `-48` to `48`.
+    pub offset: [i8; 3],
+    /// The size of the structure, from `0` to `48`.
+    pub size: [i8; 3],
+    /// How the structure is mirrored.
+    ///
+    /// `0` for none, `1` for left-right, `2` for front-back.
+    pub mirror: i32,
+    /// How the structure is rotated.
+    ///
+    /// `0` for none, `1` for 90 degrees clockwise, `2` for 180 degrees, and `3`
+    /// for 90 degrees counter-clockwise.
+    pub rotation: i32,
+    /// The custom data of a structure block in data mode.
+    pub metadata: String,
+    /// The fraction of blocks placed when loading the structure, from `0.0` to `1.0`.
+    pub integrity: f32,
+    /// The seed used to choose which blocks are placed, based on [`Self::integrity`].
+    pub seed: i64,
+    /// A bitmask of the structure block's flags.
+    ///
+    /// | Bit    | Flag              |
+    /// |--------|-------------------|
+    /// | `0x01` | Ignore entities   |
+    /// | `0x02` | Show air          |
+    /// | `0x04` | Show bounding box |
+    pub flags: u8,
+}
+
+impl McRead for ProgramStructureBlock {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            position: IVec3::read(&mut reader, IVec3Args { compressed: true })?,
+            action: i32::read(&mut reader, IntArgs { varint: true })?,
+            mode: i32::read(&mut reader, IntArgs { varint: true })?,
+            name: String::read(
+                &mut reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+            offset: <[i8; 3]>::read_default(&mut reader)?,
+            size: <[i8; 3]>::read_default(&mut reader)?,
+            mirror: i32::read(&mut reader, IntArgs { varint: true })?,
+            rotation: i32::read(&mut reader, IntArgs { varint: true })?,
+            metadata: String::read(&mut reader, StringArgs { max_len: Some(128) })?,
+            integrity: f32::read(&mut reader, ())?,
+            seed: i64::read(&mut reader, IntArgs { varint: true })?,
+            flags: u8::read(&mut reader, ())?,
+        })
+    }
+}
+
+/// A packet sent by the client to run an unsigned command, without the leading `/`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChatCommand {
+    /// The command, such as `gamemode creative`.
+    pub command: String,
+}
+
+impl McRead for ChatCommand {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            command: String::read(
+                reader,
+                StringArgs {
+                    max_len: Some(32767),
+                },
+            )?,
+        })
+    }
+}
+
+/// A packet sent by the server to display a system message in the client's chat.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SystemChat {
+    /// The message to display.
+    pub content: Text,
+    /// Whether the message is displayed above the hotbar instead of in chat.
+    pub overlay: bool,
+}
+
+impl SystemChat {
+    /// Creates a new [`SystemChat`] that displays the given message in chat.
+    ///
+    /// System messages aren't signed, so clients show them without the
+    /// warnings given to unverified player chat.
+    pub fn new(content: impl Into<Text>) -> Self {
+        Self {
+            content: content.into(),
+            overlay: false,
+        }
+    }
+
+    /// Creates a new [`SystemChat`] that displays the given message above the
+    /// hotbar.
+    pub fn overlay(content: impl Into<Text>) -> Self {
+        Self {
+            content: content.into(),
+            overlay: true,
+        }
+    }
+}
+
+impl McWrite for SystemChat {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.content.write(
+            &mut writer,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )?;
+        self.overlay.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to spawn one or more particles.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpawnParticle {
+    /// Whether the particles are visible from up to 512 blocks away, instead
+    /// of 256.
+    pub long_distance: bool,
+    /// The position to spawn the particles at.
+    pub position: DVec3,
+    /// The random offset of each particle, multiplied by a gaussian random number.
+    pub offset: Vec3,
+    /// The maximum speed of each particle.
+    pub max_speed: f32,
+    /// The number of particles to spawn.
+    pub count: i32,
+    /// The protocol ID of the particle type.
+    pub particle: i32,
+    /// The data of the particle type, which must match [`Self::particle`].
+    pub data: ParticleData,
+}
+
+impl McRead for SpawnParticle {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let long_distance = bool::read(&mut reader, ())?;
+        let position = DVec3::read(&mut reader, ())?;
+        let offset = Vec3::read(&mut reader, ())?;
+        let max_speed = f32::read(&mut reader, ())?;
+        let count = i32::read(&mut reader, IntArgs { varint: false })?;
+        let particle = i32::read(&mut reader, IntArgs { varint: true })?;
+
+        Ok(Self {
+            long_distance,
+            position,
+            offset,
+            max_speed,
+            count,
+            particle,
+            data: ParticleData::read(&mut reader, particle)?,
+        })
+    }
+}
+
+impl McWrite for SpawnParticle {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.long_distance.write(&mut writer, ())?;
+        self.position.write(&mut writer, ())?;
+        self.offset.write(&mut writer, ())?;
+        self.max_speed.write(&mut writer, ())?;
+        self.count.write(&mut writer, IntArgs { varint: false })?;
+        self.particle.write(&mut writer, IntArgs { varint: true })?;
+        self.data.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// The data carried by a particle, which depends on its particle type.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum ParticleData {
+    /// Most particle types carry no data.
+    #[default]
+    None,
+    /// The block state ID of a [`ParticleData::BLOCK`] particle.
+    Block(i32),
+    /// The block state ID of a [`ParticleData::BLOCK_MARKER`] particle.
+    BlockMarker(i32),
+    /// The block state ID of a [`ParticleData::FALLING_DUST`] particle.
+    FallingDust(i32),
+    /// The color and scale of a [`ParticleData::DUST`] particle.
+    Dust {
+        /// The red, green, and blue components, from `0.0` to `1.0`.
+        color: Vec3,
+        /// The scale of the particle, from `0.01` to `4.0`.
+        scale: f32,
+    },
+    /// The colors and scale of a [`ParticleData::DUST_COLOR_TRANSITION`] particle.
+    DustColorTransition {
+        /// The starting red, green, and blue components, from `0.0` to `1.0`.
+        from: Vec3,
+        /// The ending red, green, and blue components, from `0.0` to `1.0`.
+        to: Vec3,
+        /// The scale of the particle, from `0.01` to `4.0`.
+        scale: f32,
+    },
+    /// The ARGB color of a [`ParticleData::ENTITY_EFFECT`] particle.
+    EntityEffect(i32),
+    /// The roll of a [`ParticleData::SCULK_CHARGE`] particle, in radians.
+    SculkCharge(f32),
+    /// The item stack of an [`ParticleData::ITEM`] particle.
+    Item(ItemStack),
+}
+
+impl ParticleData {
+    /// The protocol ID of the `minecraft:block` particle type.
+    pub const BLOCK: i32 = 1;
+    /// The protocol ID of the `minecraft:block_marker` particle type.
+    pub const BLOCK_MARKER: i32 = 2;
+    /// The protocol ID of the `minecraft:dust` particle type.
+    pub const DUST: i32 = 13;
+    /// The protocol ID of the `minecraft:dust_color_transition` particle type.
+    pub const DUST_COLOR_TRANSITION: i32 = 14;
+    /// The protocol ID of the `minecraft:entity_effect` particle type.
+    pub const ENTITY_EFFECT: i32 = 20;
+    /// The protocol ID of the `minecraft:falling_dust` particle type.
+    pub const FALLING_DUST: i32 = 28;
+    /// The protocol ID of the `minecraft:sculk_charge` particle type.
+    pub const SCULK_CHARGE: i32 = 35;
+    /// The protocol ID of the `minecraft:item` particle type.
+    pub const ITEM: i32 = 44;
+    /// The protocol ID of the `minecraft:vibration` particle type.
+    pub const VIBRATION: i32 = 45;
+    /// The protocol ID of the `minecraft:shriek` particle type.
+    pub const SHRIEK: i32 = 99;
+    /// The protocol ID of the `minecraft:dust_pillar` particle type.
+    pub const DUST_PILLAR: i32 = 105;
+    /// The highest protocol ID of a particle type.
+    pub const MAX_ID: i32 = 108;
+}
+
+impl McRead for ParticleData {
+    /// The protocol ID of the particle type.
+    type Args = i32;
+
+    fn read(mut reader: impl io::Read, particle: Self::Args) -> io::Result<Self> {
+        Ok(match particle {
+            Self::BLOCK => Self::Block(i32::read(reader, IntArgs { varint: true })?),
+            Self::BLOCK_MARKER => Self::BlockMarker(i32::read(reader, IntArgs { varint: true })?),
+            Self::FALLING_DUST => Self::FallingDust(i32::read(reader, IntArgs { varint: true })?),
+            Self::DUST => Self::Dust {
+                color: Vec3::read(&mut reader, ())?,
+                scale: f32::read(&mut reader, ())?,
+            },
+            Self::DUST_COLOR_TRANSITION => Self::DustColorTransition {
+                from: Vec3::read(&mut reader, ())?,
+                to: Vec3::read(&mut reader, ())?,
+                scale: f32::read(&mut reader, ())?,
+            },
+            Self::ENTITY_EFFECT => {
+                Self::EntityEffect(i32::read(reader, IntArgs { varint: false })?)
+            }
+            Self::SCULK_CHARGE => Self::SculkCharge(f32::read(reader, ())?),
+            Self::ITEM => Self::Item(ItemStack::read(reader, ())?),
+            Self::VIBRATION | Self::SHRIEK | Self::DUST_PILLAR => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("reading data of particle {particle} is not supported"),
+                ))
+            }
+            0..=Self::MAX_ID => Self::None,
+            _ => return Err(DecodeError::invalid_discriminant::<Self>(particle).into()),
+        })
+    }
+}
+
+impl McWrite for ParticleData {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        match self {
+            Self::None => Ok(()),
+            Self::Block(state) | Self::BlockMarker(state) | Self::FallingDust(state) => {
+                state.write(writer, IntArgs { varint: true })
+            }
+            Self::Dust { color, scale } => {
+                color.write(&mut writer, ())?;
+                scale.write(&mut writer, ())
+            }
+            Self::DustColorTransition { from, to, scale } => {
+                from.write(&mut writer, ())?;
+                to.write(&mut writer, ())?;
+                scale.write(&mut writer, ())
+            }
+            Self::EntityEffect(color) => color.write(writer, IntArgs { varint: false }),
+            Self::SculkCharge(roll) => roll.write(writer, ()),
+            Self::Item(stack) => stack.write(writer, ()),
+        }
+    }
+}
+
+/// A packet sent by the server to declare the commands available to the client,
+/// as a flat array of [`CommandNode`]s.
+///
+/// See [`CommandTree`](crate::command::CommandTree) for building a valid graph.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DeclareCommands {
+    /// The nodes of the command graph.
+    pub nodes: Vec<CommandNode>,
+    /// The index of the root node.
+    pub root: i32,
+}
+
+impl DeclareCommands {
+    /// Checks that the root index points at a root node, and that all child
+    /// and redirect indices are in range.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid index.
+    pub fn validate(&self) -> io::Result<()> {
+        let in_range = |i: i32| usize::try_from(i).is_ok_and(|i| i < self.nodes.len());
+        let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+
+        if !in_range(self.root) || !matches!(self.nodes[self.root as usize].kind, NodeKind::Root) {
+            return invalid(format!("root index {} is not a root node", self.root));
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            for &child in node.children.iter().chain(&node.redirect) {
+                if !in_range(child) {
+                    return invalid(format!("node {i} references missing node {child}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl McWrite for DeclareCommands {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.validate()?;
+
+        let len = i32::try_from(self.nodes.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        len.write(&mut writer, IntArgs { varint: true })?;
+        for node in &self.nodes {
+            node.write(&mut writer, ())?;
+        }
+        self.root.write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to update the light of a chunk column.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpdateLight {
+    /// The X coordinate of the chunk.
+    pub chunk_x: i32,
+    /// The Z coordinate of the chunk.
+    pub chunk_z: i32,
+    /// The light of the chunk's sections.
+    pub light: LightData,
+}
+
+impl McWrite for UpdateLight {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.chunk_x.write(&mut writer, IntArgs { varint: true })?;
+        self.chunk_z.write(&mut writer, IntArgs { varint: true })?;
+        self.light.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// The sky and block light of each section of a chunk column.
+///
+/// There is one more section below and above the world than the world has
+/// sections, so that light can spread into the world from outside of it.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct LightData {
+    /// The sky light of each section, from the bottom up.
+    pub sky: Vec<LightSection>,
+    /// The block light of each section, from the bottom up.
+    pub block: Vec<LightSection>,
+}
+
+impl LightData {
+    /// Creates a new [`LightData`] for a world with the given number of
+    /// sections, where every section is [`LightSection::Unchanged`].
+    pub fn new(world_sections: usize) -> Self {
+        Self {
+            sky: vec![LightSection::Unchanged; world_sections + 2],
+            block: vec![LightSection::Unchanged; world_sections + 2],
+        }
+    }
+}
+
+/// Returns the bitsets of populated and empty sections.
+fn light_masks(sections: &[LightSection]) -> (BitSet, BitSet) {
+    let mut populated = BitSet::new();
+    let mut empty = BitSet::new();
+    for (i, section) in sections.iter().enumerate() {
+        match section {
+            LightSection::Unchanged => {}
+            LightSection::Empty => empty.set(i),
+            LightSection::Populated(_) => populated.set(i),
+        }
+    }
+    (populated, empty)
+}
+
+impl McRead for LightData {
+    /// The number of sections in the world, not including the sections below
+    /// and above it.
+    type Args = usize;
+
+    fn read(mut reader: impl io::Read, world_sections: Self::Args) -> io::Result<Self> {
+        let sky_mask = BitSet::read(&mut reader, ())?;
+        let block_mask = BitSet::read(&mut reader, ())?;
+        let empty_sky_mask = BitSet::read(&mut reader, ())?;
+        let empty_block_mask = BitSet::read(&mut reader, ())?;
+
+        let mut light = Self::new(world_sections);
+        for (sections, mask, empty_mask) in [
+            (&mut light.sky, &sky_mask, &empty_sky_mask),
+            (&mut light.block, &block_mask, &empty_block_mask),
+        ] {
+            let len = i32::read(&mut reader, IntArgs { varint: true })?;
+            if usize::try_from(len).ok() != Some(mask.count_ones()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("expected {} light arrays, got {len}", mask.count_ones()),
+                ));
+            }
+
+            for i in empty_mask.iter_set() {
+                let section = sections.get_mut(i).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "light section out of range")
+                })?;
+                *section = LightSection::Empty;
+            }
+            for i in mask.iter_set() {
+                let section = sections.get_mut(i).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "light section out of range")
+                })?;
+                let len = i32::read(&mut reader, IntArgs { varint: true })?;
+                if len != LightSection::LEN as i32 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid light array length: {len}"),
+                    ));
+                }
+                let mut data = Box::new([0; LightSection::LEN]);
+                reader.read_exact(&mut data[..])?;
+                *section = LightSection::Populated(data);
+            }
+        }
+
+        Ok(light)
+    }
+}
+
+impl McWrite for LightData {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let (sky_mask, empty_sky_mask) = light_masks(&self.sky);
+        let (block_mask, empty_block_mask) = light_masks(&self.block);
+        sky_mask.write(&mut writer, ())?;
+        block_mask.write(&mut writer, ())?;
+        empty_sky_mask.write(&mut writer, ())?;
+        empty_block_mask.write(&mut writer, ())?;
+
+        for (sections, mask) in [(&self.sky, &sky_mask), (&self.block, &block_mask)] {
+            let len = i32::try_from(mask.count_ones())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            len.write(&mut writer, IntArgs { varint: true })?;
+            for section in sections {
+                if let LightSection::Populated(data) = section {
+                    (LightSection::LEN as i32).write(&mut writer, IntArgs { varint: true })?;
+                    writer.write_all(&data[..])?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The light of a single 16x16x16 chunk section.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum LightSection {
+    /// The client keeps its current light for this section.
+    #[default]
+    Unchanged,
+    /// The section has no light.
+    Empty,
+    /// The light level of each block, as 4-bit nibbles in YZX order.
+    Populated(Box<[u8; LightSection::LEN]>),
+}
+
+impl LightSection {
+    /// The number of bytes of a populated section, with two blocks per byte.
+    pub const LEN: usize = 2048;
+}
+
+/// A packet sent by the server to load a chunk column, along with its light.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChunkDataAndLight {
+    /// The X coordinate of the chunk.
+    pub chunk_x: i32,
+    /// The Z coordinate of the chunk.
+    pub chunk_z: i32,
+    /// The blocks, biomes, and block entities of the chunk.
+    pub chunk: ChunkData,
+    /// The light of the chunk's sections.
+    pub light: LightData,
+}
+
+impl McWrite for ChunkDataAndLight {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.chunk_x.write(&mut writer, IntArgs { varint: false })?;
+        self.chunk_z.write(&mut writer, IntArgs { varint: false })?;
+        self.chunk.write(&mut writer, ())?;
+        self.light.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// The blocks, biomes, and block entities of a chunk column.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ChunkData {
+    /// The heightmaps of the chunk, such as `MOTION_BLOCKING`, keyed by name.
+    ///
+    /// Each heightmap holds one entry per column, packed with
+    /// [`PalettedContainer::pack`].
+    pub heightmaps: BTreeMap<String, Vec<i64>>,
+    /// The sections of the chunk, from the bottom of the world up.
+    pub sections: Vec<ChunkSection>,
+    /// The block entities in the chunk.
+    pub block_entities: Vec<ChunkBlockEntity>,
+}
+
+impl McWrite for ChunkData {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        // Heightmaps are a nameless NBT compound of long arrays.
+        let mut heightmaps = CompoundWriter::root(&mut writer)?;
+        for (name, values) in &self.heightmaps {
+            heightmaps.long_array(name, values)?;
+        }
+        heightmaps.end()?;
+
+        let mut data = Vec::new();
+        for section in &self.sections {
+            section.write(&mut data, ())?;
+        }
+        write_len(&mut writer, data.len(), true)?;
+        writer.write_all(&data)?;
+
+        write_len(&mut writer, self.block_entities.len(), true)?;
+        for block_entity in &self.block_entities {
+            block_entity.write(&mut writer, ())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the given length as an int or varint.
+fn write_len(writer: impl io::Write, len: usize, varint: bool) -> io::Result<()> {
+    i32::try_from(len)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+        .write(writer, IntArgs { varint })
+}
+
+/// A 16x16x16 section of a chunk column.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChunkSection {
+    /// The number of non-air blocks in the section.
+    pub block_count: i16,
+    /// The block state IDs of the section's 4096 blocks, in YZX order.
+    pub block_states: PalettedContainer,
+    /// The biome IDs of the section's 64 4x4x4 cells, in YZX order.
+    pub biomes: PalettedContainer,
+}
+
+impl ChunkSection {
+    /// The number of blocks in a section.
+    pub const BLOCKS: usize = 4096;
+    /// The number of biome cells in a section.
+    pub const BIOMES: usize = 64;
+
+    /// Creates a new [`ChunkSection`] filled with air, whose biome cells are
+    /// all the given biome ID.
+    pub fn empty(biome: i32) -> Self {
+        Self {
+            block_count: 0,
+            block_states: PalettedContainer(vec![0; Self::BLOCKS]),
+            biomes: PalettedContainer(vec![biome; Self::BIOMES]),
+        }
+    }
+}
+
+impl McWrite for ChunkSection {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.block_count.write(&mut writer, ())?;
+        self.block_states
+            .write(&mut writer, PalettedContainerArgs::BLOCK_STATES)?;
+        self.biomes
+            .write(&mut writer, PalettedContainerArgs::BIOMES)?;
+        Ok(())
+    }
+}
+
+/// The values of a section's blocks or biomes, encoded on the wire with the
+/// smallest palette that fits them.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PalettedContainer(pub Vec<i32>);
+
+impl PalettedContainer {
+    /// Packs the given values into longs, using `bits` bits per value.
+    ///
+    /// Values don't span across longs, so any leftover high bits of each long
+    /// are unused.
+    pub fn pack(values: impl IntoIterator<Item = u64>, bits: u32) -> Vec<i64> {
+        let per_long = (64 / bits) as usize;
+        let mask = (1u64 << bits) - 1;
+
+        let mut longs = Vec::new();
+        for (i, value) in values.into_iter().enumerate() {
+            if i % per_long == 0 {
+                longs.push(0);
+            }
+            let shift = (i % per_long) as u32 * bits;
+            *longs.last_mut().unwrap() |= ((value & mask) << shift) as i64;
+        }
+        longs
+    }
+}
+
+/// Arguments for writing [`PalettedContainer`]s.
+///
+/// Defaults to [`PalettedContainerArgs::BLOCK_STATES`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct PalettedContainerArgs {
+    /// The minimum bits per entry when using an indirect palette.
+    pub min_bits: u32,
+    /// The maximum bits per entry when using an indirect palette.
+    pub max_bits: u32,
+    /// The bits per entry when using the global palette directly.
+    pub direct_bits: u32,
+}
+
+impl PalettedContainerArgs {
+    /// The arguments for block states.
+    pub const BLOCK_STATES: Self = Self {
+        min_bits: 4,
+        max_bits: 8,
+        direct_bits: 15,
+    };
+
+    /// The arguments for biomes.
+    pub const BIOMES: Self = Self {
+        min_bits: 1,
+        max_bits: 3,
+        direct_bits: 6,
+    };
+}
+
+impl Default for PalettedContainerArgs {
+    fn default() -> Self {
+        Self::BLOCK_STATES
+    }
+}
+
+impl McWrite for PalettedContainer {
+    type Args = PalettedContainerArgs;
+
+    fn write(&self, mut writer: impl io::Write, args: Self::Args) -> io::Result<()> {
+        let mut palette = Vec::new();
+        for value in &self.0 {
+            if !palette.contains(value) {
+                palette.push(*value);
+            }
+        }
+
+        // A single value is encoded without any data.
+        if palette.len() <= 1 {
+            0u8.write(&mut writer, ())?;
+            palette
+                .first()
+                .copied()
+                .unwrap_or_default()
+                .write(&mut writer, IntArgs { varint: true })?;
+            return write_len(&mut writer, 0, true);
+        }
+
+        let needed = usize::BITS - (palette.len() - 1).leading_zeros();
+        let (bits, data) = if needed <= args.max_bits {
+            let bits = needed.max(args.min_bits);
+            let indices = self
+                .0
+                .iter()
+                .map(|v| palette.iter().position(|p| p == v).unwrap_or_default() as u64);
+            (bits, Self::pack(indices, bits))
+        } else {
+            let bits = args.direct_bits;
+            (bits, Self::pack(self.0.iter().map(|&v| v as u64), bits))
+        };
+
+        (bits as u8).write(&mut writer, ())?;
+        if bits != args.direct_bits {
+            write_len(&mut writer, palette.len(), true)?;
+            for value in &palette {
+                value.write(&mut writer, IntArgs { varint: true })?;
+            }
+        }
+        write_len(&mut writer, data.len(), true)?;
+        for long in data {
+            long.write(&mut writer, IntArgs { varint: false })?;
+        }
+        Ok(())
+    }
+}
+
+/// A block entity within a [`ChunkData`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChunkBlockEntity {
+    /// The X coordinate within the chunk, from `0` to `15`.
+    pub x: u8,
+    /// The Y coordinate in the world.
+    pub y: i16,
+    /// The Z coordinate within the chunk, from `0` to `15`.
+    pub z: u8,
+    /// The protocol ID of the block entity type.
+    pub kind: i32,
+    /// The block entity's data, as an encoded network NBT compound, or
+    /// [`None`] for no data.
+    pub data: Option<Vec<u8>>,
+}
+
+impl McWrite for ChunkBlockEntity {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        ((self.x & 0xF) << 4 | (self.z & 0xF)).write(&mut writer, ())?;
+        self.y.write(&mut writer, ())?;
+        self.kind.write(&mut writer, IntArgs { varint: true })?;
+        match &self.data {
+            Some(data) => writer.write_all(data)?,
+            // An empty NBT tag.
+            None => 0u8.write(&mut writer, ())?,
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server when a player joins the game, describing the
+/// player and the world they're spawning in.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Login {
+    /// The entity ID of the player.
+    pub entity_id: i32,
+    /// Whether the world is in hardcore mode.
+    pub hardcore: bool,
+    /// The names of all dimensions on the server.
+    pub dimensions: Vec<Key>,
+    /// The maximum number of players, which is unused by the client.
+    pub max_players: i32,
+    /// The render distance of the server, in chunks.
+    pub view_distance: i32,
+    /// The distance in chunks that the client processes entities.
+    pub simulation_distance: i32,
+    /// Whether the debug screen shows reduced information.
+    pub reduced_debug_info: bool,
+    /// Whether the client shows the respawn screen when the player dies.
+    pub respawn_screen: bool,
+    /// Whether players can only craft recipes they've unlocked.
+    pub limited_crafting: bool,
+    /// The ID of the dimension type in the `minecraft:dimension_type` registry.
+    pub dimension_type: i32,
+    /// The name of the dimension the player is spawning in.
+    pub dimension: Key,
+    /// The first 8 bytes of the SHA-256 hash of the world seed.
+    pub hashed_seed: i64,
+    /// The game mode of the player.
+    pub game_mode: GameMode,
+    /// The previous game mode of the player.
+    pub previous_game_mode: PreviousGameMode,
+    /// Whether the world is a debug mode world.
+    pub debug: bool,
+    /// Whether the world is a superflat world.
+    pub flat: bool,
+    /// The dimension and position the player last died at, if any.
+    pub death_location: Option<(Key, IVec3)>,
+    /// The number of ticks until the player can use a portal again.
+    pub portal_cooldown: i32,
+    /// Whether the server requires chat messages to be signed.
+    pub enforces_secure_chat: bool,
+}
+
+impl McWrite for Login {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: false })?;
+        self.hardcore.write(&mut writer, ())?;
+        self.dimensions.write(
+            &mut writer,
+            ListArgs {
+                length: ListLength::VarInt,
+                inner: (),
+            },
+        )?;
+        self.max_players
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.view_distance
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.simulation_distance
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.reduced_debug_info.write(&mut writer, ())?;
+        self.respawn_screen.write(&mut writer, ())?;
+        self.limited_crafting.write(&mut writer, ())?;
+        self.dimension_type
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.dimension.write(&mut writer, ())?;
+        self.hashed_seed
+            .write(&mut writer, IntArgs { varint: false })?;
+        self.game_mode.write(&mut writer, ())?;
+        self.previous_game_mode.write(&mut writer, ())?;
+        self.debug.write(&mut writer, ())?;
+        self.flat.write(&mut writer, ())?;
+        match &self.death_location {
+            Some((dimension, position)) => {
+                true.write(&mut writer, ())?;
+                dimension.write(&mut writer, ())?;
+                position.write(&mut writer, IVec3Args { compressed: true })?;
+            }
+            None => false.write(&mut writer, ())?,
+        }
+        self.portal_cooldown
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.enforces_secure_chat.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to update the icons and colors of a map item.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapUpdate {
+    /// The ID of the map.
+    pub map_id: i32,
+    /// The scale of the map, from `0` for 1 block per pixel to `4` for 16
+    /// blocks per pixel.
+    pub scale: i8,
+    /// Whether the map has been locked in a cartography table.
+    pub locked: bool,
+    /// The icons shown on the map, or [`None`] to keep the current ones.
+    pub icons: Option<Vec<Icon>>,
+    /// The colors of a rectangle of the map's pixels, or [`None`] to keep the
+    /// current colors.
+    pub patch: Option<MapPatch>,
+}
+
+impl McWrite for MapUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.map_id.write(&mut writer, IntArgs { varint: true })?;
+        self.scale.write(&mut writer, ())?;
+        self.locked.write(&mut writer, ())?;
+        match &self.icons {
+            Some(icons) => {
+                true.write(&mut writer, ())?;
+                write_len(&mut writer, icons.len(), true)?;
+                for icon in icons {
+                    icon.write(&mut writer, ())?;
+                }
+            }
+            None => false.write(&mut writer, ())?,
+        }
+        match &self.patch {
+            Some(patch) => patch.write(&mut writer, ())?,
+            // No columns means no patch.
+            None => 0u8.write(&mut writer, ())?,
+        }
+        Ok(())
+    }
+}
+
+/// An icon shown on a map by a [`MapUpdate`] packet.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Icon {
+    /// The type of the icon.
+    pub kind: MapIconType,
+    /// The X position of the icon, from `-128` for the left edge of the map to
+    /// `127` for the right edge.
+    pub x: i8,
+    /// The Z position of the icon, from `-128` for the top edge of the map to
+    /// `127` for the bottom edge.
+    pub z: i8,
+    /// The rotation of the icon, from `0` to `15`, in steps of 22.5 degrees
+    /// clockwise.
+    pub direction: u8,
+    /// The name shown below the icon, if any.
+    pub display_name: Option<Text>,
+}
+
+impl McRead for Icon {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let kind = i32::read(&mut reader, IntArgs { varint: true })?;
+        let kind = MapIconType::from_id(kind)
+            .ok_or_else(|| DecodeError::invalid_discriminant::<MapIconType>(kind))?;
+
+        Ok(Self {
+            kind,
+            x: i8::read(&mut reader, ())?,
+            z: i8::read(&mut reader, ())?,
+            direction: u8::read(&mut reader, ())? & 0x0F,
+            display_name: Option::<Text>::read(
+                &mut reader,
+                OptionArgs {
+                    inner: nbt_text(),
+                    ..Default::default()
+                },
+            )?,
+        })
+    }
+}
+
+impl McWrite for Icon {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.kind
+            .to_id()
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.x.write(&mut writer, ())?;
+        self.z.write(&mut writer, ())?;
+        (self.direction & 0x0F).write(&mut writer, ())?;
+        self.display_name.write(
+            &mut writer,
+            OptionArgs {
+                inner: nbt_text(),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// The type of a map [`Icon`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum MapIconType {
+    /// A player holding the map, in the same world.
+    Player,
+    /// The item frame the map is in.
+    Frame,
+    /// A red marker, as used by some structure maps.
+    RedMarker,
+    /// A blue marker.
+    BlueMarker,
+    /// A white cross.
+    TargetX,
+    /// A red triangle.
+    TargetPoint,
+    /// A player outside of the map's area.
+    PlayerOffMap,
+    /// A player far outside of the map's area.
+    PlayerOffLimits,
+    /// A woodland mansion, as marked by explorer maps.
+    Mansion,
+    /// An ocean monument, as marked by explorer maps.
+    Monument,
+    /// A white banner.
+    BannerWhite,
+    /// An orange banner.
+    BannerOrange,
+    /// A magenta banner.
+    BannerMagenta,
+    /// A light blue banner.
+    BannerLightBlue,
+    /// A yellow banner.
+    BannerYellow,
+    /// A lime banner.
+    BannerLime,
+    /// A pink banner.
+    BannerPink,
+    /// A gray banner.
+    BannerGray,
+    /// A light gray banner.
+    BannerLightGray,
+    /// A cyan banner.
+    BannerCyan,
+    /// A purple banner.
+    BannerPurple,
+    /// A blue banner.
+    BannerBlue,
+    /// A brown banner.
+    BannerBrown,
+    /// A green banner.
+    BannerGreen,
+    /// A red banner.
+    BannerRed,
+    /// A black banner.
+    BannerBlack,
+    /// A red cross, as marked by buried treasure maps.
+    RedX,
+    /// A desert village.
+    VillageDesert,
+    /// A plains village.
+    VillagePlains,
+    /// A savanna village.
+    VillageSavanna,
+    /// A snowy village.
+    VillageSnowy,
+    /// A taiga village.
+    VillageTaiga,
+    /// A jungle temple.
+    JungleTemple,
+    /// A swamp hut.
+    SwampHut,
+    /// Trial chambers.
+    TrialChambers,
+}
+
+impl MapIconType {
+    /// All map icon types, in the order of their protocol IDs.
+    pub const ALL: [MapIconType; 35] = [
+        Self::Player,
+        Self::Frame,
+        Self::RedMarker,
+        Self::BlueMarker,
+        Self::TargetX,
+        Self::TargetPoint,
+        Self::PlayerOffMap,
+        Self::PlayerOffLimits,
+        Self::Mansion,
+        Self::Monument,
+        Self::BannerWhite,
+        Self::BannerOrange,
+        Self::BannerMagenta,
+        Self::BannerLightBlue,
+        Self::BannerYellow,
+        Self::BannerLime,
+        Self::BannerPink,
+        Self::BannerGray,
+        Self::BannerLightGray,
+        Self::BannerCyan,
+        Self::BannerPurple,
+        Self::BannerBlue,
+        Self::BannerBrown,
+        Self::BannerGreen,
+        Self::BannerRed,
+        Self::BannerBlack,
+        Self::RedX,
+        Self::VillageDesert,
+        Self::VillagePlains,
+        Self::VillageSavanna,
+        Self::VillageSnowy,
+        Self::VillageTaiga,
+        Self::JungleTemple,
+        Self::SwampHut,
+        Self::TrialChambers,
+    ];
+
+    /// Returns the [`MapIconType`] with the given protocol ID, or [`None`] if
+    /// the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| Self::ALL.get(id))
+            .copied()
+    }
+
+    /// Returns the protocol ID of this [`MapIconType`].
+    pub fn to_id(self) -> i32 {
+        // The variants are declared in the order of their IDs.
+        self as i32
+    }
+}
+
+/// The colors of a rectangle of a map's pixels, sent in a [`MapUpdate`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MapPatch {
+    /// The width of the rectangle, in pixels.
+    pub columns: u8,
+    /// The height of the rectangle, in pixels.
+    pub rows: u8,
+    /// The X position of the rectangle's left edge on the map.
+    pub x: u8,
+    /// The Z position of the rectangle's top edge on the map.
+    pub z: u8,
+    /// The map color IDs of the rectangle's pixels, row by row.
+    pub data: Vec<u8>,
+}
+
+impl McWrite for MapPatch {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        if self.columns == 0 || self.rows == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "map patches must be at least 1x1 pixels",
+            ));
+        }
+        if self.data.len() != usize::from(self.columns) * usize::from(self.rows) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "map patch data has {} pixels, but should have {}x{}",
+                    self.data.len(),
+                    self.columns,
+                    self.rows,
+                ),
+            ));
+        }
+
+        self.columns.write(&mut writer, ())?;
+        self.rows.write(&mut writer, ())?;
+        self.x.write(&mut writer, ())?;
+        self.z.write(&mut writer, ())?;
+        write_len(&mut writer, self.data.len(), true)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the position that compasses point to,
+/// and that players respawn at without a bed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetDefaultSpawnPosition {
+    /// The spawn position.
+    pub position: IVec3,
+    /// The yaw players face when spawning, in degrees.
+    pub angle: f32,
+}
+
+impl McWrite for SetDefaultSpawnPosition {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.position
+            .write(&mut writer, IVec3Args { compressed: true })?;
+        self.angle.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to notify the client of a change in the game,
+/// such as the weather or the player's game mode.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameEvent {
+    /// The kind of event, such as [`GameEvent::START_WAITING_FOR_CHUNKS`].
+    pub event: u8,
+    /// The value of the event, whose meaning depends on its kind.
+    pub value: f32,
+}
+
+impl GameEvent {
+    /// Changes the player's game mode to the [`GameMode`] with the ID in the
+    /// value.
+    pub const CHANGE_GAME_MODE: u8 = 3;
+    /// Tells the client to wait for the chunks around the player before
+    /// closing the loading screen.
+    pub const START_WAITING_FOR_CHUNKS: u8 = 13;
+}
+
+impl McWrite for GameEvent {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.event.write(&mut writer, ())?;
+        self.value.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the chunk that the client loads chunks
+/// around, usually the chunk the player is in.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetCenterChunk {
+    /// The X coordinate of the chunk.
+    pub chunk_x: i32,
+    /// The Z coordinate of the chunk.
+    pub chunk_z: i32,
+}
+
+impl McWrite for SetCenterChunk {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.chunk_x.write(&mut writer, IntArgs { varint: true })?;
+        self.chunk_z.write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to apply a status effect to an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntityEffect {
+    /// The ID of the entity.
+    pub entity_id: i32,
+    /// The ID of the effect in the `minecraft:mob_effect` registry.
+    pub effect_id: i32,
+    /// The amplifier of the effect, where `0` is level I.
+    pub amplifier: i32,
+    /// The duration of the effect in ticks, or `-1` if it's infinite.
+    pub duration: i32,
+    /// A bitmask of the effect's flags, such as [`EntityEffect::SHOW_ICON`].
+    pub flags: u8,
+}
+
+impl EntityEffect {
+    /// The flag for effects from beacons or conduits.
+    pub const AMBIENT: u8 = 0x01;
+    /// The flag for effects that show particles.
+    pub const SHOW_PARTICLES: u8 = 0x02;
+    /// The flag for effects that show an icon.
+    pub const SHOW_ICON: u8 = 0x04;
+    /// The flag for effects that blend into the screen, such as darkness.
+    pub const BLEND: u8 = 0x08;
+
+    /// Creates a new [`EntityEffect`] applying the given [`PotionEffect`] to an
+    /// entity, looking up its numeric ID in the given effect registry.
+    ///
+    /// Returns [`None`] if the effect isn't registered.
+    pub fn new(entity_id: i32, effect: &PotionEffect, effects: &Registry) -> Option<Self> {
+        let mut flags = 0;
+        if effect.ambient {
+            flags |= Self::AMBIENT;
+        }
+        if effect.show_particles {
+            flags |= Self::SHOW_PARTICLES;
+        }
+        if effect.show_icon {
+            flags |= Self::SHOW_ICON;
+        }
+
+        Some(Self {
+            entity_id,
+            effect_id: effects.id_of(&effect.id)?,
+            amplifier: i32::from(effect.amplifier),
+            duration: effect.duration,
+            flags,
+        })
+    }
+
+    /// Returns the [`PotionEffect`] applied by this packet, looking up its
+    /// [`Key`] in the given effect registry.
+    ///
+    /// Returns [`None`] if the effect isn't registered or the amplifier is out
+    /// of range.
+    pub fn effect(&self, effects: &Registry) -> Option<PotionEffect> {
+        Some(PotionEffect {
+            id: effects.key_of(self.effect_id)?.clone(),
+            amplifier: u8::try_from(self.amplifier).ok()?,
+            duration: self.duration,
+            ambient: self.flags & Self::AMBIENT != 0,
+            show_particles: self.flags & Self::SHOW_PARTICLES != 0,
+            show_icon: self.flags & Self::SHOW_ICON != 0,
+        })
+    }
+}
+
+impl McWrite for EntityEffect {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.effect_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.amplifier
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.duration.write(&mut writer, IntArgs { varint: true })?;
+        self.flags.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to update the attributes of an entity, such as
+/// its movement speed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpdateAttributes {
+    /// The ID of the entity.
+    pub entity_id: i32,
+    /// The attributes being updated.
+    pub attributes: Vec<AttributeProperty>,
+}
+
+impl McWrite for UpdateAttributes {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.attributes.write(
+            &mut writer,
+            ListArgs {
+                length: ListLength::VarInt,
+                inner: (),
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// The value and modifiers of a single attribute of an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AttributeProperty {
+    /// The ID of the attribute in the `minecraft:attribute` registry.
+    pub attribute: i32,
+    /// The base value of the attribute.
+    pub base: f64,
+    /// The modifiers applied to the base value.
+    pub modifiers: Vec<AttributeModifier>,
+}
+
+impl McWrite for AttributeProperty {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.attribute
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.base.write(&mut writer, ())?;
+        self.modifiers.write(
+            &mut writer,
+            ListArgs {
+                length: ListLength::VarInt,
+                inner: (),
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to play a sound at a position.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SoundEffect {
+    /// The sound to play.
+    pub sound: SoundEvent,
+    /// The category of the sound.
+    pub category: SoundCategory,
+    /// The position of the sound, which is sent with a precision of 1/8 block.
+    pub position: DVec3,
+    /// The volume of the sound, where `1.0` is normal volume.
+    pub volume: f32,
+    /// The pitch of the sound, from `0.5` to `2.0`.
+    pub pitch: f32,
+    /// The seed used to pick a variant of the sound.
+    pub seed: i64,
+}
+
+impl SoundEffect {
+    /// Creates a new [`SoundEffect`] playing the sound with the given [`Key`].
+    ///
+    /// The sound is sent by its ID if it's in the given sound event registry,
+    /// or by its key otherwise.
+    pub fn new(
+        sounds: &Registry,
+        sound: Key,
+        category: SoundCategory,
+        position: DVec3,
+        volume: f32,
+        pitch: f32,
+    ) -> Self {
+        Self {
+            sound: SoundEvent::new(sounds, sound),
+            category,
+            position,
+            volume,
+            pitch,
+            seed: 0,
+        }
+    }
+}
+
+impl McWrite for SoundEffect {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.sound.write(&mut writer, ())?;
+        self.category.write(&mut writer, ())?;
+        for coord in self.position.to_array() {
+            ((coord * 8.) as i32).write(&mut writer, IntArgs { varint: false })?;
+        }
+        self.volume.write(&mut writer, ())?;
+        self.pitch.write(&mut writer, ())?;
+        self.seed.write(&mut writer, IntArgs { varint: false })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to play a sound that follows an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntitySoundEffect {
+    /// The sound to play.
+    pub sound: SoundEvent,
+    /// The category of the sound.
+    pub category: SoundCategory,
+    /// The ID of the entity the sound follows.
+    pub entity_id: i32,
+    /// The volume of the sound, where `1.0` is normal volume.
+    pub volume: f32,
+    /// The pitch of the sound, from `0.5` to `2.0`.
+    pub pitch: f32,
+    /// The seed used to pick a variant of the sound.
+    pub seed: i64,
+}
+
+impl McWrite for EntitySoundEffect {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.sound.write(&mut writer, ())?;
+        self.category.write(&mut writer, ())?;
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.volume.write(&mut writer, ())?;
+        self.pitch.write(&mut writer, ())?;
+        self.seed.write(&mut writer, IntArgs { varint: false })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to stop sounds that are playing.
+///
+/// Without a category or sound, all sounds are stopped.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct StopSound {
+    /// The category of the sounds to stop, or [`None`] for all categories.
+    pub category: Option<SoundCategory>,
+    /// The key of the sound to stop, or [`None`] for all sounds.
+    pub sound: Option<Key>,
+}
+
+impl McWrite for StopSound {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let flags = u8::from(self.category.is_some()) | u8::from(self.sound.is_some()) << 1;
+        flags.write(&mut writer, ())?;
+        if let Some(category) = &self.category {
+            category.write(&mut writer, ())?;
+        }
+        if let Some(sound) = &self.sound {
+            sound.write(&mut writer, ())?;
+        }
+        Ok(())
+    }
+}
+
+/// A sound, either by its ID in the sound event registry or by its [`Key`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum SoundEvent {
+    /// A sound in the `minecraft:sound_event` registry.
+    Registered(i32),
+    /// A sound that isn't in the registry, such as one from a resource pack.
+    Inline {
+        /// The key of the sound.
+        key: Key,
+        /// The fixed distance the sound can be heard from, if any, rather
+        /// than a distance based on its volume.
+        fixed_range: Option<f32>,
+    },
+}
+
+impl SoundEvent {
+    /// Returns the [`SoundEvent`] for the sound with the given [`Key`], which
+    /// is [`SoundEvent::Registered`] if it's in the given sound event registry.
+    pub fn new(sounds: &Registry, key: Key) -> Self {
+        match sounds.id_of(&key) {
+            Some(id) => Self::Registered(id),
+            None => Self::Inline {
+                key,
+                fixed_range: None,
+            },
+        }
+    }
+}
+
+impl McRead for SoundEvent {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = i32::read(&mut reader, IntArgs { varint: true })?;
+        match id {
+            0 => Ok(Self::Inline {
+                key: Key::read(&mut reader, ())?,
+                fixed_range: Option::<f32>::read(
+                    &mut reader,
+                    OptionArgs {
+                        tag: OptionTag::Bool,
+                        inner: (),
+                    },
+                )?,
+            }),
+            1.. => Ok(Self::Registered(id - 1)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid sound event ID: {id}"),
+            )),
+        }
+    }
+}
+
+impl McWrite for SoundEvent {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        match self {
+            // Registered sounds are offset by one, as zero marks an inline sound.
+            Self::Registered(id) => {
+                let id = id.checked_add(1).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("sound event ID {id} is out of range"),
+                    )
+                })?;
+                id.write(&mut writer, IntArgs { varint: true })?;
+            }
+            Self::Inline { key, fixed_range } => {
+                0i32.write(&mut writer, IntArgs { varint: true })?;
+                key.write(&mut writer, ())?;
+                fixed_range.write(
+                    &mut writer,
+                    OptionArgs {
+                        tag: OptionTag::Bool,
+                        inner: (),
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to make a non-player entity appear to a client.
+///
+/// The network ID must be unique among the entities the client can see, and
+/// the entity's metadata is sent separately with an [`EntityMetadata`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpawnEntity {
+    /// The network ID of the entity.
+    pub entity_id: i32,
+    /// The UUID of the entity.
+    pub uuid: Uuid,
+    /// The ID of the entity's type in the entity type registry.
+    pub kind: i32,
+    /// The position of the entity.
+    pub position: DVec3,
+    /// The pitch of the entity.
+    pub pitch: Angle,
+    /// The yaw of the entity.
+    pub yaw: Angle,
+    /// The yaw of the entity's head.
+    pub head_yaw: Angle,
+    /// Extra data whose meaning depends on the type of entity, such as the
+    /// block state of a falling block.
+    pub data: i32,
+    /// The velocity of the entity, in blocks per tick.
+    pub velocity: DVec3,
+}
+
+impl McWrite for SpawnEntity {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.uuid.write_default(&mut writer)?;
+        self.kind.write(&mut writer, IntArgs { varint: true })?;
+        self.position.write(&mut writer, ())?;
+        self.pitch.write(&mut writer, ())?;
+        self.yaw.write(&mut writer, ())?;
+        self.head_yaw.write(&mut writer, ())?;
+        self.data.write(&mut writer, IntArgs { varint: true })?;
+        write_velocity(&mut writer, self.velocity)?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the velocity of an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetEntityVelocity {
+    /// The network ID of the entity.
+    pub entity_id: i32,
+    /// The velocity of the entity, in blocks per tick.
+    pub velocity: DVec3,
+}
+
+impl McWrite for SetEntityVelocity {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_id
+            .write(&mut writer, IntArgs { varint: true })?;
+        write_velocity(&mut writer, self.velocity)?;
+        Ok(())
+    }
+}
+
+/// Writes a velocity in blocks per tick, in units of 1/8000 block per tick
+/// clamped to the range the client accepts.
+fn write_velocity(mut writer: impl io::Write, velocity: DVec3) -> io::Result<()> {
+    for component in velocity.to_array() {
+        velocity_to_fixed(component).write(&mut writer, ())?;
+    }
+    Ok(())
+}
+
+/// A packet sent by the server to add, update, or remove a boss bar.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BossBarUpdate {
+    /// The UUID that identifies the boss bar.
+    pub uuid: Uuid,
+    /// The change made to the boss bar.
+    pub action: BossBarAction,
+}
+
+impl BossBarUpdate {
+    /// The flag for boss bars that darken the sky.
+    pub const DARKEN_SKY: u8 = 0x01;
+    /// The flag for boss bars that play the ender dragon's music.
+    pub const DRAGON_BAR: u8 = 0x02;
+    /// The flag for boss bars that create fog.
+    pub const CREATE_FOG: u8 = 0x04;
+}
+
+impl McWrite for BossBarUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.uuid.write_default(&mut writer)?;
+        self.action.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A change made to a boss bar by a [`BossBarUpdate`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum BossBarAction {
+    /// Shows a new boss bar.
+    Add {
+        /// The title displayed above the bar.
+        title: Text,
+        /// How full the bar is, from `0.0` to `1.0`.
+        health: f32,
+        /// The color of the bar.
+        color: BossBarColor,
+        /// The number of notches the bar is divided into.
+        division: BossBarDivision,
+        /// The flags of the bar, such as [`BossBarUpdate::DARKEN_SKY`].
+        flags: u8,
+    },
+    /// Hides the boss bar.
+    Remove,
+    /// Sets how full the bar is, from `0.0` to `1.0`.
+    UpdateHealth(f32),
+    /// Sets the title displayed above the bar.
+    UpdateTitle(Text),
+    /// Sets the color and division of the bar.
+    UpdateStyle {
+        /// The color of the bar.
+        color: BossBarColor,
+        /// The number of notches the bar is divided into.
+        division: BossBarDivision,
+    },
+    /// Sets the flags of the bar.
+    UpdateFlags(u8),
+}
+
+impl BossBarAction {
+    /// Returns the protocol ID of this [`BossBarAction`].
+    pub fn to_id(&self) -> i32 {
+        match self {
+            Self::Add { .. } => 0,
+            Self::Remove => 1,
+            Self::UpdateHealth(_) => 2,
+            Self::UpdateTitle(_) => 3,
+            Self::UpdateStyle { .. } => 4,
+            Self::UpdateFlags(_) => 5,
+        }
+    }
+}
+
+impl McWrite for BossBarAction {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let text_args = TextArgs {
+            nbt: true,
+            ..Default::default()
+        };
+
+        self.to_id().write(&mut writer, IntArgs { varint: true })?;
+        match self {
+            Self::Add {
+                title,
+                health,
+                color,
+                division,
+                flags,
+            } => {
+                title.write(&mut writer, text_args)?;
+                health.write(&mut writer, ())?;
+                color.to_id().write(&mut writer, IntArgs { varint: true })?;
+                division
+                    .to_id()
+                    .write(&mut writer, IntArgs { varint: true })?;
+                flags.write(&mut writer, ())?;
+            }
+            Self::Remove => {}
+            Self::UpdateHealth(health) => health.write(&mut writer, ())?,
+            Self::UpdateTitle(title) => title.write(&mut writer, text_args)?,
+            Self::UpdateStyle { color, division } => {
+                color.to_id().write(&mut writer, IntArgs { varint: true })?;
+                division
+                    .to_id()
+                    .write(&mut writer, IntArgs { varint: true })?;
+            }
+            Self::UpdateFlags(flags) => flags.write(&mut writer, ())?,
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to create, update, or remove a scoreboard
+/// objective.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpdateObjectives {
+    /// The unique name of the objective.
+    pub name: String,
+    /// The change made to the objective.
+    pub action: ObjectiveAction,
+}
+
+impl McWrite for UpdateObjectives {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.name.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        match &self.action {
+            ObjectiveAction::Create(display) => {
+                0u8.write(&mut writer, ())?;
+                display.write(&mut writer, ())?;
+            }
+            ObjectiveAction::Remove => 1u8.write(&mut writer, ())?,
+            ObjectiveAction::Update(display) => {
+                2u8.write(&mut writer, ())?;
+                display.write(&mut writer, ())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A change made to a scoreboard objective by an [`UpdateObjectives`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum ObjectiveAction {
+    /// Creates the objective.
+    Create(ObjectiveDisplay),
+    /// Removes the objective.
+    Remove,
+    /// Updates how the objective is displayed.
+    Update(ObjectiveDisplay),
+}
+
+/// How a scoreboard objective is displayed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ObjectiveDisplay {
+    /// The name shown for the objective.
+    pub display_name: Text,
+    /// How scores are rendered in the player list.
+    pub render_type: ObjectiveRenderType,
+    /// The default format of the objective's scores, or [`None`] to show them
+    /// as plain numbers.
+    pub number_format: Option<NumberFormat>,
+}
+
+impl McWrite for ObjectiveDisplay {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.display_name.write(
+            &mut writer,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )?;
+        self.render_type
+            .to_id()
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.number_format.write(
+            &mut writer,
+            OptionArgs {
+                tag: OptionTag::Bool,
+                inner: (),
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// How the number of a score is displayed in the sidebar.
+#[derive(Clone, PartialEq, Debug)]
+pub enum NumberFormat {
+    /// The number is hidden.
+    Blank,
+    /// The number is shown in the given color.
+    Styled(NamedColor),
+    /// The given text is shown instead of the number.
+    Fixed(Text),
+}
+
+impl McWrite for NumberFormat {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        match self {
+            Self::Blank => 0i32.write(&mut writer, IntArgs { varint: true })?,
+            Self::Styled(color) => {
+                1i32.write(&mut writer, IntArgs { varint: true })?;
+                let mut style = CompoundWriter::root(&mut writer)?;
+                style.string("color", color.name())?;
+                style.end()?;
+            }
+            Self::Fixed(text) => {
+                2i32.write(&mut writer, IntArgs { varint: true })?;
+                text.write(
+                    &mut writer,
+                    TextArgs {
+                        nbt: true,
+                        ..Default::default()
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to show a scoreboard objective in a
+/// [`DisplaySlot`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct DisplayObjective {
+    /// The slot the objective is shown in.
+    pub slot: DisplaySlot,
+    /// The name of the objective, or an empty string to clear the slot.
+    pub objective: String,
+}
+
+impl McWrite for DisplayObjective {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.slot
+            .to_id()
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.objective.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the score of an entity for an objective.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpdateScore {
+    /// The username or UUID of the entity holding the score.
+    pub entity_name: String,
+    /// The name of the objective.
+    pub objective: String,
+    /// The value of the score.
+    pub value: i32,
+    /// The name shown for the entity, or [`None`] to show its entity name.
+    pub display_name: Option<Text>,
+    /// The format of the score, or [`None`] to use the objective's format.
+    pub number_format: Option<NumberFormat>,
+}
+
+impl McWrite for UpdateScore {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_name.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        self.objective.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        self.value.write(&mut writer, IntArgs { varint: true })?;
+        self.display_name.write(
+            &mut writer,
+            OptionArgs {
+                tag: OptionTag::Bool,
+                inner: TextArgs {
+                    nbt: true,
+                    ..Default::default()
+                },
+            },
+        )?;
+        self.number_format.write(
+            &mut writer,
+            OptionArgs {
+                tag: OptionTag::Bool,
+                inner: (),
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to remove the score of an entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResetScore {
+    /// The username or UUID of the entity holding the score.
+    pub entity_name: String,
+    /// The name of the objective, or [`None`] to remove the entity's scores
+    /// for all objectives.
+    pub objective: Option<String>,
+}
+
+impl McWrite for ResetScore {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.entity_name.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        self.objective.write(
+            &mut writer,
+            OptionArgs {
+                tag: OptionTag::Bool,
+                inner: StringArgs {
+                    max_len: Some(32767),
+                },
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to create, update, or remove a scoreboard team,
+/// or change its members.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TeamUpdate {
+    /// The unique name of the team.
+    pub name: String,
+    /// The change made to the team.
+    pub action: TeamAction,
+}
+
+impl McWrite for TeamUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let entities_args = ListArgs {
+            length: ListLength::VarInt,
+            inner: StringArgs {
+                max_len: Some(32767),
+            },
+        };
+
+        self.name.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        match &self.action {
+            TeamAction::Create { info, entities } => {
+                0u8.write(&mut writer, ())?;
+                info.write(&mut writer, ())?;
+                entities.write(&mut writer, entities_args.clone())?;
+            }
+            TeamAction::Remove => 1u8.write(&mut writer, ())?,
+            TeamAction::Update(info) => {
+                2u8.write(&mut writer, ())?;
+                info.write(&mut writer, ())?;
+            }
+            TeamAction::AddEntities(entities) => {
+                3u8.write(&mut writer, ())?;
+                entities.write(&mut writer, entities_args.clone())?;
+            }
+            TeamAction::RemoveEntities(entities) => {
+                4u8.write(&mut writer, ())?;
+                entities.write(&mut writer, entities_args)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A change made to a scoreboard team by a [`TeamUpdate`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum TeamAction {
+    /// Creates the team.
+    Create {
+        /// The appearance and rules of the team.
+        info: TeamInfo,
+        /// The usernames or UUIDs of the team's initial members.
+        entities: Vec<String>,
+    },
+    /// Removes the team.
+    Remove,
+    /// Updates the appearance and rules of the team.
+    Update(TeamInfo),
+    /// Adds the entities with the given usernames or UUIDs to the team.
+    AddEntities(Vec<String>),
+    /// Removes the entities with the given usernames or UUIDs from the team.
+    RemoveEntities(Vec<String>),
+}
+
+/// The appearance and rules of a scoreboard team.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TeamInfo {
+    /// The name shown for the team.
+    pub display_name: Text,
+    /// Whether members can hurt each other.
+    pub friendly_fire: bool,
+    /// Whether members can see invisible members of the same team.
+    pub see_invisible_teammates: bool,
+    /// Which players can see the name tags of members.
+    pub name_tag_visibility: NameTagVisibility,
+    /// Which entities members push and are pushed by.
+    pub collision_rule: CollisionRule,
+    /// The color of members' names, or [`None`] for no color.
+    pub color: Option<NamedColor>,
+    /// The text shown before members' names.
+    pub prefix: Text,
+    /// The text shown after members' names.
+    pub suffix: Text,
+}
+
+impl TeamInfo {
+    const FRIENDLY_FIRE: u8 = 0x01;
+    const SEE_INVISIBLE_TEAMMATES: u8 = 0x02;
+
+    /// The color ID that resets the color of members' names.
+    const RESET_COLOR: i32 = 21;
+}
+
+impl Default for TeamInfo {
+    fn default() -> Self {
+        Self {
+            display_name: Text::from(""),
+            friendly_fire: true,
+            see_invisible_teammates: true,
+            name_tag_visibility: NameTagVisibility::default(),
+            collision_rule: CollisionRule::default(),
+            color: None,
+            prefix: Text::from(""),
+            suffix: Text::from(""),
+        }
+    }
+}
+
+impl McWrite for TeamInfo {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let text_args = TextArgs {
+            nbt: true,
+            ..Default::default()
+        };
+
+        let mut flags = 0;
+        if self.friendly_fire {
+            flags |= Self::FRIENDLY_FIRE;
+        }
+        if self.see_invisible_teammates {
+            flags |= Self::SEE_INVISIBLE_TEAMMATES;
+        }
+        let color = match self.color {
+            Some(color) => NamedColor::ALL.iter().position(|c| *c == color).unwrap() as i32,
+            None => Self::RESET_COLOR,
+        };
+
+        self.display_name.write(&mut writer, text_args.clone())?;
+        flags.write(&mut writer, ())?;
+        writer.write_string(self.name_tag_visibility.name())?;
+        writer.write_string(self.collision_rule.name())?;
+        color.write(&mut writer, IntArgs { varint: true })?;
+        self.prefix.write(&mut writer, text_args.clone())?;
+        self.suffix.write(&mut writer, text_args)?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set the title shown in the middle of the
+/// client's screen, which is displayed using the last [`TitleTimes`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetTitle(pub Text);
+
+impl McWrite for SetTitle {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(
+            writer,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// A packet sent by the server to set the subtitle shown below the title.
+///
+/// The subtitle is only displayed once a [`SetTitle`] is sent.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetSubtitle(pub Text);
+
+impl McWrite for SetSubtitle {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(
+            writer,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// A packet sent by the server to show a message above the client's hotbar.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetActionBar(pub Text);
+
+impl McWrite for SetActionBar {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(
+            writer,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// A packet sent by the server to set how long titles fade in, stay, and fade
+/// out for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct TitleTimes {
+    /// How long the title takes to fade in.
+    pub fade_in: Ticks,
+    /// How long the title stays fully visible.
+    pub stay: Ticks,
+    /// How long the title takes to fade out.
+    pub fade_out: Ticks,
+}
+
+impl Default for TitleTimes {
+    /// Returns the times vanilla clients use until told otherwise.
+    fn default() -> Self {
+        Self {
+            fade_in: Ticks(10),
+            stay: Ticks(70),
+            fade_out: Ticks(20),
+        }
+    }
+}
+
+impl McWrite for TitleTimes {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        for ticks in [self.fade_in, self.stay, self.fade_out] {
+            (ticks.0.clamp(0, i32::MAX as i64) as i32)
+                .write(&mut writer, IntArgs { varint: false })?;
+        }
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to hide the client's current title.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct ClearTitles {
+    /// Whether the subtitle and [`TitleTimes`] are also reset to their
+    /// defaults.
+    pub reset: bool,
+}
+
+impl McWrite for ClearTitles {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.reset.write(writer, ())
+    }
+}
+
+/// A packet sent by the server to initialize the world border, sent once when
+/// the client joins or changes worlds.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CreateWorldBorder {
+    /// The X coordinate of the center of the border.
+    pub x: f64,
+    /// The Z coordinate of the center of the border.
+    pub z: f64,
+    /// The current diameter of the border.
+    pub old_diameter: f64,
+    /// The diameter the border is moving towards.
+    pub new_diameter: f64,
+    /// How long the border takes to reach its new diameter, in milliseconds.
+    pub speed: i64,
+    /// How far from the origin nether portals can teleport players, in
+    /// blocks.
+    pub portal_teleport_boundary: i32,
+    /// How far from the border players see its warning, in blocks.
+    pub warning_blocks: i32,
+    /// How many seconds before reaching a player a moving border shows its
+    /// warning.
+    pub warning_time: i32,
+}
+
+impl McWrite for CreateWorldBorder {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.x.write(&mut writer, ())?;
+        self.z.write(&mut writer, ())?;
+        self.old_diameter.write(&mut writer, ())?;
+        self.new_diameter.write(&mut writer, ())?;
+        self.speed.write(&mut writer, IntArgs { varint: true })?;
+        self.portal_teleport_boundary
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.warning_blocks
+            .write(&mut writer, IntArgs { varint: true })?;
+        self.warning_time
+            .write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to move the center of the world border.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WorldBorderCenter {
+    /// The X coordinate of the center of the border.
+    pub x: f64,
+    /// The Z coordinate of the center of the border.
+    pub z: f64,
+}
+
+impl McWrite for WorldBorderCenter {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.x.write(&mut writer, ())?;
+        self.z.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to immediately resize the world border.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WorldBorderSize {
+    /// The diameter of the border.
+    pub diameter: f64,
+}
+
+impl McWrite for WorldBorderSize {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.diameter.write(writer, ())
+    }
+}
+
+/// A packet sent by the server to gradually resize the world border.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WorldBorderLerpSize {
+    /// The current diameter of the border.
+    pub old_diameter: f64,
+    /// The diameter the border is moving towards.
+    pub new_diameter: f64,
+    /// How long the border takes to reach its new diameter, in milliseconds.
+    pub speed: i64,
+}
+
+impl McWrite for WorldBorderLerpSize {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.old_diameter.write(&mut writer, ())?;
+        self.new_diameter.write(&mut writer, ())?;
+        self.speed.write(&mut writer, IntArgs { varint: true })?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the server to set how many seconds before reaching a
+/// player a moving world border shows its warning.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct WorldBorderWarningDelay(pub i32);
+
+impl McWrite for WorldBorderWarningDelay {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(writer, IntArgs { varint: true })
+    }
+}
+
+/// A packet sent by the server to set how far from the world border players
+/// see its warning, in blocks.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct WorldBorderWarningReach(pub i32);
+
+impl McWrite for WorldBorderWarningReach {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(writer, IntArgs { varint: true })
+    }
+}
+
+/// A packet sent by the server to disconnect the client, showing it the
+/// reason.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Disconnect {
+    /// The reason for the disconnect.
+    pub reason: Text,
+}
+
+impl McWrite for Disconnect {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.reason.write(
+            writer,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// A packet sent by the server to ask the client to download and apply a
+/// resource pack.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AddResourcePack {
+    /// The UUID that identifies the resource pack.
+    pub uuid: Uuid,
+    /// The URL the resource pack is downloaded from.
+    pub url: String,
+    /// The hex-encoded SHA-1 hash of the resource pack, or an empty string to
+    /// skip verifying it.
+    pub hash: String,
+    /// Whether the client must accept the resource pack to stay connected.
+    pub forced: bool,
+    /// The message shown in the prompt to accept the resource pack, if any.
+    pub prompt: Option<Text>,
+}
+
+impl McWrite for AddResourcePack {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.uuid.write_default(&mut writer)?;
+        self.url.write(
+            &mut writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+        self.hash
+            .write(&mut writer, StringArgs { max_len: Some(40) })?;
+        self.forced.write(&mut writer, ())?;
+        self.prompt.write(
+            &mut writer,
+            OptionArgs {
+                tag: OptionTag::Bool,
+                inner: TextArgs {
+                    nbt: true,
+                    ..Default::default()
+                },
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the client to report its progress applying a resource pack.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResourcePackResponse {
+    /// The UUID of the resource pack.
+    pub uuid: Uuid,
+    /// The progress of the client.
+    pub status: ResourcePackStatus,
+}
+
+impl McRead for ResourcePackResponse {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let uuid = Uuid::read_default(&mut reader)?;
+        let status = i32::read(&mut reader, IntArgs { varint: true })?;
+        let status = ResourcePackStatus::from_id(status)
+            .ok_or_else(|| DecodeError::invalid_discriminant::<ResourcePackStatus>(status))?;
+        Ok(Self { uuid, status })
+    }
+}
+
+/// The progress of a client applying a resource pack.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum ResourcePackStatus {
+    /// The resource pack was downloaded and applied.
+    SuccessfullyLoaded,
+    /// The player declined the resource pack.
+    Declined,
+    /// The resource pack failed to download.
+    FailedDownload,
+    /// The player accepted the resource pack, and it's being downloaded.
+    Accepted,
+    /// The resource pack was downloaded, and it's being applied.
+    Downloaded,
+    /// The URL of the resource pack is invalid.
+    InvalidUrl,
+    /// The resource pack failed to apply.
+    FailedReload,
+    /// The resource pack was discarded by the client.
+    Discarded,
+}
+
+impl ResourcePackStatus {
+    /// Returns the [`ResourcePackStatus`] with the given protocol ID, or
+    /// [`None`] if the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::SuccessfullyLoaded),
+            1 => Some(Self::Declined),
+            2 => Some(Self::FailedDownload),
+            3 => Some(Self::Accepted),
+            4 => Some(Self::Downloaded),
+            5 => Some(Self::InvalidUrl),
+            6 => Some(Self::FailedReload),
+            7 => Some(Self::Discarded),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`ResourcePackStatus`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::SuccessfullyLoaded => 0,
+            Self::Declined => 1,
+            Self::FailedDownload => 2,
+            Self::Accepted => 3,
+            Self::Downloaded => 4,
+            Self::InvalidUrl => 5,
+            Self::FailedReload => 6,
+            Self::Discarded => 7,
+        }
+    }
+
+    /// Returns `true` if the client is done with the resource pack, whether
+    /// or not it was applied.
+    pub fn is_final(self) -> bool {
+        !matches!(self, Self::Accepted | Self::Downloaded)
+    }
+
+    /// Returns `true` if the client didn't apply the resource pack.
+    pub fn is_failure(self) -> bool {
+        self.is_final() && self != Self::SuccessfullyLoaded
+    }
+}
+
+/// A packet sent by the server to set the difficulty shown to the client.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct DifficultyUpdate {
+    /// The difficulty of the world.
+    pub difficulty: Difficulty,
+    /// Whether the difficulty can't be changed from the client's options.
+    pub locked: bool,
+}
+
+impl McWrite for DifficultyUpdate {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.difficulty.write(&mut writer, ())?;
+        self.locked.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// A packet sent by the client to request changing the difficulty.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct SetDifficulty(pub Difficulty);
+
+impl McRead for SetDifficulty {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self(Difficulty::read(reader, ())?))
+    }
+}
+
+/// A packet sent by the client to request locking the difficulty.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct LockDifficulty(pub bool);
+
+impl McRead for LockDifficulty {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self(bool::read(reader, ())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use glam::Vec3;
+    use minecrevy_io::{args::IntArgs, McRead, McWrite};
+    use minecrevy_text::{Text, TextArgs};
+    use minecrevy_util::{
+        color::NamedColor, item::ItemStack, key::Key, pose::Pose, potion::PotionEffect,
+        registry::Registry,
+    };
+
+    use super::{
+        ChunkSection, EntityEffect, Icon, LightData, LightSection, MapIconType, MapPatch,
+        MapUpdate, Metadata, MetadataValue, NumberFormat, PalettedContainer, PalettedContainerArgs,
+        ParticleData, SetCreativeModeSlot, SoundEvent, StatCategory, Statistic, StatisticsUpdate,
+    };
+
+    /// Writes the given [`MetadataValue`], checks that it reads back unchanged,
+    /// and returns its bytes.
+    fn metadata_value_bytes(value: MetadataValue) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.write(&mut bytes, ()).unwrap();
+        assert_eq!(MetadataValue::read(bytes.as_slice(), ()).unwrap(), value);
+        bytes
+    }
+
+    /// Writes the given [`ParticleData`], checks that it reads back unchanged
+    /// as the given particle type, and returns its bytes.
+    fn particle_bytes(data: ParticleData, particle: i32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        data.write(&mut bytes, ()).unwrap();
+        assert_eq!(
+            ParticleData::read(bytes.as_slice(), particle).unwrap(),
+            data
+        );
+        bytes
+    }
+
+    #[test]
+    fn block_particle_round_trip() {
+        let bytes = particle_bytes(ParticleData::Block(300), ParticleData::BLOCK);
+        assert_eq!(bytes, [0xAC, 0x02]);
+    }
+
+    #[test]
+    fn dust_particle_round_trip() {
+        let dust = ParticleData::Dust {
+            color: Vec3::new(1.0, 0.5, 0.0),
+            scale: 2.0,
+        };
+        let bytes = particle_bytes(dust, ParticleData::DUST);
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // red, green, and blue as floats
+            0x3F, 0x80, 0x00, 0x00,
+            0x3F, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            // scale
+            0x40, 0x00, 0x00, 0x00,
+        ]);
+    }
+
+    #[test]
+    fn item_particle_round_trip() {
+        let bytes = particle_bytes(
+            ParticleData::Item(ItemStack::new(812, 1)),
+            ParticleData::ITEM,
+        );
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // count, item ID, added and removed component counts
+            0x01, 0xAC, 0x06, 0x00, 0x00,
+        ]);
+    }
+
+    #[test]
+    fn particle_without_data_reads_nothing() {
+        let mut reader: &[u8] = &[0xFF];
+        let flame = 31;
+        assert_eq!(
+            ParticleData::read(&mut reader, flame).unwrap(),
+            ParticleData::None
+        );
+        assert_eq!(reader, [0xFF]);
+    }
+
+    #[test]
+    fn unknown_particle_is_rejected() {
+        let error = ParticleData::read(&[][..], ParticleData::MAX_ID + 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = ParticleData::read(&[][..], -1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn unsupported_particle_data_is_rejected() {
+        let error = ParticleData::read(&[0, 0, 0, 0][..], ParticleData::SHRIEK).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn statistics_update_layout() {
+        let update = StatisticsUpdate {
+            statistics: vec![Statistic {
+                category: StatCategory::Mined,
+                // minecraft:stone
+                statistic_id: 1,
+                value: 300,
+            }],
+        };
+        let mut bytes = Vec::new();
+        update.write(&mut bytes, ()).unwrap();
+        // count, category, statistic ID, then 300 as a two-byte varint.
+        assert_eq!(bytes, [0x01, 0x00, 0x01, 0xAC, 0x02]);
+    }
+
+    #[test]
+    fn map_update_with_two_icons() {
+        let update = MapUpdate {
+            map_id: 5,
+            scale: 0,
+            locked: false,
+            icons: Some(vec![
+                Icon {
+                    kind: MapIconType::Player,
+                    x: 16,
+                    z: -16,
+                    direction: 3,
+                    display_name: None,
+                },
+                Icon {
+                    kind: MapIconType::RedMarker,
+                    x: -128,
+                    z: 127,
+                    // Only the low 4 bits are sent.
+                    direction: 0x1F,
+                    display_name: None,
+                },
+            ]),
+            patch: None,
+        };
+        let mut bytes = Vec::new();
+        update.write(&mut bytes, ()).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            0x05, 0x00, 0x00,
+            // icons present, then the count
+            0x01, 0x02,
+            0x00, 0x10, 0xF0, 0x03, 0x00,
+            0x02, 0x80, 0x7F, 0x0F, 0x00,
+            // no columns
+            0x00,
+        ]);
+    }
+
+    #[test]
+    fn empty_statistics_update_is_only_the_count() {
+        let mut bytes = Vec::new();
+        StatisticsUpdate {
+            statistics: Vec::new(),
+        }
+        .write(&mut bytes, ())
+        .unwrap();
+        assert_eq!(bytes, [0x00]);
+    }
+
+    #[test]
+    fn map_update_with_empty_icons_and_no_patch() {
+        let update = MapUpdate {
+            map_id: 300,
+            scale: 1,
+            locked: false,
+            icons: Some(Vec::new()),
+            patch: None,
+        };
+        let mut bytes = Vec::new();
+        update.write(&mut bytes, ()).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // map ID, scale, locked
+            0xAC, 0x02, 0x01, 0x00,
+            // icons present, but none of them
+            0x01, 0x00,
+            // no columns
+            0x00,
+        ]);
+    }
+
+    #[test]
+    fn map_update_without_columns() {
+        let mut update = MapUpdate {
+            map_id: 5,
+            scale: 4,
+            locked: true,
+            icons: None,
+            patch: None,
+        };
+        let mut bytes = Vec::new();
+        update.write(&mut bytes, ()).unwrap();
+        assert_eq!(bytes, [0x05, 0x04, 0x01, 0x00, 0x00]);
+
+        update.patch = Some(MapPatch {
+            columns: 0,
+            rows: 1,
+            x: 0,
+            z: 0,
+            data: Vec::new(),
+        });
+        let error = update.write(&mut Vec::new(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn entity_effect_maps_keys_to_registry_ids() {
+        let effects: Registry = ["speed", "slowness", "haste"]
+            .into_iter()
+            .map(|path| Key::minecraft(path).unwrap())
+            .collect();
+        let effect = PotionEffect {
+            ambient: true,
+            show_particles: false,
+            ..PotionEffect::new(Key::minecraft("haste").unwrap(), 2, PotionEffect::INFINITE)
+        };
+
+        let packet = EntityEffect::new(7, &effect, &effects).unwrap();
+        assert_eq!(packet.effect_id, 2);
+        assert_eq!(
+            packet.flags,
+            EntityEffect::AMBIENT | EntityEffect::SHOW_ICON
+        );
+        assert_eq!(packet.effect(&effects), Some(effect));
+
+        let mut bytes = Vec::new();
+        packet.write(&mut bytes, ()).unwrap();
+        // entity ID, effect ID, amplifier, -1 as a varint, then the flags.
+        assert_eq!(
+            bytes,
+            [0x07, 0x02, 0x02, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0x05]
+        );
+    }
+
+    #[test]
+    fn entity_effect_of_unregistered_effect() {
+        let effects: Registry = [Key::minecraft("speed").unwrap()].into_iter().collect();
+        let effect = PotionEffect::new(Key::minecraft("haste").unwrap(), 0, 20);
+
+        assert_eq!(EntityEffect::new(7, &effect, &effects), None);
+    }
+
+    #[test]
+    fn blank_number_format_layout() {
+        let mut bytes = Vec::new();
+        NumberFormat::Blank.write(&mut bytes, ()).unwrap();
+        assert_eq!(bytes, [0x00]);
+    }
+
+    #[test]
+    fn styled_number_format_layout() {
+        let mut bytes = Vec::new();
+        NumberFormat::Styled(NamedColor::Gold)
+            .write(&mut bytes, ())
+            .unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            0x01,
+            // a compound with a single string field, "color": "gold"
+            0x0A,
+            0x08, 0x00, 0x05, b'c', b'o', b'l', b'o', b'r',
+            0x00, 0x04, b'g', b'o', b'l', b'd',
+            0x00,
+        ]);
+    }
+
+    #[test]
+    fn fixed_number_format_layout() {
+        let text = Text::string("MAX");
+        let mut bytes = Vec::new();
+        NumberFormat::Fixed(text.clone())
+            .write(&mut bytes, ())
+            .unwrap();
+
+        let mut expected = vec![0x02];
+        text.write(
+            &mut expected,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn registered_sound_event_round_trip() {
+        let sound = SoundEvent::Registered(127);
+        let mut bytes = Vec::new();
+        sound.write(&mut bytes, ()).unwrap();
+        // The ID is offset by one, which makes 127 a two-byte varint.
+        assert_eq!(bytes, [0x80, 0x01]);
+        assert_eq!(SoundEvent::read(bytes.as_slice(), ()).unwrap(), sound);
+    }
+
+    #[test]
+    fn inline_sound_event_round_trip() {
+        for fixed_range in [None, Some(16.0)] {
+            let sound = SoundEvent::Inline {
+                key: Key::new("custom", "bell").unwrap(),
+                fixed_range,
+            };
+            let mut bytes = Vec::new();
+            sound.write(&mut bytes, ()).unwrap();
+
+            let mut expected = vec![0x00, 11];
+            expected.extend_from_slice(b"custom:bell");
+            match fixed_range {
+                Some(range) => {
+                    expected.push(0x01);
+                    expected.extend_from_slice(&f32::to_be_bytes(range));
+                }
+                None => expected.push(0x00),
+            }
+            assert_eq!(bytes, expected);
+            assert_eq!(SoundEvent::read(bytes.as_slice(), ()).unwrap(), sound);
+        }
+    }
+
+    #[test]
+    fn sound_event_new_prefers_the_registry() {
+        let sounds: Registry = [Key::minecraft("block.bell.use").unwrap()]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            SoundEvent::new(&sounds, Key::minecraft("block.bell.use").unwrap()),
+            SoundEvent::Registered(0)
+        );
+        assert_eq!(
+            SoundEvent::new(&sounds, Key::new("custom", "bell").unwrap()),
+            SoundEvent::Inline {
+                key: Key::new("custom", "bell").unwrap(),
+                fixed_range: None,
+            }
+        );
+    }
+
+    #[test]
+    fn sound_event_id_overflow_is_rejected() {
+        let error = SoundEvent::Registered(i32::MAX)
+            .write(&mut Vec::new(), ())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn empty_metadata_is_only_the_terminator() {
+        let mut bytes = Vec::new();
+        Metadata::new().write(&mut bytes, ()).unwrap();
+        assert_eq!(bytes, [0xFF]);
+    }
+
+    #[test]
+    fn metadata_layout() {
+        let mut metadata = Metadata::new();
+        metadata.insert(Metadata::POSE, MetadataValue::Pose(Pose::Sneaking));
+        metadata.insert(Metadata::FLAGS, MetadataValue::Byte(0x02));
+        let mut bytes = Vec::new();
+        metadata.write(&mut bytes, ()).unwrap();
+
+        // Entries are written in index order.
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // index, type, value
+            0x00, 0x00, 0x02,
+            0x06, 0x15, 0x05,
+            // terminator
+            0xFF,
+        ]);
+        assert_eq!(Metadata::read(bytes.as_slice(), ()).unwrap(), metadata);
+    }
+
+    #[test]
+    fn reserved_metadata_index_is_rejected() {
+        let mut metadata = Metadata::new();
+        metadata.insert(0xFF, MetadataValue::Bool(true));
+        let error = metadata.write(&mut Vec::new(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn metadata_byte_layout() {
+        assert_eq!(metadata_value_bytes(MetadataValue::Byte(-1)), [0x00, 0xFF]);
+    }
+
+    #[test]
+    fn metadata_varint_layout() {
+        assert_eq!(
+            metadata_value_bytes(MetadataValue::VarInt(300)),
+            [0x01, 0xAC, 0x02]
+        );
+    }
+
+    #[test]
+    fn metadata_optional_text_layout() {
+        assert_eq!(
+            metadata_value_bytes(MetadataValue::OptionalText(None)),
+            [0x06, 0x00]
+        );
+
+        let text = Text::string("hello");
+        let bytes = metadata_value_bytes(MetadataValue::OptionalText(Some(text.clone())));
+        let mut expected = vec![0x06, 0x01];
+        text.write(
+            &mut expected,
+            TextArgs {
+                nbt: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn metadata_optional_block_state_layout() {
+        assert_eq!(
+            metadata_value_bytes(MetadataValue::OptionalBlockState(None)),
+            [0x0F, 0x00]
+        );
+        assert_eq!(
+            metadata_value_bytes(MetadataValue::OptionalBlockState(Some(1))),
+            [0x0F, 0x01]
+        );
+    }
+
+    #[test]
+    fn metadata_pose_layout() {
+        assert_eq!(
+            metadata_value_bytes(MetadataValue::Pose(Pose::Inhaling)),
+            [0x15, 0x11]
+        );
+    }
+
+    #[test]
+    fn unsupported_metadata_type_is_rejected() {
+        let error = MetadataValue::read(&[0x10][..], ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn light_data_layout() {
+        let mut light = LightData::new(2);
+        light.sky[1] = LightSection::Populated(Box::new([0x11; LightSection::LEN]));
+        light.sky[2] = LightSection::Empty;
+        light.block[0] = LightSection::Empty;
+        light.block[3] = LightSection::Populated(Box::new([0xF0; LightSection::LEN]));
+        let mut bytes = Vec::new();
+        light.write(&mut bytes, ()).unwrap();
+
+        #[rustfmt::skip]
+        let mut expected = vec![
+            // sky light mask
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0x02,
+            // block light mask
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0x08,
+            // empty sky light mask
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0x04,
+            // empty block light mask
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0x01,
+        ];
+        // One sky light array of 2048 bytes, then one block light array.
+        expected.extend_from_slice(&[0x01, 0x80, 0x10]);
+        expected.extend_from_slice(&[0x11; LightSection::LEN]);
+        expected.extend_from_slice(&[0x01, 0x80, 0x10]);
+        expected.extend_from_slice(&[0xF0; LightSection::LEN]);
+        assert_eq!(bytes, expected);
+
+        assert_eq!(LightData::read(bytes.as_slice(), 2).unwrap(), light);
+    }
+
+    #[test]
+    fn unchanged_light_data_is_empty_masks() {
+        let mut bytes = Vec::new();
+        LightData::new(2).write(&mut bytes, ()).unwrap();
+        // Four empty masks, then no sky or block light arrays.
+        assert_eq!(bytes, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn light_array_count_must_match_the_mask() {
+        #[rustfmt::skip]
+        let bytes = [
+            // sky light mask with section 0 set
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0x01,
+            // empty block light, empty sky light, and empty block light masks
+            0x00, 0x00, 0x00,
+            // no sky light arrays
+            0x00,
+        ];
+        let error = LightData::read(&bytes[..], 2).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// Reads the length-prefixed data array at the end of an encoded
+    /// [`PalettedContainer`], and unpacks `len` entries of `bits` bits each.
+    fn unpack_data(mut reader: &[u8], bits: u32, len: usize) -> Vec<u64> {
+        let longs = i32::read(&mut reader, IntArgs { varint: true }).unwrap();
+        let longs: Vec<i64> = (0..longs)
+            .map(|_| i64::read(&mut reader, IntArgs { varint: false }).unwrap())
+            .collect();
+        assert!(reader.is_empty());
+
+        let per_long = (64 / bits) as usize;
+        assert_eq!(longs.len(), len.div_ceil(per_long));
+        (0..len)
+            .map(|i| {
+                let shift = (i % per_long) as u32 * bits;
+                (longs[i / per_long] as u64 >> shift) & ((1 << bits) - 1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_value_palette_has_no_data() {
+        let mut bytes = Vec::new();
+        PalettedContainer(vec![7; ChunkSection::BIOMES])
+            .write(&mut bytes, PalettedContainerArgs::BIOMES)
+            .unwrap();
+        // bits per entry, value, data length
+        assert_eq!(bytes, [0x00, 0x07, 0x00]);
+    }
+
+    #[test]
+    fn empty_section_layout() {
+        let mut bytes = Vec::new();
+        ChunkSection::empty(3).write(&mut bytes, ()).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // block count
+            0x00, 0x00,
+            // block states, all air
+            0x00, 0x00, 0x00,
+            // biomes
+            0x00, 0x03, 0x00,
+        ]);
+    }
+
+    #[test]
+    fn indirect_palette_uses_the_minimum_bits() {
+        let mut blocks = vec![0; ChunkSection::BLOCKS];
+        blocks[1] = 9;
+        let mut bytes = Vec::new();
+        PalettedContainer(blocks)
+            .write(&mut bytes, PalettedContainerArgs::BLOCK_STATES)
+            .unwrap();
+
+        // Two values need one bit, but block states use at least four.
+        #[rustfmt::skip]
+        assert_eq!(bytes[..4], [
+            // bits per entry
+            0x04,
+            // palette length, then the palette in order of appearance
+            0x02, 0x00, 0x09,
+        ]);
+        let indices = unpack_data(&bytes[4..], 4, ChunkSection::BLOCKS);
+        assert_eq!(indices[..3], [0, 1, 0]);
+        assert!(indices[2..].iter().all(|&i| i == 0));
+    }
+
+    #[test]
+    fn indirect_palette_at_the_maximum_bits() {
+        // Eight values fit in the three bits biomes allow for a palette.
+        let biomes: Vec<i32> = (0..ChunkSection::BIOMES as i32)
+            .map(|i| 10 + i % 8)
+            .collect();
+        let mut bytes = Vec::new();
+        PalettedContainer(biomes)
+            .write(&mut bytes, PalettedContainerArgs::BIOMES)
+            .unwrap();
+
+        #[rustfmt::skip]
+        assert_eq!(bytes[..10], [
+            // bits per entry
+            0x03,
+            // palette length, then the palette
+            0x08, 10, 11, 12, 13, 14, 15, 16, 17,
+        ]);
+        let indices = unpack_data(&bytes[10..], 3, ChunkSection::BIOMES);
+        let expected: Vec<u64> = (0..ChunkSection::BIOMES as u64).map(|i| i % 8).collect();
+        assert_eq!(indices, expected);
+    }
+
+    #[test]
+    fn direct_palette_past_the_maximum_bits() {
+        // Nine values need four bits, which is more than biomes allow for a
+        // palette, so the biome IDs are written directly.
+        let biomes: Vec<i32> = (0..ChunkSection::BIOMES as i32)
+            .map(|i| 50 + i % 9)
+            .collect();
+        let mut bytes = Vec::new();
+        PalettedContainer(biomes.clone())
+            .write(&mut bytes, PalettedContainerArgs::BIOMES)
+            .unwrap();
+
+        // The bits per entry, followed by the data without a palette.
+        assert_eq!(bytes[0], 0x06);
+        let values = unpack_data(&bytes[1..], 6, ChunkSection::BIOMES);
+        let expected: Vec<u64> = biomes.iter().map(|&b| b as u64).collect();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn packed_values_do_not_span_longs() {
+        // Only ten 6-bit values fit in a long, leaving 4 bits unused.
+        let longs = PalettedContainer::pack([1; 11], 6);
+        assert_eq!(longs, [0x0041_0410_4104_1041, 0x1]);
+    }
+
+    #[test]
+    fn set_creative_mode_slot_layout() {
+        #[rustfmt::skip]
+        let bytes = [
+            0, 36, // slot
+            1,     // count
+            0x8C, 0x01, // item
+            0, 0,  // added and removed components
+        ];
+        let packet = SetCreativeModeSlot::read(bytes.as_slice(), ()).unwrap();
+        assert_eq!(packet.slot, SetCreativeModeSlot::HOTBAR_START);
+        assert_eq!(packet.item, ItemStack::new(140, 1));
+    }
+
+    #[test]
+    fn set_creative_mode_slot_clears_with_an_empty_stack() {
+        let bytes = [0, 40, 0];
+        let packet = SetCreativeModeSlot::read(bytes.as_slice(), ()).unwrap();
+        assert_eq!(packet.slot, 40);
+        assert!(packet.item.is_empty());
+    }
+}
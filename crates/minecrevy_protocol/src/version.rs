@@ -0,0 +1,311 @@
+//! Minecraft protocol versions, and packet IDs that differ between them.
+
+use std::fmt;
+
+use bevy::prelude::*;
+use minecrevy_io::{McRead, McWrite};
+use minecrevy_net::{
+    client::ProtocolState,
+    packet::{PacketIdMap, PacketRegistration},
+};
+
+/// A Minecraft protocol version number, as sent by the client in its
+/// [`Handshake`](crate::handshake::Handshake).
+///
+/// Versions are ordered by release, so ranges of versions can be compared.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct ProtocolVersion(pub i32);
+
+impl ProtocolVersion {
+    /// Minecraft 1.20.2.
+    pub const V1_20_2: Self = Self(764);
+    /// Minecraft 1.20.3 and 1.20.4.
+    pub const V1_20_3: Self = Self(765);
+    /// Minecraft 1.20.5 and 1.20.6.
+    pub const V1_20_5: Self = Self(766);
+    /// Minecraft 1.21 and 1.21.1.
+    pub const V1_21: Self = Self(767);
+    /// Minecraft 1.21.2 and 1.21.3.
+    pub const V1_21_2: Self = Self(768);
+
+    /// The version whose packets are defined by this crate.
+    pub const LATEST: Self = Self::V1_21;
+
+    /// Returns the name of the earliest release using this protocol version,
+    /// if it's known.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Self::V1_20_2 => Some("1.20.2"),
+            Self::V1_20_3 => Some("1.20.3"),
+            Self::V1_20_5 => Some("1.20.5"),
+            Self::V1_21 => Some("1.21"),
+            Self::V1_21_2 => Some("1.21.2"),
+            _ => None,
+        }
+    }
+}
+
+/// Displays the release name if it's known, or the version number otherwise.
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "protocol {}", self.0),
+        }
+    }
+}
+
+impl From<i32> for ProtocolVersion {
+    fn from(version: i32) -> Self {
+        Self(version)
+    }
+}
+
+/// The ID of a packet across protocol versions, for packets whose ID moved
+/// when other packets were added or removed.
+///
+/// Each ID applies from the version it was introduced in until the next ID
+/// takes over.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VersionedPacketId {
+    /// The versions each ID was introduced in, sorted by version.
+    ids: Vec<(ProtocolVersion, i32)>,
+}
+
+impl VersionedPacketId {
+    /// Creates a new [`VersionedPacketId`] that uses the given ID from the
+    /// given version onwards.
+    pub fn new(since: ProtocolVersion, id: i32) -> Self {
+        Self {
+            ids: vec![(since, id)],
+        }
+    }
+
+    /// Uses the given ID from the given version onwards, replacing the
+    /// previous ID for that version and later ones.
+    #[must_use]
+    pub fn since(mut self, version: ProtocolVersion, id: i32) -> Self {
+        let index = self.ids.partition_point(|(v, _)| *v < version);
+        if self.ids.get(index).is_some_and(|(v, _)| *v == version) {
+            self.ids[index].1 = id;
+        } else {
+            self.ids.insert(index, (version, id));
+        }
+        self
+    }
+
+    /// Returns the ID of the packet in the given version, or [`None`] if the
+    /// packet doesn't exist yet in that version.
+    pub fn resolve(&self, version: ProtocolVersion) -> Option<i32> {
+        let index = self.ids.partition_point(|(v, _)| *v <= version);
+        index.checked_sub(1).map(|i| self.ids[i].1)
+    }
+}
+
+/// [`Resource`] that stores the IDs of packets whose ID differs between the
+/// protocol versions clients can log in with.
+///
+/// The [`ServerProtocolPlugin`](crate::ServerProtocolPlugin) registers each
+/// packet with its ID in [`ProtocolVersion::LATEST`], which clients on other
+/// versions fall back to for packets that aren't listed here. Each client's
+/// IDs are then translated with the [`PacketIdMap`] returned by
+/// [`VersionedPackets::id_map`] for its version.
+///
+/// Insert this resource before adding the
+/// [`ServerProtocolPlugin`](crate::ServerProtocolPlugin), so that its packets
+/// are registered.
+#[derive(Resource)]
+#[derive(Clone, Default)]
+pub struct VersionedPackets {
+    packets: Vec<VersionedPacket>,
+}
+
+/// A packet type in a [`VersionedPackets`] set.
+#[derive(Clone)]
+struct VersionedPacket {
+    registration: PacketRegistration,
+    incoming: bool,
+    state: ProtocolState,
+    id: VersionedPacketId,
+}
+
+impl VersionedPackets {
+    /// Creates an empty [`VersionedPackets`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `T` as an incoming packet with the given [`ProtocolState`] and
+    /// [`VersionedPacketId`].
+    #[must_use]
+    pub fn incoming<T: McRead + Send + Sync + 'static>(
+        mut self,
+        state: ProtocolState,
+        id: VersionedPacketId,
+    ) -> Self {
+        self.packets.push(VersionedPacket {
+            registration: PacketRegistration::incoming::<T>(),
+            incoming: true,
+            state,
+            id,
+        });
+        self
+    }
+
+    /// Adds `T` as an outgoing packet with the given [`ProtocolState`] and
+    /// [`VersionedPacketId`].
+    #[must_use]
+    pub fn outgoing<T: McWrite + Send + Sync + 'static>(
+        mut self,
+        state: ProtocolState,
+        id: VersionedPacketId,
+    ) -> Self {
+        self.packets.push(VersionedPacket {
+            registration: PacketRegistration::outgoing::<T>(),
+            incoming: false,
+            state,
+            id,
+        });
+        self
+    }
+
+    /// Registers each packet with its ID in the given version.
+    ///
+    /// Packets without an ID in the version are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two different packet types in the same direction resolve to
+    /// the same ID and [`ProtocolState`].
+    pub fn register(&self, app: &mut App, version: ProtocolVersion) {
+        for packet in &self.packets {
+            if let Some(id) = packet.id.resolve(version) {
+                packet.registration.register(app, packet.state, id);
+            }
+        }
+    }
+
+    /// Returns the [`PacketIdMap`] that translates the IDs registered for
+    /// [`ProtocolVersion::LATEST`] to and from the IDs in the given version.
+    pub fn id_map(&self, version: ProtocolVersion) -> PacketIdMap {
+        let mut map = PacketIdMap::default();
+        let moved = self.packets.iter().filter_map(|packet| {
+            let latest = packet.id.resolve(ProtocolVersion::LATEST);
+            let client = packet.id.resolve(version);
+            (latest != client).then_some((packet, latest, client))
+        });
+
+        for (packet, latest, client) in moved.clone() {
+            match (packet.incoming, latest) {
+                // The client uses this packet's ID for another packet, or for
+                // none at all, unless another packet moved into it below.
+                (true, Some(latest)) => map.insert_incoming(packet.state, latest, None),
+                (false, Some(latest)) => map.insert_outgoing(packet.state, latest, client),
+                // Packets missing from the latest version aren't registered.
+                (_, None) => {}
+            }
+        }
+        for (packet, latest, client) in moved {
+            if let (true, Some(client)) = (packet.incoming, client) {
+                map.insert_incoming(packet.state, client, latest);
+            }
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_net::{client::ProtocolState, packet::OutgoingPacketIds};
+
+    use super::{ProtocolVersion, VersionedPacketId, VersionedPackets};
+    use crate::status::{Ping, Request, Response};
+
+    #[test]
+    fn resolve_selects_the_id_for_the_version_range() {
+        let id = VersionedPacketId::new(ProtocolVersion::V1_20_3, 0x10)
+            .since(ProtocolVersion::V1_21, 0x12);
+
+        assert_eq!(id.resolve(ProtocolVersion::V1_20_2), None);
+        assert_eq!(id.resolve(ProtocolVersion::V1_20_3), Some(0x10));
+        assert_eq!(id.resolve(ProtocolVersion::V1_20_5), Some(0x10));
+        assert_eq!(id.resolve(ProtocolVersion::V1_21), Some(0x12));
+        assert_eq!(id.resolve(ProtocolVersion::V1_21_2), Some(0x12));
+    }
+
+    #[test]
+    fn since_replaces_the_id_for_the_same_version() {
+        let id = VersionedPacketId::new(ProtocolVersion::V1_21, 0x10)
+            .since(ProtocolVersion::V1_21, 0x11);
+
+        assert_eq!(id, VersionedPacketId::new(ProtocolVersion::V1_21, 0x11));
+    }
+
+    fn packets() -> VersionedPackets {
+        VersionedPackets::new().outgoing::<Ping>(
+            ProtocolState::Status,
+            VersionedPacketId::new(ProtocolVersion::V1_20_5, 0x01)
+                .since(ProtocolVersion::V1_21, 0x02),
+        )
+    }
+
+    #[test]
+    fn register_uses_the_id_for_the_version() {
+        let mut old = App::new();
+        packets().register(&mut old, ProtocolVersion::V1_20_5);
+        let ids = old.world().resource::<OutgoingPacketIds>();
+        assert_eq!(ids.get::<Ping>(ProtocolState::Status), Some(0x01));
+
+        let mut new = App::new();
+        packets().register(&mut new, ProtocolVersion::V1_21);
+        let ids = new.world().resource::<OutgoingPacketIds>();
+        assert_eq!(ids.get::<Ping>(ProtocolState::Status), Some(0x02));
+    }
+
+    #[test]
+    fn register_skips_packets_missing_from_the_version() {
+        let mut app = App::new();
+        packets().register(&mut app, ProtocolVersion::V1_20_3);
+
+        let ids = app.world().get_resource::<OutgoingPacketIds>();
+        assert!(ids.is_none_or(|ids| ids.get::<Ping>(ProtocolState::Status).is_none()));
+    }
+
+    #[test]
+    fn id_map_translates_moved_packets() {
+        let packets = VersionedPackets::new()
+            .incoming::<Ping>(
+                ProtocolState::Status,
+                VersionedPacketId::new(ProtocolVersion::V1_20_5, 0x00)
+                    .since(ProtocolVersion::V1_21, 0x01),
+            )
+            .incoming::<Request>(
+                ProtocolState::Status,
+                VersionedPacketId::new(ProtocolVersion::V1_20_5, 0x02)
+                    .since(ProtocolVersion::V1_21, 0x00),
+            )
+            .outgoing::<Ping>(
+                ProtocolState::Status,
+                VersionedPacketId::new(ProtocolVersion::V1_20_5, 0x05)
+                    .since(ProtocolVersion::V1_21, 0x01),
+            )
+            .outgoing::<Response>(
+                ProtocolState::Status,
+                VersionedPacketId::new(ProtocolVersion::V1_21, 0x00),
+            );
+
+        let map = packets.id_map(ProtocolVersion::V1_20_5);
+        assert_eq!(map.incoming(ProtocolState::Status, 0x00), Some(0x01));
+        assert_eq!(map.incoming(ProtocolState::Status, 0x02), Some(0x00));
+        // The client's 0x01 isn't the registered Ping.
+        assert_eq!(map.incoming(ProtocolState::Status, 0x01), None);
+        // Packets that didn't move keep their ID.
+        assert_eq!(map.incoming(ProtocolState::Status, 0x03), Some(0x03));
+        assert_eq!(map.outgoing(ProtocolState::Status, 0x01), Some(0x05));
+        // The client doesn't have the response yet.
+        assert_eq!(map.outgoing(ProtocolState::Status, 0x00), None);
+
+        assert!(packets.id_map(ProtocolVersion::LATEST).is_empty());
+    }
+}
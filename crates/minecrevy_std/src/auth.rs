@@ -0,0 +1,365 @@
+//! This module contains the [`SessionServer`], which authenticates players in
+//! online mode.
+
+use bevy::prelude::*;
+use minecrevy_protocol::login::{EncryptionResponse, Property};
+use rsa::{pkcs8::EncodePublicKey, Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::profile::GameProfile;
+
+/// [`Resource`] for the session server that players are authenticated with
+/// in online mode.
+#[derive(Resource)]
+#[derive(Clone, Debug)]
+pub struct SessionServer {
+    /// The base URL of the session server.
+    pub url: String,
+    /// The HTTP client used to query the session server.
+    client: reqwest::Client,
+}
+
+impl Default for SessionServer {
+    fn default() -> Self {
+        Self::new("https://sessionserver.mojang.com")
+    }
+}
+
+impl SessionServer {
+    /// Creates a new [`SessionServer`] with the given base URL.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Asks the session server whether the player with the given username has
+    /// joined the server identified by the given [server hash](server_hash),
+    /// returning their verified [`GameProfile`] if so.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player isn't authenticated, or if the session
+    /// server couldn't be reached.
+    pub fn has_joined(
+        &self,
+        username: &str,
+        server_hash: &str,
+    ) -> impl std::future::Future<Output = Result<GameProfile, AuthError>> + Send + 'static {
+        let request = self
+            .client
+            .get(format!("{}/session/minecraft/hasJoined", self.url))
+            .query(&[("username", username), ("serverId", server_hash)]);
+
+        async move {
+            let response = request.send().await?.error_for_status()?;
+            // The session server responds with no content if the player
+            // hasn't joined.
+            if response.status() == reqwest::StatusCode::NO_CONTENT {
+                return Err(AuthError::NotAuthenticated);
+            }
+
+            let profile = response.json::<HasJoinedResponse>().await?;
+            Ok(GameProfile {
+                uuid: Uuid::parse_str(&profile.id).map_err(|_| AuthError::InvalidProfile)?,
+                username: profile.name,
+                properties: profile
+                    .properties
+                    .into_iter()
+                    .map(|p| Property {
+                        name: p.name,
+                        value: p.value,
+                        signature: p.signature,
+                    })
+                    .collect(),
+            })
+        }
+    }
+}
+
+/// Error type for [`SessionServer::has_joined`].
+#[derive(Error, Debug)]
+pub enum AuthError {
+    /// The player hasn't joined the server according to the session server.
+    #[error("player is not authenticated")]
+    NotAuthenticated,
+    /// The session server responded with an invalid profile.
+    #[error("session server responded with an invalid profile")]
+    InvalidProfile,
+    /// The session server couldn't be reached.
+    #[error("could not query session server: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The client's encryption response couldn't be decrypted, or didn't
+    /// match the request.
+    #[error("client sent an invalid encryption response")]
+    InvalidEncryption,
+}
+
+/// The body of a successful `hasJoined` response.
+#[derive(Deserialize)]
+struct HasJoinedResponse {
+    id: String,
+    name: String,
+    #[serde(default)]
+    properties: Vec<HasJoinedProperty>,
+}
+
+/// A property in a `hasJoined` response, such as the player's textures.
+#[derive(Deserialize)]
+struct HasJoinedProperty {
+    name: String,
+    value: String,
+    signature: Option<String>,
+}
+
+/// [`Event`] that starts authenticating a client with the [`SessionServer`],
+/// triggered once encryption has been established with the client.
+#[derive(Event)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Authenticate {
+    /// The username the client is logging in with.
+    pub username: String,
+    /// The [server hash](server_hash) of the client's encryption handshake.
+    pub server_hash: String,
+}
+
+/// [`Resource`] that stores the RSA key pair clients encrypt their shared
+/// secret with in online mode.
+#[derive(Resource)]
+#[derive(Clone, Debug)]
+pub struct ServerKey {
+    private: RsaPrivateKey,
+    /// The public key in ASN.1 DER format, as sent to clients.
+    public: Vec<u8>,
+}
+
+impl Default for ServerKey {
+    fn default() -> Self {
+        Self::generate()
+    }
+}
+
+impl ServerKey {
+    /// The size of generated keys, in bits, which matches vanilla.
+    pub const BITS: usize = 1024;
+
+    /// Generates a new random [`ServerKey`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can't be generated.
+    pub fn generate() -> Self {
+        let private = RsaPrivateKey::new(&mut rand::thread_rng(), Self::BITS)
+            .expect("failed to generate server key");
+        let public = RsaPublicKey::from(&private)
+            .to_public_key_der()
+            .expect("failed to encode server key")
+            .into_vec();
+        Self { private, public }
+    }
+
+    /// Returns the public key in ASN.1 DER format, as sent in
+    /// [`EncryptionRequest`](minecrevy_protocol::login::EncryptionRequest).
+    pub fn public_key(&self) -> &[u8] {
+        &self.public
+    }
+
+    /// Decrypts the shared secret of the given [`EncryptionResponse`], after
+    /// checking that its verify token matches the one the client was sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::InvalidEncryption`] if either value can't be
+    /// decrypted, the verify token doesn't match, or the shared secret isn't
+    /// 16 bytes long.
+    pub fn decrypt_secret(
+        &self,
+        response: &EncryptionResponse,
+        verify_token: &[u8],
+    ) -> Result<[u8; 16], AuthError> {
+        let decrypt = |data: &[u8]| {
+            self.private
+                .decrypt(Pkcs1v15Encrypt, data)
+                .map_err(|_| AuthError::InvalidEncryption)
+        };
+
+        if decrypt(&response.verify_token)? != verify_token {
+            return Err(AuthError::InvalidEncryption);
+        }
+        decrypt(&response.shared_secret)?
+            .try_into()
+            .map_err(|_| AuthError::InvalidEncryption)
+    }
+}
+
+/// [`Component`] for clients that were sent an
+/// [`EncryptionRequest`](minecrevy_protocol::login::EncryptionRequest) and
+/// haven't responded yet.
+#[derive(Component)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PendingEncryption {
+    /// The username the client is logging in with.
+    pub username: String,
+    /// The verify token the client was sent.
+    pub verify_token: [u8; 4],
+}
+
+/// [`Component`] for clients waiting on a response from the [`SessionServer`].
+#[derive(Component)]
+pub struct PendingAuth(pub oneshot::Receiver<Result<GameProfile, AuthError>>);
+
+/// Computes the server hash sent to the session server, which is the SHA-1
+/// digest of the server ID, shared secret, and encoded public key, formatted
+/// as a signed hexadecimal number.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut digest: [u8; 20] = Sha1::new()
+        .chain_update(server_id)
+        .chain_update(shared_secret)
+        .chain_update(public_key)
+        .finalize()
+        .into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        // Two's complement, to get the magnitude of the negative number.
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            let (value, overflow) = (!*byte).overflowing_add(u8::from(carry));
+            *byte = value;
+            carry = carry && overflow;
+        }
+    }
+
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let hex = hex.trim_start_matches('0');
+
+    if negative {
+        format!("-{hex}")
+    } else {
+        hex.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_protocol::login::EncryptionResponse;
+    use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
+    use uuid::Uuid;
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::{server_hash, AuthError, ServerKey, SessionServer};
+
+    #[test]
+    fn server_hash_matches_vanilla() {
+        assert_eq!(
+            server_hash("Notch", &[], &[]),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            server_hash("jeb_", &[], &[]),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            server_hash("simon", &[], &[]),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn decrypt_secret_checks_verify_token() {
+        let key = ServerKey::generate();
+        let public = RsaPublicKey::from_public_key_der(key.public_key()).unwrap();
+        let encrypt = |data: &[u8]| {
+            public
+                .encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, data)
+                .unwrap()
+        };
+
+        let secret = [42; 16];
+        let response = EncryptionResponse {
+            shared_secret: encrypt(&secret),
+            verify_token: encrypt(&[1, 2, 3, 4]),
+        };
+        assert_eq!(
+            key.decrypt_secret(&response, &[1, 2, 3, 4]).unwrap(),
+            secret
+        );
+        assert!(matches!(
+            key.decrypt_secret(&response, &[4, 3, 2, 1]),
+            Err(AuthError::InvalidEncryption)
+        ));
+    }
+
+    async fn session_server(response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/session/minecraft/hasJoined"))
+            .and(query_param("username", "Notch"))
+            .and(query_param("serverId", "hash"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn has_joined_returns_verified_profile() {
+        let server = session_server(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "069a79f444e94726a5befca90e38aaf5",
+            "name": "Notch",
+            "properties": [{
+                "name": "textures",
+                "value": "dGV4dHVyZXM=",
+                "signature": "c2lnbmF0dXJl",
+            }],
+        })))
+        .await;
+
+        let profile = SessionServer::new(server.uri())
+            .has_joined("Notch", "hash")
+            .await
+            .unwrap();
+        assert_eq!(
+            profile.uuid,
+            Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+        );
+        assert_eq!(profile.username, "Notch");
+        assert_eq!(profile.properties.len(), 1);
+        assert_eq!(profile.properties[0].name, "textures");
+        assert_eq!(
+            profile.properties[0].signature.as_deref(),
+            Some("c2lnbmF0dXJl")
+        );
+    }
+
+    #[tokio::test]
+    async fn has_joined_rejects_unauthenticated_player() {
+        let server = session_server(ResponseTemplate::new(204)).await;
+
+        let result = SessionServer::new(server.uri())
+            .has_joined("Notch", "hash")
+            .await;
+        assert!(matches!(result, Err(AuthError::NotAuthenticated)));
+    }
+
+    #[tokio::test]
+    async fn has_joined_reports_server_errors() {
+        let server = session_server(ResponseTemplate::new(500)).await;
+
+        let result = SessionServer::new(server.uri())
+            .has_joined("Notch", "hash")
+            .await;
+        assert!(matches!(result, Err(AuthError::Http(_))));
+    }
+}
@@ -0,0 +1,1421 @@
+//! This module contains the [`BlockPlugin`], which lets players break and place
+//! blocks, and edit signs.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::{
+    math::{DVec3, IVec3},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use minecrevy_io::{
+    nbt::{Compound, Tag},
+    McWrite,
+};
+use minecrevy_net::{
+    client::{PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{
+        AckBlockChange, BlockEntityData, BlockUpdate, ChunkBlockEntity, ChunkData, ChunkSection,
+        DigStatus, OpenSignEditor, PalettedContainer, PlayerAction, SetCreativeModeSlot,
+        SetHeldItem, UpdateSign, UseItemOn,
+    },
+    ServerProtocolPlugin,
+};
+use minecrevy_util::{coords, direction::Direction, game_mode::GameMode, item::ItemStack};
+
+use crate::play::{EnterPlay, PlayerGameMode, Position};
+
+/// [`Plugin`] that applies blocks broken and placed by players to the
+/// [`Blocks`] of the world, and broadcasts the changes to clients in the play
+/// state.
+///
+/// Placing a block listed in [`BlockEntityTypes`] creates its [`BlockEntity`],
+/// and placing a sign opens its editor. Text written on signs by players is
+/// stored in the sign's [`BlockEntity`]. If an [`ObfuscationConfig`] is
+/// inserted, hidden blocks exposed by breaking a block are revealed to clients.
+///
+/// Players can only break and place blocks within their reach, and survival
+/// players must dig a block for its [`DigTimes`] entry before it breaks. The
+/// block a player places is their [`HeldBlock`], derived from the selected
+/// slot of their [`Hotbar`] through [`BlockItems`].
+pub struct BlockPlugin;
+
+impl Plugin for BlockPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<Blocks>();
+        app.init_resource::<BlockEntityTypes>();
+        app.init_resource::<BlockItems>();
+        app.init_resource::<DigTimes>();
+
+        app.add_observer(Self::on_enter_play);
+        app.add_observer(Self::on_set_held_item);
+        app.add_observer(Self::on_set_creative_mode_slot);
+        app.add_observer(Self::on_player_action);
+        app.add_observer(Self::on_use_item_on);
+        app.add_observer(Self::on_update_sign);
+    }
+}
+
+impl BlockPlugin {
+    /// How far survival players can reach blocks from their eyes.
+    pub const SURVIVAL_REACH: f64 = 4.5;
+    /// How far creative players can reach blocks from their eyes.
+    pub const CREATIVE_REACH: f64 = 5.0;
+    /// The height of a standing player's eyes above their feet.
+    pub const EYE_HEIGHT: f64 = 1.62;
+    /// The fraction of a block's dig time that must pass before a survival
+    /// player can finish digging it, to allow for latency.
+    pub const DIG_TOLERANCE: f64 = 0.7;
+
+    /// Returns `true` if a player in the given [`GameMode`], with their feet at
+    /// the given position, can reach the block at `target`.
+    ///
+    /// As in vanilla, the closest point of the block must be within the
+    /// player's reach plus one block, to allow for latency.
+    pub fn in_reach(position: DVec3, game_mode: GameMode, target: IVec3) -> bool {
+        let reach = match game_mode {
+            GameMode::Creative => Self::CREATIVE_REACH,
+            _ => Self::SURVIVAL_REACH,
+        } + 1.;
+        let eye = position + DVec3::new(0., Self::EYE_HEIGHT, 0.);
+        let min = target.as_dvec3();
+        let closest = eye.clamp(min, min + DVec3::ONE);
+        eye.distance_squared(closest) < reach * reach
+    }
+
+    /// [`Observer`] [`System`] that gives players an empty [`Hotbar`] when they
+    /// enter the play state.
+    pub fn on_enter_play(trigger: Trigger<EnterPlay>, mut commands: Commands) {
+        commands
+            .entity(trigger.entity())
+            .insert((Hotbar::default(), HeldBlock::default()));
+    }
+
+    /// [`Observer`] [`System`] that selects a slot of a player's [`Hotbar`],
+    /// and updates their [`HeldBlock`] to match.
+    pub fn on_set_held_item(
+        trigger: Trigger<Recv<SetHeldItem>>,
+        block_items: Res<BlockItems>,
+        mut players: Query<(&mut Hotbar, &mut HeldBlock)>,
+    ) {
+        let packet = &trigger.event().0;
+        let player = trigger.entity();
+
+        let Ok((mut hotbar, mut held)) = players.get_mut(player) else {
+            return;
+        };
+        let Some(slot) = usize::try_from(packet.slot)
+            .ok()
+            .filter(|&slot| slot < Hotbar::SLOTS)
+        else {
+            debug!("Ignoring invalid held slot {} from {player}", packet.slot);
+            return;
+        };
+
+        hotbar.selected = slot;
+        held.0 = block_items.block_state(hotbar.held());
+    }
+
+    /// [`Observer`] [`System`] that puts the item stacks set by players in
+    /// [`GameMode::Creative`] in their [`Hotbar`], and updates their
+    /// [`HeldBlock`] to match.
+    ///
+    /// Slots outside of the hotbar aren't tracked.
+    pub fn on_set_creative_mode_slot(
+        trigger: Trigger<Recv<SetCreativeModeSlot>>,
+        block_items: Res<BlockItems>,
+        mut players: Query<(&PlayerGameMode, &mut Hotbar, &mut HeldBlock)>,
+    ) {
+        let packet = &trigger.event().0;
+        let player = trigger.entity();
+
+        let Ok((game_mode, mut hotbar, mut held)) = players.get_mut(player) else {
+            return;
+        };
+        if game_mode.0 != GameMode::Creative {
+            debug!("Ignoring creative mode slot from {player}, who isn't in creative mode");
+            return;
+        }
+        let Some(slot) = usize::try_from(packet.slot)
+            .ok()
+            .and_then(|slot| slot.checked_sub(SetCreativeModeSlot::HOTBAR_START as usize))
+            .filter(|&slot| slot < Hotbar::SLOTS)
+        else {
+            return;
+        };
+
+        hotbar.slots[slot] = packet.item.clone();
+        held.0 = block_items.block_state(hotbar.held());
+    }
+
+    /// [`Observer`] [`System`] that breaks blocks dug by players.
+    ///
+    /// Players in [`GameMode::Creative`] break blocks as soon as they start
+    /// digging, while players in [`GameMode::Survival`] break them once they
+    /// finish digging, if they started digging the same block at least
+    /// [`Self::DIG_TOLERANCE`] of its [`DigTimes`] entry earlier. Blocks out of
+    /// the player's reach are never broken.
+    pub fn on_player_action(
+        trigger: Trigger<Recv<PlayerAction>>,
+        mut commands: Commands,
+        mut writer: PacketWriter,
+        mut blocks: ResMut<Blocks>,
+        dig_times: Res<DigTimes>,
+        time: Res<Time<Real>>,
+        players: Query<(&PlayerGameMode, &Position, Option<&Digging>)>,
+        obfuscation: Option<Res<ObfuscationConfig>>,
+    ) {
+        let packet = &trigger.event().0;
+        let digger = trigger.entity();
+
+        let Ok((game_mode, position, digging)) = players.get(digger) else {
+            return;
+        };
+        let in_reach = Self::in_reach(position.0, game_mode.0, packet.position);
+
+        let breaks = match packet.status {
+            DigStatus::StartedDigging if in_reach => match game_mode.0 {
+                GameMode::Creative => true,
+                GameMode::Survival => {
+                    commands.entity(digger).insert(Digging {
+                        position: packet.position,
+                        started: time.elapsed(),
+                    });
+                    false
+                }
+                _ => false,
+            },
+            DigStatus::FinishedDigging if in_reach && game_mode.0 == GameMode::Survival => {
+                commands.entity(digger).remove::<Digging>();
+                let dig_time = dig_times
+                    .dig_time(blocks.get(packet.position))
+                    .mul_f64(Self::DIG_TOLERANCE);
+                let finished = digging.is_some_and(|digging| {
+                    digging.position == packet.position
+                        && time.elapsed().saturating_sub(digging.started) >= dig_time
+                });
+                if !finished {
+                    debug!(
+                        "Refusing to break {} for {digger}, who didn't dig it for long enough",
+                        packet.position
+                    );
+                    // Undo the break the client predicted.
+                    writer.client(digger).send(&BlockUpdate {
+                        position: packet.position,
+                        block_state: blocks.get(packet.position),
+                    });
+                }
+                finished
+            }
+            DigStatus::CancelledDigging => {
+                commands.entity(digger).remove::<Digging>();
+                false
+            }
+            DigStatus::StartedDigging | DigStatus::FinishedDigging => {
+                if !in_reach {
+                    debug!(
+                        "Ignoring digging at {} from {digger}, which is out of reach",
+                        packet.position
+                    );
+                }
+                false
+            }
+            // Not related to digging blocks.
+            _ => return,
+        };
+
+        if breaks && blocks.set(packet.position, Blocks::AIR) != Blocks::AIR {
+            let update = BlockUpdate {
+                position: packet.position,
+                block_state: Blocks::AIR,
+            };
+            writer.broadcast(ProtocolState::Play, &update);
+
+            // Reveal the hidden blocks that are now next to air.
+            if let Some(obfuscation) = obfuscation {
+                for direction in Direction::ALL {
+                    let position = packet.position + direction.offset();
+                    let block_state = blocks.get(position);
+                    if obfuscation.hidden.contains(&block_state) {
+                        let update = BlockUpdate {
+                            position,
+                            block_state,
+                        };
+                        writer.broadcast(ProtocolState::Play, &update);
+                    }
+                }
+            }
+        }
+
+        writer.client(digger).send(&AckBlockChange {
+            sequence: packet.sequence,
+        });
+    }
+
+    /// [`Observer`] [`System`] that places the [`HeldBlock`] of players
+    /// against the block they clicked.
+    ///
+    /// Players in [`GameMode::Adventure`] or [`GameMode::Spectator`] can't
+    /// place blocks, and nobody can place against a block out of their reach.
+    /// Placing a sign opens its editor for the player, who is
+    /// marked as [`EditingSign`] until they finish.
+    pub fn on_use_item_on(
+        trigger: Trigger<Recv<UseItemOn>>,
+        mut commands: Commands,
+        mut writer: PacketWriter,
+        mut blocks: ResMut<Blocks>,
+        block_entity_types: Res<BlockEntityTypes>,
+        players: Query<(&PlayerGameMode, &Position, &HeldBlock)>,
+    ) {
+        let packet = &trigger.event().0;
+        let placer = trigger.entity();
+
+        let Ok((game_mode, player_position, held)) = players.get(placer) else {
+            return;
+        };
+        let in_reach = Self::in_reach(player_position.0, game_mode.0, packet.position);
+        if !in_reach {
+            debug!(
+                "Ignoring placement against {} from {placer}, which is out of reach",
+                packet.position
+            );
+        }
+        let can_place = in_reach && matches!(game_mode.0, GameMode::Survival | GameMode::Creative);
+        let position = packet.position + packet.face.offset();
+
+        if let (true, Some(block_state)) = (can_place, held.0) {
+            if blocks.get(position) == Blocks::AIR {
+                blocks.set(position, block_state);
+                let update = BlockUpdate {
+                    position,
+                    block_state,
+                };
+                writer.broadcast(ProtocolState::Play, &update);
+
+                if let Some(&kind) = block_entity_types.get(&block_state) {
+                    let block_entity = BlockEntity::new(kind);
+                    let is_sign = block_entity.is_sign();
+                    blocks.set_block_entity(position, block_entity);
+
+                    if is_sign {
+                        writer.client(placer).send(&OpenSignEditor {
+                            position,
+                            is_front_text: true,
+                        });
+                        commands.entity(placer).insert(EditingSign(position));
+                    }
+                }
+            }
+        }
+
+        writer.client(placer).send(&AckBlockChange {
+            sequence: packet.sequence,
+        });
+    }
+
+    /// [`Observer`] [`System`] that stores the text written on a sign by a
+    /// player in the sign's [`BlockEntity`], and broadcasts it to clients.
+    ///
+    /// Updates are ignored unless the player is [`EditingSign`] at the
+    /// position and a sign is still there. Legacy formatting codes are stripped
+    /// from each line, and lines are truncated to
+    /// [`BlockEntity::MAX_SIGN_LINE_LEN`] characters. Lines longer than
+    /// [`UpdateSign::MAX_LINE_LEN`] are rejected when the packet is read.
+    pub fn on_update_sign(
+        trigger: Trigger<Recv<UpdateSign>>,
+        mut commands: Commands,
+        mut writer: PacketWriter,
+        mut blocks: ResMut<Blocks>,
+        editors: Query<&EditingSign>,
+    ) {
+        let packet = &trigger.event().0;
+        let editor = trigger.entity();
+
+        if !editors
+            .get(editor)
+            .is_ok_and(|editing| editing.0 == packet.position)
+        {
+            debug!(
+                "Ignoring sign update at {} from {editor}, who isn't editing it",
+                packet.position
+            );
+            return;
+        }
+        commands.entity(editor).remove::<EditingSign>();
+
+        let Some(sign) = blocks
+            .block_entity_mut(packet.position)
+            .filter(|block_entity| block_entity.is_sign())
+        else {
+            debug!(
+                "Ignoring sign update at {}, which isn't a sign",
+                packet.position
+            );
+            return;
+        };
+
+        let lines = packet.lines.clone().map(|line| {
+            strip_formatting(&line)
+                .chars()
+                .take(BlockEntity::MAX_SIGN_LINE_LEN)
+                .collect()
+        });
+        sign.set_sign_text(packet.is_front_text, lines);
+
+        let update = BlockEntityData {
+            position: packet.position,
+            kind: sign.kind,
+            data: Some(Tag::Compound(sign.data.clone())),
+        };
+        writer.broadcast(ProtocolState::Play, &update);
+    }
+}
+
+/// [`Resource`] that stores the block states and [`BlockEntity`]s of the
+/// world, keyed by block position.
+///
+/// Positions that have never been set are [`Blocks::AIR`].
+#[derive(Resource, Default)]
+pub struct Blocks {
+    states: HashMap<IVec3, i32>,
+    entities: HashMap<IVec3, BlockEntity>,
+}
+
+impl Blocks {
+    /// The block state ID of air.
+    pub const AIR: i32 = 0;
+
+    /// Returns the block state ID at the given position.
+    pub fn get(&self, position: IVec3) -> i32 {
+        self.states.get(&position).copied().unwrap_or(Self::AIR)
+    }
+
+    /// Sets the block state ID at the given position, returning the previous one.
+    ///
+    /// Changing the block state removes the [`BlockEntity`] at the position,
+    /// if any.
+    pub fn set(&mut self, position: IVec3, block_state: i32) -> i32 {
+        let previous = if block_state == Self::AIR {
+            self.states.remove(&position)
+        } else {
+            self.states.insert(position, block_state)
+        };
+        let previous = previous.unwrap_or(Self::AIR);
+
+        if previous != block_state {
+            self.entities.remove(&position);
+        }
+        previous
+    }
+
+    /// Returns the [`BlockEntity`] at the given position, if any.
+    pub fn block_entity(&self, position: IVec3) -> Option<&BlockEntity> {
+        self.entities.get(&position)
+    }
+
+    /// Returns the [`BlockEntity`] at the given position mutably, if any.
+    pub fn block_entity_mut(&mut self, position: IVec3) -> Option<&mut BlockEntity> {
+        self.entities.get_mut(&position)
+    }
+
+    /// Sets the [`BlockEntity`] at the given position, returning the previous
+    /// one.
+    pub fn set_block_entity(
+        &mut self,
+        position: IVec3,
+        block_entity: BlockEntity,
+    ) -> Option<BlockEntity> {
+        self.entities.insert(position, block_entity)
+    }
+
+    /// Removes the [`BlockEntity`] at the given position, returning it.
+    pub fn remove_block_entity(&mut self, position: IVec3) -> Option<BlockEntity> {
+        self.entities.remove(&position)
+    }
+
+    /// Returns the number of regions with at least one block that isn't air.
+    pub fn loaded_regions(&self) -> usize {
+        self.states
+            .keys()
+            .map(|&position| coords::block_to_region(position))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Builds the [`ChunkData`] of the given chunk column, for a world whose
+    /// lowest block is at `min_y` and that is `sections` sections tall.
+    ///
+    /// Every biome of the chunk is set to the given biome ID. If an
+    /// [`ObfuscationConfig`] is given, the blocks it hides are replaced by its
+    /// placeholder.
+    pub fn chunk_data(
+        &self,
+        chunk_x: i32,
+        chunk_z: i32,
+        min_y: i32,
+        sections: usize,
+        biome: i32,
+        obfuscation: Option<&ObfuscationConfig>,
+    ) -> ChunkData {
+        let origin = coords::chunk_to_block(IVec2::new(chunk_x, chunk_z), min_y);
+
+        let sections = (0..sections)
+            .map(|section| {
+                let mut block_states = Vec::with_capacity(ChunkSection::BLOCKS);
+                let mut block_count = 0;
+                for y in 0..16 {
+                    for z in 0..16 {
+                        for x in 0..16 {
+                            let y = section as i32 * 16 + y;
+                            let position = origin + IVec3::new(x, y, z);
+                            let state = match obfuscation {
+                                Some(obfuscation) => obfuscation.block_state(self, position),
+                                None => self.get(position),
+                            };
+                            if state != Self::AIR {
+                                block_count += 1;
+                            }
+                            block_states.push(state);
+                        }
+                    }
+                }
+
+                ChunkSection {
+                    block_count,
+                    block_states: PalettedContainer(block_states),
+                    biomes: PalettedContainer(vec![biome; ChunkSection::BIOMES]),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Without a block registry, every block is treated as motion-blocking.
+        let heightmaps =
+            self.compute_heightmaps(chunk_x, chunk_z, min_y, sections.len(), |state| {
+                state != Self::AIR
+            });
+
+        let height = sections.len() as i32 * 16;
+        let block_entities = self
+            .entities
+            .iter()
+            .filter(|(position, _)| {
+                coords::block_to_chunk(**position) == IVec2::new(chunk_x, chunk_z)
+                    && (min_y..min_y + height).contains(&position.y)
+            })
+            .map(|(position, block_entity)| {
+                let local = coords::block_to_local(*position);
+                let mut data = Vec::new();
+                Tag::Compound(block_entity.data.clone())
+                    .write_default(&mut data)
+                    .expect("writing to a Vec can't fail");
+                ChunkBlockEntity {
+                    x: local.x as u8,
+                    y: position.y as i16,
+                    z: local.z as u8,
+                    kind: block_entity.kind,
+                    data: Some(data),
+                }
+            })
+            .collect();
+
+        ChunkData {
+            heightmaps,
+            sections,
+            block_entities,
+        }
+    }
+
+    /// Computes the `MOTION_BLOCKING` and `WORLD_SURFACE` heightmaps of the
+    /// given chunk column, packed as [`ChunkData::heightmaps`] expects, for a
+    /// world whose lowest block is at `min_y` and that is `sections` sections
+    /// tall.
+    ///
+    /// Each entry is the height above the bottom of the world of the column's
+    /// highest block that counts, or `0` if there's none. Every non-air block
+    /// counts for `WORLD_SURFACE`, while `motion_blocking` decides which block
+    /// state IDs count for `MOTION_BLOCKING`.
+    pub fn compute_heightmaps(
+        &self,
+        chunk_x: i32,
+        chunk_z: i32,
+        min_y: i32,
+        sections: usize,
+        motion_blocking: impl Fn(i32) -> bool,
+    ) -> BTreeMap<String, Vec<i64>> {
+        let origin = coords::chunk_to_block(IVec2::new(chunk_x, chunk_z), min_y);
+        let height = sections as i32 * 16;
+        // The heights of each column in ZX order.
+        let mut world_surface = [0u64; 256];
+        let mut motion = [0u64; 256];
+
+        for z in 0..16 {
+            for x in 0..16 {
+                let column = (z * 16 + x) as usize;
+                // Scan down from the top until both heights are found.
+                for y in (0..height).rev() {
+                    let state = self.get(origin + IVec3::new(x, y, z));
+                    if world_surface[column] == 0 && state != Self::AIR {
+                        world_surface[column] = y as u64 + 1;
+                    }
+                    if motion[column] == 0 && motion_blocking(state) {
+                        motion[column] = y as u64 + 1;
+                    }
+                    if world_surface[column] != 0 && motion[column] != 0 {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let bits = u64::BITS - (height as u64).leading_zeros();
+        [
+            ("MOTION_BLOCKING", motion),
+            ("WORLD_SURFACE", world_surface),
+        ]
+        .into_iter()
+        .map(|(name, heights)| (name.to_owned(), PalettedContainer::pack(heights, bits)))
+        .collect()
+    }
+}
+
+/// [`Resource`] that hides blocks from clients, such as ores from players using
+/// X-ray cheats, unless they're next to air.
+///
+/// Hidden blocks are replaced with the placeholder in the chunks built with
+/// [`Blocks::chunk_data`], and revealed by the [`BlockPlugin`] once
+/// breaking a block exposes them.
+#[derive(Resource)]
+#[derive(Clone, Debug, Default)]
+pub struct ObfuscationConfig {
+    /// The block state IDs to hide.
+    pub hidden: HashSet<i32>,
+    /// The block state ID that hidden blocks are replaced with, such as stone.
+    pub placeholder: i32,
+}
+
+impl ObfuscationConfig {
+    /// Returns the block state ID that clients should see at the given
+    /// position.
+    pub fn block_state(&self, blocks: &Blocks, position: IVec3) -> i32 {
+        let state = blocks.get(position);
+        if !self.hidden.contains(&state) {
+            return state;
+        }
+
+        let exposed = Direction::ALL
+            .into_iter()
+            .any(|direction| blocks.get(position + direction.offset()) == Blocks::AIR);
+        if exposed {
+            state
+        } else {
+            self.placeholder
+        }
+    }
+}
+
+/// [`Component`] that stores the block state ID a player places when using
+/// their held item on a block, if any.
+///
+/// This is kept in sync with the selected slot of the player's [`Hotbar`].
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct HeldBlock(pub Option<i32>);
+
+/// [`Component`] that stores the item stacks in a player's hotbar, and which
+/// of its slots is selected.
+#[derive(Component)]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Hotbar {
+    /// The index of the selected slot.
+    pub selected: usize,
+    /// The item stacks in each slot, from left to right.
+    pub slots: [ItemStack; 9],
+}
+
+impl Hotbar {
+    /// The number of slots in a hotbar.
+    pub const SLOTS: usize = 9;
+
+    /// Returns the item stack in the selected slot.
+    pub fn held(&self) -> &ItemStack {
+        &self.slots[self.selected]
+    }
+}
+
+/// [`Component`] that stores the block a survival player is digging, and the
+/// [`Time<Real>`] elapsed when they started.
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct Digging {
+    /// The position of the block being dug.
+    pub position: IVec3,
+    /// When the player started digging.
+    pub started: Duration,
+}
+
+/// [`Component`] that marks a player as editing the sign at the given
+/// position, after placing it.
+#[derive(Component, Deref)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct EditingSign(pub IVec3);
+
+/// [`Resource`] that maps block state IDs to the protocol ID of the
+/// [`BlockEntity`] type created when they're placed, such as
+/// [`BlockEntity::SIGN`] for the states of signs.
+///
+/// Without a block registry, this starts empty.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Debug, Default)]
+pub struct BlockEntityTypes(pub HashMap<i32, i32>);
+
+/// [`Resource`] that maps item protocol IDs to the block state ID placed by
+/// the item, such as the default state of stone for the stone item.
+///
+/// Without a block registry, this starts empty.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Debug, Default)]
+pub struct BlockItems(pub HashMap<i32, i32>);
+
+impl BlockItems {
+    /// Returns the block state ID placed by the given item stack, if any.
+    pub fn block_state(&self, item: &ItemStack) -> Option<i32> {
+        if item.is_empty() {
+            return None;
+        }
+        self.get(&item.item).copied()
+    }
+}
+
+/// [`Resource`] that maps block state IDs to how long survival players take
+/// to dig them with their bare hands.
+///
+/// Block states that aren't listed take [`DigTimes::DEFAULT`], so that no
+/// block breaks instantly. Without a block registry, this starts empty.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Debug, Default)]
+pub struct DigTimes(pub HashMap<i32, Duration>);
+
+impl DigTimes {
+    /// The dig time of block states that aren't listed, which is one tick.
+    pub const DEFAULT: Duration = Duration::from_millis(50);
+
+    /// Returns how long survival players take to dig the given block state.
+    pub fn dig_time(&self, block_state: i32) -> Duration {
+        self.get(&block_state).copied().unwrap_or(Self::DEFAULT)
+    }
+}
+
+/// A block entity, which stores extra data about the block at its position,
+/// such as the text of a sign.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockEntity {
+    /// The protocol ID of the block entity type.
+    pub kind: i32,
+    /// The block entity's data, as an NBT compound.
+    pub data: Compound,
+}
+
+impl BlockEntity {
+    /// The protocol ID of the sign block entity type.
+    pub const SIGN: i32 = 7;
+    /// The protocol ID of the hanging sign block entity type.
+    pub const HANGING_SIGN: i32 = 8;
+    /// The protocol ID of the structure block entity type.
+    pub const STRUCTURE_BLOCK: i32 = 20;
+    /// The protocol ID of the command block entity type.
+    pub const COMMAND_BLOCK: i32 = 22;
+    /// The protocol ID of the jigsaw block entity type.
+    pub const JIGSAW: i32 = 31;
+
+    /// The maximum length of a line of sign text that's stored, in characters.
+    pub const MAX_SIGN_LINE_LEN: usize = 90;
+
+    /// Creates a new [`BlockEntity`] of the given type, with no data.
+    pub fn new(kind: i32) -> Self {
+        Self {
+            kind,
+            data: Compound::new(),
+        }
+    }
+
+    /// Returns `true` if this is a sign or hanging sign.
+    pub fn is_sign(&self) -> bool {
+        matches!(self.kind, Self::SIGN | Self::HANGING_SIGN)
+    }
+
+    /// Sets the lines of text on the front or back of this sign.
+    ///
+    /// Each line is stored as a JSON text component, as vanilla does, while
+    /// the rest of the side's data, such as its color, is kept.
+    pub fn set_sign_text(&mut self, is_front_text: bool, lines: [String; 4]) {
+        let side = if is_front_text {
+            "front_text"
+        } else {
+            "back_text"
+        };
+        let mut text = match self.data.remove(side) {
+            Some(Tag::Compound(text)) => text,
+            _ => Compound::new(),
+        };
+
+        let messages = lines
+            .into_iter()
+            .map(|line| Tag::String(serde_json::Value::String(line).to_string()))
+            .collect();
+        text.insert("messages".to_owned(), Tag::List(messages));
+        text.entry("color".to_owned())
+            .or_insert_with(|| Tag::from("black"));
+        text.entry("has_glowing_text".to_owned())
+            .or_insert_with(|| Tag::from(false));
+
+        self.data.insert(side.to_owned(), Tag::Compound(text));
+        self.data
+            .entry("is_waxed".to_owned())
+            .or_insert_with(|| Tag::from(false));
+    }
+}
+
+/// Removes legacy formatting codes, such as `§c`, from the given line.
+fn strip_formatting(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        math::{DVec3, IVec3, Vec3},
+        prelude::*,
+        time::TimeUpdateStrategy,
+    };
+    use minecrevy_io::{nbt::Tag, packet::RawPacket, McWrite};
+    use minecrevy_net::{client::ProtocolState, loopback::LoopbackClient, packet::Recv};
+    use minecrevy_protocol::{
+        play::{
+            BlockUpdate, DigStatus, PlayerAction, SetCreativeModeSlot, SetHeldItem, UpdateSign,
+            UseItemOn,
+        },
+        ServerProtocolPlugin,
+    };
+    use minecrevy_util::{direction::Direction, game_mode::GameMode, hand::Hand, item::ItemStack};
+
+    use super::{
+        BlockEntity, BlockItems, BlockPlugin, Blocks, DigTimes, EditingSign, HeldBlock, Hotbar,
+        ObfuscationConfig,
+    };
+    use crate::{
+        play::{EnterPlay, PlayerGameMode, Position},
+        testing,
+    };
+
+    const SIGN_STATE: i32 = 1;
+    const STONE_STATE: i32 = 2;
+    const ORE_STATE: i32 = 3;
+    const STONE_ITEM: i32 = 1;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(ServerProtocolPlugin {
+            handshake: false,
+            login: false,
+            play: true,
+            status: false,
+            config: false,
+        });
+        app.add_plugins(BlockPlugin);
+        app
+    }
+
+    /// Returns an app with the network server, to check the packets sent.
+    fn network_app() -> App {
+        let mut app = testing::app();
+        app.add_plugins(BlockPlugin);
+        app
+    }
+
+    /// Returns a network app whose real time advances by a tick every update.
+    fn ticking_app() -> App {
+        let mut app = network_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            50,
+        )));
+        app
+    }
+
+    /// Connects a player standing at the origin, holding stone.
+    fn player(app: &mut App, game_mode: GameMode) -> (Entity, LoopbackClient) {
+        let (player, connection) = testing::connect(app, ProtocolState::Play);
+        app.world_mut().entity_mut(player).insert((
+            PlayerGameMode(game_mode),
+            Position(DVec3::new(0.5, 64., 0.5)),
+            HeldBlock(Some(STONE_STATE)),
+        ));
+        (player, connection)
+    }
+
+    fn dig(app: &mut App, digger: Entity, status: DigStatus, position: IVec3, sequence: i32) {
+        let packet = PlayerAction {
+            status,
+            position,
+            face: Direction::Up,
+            sequence,
+        };
+        app.world_mut().trigger_targets(Recv(packet), digger);
+        app.world_mut().flush();
+    }
+
+    fn use_item_on(app: &mut App, placer: Entity, position: IVec3, face: Direction, sequence: i32) {
+        let packet = UseItemOn {
+            hand: Hand::MainHand,
+            position,
+            face,
+            cursor: Vec3::splat(0.5),
+            inside_block: false,
+            sequence,
+        };
+        app.world_mut().trigger_targets(Recv(packet), placer);
+        app.world_mut().flush();
+    }
+
+    fn block_at(app: &App, position: IVec3) -> i32 {
+        app.world().resource::<Blocks>().get(position)
+    }
+
+    fn assert_block_update(packet: RawPacket, position: IVec3, block_state: i32) {
+        let mut expected = Vec::new();
+        BlockUpdate {
+            position,
+            block_state,
+        }
+        .write_default(&mut expected)
+        .unwrap();
+        assert_eq!(packet.id, 0x09);
+        assert_eq!(packet.body, expected);
+    }
+
+    fn assert_ack(packet: RawPacket, sequence: u8) {
+        assert_eq!(packet.id, 0x05);
+        assert_eq!(packet.body, [sequence]);
+    }
+
+    #[test]
+    fn creative_digging_breaks_immediately() {
+        let mut app = network_app();
+        let (digger, mut connection) = player(&mut app, GameMode::Creative);
+        let (_, mut observer) = player(&mut app, GameMode::Survival);
+        let position = IVec3::new(3, 64, -2);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(position, STONE_STATE);
+
+        dig(&mut app, digger, DigStatus::StartedDigging, position, 5);
+
+        assert_eq!(block_at(&app, position), Blocks::AIR);
+        assert_block_update(
+            testing::recv(&mut app, &mut connection),
+            position,
+            Blocks::AIR,
+        );
+        assert_ack(testing::recv(&mut app, &mut connection), 5);
+        assert_block_update(
+            testing::recv(&mut app, &mut observer),
+            position,
+            Blocks::AIR,
+        );
+    }
+
+    #[test]
+    fn survival_digging_breaks_once_finished() {
+        let mut app = ticking_app();
+        let (digger, mut connection) = player(&mut app, GameMode::Survival);
+        let position = IVec3::new(0, 63, 0);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(position, STONE_STATE);
+        app.world_mut()
+            .resource_mut::<DigTimes>()
+            .insert(STONE_STATE, Duration::from_millis(200));
+
+        dig(&mut app, digger, DigStatus::StartedDigging, position, 1);
+        assert_eq!(block_at(&app, position), STONE_STATE);
+        assert_ack(testing::recv(&mut app, &mut connection), 1);
+
+        // 70% of the dig time is enough, to allow for latency.
+        for _ in 0..3 {
+            app.update();
+        }
+        dig(&mut app, digger, DigStatus::FinishedDigging, position, 2);
+        assert_eq!(block_at(&app, position), Blocks::AIR);
+        assert_block_update(
+            testing::recv(&mut app, &mut connection),
+            position,
+            Blocks::AIR,
+        );
+        assert_ack(testing::recv(&mut app, &mut connection), 2);
+    }
+
+    #[test]
+    fn survival_digging_too_fast_is_refused() {
+        let mut app = ticking_app();
+        let (digger, mut connection) = player(&mut app, GameMode::Survival);
+        let position = IVec3::new(0, 63, 0);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(position, STONE_STATE);
+
+        // Finishing in the same frame as starting is an instant break.
+        dig(&mut app, digger, DigStatus::StartedDigging, position, 1);
+        dig(&mut app, digger, DigStatus::FinishedDigging, position, 2);
+
+        assert_eq!(block_at(&app, position), STONE_STATE);
+        assert_ack(testing::recv(&mut app, &mut connection), 1);
+        // The client is told the block is still there.
+        assert_block_update(
+            testing::recv(&mut app, &mut connection),
+            position,
+            STONE_STATE,
+        );
+        assert_ack(testing::recv(&mut app, &mut connection), 2);
+    }
+
+    #[test]
+    fn survival_finish_requires_digging_the_same_block() {
+        let mut app = ticking_app();
+        let (digger, mut connection) = player(&mut app, GameMode::Survival);
+        let position = IVec3::new(0, 63, 0);
+        let other = IVec3::new(1, 63, 0);
+        for block in [position, other] {
+            app.world_mut()
+                .resource_mut::<Blocks>()
+                .set(block, STONE_STATE);
+        }
+
+        // Cancelling forgets the block being dug.
+        dig(&mut app, digger, DigStatus::StartedDigging, position, 1);
+        dig(&mut app, digger, DigStatus::CancelledDigging, position, 2);
+        assert_ack(testing::recv(&mut app, &mut connection), 1);
+        assert_ack(testing::recv(&mut app, &mut connection), 2);
+        dig(&mut app, digger, DigStatus::FinishedDigging, position, 3);
+        assert_eq!(block_at(&app, position), STONE_STATE);
+        assert_block_update(
+            testing::recv(&mut app, &mut connection),
+            position,
+            STONE_STATE,
+        );
+        assert_ack(testing::recv(&mut app, &mut connection), 3);
+
+        // Digging one block doesn't allow finishing another.
+        dig(&mut app, digger, DigStatus::StartedDigging, other, 4);
+        assert_ack(testing::recv(&mut app, &mut connection), 4);
+        dig(&mut app, digger, DigStatus::FinishedDigging, position, 5);
+        assert_eq!(block_at(&app, position), STONE_STATE);
+        assert_block_update(
+            testing::recv(&mut app, &mut connection),
+            position,
+            STONE_STATE,
+        );
+        assert_ack(testing::recv(&mut app, &mut connection), 5);
+    }
+
+    #[test]
+    fn digging_out_of_reach_is_ignored() {
+        let mut app = network_app();
+        let (digger, mut connection) = player(&mut app, GameMode::Creative);
+        // The eyes are at y = 65.62, so the top of this block is 6.62 away.
+        let position = IVec3::new(0, 58, 0);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(position, STONE_STATE);
+
+        dig(&mut app, digger, DigStatus::StartedDigging, position, 1);
+
+        assert_eq!(block_at(&app, position), STONE_STATE);
+        assert_ack(testing::recv(&mut app, &mut connection), 1);
+
+        // One block closer is within the creative reach plus tolerance.
+        let position = IVec3::new(0, 59, 0);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(position, STONE_STATE);
+        dig(&mut app, digger, DigStatus::StartedDigging, position, 2);
+        assert_eq!(block_at(&app, position), Blocks::AIR);
+    }
+
+    #[test]
+    fn placing_fills_the_block_against_the_clicked_face() {
+        let mut app = network_app();
+        let (placer, mut connection) = player(&mut app, GameMode::Survival);
+        let clicked = IVec3::new(0, 63, 0);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(clicked, STONE_STATE);
+
+        use_item_on(&mut app, placer, clicked, Direction::Up, 7);
+
+        let placed = IVec3::new(0, 64, 0);
+        assert_eq!(block_at(&app, placed), STONE_STATE);
+        assert_block_update(
+            testing::recv(&mut app, &mut connection),
+            placed,
+            STONE_STATE,
+        );
+        assert_ack(testing::recv(&mut app, &mut connection), 7);
+
+        // The block is now occupied, so placing again only acknowledges.
+        app.world_mut()
+            .entity_mut(placer)
+            .insert(HeldBlock(Some(ORE_STATE)));
+        use_item_on(&mut app, placer, clicked, Direction::Up, 8);
+        assert_eq!(block_at(&app, placed), STONE_STATE);
+        assert_ack(testing::recv(&mut app, &mut connection), 8);
+    }
+
+    #[test]
+    fn placing_out_of_reach_is_ignored() {
+        let mut app = network_app();
+        let (placer, mut connection) = player(&mut app, GameMode::Survival);
+        let clicked = IVec3::new(7, 64, 0);
+        app.world_mut()
+            .resource_mut::<Blocks>()
+            .set(clicked, STONE_STATE);
+
+        use_item_on(&mut app, placer, clicked, Direction::West, 1);
+
+        assert_eq!(block_at(&app, IVec3::new(6, 64, 0)), Blocks::AIR);
+        assert_ack(testing::recv(&mut app, &mut connection), 1);
+    }
+
+    /// Spawns a player in the given game mode that has entered the play state.
+    fn entered_player(app: &mut App, game_mode: GameMode) -> Entity {
+        let player = app.world_mut().spawn(PlayerGameMode(game_mode)).id();
+        app.world_mut().trigger_targets(EnterPlay, player);
+        app.world_mut().flush();
+        player
+    }
+
+    fn set_creative_mode_slot(app: &mut App, player: Entity, slot: i16, item: ItemStack) {
+        let packet = SetCreativeModeSlot { slot, item };
+        app.world_mut().trigger_targets(Recv(packet), player);
+        app.world_mut().flush();
+    }
+
+    fn set_held_item(app: &mut App, player: Entity, slot: i16) {
+        app.world_mut()
+            .trigger_targets(Recv(SetHeldItem { slot }), player);
+        app.world_mut().flush();
+    }
+
+    fn held_block(app: &App, player: Entity) -> Option<i32> {
+        app.world().get::<HeldBlock>(player).unwrap().0
+    }
+
+    #[test]
+    fn held_block_follows_the_selected_hotbar_slot() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<BlockItems>()
+            .insert(STONE_ITEM, STONE_STATE);
+        let player = entered_player(&mut app, GameMode::Creative);
+        assert_eq!(held_block(&app, player), None);
+
+        let stone = ItemStack::new(STONE_ITEM, 1);
+        set_creative_mode_slot(&mut app, player, SetCreativeModeSlot::HOTBAR_START, stone);
+        assert_eq!(held_block(&app, player), Some(STONE_STATE));
+
+        set_held_item(&mut app, player, 1);
+        assert_eq!(held_block(&app, player), None);
+
+        // Slots past the hotbar are ignored.
+        set_held_item(&mut app, player, Hotbar::SLOTS as i16);
+        assert_eq!(app.world().get::<Hotbar>(player).unwrap().selected, 1);
+
+        set_held_item(&mut app, player, 0);
+        assert_eq!(held_block(&app, player), Some(STONE_STATE));
+
+        // Clearing the selected slot leaves nothing to place.
+        set_creative_mode_slot(
+            &mut app,
+            player,
+            SetCreativeModeSlot::HOTBAR_START,
+            ItemStack::default(),
+        );
+        assert_eq!(held_block(&app, player), None);
+    }
+
+    #[test]
+    fn non_block_items_and_other_slots_do_not_set_the_held_block() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<BlockItems>()
+            .insert(STONE_ITEM, STONE_STATE);
+        let player = entered_player(&mut app, GameMode::Creative);
+
+        set_creative_mode_slot(
+            &mut app,
+            player,
+            SetCreativeModeSlot::HOTBAR_START,
+            ItemStack::new(STONE_ITEM + 1, 1),
+        );
+        assert_eq!(held_block(&app, player), None);
+
+        // The slot before the hotbar is in the main inventory.
+        let stone = ItemStack::new(STONE_ITEM, 1);
+        set_creative_mode_slot(
+            &mut app,
+            player,
+            SetCreativeModeSlot::HOTBAR_START - 1,
+            stone,
+        );
+        assert_eq!(held_block(&app, player), None);
+    }
+
+    #[test]
+    fn survival_players_cannot_set_creative_slots() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<BlockItems>()
+            .insert(STONE_ITEM, STONE_STATE);
+        let player = entered_player(&mut app, GameMode::Survival);
+
+        let stone = ItemStack::new(STONE_ITEM, 1);
+        set_creative_mode_slot(&mut app, player, SetCreativeModeSlot::HOTBAR_START, stone);
+
+        assert_eq!(held_block(&app, player), None);
+        assert!(app.world().get::<Hotbar>(player).unwrap().held().is_empty());
+    }
+
+    #[test]
+    fn adventure_players_cannot_place() {
+        let mut app = network_app();
+        let (placer, mut connection) = player(&mut app, GameMode::Adventure);
+        let clicked = IVec3::new(0, 63, 0);
+
+        use_item_on(&mut app, placer, clicked, Direction::East, 1);
+
+        assert_eq!(block_at(&app, IVec3::new(1, 63, 0)), Blocks::AIR);
+        assert_ack(testing::recv(&mut app, &mut connection), 1);
+    }
+
+    fn place_sign(app: &mut App, position: IVec3) {
+        let mut blocks = app.world_mut().resource_mut::<Blocks>();
+        blocks.set(position, SIGN_STATE);
+        blocks.set_block_entity(position, BlockEntity::new(BlockEntity::SIGN));
+    }
+
+    fn update_sign(app: &mut App, editor: Entity, position: IVec3, lines: [&str; 4]) {
+        let packet = UpdateSign {
+            position,
+            is_front_text: true,
+            lines: lines.map(str::to_owned),
+        };
+        app.world_mut().trigger_targets(Recv(packet), editor);
+        app.world_mut().flush();
+    }
+
+    fn front_messages(app: &App, position: IVec3) -> Option<Vec<String>> {
+        let sign = app.world().resource::<Blocks>().block_entity(position)?;
+        let messages = sign
+            .data
+            .get("front_text")?
+            .as_compound()?
+            .get("messages")?;
+        let messages = messages.as_list()?.iter().map(Tag::as_str);
+        messages.map(|line| line.map(str::to_owned)).collect()
+    }
+
+    #[test]
+    fn sign_update_stores_lines() {
+        let mut app = app();
+        let position = IVec3::new(1, 64, -3);
+        place_sign(&mut app, position);
+        let editor = app.world_mut().spawn(EditingSign(position)).id();
+
+        update_sign(&mut app, editor, position, ["§cHello", "world", "", "!"]);
+
+        assert_eq!(
+            front_messages(&app, position).unwrap(),
+            ["\"Hello\"", "\"world\"", "\"\"", "\"!\""],
+        );
+        let sign = app.world().resource::<Blocks>().block_entity(position);
+        assert_eq!(sign.unwrap().data.get("is_waxed"), Some(&Tag::Byte(0)));
+        assert!(app.world().get::<EditingSign>(editor).is_none());
+    }
+
+    #[test]
+    fn sign_update_truncates_long_lines() {
+        let mut app = app();
+        let position = IVec3::new(0, 0, 0);
+        place_sign(&mut app, position);
+        let editor = app.world_mut().spawn(EditingSign(position)).id();
+
+        let long = "a".repeat(BlockEntity::MAX_SIGN_LINE_LEN + 10);
+        update_sign(&mut app, editor, position, [&long, "", "", ""]);
+
+        let expected = format!("\"{}\"", "a".repeat(BlockEntity::MAX_SIGN_LINE_LEN));
+        assert_eq!(front_messages(&app, position).unwrap()[0], expected);
+    }
+
+    #[test]
+    fn sign_update_requires_editing_sign() {
+        let mut app = app();
+        let position = IVec3::new(0, 0, 0);
+        place_sign(&mut app, position);
+        let stranger = app.world_mut().spawn_empty().id();
+        let elsewhere = app.world_mut().spawn(EditingSign(IVec3::new(5, 0, 0))).id();
+
+        update_sign(&mut app, stranger, position, ["hi", "", "", ""]);
+        update_sign(&mut app, elsewhere, position, ["hi", "", "", ""]);
+
+        assert_eq!(front_messages(&app, position), None);
+    }
+
+    #[test]
+    fn breaking_a_sign_removes_its_block_entity() {
+        let mut app = app();
+        let position = IVec3::new(0, 0, 0);
+        place_sign(&mut app, position);
+
+        let mut blocks = app.world_mut().resource_mut::<Blocks>();
+        blocks.set(position, Blocks::AIR);
+        assert!(blocks.block_entity(position).is_none());
+    }
+
+    #[test]
+    fn chunk_data_includes_block_entities() {
+        let mut app = app();
+        let position = IVec3::new(-1, -60, 17);
+        place_sign(&mut app, position);
+
+        let blocks = app.world().resource::<Blocks>();
+        let chunk = blocks.chunk_data(-1, 1, -64, 24, 0, None);
+        assert_eq!(chunk.block_entities.len(), 1);
+        let block_entity = &chunk.block_entities[0];
+        assert_eq!(
+            (block_entity.x, block_entity.y, block_entity.z),
+            (15, -60, 1)
+        );
+        assert_eq!(block_entity.kind, BlockEntity::SIGN);
+        assert!(blocks
+            .chunk_data(0, 1, -64, 24, 0, None)
+            .block_entities
+            .is_empty());
+    }
+
+    fn obfuscation() -> ObfuscationConfig {
+        ObfuscationConfig {
+            hidden: [ORE_STATE].into(),
+            placeholder: STONE_STATE,
+        }
+    }
+
+    /// Returns the block state at the given position in the first section of
+    /// the chunk at the origin, built with the given obfuscation.
+    fn chunk_block(
+        blocks: &Blocks,
+        obfuscation: Option<&ObfuscationConfig>,
+        position: IVec3,
+    ) -> i32 {
+        let chunk = blocks.chunk_data(0, 0, 0, 1, 0, obfuscation);
+        let index = (position.y * 256 + position.z * 16 + position.x) as usize;
+        chunk.sections[0].block_states.0[index]
+    }
+
+    /// Returns blocks with an ore buried in stone at `(5, 5, 5)`, and an ore
+    /// next to air at `(10, 5, 5)`.
+    fn ores() -> Blocks {
+        let mut blocks = Blocks::default();
+        for x in 4..=6 {
+            for y in 4..=6 {
+                for z in 4..=6 {
+                    blocks.set(IVec3::new(x, y, z), STONE_STATE);
+                }
+            }
+        }
+        blocks.set(IVec3::new(5, 5, 5), ORE_STATE);
+        blocks.set(IVec3::new(10, 5, 5), ORE_STATE);
+        blocks
+    }
+
+    #[test]
+    fn chunk_data_hides_buried_ores() {
+        let blocks = ores();
+        let obfuscation = obfuscation();
+
+        let buried = IVec3::new(5, 5, 5);
+        assert_eq!(
+            chunk_block(&blocks, Some(&obfuscation), buried),
+            STONE_STATE
+        );
+        assert_eq!(chunk_block(&blocks, None, buried), ORE_STATE);
+    }
+
+    #[test]
+    fn chunk_data_shows_exposed_ores() {
+        let blocks = ores();
+        let obfuscation = obfuscation();
+
+        let exposed = IVec3::new(10, 5, 5);
+        assert_eq!(chunk_block(&blocks, Some(&obfuscation), exposed), ORE_STATE);
+        assert_eq!(chunk_block(&blocks, None, exposed), ORE_STATE);
+    }
+
+    #[test]
+    fn chunk_data_at_negative_coordinates() {
+        let mut blocks = Blocks::default();
+        blocks.set(IVec3::new(-1, -60, -17), STONE_STATE);
+
+        let chunk = blocks.chunk_data(-1, -2, -64, 1, 0, None);
+        // The block is at the highest X and Z of the chunk, 4 blocks up.
+        let index = 4 * 256 + 15 * 16 + 15;
+        assert_eq!(chunk.sections[0].block_count, 1);
+        assert_eq!(chunk.sections[0].block_states.0[index], STONE_STATE);
+
+        let neighbor = blocks.chunk_data(0, -1, -64, 1, 0, None);
+        assert_eq!(neighbor.sections[0].block_count, 0);
+    }
+
+    #[test]
+    fn heightmaps_of_known_columns() {
+        const GLASS_STATE: i32 = 4;
+        let mut blocks = Blocks::default();
+        // The first column of chunk (1, -1) has stone at the bottom of the
+        // world and glass above it, while the second has stone at the top.
+        blocks.set(IVec3::new(16, -16, -16), STONE_STATE);
+        blocks.set(IVec3::new(16, 10, -16), GLASS_STATE);
+        blocks.set(IVec3::new(17, 15, -16), STONE_STATE);
+
+        let heightmaps = blocks.compute_heightmaps(1, -1, -16, 2, |state| state == STONE_STATE);
+
+        // Heights up to 32 take 6 bits, so 10 columns fit in each long.
+        let motion_blocking = &heightmaps["MOTION_BLOCKING"];
+        assert_eq!(motion_blocking.len(), 26);
+        assert_eq!(motion_blocking[0], 1 | (32 << 6));
+        assert!(motion_blocking[1..].iter().all(|&long| long == 0));
+
+        // Glass isn't motion-blocking, but counts for the world surface.
+        let world_surface = &heightmaps["WORLD_SURFACE"];
+        assert_eq!(world_surface.len(), 26);
+        assert_eq!(world_surface[0], 27 | (32 << 6));
+        assert!(world_surface[1..].iter().all(|&long| long == 0));
+    }
+}
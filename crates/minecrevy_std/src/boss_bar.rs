@@ -0,0 +1,255 @@
+//! This module contains the [`BossBarPlugin`], which keeps the boss bars
+//! shown to players in sync with their [`BossBars`].
+
+use bevy::{prelude::*, utils::HashMap};
+use minecrevy_net::client::{PacketWriter, ProtocolState};
+use minecrevy_protocol::{
+    play::{BossBarAction, BossBarUpdate},
+    ServerProtocolPlugin,
+};
+use minecrevy_text::Text;
+use minecrevy_util::boss_bar::{BossBarColor, BossBarDivision};
+use uuid::Uuid;
+
+/// [`Plugin`] that sends boss bar packets to players whose [`BossBars`] have
+/// changed, so that only the differences from what they already see are sent.
+pub struct BossBarPlugin;
+
+impl Plugin for BossBarPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.add_systems(PostUpdate, Self::sync_boss_bars);
+    }
+}
+
+impl BossBarPlugin {
+    /// [`System`] that diffs the [`BossBars`] of players in the play state
+    /// against the boss bars last sent to them, and sends the packets needed
+    /// to add, update, or remove them.
+    pub fn sync_boss_bars(
+        mut writer: PacketWriter,
+        mut players: Query<(Entity, Ref<BossBars>, Option<&mut SentBossBars>)>,
+        mut commands: Commands,
+    ) {
+        for (player, bars, sent) in &mut players {
+            // Only open a writer when there's something to send, as each one
+            // flushes the client when dropped.
+            if !bars.is_changed() && sent.is_some() {
+                continue;
+            }
+            let Ok(writer) = writer.get_client(player) else {
+                continue;
+            };
+            if writer.state() != ProtocolState::Play {
+                continue;
+            }
+
+            let empty = SentBossBars::default();
+            let known = sent.as_deref().unwrap_or(&empty);
+
+            for uuid in known.0.keys() {
+                if !bars.contains_key(uuid) {
+                    writer.send(&BossBarUpdate {
+                        uuid: *uuid,
+                        action: BossBarAction::Remove,
+                    });
+                }
+            }
+            for (uuid, bar) in bars.iter() {
+                for action in bar.diff(known.0.get(uuid)) {
+                    writer.send(&BossBarUpdate {
+                        uuid: *uuid,
+                        action,
+                    });
+                }
+            }
+
+            match sent {
+                Some(mut sent) => sent.0.clone_from(&bars.0),
+                None => {
+                    commands.entity(player).insert(SentBossBars(bars.0.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// [`Component`] that stores the boss bars a player should see, keyed by the
+/// UUID that identifies each bar to the client.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct BossBars(pub HashMap<Uuid, BossBar>);
+
+/// [`Component`] that stores the boss bars last sent to a player.
+#[derive(Component)]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SentBossBars(HashMap<Uuid, BossBar>);
+
+/// A boss bar shown at the top of a player's screen.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BossBar {
+    /// The title displayed above the bar.
+    pub title: Text,
+    /// How full the bar is, from `0.0` to `1.0`.
+    pub health: f32,
+    /// The color of the bar.
+    pub color: BossBarColor,
+    /// The number of notches the bar is divided into.
+    pub division: BossBarDivision,
+    /// The flags of the bar, such as [`BossBarUpdate::DARKEN_SKY`].
+    pub flags: u8,
+}
+
+impl BossBar {
+    /// Creates a new, full [`BossBar`] with the given title.
+    pub fn new(title: impl Into<Text>) -> Self {
+        Self {
+            title: title.into(),
+            health: 1.,
+            color: BossBarColor::default(),
+            division: BossBarDivision::default(),
+            flags: 0,
+        }
+    }
+
+    /// Returns the actions that change the given previously sent boss bar
+    /// into this one, or add this one if none was sent.
+    pub fn diff(&self, previous: Option<&BossBar>) -> Vec<BossBarAction> {
+        let Some(previous) = previous else {
+            return vec![BossBarAction::Add {
+                title: self.title.clone(),
+                health: self.health,
+                color: self.color,
+                division: self.division,
+                flags: self.flags,
+            }];
+        };
+
+        let mut actions = Vec::new();
+        if self.title != previous.title {
+            actions.push(BossBarAction::UpdateTitle(self.title.clone()));
+        }
+        if self.health != previous.health {
+            actions.push(BossBarAction::UpdateHealth(self.health));
+        }
+        if self.color != previous.color || self.division != previous.division {
+            actions.push(BossBarAction::UpdateStyle {
+                color: self.color,
+                division: self.division,
+            });
+        }
+        if self.flags != previous.flags {
+            actions.push(BossBarAction::UpdateFlags(self.flags));
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_protocol::play::BossBarAction;
+    use minecrevy_text::Text;
+    use minecrevy_util::boss_bar::{BossBarColor, BossBarDivision};
+
+    use super::BossBar;
+
+    #[test]
+    fn new_bar_is_added() {
+        let bar = BossBar::new("Ender Dragon");
+        assert_eq!(
+            bar.diff(None),
+            [BossBarAction::Add {
+                title: Text::from("Ender Dragon"),
+                health: 1.,
+                color: BossBarColor::Pink,
+                division: BossBarDivision::None,
+                flags: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn unchanged_bar_has_no_actions() {
+        let bar = BossBar::new("Ender Dragon");
+        assert!(bar.diff(Some(&bar.clone())).is_empty());
+    }
+
+    #[test]
+    fn title_change_updates_only_the_title() {
+        let previous = BossBar::new("Ender Dragon");
+        let bar = BossBar::new("Wither");
+        assert_eq!(
+            bar.diff(Some(&previous)),
+            [BossBarAction::UpdateTitle(Text::from("Wither"))]
+        );
+    }
+
+    #[test]
+    fn health_change_updates_only_the_health() {
+        let previous = BossBar::new("Ender Dragon");
+        let bar = BossBar {
+            health: 0.25,
+            ..previous.clone()
+        };
+        assert_eq!(
+            bar.diff(Some(&previous)),
+            [BossBarAction::UpdateHealth(0.25)]
+        );
+    }
+
+    #[test]
+    fn color_or_division_change_updates_the_style() {
+        let previous = BossBar::new("Ender Dragon");
+        let recolored = BossBar {
+            color: BossBarColor::Purple,
+            ..previous.clone()
+        };
+        assert_eq!(
+            recolored.diff(Some(&previous)),
+            [BossBarAction::UpdateStyle {
+                color: BossBarColor::Purple,
+                division: BossBarDivision::None,
+            }]
+        );
+
+        let divided = BossBar {
+            division: BossBarDivision::Notches6,
+            ..previous.clone()
+        };
+        assert_eq!(
+            divided.diff(Some(&previous)),
+            [BossBarAction::UpdateStyle {
+                color: BossBarColor::Pink,
+                division: BossBarDivision::Notches6,
+            }]
+        );
+    }
+
+    #[test]
+    fn several_changes_are_sent_in_order() {
+        let previous = BossBar::new("Ender Dragon");
+        let bar = BossBar {
+            title: Text::from("Wither"),
+            health: 0.5,
+            color: BossBarColor::Red,
+            ..previous.clone()
+        };
+        assert_eq!(
+            bar.diff(Some(&previous)),
+            [
+                BossBarAction::UpdateTitle(Text::from("Wither")),
+                BossBarAction::UpdateHealth(0.5),
+                BossBarAction::UpdateStyle {
+                    color: BossBarColor::Red,
+                    division: BossBarDivision::None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! This module contains the [`BrandPlugin`], which exchanges brands with
+//! clients over the `minecraft:brand` plugin channel.
+
+use std::io;
+
+use bevy::prelude::*;
+use minecrevy_io::{args::StringArgs, McRead, McWrite};
+use minecrevy_net::client::PacketWriter;
+use minecrevy_protocol::play::PluginMessage;
+use minecrevy_util::key::Key;
+
+use crate::plugin_message::{
+    AppPluginChannelExt, PluginChannel, PluginMessagePlugin, RecvPluginMessage,
+};
+
+/// [`Plugin`] that stores the [`ClientBrand`] clients send, and replies with
+/// the [`ServerBrand`].
+///
+/// Vanilla clients send their brand in the configuration state, but brands
+/// are handled in the play state as well.
+///
+/// Configurable [`Resource`]s:
+/// - [`ServerBrand`]: The brand shown in the client's debug screen.
+pub struct BrandPlugin;
+
+impl Plugin for BrandPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<PluginMessagePlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<PluginMessagePlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<ServerBrand>();
+        app.add_plugin_channel::<Brand>(Brand::channel().key().clone());
+
+        app.add_observer(Self::on_brand);
+    }
+}
+
+impl BrandPlugin {
+    /// [`Observer`] [`System`] that stores the brand a client sent, and
+    /// replies with the server's brand.
+    pub fn on_brand(
+        trigger: Trigger<RecvPluginMessage<Brand>>,
+        brand_name: Res<ServerBrand>,
+        mut writer: PacketWriter,
+        mut commands: Commands,
+    ) {
+        let client = trigger.entity();
+        let brand = trigger.event().payload.0.clone();
+
+        commands.entity(client).insert(ClientBrand(brand));
+
+        let Ok(writer) = writer.get_client(client) else {
+            return;
+        };
+        match server_brand(&brand_name) {
+            Ok(message) => {
+                writer.send(&message);
+            }
+            Err(e) => warn!("Failed to encode server brand: {e}"),
+        }
+    }
+}
+
+/// The payload of a `minecraft:brand` plugin message, which names the
+/// software the sender is running, such as `vanilla` or `fabric`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Brand(pub String);
+
+impl Brand {
+    /// Returns the `minecraft:brand` [`PluginChannel`].
+    pub fn channel() -> PluginChannel<Brand> {
+        PluginChannel::new(Key::minecraft("brand").unwrap())
+    }
+}
+
+impl McRead for Brand {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self(String::read(
+            reader,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?))
+    }
+}
+
+impl McWrite for Brand {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.0.write(
+            writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )
+    }
+}
+
+/// Encodes the given brand into a `minecraft:brand` [`PluginMessage`] for a
+/// client.
+///
+/// # Errors
+///
+/// Returns an error if the brand is too long to encode.
+pub fn server_brand(name: &str) -> io::Result<PluginMessage> {
+    Brand::channel().encode(&Brand(name.to_owned()))
+}
+
+/// [`Component`] that stores the brand a client sent, such as `vanilla`.
+#[derive(Component, Deref)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ClientBrand(pub String);
+
+/// [`Resource`] that stores the brand sent to clients, which is shown in
+/// their debug screen.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ServerBrand(pub String);
+
+impl Default for ServerBrand {
+    fn default() -> Self {
+        Self("Minecrevy".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_io::{packet::RawPacket, McWrite};
+    use minecrevy_net::client::ProtocolState;
+
+    use super::{server_brand, Brand, BrandPlugin, ClientBrand, ServerBrand};
+    use crate::{plugin_message::PluginMessagePlugin, testing};
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins((PluginMessagePlugin, BrandPlugin));
+        app
+    }
+
+    fn brand_packet(id: i32, brand: &str) -> RawPacket {
+        let message = Brand::channel().encode(&Brand(brand.to_owned())).unwrap();
+        let mut body = Vec::new();
+        message.write_default(&mut body).unwrap();
+        RawPacket { id, body }
+    }
+
+    #[test]
+    fn server_brand_is_length_prefixed() {
+        let message = server_brand("Minecrevy").unwrap();
+        assert_eq!(message.channel.to_string(), "minecraft:brand");
+        assert_eq!(message.data, b"\x09Minecrevy");
+    }
+
+    #[test]
+    fn brand_is_exchanged_in_config() {
+        let mut app = app();
+        app.insert_resource(ServerBrand("Custom".to_owned()));
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Config);
+
+        connection.send_raw(brand_packet(0x02, "vanilla")).unwrap();
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x01,
+            &server_brand("Custom").unwrap(),
+        );
+        assert_eq!(
+            app.world().get::<ClientBrand>(client),
+            Some(&ClientBrand("vanilla".to_owned()))
+        );
+    }
+
+    #[test]
+    fn brand_is_exchanged_in_play() {
+        let mut app = app();
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        connection.send_raw(brand_packet(0x12, "fabric")).unwrap();
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x19,
+            &server_brand("Minecrevy").unwrap(),
+        );
+        assert_eq!(
+            app.world().get::<ClientBrand>(client),
+            Some(&ClientBrand("fabric".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! This module contains helpers for sending unsigned system messages, as used
+//! for server announcements and command feedback.
+
+use minecrevy_net::client::{ClientPacketWriter, PacketWriter, ProtocolState};
+use minecrevy_protocol::play::SystemChat;
+use minecrevy_text::Text;
+
+/// Shows a system message in a client's chat.
+pub fn send_system_message(writer: &ClientPacketWriter, message: impl Into<Text>) {
+    writer.send(&SystemChat::new(message));
+}
+
+/// Shows a system message in the chat of all clients in the play state.
+pub fn broadcast_system_message(writer: &mut PacketWriter, message: impl Into<Text>) {
+    writer.broadcast(ProtocolState::Play, &SystemChat::new(message));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::Duration};
+
+    use bevy::ecs::system::RunSystemOnce;
+    use minecrevy_net::client::{PacketWriter, ProtocolState};
+    use minecrevy_protocol::play::SystemChat;
+
+    use super::{broadcast_system_message, send_system_message};
+    use crate::testing;
+
+    #[test]
+    fn system_message_is_sent_to_chat() {
+        let mut app = testing::app();
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        testing::write(&mut app, client, |writer| {
+            send_system_message(writer, "Server restarting");
+        });
+
+        let packet = testing::recv(&mut app, &mut connection);
+        // Shown in chat, rather than above the hotbar.
+        assert_eq!(packet.body.last(), Some(&0));
+        testing::assert_packet(packet, 0x6C, &SystemChat::new("Server restarting"));
+    }
+
+    #[test]
+    fn system_message_is_broadcast_to_players() {
+        let mut app = testing::app();
+        let (_, mut first) = testing::connect(&mut app, ProtocolState::Play);
+        let (_, mut second) = testing::connect(&mut app, ProtocolState::Play);
+        let (_, mut logging_in) = testing::connect(&mut app, ProtocolState::Login);
+
+        app.world_mut()
+            .run_system_once(|mut writer: PacketWriter| {
+                broadcast_system_message(&mut writer, "Hello, everyone");
+            })
+            .unwrap();
+
+        let expected = SystemChat::new("Hello, everyone");
+        for connection in [&mut first, &mut second] {
+            testing::assert_packet(testing::recv(&mut app, connection), 0x6C, &expected);
+        }
+
+        logging_in.set_timeout(Duration::from_millis(50));
+        let error = logging_in.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,815 @@
+//! This module contains the [`ConfigPlugin`], which configures clients between
+//! logging in and entering the play state.
+
+use std::io;
+
+use bevy::{prelude::*, utils::HashMap};
+use minecrevy_io::{nbt::Tag, McRead, McWrite};
+use minecrevy_net::{
+    client::{PacketWriter, ProtocolState, StateChanged},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    config::{
+        AcknowledgeFinishConfiguration, ClientboundKnownPacks, FeatureFlags, FinishConfiguration,
+        KnownPack, RegistryData, ServerboundKnownPacks,
+    },
+    ServerProtocolPlugin,
+};
+use minecrevy_util::{
+    dimension::{Biome, DimensionType},
+    key::Key,
+    registry::Registry,
+};
+
+/// [`Plugin`] that configures clients once they've logged in, and moves them
+/// to the play state.
+///
+/// The client is sent, in order:
+/// 1. [`FeatureFlags`], enabling `minecraft:vanilla`.
+/// 2. [`ClientboundKnownPacks`], listing the [`KnownPacks`].
+///
+/// Once the client replies with the packs it knows, it's sent:
+/// 3. [`RegistryData`] for each of the [`SyncedRegistries`], with the data of
+///    the entries that have it. The client fills in the rest from the known
+///    packs.
+/// 4. [`FinishConfiguration`].
+///
+/// The client is moved to [`ProtocolState::Play`] once it acknowledges.
+///
+/// Configurable [`Resource`]s:
+/// - [`KnownPacks`]: The data packs the client is expected to know.
+/// - [`SyncedRegistries`]: The registries sent to the client.
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<KnownPacks>();
+        app.init_resource::<SyncedRegistries>();
+
+        app.add_observer(Self::on_enter_config);
+        app.add_observer(Self::on_known_packs);
+        app.add_observer(Self::on_acknowledge_finish);
+    }
+}
+
+impl ConfigPlugin {
+    /// [`Observer`] [`System`] that starts configuring a client that entered
+    /// the configuration state.
+    pub fn on_enter_config(
+        trigger: Trigger<StateChanged>,
+        mut writer: PacketWriter,
+        known_packs: Res<KnownPacks>,
+    ) {
+        if trigger.event().to != ProtocolState::Config {
+            return;
+        }
+        let Ok(writer) = writer.get_client(trigger.entity()) else {
+            return;
+        };
+
+        writer.send(&FeatureFlags {
+            flags: vec![Key::minecraft("vanilla").unwrap()],
+        });
+        writer.send(&ClientboundKnownPacks {
+            packs: known_packs.0.clone(),
+        });
+    }
+
+    /// [`Observer`] [`System`] that sends the [`SyncedRegistries`] to a client
+    /// once it replies with the packs it knows, and finishes configuration.
+    pub fn on_known_packs(
+        trigger: Trigger<Recv<ServerboundKnownPacks>>,
+        mut writer: PacketWriter,
+        known_packs: Res<KnownPacks>,
+        registries: Res<SyncedRegistries>,
+    ) {
+        let packet = &trigger.event().0;
+        let client = trigger.entity();
+
+        if let Some(missing) = known_packs.0.iter().find(|p| !packet.packs.contains(p)) {
+            // The client will fail to fill in the registry entries.
+            warn!(
+                "Client {client} doesn't know pack {}:{} {}",
+                missing.namespace, missing.id, missing.version
+            );
+        }
+
+        let writer = writer.client(client);
+        for registry in &registries.0 {
+            writer.send(&RegistryData::<Tag> {
+                registry: registry.id.clone(),
+                entries: registry
+                    .entries
+                    .iter()
+                    .map(|(_, key)| (key.clone(), registry.data.get(key).cloned()))
+                    .collect(),
+            });
+        }
+        writer.send(&FinishConfiguration);
+    }
+
+    /// [`Observer`] [`System`] that moves a client to the play state once it
+    /// acknowledges the end of configuration.
+    pub fn on_acknowledge_finish(
+        trigger: Trigger<Recv<AcknowledgeFinishConfiguration>>,
+        mut writer: PacketWriter,
+    ) {
+        let Ok(mut writer) = writer.get_client(trigger.entity()) else {
+            return;
+        };
+        writer.set_state(ProtocolState::Play);
+    }
+}
+
+/// [`Resource`] that stores the data packs that clients are expected to know,
+/// so that registry entry data doesn't need to be sent.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct KnownPacks(pub Vec<KnownPack>);
+
+impl Default for KnownPacks {
+    fn default() -> Self {
+        Self(vec![KnownPack::core("1.21")])
+    }
+}
+
+/// [`Resource`] that stores the registries sent to clients during
+/// configuration.
+///
+/// The numeric IDs of each registry's entries, such as the biome IDs in chunk
+/// data, are their indices in its [`Registry`]. Defaults to every synchronized
+/// vanilla 1.21 registry, with the vanilla [`DimensionType`]s and [`Biome`]s
+/// that have typed definitions sent as data. Custom entries, such as custom
+/// dimension types or biomes, are added with [`SyncedRegistries::register`].
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct SyncedRegistries(pub Vec<SyncedRegistry>);
+
+impl SyncedRegistries {
+    /// Returns the registry with the given ID, if it's synced.
+    pub fn registry(&self, id: &Key) -> Option<&SyncedRegistry> {
+        self.0.iter().find(|registry| registry.id == *id)
+    }
+
+    /// Adds an entry with the given data to the registry with the given ID,
+    /// returning the entry's numeric ID.
+    ///
+    /// The data is encoded as network NBT, such as a [`DimensionType`] or a
+    /// [`Biome`]. The registry is added if it isn't synced yet. If the entry
+    /// already exists, such as a vanilla entry, it keeps its numeric ID and
+    /// its data is replaced.
+    pub fn register(
+        &mut self,
+        registry: Key,
+        entry: Key,
+        data: &impl McWrite<Args = ()>,
+    ) -> io::Result<i32> {
+        let mut bytes = Vec::new();
+        data.write(&mut bytes, ())?;
+        let data = Tag::read(bytes.as_slice(), ())?;
+
+        let index = match self.0.iter().position(|synced| synced.id == registry) {
+            Some(index) => index,
+            None => {
+                self.0.push(SyncedRegistry::new(registry));
+                self.0.len() - 1
+            }
+        };
+        let registry = &mut self.0[index];
+        let id = registry.entries.insert(entry.clone());
+        registry.data.insert(entry, data);
+        Ok(id)
+    }
+}
+
+impl Default for SyncedRegistries {
+    fn default() -> Self {
+        let mut registries = Self(
+            VANILLA_REGISTRIES
+                .iter()
+                .map(|(registry, entries)| SyncedRegistry {
+                    id: Key::minecraft(*registry).unwrap(),
+                    entries: entries
+                        .iter()
+                        .map(|entry| Key::minecraft(*entry).unwrap())
+                        .collect(),
+                    data: HashMap::new(),
+                })
+                .collect(),
+        );
+
+        let dimension_type = Key::minecraft("dimension_type").unwrap();
+        for (entry, data) in [
+            ("overworld", DimensionType::OVERWORLD),
+            ("the_nether", DimensionType::THE_NETHER),
+            ("the_end", DimensionType::THE_END),
+        ] {
+            let entry = Key::minecraft(entry).unwrap();
+            registries
+                .register(dimension_type.clone(), entry, &data)
+                .expect("dimension types encode as NBT");
+        }
+
+        let biome = Key::minecraft("worldgen/biome").unwrap();
+        for (entry, data) in [("plains", Biome::PLAINS), ("the_void", Biome::THE_VOID)] {
+            let entry = Key::minecraft(entry).unwrap();
+            registries
+                .register(biome.clone(), entry, &data)
+                .expect("biomes encode as NBT");
+        }
+
+        registries
+    }
+}
+
+/// A registry sent to clients during configuration.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SyncedRegistry {
+    /// The ID of the registry, such as `minecraft:dimension_type`.
+    pub id: Key,
+    /// The keys of the registry's entries, in order of their numeric IDs.
+    pub entries: Registry,
+    /// The data of the registry's entries, as network NBT, keyed by entry.
+    ///
+    /// Entries without data are filled in by the client from its known packs.
+    pub data: HashMap<Key, Tag>,
+}
+
+impl SyncedRegistry {
+    /// Creates a new, empty [`SyncedRegistry`] with the given ID.
+    pub fn new(id: Key) -> Self {
+        Self {
+            id,
+            entries: Registry::new(),
+            data: HashMap::new(),
+        }
+    }
+}
+
+/// The entries of the synchronized vanilla 1.21 registries.
+const VANILLA_REGISTRIES: &[(&str, &[&str])] = &[
+    (
+        "banner_pattern",
+        &[
+            "base",
+            "border",
+            "bricks",
+            "circle",
+            "creeper",
+            "cross",
+            "curly_border",
+            "diagonal_left",
+            "diagonal_right",
+            "diagonal_up_left",
+            "diagonal_up_right",
+            "flow",
+            "flower",
+            "globe",
+            "gradient",
+            "gradient_up",
+            "guster",
+            "half_horizontal",
+            "half_horizontal_bottom",
+            "half_vertical",
+            "half_vertical_right",
+            "mojang",
+            "piglin",
+            "rhombus",
+            "skull",
+            "small_stripes",
+            "square_bottom_left",
+            "square_bottom_right",
+            "square_top_left",
+            "square_top_right",
+            "straight_cross",
+            "stripe_bottom",
+            "stripe_center",
+            "stripe_downleft",
+            "stripe_downright",
+            "stripe_left",
+            "stripe_middle",
+            "stripe_right",
+            "stripe_top",
+            "triangle_bottom",
+            "triangle_top",
+            "triangles_bottom",
+            "triangles_top",
+        ],
+    ),
+    (
+        "chat_type",
+        &[
+            "chat",
+            "emote_command",
+            "msg_command_incoming",
+            "msg_command_outgoing",
+            "say_command",
+            "team_msg_command_incoming",
+            "team_msg_command_outgoing",
+        ],
+    ),
+    (
+        "damage_type",
+        &[
+            "arrow",
+            "bad_respawn_point",
+            "cactus",
+            "campfire",
+            "cramming",
+            "dragon_breath",
+            "drown",
+            "dry_out",
+            "explosion",
+            "fall",
+            "falling_anvil",
+            "falling_block",
+            "falling_stalactite",
+            "fireball",
+            "fireworks",
+            "fly_into_wall",
+            "freeze",
+            "generic",
+            "generic_kill",
+            "hot_floor",
+            "in_fire",
+            "in_wall",
+            "indirect_magic",
+            "lava",
+            "lightning_bolt",
+            "magic",
+            "mob_attack",
+            "mob_attack_no_aggro",
+            "mob_projectile",
+            "on_fire",
+            "out_of_world",
+            "outside_border",
+            "player_attack",
+            "player_explosion",
+            "sonic_boom",
+            "spit",
+            "stalagmite",
+            "starve",
+            "sting",
+            "sweet_berry_bush",
+            "thorns",
+            "thrown",
+            "trident",
+            "unattributed_fireball",
+            "wind_charge",
+            "wither",
+            "wither_skull",
+        ],
+    ),
+    (
+        "dimension_type",
+        &["overworld", "overworld_caves", "the_end", "the_nether"],
+    ),
+    (
+        "enchantment",
+        &[
+            "aqua_affinity",
+            "bane_of_arthropods",
+            "binding_curse",
+            "blast_protection",
+            "breach",
+            "channeling",
+            "density",
+            "depth_strider",
+            "efficiency",
+            "feather_falling",
+            "fire_aspect",
+            "fire_protection",
+            "flame",
+            "fortune",
+            "frost_walker",
+            "impaling",
+            "infinity",
+            "knockback",
+            "looting",
+            "loyalty",
+            "luck_of_the_sea",
+            "lure",
+            "mending",
+            "multishot",
+            "piercing",
+            "power",
+            "projectile_protection",
+            "protection",
+            "punch",
+            "quick_charge",
+            "respiration",
+            "riptide",
+            "sharpness",
+            "silk_touch",
+            "smite",
+            "soul_speed",
+            "sweeping_edge",
+            "swift_sneak",
+            "thorns",
+            "unbreaking",
+            "vanishing_curse",
+            "wind_burst",
+        ],
+    ),
+    (
+        "jukebox_song",
+        &[
+            "11",
+            "13",
+            "5",
+            "blocks",
+            "cat",
+            "chirp",
+            "creator",
+            "creator_music_box",
+            "far",
+            "mall",
+            "mellohi",
+            "otherside",
+            "pigstep",
+            "precipice",
+            "relic",
+            "stal",
+            "strad",
+            "wait",
+            "ward",
+        ],
+    ),
+    (
+        "painting_variant",
+        &[
+            "alban",
+            "aztec",
+            "aztec2",
+            "backyard",
+            "baroque",
+            "bomb",
+            "bouquet",
+            "burning_skull",
+            "bust",
+            "cavebird",
+            "changing",
+            "cotan",
+            "courbet",
+            "creebet",
+            "donkey_kong",
+            "earth",
+            "endboss",
+            "fern",
+            "fighters",
+            "finding",
+            "fire",
+            "graham",
+            "humble",
+            "kebab",
+            "lowmist",
+            "match",
+            "meditative",
+            "orb",
+            "owlemons",
+            "passage",
+            "pigscene",
+            "plant",
+            "pointer",
+            "pond",
+            "pool",
+            "prairie_ride",
+            "sea",
+            "skeleton",
+            "skull_and_roses",
+            "stage",
+            "sunflowers",
+            "sunset",
+            "tides",
+            "unpacked",
+            "void",
+            "wanderer",
+            "wasteland",
+            "water",
+            "wind",
+            "wither",
+        ],
+    ),
+    (
+        "trim_material",
+        &[
+            "amethyst",
+            "copper",
+            "diamond",
+            "emerald",
+            "gold",
+            "iron",
+            "lapis",
+            "netherite",
+            "quartz",
+            "redstone",
+        ],
+    ),
+    (
+        "trim_pattern",
+        &[
+            "bolt",
+            "coast",
+            "dune",
+            "eye",
+            "flow",
+            "host",
+            "raiser",
+            "rib",
+            "sentry",
+            "shaper",
+            "silence",
+            "snout",
+            "spire",
+            "tide",
+            "vex",
+            "ward",
+            "wayfinder",
+            "wild",
+        ],
+    ),
+    (
+        "wolf_variant",
+        &[
+            "ashen", "black", "chestnut", "pale", "rusty", "snowy", "spotted", "striped", "woods",
+        ],
+    ),
+    (
+        "worldgen/biome",
+        &[
+            "badlands",
+            "bamboo_jungle",
+            "basalt_deltas",
+            "beach",
+            "birch_forest",
+            "cherry_grove",
+            "cold_ocean",
+            "crimson_forest",
+            "dark_forest",
+            "deep_cold_ocean",
+            "deep_dark",
+            "deep_frozen_ocean",
+            "deep_lukewarm_ocean",
+            "deep_ocean",
+            "desert",
+            "dripstone_caves",
+            "end_barrens",
+            "end_highlands",
+            "end_midlands",
+            "eroded_badlands",
+            "flower_forest",
+            "forest",
+            "frozen_ocean",
+            "frozen_peaks",
+            "frozen_river",
+            "grove",
+            "ice_spikes",
+            "jagged_peaks",
+            "jungle",
+            "lukewarm_ocean",
+            "lush_caves",
+            "mangrove_swamp",
+            "meadow",
+            "mushroom_fields",
+            "nether_wastes",
+            "ocean",
+            "old_growth_birch_forest",
+            "old_growth_pine_taiga",
+            "old_growth_spruce_taiga",
+            "plains",
+            "river",
+            "savanna",
+            "savanna_plateau",
+            "small_end_islands",
+            "snowy_beach",
+            "snowy_plains",
+            "snowy_slopes",
+            "snowy_taiga",
+            "soul_sand_valley",
+            "sparse_jungle",
+            "stony_peaks",
+            "stony_shore",
+            "sunflower_plains",
+            "swamp",
+            "taiga",
+            "the_end",
+            "the_void",
+            "warm_ocean",
+            "warped_forest",
+            "windswept_forest",
+            "windswept_gravelly_hills",
+            "windswept_hills",
+            "windswept_savanna",
+            "wooded_badlands",
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_io::{ext::ReadMinecraftExt, nbt::Tag, McRead};
+    use minecrevy_net::{client::ProtocolState, packet::Recv};
+    use minecrevy_protocol::config::ServerboundKnownPacks;
+    use minecrevy_util::{
+        dimension::{Biome, DimensionType},
+        key::Key,
+    };
+
+    use super::{ConfigPlugin, KnownPacks, SyncedRegistries, VANILLA_REGISTRIES};
+    use crate::testing;
+
+    /// The ID of a registry sent to a client, and its entries with their data,
+    /// if any.
+    type SentRegistry = (Key, Vec<(Key, Option<Tag>)>);
+
+    fn key(path: &str) -> Key {
+        Key::minecraft(path).unwrap()
+    }
+
+    /// Configures a client, and returns the registries it's sent, in order.
+    fn sent_registries(app: &mut App) -> Vec<SentRegistry> {
+        let (client, mut connection) = testing::connect(app, ProtocolState::Config);
+        let packs = app.world().resource::<KnownPacks>().0.clone();
+        app.world_mut()
+            .trigger_targets(Recv(ServerboundKnownPacks { packs }), client);
+        app.world_mut().flush();
+
+        let mut registries = Vec::new();
+        loop {
+            let packet = testing::recv(app, &mut connection);
+            match packet.id {
+                // FinishConfiguration
+                0x03 => return registries,
+                // RegistryData
+                0x07 => {
+                    let mut reader = packet.body.as_slice();
+                    let registry = Key::read_default(&mut reader).unwrap();
+                    let len = reader.read_var_i32().unwrap();
+                    let entries = (0..len)
+                        .map(|_| {
+                            let entry = Key::read_default(&mut reader).unwrap();
+                            let data = reader
+                                .read_bool()
+                                .unwrap()
+                                .then(|| Tag::read_default(&mut reader).unwrap());
+                            (entry, data)
+                        })
+                        .collect();
+                    assert!(reader.is_empty(), "trailing bytes");
+                    registries.push((registry, entries));
+                }
+                // The feature flags and known packs sent on entering configuration.
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the data sent for the given entry of the given registry.
+    fn sent_data<'a>(sent: &'a [SentRegistry], registry: &str, entry: &str) -> Option<&'a Tag> {
+        let (_, entries) = sent.iter().find(|(id, _)| *id == key(registry)).unwrap();
+        let (_, data) = entries.iter().find(|(id, _)| *id == key(entry)).unwrap();
+        data.as_ref()
+    }
+
+    #[test]
+    fn sent_registry_keys_match_vanilla() {
+        let mut app = testing::app();
+        app.add_plugins(ConfigPlugin);
+
+        let sent: Vec<(Key, Vec<Key>)> = sent_registries(&mut app)
+            .into_iter()
+            .map(|(registry, entries)| (registry, entries.into_iter().map(|(e, _)| e).collect()))
+            .collect();
+
+        let vanilla: Vec<(Key, Vec<Key>)> = VANILLA_REGISTRIES
+            .iter()
+            .map(|(registry, entries)| (key(registry), entries.iter().map(|e| key(e)).collect()))
+            .collect();
+        assert_eq!(sent, vanilla);
+
+        let registries: Vec<&str> = sent.iter().map(|(id, _)| id.path()).collect();
+        assert_eq!(
+            registries,
+            [
+                "banner_pattern",
+                "chat_type",
+                "damage_type",
+                "dimension_type",
+                "enchantment",
+                "jukebox_song",
+                "painting_variant",
+                "trim_material",
+                "trim_pattern",
+                "wolf_variant",
+                "worldgen/biome",
+            ]
+        );
+        // The overworld is first, as the login packet refers to it by ID.
+        assert_eq!(
+            sent[3].1,
+            [
+                key("overworld"),
+                key("overworld_caves"),
+                key("the_end"),
+                key("the_nether"),
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_vanilla_entries_are_sent_as_nbt() {
+        let mut app = testing::app();
+        app.add_plugins(ConfigPlugin);
+        let sent = sent_registries(&mut app);
+
+        let overworld = sent_data(&sent, "dimension_type", "overworld").unwrap();
+        assert_eq!(overworld.as_compound().unwrap()["min_y"], Tag::Int(-64));
+        let the_nether = sent_data(&sent, "dimension_type", "the_nether").unwrap();
+        assert_eq!(
+            the_nether.as_compound().unwrap()["fixed_time"],
+            Tag::Long(18000)
+        );
+        let plains = sent_data(&sent, "worldgen/biome", "plains").unwrap();
+        assert!(plains.as_compound().unwrap().contains_key("effects"));
+
+        // Entries without typed definitions are filled in from the known packs.
+        assert_eq!(sent_data(&sent, "dimension_type", "overworld_caves"), None);
+        assert_eq!(sent_data(&sent, "worldgen/biome", "badlands"), None);
+        assert_eq!(sent_data(&sent, "chat_type", "chat"), None);
+    }
+
+    #[test]
+    fn custom_entries_are_sent_after_vanilla_entries() {
+        let mut app = testing::app();
+        app.add_plugins(ConfigPlugin);
+        let flat = Key::new("minecrevy", "flat").unwrap();
+        let flat_type = DimensionType {
+            min_y: 0,
+            height: 256,
+            logical_height: 256,
+            ..DimensionType::OVERWORLD
+        };
+        let id = app
+            .world_mut()
+            .resource_mut::<SyncedRegistries>()
+            .register(key("dimension_type"), flat.clone(), &flat_type)
+            .unwrap();
+        assert_eq!(id, 4);
+
+        let sent = sent_registries(&mut app);
+        let (_, dimension_types) = &sent[3];
+        let (last, data) = dimension_types.last().unwrap();
+        assert_eq!(*last, flat);
+        let data = data.as_ref().unwrap().as_compound().unwrap();
+        assert_eq!(data["min_y"], Tag::Int(0));
+        assert_eq!(data["height"], Tag::Int(256));
+    }
+
+    #[test]
+    fn registering_an_existing_entry_replaces_its_data() {
+        let mut registries = SyncedRegistries::default();
+        let biome = key("worldgen/biome");
+        let plains_id = registries
+            .registry(&biome)
+            .unwrap()
+            .entries
+            .id_of(&key("plains"));
+
+        let id = registries
+            .register(biome.clone(), key("plains"), &Biome::THE_VOID)
+            .unwrap();
+
+        assert_eq!(Some(id), plains_id);
+        let registry = registries.registry(&biome).unwrap();
+        assert_eq!(registry.entries.len(), VANILLA_REGISTRIES[10].1.len());
+        let data = registry.data[&key("plains")].as_compound().unwrap();
+        assert_eq!(data["has_precipitation"], Tag::Byte(0));
+    }
+
+    #[test]
+    fn registering_into_a_new_registry_adds_it() {
+        let mut registries = SyncedRegistries::default();
+        let registry = Key::new("minecrevy", "dimension_type").unwrap();
+
+        let id = registries
+            .register(
+                registry.clone(),
+                key("overworld"),
+                &DimensionType::OVERWORLD,
+            )
+            .unwrap();
+
+        assert_eq!(id, 0);
+        assert_eq!(registries.0.last().unwrap().id, registry);
+        assert_eq!(registries.0.len(), VANILLA_REGISTRIES.len() + 1);
+    }
+}
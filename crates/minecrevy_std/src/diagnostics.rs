@@ -0,0 +1,241 @@
+//! This module contains the [`DiagnosticsPlugin`], which lets operators inspect
+//! the internal state of the server.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use minecrevy_net::{
+    client::{PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{ChatCommand, SystemChat},
+    ServerProtocolPlugin,
+};
+use minecrevy_text::Text;
+
+use crate::{block::Blocks, permission::PermissionLevel};
+
+/// [`Plugin`] that measures the server's [`TicksPerSecond`], and replies to
+/// operators running the `/minecrevy debug` command with a diagnostics report.
+///
+/// The report includes the number of loaded regions if the
+/// [`BlockPlugin`](crate::block::BlockPlugin) is added.
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<TicksPerSecond>();
+
+        app.add_systems(FixedUpdate, Self::measure_tps);
+        app.add_observer(Self::on_debug_command);
+    }
+}
+
+impl DiagnosticsPlugin {
+    /// The command that requests a diagnostics report.
+    pub const COMMAND: &'static str = "minecrevy debug";
+
+    /// [`System`] that counts [`FixedUpdate`] ticks, and updates the
+    /// [`TicksPerSecond`] once at least a second of real time has passed.
+    ///
+    /// Ticks are counted against real time rather than [`Time<Fixed>`], so a
+    /// server that can't keep up with its fixed timestep reports fewer ticks.
+    pub fn measure_tps(
+        time: Res<Time<Real>>,
+        mut tps: ResMut<TicksPerSecond>,
+        mut window: Local<TickWindow>,
+    ) {
+        // Every tick of a frame sees the same real time, so the window is
+        // only closed on the first tick of a new frame, once the ticks of the
+        // previous frames have all been counted.
+        let now = time.elapsed();
+        if now != window.last {
+            let elapsed = window.last - window.start;
+            if elapsed >= TickWindow::LEN {
+                tps.0 = f64::from(window.ticks) / elapsed.as_secs_f64();
+                window.start = window.last;
+                window.ticks = 0;
+            }
+            window.last = now;
+        }
+        window.ticks += 1;
+    }
+
+    /// [`Observer`] [`System`] that sends a diagnostics report to operators
+    /// running the [`DiagnosticsPlugin::COMMAND`].
+    pub fn on_debug_command(
+        trigger: Trigger<Recv<ChatCommand>>,
+        mut writer: PacketWriter,
+        tps: Res<TicksPerSecond>,
+        blocks: Option<Res<Blocks>>,
+        levels: Query<&PermissionLevel>,
+    ) {
+        let packet = &trigger.event().0;
+        let sender = trigger.entity();
+
+        if packet.command.trim() != Self::COMMAND {
+            return;
+        }
+        if !levels.get(sender).is_ok_and(|level| level.is_operator()) {
+            debug!("Ignoring debug command from non-operator {sender}");
+            return;
+        }
+
+        let states = writer.states().map(|(_, state)| state).collect::<Vec<_>>();
+        let regions = blocks.map(|blocks| blocks.loaded_regions());
+        let report = report(states, tps.0, regions);
+
+        writer.client(sender).send(&SystemChat::new(report));
+    }
+}
+
+/// Builds the diagnostics report for clients in the given states.
+fn report(
+    client_states: impl IntoIterator<Item = ProtocolState>,
+    tps: f64,
+    regions: Option<usize>,
+) -> Text {
+    let mut states = [
+        (ProtocolState::Handshake, 0),
+        (ProtocolState::Status, 0),
+        (ProtocolState::Login, 0),
+        (ProtocolState::Config, 0),
+        (ProtocolState::Play, 0),
+    ];
+    for state in client_states {
+        if let Some((_, count)) = states.iter_mut().find(|(s, _)| *s == state) {
+            *count += 1;
+        }
+    }
+    let total = states.iter().map(|(_, count)| count).sum::<usize>();
+
+    let mut report = Text::empty();
+    report
+        .extra
+        .push(Text::string("Minecrevy diagnostics").bold());
+    report
+        .extra
+        .push(Text::string(format!("\nClients: {total}")));
+    for (state, count) in states {
+        report
+            .extra
+            .push(Text::string(format!("\n  {state:?}: {count}")));
+    }
+    if let Some(regions) = regions {
+        report
+            .extra
+            .push(Text::string(format!("\nLoaded regions: {regions}")));
+    }
+    report.extra.push(Text::string(format!("\nTPS: {tps:.1}")));
+
+    report
+}
+
+/// [`Resource`] that stores the number of [`FixedUpdate`] ticks the server
+/// runs per second, averaged over roughly the last second.
+///
+/// This is `0.0` until the first second has been measured.
+#[derive(Resource, Deref)]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct TicksPerSecond(pub f64);
+
+/// The [`FixedUpdate`] ticks counted by [`DiagnosticsPlugin::measure_tps`]
+/// since the [`TicksPerSecond`] were last updated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TickWindow {
+    /// The [`Time<Real>::elapsed`] when the window started.
+    start: Duration,
+    /// The [`Time<Real>::elapsed`] of the frame of the last tick.
+    last: Duration,
+    /// The number of ticks counted since the window started.
+    ticks: u32,
+}
+
+impl TickWindow {
+    /// The minimum length of a window.
+    const LEN: Duration = Duration::from_secs(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{prelude::*, time::TimeUpdateStrategy};
+    use minecrevy_net::client::ProtocolState;
+    use minecrevy_protocol::ServerProtocolPlugin;
+
+    use super::{report, DiagnosticsPlugin, TicksPerSecond};
+
+    /// Returns an app running [`FixedUpdate`] 20 times per second, where each
+    /// update advances real time by the given duration.
+    fn app(frame: Duration) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(ServerProtocolPlugin {
+            handshake: false,
+            login: false,
+            play: true,
+            status: false,
+            config: false,
+        });
+        app.add_plugins(DiagnosticsPlugin);
+        app.insert_resource(Time::<Fixed>::from_hz(20.0));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame));
+        app
+    }
+
+    fn tps(app: &App) -> f64 {
+        app.world().resource::<TicksPerSecond>().0
+    }
+
+    #[test]
+    fn report_contains_client_count() {
+        let states = [
+            ProtocolState::Play,
+            ProtocolState::Play,
+            ProtocolState::Config,
+        ];
+        let report = serde_json::to_string(&report(states, 20.0, Some(2))).unwrap();
+
+        assert!(report.contains("Clients: 3"));
+        assert!(report.contains("Play: 2"));
+        assert!(report.contains("Config: 1"));
+        assert!(report.contains("Loaded regions: 2"));
+        assert!(report.contains("TPS: 20.0"));
+    }
+
+    #[test]
+    fn tps_matches_the_fixed_timestep() {
+        let mut app = app(Duration::from_millis(50));
+
+        // The first update starts the clock, so the first second of ticks
+        // ends on the 21st update, and is measured on the next tick.
+        for _ in 0..21 {
+            app.update();
+        }
+        assert_eq!(tps(&app), 0.0);
+
+        app.update();
+        assert!((tps(&app) - 20.0).abs() < 1e-9, "TPS was {}", tps(&app));
+    }
+
+    #[test]
+    fn tps_drops_when_updates_fall_behind() {
+        // Virtual time advances by at most 250ms per update, so half a second
+        // per update only leaves time for 5 ticks.
+        let mut app = app(Duration::from_millis(500));
+
+        for _ in 0..4 {
+            app.update();
+        }
+        assert!((tps(&app) - 10.0).abs() < 1e-9, "TPS was {}", tps(&app));
+    }
+}
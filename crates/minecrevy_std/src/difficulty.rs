@@ -0,0 +1,276 @@
+//! This module contains the [`DifficultyPlugin`], which keeps the difficulty
+//! shown to players in sync with the [`WorldDifficulty`].
+
+use bevy::prelude::*;
+use minecrevy_net::{
+    client::{PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{DifficultyUpdate, LockDifficulty, SetDifficulty},
+    ServerProtocolPlugin,
+};
+use minecrevy_util::difficulty::Difficulty;
+
+use crate::{
+    permission::PermissionLevel,
+    play::{EnterPlay, Hardcore},
+};
+
+/// [`Plugin`] that sends the [`WorldDifficulty`] to players when they join and
+/// whenever it changes, and lets operators change it from their options.
+///
+/// Configurable [`Resource`]s:
+/// - [`WorldDifficulty`]: The difficulty of the world, and whether it's locked.
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<WorldDifficulty>();
+        app.init_resource::<Hardcore>();
+
+        app.add_systems(PostUpdate, Self::broadcast_difficulty);
+        app.add_observer(Self::on_enter_play);
+        app.add_observer(Self::on_set_difficulty);
+        app.add_observer(Self::on_lock_difficulty);
+    }
+}
+
+impl DifficultyPlugin {
+    /// [`System`] that broadcasts the [`WorldDifficulty`] to clients in the
+    /// play state when it changes.
+    pub fn broadcast_difficulty(
+        difficulty: Res<WorldDifficulty>,
+        hardcore: Res<Hardcore>,
+        mut writer: PacketWriter,
+    ) {
+        if !difficulty.is_changed() && !hardcore.is_changed() {
+            return;
+        }
+
+        writer.broadcast(ProtocolState::Play, &difficulty.packet(&hardcore));
+    }
+
+    /// [`Observer`] [`System`] that sends the [`WorldDifficulty`] to a player
+    /// entering the play state.
+    pub fn on_enter_play(
+        trigger: Trigger<EnterPlay>,
+        difficulty: Res<WorldDifficulty>,
+        hardcore: Res<Hardcore>,
+        mut writer: PacketWriter,
+    ) {
+        let Ok(writer) = writer.get_client(trigger.entity()) else {
+            return;
+        };
+
+        writer.send(&difficulty.packet(&hardcore));
+    }
+
+    /// [`Observer`] [`System`] that changes the [`WorldDifficulty`] when an
+    /// operator asks to, unless it's locked.
+    pub fn on_set_difficulty(
+        trigger: Trigger<Recv<SetDifficulty>>,
+        mut difficulty: ResMut<WorldDifficulty>,
+        hardcore: Res<Hardcore>,
+        permissions: Query<&PermissionLevel>,
+    ) {
+        let packet = &trigger.event().0;
+
+        if difficulty.is_locked(&hardcore) {
+            return;
+        }
+        if !permissions
+            .get(trigger.entity())
+            .is_ok_and(|level| level.is_operator())
+        {
+            return;
+        }
+
+        difficulty.difficulty = packet.0;
+    }
+
+    /// [`Observer`] [`System`] that locks the [`WorldDifficulty`] when an
+    /// operator asks to.
+    ///
+    /// Like in vanilla, a locked difficulty can't be unlocked by clients.
+    pub fn on_lock_difficulty(
+        trigger: Trigger<Recv<LockDifficulty>>,
+        mut difficulty: ResMut<WorldDifficulty>,
+        permissions: Query<&PermissionLevel>,
+    ) {
+        let packet = &trigger.event().0;
+
+        if !packet.0 || difficulty.locked {
+            return;
+        }
+        if !permissions
+            .get(trigger.entity())
+            .is_ok_and(|level| level.is_operator())
+        {
+            return;
+        }
+
+        difficulty.locked = true;
+    }
+}
+
+/// [`Resource`] that stores the difficulty of the world, and whether it's
+/// locked.
+#[derive(Resource)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct WorldDifficulty {
+    /// The difficulty of the world.
+    pub difficulty: Difficulty,
+    /// Whether the difficulty can't be changed by clients.
+    pub locked: bool,
+}
+
+impl WorldDifficulty {
+    /// Returns `true` if the difficulty can't be changed by clients, which is
+    /// always the case in [`Hardcore`] mode.
+    pub fn is_locked(&self, hardcore: &Hardcore) -> bool {
+        self.locked || hardcore.0
+    }
+
+    /// Returns the packet that shows the difficulty to clients.
+    ///
+    /// In [`Hardcore`] mode, the difficulty is always shown as locked.
+    pub fn packet(&self, hardcore: &Hardcore) -> DifficultyUpdate {
+        DifficultyUpdate {
+            difficulty: self.difficulty,
+            locked: self.is_locked(hardcore),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_net::{client::ProtocolState, loopback::LoopbackClient, packet::Recv};
+    use minecrevy_protocol::play::{DifficultyUpdate, LockDifficulty, SetDifficulty};
+    use minecrevy_util::difficulty::Difficulty;
+
+    use super::{DifficultyPlugin, WorldDifficulty};
+    use crate::{
+        permission::PermissionLevel,
+        play::{EnterPlay, Hardcore},
+        testing,
+    };
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins(DifficultyPlugin);
+        app
+    }
+
+    fn player(app: &mut App, level: PermissionLevel) -> (Entity, LoopbackClient) {
+        let (player, connection) = testing::connect(app, ProtocolState::Play);
+        app.world_mut().entity_mut(player).insert(level);
+        (player, connection)
+    }
+
+    fn difficulty(app: &App) -> WorldDifficulty {
+        *app.world().resource::<WorldDifficulty>()
+    }
+
+    #[test]
+    fn joining_player_is_sent_the_difficulty() {
+        let mut app = app();
+        app.insert_resource(WorldDifficulty {
+            difficulty: Difficulty::Hard,
+            locked: true,
+        });
+        let (client, mut connection) = player(&mut app, PermissionLevel::NONE);
+
+        app.world_mut().trigger_targets(EnterPlay, client);
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x0B,
+            &DifficultyUpdate {
+                difficulty: Difficulty::Hard,
+                locked: true,
+            },
+        );
+    }
+
+    #[test]
+    fn hardcore_is_shown_as_locked() {
+        let mut app = app();
+        app.insert_resource(Hardcore(true));
+        let (client, mut connection) = player(&mut app, PermissionLevel::NONE);
+
+        app.world_mut().trigger_targets(EnterPlay, client);
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x0B,
+            &DifficultyUpdate {
+                difficulty: Difficulty::Normal,
+                locked: true,
+            },
+        );
+    }
+
+    #[test]
+    fn operator_changes_and_locks_the_difficulty() {
+        let mut app = app();
+        let (operator, mut connection) = player(&mut app, PermissionLevel::GAME_MASTER);
+        let (_, mut other) = player(&mut app, PermissionLevel::NONE);
+
+        app.world_mut()
+            .trigger_targets(Recv(SetDifficulty(Difficulty::Peaceful)), operator);
+        let changed = DifficultyUpdate {
+            difficulty: Difficulty::Peaceful,
+            locked: false,
+        };
+        testing::assert_packet(testing::recv(&mut app, &mut connection), 0x0B, &changed);
+        testing::assert_packet(testing::recv(&mut app, &mut other), 0x0B, &changed);
+
+        app.world_mut()
+            .trigger_targets(Recv(LockDifficulty(true)), operator);
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x0B,
+            &DifficultyUpdate {
+                difficulty: Difficulty::Peaceful,
+                locked: true,
+            },
+        );
+
+        // A locked difficulty can't be changed or unlocked.
+        app.world_mut()
+            .trigger_targets(Recv(SetDifficulty(Difficulty::Hard)), operator);
+        app.world_mut()
+            .trigger_targets(Recv(LockDifficulty(false)), operator);
+        app.update();
+        assert_eq!(
+            difficulty(&app),
+            WorldDifficulty {
+                difficulty: Difficulty::Peaceful,
+                locked: true,
+            }
+        );
+    }
+
+    #[test]
+    fn non_operator_cannot_change_the_difficulty() {
+        let mut app = app();
+        let (player, _connection) = player(&mut app, PermissionLevel::MODERATOR);
+
+        app.world_mut()
+            .trigger_targets(Recv(SetDifficulty(Difficulty::Hard)), player);
+        app.world_mut()
+            .trigger_targets(Recv(LockDifficulty(true)), player);
+        app.update();
+
+        assert_eq!(difficulty(&app), WorldDifficulty::default());
+    }
+}
@@ -0,0 +1,232 @@
+//! This module contains the [`EntityActionPlugin`], which tracks players
+//! sneaking and sprinting.
+
+use bevy::prelude::*;
+use minecrevy_net::{
+    client::{PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{EntityAction, EntityActionKind, EntityMetadata, Metadata, MetadataValue},
+    ServerProtocolPlugin,
+};
+use minecrevy_util::pose::Pose;
+
+use crate::play::EntityId;
+
+/// [`Plugin`] that updates the [`EntityFlags`] and [`EntityPose`] of players
+/// when they perform an [`EntityAction`], and broadcasts their updated metadata
+/// to the other players in the play state.
+pub struct EntityActionPlugin;
+
+impl Plugin for EntityActionPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.add_observer(Self::on_entity_action);
+    }
+}
+
+impl EntityActionPlugin {
+    /// [`Observer`] [`System`] that applies sneaking and sprinting actions to
+    /// the player performing them.
+    pub fn on_entity_action(
+        trigger: Trigger<Recv<EntityAction>>,
+        mut writer: PacketWriter,
+        mut players: Query<(&EntityId, &mut EntityFlags, &mut EntityPose)>,
+    ) {
+        let packet = &trigger.event().0;
+        let player = trigger.entity();
+
+        let Ok((entity_id, mut flags, mut pose)) = players.get_mut(player) else {
+            return;
+        };
+
+        match packet.action {
+            EntityActionKind::StartSneaking => {
+                flags.set(EntityFlags::SNEAKING, true);
+                pose.0 = Pose::Sneaking;
+            }
+            EntityActionKind::StopSneaking => {
+                flags.set(EntityFlags::SNEAKING, false);
+                pose.0 = Pose::Standing;
+            }
+            EntityActionKind::StartSprinting => flags.set(EntityFlags::SPRINTING, true),
+            EntityActionKind::StopSprinting => flags.set(EntityFlags::SPRINTING, false),
+            EntityActionKind::LeaveBed => pose.0 = Pose::Standing,
+            // Not related to the player's pose.
+            _ => return,
+        }
+
+        let mut metadata = Metadata::new();
+        metadata.insert(Metadata::FLAGS, MetadataValue::Byte(flags.0));
+        metadata.insert(Metadata::POSE, MetadataValue::Pose(pose.0));
+
+        let metadata = EntityMetadata {
+            entity_id: entity_id.0,
+            metadata,
+        };
+        writer.broadcast_except(ProtocolState::Play, player, &metadata);
+    }
+}
+
+/// [`Component`] that stores the [`Metadata::FLAGS`] bits of an entity, such
+/// as whether it is sneaking or sprinting.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct EntityFlags(pub u8);
+
+impl EntityFlags {
+    /// The entity is on fire.
+    pub const ON_FIRE: u8 = 0x01;
+    /// The entity is sneaking.
+    pub const SNEAKING: u8 = 0x02;
+    /// The entity is sprinting.
+    pub const SPRINTING: u8 = 0x08;
+    /// The entity is swimming.
+    pub const SWIMMING: u8 = 0x10;
+    /// The entity is invisible.
+    pub const INVISIBLE: u8 = 0x20;
+    /// The entity is glowing.
+    pub const GLOWING: u8 = 0x40;
+    /// The entity is flying with an elytra.
+    pub const ELYTRA_FLYING: u8 = 0x80;
+
+    /// Returns `true` if the given flag is set.
+    pub fn contains(&self, flag: u8) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// Sets or clears the given flag, leaving the other flags untouched.
+    pub fn set(&mut self, flag: u8, value: bool) {
+        if value {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+}
+
+/// [`Component`] that stores the current [`Pose`] of an entity.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct EntityPose(pub Pose);
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::Duration};
+
+    use bevy::prelude::*;
+    use minecrevy_io::McRead;
+    use minecrevy_net::{client::ProtocolState, loopback::LoopbackClient, packet::Recv};
+    use minecrevy_protocol::play::{
+        EntityAction, EntityActionKind, EntityMetadata, Metadata, MetadataValue,
+    };
+    use minecrevy_util::pose::Pose;
+
+    use super::{EntityActionPlugin, EntityFlags, EntityPose};
+    use crate::{play::EntityId, testing};
+
+    fn player(app: &mut App, entity_id: i32) -> (Entity, LoopbackClient) {
+        let (player, connection) = testing::connect(app, ProtocolState::Play);
+        app.world_mut().entity_mut(player).insert((
+            EntityId(entity_id),
+            EntityFlags::default(),
+            EntityPose::default(),
+        ));
+        (player, connection)
+    }
+
+    fn act(app: &mut App, player: Entity, action: EntityActionKind) {
+        let entity_id = app.world().get::<EntityId>(player).unwrap().0;
+        app.world_mut().trigger_targets(
+            Recv(EntityAction {
+                entity_id,
+                action,
+                jump_boost: 0,
+            }),
+            player,
+        );
+    }
+
+    #[test]
+    fn start_sneaking_sets_the_pose_and_broadcasts_metadata() {
+        let mut app = testing::app();
+        app.add_plugins(EntityActionPlugin);
+        let (sneaker, mut sneaker_connection) = player(&mut app, 1);
+        let (_, mut observer) = player(&mut app, 2);
+
+        act(&mut app, sneaker, EntityActionKind::StartSneaking);
+
+        assert_eq!(
+            app.world().get::<EntityFlags>(sneaker),
+            Some(&EntityFlags(EntityFlags::SNEAKING))
+        );
+        assert_eq!(
+            app.world().get::<EntityPose>(sneaker),
+            Some(&EntityPose(Pose::Sneaking))
+        );
+
+        let packet = testing::recv(&mut app, &mut observer);
+        assert_eq!(packet.id, 0x58);
+        let metadata = EntityMetadata::read_default(packet.reader()).unwrap();
+        assert_eq!(metadata.entity_id, 1);
+        assert_eq!(
+            metadata.metadata.0.get(&Metadata::FLAGS),
+            Some(&MetadataValue::Byte(0x02))
+        );
+        assert_eq!(
+            metadata.metadata.0.get(&Metadata::POSE),
+            Some(&MetadataValue::Pose(Pose::Sneaking))
+        );
+
+        // The sneaking player isn't sent its own metadata.
+        sneaker_connection.set_timeout(Duration::from_millis(50));
+        let error = sneaker_connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn stop_sneaking_keeps_sprinting() {
+        let mut app = testing::app();
+        app.add_plugins(EntityActionPlugin);
+        let (player, _connection) = player(&mut app, 1);
+
+        act(&mut app, player, EntityActionKind::StartSprinting);
+        act(&mut app, player, EntityActionKind::StartSneaking);
+        act(&mut app, player, EntityActionKind::StopSneaking);
+
+        assert_eq!(
+            app.world().get::<EntityFlags>(player),
+            Some(&EntityFlags(EntityFlags::SPRINTING))
+        );
+        assert_eq!(
+            app.world().get::<EntityPose>(player),
+            Some(&EntityPose(Pose::Standing))
+        );
+    }
+
+    #[test]
+    fn actions_keep_unrelated_flags() {
+        let mut app = testing::app();
+        app.add_plugins(EntityActionPlugin);
+        let (player, _connection) = player(&mut app, 1);
+        app.world_mut()
+            .get_mut::<EntityFlags>(player)
+            .unwrap()
+            .set(EntityFlags::ON_FIRE | EntityFlags::GLOWING, true);
+
+        act(&mut app, player, EntityActionKind::StartSprinting);
+        act(&mut app, player, EntityActionKind::StopSprinting);
+
+        assert_eq!(
+            app.world().get::<EntityFlags>(player),
+            Some(&EntityFlags(EntityFlags::ON_FIRE | EntityFlags::GLOWING))
+        );
+    }
+}
@@ -1,17 +1,26 @@
 //! This module contains the [`HandshakePlugin`], which handles handshake packets.
 
+use std::{fmt, net::SocketAddr, ops::RangeInclusive};
+
 use bevy::prelude::*;
 use minecrevy_net::{
-    client::{PacketWriter, ProtocolState},
+    client::{ClientAddressIndex, PacketWriter, ProtocolState},
     packet::Recv,
 };
-use minecrevy_protocol::{handshake::Handshake, login::Disconnect, ServerProtocolPlugin};
+use minecrevy_protocol::{
+    handshake::Handshake,
+    login::{Disconnect, LoginStart},
+    version::{ProtocolVersion, VersionedPackets},
+    ServerProtocolPlugin,
+};
 use minecrevy_text::Text;
+use uuid::Uuid;
 
 /// [`Plugin`] that handles the Minecraft protocol handshake.
 ///
 /// Configurable [`Resource`]s:
-/// - [`AllowLogin`]: Whether or not clients are allowed to log in.
+/// - [`AllowLogin`]: Decides whether or not each client is allowed to log in.
+/// - [`SupportedVersions`]: The protocol versions clients can log in with.
 pub struct HandshakePlugin;
 
 impl Plugin for HandshakePlugin {
@@ -24,17 +33,26 @@ impl Plugin for HandshakePlugin {
         );
 
         app.init_resource::<AllowLogin>();
+        app.init_resource::<SupportedVersions>();
 
         app.add_observer(Self::on_handshake);
+        app.add_observer(Self::on_login_start);
     }
 }
 
 impl HandshakePlugin {
-    /// [`Observer`] [`System`] that handles incoming handshake packets.
+    /// [`Observer`] [`System`] that handles incoming handshake packets, and
+    /// disconnects clients logging in with a protocol version outside of the
+    /// [`SupportedVersions`].
+    ///
+    /// Clients on a version whose packet IDs differ from the latest version's,
+    /// according to the [`VersionedPackets`], are given a
+    /// [`PacketIdMap`](minecrevy_net::packet::PacketIdMap).
     pub fn on_handshake(
         trigger: Trigger<Recv<Handshake>>,
         mut writer: PacketWriter,
-        allow_login: Res<AllowLogin>,
+        supported: Res<SupportedVersions>,
+        versioned: Res<VersionedPackets>,
         mut commands: Commands,
     ) {
         let packet = &trigger.event().0;
@@ -47,23 +65,52 @@ impl HandshakePlugin {
             _ => return,
         });
 
-        if writer.state() == ProtocolState::Login {
-            if let Err(reason) = &allow_login.0 {
-                writer.send(&Disconnect {
-                    reason: reason
-                        .clone()
-                        .unwrap_or_else(|| Text::from("Logins are disabled.")),
-                });
-                commands.entity(trigger.entity()).despawn();
-                return;
-            }
-        }
-
         commands.entity(trigger.entity()).insert(ClientInfo {
             protocol_version: packet.protocol_version,
             server_address: packet.server_address.clone(),
             server_port: packet.server_port,
         });
+
+        let version = ProtocolVersion(packet.protocol_version);
+        if writer.state() == ProtocolState::Login && !supported.contains(version) {
+            writer.send(&Disconnect {
+                reason: supported.rejection(version),
+            });
+            commands.entity(trigger.entity()).despawn();
+            return;
+        }
+
+        let id_map = versioned.id_map(version);
+        if !id_map.is_empty() {
+            commands.entity(trigger.entity()).insert(id_map);
+        }
+    }
+
+    /// [`Observer`] [`System`] that disconnects clients that aren't allowed to
+    /// log in, as decided by the [`AllowLogin`] predicate, and triggers
+    /// [`LoginAllowed`] for the others.
+    pub fn on_login_start(
+        trigger: Trigger<Recv<LoginStart>>,
+        mut writer: PacketWriter,
+        allow_login: Res<AllowLogin>,
+        addresses: Res<ClientAddressIndex>,
+        mut commands: Commands,
+    ) {
+        let packet = &trigger.event().0;
+
+        let context = LoginContext {
+            username: packet.username.clone(),
+            uuid: packet.uuid,
+            address: addresses.address(trigger.entity()),
+        };
+
+        match allow_login.check(&context) {
+            Ok(()) => commands.trigger_targets(LoginAllowed(context), trigger.entity()),
+            Err(reason) => {
+                writer.client(trigger.entity()).send(&Disconnect { reason });
+                commands.entity(trigger.entity()).despawn();
+            }
+        }
     }
 }
 
@@ -78,13 +125,312 @@ pub struct ClientInfo {
     pub server_port: u16,
 }
 
-/// [`Resource`] that stores whether or not clients are allowed to log in.
-#[derive(Resource, Deref, DerefMut)]
+/// [`Resource`] that stores the range of protocol versions clients are allowed
+/// to log in with.
+///
+/// Clients outside of the range can still request the server's status.
+#[derive(Resource)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SupportedVersions(pub RangeInclusive<ProtocolVersion>);
+
+impl SupportedVersions {
+    /// Returns `true` if clients can log in with the given protocol version.
+    pub fn contains(&self, version: ProtocolVersion) -> bool {
+        self.0.contains(&version)
+    }
+
+    /// Returns the reason to disconnect a client logging in with the given
+    /// unsupported protocol version.
+    pub fn rejection(&self, version: ProtocolVersion) -> Text {
+        if version < *self.0.start() {
+            Text::from(format!("Outdated client! Please use {}", self.0.start()))
+        } else {
+            Text::from(format!("Outdated server! I'm still on {}", self.0.end()))
+        }
+    }
+}
+
+impl Default for SupportedVersions {
+    fn default() -> Self {
+        Self(ProtocolVersion::LATEST..=ProtocolVersion::LATEST)
+    }
+}
+
+/// Information about a client attempting to log in, passed to the
+/// [`AllowLogin`] predicate.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LoginContext {
+    /// The username the client is logging in with.
+    pub username: String,
+    /// The UUID the client is logging in with.
+    pub uuid: Uuid,
+    /// The address the client is connecting from, if known.
+    pub address: Option<SocketAddr>,
+}
+
+/// [`Event`] triggered on a client that passed the [`AllowLogin`] predicate.
+#[derive(Event)]
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct AllowLogin(pub Result<(), Option<Text>>);
+pub struct LoginAllowed(pub LoginContext);
+
+/// [`Resource`] that decides whether or not a client is allowed to log in.
+///
+/// The predicate is evaluated once per connection, when the client starts
+/// logging in. Returning an error disconnects the client with the given reason.
+#[derive(Resource)]
+pub struct AllowLogin(pub Box<LoginPredicate>);
+
+/// A predicate that decides whether or not a client is allowed to log in.
+pub type LoginPredicate = dyn Fn(&LoginContext) -> Result<(), Text> + Send + Sync;
+
+// Logins are denied rarely enough that the size of `Text` doesn't matter.
+#[expect(clippy::result_large_err)]
+impl AllowLogin {
+    /// Creates a new [`AllowLogin`] from the given predicate.
+    pub fn new(
+        predicate: impl Fn(&LoginContext) -> Result<(), Text> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Box::new(predicate))
+    }
+
+    /// Returns an [`AllowLogin`] that allows all clients to log in.
+    pub fn allow() -> Self {
+        Self::new(|_| Ok(()))
+    }
+
+    /// Returns an [`AllowLogin`] that disconnects all clients with the given
+    /// reason.
+    pub fn deny(reason: impl Into<Text>) -> Self {
+        let reason = reason.into();
+        Self::new(move |_| Err(reason.clone()))
+    }
+
+    /// Returns an [`AllowLogin`] that disconnects all clients with a default
+    /// reason.
+    pub fn disabled() -> Self {
+        Self::deny("Logins are disabled.")
+    }
+
+    /// Evaluates the predicate for the given client.
+    pub fn check(&self, context: &LoginContext) -> Result<(), Text> {
+        (self.0)(context)
+    }
+}
 
 impl Default for AllowLogin {
     fn default() -> Self {
-        Self(Ok(()))
+        Self::allow()
+    }
+}
+
+impl fmt::Debug for AllowLogin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AllowLogin").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_io::{packet::RawPacket, McRead};
+    use minecrevy_net::{
+        client::ProtocolState,
+        loopback::LoopbackClient,
+        packet::{PacketIdMap, Recv},
+    };
+    use minecrevy_protocol::{
+        login::LoginStart,
+        status::{Request, Response},
+        version::{ProtocolVersion, VersionedPacketId, VersionedPackets},
+    };
+    use minecrevy_text::Text;
+    use uuid::Uuid;
+
+    use super::{AllowLogin, HandshakePlugin, LoginAllowed, LoginContext, SupportedVersions};
+    use crate::{status::StatusPlugin, testing};
+
+    #[test]
+    fn unsupported_version_is_disconnected_at_login() {
+        let mut app = testing::app();
+        app.add_plugins(HandshakePlugin);
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Handshake);
+
+        testing::handshake(&mut connection, 760, 2);
+        let disconnect = testing::recv(&mut app, &mut connection);
+        assert_eq!(disconnect.id, 0x00);
+        let reason = String::read_default(disconnect.reader()).unwrap();
+        assert!(
+            reason.contains("Outdated client! Please use 1.21"),
+            "{reason}"
+        );
+
+        app.update();
+        assert!(app.world().get_entity(client).is_err());
+    }
+
+    /// Returns an app where the status packets have pretend IDs in 1.20.5.
+    fn versioned_app() -> App {
+        let mut app = testing::versioned_app(
+            VersionedPackets::new()
+                .incoming::<Request>(
+                    ProtocolState::Status,
+                    VersionedPacketId::new(ProtocolVersion::V1_20_5, 0x04)
+                        .since(ProtocolVersion::V1_21, 0x00),
+                )
+                .outgoing::<Response>(
+                    ProtocolState::Status,
+                    VersionedPacketId::new(ProtocolVersion::V1_20_5, 0x05)
+                        .since(ProtocolVersion::V1_21, 0x00),
+                ),
+        );
+        app.add_plugins((HandshakePlugin, StatusPlugin::default()));
+        app.insert_resource(SupportedVersions(
+            ProtocolVersion::V1_20_5..=ProtocolVersion::V1_21,
+        ));
+        app
+    }
+
+    #[test]
+    fn packet_ids_follow_the_client_version() {
+        let mut app = versioned_app();
+        let (old, mut connection) = testing::connect(&mut app, ProtocolState::Handshake);
+        testing::handshake(&mut connection, ProtocolVersion::V1_20_5.0, 1);
+        connection
+            .send_raw(RawPacket {
+                id: 0x04,
+                body: Vec::new(),
+            })
+            .unwrap();
+
+        let response = testing::recv(&mut app, &mut connection);
+        assert_eq!(response.id, 0x05);
+        let response = Response::read_default(response.reader()).unwrap();
+        assert_eq!(response.version.protocol, ProtocolVersion::V1_20_5.0);
+        assert!(app.world().get::<PacketIdMap>(old).is_some());
+    }
+
+    #[test]
+    fn latest_version_uses_the_registered_ids() {
+        let mut app = versioned_app();
+        let (latest, mut connection) = testing::connect(&mut app, ProtocolState::Handshake);
+        testing::handshake(&mut connection, ProtocolVersion::LATEST.0, 1);
+        connection
+            .send_raw(RawPacket {
+                id: 0x00,
+                body: Vec::new(),
+            })
+            .unwrap();
+
+        let response = testing::recv(&mut app, &mut connection);
+        assert_eq!(response.id, 0x00);
+        assert!(app.world().get::<PacketIdMap>(latest).is_none());
+    }
+
+    fn context(username: &str) -> LoginContext {
+        LoginContext {
+            username: username.into(),
+            uuid: Uuid::nil(),
+            address: None,
+        }
+    }
+
+    /// Denies only the player named "Herobrine".
+    #[expect(clippy::result_large_err)]
+    fn deny_herobrine() -> AllowLogin {
+        AllowLogin::new(|context| match context.username.as_str() {
+            "Herobrine" => Err(Text::from("Herobrine is banned.")),
+            _ => Ok(()),
+        })
+    }
+
+    #[test]
+    fn allow_login_check() {
+        assert_eq!(AllowLogin::allow().check(&context("Notch")), Ok(()));
+        assert_eq!(
+            AllowLogin::deny("Maintenance").check(&context("Notch")),
+            Err(Text::from("Maintenance"))
+        );
+        assert_eq!(deny_herobrine().check(&context("Notch")), Ok(()));
+        assert_eq!(
+            deny_herobrine().check(&context("Herobrine")),
+            Err(Text::from("Herobrine is banned."))
+        );
+    }
+
+    /// The usernames that were allowed to log in.
+    #[derive(Resource, Default)]
+    struct Allowed(Vec<String>);
+
+    fn login_app(allow_login: AllowLogin) -> App {
+        let mut app = testing::app();
+        app.add_plugins(HandshakePlugin);
+        app.insert_resource(allow_login);
+        app.init_resource::<Allowed>();
+        app.add_observer(
+            |trigger: Trigger<LoginAllowed>, mut allowed: ResMut<Allowed>| {
+                allowed.0.push(trigger.event().0.username.clone());
+            },
+        );
+        app
+    }
+
+    fn start_login(app: &mut App, client: Entity, username: &str) {
+        app.world_mut().trigger_targets(
+            Recv(LoginStart {
+                username: username.into(),
+                uuid: Uuid::nil(),
+            }),
+            client,
+        );
+        app.update();
+    }
+
+    /// Receives the Login Disconnect sent to the client, and returns its
+    /// reason as JSON.
+    fn recv_disconnect(app: &mut App, connection: &mut LoopbackClient) -> String {
+        let disconnect = testing::recv(app, connection);
+        assert_eq!(disconnect.id, 0x00);
+        String::read_default(disconnect.reader()).unwrap()
+    }
+
+    #[test]
+    fn allowed_login_continues() {
+        let mut app = login_app(AllowLogin::allow());
+        let (client, _connection) = testing::connect(&mut app, ProtocolState::Login);
+
+        start_login(&mut app, client, "Notch");
+
+        assert_eq!(app.world().resource::<Allowed>().0, ["Notch"]);
+        assert!(app.world().get_entity(client).is_ok());
+    }
+
+    #[test]
+    fn denied_login_is_disconnected_with_the_reason() {
+        let mut app = login_app(AllowLogin::deny("Maintenance"));
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Login);
+
+        start_login(&mut app, client, "Notch");
+
+        let reason = recv_disconnect(&mut app, &mut connection);
+        assert!(reason.contains("Maintenance"), "{reason}");
+        assert!(app.world().resource::<Allowed>().0.is_empty());
+        assert!(app.world().get_entity(client).is_err());
+    }
+
+    #[test]
+    fn predicate_denies_one_username() {
+        let mut app = login_app(deny_herobrine());
+        let (notch, _notch_connection) = testing::connect(&mut app, ProtocolState::Login);
+        let (herobrine, mut herobrine_connection) =
+            testing::connect(&mut app, ProtocolState::Login);
+
+        start_login(&mut app, notch, "Notch");
+        start_login(&mut app, herobrine, "Herobrine");
+
+        let reason = recv_disconnect(&mut app, &mut herobrine_connection);
+        assert!(reason.contains("Herobrine is banned."), "{reason}");
+        assert_eq!(app.world().resource::<Allowed>().0, ["Notch"]);
+        assert!(app.world().get_entity(notch).is_ok());
+        assert!(app.world().get_entity(herobrine).is_err());
     }
 }
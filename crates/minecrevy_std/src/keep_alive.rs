@@ -0,0 +1,205 @@
+//! This module contains the [`KeepAlivePlugin`], which keeps connections in the play state alive.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use minecrevy_net::{
+    client::{Client, PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{play::KeepAlive, ServerProtocolPlugin};
+
+/// [`Plugin`] that periodically sends keep-alive packets to clients in the
+/// [`ProtocolState::Play`] state, disconnecting clients that fail to respond
+/// in time or respond with the wrong ID.
+///
+/// Configurable [`Resource`]s:
+/// - [`KeepAliveInterval`]: How often keep-alive packets are sent.
+pub struct KeepAlivePlugin;
+
+impl Plugin for KeepAlivePlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<KeepAliveInterval>();
+
+        app.add_systems(Update, Self::send_keep_alives);
+        app.add_observer(Self::on_keep_alive);
+    }
+}
+
+impl KeepAlivePlugin {
+    /// [`System`] that sends keep-alive packets to clients in the play state,
+    /// and disconnects clients that haven't responded to the previous one.
+    pub fn send_keep_alives(
+        time: Res<Time<Real>>,
+        interval: Res<KeepAliveInterval>,
+        mut writer: PacketWriter,
+        mut clients: Query<(Entity, Option<&mut ClientKeepAlive>), With<Client>>,
+        mut commands: Commands,
+    ) {
+        let now = time.elapsed();
+
+        for (entity, keep_alive) in &mut clients {
+            let Ok(writer) = writer.get_client(entity) else {
+                continue;
+            };
+            if writer.state() != ProtocolState::Play {
+                continue;
+            }
+
+            let id = now.as_millis() as i64;
+
+            match keep_alive {
+                Some(mut keep_alive) => {
+                    if now.saturating_sub(keep_alive.last_sent) < interval.0 {
+                        continue;
+                    }
+                    if keep_alive.pending.is_some() {
+                        warn!("Client {entity} timed out responding to keep-alive");
+                        commands.entity(entity).despawn();
+                        continue;
+                    }
+
+                    keep_alive.last_sent = now;
+                    keep_alive.pending = Some(id);
+                }
+                None => {
+                    commands.entity(entity).insert(ClientKeepAlive {
+                        last_sent: now,
+                        pending: Some(id),
+                    });
+                }
+            }
+
+            writer.send(&KeepAlive(id));
+        }
+    }
+
+    /// [`Observer`] [`System`] that handles incoming keep-alive responses.
+    ///
+    /// Clients that respond with an ID other than the one that was sent are
+    /// disconnected, as this indicates a desynced or malicious client.
+    pub fn on_keep_alive(
+        trigger: Trigger<Recv<KeepAlive>>,
+        mut clients: Query<&mut ClientKeepAlive>,
+        mut commands: Commands,
+    ) {
+        let packet = &trigger.event().0;
+
+        let Ok(mut keep_alive) = clients.get_mut(trigger.entity()) else {
+            // We never sent a keep-alive to this client.
+            warn!("Client {} sent an unexpected keep-alive", trigger.entity());
+            commands.entity(trigger.entity()).despawn();
+            return;
+        };
+
+        if keep_alive.pending != Some(packet.0) {
+            warn!(
+                "Client {} sent a mismatched keep-alive (expected: {:?}, actual: {})",
+                trigger.entity(),
+                keep_alive.pending,
+                packet.0,
+            );
+            commands.entity(trigger.entity()).despawn();
+            return;
+        }
+
+        keep_alive.pending = None;
+    }
+}
+
+/// [`Resource`] that stores how often keep-alive packets are sent to clients.
+///
+/// Clients that don't respond before the next keep-alive is due are disconnected.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct KeepAliveInterval(pub Duration);
+
+impl Default for KeepAliveInterval {
+    fn default() -> Self {
+        Self(Duration::from_secs(15))
+    }
+}
+
+/// [`Component`] that tracks the keep-alive state of a client.
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ClientKeepAlive {
+    /// When the last keep-alive was sent, relative to the app's startup.
+    pub last_sent: Duration,
+    /// The ID of the keep-alive the client has yet to respond to, if any.
+    pub pending: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+    use minecrevy_io::McRead;
+    use minecrevy_net::{client::ProtocolState, packet::Recv};
+    use minecrevy_protocol::play::KeepAlive;
+
+    use super::{ClientKeepAlive, KeepAliveInterval, KeepAlivePlugin};
+    use crate::testing;
+
+    fn app(interval: Duration) -> App {
+        let mut app = testing::app();
+        app.add_plugins(KeepAlivePlugin);
+        app.insert_resource(KeepAliveInterval(interval));
+        app
+    }
+
+    #[test]
+    fn matching_id_is_accepted() {
+        let mut app = app(Duration::from_secs(60));
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        let packet = testing::recv(&mut app, &mut connection);
+        assert_eq!(packet.id, 0x26);
+        let sent = KeepAlive::read_default(packet.reader()).unwrap();
+        let pending = app.world().get::<ClientKeepAlive>(client).unwrap().pending;
+        assert_eq!(pending, Some(sent.0));
+
+        app.world_mut().trigger_targets(Recv(sent), client);
+        app.update();
+
+        let keep_alive = app.world().get::<ClientKeepAlive>(client).unwrap();
+        assert_eq!(keep_alive.pending, None);
+    }
+
+    #[test]
+    fn mismatched_id_disconnects() {
+        let mut app = app(Duration::from_secs(60));
+        let (client, _connection) = testing::connect(&mut app, ProtocolState::Play);
+        app.world_mut().entity_mut(client).insert(ClientKeepAlive {
+            last_sent: Duration::ZERO,
+            pending: Some(42),
+        });
+
+        app.world_mut().trigger_targets(Recv(KeepAlive(41)), client);
+        app.update();
+
+        assert!(app.world().get_entity(client).is_err());
+    }
+
+    #[test]
+    fn missing_reply_disconnects_at_the_next_interval() {
+        let interval = Duration::from_millis(50);
+        let mut app = app(interval);
+        let (client, _connection) = testing::connect(&mut app, ProtocolState::Play);
+        app.update();
+        assert!(app.world().get::<ClientKeepAlive>(client).is_some());
+
+        std::thread::sleep(interval);
+        app.update();
+
+        assert!(app.world().get_entity(client).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! This module contains the [`LatencyPlugin`], which measures the round-trip latency of clients.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use minecrevy_net::{
+    client::{Client, PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{Ping, Pong},
+    ServerProtocolPlugin,
+};
+
+use crate::{
+    profile::GameProfile,
+    tab_list::{TabList, TabListPlugin},
+};
+
+/// [`Plugin`] that periodically pings clients in the [`ProtocolState::Play`]
+/// state, and records their round-trip [`Latency`].
+///
+/// Measured latencies are set on the players' [`TabList`] entries, so that
+/// they're displayed in the tab list of all clients in the play state.
+///
+/// Configurable [`Resource`]s:
+/// - [`LatencyInterval`]: How often clients are pinged.
+pub struct LatencyPlugin;
+
+impl Plugin for LatencyPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+        assert!(
+            app.is_plugin_added::<TabListPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<TabListPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<LatencyInterval>();
+
+        app.add_systems(Update, (Self::send_pings, Self::broadcast_latencies));
+        app.add_observer(Self::on_pong);
+    }
+}
+
+impl LatencyPlugin {
+    /// [`System`] that sends ping packets to clients in the play state.
+    pub fn send_pings(
+        time: Res<Time<Real>>,
+        interval: Res<LatencyInterval>,
+        mut writer: PacketWriter,
+        clients: Query<(Entity, Option<&LastPing>), With<Client>>,
+        mut commands: Commands,
+    ) {
+        let now = Instant::now();
+
+        for (entity, last_ping) in &clients {
+            let Ok(writer) = writer.get_client(entity) else {
+                continue;
+            };
+            if writer.state() != ProtocolState::Play {
+                continue;
+            }
+            if last_ping.is_some_and(|last| now.duration_since(last.0) < interval.0) {
+                continue;
+            }
+
+            // Derive the ID from the current time, so that each ping is distinct.
+            let id = time.elapsed().as_millis() as i32;
+
+            commands
+                .entity(entity)
+                .insert((PendingPing { id, sent_at: now }, LastPing(now)));
+            writer.send(&Ping(id));
+        }
+    }
+
+    /// [`System`] that sets newly measured player latencies on their
+    /// [`TabList`] entries, which broadcasts them to clients in the play state.
+    ///
+    /// Players that aren't in the [`TabList`] are skipped.
+    pub fn broadcast_latencies(
+        mut tab_list: ResMut<TabList>,
+        players: Query<(&GameProfile, &Latency), Changed<Latency>>,
+    ) {
+        for (profile, latency) in &players {
+            let latency = i32::try_from(latency.as_millis()).unwrap_or(i32::MAX);
+            tab_list.set_latency(profile.uuid, latency);
+        }
+    }
+
+    /// [`Observer`] [`System`] that records the [`Latency`] of clients
+    /// responding to a ping.
+    pub fn on_pong(
+        trigger: Trigger<Recv<Pong>>,
+        pending: Query<&PendingPing>,
+        mut commands: Commands,
+    ) {
+        let packet = &trigger.event().0;
+
+        let Ok(pending) = pending.get(trigger.entity()) else {
+            return;
+        };
+        if pending.id != packet.0 {
+            // Stale or unsolicited response, ignore it.
+            return;
+        }
+
+        commands
+            .entity(trigger.entity())
+            .insert(Latency(pending.sent_at.elapsed()))
+            .remove::<PendingPing>();
+    }
+}
+
+/// [`Resource`] that stores how often clients are pinged to measure their latency.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct LatencyInterval(pub Duration);
+
+impl Default for LatencyInterval {
+    fn default() -> Self {
+        Self(Duration::from_secs(5))
+    }
+}
+
+/// [`Component`] that stores the most recently measured round-trip latency of a client.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct Latency(pub Duration);
+
+/// [`Component`] that tracks a ping sent to a client that hasn't been
+/// answered yet.
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct PendingPing {
+    /// The ID of the ping.
+    pub id: i32,
+    /// When the ping was sent.
+    pub sent_at: Instant,
+}
+
+/// [`Component`] that stores when a client was last sent a ping.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct LastPing(pub Instant);
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        time::{Duration, Instant},
+    };
+
+    use bevy::prelude::*;
+    use minecrevy_net::{client::ProtocolState, loopback::LoopbackClient, packet::Recv};
+    use minecrevy_protocol::play::Pong;
+    use minecrevy_util::game_mode::GameMode;
+
+    use super::{LastPing, Latency, LatencyInterval, LatencyPlugin, PendingPing};
+    use crate::{
+        profile::GameProfile,
+        tab_list::{TabList, TabListPlayer, TabListPlugin},
+        testing,
+    };
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins((TabListPlugin, LatencyPlugin));
+        app
+    }
+
+    /// Connects a client in the play state that was sent a ping with the
+    /// given ID at the given time.
+    fn pinged(app: &mut App, id: i32, sent_at: Instant) -> (Entity, LoopbackClient) {
+        let (client, connection) = testing::connect(app, ProtocolState::Play);
+        app.world_mut()
+            .entity_mut(client)
+            .insert(PendingPing { id, sent_at });
+        (client, connection)
+    }
+
+    #[test]
+    fn matching_pong_records_latency() {
+        let mut app = app();
+        let sent_at = Instant::now() - Duration::from_millis(30);
+        let (client, _connection) = pinged(&mut app, 7, sent_at);
+
+        app.world_mut().trigger_targets(Recv(Pong(7)), client);
+        app.world_mut().flush();
+
+        let latency = app.world().get::<Latency>(client).unwrap();
+        assert!(latency.0 >= Duration::from_millis(30), "{latency:?}");
+        assert!(app.world().get::<PendingPing>(client).is_none());
+    }
+
+    #[test]
+    fn stale_pong_is_ignored() {
+        let mut app = app();
+        let (client, _connection) = pinged(&mut app, 7, Instant::now());
+
+        app.world_mut().trigger_targets(Recv(Pong(6)), client);
+        app.world_mut().flush();
+
+        assert!(app.world().get::<Latency>(client).is_none());
+        let pending = app.world().get::<PendingPing>(client).unwrap();
+        assert_eq!(pending.id, 7);
+    }
+
+    #[test]
+    fn changed_latency_is_broadcast_to_the_tab_list() {
+        let mut app = app();
+        let (player, _connection) = testing::connect(&mut app, ProtocolState::Play);
+        let (other, mut observer) = testing::connect(&mut app, ProtocolState::Play);
+        let profile = GameProfile::offline("Notch");
+        let uuid = profile.uuid;
+        // Don't ping the clients during the test.
+        for client in [player, other] {
+            app.world_mut()
+                .entity_mut(client)
+                .insert(LastPing(Instant::now()));
+        }
+        app.insert_resource(LatencyInterval(Duration::from_secs(60)));
+        app.world_mut()
+            .resource_mut::<TabList>()
+            .add(TabListPlayer::new(profile.clone(), GameMode::Survival));
+        // The player being added to the tab list.
+        testing::recv(&mut app, &mut observer);
+
+        app.world_mut()
+            .entity_mut(player)
+            .insert((profile, Latency(Duration::from_millis(150))));
+
+        let packet = testing::recv(&mut app, &mut observer);
+        assert_eq!(packet.id, 0x3E);
+        let mut expected = vec![0x10, 0x01];
+        expected.extend_from_slice(uuid.as_bytes());
+        // 150ms as a varint.
+        expected.extend_from_slice(&[0x96, 0x01]);
+        assert_eq!(packet.body, expected);
+
+        let tab_list = app.world().resource::<TabList>();
+        assert_eq!(tab_list.player(uuid).unwrap().latency, 150);
+    }
+
+    #[test]
+    fn latency_of_players_missing_from_the_tab_list_is_not_sent() {
+        let mut app = app();
+        let (player, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+        app.insert_resource(LatencyInterval(Duration::from_secs(60)));
+        app.world_mut().entity_mut(player).insert((
+            LastPing(Instant::now()),
+            GameProfile::offline("Notch"),
+            Latency(Duration::from_millis(150)),
+        ));
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        connection.set_timeout(Duration::from_millis(10));
+        let error = connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        let uuid = GameProfile::offline("Notch").uuid;
+        assert!(app.world().resource::<TabList>().player(uuid).is_none());
+    }
+}
@@ -4,8 +4,33 @@
 
 use bevy::prelude::*;
 
+#[cfg(feature = "online-mode")]
+pub mod auth;
+pub mod block;
+pub mod boss_bar;
+pub mod brand;
+pub mod chat;
+pub mod config;
+pub mod diagnostics;
+pub mod difficulty;
+pub mod entity_action;
 pub mod handshake;
+pub mod keep_alive;
+pub mod latency;
+pub mod login;
+pub mod operator_block;
+pub mod permission;
+pub mod play;
+pub mod plugin_message;
+pub mod profile;
+pub mod resource_pack;
+pub mod scoreboard;
 pub mod status;
+pub mod tab_list;
+#[cfg(test)]
+mod testing;
+pub mod title;
+pub mod world_border;
 
 /// [`Plugin`] that provides core functionality for Minecrevy servers.
 ///
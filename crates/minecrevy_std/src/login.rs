@@ -0,0 +1,270 @@
+//! This module contains the [`LoginPlugin`], which handles login packets.
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+#[cfg(feature = "online-mode")]
+use minecrevy_net::server::Server;
+use minecrevy_net::{
+    client::{ClientPacketWriter, PacketWriter, ProtocolState},
+    packet::Recv,
+};
+#[cfg(feature = "online-mode")]
+use minecrevy_protocol::login::{EncryptionRequest, EncryptionResponse};
+use minecrevy_protocol::{
+    login::{Disconnect, LoginAcknowledged, LoginSuccess},
+    ServerProtocolPlugin,
+};
+use minecrevy_text::Text;
+
+#[cfg(feature = "online-mode")]
+use tokio::sync::oneshot;
+
+#[cfg(feature = "online-mode")]
+use crate::auth::{
+    server_hash, AuthError, Authenticate, PendingAuth, PendingEncryption, ServerKey, SessionServer,
+};
+use crate::{
+    handshake::{HandshakePlugin, LoginAllowed},
+    profile::{is_valid_username, GameProfile},
+};
+
+/// [`Plugin`] that logs in clients that passed the
+/// [`AllowLogin`](crate::handshake::AllowLogin) predicate, and moves them to
+/// the configuration state once they acknowledge the login.
+///
+/// Configurable [`Resource`]s:
+/// - [`OnlineMode`]: Whether or not players are authenticated with Mojang.
+/// - `SessionServer`: The session server players are authenticated with,
+///   if the `online-mode` feature is enabled.
+/// - `ServerKey`: The key pair clients encrypt their shared secret with, if
+///   the `online-mode` feature is enabled.
+pub struct LoginPlugin;
+
+impl Plugin for LoginPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+        assert!(
+            app.is_plugin_added::<HandshakePlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<HandshakePlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<OnlineMode>();
+
+        app.add_observer(Self::on_login_allowed);
+        app.add_observer(Self::on_login_acknowledged);
+
+        #[cfg(feature = "online-mode")]
+        {
+            app.init_resource::<SessionServer>();
+            app.init_resource::<ServerKey>();
+
+            app.add_systems(Update, Self::poll_authentications);
+            app.add_observer(Self::on_encryption_response);
+            app.add_observer(Self::on_authenticate);
+        }
+    }
+}
+
+impl LoginPlugin {
+    /// [`Observer`] [`System`] that validates the username of a client that is
+    /// allowed to log in, and completes the login with its [`GameProfile`].
+    ///
+    /// In [`OnlineMode`], the client is sent an [`EncryptionRequest`] instead,
+    /// and is logged in once it has been authenticated. Online mode requires
+    /// the `online-mode` feature, without which clients are disconnected.
+    pub fn on_login_allowed(
+        trigger: Trigger<LoginAllowed>,
+        mut writer: PacketWriter,
+        online_mode: Res<OnlineMode>,
+        #[cfg(feature = "online-mode")] key: Res<ServerKey>,
+        mut commands: Commands,
+    ) {
+        let context = &trigger.event().0;
+        let writer = writer.client(trigger.entity());
+
+        if !is_valid_username(&context.username) {
+            writer.send(&Disconnect {
+                reason: Text::from("Invalid username."),
+            });
+            commands.entity(trigger.entity()).despawn();
+            return;
+        }
+
+        #[cfg(feature = "online-mode")]
+        if online_mode.0 {
+            let verify_token = rand::random::<[u8; 4]>();
+            writer.send(&EncryptionRequest {
+                server_id: String::new(),
+                public_key: key.public_key().to_vec(),
+                verify_token: verify_token.to_vec(),
+                should_authenticate: true,
+            });
+            commands.entity(trigger.entity()).insert(PendingEncryption {
+                username: context.username.clone(),
+                verify_token,
+            });
+            return;
+        }
+
+        #[cfg(not(feature = "online-mode"))]
+        if online_mode.0 {
+            writer.send(&Disconnect {
+                reason: Text::from("Online mode is not supported."),
+            });
+            commands.entity(trigger.entity()).despawn();
+            return;
+        }
+
+        Self::finish_login(
+            &writer,
+            commands.entity(trigger.entity()),
+            GameProfile::offline(context.username.clone()),
+        );
+    }
+
+    /// [`Observer`] [`System`] that enables encryption for a client that
+    /// responded to its [`EncryptionRequest`], and starts authenticating it.
+    ///
+    /// Clients whose response doesn't match the request are disconnected.
+    /// Responses from clients that weren't sent a request are ignored.
+    #[cfg(feature = "online-mode")]
+    pub fn on_encryption_response(
+        trigger: Trigger<Recv<EncryptionResponse>>,
+        mut writer: PacketWriter,
+        key: Res<ServerKey>,
+        pending: Query<&PendingEncryption>,
+        mut commands: Commands,
+    ) {
+        let client = trigger.entity();
+        let Ok(pending) = pending.get(client) else {
+            debug!("Ignoring encryption response from {client}, which wasn't sent a request");
+            return;
+        };
+        let Ok(writer) = writer.get_client(client) else {
+            return;
+        };
+        commands.entity(client).remove::<PendingEncryption>();
+
+        match key.decrypt_secret(&trigger.event().0, &pending.verify_token) {
+            Ok(shared_secret) => {
+                writer.enable_encryption(shared_secret);
+                commands.trigger_targets(
+                    Authenticate {
+                        username: pending.username.clone(),
+                        server_hash: server_hash("", &shared_secret, key.public_key()),
+                    },
+                    client,
+                );
+            }
+            Err(error) => {
+                warn!("Failed to establish encryption with client: {error}");
+                writer.send(&Disconnect {
+                    reason: Text::from("Failed to verify username!"),
+                });
+                commands.entity(client).despawn();
+            }
+        }
+    }
+
+    /// [`Observer`] [`System`] that queries the [`SessionServer`] to verify
+    /// that a client is who they claim to be.
+    #[cfg(feature = "online-mode")]
+    pub fn on_authenticate(
+        trigger: Trigger<Authenticate>,
+        session_server: Res<SessionServer>,
+        server: Res<Server>,
+        mut commands: Commands,
+    ) {
+        let event = trigger.event();
+        let (tx, rx) = oneshot::channel();
+
+        let has_joined = session_server.has_joined(&event.username, &event.server_hash);
+        server.spawn(async move {
+            let _ = tx.send(has_joined.await);
+        });
+
+        commands.entity(trigger.entity()).insert(PendingAuth(rx));
+    }
+
+    /// [`System`] that completes the login of clients whose [`PendingAuth`]
+    /// has finished, disconnecting those that failed to authenticate.
+    #[cfg(feature = "online-mode")]
+    pub fn poll_authentications(
+        mut writer: PacketWriter,
+        mut pending: Query<(Entity, &mut PendingAuth)>,
+        mut commands: Commands,
+    ) {
+        for (entity, mut auth) in &mut pending {
+            let result = match auth.0.try_recv() {
+                Ok(result) => result,
+                Err(oneshot::error::TryRecvError::Empty) => continue,
+                Err(oneshot::error::TryRecvError::Closed) => Err(AuthError::NotAuthenticated),
+            };
+
+            let Ok(writer) = writer.get_client(entity) else {
+                continue;
+            };
+            let mut entity = commands.entity(entity);
+            entity.remove::<PendingAuth>();
+
+            match result {
+                Ok(profile) => Self::finish_login(&writer, entity, profile),
+                Err(error) => {
+                    warn!("Failed to authenticate client: {error}");
+                    writer.send(&Disconnect {
+                        reason: Text::from("Failed to verify username!"),
+                    });
+                    entity.despawn();
+                }
+            }
+        }
+    }
+
+    /// [`Observer`] [`System`] that moves a client to the configuration state
+    /// once it acknowledges a successful login.
+    ///
+    /// Acknowledgements from clients that haven't been sent [`LoginSuccess`]
+    /// are ignored.
+    pub fn on_login_acknowledged(
+        trigger: Trigger<Recv<LoginAcknowledged>>,
+        mut writer: PacketWriter,
+        profiles: Query<(), With<GameProfile>>,
+    ) {
+        let client = trigger.entity();
+        if !profiles.contains(client) {
+            debug!("Ignoring login acknowledgement from {client}, which hasn't logged in");
+            return;
+        }
+
+        let Ok(mut writer) = writer.get_client(client) else {
+            return;
+        };
+        writer.set_state(ProtocolState::Config);
+    }
+
+    /// Completes the login of a client with the given [`GameProfile`].
+    fn finish_login(writer: &ClientPacketWriter, mut entity: EntityCommands, profile: GameProfile) {
+        writer.send(&LoginSuccess {
+            uuid: profile.uuid,
+            username: profile.username.clone(),
+            properties: profile.properties.clone(),
+            strict_error_handling: false,
+        });
+        entity.insert(profile);
+    }
+}
+
+/// [`Resource`] that stores whether or not players are authenticated with
+/// Mojang when logging in.
+///
+/// When offline, players are trusted to be who they claim, and are given a
+/// UUID derived from their username.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct OnlineMode(pub bool);
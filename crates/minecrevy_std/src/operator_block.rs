@@ -0,0 +1,352 @@
+//! This module contains the [`OperatorBlockPlugin`], which lets operators
+//! configure command, jigsaw, and structure blocks.
+
+use bevy::{math::IVec3, prelude::*};
+use minecrevy_io::nbt::{Compound, Tag};
+use minecrevy_net::{
+    client::{PacketWriter, ProtocolState},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{BlockEntityData, ProgramCommandBlock, ProgramJigsawBlock, ProgramStructureBlock},
+    ServerProtocolPlugin,
+};
+use minecrevy_util::game_mode::GameMode;
+
+use crate::{
+    block::{BlockEntity, BlockPlugin, Blocks},
+    permission::PermissionLevel,
+    play::PlayerGameMode,
+};
+
+/// [`Plugin`] that stores the configuration of operator blocks, such as
+/// command blocks, in their [`BlockEntity`], and broadcasts it to clients.
+///
+/// Only players in [`GameMode::Creative`] with at least
+/// [`PermissionLevel::GAME_MASTER`] can configure operator blocks, as in vanilla.
+/// The block entity is created when the block is placed, so the operator
+/// blocks' states must be listed in the
+/// [`BlockEntityTypes`](crate::block::BlockEntityTypes).
+pub struct OperatorBlockPlugin;
+
+impl Plugin for OperatorBlockPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+        assert!(
+            app.is_plugin_added::<BlockPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<BlockPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.add_observer(Self::on_program_command_block);
+        app.add_observer(Self::on_program_jigsaw_block);
+        app.add_observer(Self::on_program_structure_block);
+    }
+}
+
+impl OperatorBlockPlugin {
+    /// [`Observer`] [`System`] that stores the configuration of command blocks.
+    ///
+    /// The mode and conditional flag are part of the block state in vanilla,
+    /// so only the command and the other flags are stored.
+    pub fn on_program_command_block(
+        trigger: Trigger<Recv<ProgramCommandBlock>>,
+        players: Query<(&PlayerGameMode, &PermissionLevel)>,
+        mut writer: PacketWriter,
+        mut blocks: ResMut<Blocks>,
+    ) {
+        let packet = &trigger.event().0;
+        if !can_configure(&players, trigger.entity()) {
+            return;
+        }
+
+        let position = packet.position;
+        configure(
+            &mut writer,
+            &mut blocks,
+            position,
+            BlockEntity::COMMAND_BLOCK,
+            |data| {
+                data.insert("Command".to_owned(), Tag::from(packet.command.as_str()));
+                data.insert(
+                    "TrackOutput".to_owned(),
+                    Tag::from(packet.flags & 0x01 != 0),
+                );
+                data.insert("auto".to_owned(), Tag::from(packet.flags & 0x04 != 0));
+            },
+        );
+    }
+
+    /// [`Observer`] [`System`] that stores the configuration of jigsaw blocks.
+    pub fn on_program_jigsaw_block(
+        trigger: Trigger<Recv<ProgramJigsawBlock>>,
+        players: Query<(&PlayerGameMode, &PermissionLevel)>,
+        mut writer: PacketWriter,
+        mut blocks: ResMut<Blocks>,
+    ) {
+        let packet = &trigger.event().0;
+        if !can_configure(&players, trigger.entity()) {
+            return;
+        }
+
+        let position = packet.position;
+        configure(
+            &mut writer,
+            &mut blocks,
+            position,
+            BlockEntity::JIGSAW,
+            |data| {
+                data.insert("name".to_owned(), Tag::from(packet.name.to_string()));
+                data.insert("target".to_owned(), Tag::from(packet.target.to_string()));
+                data.insert("pool".to_owned(), Tag::from(packet.pool.to_string()));
+                data.insert(
+                    "final_state".to_owned(),
+                    Tag::from(packet.final_state.as_str()),
+                );
+                data.insert("joint".to_owned(), Tag::from(packet.joint_type.as_str()));
+                data.insert(
+                    "selection_priority".to_owned(),
+                    Tag::from(packet.selection_priority),
+                );
+                data.insert(
+                    "placement_priority".to_owned(),
+                    Tag::from(packet.placement_priority),
+                );
+            },
+        );
+    }
+
+    /// [`Observer`] [`System`] that stores the configuration of structure blocks.
+    ///
+    /// The action isn't stored, as it's performed rather than configured.
+    /// Updates with an unknown mode, mirror, or rotation are ignored.
+    pub fn on_program_structure_block(
+        trigger: Trigger<Recv<ProgramStructureBlock>>,
+        players: Query<(&PlayerGameMode, &PermissionLevel)>,
+        mut writer: PacketWriter,
+        mut blocks: ResMut<Blocks>,
+    ) {
+        const MODES: [&str; 4] = ["SAVE", "LOAD", "CORNER", "DATA"];
+        const MIRRORS: [&str; 3] = ["NONE", "LEFT_RIGHT", "FRONT_BACK"];
+        const ROTATIONS: [&str; 4] = [
+            "NONE",
+            "CLOCKWISE_90",
+            "CLOCKWISE_180",
+            "COUNTERCLOCKWISE_90",
+        ];
+
+        let packet = &trigger.event().0;
+        if !can_configure(&players, trigger.entity()) {
+            return;
+        }
+
+        let name = |names: &[&'static str], id: i32| {
+            usize::try_from(id)
+                .ok()
+                .and_then(|id| names.get(id).copied())
+        };
+        let (Some(mode), Some(mirror), Some(rotation)) = (
+            name(&MODES, packet.mode),
+            name(&MIRRORS, packet.mirror),
+            name(&ROTATIONS, packet.rotation),
+        ) else {
+            debug!(
+                "Ignoring invalid structure block update at {}",
+                packet.position
+            );
+            return;
+        };
+
+        let position = packet.position;
+        configure(
+            &mut writer,
+            &mut blocks,
+            position,
+            BlockEntity::STRUCTURE_BLOCK,
+            |data| {
+                data.insert("mode".to_owned(), Tag::from(mode));
+                data.insert("name".to_owned(), Tag::from(packet.name.as_str()));
+                for (axis, (offset, size)) in ["X", "Y", "Z"]
+                    .into_iter()
+                    .zip(packet.offset.into_iter().zip(packet.size))
+                {
+                    data.insert(format!("pos{axis}"), Tag::from(i32::from(offset)));
+                    data.insert(format!("size{axis}"), Tag::from(i32::from(size)));
+                }
+                data.insert("mirror".to_owned(), Tag::from(mirror));
+                data.insert("rotation".to_owned(), Tag::from(rotation));
+                data.insert("metadata".to_owned(), Tag::from(packet.metadata.as_str()));
+                data.insert("integrity".to_owned(), Tag::Float(packet.integrity));
+                data.insert("seed".to_owned(), Tag::Long(packet.seed));
+                data.insert(
+                    "ignoreEntities".to_owned(),
+                    Tag::from(packet.flags & 0x01 != 0),
+                );
+                data.insert("showair".to_owned(), Tag::from(packet.flags & 0x02 != 0));
+                data.insert(
+                    "showboundingbox".to_owned(),
+                    Tag::from(packet.flags & 0x04 != 0),
+                );
+            },
+        );
+    }
+}
+
+/// Returns `true` if the given player is allowed to configure operator blocks.
+fn can_configure(players: &Query<(&PlayerGameMode, &PermissionLevel)>, player: Entity) -> bool {
+    let allowed = players
+        .get(player)
+        .is_ok_and(|(game_mode, level)| game_mode.0 == GameMode::Creative && level.is_operator());
+    if !allowed {
+        debug!("Ignoring operator block update from non-operator {player}");
+    }
+    allowed
+}
+
+/// Updates the data of the [`BlockEntity`] of the given type at the given
+/// position, and broadcasts it to clients.
+///
+/// Nothing happens if there's no such block entity at the position.
+fn configure(
+    writer: &mut PacketWriter,
+    blocks: &mut Blocks,
+    position: IVec3,
+    kind: i32,
+    update: impl FnOnce(&mut Compound),
+) {
+    let Some(block_entity) = blocks
+        .block_entity_mut(position)
+        .filter(|block_entity| block_entity.kind == kind)
+    else {
+        debug!("Ignoring operator block update at {position}, which has no matching block entity");
+        return;
+    };
+
+    update(&mut block_entity.data);
+
+    let update = BlockEntityData {
+        position,
+        kind,
+        data: Some(Tag::Compound(block_entity.data.clone())),
+    };
+    writer.broadcast(ProtocolState::Play, &update);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{math::IVec3, prelude::*};
+    use minecrevy_io::nbt::Tag;
+    use minecrevy_net::packet::Recv;
+    use minecrevy_protocol::{play::ProgramCommandBlock, ServerProtocolPlugin};
+    use minecrevy_util::game_mode::GameMode;
+
+    use super::OperatorBlockPlugin;
+    use crate::{
+        block::{BlockEntity, BlockPlugin, Blocks},
+        permission::PermissionLevel,
+        play::PlayerGameMode,
+    };
+
+    const POSITION: IVec3 = IVec3::new(3, 70, -8);
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(ServerProtocolPlugin {
+            handshake: false,
+            login: false,
+            play: true,
+            status: false,
+            config: false,
+        });
+        app.add_plugins((BlockPlugin, OperatorBlockPlugin));
+
+        let mut blocks = app.world_mut().resource_mut::<Blocks>();
+        blocks.set(POSITION, 1);
+        blocks.set_block_entity(POSITION, BlockEntity::new(BlockEntity::COMMAND_BLOCK));
+        app
+    }
+
+    fn program(app: &mut App, player: Entity) {
+        let packet = ProgramCommandBlock {
+            position: POSITION,
+            command: "say hi".to_owned(),
+            mode: 1,
+            flags: 0x01 | 0x04,
+        };
+        app.world_mut().trigger_targets(Recv(packet), player);
+        app.world_mut().flush();
+    }
+
+    fn command(app: &App) -> Option<&Tag> {
+        let blocks = app.world().resource::<Blocks>();
+        blocks.block_entity(POSITION)?.data.get("Command")
+    }
+
+    #[test]
+    fn operator_programs_command_block() {
+        let mut app = app();
+        let operator = app
+            .world_mut()
+            .spawn((
+                PlayerGameMode(GameMode::Creative),
+                PermissionLevel::GAME_MASTER,
+            ))
+            .id();
+
+        program(&mut app, operator);
+
+        assert_eq!(command(&app), Some(&Tag::from("say hi")));
+        let data = &app
+            .world()
+            .resource::<Blocks>()
+            .block_entity(POSITION)
+            .unwrap()
+            .data;
+        assert_eq!(data.get("TrackOutput"), Some(&Tag::Byte(1)));
+        assert_eq!(data.get("auto"), Some(&Tag::Byte(1)));
+    }
+
+    #[test]
+    fn non_operator_is_rejected() {
+        let mut app = app();
+        let player = app
+            .world_mut()
+            .spawn((PlayerGameMode(GameMode::Creative), PermissionLevel::NONE))
+            .id();
+        let survival_operator = app
+            .world_mut()
+            .spawn((
+                PlayerGameMode(GameMode::Survival),
+                PermissionLevel::GAME_MASTER,
+            ))
+            .id();
+
+        program(&mut app, player);
+        program(&mut app, survival_operator);
+
+        assert_eq!(command(&app), None);
+    }
+
+    #[test]
+    fn breaking_removes_configuration() {
+        let mut app = app();
+        let operator = app
+            .world_mut()
+            .spawn((
+                PlayerGameMode(GameMode::Creative),
+                PermissionLevel::GAME_MASTER,
+            ))
+            .id();
+        program(&mut app, operator);
+
+        let mut blocks = app.world_mut().resource_mut::<Blocks>();
+        blocks.set(POSITION, Blocks::AIR);
+        assert!(blocks.block_entity(POSITION).is_none());
+    }
+}
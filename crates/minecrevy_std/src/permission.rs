@@ -0,0 +1,30 @@
+//! This module contains the [`PermissionLevel`] of players.
+
+use bevy::prelude::*;
+
+/// [`Component`] that stores the operator permission level of a player, from
+/// `0` to `4`.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Default)]
+pub struct PermissionLevel(pub u8);
+
+impl PermissionLevel {
+    /// The permission level of regular players.
+    pub const NONE: Self = Self(0);
+    /// The permission level that bypasses spawn protection.
+    pub const MODERATOR: Self = Self(1);
+    /// The permission level that allows using cheat commands and operator
+    /// blocks, such as command blocks.
+    pub const GAME_MASTER: Self = Self(2);
+    /// The permission level that allows using multiplayer management commands,
+    /// such as banning players.
+    pub const ADMIN: Self = Self(3);
+    /// The permission level that allows using all commands.
+    pub const OWNER: Self = Self(4);
+
+    /// Returns `true` if this permission level is at least
+    /// [`PermissionLevel::GAME_MASTER`], the level of server operators.
+    pub fn is_operator(self) -> bool {
+        self >= Self::GAME_MASTER
+    }
+}
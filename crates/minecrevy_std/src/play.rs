@@ -0,0 +1,1250 @@
+//! This module contains the [`PlayPlugin`], which handles core packets in the play state.
+
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, math::DVec3, prelude::*};
+use minecrevy_io::angle::Angle;
+use minecrevy_net::{
+    client::{ClientPacketWriter, PacketWriter, ProtocolState, StateChanged},
+    packet::Recv,
+};
+use minecrevy_protocol::{
+    play::{
+        Animation, ChunkData, ChunkDataAndLight, ChunkSection, ConfirmTeleport, EntityAnimation,
+        EntityMetadata, GameEvent, LightData, Login, Metadata, SetCenterChunk,
+        SetDefaultSpawnPosition, SetPlayerPosition, SetPlayerPositionAndRotation, SpawnEntity,
+        Spectate, SwingArm, SyncPlayerPosition,
+    },
+    ServerProtocolPlugin,
+};
+use minecrevy_util::{
+    coords::block_to_chunk,
+    dimension::DimensionType,
+    game_mode::{GameMode, PreviousGameMode},
+    key::Key,
+    registry::Registry,
+    ticks::Ticks,
+};
+use uuid::Uuid;
+
+use crate::{
+    entity_action::{EntityFlags, EntityPose},
+    profile::GameProfile,
+    CorePlugin, PlayerCount,
+};
+
+/// [`Plugin`] that handles core packets in the play state, such as player
+/// movement, arm swings, and spectating.
+///
+/// Configurable [`Resource`]s:
+/// - [`SpawnPoint`]: Where players spawn when they join.
+/// - [`DefaultGameMode`]: The game mode players have when they join.
+/// - [`Hardcore`]: Whether players get a single life.
+/// - [`ViewDistance`]: How many chunks around players are sent to them.
+/// - [`ResyncThreshold`]: How far players can move in a single update before
+///   they're teleported back.
+/// - [`MovementLimits`]: How fast and how often players can move.
+///
+/// [`FixedUpdate`] is set to run once per game tick, at [`Ticks::PER_SECOND`],
+/// unless [`PlayPlugin::tick_rate`] says otherwise.
+pub struct PlayPlugin {
+    /// How many times per second [`FixedUpdate`] runs, or [`None`] to keep the
+    /// app's own [`Time<Fixed>`] configuration.
+    pub tick_rate: Option<f64>,
+}
+
+impl Default for PlayPlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: Some(Ticks::PER_SECOND as f64),
+        }
+    }
+}
+
+impl Plugin for PlayPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+        assert!(
+            app.is_plugin_added::<CorePlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<CorePlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<EntityIds>();
+        app.init_resource::<TeleportIds>();
+        app.init_resource::<SpawnPoint>();
+        app.init_resource::<DefaultGameMode>();
+        app.init_resource::<Hardcore>();
+        app.init_resource::<ViewDistance>();
+        app.init_resource::<ResyncThreshold>();
+        app.init_resource::<MovementLimits>();
+        if let Some(hz) = self.tick_rate {
+            app.insert_resource(Time::<Fixed>::from_hz(hz));
+        }
+
+        app.add_systems(FixedUpdate, Self::reset_movement_counts);
+        app.add_observer(Self::on_state_changed);
+        app.add_observer(Self::on_enter_play);
+        app.add_observer(Self::on_confirm_teleport);
+        app.add_observer(Self::on_set_player_position);
+        app.add_observer(Self::on_set_player_position_and_rotation);
+        app.add_observer(Self::on_swing_arm);
+        app.add_observer(Self::on_spectate);
+    }
+}
+
+impl PlayPlugin {
+    /// The name of the only dimension players spawn in.
+    pub const DIMENSION: &str = "overworld";
+
+    /// [`Observer`] [`System`] that triggers [`EnterPlay`] on a client that
+    /// moved from the configuration state to the play state.
+    pub fn on_state_changed(trigger: Trigger<StateChanged>, mut commands: Commands) {
+        let event = trigger.event();
+        if event.from == ProtocolState::Config && event.to == ProtocolState::Play {
+            commands.trigger_targets(EnterPlay, trigger.entity());
+        }
+    }
+
+    /// [`Observer`] [`System`] that spawns a player that entered the play
+    /// state into an empty overworld.
+    ///
+    /// The client is sent, in order:
+    /// 1. [`Login`], describing the player and the world.
+    /// 2. [`SetDefaultSpawnPosition`] at the [`SpawnPoint`].
+    /// 3. [`SyncPlayerPosition`], teleporting the player to the [`SpawnPoint`].
+    /// 4. [`GameEvent::START_WAITING_FOR_CHUNKS`].
+    /// 5. [`SetCenterChunk`] at the chunk the player spawned in.
+    /// 6. [`ChunkDataAndLight`] for every chunk within the [`ViewDistance`],
+    ///    which are all empty.
+    #[expect(clippy::too_many_arguments)]
+    pub fn on_enter_play(
+        trigger: Trigger<EnterPlay>,
+        mut writer: PacketWriter,
+        mut entity_ids: ResMut<EntityIds>,
+        mut teleport_ids: ResMut<TeleportIds>,
+        time: Res<Time<Real>>,
+        spawn: Res<SpawnPoint>,
+        game_mode: Res<DefaultGameMode>,
+        hardcore: Res<Hardcore>,
+        view_distance: Res<ViewDistance>,
+        counts: Res<PlayerCount>,
+        mut commands: Commands,
+    ) {
+        let player = trigger.entity();
+        let Ok(writer) = writer.get_client(player) else {
+            return;
+        };
+
+        let entity_id = entity_ids.allocate();
+        let dimension = Key::minecraft(Self::DIMENSION).unwrap();
+
+        writer.send(&Login {
+            entity_id: entity_id.0,
+            hardcore: hardcore.0,
+            dimensions: vec![dimension.clone()],
+            max_players: counts.max,
+            view_distance: view_distance.0,
+            simulation_distance: view_distance.0,
+            reduced_debug_info: false,
+            respawn_screen: true,
+            limited_crafting: false,
+            // The overworld is the first entry of the dimension type registry.
+            dimension_type: 0,
+            dimension,
+            hashed_seed: 0,
+            game_mode: game_mode.0,
+            previous_game_mode: PreviousGameMode::None,
+            debug: false,
+            flat: false,
+            death_location: None,
+            portal_cooldown: 0,
+            enforces_secure_chat: false,
+        });
+
+        writer.send(&SetDefaultSpawnPosition {
+            position: spawn.position.floor().as_ivec3(),
+            angle: spawn.yaw,
+        });
+
+        let mut pending_teleport = PendingTeleport::default();
+        pending_teleport.teleport(
+            &writer,
+            &mut teleport_ids,
+            spawn.position,
+            Rotation {
+                yaw: spawn.yaw,
+                pitch: spawn.pitch,
+            },
+        );
+
+        writer.send(&GameEvent {
+            event: GameEvent::START_WAITING_FOR_CHUNKS,
+            value: 0.,
+        });
+
+        let center = block_to_chunk(spawn.position.floor().as_ivec3());
+        let (center_x, center_z) = (center.x, center.y);
+        writer.send(&SetCenterChunk {
+            chunk_x: center_x,
+            chunk_z: center_z,
+        });
+
+        let sections = DimensionType::OVERWORLD.sections();
+        let chunk = ChunkData {
+            sections: vec![ChunkSection::empty(0); sections],
+            ..default()
+        };
+        for chunk_x in center_x - view_distance.0..=center_x + view_distance.0 {
+            for chunk_z in center_z - view_distance.0..=center_z + view_distance.0 {
+                writer.send(&ChunkDataAndLight {
+                    chunk_x,
+                    chunk_z,
+                    chunk: chunk.clone(),
+                    light: LightData::new(sections),
+                });
+            }
+        }
+
+        commands.entity(player).insert((
+            entity_id,
+            Position(spawn.position),
+            Rotation {
+                yaw: spawn.yaw,
+                pitch: spawn.pitch,
+            },
+            PlayerGameMode(game_mode.0),
+            EntityFlags::default(),
+            EntityPose::default(),
+            pending_teleport,
+            MovementTracker {
+                last_move: time.elapsed(),
+                moves_this_tick: 0,
+            },
+        ));
+    }
+
+    /// [`Observer`] [`System`] that clears a player's [`PendingTeleport`] once
+    /// the client confirms it.
+    ///
+    /// Confirmations of any other teleport are ignored.
+    pub fn on_confirm_teleport(
+        trigger: Trigger<Recv<ConfirmTeleport>>,
+        mut players: Query<&mut PendingTeleport>,
+    ) {
+        let packet = &trigger.event().0;
+        let player = trigger.entity();
+
+        let Ok(mut pending) = players.get_mut(player) else {
+            return;
+        };
+        if pending.0 == Some(packet.teleport_id) {
+            pending.0 = None;
+        } else {
+            debug!(
+                "Ignoring confirmation of teleport {} from {player}, expected {:?}",
+                packet.teleport_id, pending.0
+            );
+        }
+    }
+
+    /// [`Observer`] [`System`] that updates the [`Position`] of a moving player.
+    pub fn on_set_player_position(
+        trigger: Trigger<Recv<SetPlayerPosition>>,
+        mut writer: PacketWriter,
+        mut validator: MovementValidator,
+        mut players: Query<(
+            &mut Position,
+            &Rotation,
+            &mut PendingTeleport,
+            &mut MovementTracker,
+        )>,
+    ) {
+        let packet = &trigger.event().0;
+        let player = trigger.entity();
+
+        let Ok((mut position, rotation, mut pending, mut tracker)) = players.get_mut(player) else {
+            return;
+        };
+        let Ok(writer) = writer.get_client(player) else {
+            return;
+        };
+
+        if validator.accept(
+            &writer,
+            (&position, *rotation),
+            &mut pending,
+            &mut tracker,
+            packet.position,
+        ) {
+            position.0 = packet.position;
+        }
+    }
+
+    /// [`Observer`] [`System`] that updates the [`Position`] and [`Rotation`]
+    /// of a moving player.
+    pub fn on_set_player_position_and_rotation(
+        trigger: Trigger<Recv<SetPlayerPositionAndRotation>>,
+        mut writer: PacketWriter,
+        mut validator: MovementValidator,
+        mut players: Query<(
+            &mut Position,
+            &mut Rotation,
+            &mut PendingTeleport,
+            &mut MovementTracker,
+        )>,
+    ) {
+        let packet = &trigger.event().0;
+        let player = trigger.entity();
+
+        let Ok((mut position, mut rotation, mut pending, mut tracker)) = players.get_mut(player)
+        else {
+            return;
+        };
+        let Ok(writer) = writer.get_client(player) else {
+            return;
+        };
+
+        if validator.accept(
+            &writer,
+            (&position, *rotation),
+            &mut pending,
+            &mut tracker,
+            packet.position,
+        ) {
+            position.0 = packet.position;
+            *rotation = Rotation {
+                yaw: packet.yaw,
+                pitch: packet.pitch,
+            };
+        }
+    }
+
+    /// [`System`] that resets the number of movement packets each player has
+    /// sent this tick, run once per tick in [`FixedUpdate`].
+    pub fn reset_movement_counts(mut trackers: Query<&mut MovementTracker>) {
+        for mut tracker in &mut trackers {
+            tracker.moves_this_tick = 0;
+        }
+    }
+
+    /// [`Observer`] [`System`] that broadcasts a player's arm swing to the
+    /// other players in the play state.
+    pub fn on_swing_arm(
+        trigger: Trigger<Recv<SwingArm>>,
+        mut writer: PacketWriter,
+        entity_ids: Query<&EntityId>,
+    ) {
+        let packet = &trigger.event().0;
+        let swinger = trigger.entity();
+
+        let Ok(entity_id) = entity_ids.get(swinger) else {
+            return;
+        };
+
+        let animation = EntityAnimation {
+            entity_id: entity_id.0,
+            animation: Animation::swing(packet.hand),
+        };
+        writer.broadcast_except(ProtocolState::Play, swinger, &animation);
+    }
+
+    /// [`Observer`] [`System`] that teleports spectating players to the
+    /// player they chose to spectate.
+    ///
+    /// Requests from players that aren't in [`GameMode::Spectator`] are ignored.
+    pub fn on_spectate(
+        trigger: Trigger<Recv<Spectate>>,
+        mut writer: PacketWriter,
+        mut teleport_ids: ResMut<TeleportIds>,
+        mut players: Query<(
+            &GameProfile,
+            &PlayerGameMode,
+            &mut Position,
+            &Rotation,
+            &mut PendingTeleport,
+        )>,
+    ) {
+        let packet = &trigger.event().0;
+        let spectator = trigger.entity();
+
+        let Ok((_, game_mode, ..)) = players.get(spectator) else {
+            return;
+        };
+        if game_mode.0 != GameMode::Spectator {
+            debug!("Ignoring spectate request from non-spectator {spectator}");
+            return;
+        }
+
+        let Some(target) = players
+            .iter()
+            .find(|(profile, ..)| profile.uuid == packet.target)
+            .map(|(_, _, position, ..)| position.0)
+        else {
+            debug!(
+                "Ignoring spectate request for unknown player {}",
+                packet.target
+            );
+            return;
+        };
+
+        let Ok(writer) = writer.get_client(spectator) else {
+            return;
+        };
+        let Ok((_, _, mut position, rotation, mut pending)) = players.get_mut(spectator) else {
+            return;
+        };
+        position.0 = target;
+
+        pending.teleport(&writer, &mut teleport_ids, target, *rotation);
+    }
+}
+
+/// [`Event`] triggered on a client when it enters the play state, once
+/// configuration has finished.
+#[derive(Event)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct EnterPlay;
+
+/// [`Resource`] that stores where players spawn when they join.
+#[derive(Resource)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpawnPoint {
+    /// The position players spawn at.
+    pub position: DVec3,
+    /// The yaw players face when spawning, in degrees.
+    pub yaw: f32,
+    /// The pitch players face when spawning, in degrees.
+    pub pitch: f32,
+}
+
+impl Default for SpawnPoint {
+    fn default() -> Self {
+        Self {
+            position: DVec3::new(0.5, 64., 0.5),
+            yaw: 0.,
+            pitch: 0.,
+        }
+    }
+}
+
+/// [`Resource`] that stores the [`GameMode`] players have when they join.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct DefaultGameMode(pub GameMode);
+
+/// [`Resource`] that stores whether the server is in hardcore mode, where
+/// players get a single life and the difficulty is locked.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct Hardcore(pub bool);
+
+/// [`Resource`] that stores how many chunks around players are sent to them,
+/// in each direction.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ViewDistance(pub i32);
+
+impl Default for ViewDistance {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+/// [`Component`] that stores the network ID of an entity, which identifies it
+/// in packets sent to clients.
+#[derive(Component, Deref)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct EntityId(pub i32);
+
+/// [`Resource`] that allocates [`EntityId`]s.
+#[derive(Resource, Default)]
+pub struct EntityIds(i32);
+
+impl EntityIds {
+    /// Returns the next unused [`EntityId`].
+    pub fn allocate(&mut self) -> EntityId {
+        self.0 = self.0.wrapping_add(1);
+        EntityId(self.0)
+    }
+
+    /// Allocates an [`EntityId`] for a new entity of the given kind, returning
+    /// the packets that make it appear to clients.
+    ///
+    /// Returns [`None`] if the kind isn't in the given entity type registry.
+    pub fn spawn(
+        &mut self,
+        entity_types: &Registry,
+        kind: &Key,
+        uuid: Uuid,
+        position: DVec3,
+        rotation: Rotation,
+        metadata: Metadata,
+    ) -> Option<EntitySpawn> {
+        let kind = entity_types.id_of(kind)?;
+        let entity_id = self.allocate();
+
+        Some(EntitySpawn {
+            entity_id,
+            spawn: SpawnEntity {
+                entity_id: entity_id.0,
+                uuid,
+                kind,
+                position,
+                pitch: Angle::from_degrees(rotation.pitch),
+                yaw: Angle::from_degrees(rotation.yaw),
+                head_yaw: Angle::from_degrees(rotation.yaw),
+                data: 0,
+                velocity: DVec3::ZERO,
+            },
+            metadata: EntityMetadata {
+                entity_id: entity_id.0,
+                metadata,
+            },
+        })
+    }
+}
+
+/// The packets that make a newly spawned entity appear to clients, created
+/// with [`EntityIds::spawn`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct EntitySpawn {
+    /// The network ID allocated for the entity.
+    pub entity_id: EntityId,
+    /// The packet that adds the entity.
+    pub spawn: SpawnEntity,
+    /// The packet that sets the initial metadata of the entity.
+    pub metadata: EntityMetadata,
+}
+
+impl EntitySpawn {
+    /// Sets the initial velocity of the entity, in blocks per tick.
+    pub fn with_velocity(mut self, velocity: DVec3) -> Self {
+        self.spawn.velocity = velocity;
+        self
+    }
+
+    /// Sends the packets to a client, in the order they must be received.
+    ///
+    /// The metadata packet is skipped if there's no metadata to send.
+    pub fn send(&self, writer: &ClientPacketWriter) {
+        writer.send(&self.spawn);
+        if !self.metadata.metadata.0.is_empty() {
+            writer.send(&self.metadata);
+        }
+    }
+}
+
+/// [`Component`] that stores the position of an entity in its world.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Position(pub DVec3);
+
+/// [`Component`] that stores the rotation of an entity, in degrees.
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Rotation {
+    /// The rotation around the vertical axis.
+    pub yaw: f32,
+    /// The rotation around the horizontal axis.
+    pub pitch: f32,
+}
+
+/// [`Component`] that stores the current [`GameMode`] of a player.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct PlayerGameMode(pub GameMode);
+
+/// [`Component`] that stores the ID of the last [`SyncPlayerPosition`]
+/// teleport sent to a player, until the client confirms it.
+///
+/// Movement from the player is ignored while a teleport is pending, as it was
+/// sent before the client knew about the teleport.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct PendingTeleport(pub Option<i32>);
+
+impl PendingTeleport {
+    /// Teleports the player to the given position and rotation, and waits for
+    /// the client to confirm it.
+    pub fn teleport(
+        &mut self,
+        writer: &ClientPacketWriter,
+        teleport_ids: &mut TeleportIds,
+        position: DVec3,
+        rotation: Rotation,
+    ) {
+        let teleport_id = teleport_ids.allocate();
+        writer.send(&SyncPlayerPosition {
+            position,
+            yaw: rotation.yaw,
+            pitch: rotation.pitch,
+            relative: 0,
+            teleport_id,
+        });
+        self.0 = Some(teleport_id);
+    }
+}
+
+/// [`Resource`] that stores how far, in blocks, a player can move in a single
+/// update before they're teleported back to their last accepted position.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ResyncThreshold(pub f64);
+
+impl Default for ResyncThreshold {
+    fn default() -> Self {
+        Self(10.)
+    }
+}
+
+/// [`Resource`] that limits how fast and how often players can move.
+#[derive(Resource)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MovementLimits {
+    /// The maximum speed of a player, in blocks per tick.
+    ///
+    /// Moves that are faster than this, measured since the player's last
+    /// accepted move, teleport the player back.
+    pub max_speed: f64,
+    /// The maximum number of movement packets a player can send per tick.
+    ///
+    /// Any further movement packets in the same tick are ignored.
+    pub max_moves_per_tick: u32,
+}
+
+impl Default for MovementLimits {
+    fn default() -> Self {
+        Self {
+            max_speed: 4.,
+            max_moves_per_tick: 10,
+        }
+    }
+}
+
+/// [`Component`] that tracks a player's recent movement, to enforce the
+/// [`MovementLimits`].
+///
+/// The last accepted position of the player is their [`Position`].
+#[derive(Component)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct MovementTracker {
+    /// When the player's last move was accepted, in real time since startup.
+    pub last_move: Duration,
+    /// The number of movement packets the player has sent this tick.
+    pub moves_this_tick: u32,
+}
+
+/// [`SystemParam`] that decides whether player moves are accepted, according
+/// to the [`ResyncThreshold`] and [`MovementLimits`].
+#[derive(SystemParam)]
+pub struct MovementValidator<'w> {
+    teleport_ids: ResMut<'w, TeleportIds>,
+    threshold: Res<'w, ResyncThreshold>,
+    limits: Res<'w, MovementLimits>,
+    time: Res<'w, Time<Real>>,
+}
+
+impl MovementValidator<'_> {
+    /// Returns `true` if a player at the given position and rotation may move
+    /// to the target position.
+    ///
+    /// Moves are ignored while the player has a [`PendingTeleport`] or has
+    /// exceeded [`MovementLimits::max_moves_per_tick`]. Moves beyond the
+    /// [`ResyncThreshold`], faster than [`MovementLimits::max_speed`], or to
+    /// a non-finite target teleport the player back to their current
+    /// [`Position`].
+    pub fn accept(
+        &mut self,
+        writer: &ClientPacketWriter,
+        (position, rotation): (&Position, Rotation),
+        pending: &mut PendingTeleport,
+        tracker: &mut MovementTracker,
+        target: DVec3,
+    ) -> bool {
+        tracker.moves_this_tick += 1;
+        if pending.0.is_some() || tracker.moves_this_tick > self.limits.max_moves_per_tick {
+            return false;
+        }
+
+        let now = self.time.elapsed();
+        let ticks = Ticks::from_duration(now.saturating_sub(tracker.last_move))
+            .0
+            .max(1);
+        let distance = position.distance(target);
+
+        if !target.is_finite()
+            || distance > self.threshold.0
+            || distance > self.limits.max_speed * ticks as f64
+        {
+            pending.teleport(writer, &mut self.teleport_ids, position.0, rotation);
+            return false;
+        }
+
+        tracker.last_move = now;
+        true
+    }
+}
+
+/// [`Resource`] that allocates IDs for [`SyncPlayerPosition`] teleports.
+#[derive(Resource, Default)]
+pub struct TeleportIds(i32);
+
+impl TeleportIds {
+    /// Returns the next teleport ID.
+    pub fn allocate(&mut self) -> i32 {
+        self.0 = self.0.wrapping_add(1);
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        time::{Duration, Instant},
+    };
+
+    use bevy::{math::DVec3, prelude::*, time::TimeUpdateStrategy};
+    use minecrevy_io::{
+        ext::{ReadMinecraftExt, WriteMinecraftExt},
+        packet::RawPacket,
+    };
+    use minecrevy_net::{
+        client::ProtocolState, loopback::LoopbackClient, packet::Recv, server::Server,
+        stats::NetworkStats,
+    };
+    use minecrevy_protocol::play::{ConfirmTeleport, SetPlayerPosition, Spectate, SwingArm};
+    use minecrevy_util::{game_mode::GameMode, hand::Hand, pose::Pose};
+    use uuid::Uuid;
+
+    use super::{
+        EntityId, MovementTracker, PendingTeleport, PlayPlugin, PlayerGameMode, Position, Rotation,
+        SpawnPoint, ViewDistance,
+    };
+    use crate::{
+        config::{ConfigPlugin, SyncedRegistries},
+        entity_action::{EntityFlags, EntityPose},
+        handshake::HandshakePlugin,
+        login::LoginPlugin,
+        profile::GameProfile,
+        testing,
+    };
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins((
+            HandshakePlugin,
+            LoginPlugin,
+            ConfigPlugin,
+            PlayPlugin::default(),
+        ));
+        app
+    }
+
+    fn send(
+        client: &mut LoopbackClient,
+        id: i32,
+        write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>,
+    ) {
+        let mut body = Vec::new();
+        write(&mut body).unwrap();
+        client.send_raw(RawPacket { id, body }).unwrap();
+    }
+
+    /// Updates the app until the given condition holds for the only client.
+    fn update_until<T: Component>(app: &mut App, condition: impl Fn(&T) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            app.update();
+            let value = app.world_mut().query::<&T>().single(app.world());
+            if condition(value) {
+                return;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for the server"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Updates the app until the server has sent the given number of packets
+    /// in total, then returns the IDs of the packets the client received.
+    fn recv_ids(
+        app: &mut App,
+        client: &mut LoopbackClient,
+        sent: &mut u64,
+        count: u64,
+    ) -> Vec<i32> {
+        update_until(app, |stats: &NetworkStats| {
+            stats.packets_sent() >= *sent + count
+        });
+        *sent += count;
+        (0..count).map(|_| client.recv_raw().unwrap().id).collect()
+    }
+
+    /// Joins the client from the handshake through to the play state, checking
+    /// the IDs of the packets it receives, in order.
+    fn join(app: &mut App, client: &mut LoopbackClient, sent: &mut u64) {
+        let registries = app.world().resource::<SyncedRegistries>().0.len() as u64;
+        let chunks = (2 * app.world().resource::<ViewDistance>().0 as u64 + 1).pow(2);
+
+        testing::handshake(client, 767, 2);
+        send(client, 0x00, |body| {
+            body.write_string("Notch")?;
+            body.write_uuid(Uuid::nil())
+        });
+        assert_eq!(recv_ids(app, client, sent, 1), [0x02]);
+
+        send(client, 0x03, |_| Ok(()));
+        assert_eq!(recv_ids(app, client, sent, 2), [0x0C, 0x0E]);
+
+        send(client, 0x07, |body| {
+            body.write_var_i32(1)?;
+            body.write_string("minecraft")?;
+            body.write_string("core")?;
+            body.write_string("1.21")
+        });
+        let ids = recv_ids(app, client, sent, registries + 1);
+        assert!(ids[..ids.len() - 1].iter().all(|&id| id == 0x07));
+        assert_eq!(ids.last(), Some(&0x03));
+
+        send(client, 0x03, |_| Ok(()));
+        let ids = recv_ids(app, client, sent, 5 + chunks);
+        assert_eq!(ids[..5], [0x2B, 0x56, 0x40, 0x22, 0x54]);
+        assert!(ids[5..].iter().all(|&id| id == 0x27));
+    }
+
+    #[test]
+    fn join_sends_play_sequence_in_order() {
+        let mut app = app();
+        let mut client = app.world().resource::<Server>().connect_loopback();
+        join(&mut app, &mut client, &mut 0);
+    }
+
+    #[test]
+    fn join_inserts_entity_flags_and_pose() {
+        let mut app = app();
+        let mut client = app.world().resource::<Server>().connect_loopback();
+        join(&mut app, &mut client, &mut 0);
+
+        let (flags, pose) = app
+            .world_mut()
+            .query::<(&EntityFlags, &EntityPose)>()
+            .single(app.world());
+        assert_eq!(*flags, EntityFlags::default());
+        assert_eq!(*pose, EntityPose(Pose::Standing));
+    }
+
+    #[test]
+    fn non_finite_move_is_rejected() {
+        let mut app = app();
+        let mut client = app.world().resource::<Server>().connect_loopback();
+        let mut sent = 0;
+        join(&mut app, &mut client, &mut sent);
+
+        // Confirm the spawn teleport, which is the first teleport.
+        send(&mut client, 0x00, |body| body.write_var_i32(1));
+        update_until(&mut app, |pending: &PendingTeleport| pending.0.is_none());
+
+        let spawn = app.world().resource::<SpawnPoint>().position;
+        let target = spawn + DVec3::new(0.5, 0., 0.);
+        let move_to = |client: &mut LoopbackClient, target: DVec3| {
+            send(client, 0x1A, |body| {
+                body.write_f64(target.x)?;
+                body.write_f64(target.y)?;
+                body.write_f64(target.z)?;
+                body.write_bool(true)
+            });
+        };
+
+        move_to(&mut client, target);
+        update_until(&mut app, |position: &Position| position.0 == target);
+
+        for invalid in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            move_to(&mut client, DVec3::new(invalid, target.y, target.z));
+            // The player is teleported back.
+            assert_eq!(recv_ids(&mut app, &mut client, &mut sent, 1), [0x40]);
+            let position = app.world_mut().query::<&Position>().single(app.world());
+            assert_eq!(position.0, target);
+
+            let pending = app
+                .world_mut()
+                .query::<&PendingTeleport>()
+                .single(app.world());
+            let teleport_id = pending.0.unwrap();
+            send(&mut client, 0x00, |body| body.write_var_i32(teleport_id));
+            update_until(&mut app, |pending: &PendingTeleport| pending.0.is_none());
+        }
+    }
+
+    #[test]
+    fn movement_counts_reset_once_per_tick() {
+        let mut app = app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            10,
+        )));
+        let player = app
+            .world_mut()
+            .spawn(MovementTracker {
+                last_move: Duration::ZERO,
+                moves_this_tick: 5,
+            })
+            .id();
+        let moves = |app: &App| {
+            app.world()
+                .get::<MovementTracker>(player)
+                .unwrap()
+                .moves_this_tick
+        };
+
+        // Frames shorter than a tick don't reset the count.
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(moves(&app), 5);
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(moves(&app), 0);
+    }
+
+    /// Connects a player in the play state with the given name, game mode,
+    /// and position.
+    fn player(
+        app: &mut App,
+        username: &str,
+        game_mode: GameMode,
+        position: DVec3,
+    ) -> (Entity, LoopbackClient) {
+        let (player, connection) = testing::connect(app, ProtocolState::Play);
+        app.world_mut().entity_mut(player).insert((
+            GameProfile::offline(username),
+            PlayerGameMode(game_mode),
+            Position(position),
+            Rotation {
+                yaw: 90.,
+                pitch: 10.,
+            },
+            PendingTeleport::default(),
+        ));
+        (player, connection)
+    }
+
+    fn spectate(app: &mut App, spectator: Entity, target: Uuid) {
+        app.world_mut()
+            .trigger_targets(Recv(Spectate { target }), spectator);
+        app.world_mut().flush();
+    }
+
+    #[test]
+    fn spectator_is_teleported_to_the_target() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (spectator, mut connection) =
+            player(&mut app, "Spectator", GameMode::Spectator, DVec3::ZERO);
+        let target = DVec3::new(10.5, 70., -3.5);
+        player(&mut app, "Notch", GameMode::Survival, target);
+
+        spectate(&mut app, spectator, GameProfile::offline("Notch").uuid);
+
+        let world = app.world();
+        assert_eq!(world.get::<Position>(spectator), Some(&Position(target)));
+        let teleport_id = world.get::<PendingTeleport>(spectator).unwrap().0.unwrap();
+
+        let packet = testing::recv(&mut app, &mut connection);
+        assert_eq!(packet.id, 0x40);
+        let mut body = packet.reader();
+        let position = DVec3::new(
+            body.read_f64().unwrap(),
+            body.read_f64().unwrap(),
+            body.read_f64().unwrap(),
+        );
+        assert_eq!(position, target);
+        // The spectator keeps their rotation.
+        assert_eq!(body.read_f32().unwrap(), 90.);
+        assert_eq!(body.read_f32().unwrap(), 10.);
+        assert_eq!(body.read_u8().unwrap(), 0);
+        assert_eq!(body.read_var_i32().unwrap(), teleport_id);
+    }
+
+    #[test]
+    fn non_spectator_is_not_teleported() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player_entity, _connection) =
+            player(&mut app, "Player", GameMode::Creative, DVec3::ZERO);
+        player(&mut app, "Notch", GameMode::Survival, DVec3::ONE);
+
+        spectate(&mut app, player_entity, GameProfile::offline("Notch").uuid);
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<Position>(player_entity),
+            Some(&Position(DVec3::ZERO))
+        );
+        assert_eq!(
+            world.get::<PendingTeleport>(player_entity),
+            Some(&PendingTeleport(None))
+        );
+    }
+
+    #[test]
+    fn unknown_target_is_ignored() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (spectator, _connection) =
+            player(&mut app, "Spectator", GameMode::Spectator, DVec3::ZERO);
+
+        spectate(&mut app, spectator, GameProfile::offline("Nobody").uuid);
+
+        let world = app.world();
+        assert_eq!(
+            world.get::<Position>(spectator),
+            Some(&Position(DVec3::ZERO))
+        );
+        assert_eq!(
+            world.get::<PendingTeleport>(spectator),
+            Some(&PendingTeleport(None))
+        );
+    }
+
+    fn swing(app: &mut App, swinger: Entity, hand: Hand) {
+        app.world_mut()
+            .trigger_targets(Recv(SwingArm { hand }), swinger);
+        app.world_mut().flush();
+    }
+
+    #[test]
+    fn swing_is_broadcast_to_other_players() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (swinger, mut swinger_connection) = testing::connect(&mut app, ProtocolState::Play);
+        app.world_mut().entity_mut(swinger).insert(EntityId(7));
+        let (_, mut observer) = testing::connect(&mut app, ProtocolState::Play);
+
+        for (hand, animation) in [(Hand::MainHand, 0), (Hand::OffHand, 3)] {
+            swing(&mut app, swinger, hand);
+
+            let packet = testing::recv(&mut app, &mut observer);
+            assert_eq!(packet.id, 0x03);
+            assert_eq!(packet.body, [7, animation]);
+        }
+
+        // The swinging player isn't sent their own animation.
+        swinger_connection.set_timeout(Duration::from_millis(50));
+        let error = swinger_connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn swing_without_entity_id_is_ignored() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (swinger, _connection) = testing::connect(&mut app, ProtocolState::Play);
+        let (_, mut observer) = testing::connect(&mut app, ProtocolState::Play);
+
+        swing(&mut app, swinger, Hand::MainHand);
+        app.update();
+
+        observer.set_timeout(Duration::from_millis(50));
+        let error = observer.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    /// Connects a player at the origin whose last move was accepted at the
+    /// current time, so that moves of up to one tick are allowed.
+    fn mover(app: &mut App) -> (Entity, LoopbackClient) {
+        let (player, connection) = player(app, "Notch", GameMode::Survival, DVec3::ZERO);
+        let now = app.world().resource::<Time<Real>>().elapsed();
+        app.world_mut().entity_mut(player).insert(MovementTracker {
+            last_move: now,
+            moves_this_tick: 0,
+        });
+        (player, connection)
+    }
+
+    fn move_to(app: &mut App, player: Entity, position: DVec3) {
+        let packet = SetPlayerPosition {
+            position,
+            on_ground: true,
+        };
+        app.world_mut().trigger_targets(Recv(packet), player);
+        app.world_mut().flush();
+    }
+
+    fn position(app: &App, player: Entity) -> DVec3 {
+        app.world().get::<Position>(player).unwrap().0
+    }
+
+    /// Asserts that the packet teleports the player to the given position,
+    /// keeping the rotation given by [`player`].
+    fn assert_sync_position(packet: RawPacket, position: DVec3, teleport_id: i32) {
+        assert_eq!(packet.id, 0x40);
+        let mut body = packet.reader();
+        let synced = DVec3::new(
+            body.read_f64().unwrap(),
+            body.read_f64().unwrap(),
+            body.read_f64().unwrap(),
+        );
+        assert_eq!(synced, position);
+        assert_eq!(body.read_f32().unwrap(), 90.);
+        assert_eq!(body.read_f32().unwrap(), 10.);
+        assert_eq!(body.read_u8().unwrap(), 0);
+        assert_eq!(body.read_var_i32().unwrap(), teleport_id);
+    }
+
+    #[test]
+    fn move_within_the_limits_is_accepted() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player, _connection) = mover(&mut app);
+
+        let target = DVec3::new(3., 0., 0.);
+        move_to(&mut app, player, target);
+
+        assert_eq!(position(&app, player), target);
+        assert_eq!(
+            app.world().get::<PendingTeleport>(player),
+            Some(&PendingTeleport(None))
+        );
+    }
+
+    #[test]
+    fn move_beyond_the_threshold_teleports_back() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player, mut connection) = mover(&mut app);
+
+        move_to(&mut app, player, DVec3::new(20., 0., 0.));
+
+        assert_eq!(position(&app, player), DVec3::ZERO);
+        let teleport_id = app.world().get::<PendingTeleport>(player).unwrap().0;
+        let teleport_id = teleport_id.expect("the player should be teleported back");
+        assert_sync_position(
+            testing::recv(&mut app, &mut connection),
+            DVec3::ZERO,
+            teleport_id,
+        );
+    }
+
+    #[test]
+    fn move_faster_than_the_max_speed_teleports_back() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player, mut connection) = mover(&mut app);
+
+        // Within the resync threshold, but further than the player can move
+        // in a single tick.
+        move_to(&mut app, player, DVec3::new(0., 0., 6.));
+
+        assert_eq!(position(&app, player), DVec3::ZERO);
+        let teleport_id = app.world().get::<PendingTeleport>(player).unwrap().0;
+        let teleport_id = teleport_id.expect("the player should be teleported back");
+        assert_sync_position(
+            testing::recv(&mut app, &mut connection),
+            DVec3::ZERO,
+            teleport_id,
+        );
+    }
+
+    fn confirm_teleport(app: &mut App, player: Entity, teleport_id: i32) {
+        app.world_mut()
+            .trigger_targets(Recv(ConfirmTeleport { teleport_id }), player);
+        app.world_mut().flush();
+    }
+
+    fn pending_teleport(app: &App, player: Entity) -> Option<i32> {
+        app.world().get::<PendingTeleport>(player).unwrap().0
+    }
+
+    #[test]
+    fn mismatched_teleport_confirmation_is_ignored() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player, _connection) = mover(&mut app);
+        app.world_mut()
+            .entity_mut(player)
+            .insert(PendingTeleport(Some(5)));
+
+        confirm_teleport(&mut app, player, 4);
+        assert_eq!(pending_teleport(&app, player), Some(5));
+
+        confirm_teleport(&mut app, player, 5);
+        assert_eq!(pending_teleport(&app, player), None);
+    }
+
+    #[test]
+    fn stale_teleport_confirmation_is_ignored() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player, _connection) = mover(&mut app);
+
+        // Two corrective teleports, where only the second is still pending.
+        move_to(&mut app, player, DVec3::new(20., 0., 0.));
+        let first = pending_teleport(&app, player).unwrap();
+        app.world_mut()
+            .entity_mut(player)
+            .insert(PendingTeleport(None));
+        move_to(&mut app, player, DVec3::new(20., 0., 0.));
+        let second = pending_teleport(&app, player).unwrap();
+        assert_ne!(first, second);
+
+        confirm_teleport(&mut app, player, first);
+        assert_eq!(pending_teleport(&app, player), Some(second));
+
+        confirm_teleport(&mut app, player, second);
+        assert_eq!(pending_teleport(&app, player), None);
+    }
+
+    #[test]
+    fn movement_is_ignored_while_a_teleport_is_pending() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+        let (player, mut connection) = mover(&mut app);
+        app.world_mut()
+            .entity_mut(player)
+            .insert(PendingTeleport(Some(5)));
+
+        // Even a valid move is ignored, without another teleport.
+        move_to(&mut app, player, DVec3::new(1., 0., 0.));
+        assert_eq!(position(&app, player), DVec3::ZERO);
+        assert_eq!(pending_teleport(&app, player), Some(5));
+        app.update();
+        connection.set_timeout(Duration::from_millis(50));
+        let error = connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+        // Once confirmed, the player can move again.
+        confirm_teleport(&mut app, player, 5);
+        move_to(&mut app, player, DVec3::new(1., 0., 0.));
+        assert_eq!(position(&app, player), DVec3::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn fixed_update_runs_once_per_tick() {
+        let mut app = testing::app();
+        app.add_plugins(PlayPlugin::default());
+
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        assert_eq!(timestep, Time::<Fixed>::from_hz(20.).timestep());
+    }
+
+    #[test]
+    fn configured_fixed_timestep_is_kept_without_a_tick_rate() {
+        let mut app = testing::app();
+        app.insert_resource(Time::<Fixed>::from_hz(10.));
+        app.add_plugins(PlayPlugin { tick_rate: None });
+
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        assert_eq!(timestep, Time::<Fixed>::from_hz(10.).timestep());
+    }
+}
@@ -0,0 +1,348 @@
+//! This module contains the [`PluginMessagePlugin`], which dispatches plugin
+//! messages to strongly-typed channels.
+
+use std::{
+    io,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use minecrevy_io::{McRead, McWrite};
+use minecrevy_net::packet::Recv;
+use minecrevy_protocol::{play::PluginMessage, ServerProtocolPlugin};
+use minecrevy_util::key::Key;
+
+/// [`Plugin`] that decodes incoming [`PluginMessage`]s on registered channels,
+/// and triggers a [`RecvPluginMessage`] event for each of them.
+///
+/// Messages on channels that haven't been registered via
+/// [`AppPluginChannelExt::add_plugin_channel`] are ignored.
+pub struct PluginMessagePlugin;
+
+impl Plugin for PluginMessagePlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<PluginChannels>();
+
+        app.add_observer(Self::on_plugin_message);
+    }
+}
+
+impl PluginMessagePlugin {
+    /// [`Observer`] [`System`] that dispatches incoming plugin messages to
+    /// their registered channel.
+    pub fn on_plugin_message(trigger: Trigger<Recv<PluginMessage>>, mut commands: Commands) {
+        let client = trigger.entity();
+        let PluginMessage { channel, data } = trigger.event().0.clone();
+
+        commands.queue(move |world: &mut World| {
+            let Some(handler) = world.resource::<PluginChannels>().get(&channel) else {
+                trace!("Ignoring plugin message on unregistered channel {channel}");
+                return;
+            };
+
+            (handler)(world, client, &channel, &data);
+        });
+    }
+}
+
+/// A strongly-typed plugin message channel, which encodes and decodes its
+/// payloads as `T`.
+#[derive(Debug)]
+pub struct PluginChannel<T> {
+    key: Key,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PluginChannel<T> {
+    /// Creates a new [`PluginChannel`] with the given channel [`Key`].
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the [`Key`] of this channel.
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+}
+
+impl<T> Clone for PluginChannel<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.key.clone())
+    }
+}
+
+impl<T: McWrite> PluginChannel<T> {
+    /// Encodes the given payload into a [`PluginMessage`] on this channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload fails to encode.
+    pub fn encode(&self, payload: &T) -> io::Result<PluginMessage> {
+        let mut data = Vec::new();
+        payload.write_default(&mut data)?;
+
+        Ok(PluginMessage {
+            channel: self.key.clone(),
+            data,
+        })
+    }
+}
+
+impl<T: McRead> PluginChannel<T> {
+    /// Decodes the payload of the given [`PluginMessage`], if it was sent on
+    /// this channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload fails to decode.
+    pub fn decode(&self, message: &PluginMessage) -> Option<io::Result<T>> {
+        (message.channel == self.key).then(|| T::read_default(message.data.as_slice()))
+    }
+}
+
+/// [`Event`] emitted for each incoming plugin message on a registered channel.
+///
+/// Dereferences to the decoded payload.
+#[derive(Event)]
+pub struct RecvPluginMessage<T> {
+    /// The channel the message was sent on.
+    pub channel: Key,
+    /// The decoded payload of the message.
+    pub payload: T,
+}
+
+impl<T> Deref for RecvPluginMessage<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.payload
+    }
+}
+
+impl<T> DerefMut for RecvPluginMessage<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.payload
+    }
+}
+
+/// Function pointer for triggering events for incoming plugin messages, given
+/// the client, the channel, and the message's data.
+///
+/// See [`PluginChannels`] for where these are stored.
+pub type PluginChannelHandler = fn(&mut World, Entity, &Key, &[u8]);
+
+/// [`Resource`] that stores [`PluginChannelHandler`]s for triggering [`Event`]s
+/// for incoming plugin messages, keyed by channel.
+#[derive(Resource, Default)]
+pub struct PluginChannels(HashMap<Key, PluginChannelHandler>);
+
+impl PluginChannels {
+    /// Returns the [`PluginChannelHandler`] for the given channel, if any.
+    pub fn get(&self, channel: &Key) -> Option<PluginChannelHandler> {
+        self.0.get(channel).copied()
+    }
+
+    /// Returns `true` if the given channel has been registered.
+    pub fn contains(&self, channel: &Key) -> bool {
+        self.0.contains_key(channel)
+    }
+
+    /// Inserts a [`PluginChannelHandler`] for the given channel, which
+    /// deserializes the message's data into the given type `T` and triggers a
+    /// [`RecvPluginMessage<T>`] event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel has already been registered, as only one payload
+    /// type can be decoded from each channel.
+    pub fn insert<T: McRead + Send + Sync + 'static>(&mut self, channel: Key) {
+        assert!(
+            !self.contains(&channel),
+            "plugin channel {channel} is already registered",
+        );
+
+        self.0.insert(channel, |world, client, channel, data| {
+            let Ok(payload) = T::read_default(data) else {
+                warn!(
+                    "Failed to read plugin message from client {client}: {:?}",
+                    std::any::type_name::<T>()
+                );
+                return;
+            };
+
+            let event = RecvPluginMessage {
+                channel: channel.clone(),
+                payload,
+            };
+            world.trigger_targets(event, client);
+        });
+    }
+}
+
+/// Extension trait for [`App`] to register plugin message channels.
+pub trait AppPluginChannelExt {
+    /// Registers the given channel, so that incoming plugin messages on it are
+    /// decoded as `T` and triggered as [`RecvPluginMessage<T>`] events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel has already been registered.
+    fn add_plugin_channel<T: McRead + Send + Sync + 'static>(&mut self, channel: Key) -> &mut Self;
+}
+
+impl AppPluginChannelExt for App {
+    fn add_plugin_channel<T: McRead + Send + Sync + 'static>(&mut self, channel: Key) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<PluginChannels>()
+            .insert::<T>(channel);
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_io::McWrite;
+    use minecrevy_net::packet::Recv;
+    use minecrevy_protocol::play::PluginMessage;
+    use minecrevy_util::key::Key;
+
+    use super::{AppPluginChannelExt, PluginChannel, PluginMessagePlugin, RecvPluginMessage};
+    use crate::testing;
+
+    fn channel(key: &str) -> PluginChannel<String> {
+        PluginChannel::new(Key::parse(key).unwrap())
+    }
+
+    #[test]
+    fn channel_round_trip() {
+        let brand = channel("minecraft:brand");
+        let message = brand.encode(&"minecrevy".to_owned()).unwrap();
+        assert_eq!(message.channel, *brand.key());
+
+        let mut expected = Vec::new();
+        "minecrevy".to_owned().write_default(&mut expected).unwrap();
+        assert_eq!(message.data, expected);
+
+        assert_eq!(
+            brand.decode(&message).unwrap().unwrap(),
+            "minecrevy".to_owned()
+        );
+    }
+
+    #[test]
+    fn channel_ignores_other_channels() {
+        let message = channel("test:other").encode(&"data".to_owned()).unwrap();
+        assert!(channel("minecraft:brand").decode(&message).is_none());
+    }
+
+    #[derive(Resource, Default)]
+    struct Received(Vec<(Entity, Key, String)>);
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins(PluginMessagePlugin);
+        app.add_plugin_channel::<String>(Key::parse("test:greeting").unwrap());
+        app.init_resource::<Received>();
+        app.add_observer(
+            |trigger: Trigger<RecvPluginMessage<String>>, mut received: ResMut<Received>| {
+                let event = trigger.event();
+                received.0.push((
+                    trigger.entity(),
+                    event.channel.clone(),
+                    event.payload.clone(),
+                ));
+            },
+        );
+        app
+    }
+
+    fn send(app: &mut App, client: Entity, message: PluginMessage) {
+        app.world_mut().trigger_targets(Recv(message), client);
+        app.world_mut().flush();
+    }
+
+    #[test]
+    fn registered_channel_triggers_typed_event() {
+        let mut app = app();
+        let client = app.world_mut().spawn_empty().id();
+
+        let message = channel("test:greeting")
+            .encode(&"hello".to_owned())
+            .unwrap();
+        send(&mut app, client, message);
+
+        assert_eq!(
+            app.world().resource::<Received>().0,
+            [(
+                client,
+                Key::parse("test:greeting").unwrap(),
+                "hello".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn channels_sharing_a_payload_type_are_told_apart() {
+        let mut app = app();
+        app.add_plugin_channel::<String>(Key::parse("test:farewell").unwrap());
+        let client = app.world_mut().spawn_empty().id();
+
+        let message = channel("test:farewell").encode(&"bye".to_owned()).unwrap();
+        send(&mut app, client, message);
+
+        assert_eq!(
+            app.world().resource::<Received>().0,
+            [(
+                client,
+                Key::parse("test:farewell").unwrap(),
+                "bye".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    #[should_panic = "plugin channel test:greeting is already registered"]
+    fn duplicate_channel_is_rejected() {
+        let mut app = app();
+        app.add_plugin_channel::<u8>(Key::parse("test:greeting").unwrap());
+    }
+
+    #[test]
+    fn unregistered_channel_is_ignored() {
+        let mut app = app();
+        let client = app.world_mut().spawn_empty().id();
+
+        let message = channel("test:other").encode(&"hello".to_owned()).unwrap();
+        send(&mut app, client, message);
+
+        assert!(app.world().resource::<Received>().0.is_empty());
+    }
+
+    #[test]
+    fn malformed_payload_is_ignored() {
+        let mut app = app();
+        let client = app.world_mut().spawn_empty().id();
+
+        // A string length prefix without the string.
+        let message = PluginMessage {
+            channel: Key::parse("test:greeting").unwrap(),
+            data: vec![5],
+        };
+        send(&mut app, client, message);
+
+        assert!(app.world().resource::<Received>().0.is_empty());
+    }
+}
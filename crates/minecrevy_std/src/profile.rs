@@ -0,0 +1,203 @@
+//! This module contains the [`GameProfile`] component, which identifies a player.
+
+use std::io;
+
+use base64::Engine;
+use bevy::prelude::*;
+use md5::{Digest, Md5};
+use minecrevy_io::{
+    args::{ListArgs, ListLength, StringArgs},
+    McRead, McWrite,
+};
+use minecrevy_protocol::login::Property;
+use uuid::{Builder, Uuid};
+
+/// [`Component`] that stores the profile of a player that has logged in.
+#[derive(Component)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameProfile {
+    /// The UUID of the player.
+    pub uuid: Uuid,
+    /// The username of the player.
+    pub username: String,
+    /// The properties of the player, such as their skin.
+    pub properties: Vec<Property>,
+}
+
+impl GameProfile {
+    /// Creates a new [`GameProfile`] for a player on a server in offline mode,
+    /// using their [offline UUID](offline_uuid) and no properties.
+    pub fn offline(username: impl Into<String>) -> Self {
+        let username = username.into();
+        Self {
+            uuid: offline_uuid(&username),
+            username,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Returns the `textures` property of the profile, if it has one.
+    pub fn textures_property(&self) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == "textures")
+    }
+
+    /// Returns the URL of the player's skin, decoded from the `textures`
+    /// property.
+    pub fn skin_url(&self) -> Option<String> {
+        self.texture_url("SKIN")
+    }
+
+    /// Returns the URL of the player's cape, decoded from the `textures`
+    /// property.
+    pub fn cape_url(&self) -> Option<String> {
+        self.texture_url("CAPE")
+    }
+
+    /// Returns the URL of the texture of the given kind, such as `SKIN` or
+    /// `CAPE`, decoded from the base64-encoded JSON of the `textures` property.
+    pub fn texture_url(&self, kind: &str) -> Option<String> {
+        let json = base64::engine::general_purpose::STANDARD
+            .decode(&self.textures_property()?.value)
+            .ok()?;
+        let value = serde_json::from_slice::<serde_json::Value>(&json).ok()?;
+
+        value
+            .get("textures")?
+            .get(kind)?
+            .get("url")?
+            .as_str()
+            .map(String::from)
+    }
+}
+
+impl McRead for GameProfile {
+    type Args = ();
+
+    /// Reads a profile in the same layout as the login success packet.
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        Ok(Self {
+            uuid: Uuid::read_default(&mut reader)?,
+            username: String::read(&mut reader, StringArgs { max_len: Some(16) })?,
+            properties: Vec::read(
+                reader,
+                ListArgs {
+                    length: ListLength::VarInt,
+                    inner: (),
+                },
+            )?,
+        })
+    }
+}
+
+impl McWrite for GameProfile {
+    type Args = ();
+
+    /// Writes the profile in the same layout as the login success packet.
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.uuid.write_default(&mut writer)?;
+        self.username
+            .write(&mut writer, StringArgs { max_len: Some(16) })?;
+        self.properties.write(
+            writer,
+            ListArgs {
+                length: ListLength::VarInt,
+                inner: (),
+            },
+        )?;
+        Ok(())
+    }
+}
+
+/// Returns the UUID of the player with the given username on a server in
+/// offline mode.
+///
+/// This matches the vanilla server, which uses a version 3 UUID of
+/// `OfflinePlayer:<username>` without a namespace.
+pub fn offline_uuid(username: &str) -> Uuid {
+    let hash = Md5::digest(format!("OfflinePlayer:{username}"));
+    Builder::from_md5_bytes(hash.into()).into_uuid()
+}
+
+/// Returns `true` if the given username is 3 to 16 characters long and only
+/// contains ASCII letters, digits, and underscores.
+pub fn is_valid_username(username: &str) -> bool {
+    (3..=16).contains(&username.len())
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::{McRead, McWrite};
+    use minecrevy_protocol::login::Property;
+    use uuid::Uuid;
+
+    use super::{is_valid_username, offline_uuid, GameProfile};
+
+    fn round_trip(profile: &GameProfile) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        profile.write(&mut bytes, ()).unwrap();
+        let mut reader = bytes.as_slice();
+        assert_eq!(&GameProfile::read(&mut reader, ()).unwrap(), profile);
+        assert!(reader.is_empty(), "trailing bytes");
+        bytes
+    }
+
+    #[test]
+    fn round_trip_without_properties() {
+        let profile = GameProfile::offline("Notch");
+        let bytes = round_trip(&profile);
+
+        // UUID, length-prefixed username, and an empty property list.
+        let mut expected = profile.uuid.as_bytes().to_vec();
+        expected.extend([5, b'N', b'o', b't', b'c', b'h', 0]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn round_trip_with_properties() {
+        let mut profile = GameProfile::offline("Notch");
+        profile.properties = vec![
+            Property {
+                name: "textures".to_owned(),
+                value: "e30=".to_owned(),
+                signature: Some("c2ln".to_owned()),
+            },
+            Property {
+                name: "a".to_owned(),
+                value: "b".to_owned(),
+                signature: None,
+            },
+        ];
+        let bytes = round_trip(&profile);
+
+        // The unsigned property ends with a `false` present flag.
+        assert!(bytes.ends_with(&[1, b'a', 1, b'b', 0]));
+    }
+
+    #[test]
+    fn offline_uuid_matches_vanilla() {
+        assert_eq!(
+            offline_uuid("Notch"),
+            Uuid::parse_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap(),
+        );
+    }
+
+    #[test]
+    fn username_validation() {
+        for valid in ["abc", "Notch", "jeb_", "a_b_c_d_e_f_g_hi"] {
+            assert!(is_valid_username(valid), "{valid}");
+        }
+        for invalid in [
+            "",
+            "ab",
+            "has space",
+            "dash-name",
+            "a_b_c_d_e_f_g_hij",
+            "ümlaut",
+        ] {
+            assert!(!is_valid_username(invalid), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! This module contains the [`ResourcePackPlugin`], which sends resource packs
+//! to clients and tracks their responses.
+
+use bevy::{prelude::*, utils::HashMap};
+use minecrevy_net::{client::PacketWriter, packet::Recv};
+use minecrevy_protocol::{
+    play::{AddResourcePack, Disconnect, ResourcePackResponse, ResourcePackStatus},
+    ServerProtocolPlugin,
+};
+use minecrevy_text::Text;
+use uuid::Uuid;
+
+/// [`Plugin`] that sends resource packs to clients when a [`SendResourcePack`]
+/// event is triggered on them, and records their responses in their
+/// [`ResourcePacks`].
+///
+/// Configurable [`Resource`]s:
+/// - [`EnforceResourcePacks`]: Whether clients that don't apply a forced
+///   resource pack are disconnected.
+pub struct ResourcePackPlugin;
+
+impl Plugin for ResourcePackPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<EnforceResourcePacks>();
+
+        app.add_observer(Self::on_send_resource_pack);
+        app.add_observer(Self::on_resource_pack_response);
+    }
+}
+
+impl ResourcePackPlugin {
+    /// [`Observer`] [`System`] that sends a resource pack to a client, and
+    /// starts tracking its response.
+    pub fn on_send_resource_pack(
+        trigger: Trigger<SendResourcePack>,
+        mut writer: PacketWriter,
+        mut packs: Query<&mut ResourcePacks>,
+        mut commands: Commands,
+    ) {
+        let client = trigger.entity();
+        let pack = &trigger.event().0;
+        let Ok(writer) = writer.get_client(client) else {
+            return;
+        };
+
+        writer.send(&AddResourcePack {
+            uuid: pack.uuid,
+            url: pack.url.clone(),
+            hash: pack.hash.clone(),
+            forced: pack.forced,
+            prompt: pack.prompt.clone(),
+        });
+
+        let sent = SentResourcePack {
+            forced: pack.forced,
+            status: None,
+        };
+        match packs.get_mut(client) {
+            Ok(mut packs) => {
+                packs.insert(pack.uuid, sent);
+            }
+            Err(_) => {
+                commands
+                    .entity(client)
+                    .insert(ResourcePacks(HashMap::from_iter([(pack.uuid, sent)])));
+            }
+        }
+    }
+
+    /// [`Observer`] [`System`] that records a client's response to a resource
+    /// pack, disconnecting it if it didn't apply a forced pack.
+    pub fn on_resource_pack_response(
+        trigger: Trigger<Recv<ResourcePackResponse>>,
+        enforce: Res<EnforceResourcePacks>,
+        mut writer: PacketWriter,
+        mut packs: Query<&mut ResourcePacks>,
+        mut commands: Commands,
+    ) {
+        let client = trigger.entity();
+        let packet = &trigger.event().0;
+
+        let Some(pack) = packs
+            .get_mut(client)
+            .ok()
+            .and_then(|packs| packs.into_inner().get_mut(&packet.uuid))
+        else {
+            warn!(
+                "Client {client} responded to unknown resource pack {}",
+                packet.uuid
+            );
+            return;
+        };
+        pack.status = Some(packet.status);
+
+        if pack.forced && packet.status.is_failure() && enforce.0 {
+            writer.client(client).send(&Disconnect {
+                reason: Text::from("You must accept the resource pack to play on this server."),
+            });
+            commands.entity(client).despawn();
+        }
+    }
+}
+
+/// Sends a resource pack to a client, by triggering a [`SendResourcePack`]
+/// event on it.
+pub fn send_resource_pack(commands: &mut Commands, client: Entity, pack: ResourcePack) {
+    commands.trigger_targets(SendResourcePack(pack), client);
+}
+
+/// A resource pack that clients are asked to download and apply.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResourcePack {
+    /// The UUID that identifies the resource pack.
+    pub uuid: Uuid,
+    /// The URL the resource pack is downloaded from.
+    pub url: String,
+    /// The hex-encoded SHA-1 hash of the resource pack, or an empty string to
+    /// skip verifying it.
+    pub hash: String,
+    /// Whether the client must apply the resource pack to stay connected.
+    pub forced: bool,
+    /// The message shown in the prompt to accept the resource pack, if any.
+    pub prompt: Option<Text>,
+}
+
+/// [`Event`] that sends a [`ResourcePack`] to the client it's triggered on.
+#[derive(Event)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct SendResourcePack(pub ResourcePack);
+
+/// [`Component`] that stores the resource packs sent to a client, keyed by
+/// their UUID.
+#[derive(Component, Deref, DerefMut)]
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ResourcePacks(pub HashMap<Uuid, SentResourcePack>);
+
+/// A resource pack sent to a client, and the client's latest response to it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct SentResourcePack {
+    /// Whether the client must apply the resource pack to stay connected.
+    pub forced: bool,
+    /// The latest status the client reported, if any.
+    pub status: Option<ResourcePackStatus>,
+}
+
+/// [`Resource`] that stores whether clients that decline or fail to apply a
+/// forced resource pack are disconnected.
+#[derive(Resource, Deref, DerefMut)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct EnforceResourcePacks(pub bool);
+
+impl Default for EnforceResourcePacks {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_net::{client::ProtocolState, loopback::LoopbackClient, packet::Recv};
+    use minecrevy_protocol::play::{
+        AddResourcePack, Disconnect, ResourcePackResponse, ResourcePackStatus,
+    };
+    use minecrevy_text::Text;
+    use uuid::Uuid;
+
+    use super::{
+        EnforceResourcePacks, ResourcePack, ResourcePackPlugin, ResourcePacks, SendResourcePack,
+        SentResourcePack,
+    };
+    use crate::testing;
+
+    fn pack(forced: bool) -> ResourcePack {
+        ResourcePack {
+            uuid: Uuid::from_u128(1),
+            url: "https://example.com/pack.zip".to_owned(),
+            hash: String::new(),
+            forced,
+            prompt: Some(Text::from("Please accept")),
+        }
+    }
+
+    /// Connects a client and sends it the given pack, checking the packet.
+    fn sent(app: &mut App, pack: ResourcePack) -> (Entity, LoopbackClient) {
+        let (client, mut connection) = testing::connect(app, ProtocolState::Play);
+        app.world_mut()
+            .trigger_targets(SendResourcePack(pack.clone()), client);
+        app.world_mut().flush();
+
+        testing::assert_packet(
+            testing::recv(app, &mut connection),
+            0x46,
+            &AddResourcePack {
+                uuid: pack.uuid,
+                url: pack.url,
+                hash: pack.hash,
+                forced: pack.forced,
+                prompt: pack.prompt,
+            },
+        );
+        (client, connection)
+    }
+
+    fn respond(app: &mut App, client: Entity, status: ResourcePackStatus) {
+        let response = ResourcePackResponse {
+            uuid: Uuid::from_u128(1),
+            status,
+        };
+        app.world_mut().trigger_targets(Recv(response), client);
+        app.world_mut().flush();
+    }
+
+    fn status(app: &App, client: Entity) -> Option<SentResourcePack> {
+        let packs = app.world().get::<ResourcePacks>(client)?;
+        packs.get(&Uuid::from_u128(1)).copied()
+    }
+
+    #[test]
+    fn responses_are_recorded() {
+        let mut app = testing::app();
+        app.add_plugins(ResourcePackPlugin);
+        let (client, _connection) = sent(&mut app, pack(true));
+        assert_eq!(
+            status(&app, client),
+            Some(SentResourcePack {
+                forced: true,
+                status: None,
+            })
+        );
+
+        respond(&mut app, client, ResourcePackStatus::Accepted);
+        respond(&mut app, client, ResourcePackStatus::SuccessfullyLoaded);
+
+        assert_eq!(
+            status(&app, client).unwrap().status,
+            Some(ResourcePackStatus::SuccessfullyLoaded)
+        );
+    }
+
+    #[test]
+    fn declining_a_forced_pack_disconnects() {
+        let mut app = testing::app();
+        app.add_plugins(ResourcePackPlugin);
+        let (client, mut connection) = sent(&mut app, pack(true));
+
+        respond(&mut app, client, ResourcePackStatus::Declined);
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x1D,
+            &Disconnect {
+                reason: Text::from("You must accept the resource pack to play on this server."),
+            },
+        );
+        assert!(app.world().get_entity(client).is_err());
+    }
+
+    #[test]
+    fn declining_an_optional_pack_is_allowed() {
+        let mut app = testing::app();
+        app.add_plugins(ResourcePackPlugin);
+        let (client, _connection) = sent(&mut app, pack(false));
+
+        respond(&mut app, client, ResourcePackStatus::Declined);
+
+        assert_eq!(
+            status(&app, client).unwrap().status,
+            Some(ResourcePackStatus::Declined)
+        );
+    }
+
+    #[test]
+    fn failures_are_allowed_when_not_enforced() {
+        let mut app = testing::app();
+        app.add_plugins(ResourcePackPlugin);
+        app.insert_resource(EnforceResourcePacks(false));
+        let (client, _connection) = sent(&mut app, pack(true));
+
+        respond(&mut app, client, ResourcePackStatus::FailedDownload);
+
+        assert_eq!(
+            status(&app, client).unwrap().status,
+            Some(ResourcePackStatus::FailedDownload)
+        );
+    }
+}
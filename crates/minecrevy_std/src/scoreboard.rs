@@ -0,0 +1,646 @@
+//! This module contains the [`ScoreboardPlugin`], which shows the server's
+//! [`Scoreboard`] to players.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use minecrevy_net::client::{ClientPacketWriter, PacketWriter, ProtocolState};
+use minecrevy_protocol::{
+    play::{
+        DisplayObjective, NumberFormat, ObjectiveAction, ObjectiveDisplay, ResetScore, TeamAction,
+        TeamInfo, TeamUpdate, UpdateObjectives, UpdateScore,
+    },
+    ServerProtocolPlugin,
+};
+use minecrevy_text::Text;
+use minecrevy_util::scoreboard::DisplaySlot;
+
+use crate::play::EnterPlay;
+
+/// [`Plugin`] that sends the [`Scoreboard`] to players when they join, and
+/// broadcasts changes made to it to all players in the play state.
+pub struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<Scoreboard>();
+
+        app.add_systems(PostUpdate, Self::broadcast_changes);
+        app.add_observer(Self::on_enter_play);
+    }
+}
+
+impl ScoreboardPlugin {
+    /// [`System`] that broadcasts the packets for changes made to the
+    /// [`Scoreboard`] since the last update.
+    pub fn broadcast_changes(mut scoreboard: ResMut<Scoreboard>, mut writer: PacketWriter) {
+        if scoreboard.pending.is_empty() {
+            return;
+        }
+
+        for packet in std::mem::take(&mut scoreboard.pending) {
+            packet.broadcast(&mut writer);
+        }
+    }
+
+    /// [`Observer`] [`System`] that sends the whole [`Scoreboard`] to a
+    /// player entering the play state.
+    ///
+    /// Pending changes are broadcast to the other players first, as they're
+    /// already part of what the entering player is sent.
+    pub fn on_enter_play(
+        trigger: Trigger<EnterPlay>,
+        mut scoreboard: ResMut<Scoreboard>,
+        mut writer: PacketWriter,
+    ) {
+        let player = trigger.entity();
+        for packet in std::mem::take(&mut scoreboard.pending) {
+            packet.broadcast_except(&mut writer, player);
+        }
+
+        let Ok(writer) = writer.get_client(player) else {
+            return;
+        };
+
+        for packet in scoreboard.packets() {
+            packet.send(&writer);
+        }
+    }
+}
+
+/// [`Resource`] that stores the server's scoreboard objectives, scores, and
+/// teams, and which objective is shown in each [`DisplaySlot`].
+#[derive(Resource)]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Scoreboard {
+    objectives: HashMap<String, Objective>,
+    slots: HashMap<DisplaySlot, String>,
+    teams: HashMap<String, Team>,
+    /// The packets for changes not yet broadcast to players.
+    pending: Vec<ScoreboardPacket>,
+}
+
+impl Scoreboard {
+    /// Returns the objective with the given name, if any.
+    pub fn objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives.get(name)
+    }
+
+    /// Returns the name of the objective shown in the given slot, if any.
+    pub fn displayed(&self, slot: DisplaySlot) -> Option<&str> {
+        self.slots.get(&slot).map(String::as_str)
+    }
+
+    /// Adds a new objective with the given name and display.
+    ///
+    /// Returns `false` if an objective with the name already exists.
+    pub fn add_objective(&mut self, name: impl Into<String>, display: ObjectiveDisplay) -> bool {
+        let name = name.into();
+        if self.objectives.contains_key(&name) {
+            return false;
+        }
+
+        self.pending
+            .push(ScoreboardPacket::Objective(UpdateObjectives {
+                name: name.clone(),
+                action: ObjectiveAction::Create(display.clone()),
+            }));
+        self.objectives.insert(
+            name,
+            Objective {
+                display,
+                scores: HashMap::default(),
+            },
+        );
+        true
+    }
+
+    /// Changes how the objective with the given name is displayed.
+    ///
+    /// Returns `false` if there's no such objective.
+    pub fn update_objective(&mut self, name: &str, display: ObjectiveDisplay) -> bool {
+        let Some(objective) = self.objectives.get_mut(name) else {
+            return false;
+        };
+
+        self.pending
+            .push(ScoreboardPacket::Objective(UpdateObjectives {
+                name: name.to_owned(),
+                action: ObjectiveAction::Update(display.clone()),
+            }));
+        objective.display = display;
+        true
+    }
+
+    /// Removes the objective with the given name, along with its scores.
+    ///
+    /// Returns `false` if there's no such objective.
+    pub fn remove_objective(&mut self, name: &str) -> bool {
+        if self.objectives.remove(name).is_none() {
+            return false;
+        }
+
+        // Clients clear the slots showing a removed objective by themselves.
+        self.slots.retain(|_, objective| objective != name);
+        self.pending
+            .push(ScoreboardPacket::Objective(UpdateObjectives {
+                name: name.to_owned(),
+                action: ObjectiveAction::Remove,
+            }));
+        true
+    }
+
+    /// Shows the objective with the given name in a slot, or clears the slot
+    /// if [`None`].
+    ///
+    /// Returns `false` if there's no such objective.
+    pub fn display(&mut self, slot: DisplaySlot, objective: Option<&str>) -> bool {
+        match objective {
+            Some(name) => {
+                if !self.objectives.contains_key(name) {
+                    return false;
+                }
+                self.slots.insert(slot, name.to_owned());
+            }
+            None => {
+                self.slots.remove(&slot);
+            }
+        }
+
+        self.pending
+            .push(ScoreboardPacket::Display(DisplayObjective {
+                slot,
+                objective: objective.unwrap_or_default().to_owned(),
+            }));
+        true
+    }
+
+    /// Sets the score of an entity, identified by its username or UUID, for
+    /// the objective with the given name.
+    ///
+    /// Returns `false` if there's no such objective.
+    pub fn set_score(&mut self, objective: &str, entity: impl Into<String>, score: Score) -> bool {
+        let Some(scores) = self.objectives.get_mut(objective).map(|o| &mut o.scores) else {
+            return false;
+        };
+
+        let entity = entity.into();
+        self.pending
+            .push(ScoreboardPacket::Score(score.packet(&entity, objective)));
+        scores.insert(entity, score);
+        true
+    }
+
+    /// Removes the score of an entity for the objective with the given name.
+    ///
+    /// Returns `false` if the entity has no such score.
+    pub fn remove_score(&mut self, objective: &str, entity: &str) -> bool {
+        let Some(scores) = self.objectives.get_mut(objective).map(|o| &mut o.scores) else {
+            return false;
+        };
+        if scores.remove(entity).is_none() {
+            return false;
+        }
+
+        self.pending.push(ScoreboardPacket::Reset(ResetScore {
+            entity_name: entity.to_owned(),
+            objective: Some(objective.to_owned()),
+        }));
+        true
+    }
+
+    /// Returns the team with the given name, if any.
+    pub fn team(&self, name: &str) -> Option<&Team> {
+        self.teams.get(name)
+    }
+
+    /// Returns the name of the team the given entity is a member of, if any.
+    pub fn team_of(&self, entity: &str) -> Option<&str> {
+        self.teams
+            .iter()
+            .find(|(_, team)| team.members.contains(entity))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Adds a new team with the given name, info, and members.
+    ///
+    /// Members are removed from any team they're already on. Returns `false`
+    /// if a team with the name already exists.
+    pub fn add_team(
+        &mut self,
+        name: impl Into<String>,
+        info: TeamInfo,
+        members: impl IntoIterator<Item = String>,
+    ) -> bool {
+        let name = name.into();
+        if self.teams.contains_key(&name) {
+            return false;
+        }
+
+        let members = members.into_iter().collect::<HashSet<_>>();
+        self.leave_teams(&members);
+
+        self.pending.push(ScoreboardPacket::Team(TeamUpdate {
+            name: name.clone(),
+            action: TeamAction::Create {
+                info: info.clone(),
+                entities: members.iter().cloned().collect(),
+            },
+        }));
+        self.teams.insert(name, Team { info, members });
+        true
+    }
+
+    /// Changes the info of the team with the given name, such as its prefix or
+    /// collision rule.
+    ///
+    /// Returns `false` if there's no such team.
+    pub fn update_team(&mut self, name: &str, info: TeamInfo) -> bool {
+        let Some(team) = self.teams.get_mut(name) else {
+            return false;
+        };
+
+        self.pending.push(ScoreboardPacket::Team(TeamUpdate {
+            name: name.to_owned(),
+            action: TeamAction::Update(info.clone()),
+        }));
+        team.info = info;
+        true
+    }
+
+    /// Removes the team with the given name.
+    ///
+    /// Returns `false` if there's no such team.
+    pub fn remove_team(&mut self, name: &str) -> bool {
+        if self.teams.remove(name).is_none() {
+            return false;
+        }
+
+        self.pending.push(ScoreboardPacket::Team(TeamUpdate {
+            name: name.to_owned(),
+            action: TeamAction::Remove,
+        }));
+        true
+    }
+
+    /// Adds entities, identified by their usernames or UUIDs, to the team with
+    /// the given name.
+    ///
+    /// Members are removed from any other team they're on. Returns `false` if
+    /// there's no such team.
+    pub fn add_members(&mut self, name: &str, members: impl IntoIterator<Item = String>) -> bool {
+        if !self.teams.contains_key(name) {
+            return false;
+        }
+
+        let members = members
+            .into_iter()
+            .filter(|member| !self.teams[name].members.contains(member))
+            .collect::<HashSet<_>>();
+        if members.is_empty() {
+            return true;
+        }
+        self.leave_teams(&members);
+
+        self.pending.push(ScoreboardPacket::Team(TeamUpdate {
+            name: name.to_owned(),
+            action: TeamAction::AddEntities(members.iter().cloned().collect()),
+        }));
+        self.teams.get_mut(name).unwrap().members.extend(members);
+        true
+    }
+
+    /// Removes entities, identified by their usernames or UUIDs, from the team
+    /// with the given name.
+    ///
+    /// Returns `false` if there's no such team.
+    pub fn remove_members<'a>(
+        &mut self,
+        name: &str,
+        members: impl IntoIterator<Item = &'a str>,
+    ) -> bool {
+        let Some(team) = self.teams.get_mut(name) else {
+            return false;
+        };
+
+        let removed = members
+            .into_iter()
+            .filter(|member| team.members.remove(*member))
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        if !removed.is_empty() {
+            self.pending.push(ScoreboardPacket::Team(TeamUpdate {
+                name: name.to_owned(),
+                action: TeamAction::RemoveEntities(removed),
+            }));
+        }
+        true
+    }
+
+    /// Removes the given entities from the teams they're on.
+    fn leave_teams(&mut self, members: &HashSet<String>) {
+        for (name, team) in &mut self.teams {
+            let removed = team
+                .members
+                .extract_if(|member| members.contains(member))
+                .collect::<Vec<_>>();
+            if !removed.is_empty() {
+                self.pending.push(ScoreboardPacket::Team(TeamUpdate {
+                    name: name.clone(),
+                    action: TeamAction::RemoveEntities(removed),
+                }));
+            }
+        }
+    }
+
+    /// Returns the packets that show the whole scoreboard to a client that
+    /// hasn't seen it yet.
+    pub fn packets(&self) -> Vec<ScoreboardPacket> {
+        let mut packets = Vec::new();
+        for (name, objective) in &self.objectives {
+            packets.push(ScoreboardPacket::Objective(UpdateObjectives {
+                name: name.clone(),
+                action: ObjectiveAction::Create(objective.display.clone()),
+            }));
+            for (entity, score) in &objective.scores {
+                packets.push(ScoreboardPacket::Score(score.packet(entity, name)));
+            }
+        }
+        for (name, team) in &self.teams {
+            packets.push(ScoreboardPacket::Team(TeamUpdate {
+                name: name.clone(),
+                action: TeamAction::Create {
+                    info: team.info.clone(),
+                    entities: team.members.iter().cloned().collect(),
+                },
+            }));
+        }
+        for (slot, objective) in &self.slots {
+            packets.push(ScoreboardPacket::Display(DisplayObjective {
+                slot: *slot,
+                objective: objective.clone(),
+            }));
+        }
+        packets
+    }
+}
+
+/// A scoreboard objective and the scores entities have for it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Objective {
+    /// How the objective is displayed.
+    pub display: ObjectiveDisplay,
+    /// The scores of entities, keyed by their username or UUID.
+    pub scores: HashMap<String, Score>,
+}
+
+/// The score of an entity for an [`Objective`].
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Score {
+    /// The value of the score.
+    pub value: i32,
+    /// The name shown for the entity, or [`None`] to show its username or UUID.
+    pub display_name: Option<Text>,
+    /// The format of the score, or [`None`] to use the objective's format.
+    pub number_format: Option<NumberFormat>,
+}
+
+impl Score {
+    /// Creates a new [`Score`] with the given value.
+    pub fn new(value: i32) -> Self {
+        Self {
+            value,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the packet that sets this score for the given entity.
+    fn packet(&self, entity: &str, objective: &str) -> UpdateScore {
+        UpdateScore {
+            entity_name: entity.to_owned(),
+            objective: objective.to_owned(),
+            value: self.value,
+            display_name: self.display_name.clone(),
+            number_format: self.number_format.clone(),
+        }
+    }
+}
+
+/// A scoreboard team, which changes how its members' names are shown and how
+/// they interact with each other.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Team {
+    /// The appearance and rules of the team.
+    pub info: TeamInfo,
+    /// The usernames or UUIDs of the team's members.
+    pub members: HashSet<String>,
+}
+
+/// A packet that changes the scoreboard shown to clients.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ScoreboardPacket {
+    /// Creates, updates, or removes an objective.
+    Objective(UpdateObjectives),
+    /// Shows an objective in a display slot.
+    Display(DisplayObjective),
+    /// Sets a score.
+    Score(UpdateScore),
+    /// Removes a score.
+    Reset(ResetScore),
+    /// Creates, updates, or removes a team, or changes its members.
+    Team(TeamUpdate),
+}
+
+impl ScoreboardPacket {
+    /// Sends the packet to a client.
+    pub fn send(&self, writer: &ClientPacketWriter) {
+        match self {
+            Self::Objective(packet) => writer.send(packet),
+            Self::Display(packet) => writer.send(packet),
+            Self::Score(packet) => writer.send(packet),
+            Self::Reset(packet) => writer.send(packet),
+            Self::Team(packet) => writer.send(packet),
+        };
+    }
+
+    /// Sends the packet to all clients in the play state.
+    pub fn broadcast(&self, writer: &mut PacketWriter) {
+        self.broadcast_filtered(writer, |_| true);
+    }
+
+    /// Sends the packet to all clients in the play state, except for the
+    /// given client.
+    pub fn broadcast_except(&self, writer: &mut PacketWriter, except: Entity) {
+        self.broadcast_filtered(writer, |client| client != except);
+    }
+
+    /// Sends the packet to all clients in the play state that match the given
+    /// filter.
+    fn broadcast_filtered(&self, writer: &mut PacketWriter, filter: impl FnMut(Entity) -> bool) {
+        let state = ProtocolState::Play;
+        match self {
+            Self::Objective(packet) => writer.broadcast_filtered(state, packet, filter),
+            Self::Display(packet) => writer.broadcast_filtered(state, packet, filter),
+            Self::Score(packet) => writer.broadcast_filtered(state, packet, filter),
+            Self::Reset(packet) => writer.broadcast_filtered(state, packet, filter),
+            Self::Team(packet) => writer.broadcast_filtered(state, packet, filter),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_protocol::{
+        play::{ObjectiveDisplay, TeamAction, TeamInfo, TeamUpdate},
+        ServerProtocolPlugin,
+    };
+    use minecrevy_text::Text;
+    use minecrevy_util::scoreboard::ObjectiveRenderType;
+
+    use super::{Scoreboard, ScoreboardPacket, ScoreboardPlugin};
+    use crate::play::EnterPlay;
+
+    /// Returns the team packets pending on the scoreboard, and clears them.
+    fn take_team_updates(scoreboard: &mut Scoreboard) -> Vec<TeamUpdate> {
+        std::mem::take(&mut scoreboard.pending)
+            .into_iter()
+            .map(|packet| match packet {
+                ScoreboardPacket::Team(update) => update,
+                packet => panic!("expected a team packet, got {packet:?}"),
+            })
+            .collect()
+    }
+
+    fn team_update(name: &str, action: TeamAction) -> TeamUpdate {
+        TeamUpdate {
+            name: name.to_owned(),
+            action,
+        }
+    }
+
+    #[test]
+    fn adding_a_team_creates_it_with_its_members() {
+        let mut scoreboard = Scoreboard::default();
+        assert!(scoreboard.add_team("red", TeamInfo::default(), ["Notch".to_owned()]));
+
+        assert_eq!(
+            take_team_updates(&mut scoreboard),
+            [team_update(
+                "red",
+                TeamAction::Create {
+                    info: TeamInfo::default(),
+                    entities: vec!["Notch".to_owned()],
+                },
+            )]
+        );
+        assert_eq!(scoreboard.team_of("Notch"), Some("red"));
+
+        // The name is already taken.
+        assert!(!scoreboard.add_team("red", TeamInfo::default(), []));
+        assert!(take_team_updates(&mut scoreboard).is_empty());
+    }
+
+    #[test]
+    fn adding_members_moves_them_from_their_old_team() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.add_team("red", TeamInfo::default(), ["Notch".to_owned()]);
+        scoreboard.add_team("blue", TeamInfo::default(), []);
+        take_team_updates(&mut scoreboard);
+
+        assert!(scoreboard.add_members("blue", ["Notch".to_owned()]));
+
+        assert_eq!(
+            take_team_updates(&mut scoreboard),
+            [
+                team_update("red", TeamAction::RemoveEntities(vec!["Notch".to_owned()])),
+                team_update("blue", TeamAction::AddEntities(vec!["Notch".to_owned()])),
+            ]
+        );
+        assert_eq!(scoreboard.team_of("Notch"), Some("blue"));
+
+        // Members already on the team aren't added again.
+        assert!(scoreboard.add_members("blue", ["Notch".to_owned()]));
+        assert!(take_team_updates(&mut scoreboard).is_empty());
+        assert!(!scoreboard.add_members("green", ["Notch".to_owned()]));
+    }
+
+    #[test]
+    fn removing_members_only_sends_actual_members() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.add_team("red", TeamInfo::default(), ["Notch".to_owned()]);
+        take_team_updates(&mut scoreboard);
+
+        assert!(scoreboard.remove_members("red", ["Notch", "jeb_"]));
+
+        assert_eq!(
+            take_team_updates(&mut scoreboard),
+            [team_update(
+                "red",
+                TeamAction::RemoveEntities(vec!["Notch".to_owned()]),
+            )]
+        );
+        assert_eq!(scoreboard.team_of("Notch"), None);
+
+        assert!(scoreboard.remove_members("red", ["Notch"]));
+        assert!(take_team_updates(&mut scoreboard).is_empty());
+    }
+
+    #[test]
+    fn removing_a_team_sends_its_removal() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.add_team("red", TeamInfo::default(), ["Notch".to_owned()]);
+        take_team_updates(&mut scoreboard);
+
+        assert!(scoreboard.remove_team("red"));
+
+        assert_eq!(
+            take_team_updates(&mut scoreboard),
+            [team_update("red", TeamAction::Remove)]
+        );
+        assert!(scoreboard.team("red").is_none());
+        assert_eq!(scoreboard.team_of("Notch"), None);
+        assert!(!scoreboard.remove_team("red"));
+    }
+
+    #[test]
+    fn entering_play_takes_pending_changes() {
+        let mut app = App::new();
+        app.add_plugins(ServerProtocolPlugin {
+            handshake: false,
+            login: false,
+            play: true,
+            status: false,
+            config: false,
+        });
+        app.add_plugins(ScoreboardPlugin);
+
+        let mut scoreboard = app.world_mut().resource_mut::<Scoreboard>();
+        scoreboard.add_objective(
+            "kills",
+            ObjectiveDisplay {
+                display_name: Text::from("Kills"),
+                render_type: ObjectiveRenderType::Integer,
+                number_format: None,
+            },
+        );
+
+        // The player gets the objective in the snapshot, so the pending
+        // creation mustn't be sent to them again at the end of the frame.
+        let player = app.world_mut().spawn_empty().id();
+        app.world_mut().trigger_targets(EnterPlay, player);
+        app.world_mut().flush();
+
+        let scoreboard = app.world().resource::<Scoreboard>();
+        assert!(scoreboard.pending.is_empty());
+        assert!(scoreboard.objective("kills").is_some());
+    }
+}
@@ -15,6 +15,7 @@ use image::{imageops::FilterType, ImageFormat};
 use minecrevy_net::{client::PacketWriter, packet::Recv};
 use minecrevy_protocol::{
     status::{Ping, Request, Response, ResponsePlayers, ResponseProfile, ResponseVersion},
+    version::ProtocolVersion,
     ServerProtocolPlugin,
 };
 use minecrevy_text::Text;
@@ -22,7 +23,7 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use crate::{
-    handshake::{ClientInfo, HandshakePlugin},
+    handshake::{ClientInfo, HandshakePlugin, SupportedVersions},
     CorePlugin, PlayerCount,
 };
 
@@ -63,6 +64,7 @@ impl Plugin for StatusPlugin {
         );
 
         app.init_resource::<ServerProtocol>();
+        app.init_resource::<ServerProtocolName>();
         app.init_resource::<Motd>();
         app.init_resource::<PlayerSample>();
         app.init_resource::<ServerListFavicon>();
@@ -99,6 +101,7 @@ impl StatusPlugin {
         counts: Res<PlayerCount>,
         version_name: Res<ServerProtocolName>,
         version: Res<ServerProtocol>,
+        supported: Res<SupportedVersions>,
         motd: Res<Motd>,
         sample: Res<PlayerSample>,
         favicon: Res<ServerListFavicon>,
@@ -113,13 +116,13 @@ impl StatusPlugin {
             .and_then(|handle| favicons.get(handle))
             .map(|f| f.base64.clone());
 
-        let version = match *version {
-            ServerProtocol::Echo => client_info
+        let version = version.resolve(
+            client_info
                 .get(trigger.entity())
                 .map(|i| i.protocol_version)
                 .unwrap_or(0),
-            ServerProtocol::Version(v) => v,
-        };
+            &supported,
+        );
 
         writer.send(&Response {
             version: ResponseVersion {
@@ -160,11 +163,52 @@ impl StatusPlugin {
 #[derive(Resource)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum ServerProtocol {
-    /// The client's protocol version wlll be sent back to them.
+    /// The client's protocol version will be sent back to them.
     #[default]
     Echo,
+    /// The client's protocol version will be sent back to them if they can log
+    /// in with it, as decided by the [`SupportedVersions`], otherwise the
+    /// newest supported version is sent, so that the client shows that it's
+    /// incompatible with the server.
+    Supported,
     /// This specific protocol version will be sent to clients.
     Version(i32),
+    /// The client's protocol version will be sent back to them if it's within
+    /// `min..=max`, otherwise the `fallback` version is sent, so that the
+    /// client shows that it's incompatible with the server.
+    Range {
+        /// The minimum supported protocol version.
+        min: i32,
+        /// The maximum supported protocol version.
+        max: i32,
+        /// The protocol version sent to clients outside of the supported range.
+        fallback: i32,
+    },
+}
+
+impl ServerProtocol {
+    /// Returns the protocol version to send to a client with the given
+    /// protocol version.
+    pub fn resolve(self, client_version: i32, supported: &SupportedVersions) -> i32 {
+        match self {
+            Self::Supported => {
+                if supported.contains(ProtocolVersion(client_version)) {
+                    client_version
+                } else {
+                    supported.0.end().0
+                }
+            }
+            Self::Echo => client_version,
+            Self::Version(v) => v,
+            Self::Range { min, max, fallback } => {
+                if (min..=max).contains(&client_version) {
+                    client_version
+                } else {
+                    fallback
+                }
+            }
+        }
+    }
 }
 
 /// [`Resource`] that stores the name of the protocol version to send to clients.
@@ -274,3 +318,131 @@ impl AssetLoader for FaviconLoader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_io::{packet::RawPacket, McRead};
+    use minecrevy_net::{client::ProtocolState, loopback::LoopbackClient};
+    use minecrevy_protocol::{
+        status::{Ping, Response},
+        version::ProtocolVersion,
+    };
+    use minecrevy_text::Text;
+
+    use super::{Motd, ServerProtocol, StatusPlugin};
+    use crate::{
+        handshake::{HandshakePlugin, SupportedVersions},
+        testing,
+    };
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins((HandshakePlugin, StatusPlugin::default()));
+        app.insert_resource(Motd(Text::from("Hello from Minecrevy")));
+        app
+    }
+
+    /// Connects a client and sends a handshake with the given protocol
+    /// version and a status request.
+    fn request_status(app: &mut App, protocol_version: i32) -> LoopbackClient {
+        let (_, mut connection) = testing::connect(app, ProtocolState::Handshake);
+        testing::handshake(&mut connection, protocol_version, 1);
+        // The status request has no body.
+        connection
+            .send_raw(RawPacket {
+                id: 0x00,
+                body: Vec::new(),
+            })
+            .unwrap();
+        connection
+    }
+
+    /// Receives the Status Response sent to the client.
+    fn recv_response(app: &mut App, connection: &mut LoopbackClient) -> Response {
+        let packet = testing::recv(app, connection);
+        assert_eq!(packet.id, 0x00);
+        Response::read_default(packet.reader()).unwrap()
+    }
+
+    #[test]
+    fn status_flow_returns_motd_and_echoes_ping() {
+        let mut app = app();
+        let mut connection = request_status(&mut app, 767);
+
+        let response = recv_response(&mut app, &mut connection);
+        assert_eq!(response.description, Text::from("Hello from Minecrevy"));
+        assert_eq!(response.version.protocol, 767);
+        assert_eq!(response.players.max, 20);
+        assert_eq!(response.players.online, 0);
+
+        connection.send(0x01, &Ping(1234)).unwrap();
+        testing::assert_packet(testing::recv(&mut app, &mut connection), 0x01, &Ping(1234));
+    }
+
+    #[test]
+    fn unsupported_client_is_sent_the_supported_version() {
+        let mut app = app();
+        app.insert_resource(ServerProtocol::Supported);
+        let mut connection = request_status(&mut app, 760);
+
+        let response = recv_response(&mut app, &mut connection);
+        assert_eq!(response.version.protocol, 767);
+    }
+
+    #[test]
+    fn default_protocol_echoes_the_client() {
+        let mut app = app();
+        let mut connection = request_status(&mut app, 760);
+
+        let response = recv_response(&mut app, &mut connection);
+        assert_eq!(response.version.protocol, 760);
+    }
+
+    #[test]
+    fn supported_protocol_follows_supported_versions() {
+        let supported = SupportedVersions(ProtocolVersion::V1_20_5..=ProtocolVersion::V1_21);
+
+        assert_eq!(ServerProtocol::Supported.resolve(766, &supported), 766);
+        assert_eq!(ServerProtocol::Supported.resolve(767, &supported), 767);
+        assert_eq!(ServerProtocol::Supported.resolve(765, &supported), 767);
+        assert_eq!(ServerProtocol::Supported.resolve(768, &supported), 767);
+        assert_eq!(ServerProtocol::Echo.resolve(765, &supported), 765);
+    }
+
+    #[test]
+    fn range_protocol_echoes_clients_within_it() {
+        let supported = SupportedVersions::default();
+        let range = ServerProtocol::Range {
+            min: 765,
+            max: 767,
+            fallback: 767,
+        };
+
+        // In range, including both bounds.
+        assert_eq!(range.resolve(765, &supported), 765);
+        assert_eq!(range.resolve(766, &supported), 766);
+        assert_eq!(range.resolve(767, &supported), 767);
+        // Below and above the range.
+        assert_eq!(range.resolve(764, &supported), 767);
+        assert_eq!(range.resolve(768, &supported), 767);
+    }
+
+    #[test]
+    fn out_of_range_client_is_sent_the_fallback() {
+        let mut app = app();
+        app.insert_resource(ServerProtocol::Range {
+            min: 766,
+            max: 767,
+            fallback: 767,
+        });
+
+        let mut outdated = request_status(&mut app, 760);
+        let response = recv_response(&mut app, &mut outdated);
+        assert_eq!(response.version.protocol, 767);
+
+        let mut supported = request_status(&mut app, 766);
+        let response = recv_response(&mut app, &mut supported);
+        assert_eq!(response.version.protocol, 766);
+    }
+}
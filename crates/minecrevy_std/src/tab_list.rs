@@ -0,0 +1,381 @@
+//! This module contains the [`TabListPlugin`], which shows the server's
+//! [`TabList`] to players.
+
+use bevy::{prelude::*, utils::HashMap};
+use minecrevy_net::client::{ClientPacketWriter, PacketWriter, ProtocolState};
+use minecrevy_protocol::{
+    play::{TabListAddPlayer, TabListEntry, TabListHeaderAndFooter, TabListRemove, TabListUpdate},
+    ServerProtocolPlugin,
+};
+use minecrevy_text::Text;
+use minecrevy_util::game_mode::GameMode;
+use uuid::Uuid;
+
+use crate::{play::EnterPlay, profile::GameProfile};
+
+/// [`Plugin`] that sends the [`TabList`] to players when they join, and
+/// broadcasts changes made to it to all players in the play state.
+pub struct TabListPlugin;
+
+impl Plugin for TabListPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<TabList>();
+
+        app.add_systems(PostUpdate, Self::broadcast_changes);
+        app.add_observer(Self::on_enter_play);
+    }
+}
+
+impl TabListPlugin {
+    /// [`System`] that broadcasts the packets for changes made to the
+    /// [`TabList`] since the last update.
+    pub fn broadcast_changes(mut tab_list: ResMut<TabList>, mut writer: PacketWriter) {
+        if tab_list.pending.is_empty() {
+            return;
+        }
+
+        for packet in std::mem::take(&mut tab_list.pending) {
+            packet.broadcast(&mut writer);
+        }
+    }
+
+    /// [`Observer`] [`System`] that sends the whole [`TabList`] to a player
+    /// entering the play state.
+    pub fn on_enter_play(
+        trigger: Trigger<EnterPlay>,
+        tab_list: Res<TabList>,
+        mut writer: PacketWriter,
+    ) {
+        let Ok(writer) = writer.get_client(trigger.entity()) else {
+            return;
+        };
+
+        for packet in tab_list.packets() {
+            packet.send(&writer);
+        }
+    }
+}
+
+/// [`Resource`] that stores the players shown in the tab list, and the text
+/// shown above and below it.
+#[derive(Resource)]
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TabList {
+    players: HashMap<Uuid, TabListPlayer>,
+    header: Option<Text>,
+    footer: Option<Text>,
+    /// The packets for changes not yet broadcast to players.
+    pending: Vec<TabListPacket>,
+}
+
+impl TabList {
+    /// Returns the tab list entry of the player with the given UUID, if any.
+    pub fn player(&self, uuid: Uuid) -> Option<&TabListPlayer> {
+        self.players.get(&uuid)
+    }
+
+    /// Returns the text shown above the tab list, if any.
+    pub fn header(&self) -> Option<&Text> {
+        self.header.as_ref()
+    }
+
+    /// Returns the text shown below the tab list, if any.
+    pub fn footer(&self) -> Option<&Text> {
+        self.footer.as_ref()
+    }
+
+    /// Adds a player to the tab list, replacing any entry with the same UUID.
+    pub fn add(&mut self, player: TabListPlayer) {
+        self.pending.push(TabListPacket::Update(TabListUpdate {
+            entries: vec![player.entry()],
+        }));
+        self.players.insert(player.profile.uuid, player);
+    }
+
+    /// Sets the latency shown for a player, in milliseconds.
+    ///
+    /// Returns `false` if the player isn't in the tab list.
+    pub fn set_latency(&mut self, uuid: Uuid, latency: i32) -> bool {
+        let Some(player) = self.players.get_mut(&uuid) else {
+            return false;
+        };
+
+        player.latency = latency;
+        self.pending.push(TabListPacket::Update(TabListUpdate {
+            entries: vec![TabListEntry {
+                latency: Some(latency),
+                ..TabListEntry::new(uuid)
+            }],
+        }));
+        true
+    }
+
+    /// Sets the game mode shown for a player.
+    ///
+    /// Returns `false` if the player isn't in the tab list.
+    pub fn set_game_mode(&mut self, uuid: Uuid, game_mode: GameMode) -> bool {
+        let Some(player) = self.players.get_mut(&uuid) else {
+            return false;
+        };
+
+        player.game_mode = game_mode;
+        self.pending.push(TabListPacket::Update(TabListUpdate {
+            entries: vec![TabListEntry {
+                game_mode: Some(game_mode),
+                ..TabListEntry::new(uuid)
+            }],
+        }));
+        true
+    }
+
+    /// Sets the name shown for a player, or shows their username if [`None`].
+    /// Nothing is sent if the name renders the same as the current one.
+    ///
+    /// Returns `false` if the player isn't in the tab list.
+    pub fn set_display_name(&mut self, uuid: Uuid, display_name: Option<Text>) -> bool {
+        let Some(player) = self.players.get_mut(&uuid) else {
+            return false;
+        };
+
+        let unchanged = match (&player.display_name, &display_name) {
+            (Some(current), Some(new)) => current.semantically_eq(new),
+            (current, new) => current.is_none() && new.is_none(),
+        };
+        if unchanged {
+            return true;
+        }
+
+        player.display_name.clone_from(&display_name);
+        self.pending.push(TabListPacket::Update(TabListUpdate {
+            entries: vec![TabListEntry {
+                display_name: Some(display_name),
+                ..TabListEntry::new(uuid)
+            }],
+        }));
+        true
+    }
+
+    /// Removes a player from the tab list.
+    ///
+    /// Returns `false` if the player isn't in the tab list.
+    pub fn remove(&mut self, uuid: Uuid) -> bool {
+        if self.players.remove(&uuid).is_none() {
+            return false;
+        }
+
+        self.pending
+            .push(TabListPacket::Remove(TabListRemove { uuids: vec![uuid] }));
+        true
+    }
+
+    /// Sets the text shown above and below the tab list, or hides it if
+    /// [`None`].
+    pub fn set_header_and_footer(&mut self, header: Option<Text>, footer: Option<Text>) {
+        self.header = header;
+        self.footer = footer;
+        self.pending.push(TabListPacket::HeaderAndFooter(Box::new(
+            self.header_and_footer(),
+        )));
+    }
+
+    /// Returns the packets that show the whole tab list to a client that
+    /// hasn't seen it yet.
+    pub fn packets(&self) -> Vec<TabListPacket> {
+        let mut packets = Vec::new();
+        if !self.players.is_empty() {
+            packets.push(TabListPacket::Update(TabListUpdate {
+                entries: self.players.values().map(TabListPlayer::entry).collect(),
+            }));
+        }
+        if self.header.is_some() || self.footer.is_some() {
+            packets.push(TabListPacket::HeaderAndFooter(Box::new(
+                self.header_and_footer(),
+            )));
+        }
+        packets
+    }
+
+    /// Returns the packet that sets the header and footer.
+    fn header_and_footer(&self) -> TabListHeaderAndFooter {
+        // Clients hide the header and footer when they're empty.
+        TabListHeaderAndFooter {
+            header: self.header.clone().unwrap_or_else(|| Text::from("")),
+            footer: self.footer.clone().unwrap_or_else(|| Text::from("")),
+        }
+    }
+}
+
+/// A player's entry in the [`TabList`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabListPlayer {
+    /// The profile of the player, which provides their username and skin.
+    pub profile: GameProfile,
+    /// The game mode shown for the player.
+    pub game_mode: GameMode,
+    /// The latency shown for the player, in milliseconds.
+    pub latency: i32,
+    /// The name shown for the player, or [`None`] to show their username.
+    pub display_name: Option<Text>,
+}
+
+impl TabListPlayer {
+    /// Creates a new [`TabListPlayer`] for the given profile.
+    pub fn new(profile: GameProfile, game_mode: GameMode) -> Self {
+        Self {
+            profile,
+            game_mode,
+            latency: 0,
+            display_name: None,
+        }
+    }
+
+    /// Returns the entry that adds this player to the tab list.
+    fn entry(&self) -> TabListEntry {
+        TabListEntry {
+            add_player: Some(TabListAddPlayer {
+                username: self.profile.username.clone(),
+                properties: self.profile.properties.clone(),
+            }),
+            game_mode: Some(self.game_mode),
+            listed: Some(true),
+            latency: Some(self.latency),
+            display_name: Some(self.display_name.clone()),
+            ..TabListEntry::new(self.profile.uuid)
+        }
+    }
+}
+
+/// A packet that changes the tab list shown to clients.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TabListPacket {
+    /// Adds or updates players.
+    Update(TabListUpdate),
+    /// Removes players.
+    Remove(TabListRemove),
+    /// Sets the header and footer.
+    HeaderAndFooter(Box<TabListHeaderAndFooter>),
+}
+
+impl TabListPacket {
+    /// Sends the packet to a client.
+    pub fn send(&self, writer: &ClientPacketWriter) {
+        match self {
+            Self::Update(packet) => writer.send(packet),
+            Self::Remove(packet) => writer.send(packet),
+            Self::HeaderAndFooter(packet) => writer.send(&**packet),
+        };
+    }
+
+    /// Sends the packet to all clients in the play state.
+    pub fn broadcast(&self, writer: &mut PacketWriter) {
+        match self {
+            Self::Update(packet) => writer.broadcast(ProtocolState::Play, packet),
+            Self::Remove(packet) => writer.broadcast(ProtocolState::Play, packet),
+            Self::HeaderAndFooter(packet) => writer.broadcast(ProtocolState::Play, &**packet),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use minecrevy_net::client::ProtocolState;
+    use minecrevy_protocol::play::{TabListHeaderAndFooter, TabListRemove};
+    use minecrevy_text::Text;
+    use minecrevy_util::game_mode::GameMode;
+
+    use super::{TabList, TabListPlayer, TabListPlugin};
+    use crate::{play::EnterPlay, profile::GameProfile, testing};
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins(TabListPlugin);
+        app
+    }
+
+    fn header_and_footer(header: &str, footer: &str) -> TabListHeaderAndFooter {
+        TabListHeaderAndFooter {
+            header: Text::from(header),
+            footer: Text::from(footer),
+        }
+    }
+
+    #[test]
+    fn header_and_footer_are_broadcast() {
+        let mut app = app();
+        let (_, mut first) = testing::connect(&mut app, ProtocolState::Play);
+        let (_, mut second) = testing::connect(&mut app, ProtocolState::Play);
+
+        app.world_mut()
+            .resource_mut::<TabList>()
+            .set_header_and_footer(Some(Text::from("Welcome")), Some(Text::from("Have fun")));
+
+        let expected = header_and_footer("Welcome", "Have fun");
+        for connection in [&mut first, &mut second] {
+            testing::assert_packet(testing::recv(&mut app, connection), 0x6D, &expected);
+        }
+    }
+
+    #[test]
+    fn missing_header_is_sent_empty() {
+        let mut app = app();
+        let (_, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        app.world_mut()
+            .resource_mut::<TabList>()
+            .set_header_and_footer(None, Some(Text::from("Have fun")));
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x6D,
+            &header_and_footer("", "Have fun"),
+        );
+    }
+
+    #[test]
+    fn joining_player_is_sent_the_header_and_footer() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<TabList>()
+            .set_header_and_footer(Some(Text::from("Welcome")), None);
+        app.update();
+
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+        app.world_mut().trigger_targets(EnterPlay, client);
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x6D,
+            &header_and_footer("Welcome", ""),
+        );
+    }
+
+    #[test]
+    fn removed_player_is_broadcast() {
+        let mut app = app();
+        let profile = GameProfile::offline("Notch");
+        let uuid = profile.uuid;
+        app.world_mut()
+            .resource_mut::<TabList>()
+            .add(TabListPlayer::new(profile, GameMode::Survival));
+        app.update();
+        let (_, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        let mut tab_list = app.world_mut().resource_mut::<TabList>();
+        assert!(tab_list.remove(uuid));
+        assert!(!tab_list.remove(uuid));
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x3D,
+            &TabListRemove { uuids: vec![uuid] },
+        );
+    }
+}
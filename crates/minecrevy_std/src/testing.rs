@@ -0,0 +1,108 @@
+//! Helpers for testing plugins against in-process loopback clients.
+
+use std::{io, time::Duration};
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use minecrevy_io::{ext::WriteMinecraftExt, packet::RawPacket, McWrite};
+use minecrevy_net::{
+    client::{ClientAddressIndex, ClientPacketWriter, PacketWriter, ProtocolState},
+    loopback::LoopbackClient,
+    server::Server,
+    NetworkServerPlugins,
+};
+use minecrevy_protocol::{version::VersionedPackets, ServerProtocolPlugin};
+
+use crate::CorePlugin;
+
+/// Returns an [`App`] with the network server, every protocol packet, and the
+/// [`CorePlugin`], to add the plugins under test to.
+pub(crate) fn app() -> App {
+    versioned_app(VersionedPackets::default())
+}
+
+/// Returns an [`App`] like [`app`], with the given [`VersionedPackets`] for
+/// the packets whose IDs differ between versions.
+pub(crate) fn versioned_app(versioned: VersionedPackets) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+    app.add_plugins(NetworkServerPlugins);
+    app.insert_resource(versioned);
+    app.add_plugins(ServerProtocolPlugin {
+        handshake: true,
+        login: true,
+        play: true,
+        status: true,
+        config: true,
+    });
+    app.add_plugins(CorePlugin);
+    app
+}
+
+/// Connects a loopback client and moves it straight to the given
+/// [`ProtocolState`], returning its entity.
+///
+/// The [`LoopbackClient`] must be kept alive, so that the server doesn't
+/// despawn the client for closing the connection.
+pub(crate) fn connect(app: &mut App, state: ProtocolState) -> (Entity, LoopbackClient) {
+    let connection = app.world().resource::<Server>().connect_loopback();
+    app.update();
+    let client = app
+        .world()
+        .resource::<ClientAddressIndex>()
+        .entity(connection.addr())
+        .unwrap();
+    app.world_mut()
+        .run_system_once(move |mut writer: PacketWriter| {
+            writer.client(client).set_state(state);
+        })
+        .unwrap();
+    (client, connection)
+}
+
+/// Updates the app until the client receives a packet, and returns it.
+pub(crate) fn recv(app: &mut App, connection: &mut LoopbackClient) -> RawPacket {
+    connection.set_timeout(Duration::from_millis(10));
+    for _ in 0..500 {
+        app.update();
+        match connection.recv_raw() {
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => continue,
+            result => return result.unwrap(),
+        }
+    }
+    panic!("timed out waiting for a packet");
+}
+
+/// Sends a Handshake to `localhost:25565` with the given protocol version and
+/// next state, `1` for status and `2` for login.
+pub(crate) fn handshake(connection: &mut LoopbackClient, protocol_version: i32, next_state: i32) {
+    let mut body = Vec::new();
+    body.write_var_i32(protocol_version).unwrap();
+    body.write_string("localhost").unwrap();
+    body.write_u16(25565).unwrap();
+    body.write_var_i32(next_state).unwrap();
+    connection.send_raw(RawPacket { id: 0x00, body }).unwrap();
+}
+
+/// Runs the given function with a [`ClientPacketWriter`] for the client.
+pub(crate) fn write(
+    app: &mut App,
+    client: Entity,
+    write: impl FnOnce(&ClientPacketWriter) + Send + Sync + 'static,
+) {
+    let mut write = Some(write);
+    app.world_mut()
+        .run_system_once(move |mut writer: PacketWriter| {
+            let write = write.take().unwrap();
+            write(&writer.client(client));
+        })
+        .unwrap();
+}
+
+/// Asserts that the given packet has the ID and encoding of `expected`.
+#[track_caller]
+pub(crate) fn assert_packet(packet: RawPacket, id: i32, expected: &impl McWrite<Args = ()>) {
+    let mut body = Vec::new();
+    expected.write_default(&mut body).unwrap();
+    assert_eq!(packet.id, id, "unexpected packet ID");
+    assert_eq!(packet.body, body, "unexpected body of packet {id:#04x}");
+}
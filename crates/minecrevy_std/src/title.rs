@@ -0,0 +1,172 @@
+//! This module contains [`Title`], which shows a title and subtitle in the
+//! middle of a player's screen.
+
+use minecrevy_net::client::ClientPacketWriter;
+use minecrevy_protocol::play::{ClearTitles, SetActionBar, SetSubtitle, SetTitle, TitleTimes};
+use minecrevy_text::Text;
+
+/// A title shown in the middle of a player's screen, with an optional subtitle
+/// below it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Title {
+    /// The title.
+    pub title: Text,
+    /// The subtitle, if any.
+    pub subtitle: Option<Text>,
+    /// How long the title fades in, stays, and fades out for.
+    pub times: TitleTimes,
+}
+
+impl Title {
+    /// Creates a new [`Title`] with no subtitle, shown for the default times.
+    pub fn new(title: impl Into<Text>) -> Self {
+        Self {
+            title: title.into(),
+            subtitle: None,
+            times: TitleTimes::default(),
+        }
+    }
+
+    /// Sets the subtitle shown below the title.
+    pub fn with_subtitle(mut self, subtitle: impl Into<Text>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Sets how long the title fades in, stays, and fades out for.
+    pub fn with_times(mut self, times: TitleTimes) -> Self {
+        self.times = times;
+        self
+    }
+
+    /// Shows the title to a client.
+    ///
+    /// The times and subtitle are sent before the title, since the client
+    /// displays the title as soon as it's received. Without a subtitle, the
+    /// client's previous subtitle is cleared.
+    pub fn send(&self, writer: &ClientPacketWriter) {
+        if self.subtitle.is_none() {
+            writer.send(&ClearTitles { reset: true });
+        }
+        writer.send(&self.times);
+        if let Some(subtitle) = &self.subtitle {
+            writer.send(&SetSubtitle(subtitle.clone()));
+        }
+        writer.send(&SetTitle(self.title.clone()));
+    }
+
+    /// Hides the title currently shown to a client.
+    pub fn clear(writer: &ClientPacketWriter) {
+        writer.send(&ClearTitles { reset: false });
+    }
+
+    /// Hides the title currently shown to a client, and resets its subtitle
+    /// and times to their defaults.
+    pub fn reset(writer: &ClientPacketWriter) {
+        writer.send(&ClearTitles { reset: true });
+    }
+}
+
+/// Shows a message above a client's hotbar.
+pub fn send_action_bar(writer: &ClientPacketWriter, message: impl Into<Text>) {
+    writer.send(&SetActionBar(message.into()));
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_net::client::ProtocolState;
+    use minecrevy_protocol::play::{ClearTitles, SetActionBar, SetSubtitle, SetTitle, TitleTimes};
+    use minecrevy_text::Text;
+    use minecrevy_util::ticks::Ticks;
+
+    use super::{send_action_bar, Title};
+    use crate::testing;
+
+    #[test]
+    fn title_with_subtitle() {
+        let mut app = testing::app();
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+        let times = TitleTimes {
+            fade_in: Ticks(5),
+            stay: Ticks(40),
+            fade_out: Ticks(5),
+        };
+        let title = Title::new("Hello").with_subtitle("world").with_times(times);
+
+        testing::write(&mut app, client, move |writer| title.send(writer));
+
+        testing::assert_packet(testing::recv(&mut app, &mut connection), 0x66, &times);
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x63,
+            &SetSubtitle(Text::from("world")),
+        );
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x65,
+            &SetTitle(Text::from("Hello")),
+        );
+    }
+
+    #[test]
+    fn title_without_subtitle_resets_the_previous_one() {
+        let mut app = testing::app();
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        testing::write(&mut app, client, |writer| Title::new("Hello").send(writer));
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x0F,
+            &ClearTitles { reset: true },
+        );
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x66,
+            &TitleTimes::default(),
+        );
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x65,
+            &SetTitle(Text::from("Hello")),
+        );
+    }
+
+    #[test]
+    fn clear_and_reset() {
+        let mut app = testing::app();
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        testing::write(&mut app, client, |writer| {
+            Title::clear(writer);
+            Title::reset(writer);
+        });
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x0F,
+            &ClearTitles { reset: false },
+        );
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x0F,
+            &ClearTitles { reset: true },
+        );
+    }
+
+    #[test]
+    fn action_bar() {
+        let mut app = testing::app();
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        testing::write(&mut app, client, |writer| {
+            send_action_bar(writer, "Low health");
+        });
+
+        testing::assert_packet(
+            testing::recv(&mut app, &mut connection),
+            0x4C,
+            &SetActionBar(Text::from("Low health")),
+        );
+    }
+}
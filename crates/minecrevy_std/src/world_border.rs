@@ -0,0 +1,398 @@
+//! This module contains the [`WorldBorderPlugin`], which shows the server's
+//! [`WorldBorder`] to players.
+
+use std::time::Duration;
+
+use bevy::{math::DVec2, prelude::*};
+use minecrevy_net::client::{ClientPacketWriter, PacketWriter, ProtocolState};
+use minecrevy_protocol::{
+    play::{
+        CreateWorldBorder, WorldBorderCenter, WorldBorderLerpSize, WorldBorderSize,
+        WorldBorderWarningDelay, WorldBorderWarningReach,
+    },
+    ServerProtocolPlugin,
+};
+
+use crate::play::EnterPlay;
+
+/// [`Plugin`] that sends the [`WorldBorder`] to players when they join, and
+/// broadcasts changes made to it to all players in the play state.
+pub struct WorldBorderPlugin;
+
+impl Plugin for WorldBorderPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            app.is_plugin_added::<ServerProtocolPlugin>(),
+            "{} must be added before {}",
+            std::any::type_name::<ServerProtocolPlugin>(),
+            std::any::type_name::<Self>(),
+        );
+
+        app.init_resource::<WorldBorder>();
+
+        app.add_systems(Update, Self::advance_lerp);
+        app.add_systems(PostUpdate, Self::broadcast_changes);
+        app.add_observer(Self::on_enter_play);
+    }
+}
+
+impl WorldBorderPlugin {
+    /// [`System`] that advances the [`WorldBorder`] towards the diameter it's
+    /// moving to, if any.
+    pub fn advance_lerp(time: Res<Time<Real>>, mut border: ResMut<WorldBorder>) {
+        if border.lerp.is_none() {
+            return;
+        }
+        border.advance(time.delta());
+    }
+
+    /// [`System`] that broadcasts the packets for changes made to the
+    /// [`WorldBorder`] since the last update.
+    pub fn broadcast_changes(mut border: ResMut<WorldBorder>, mut writer: PacketWriter) {
+        if border.pending.is_empty() {
+            return;
+        }
+
+        for packet in std::mem::take(&mut border.pending) {
+            packet.broadcast(&mut writer);
+        }
+    }
+
+    /// [`Observer`] [`System`] that sends the [`WorldBorder`] to a player
+    /// entering the play state.
+    pub fn on_enter_play(
+        trigger: Trigger<EnterPlay>,
+        border: Res<WorldBorder>,
+        mut writer: PacketWriter,
+    ) {
+        let Ok(writer) = writer.get_client(trigger.entity()) else {
+            return;
+        };
+
+        writer.send(&border.initial_packet());
+    }
+}
+
+/// [`Resource`] that stores the world border, which limits how far from its
+/// center players can go.
+#[derive(Resource)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct WorldBorder {
+    center: DVec2,
+    diameter: f64,
+    lerp: Option<BorderLerp>,
+    warning_blocks: i32,
+    warning_time: i32,
+    portal_teleport_boundary: i32,
+    /// The packets for changes not yet broadcast to players.
+    pending: Vec<WorldBorderPacket>,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center: DVec2::ZERO,
+            diameter: Self::MAX_DIAMETER,
+            lerp: None,
+            warning_blocks: 5,
+            warning_time: 15,
+            portal_teleport_boundary: 29999984,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl WorldBorder {
+    /// The largest diameter of the world border, as used by vanilla worlds.
+    pub const MAX_DIAMETER: f64 = 59999968.;
+
+    /// Returns the center of the border, as X and Z coordinates.
+    pub fn center(&self) -> DVec2 {
+        self.center
+    }
+
+    /// Returns the current diameter of the border, which changes over time
+    /// while the border is moving.
+    pub fn diameter(&self) -> f64 {
+        match &self.lerp {
+            Some(lerp) => lerp.diameter(),
+            None => self.diameter,
+        }
+    }
+
+    /// Returns the diameter the border is moving towards, or its current
+    /// diameter if it's not moving.
+    pub fn target_diameter(&self) -> f64 {
+        match &self.lerp {
+            Some(lerp) => lerp.to,
+            None => self.diameter,
+        }
+    }
+
+    /// Returns how far from the border players see its warning, in blocks.
+    pub fn warning_blocks(&self) -> i32 {
+        self.warning_blocks
+    }
+
+    /// Returns how many seconds before reaching a player a moving border
+    /// shows its warning.
+    pub fn warning_time(&self) -> i32 {
+        self.warning_time
+    }
+
+    /// Moves the center of the border.
+    pub fn set_center(&mut self, center: DVec2) {
+        if center == self.center {
+            return;
+        }
+
+        self.center = center;
+        self.pending
+            .push(WorldBorderPacket::Center(WorldBorderCenter {
+                x: center.x,
+                z: center.y,
+            }));
+    }
+
+    /// Immediately resizes the border, stopping it if it's moving.
+    pub fn set_size(&mut self, diameter: f64) {
+        if self.lerp.is_none() && diameter == self.diameter {
+            return;
+        }
+
+        self.lerp = None;
+        self.diameter = diameter;
+        self.pending
+            .push(WorldBorderPacket::Size(WorldBorderSize { diameter }));
+    }
+
+    /// Gradually resizes the border from its current diameter to the given
+    /// one over the given duration.
+    pub fn lerp_to(&mut self, diameter: f64, duration: Duration) {
+        if duration.is_zero() {
+            self.set_size(diameter);
+            return;
+        }
+
+        let from = self.diameter();
+        self.diameter = from;
+        self.lerp = Some(BorderLerp {
+            from,
+            to: diameter,
+            duration,
+            elapsed: Duration::ZERO,
+        });
+        self.pending
+            .push(WorldBorderPacket::LerpSize(WorldBorderLerpSize {
+                old_diameter: from,
+                new_diameter: diameter,
+                speed: duration.as_millis() as i64,
+            }));
+    }
+
+    /// Sets how far from the border players see its warning, in blocks.
+    pub fn set_warning_blocks(&mut self, blocks: i32) {
+        if blocks == self.warning_blocks {
+            return;
+        }
+
+        self.warning_blocks = blocks;
+        self.pending
+            .push(WorldBorderPacket::WarningReach(WorldBorderWarningReach(
+                blocks,
+            )));
+    }
+
+    /// Sets how many seconds before reaching a player a moving border shows
+    /// its warning.
+    pub fn set_warning_time(&mut self, seconds: i32) {
+        if seconds == self.warning_time {
+            return;
+        }
+
+        self.warning_time = seconds;
+        self.pending
+            .push(WorldBorderPacket::WarningDelay(WorldBorderWarningDelay(
+                seconds,
+            )));
+    }
+
+    /// Returns the packet that shows the border to a client that hasn't seen
+    /// it yet, including the remainder of any ongoing resize.
+    pub fn initial_packet(&self) -> CreateWorldBorder {
+        let (new_diameter, speed) = match &self.lerp {
+            Some(lerp) => (
+                lerp.to,
+                lerp.duration.saturating_sub(lerp.elapsed).as_millis() as i64,
+            ),
+            None => (self.diameter, 0),
+        };
+
+        CreateWorldBorder {
+            x: self.center.x,
+            z: self.center.y,
+            old_diameter: self.diameter(),
+            new_diameter,
+            speed,
+            portal_teleport_boundary: self.portal_teleport_boundary,
+            warning_blocks: self.warning_blocks,
+            warning_time: self.warning_time,
+        }
+    }
+
+    /// Advances an ongoing resize by the given amount of time, finishing it
+    /// once its duration has elapsed.
+    pub fn advance(&mut self, delta: Duration) {
+        let Some(lerp) = &mut self.lerp else {
+            return;
+        };
+
+        lerp.elapsed += delta;
+        if lerp.elapsed >= lerp.duration {
+            self.diameter = lerp.to;
+            self.lerp = None;
+        }
+    }
+}
+
+/// An ongoing resize of the [`WorldBorder`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct BorderLerp {
+    from: f64,
+    to: f64,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl BorderLerp {
+    /// Returns the diameter of the border at the current point of the resize.
+    fn diameter(&self) -> f64 {
+        let progress = self.elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.from + (self.to - self.from) * progress.min(1.)
+    }
+}
+
+/// A packet that changes the world border shown to clients.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WorldBorderPacket {
+    /// Moves the center.
+    Center(WorldBorderCenter),
+    /// Immediately resizes the border.
+    Size(WorldBorderSize),
+    /// Gradually resizes the border.
+    LerpSize(WorldBorderLerpSize),
+    /// Sets the warning delay.
+    WarningDelay(WorldBorderWarningDelay),
+    /// Sets the warning reach.
+    WarningReach(WorldBorderWarningReach),
+}
+
+impl WorldBorderPacket {
+    /// Sends the packet to a client.
+    pub fn send(&self, writer: &ClientPacketWriter) {
+        match self {
+            Self::Center(packet) => writer.send(packet),
+            Self::Size(packet) => writer.send(packet),
+            Self::LerpSize(packet) => writer.send(packet),
+            Self::WarningDelay(packet) => writer.send(packet),
+            Self::WarningReach(packet) => writer.send(packet),
+        };
+    }
+
+    /// Sends the packet to all clients in the play state.
+    pub fn broadcast(&self, writer: &mut PacketWriter) {
+        match self {
+            Self::Center(packet) => writer.broadcast(ProtocolState::Play, packet),
+            Self::Size(packet) => writer.broadcast(ProtocolState::Play, packet),
+            Self::LerpSize(packet) => writer.broadcast(ProtocolState::Play, packet),
+            Self::WarningDelay(packet) => writer.broadcast(ProtocolState::Play, packet),
+            Self::WarningReach(packet) => writer.broadcast(ProtocolState::Play, packet),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, time::Duration};
+
+    use bevy::{math::DVec2, prelude::*};
+    use minecrevy_net::client::ProtocolState;
+    use minecrevy_protocol::play::{
+        WorldBorderCenter, WorldBorderLerpSize, WorldBorderSize, WorldBorderWarningDelay,
+        WorldBorderWarningReach,
+    };
+
+    use super::{WorldBorder, WorldBorderPlugin};
+    use crate::{play::EnterPlay, testing};
+
+    fn app() -> App {
+        let mut app = testing::app();
+        app.add_plugins(WorldBorderPlugin);
+        app
+    }
+
+    #[test]
+    fn changes_are_broadcast_in_order() {
+        let mut app = app();
+        let (_, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        let mut border = app.world_mut().resource_mut::<WorldBorder>();
+        border.set_center(DVec2::new(100., -50.));
+        border.set_size(200.);
+        border.lerp_to(50., Duration::from_secs(10));
+        border.set_warning_time(30);
+        border.set_warning_blocks(8);
+
+        let mut recv = || testing::recv(&mut app, &mut connection);
+        testing::assert_packet(recv(), 0x4D, &WorldBorderCenter { x: 100., z: -50. });
+        testing::assert_packet(recv(), 0x4F, &WorldBorderSize { diameter: 200. });
+        testing::assert_packet(
+            recv(),
+            0x4E,
+            &WorldBorderLerpSize {
+                old_diameter: 200.,
+                new_diameter: 50.,
+                speed: 10000,
+            },
+        );
+        testing::assert_packet(recv(), 0x50, &WorldBorderWarningDelay(30));
+        testing::assert_packet(recv(), 0x51, &WorldBorderWarningReach(8));
+    }
+
+    #[test]
+    fn unchanged_values_are_not_sent() {
+        let mut app = app();
+        let (_, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+
+        let mut border = app.world_mut().resource_mut::<WorldBorder>();
+        border.set_center(DVec2::ZERO);
+        border.set_size(WorldBorder::MAX_DIAMETER);
+        border.set_warning_blocks(5);
+        border.set_warning_time(15);
+        app.update();
+
+        connection.set_timeout(Duration::from_millis(50));
+        let error = connection.recv_raw().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn joining_player_is_sent_the_rest_of_a_resize() {
+        let mut app = app();
+        let mut border = app.world_mut().resource_mut::<WorldBorder>();
+        border.set_size(100.);
+        border.lerp_to(50., Duration::from_secs(10));
+        border.advance(Duration::from_secs(4));
+
+        let (client, mut connection) = testing::connect(&mut app, ProtocolState::Play);
+        app.world_mut().trigger_targets(EnterPlay, client);
+        app.world_mut().flush();
+        let expected = app.world().resource::<WorldBorder>().initial_packet();
+
+        // The resize is at least 4 of its 10 seconds in.
+        assert_eq!(expected.new_diameter, 50.);
+        assert!(expected.old_diameter <= 80. && expected.old_diameter > 50.);
+        assert!(expected.speed <= 6000 && expected.speed > 0);
+        testing::assert_packet(testing::recv(&mut app, &mut connection), 0x25, &expected);
+    }
+}
@@ -2,15 +2,24 @@
 
 #![warn(missing_docs)]
 
-use std::io::{self, Read, Write};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
 
+use bevy::color::{Color, ColorToPacked, Mix, Srgba};
 use minecrevy_io::{args::StringArgs, McRead, McWrite};
 use serde::{Deserialize, Serialize};
 
+mod nbt;
+mod prepared;
+
+pub use prepared::PreparedText;
+
 pub mod prelude {
     //! Re-exports important traits and types.
 
-    pub use super::{ClickEvent, HoverEvent, Text, TextContent, TextStyle};
+    pub use super::{ClickEvent, HoverEvent, PreparedText, Text, TextContent, TextStyle};
 }
 
 /// A text component.
@@ -53,6 +62,38 @@ impl Text {
         Text::string("\n")
     }
 
+    /// Creates a new text component with one child per character of `text`,
+    /// colored along a gradient from `from` to `to`.
+    pub fn gradient(text: &str, from: impl Into<Color>, to: impl Into<Color>) -> Self {
+        let from = Srgba::from(from.into());
+        let to = Srgba::from(to.into());
+        Text::per_char(text, |t| from.mix(&to, t).into())
+    }
+
+    /// Creates a new text component with one child per character of `text`,
+    /// colored through the hues of the rainbow.
+    pub fn rainbow(text: &str) -> Self {
+        Text::per_char(text, |t| Color::hsl(t * 300.0, 1.0, 0.5))
+    }
+
+    /// Creates a new text component with one child per character of `text`,
+    /// colored by `color_at`, which is given the character's position along
+    /// the text from `0.0` to `1.0`.
+    fn per_char(text: &str, color_at: impl Fn(f32) -> Color) -> Self {
+        let last = text.chars().count().saturating_sub(1).max(1) as f32;
+        let mut parent = Text::empty();
+        parent.extra = text
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let mut child = Text::string(c);
+                child.style.color = Some(hex_color(color_at(i as f32 / last)));
+                child
+            })
+            .collect();
+        parent
+    }
+
     /// Sets [`TextStyle::bold`] to `true`.
     pub fn bold(mut self) -> Self {
         self.style.bold = Some(true);
@@ -106,6 +147,47 @@ impl Text {
         self.style.hover = Some(event.into());
         self
     }
+
+    /// Returns `true` if both text components render the same, treating
+    /// unset style fields as equal to their explicit defaults.
+    ///
+    /// Unlike [`PartialEq`], `bold: None` equals `bold: Some(false)`, for
+    /// example. Each child is compared with the style it inherits from its
+    /// parent, so an unset field in a child equals its parent's value.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        let root = TextStyle::default();
+        self.semantically_eq_within(other, &root, &root)
+    }
+
+    /// Compares both text components as children of parents with the given
+    /// styles.
+    fn semantically_eq_within(
+        &self,
+        other: &Self,
+        parent: &TextStyle,
+        other_parent: &TextStyle,
+    ) -> bool {
+        let mut style = self.style.clone();
+        style.inherit_from(parent);
+        let mut other_style = other.style.clone();
+        other_style.inherit_from(other_parent);
+
+        self.content.semantically_eq(&other.content)
+            && style.semantically_eq(&other_style)
+            && self.extra.len() == other.extra.len()
+            && self
+                .extra
+                .iter()
+                .zip(&other.extra)
+                .all(|(a, b)| a.semantically_eq_within(b, &style, &other_style))
+    }
+}
+
+/// Returns the given color as a `#rrggbb` string, as used by
+/// [`TextStyle::color`].
+fn hex_color(color: Color) -> String {
+    let [r, g, b] = Srgba::from(color).to_u8_array_no_alpha();
+    format!("#{r:02x}{g:02x}{b:02x}")
 }
 
 impl From<String> for Text {
@@ -131,12 +213,18 @@ pub struct TextArgs {
     ///
     /// Set to `None` to disable this limit.
     pub max_len: Option<usize>,
+    /// Whether the text is encoded as network NBT, as sent by 1.20.3 and
+    /// later, rather than as a JSON string, as sent by earlier versions.
+    ///
+    /// [`TextArgs::max_len`] doesn't apply to NBT text.
+    pub nbt: bool,
 }
 
 impl Default for TextArgs {
     fn default() -> Self {
         TextArgs {
             max_len: Some(262144),
+            nbt: false,
         }
     }
 }
@@ -145,6 +233,12 @@ impl McRead for Text {
     type Args = TextArgs;
 
     fn read(reader: impl Read, args: Self::Args) -> io::Result<Self> {
+        if args.nbt {
+            let value = nbt::normalize_component(nbt::read_value(reader)?);
+            return serde_json::from_value::<Text>(value)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        }
+
         let json = String::read(
             reader,
             StringArgs {
@@ -161,6 +255,12 @@ impl McWrite for Text {
     type Args = TextArgs;
 
     fn write(&self, writer: impl Write, args: Self::Args) -> io::Result<()> {
+        if args.nbt {
+            let value = serde_json::to_value::<&Text>(self)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            return nbt::write_value(writer, &value);
+        }
+
         let json = serde_json::to_string::<Text>(self)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
@@ -219,6 +319,28 @@ impl TextContent {
             keybind: key.into(),
         }
     }
+
+    /// Returns `true` if both contents render the same, comparing translation
+    /// arguments with [`Text::semantically_eq`].
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                TextContent::Translatable { key, with },
+                TextContent::Translatable {
+                    key: other_key,
+                    with: other_with,
+                },
+            ) => {
+                key == other_key
+                    && with.len() == other_with.len()
+                    && with
+                        .iter()
+                        .zip(other_with)
+                        .all(|(a, b)| a.semantically_eq(b))
+            }
+            _ => self == other,
+        }
+    }
 }
 
 /// The style of a text component.
@@ -257,7 +379,67 @@ pub struct TextStyle {
     pub hover: Option<HoverEvent>,
 }
 
+impl TextStyle {
+    /// The font used when [`TextStyle::font`] is unset.
+    pub const DEFAULT_FONT: &'static str = "minecraft:default";
+
+    /// Fills each unset field of this style from the given parent style,
+    /// keeping the fields that are set, the way a child component inherits its
+    /// parent's style.
+    pub fn inherit_from(&mut self, parent: &TextStyle) {
+        fn inherit<T: Clone>(child: &mut Option<T>, parent: &Option<T>) {
+            if child.is_none() {
+                child.clone_from(parent);
+            }
+        }
+
+        inherit(&mut self.bold, &parent.bold);
+        inherit(&mut self.italic, &parent.italic);
+        inherit(&mut self.underlined, &parent.underlined);
+        inherit(&mut self.strikethrough, &parent.strikethrough);
+        inherit(&mut self.obfuscated, &parent.obfuscated);
+        inherit(&mut self.font, &parent.font);
+        inherit(&mut self.color, &parent.color);
+        inherit(&mut self.insertion, &parent.insertion);
+        inherit(&mut self.click, &parent.click);
+        inherit(&mut self.hover, &parent.hover);
+    }
+
+    /// Returns `true` if both styles render the same, treating unset fields as
+    /// equal to their explicit defaults.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        fn flag(value: Option<bool>) -> bool {
+            value.unwrap_or_default()
+        }
+
+        flag(self.bold) == flag(other.bold)
+            && flag(self.italic) == flag(other.italic)
+            && flag(self.underlined) == flag(other.underlined)
+            && flag(self.strikethrough) == flag(other.strikethrough)
+            && flag(self.obfuscated) == flag(other.obfuscated)
+            && self.font.as_deref().unwrap_or(Self::DEFAULT_FONT)
+                == other.font.as_deref().unwrap_or(Self::DEFAULT_FONT)
+            && self.color == other.color
+            && self.insertion.as_deref().unwrap_or_default()
+                == other.insertion.as_deref().unwrap_or_default()
+            && self.click == other.click
+            && match (&self.hover, &other.hover) {
+                (Some(HoverEvent::ShowText(a)), Some(HoverEvent::ShowText(b))) => {
+                    a.semantically_eq(b)
+                }
+                (a, b) => a == b,
+            }
+    }
+}
+
 /// Events that can be triggered by clicking on a text component.
+///
+/// The `open_file` action is deliberately missing, as clients reject it from
+/// servers. [`ClickEvent::open_file`] always returns an [`OpenFileError`], and
+/// `open_file` events fail to deserialize as an unknown variant.
+///
+/// The `custom` and `show_dialog` actions were added in 1.21.5, so they're
+/// only available with the `v1_21_5` feature.
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "action", content = "value", rename_all = "snake_case")]
@@ -269,9 +451,45 @@ pub enum ClickEvent {
     /// Suggests the given command to the user.
     SuggestCommand(String),
     /// Changes the page of a book.
-    ChangePage(i32),
+    ChangePage(#[serde(with = "page_value")] i32),
     /// Prompts the user to copy the given text to their clipboard.
     CopyToClipboard(String),
+    /// Sends a custom payload with the given ID to the server.
+    #[cfg(feature = "v1_21_5")]
+    Custom {
+        /// The namespaced ID of the payload.
+        id: String,
+        /// The payload sent to the server, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload: Option<String>,
+    },
+    /// Shows the dialog with the given namespaced ID to the user.
+    #[cfg(feature = "v1_21_5")]
+    ShowDialog(String),
+}
+
+/// (De)serializes the page of a [`ClickEvent::ChangePage`], which vanilla
+/// encodes as a string.
+mod page_value {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(page: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(page)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Page {
+            Int(i32),
+            String(String),
+        }
+
+        match Page::deserialize(deserializer)? {
+            Page::Int(page) => Ok(page),
+            Page::String(page) => page.parse().map_err(serde::de::Error::custom),
+        }
+    }
 }
 
 impl ClickEvent {
@@ -299,8 +517,42 @@ impl ClickEvent {
     pub fn copy_to_clipboard(text: impl Into<String>) -> Self {
         ClickEvent::CopyToClipboard(text.into())
     }
+
+    /// Creates a new [`ClickEvent::Custom`] event.
+    #[cfg(feature = "v1_21_5")]
+    pub fn custom(id: impl Into<String>, payload: Option<String>) -> Self {
+        ClickEvent::Custom {
+            id: id.into(),
+            payload,
+        }
+    }
+
+    /// Creates a new [`ClickEvent::ShowDialog`] event.
+    #[cfg(feature = "v1_21_5")]
+    pub fn show_dialog(dialog: impl Into<String>) -> Self {
+        ClickEvent::ShowDialog(dialog.into())
+    }
+
+    /// Always returns an [`OpenFileError`], as clients reject `open_file`
+    /// click events from servers.
+    pub fn open_file(_path: impl Into<String>) -> Result<Self, OpenFileError> {
+        Err(OpenFileError)
+    }
+}
+
+/// Error returned by [`ClickEvent::open_file`], as clients reject `open_file`
+/// click events from servers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct OpenFileError;
+
+impl fmt::Display for OpenFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("clients reject open_file click events from servers")
+    }
 }
 
+impl std::error::Error for OpenFileError {}
+
 /// Events that can be triggered by hovering over a text component.
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 #[derive(Serialize, Deserialize)]
@@ -322,3 +574,328 @@ impl From<Text> for HoverEvent {
         HoverEvent::show_text(text)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::{
+        nbt::{Compound, Tag},
+        McRead, McWrite,
+    };
+
+    use bevy::color::Color;
+    use serde_json::{json, Value};
+
+    use super::{ClickEvent, OpenFileError, Text, TextArgs, TextContent, TextStyle};
+
+    /// Asserts that the event serializes to the given JSON and back.
+    fn assert_click_round_trip(event: ClickEvent, expected: Value) {
+        assert_eq!(serde_json::to_value(&event).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<ClickEvent>(expected).unwrap(),
+            event
+        );
+    }
+
+    fn nbt_args() -> TextArgs {
+        TextArgs {
+            nbt: true,
+            ..TextArgs::default()
+        }
+    }
+
+    fn read_nbt(tag: Tag) -> Text {
+        let mut bytes = Vec::new();
+        tag.write(&mut bytes, ()).unwrap();
+        Text::read(bytes.as_slice(), nbt_args()).unwrap()
+    }
+
+    fn compound<const N: usize>(fields: [(&str, Tag); N]) -> Tag {
+        Tag::Compound(
+            fields
+                .into_iter()
+                .map(|(name, tag)| (name.to_owned(), tag))
+                .collect::<Compound>(),
+        )
+    }
+
+    fn child_with_bold(bold: Option<bool>) -> Text {
+        let mut child = Text::string("child");
+        child.style = TextStyle {
+            bold,
+            ..TextStyle::default()
+        };
+        Text {
+            extra: vec![child],
+            ..Text::string("parent").bold()
+        }
+    }
+
+    #[test]
+    fn unset_root_style_equals_default() {
+        let unset = Text::string("text");
+        let mut explicit = Text::string("text");
+        explicit.style.bold = Some(false);
+
+        assert!(unset.semantically_eq(&explicit));
+    }
+
+    #[test]
+    fn child_inherits_parent_style() {
+        let inherited = child_with_bold(None);
+        let bold = child_with_bold(Some(true));
+        let not_bold = child_with_bold(Some(false));
+
+        assert!(inherited.semantically_eq(&bold));
+        assert!(!inherited.semantically_eq(&not_bold));
+        assert!(!bold.semantically_eq(&not_bold));
+    }
+
+    #[test]
+    fn nbt_round_trip() {
+        let text = Text {
+            extra: vec![
+                Text::string("child").italic(),
+                Text {
+                    content: TextContent::Translatable {
+                        key: "chat.type.text".into(),
+                        with: vec![Text::string("Steve"), Text::string("hi").bold()],
+                    },
+                    ..Text::string("")
+                },
+            ],
+            ..Text::string("parent").bold()
+        };
+
+        let mut bytes = Vec::new();
+        text.write(&mut bytes, nbt_args()).unwrap();
+        let read = Text::read(bytes.as_slice(), nbt_args()).unwrap();
+
+        assert_eq!(read, text);
+    }
+
+    #[test]
+    fn nbt_string_is_read_as_text() {
+        assert_eq!(read_nbt(Tag::String("hello".into())), Text::string("hello"));
+    }
+
+    #[test]
+    fn nbt_string_children_are_read_as_text() {
+        let tag = compound([
+            ("text", Tag::String("parent".into())),
+            (
+                "extra",
+                Tag::List(vec![Tag::String("a".into()), Tag::String("b".into())]),
+            ),
+        ]);
+
+        let expected = Text {
+            extra: vec![Text::string("a"), Text::string("b")],
+            ..Text::string("parent")
+        };
+        assert_eq!(read_nbt(tag), expected);
+    }
+
+    #[test]
+    fn nbt_wrapped_list_elements_are_unwrapped() {
+        let tag = compound([
+            ("translate", Tag::String("chat.type.text".into())),
+            (
+                "with",
+                Tag::List(vec![
+                    compound([("", Tag::String("Steve".into()))]),
+                    compound([("", Tag::Int(3))]),
+                    compound([
+                        ("text", Tag::String("hi".into())),
+                        ("bold", Tag::Byte(1)),
+                        ("extra", Tag::List(vec![Tag::String("!".into())])),
+                    ]),
+                ]),
+            ),
+        ]);
+
+        let expected = Text {
+            content: TextContent::Translatable {
+                key: "chat.type.text".into(),
+                with: vec![
+                    Text::string("Steve"),
+                    Text::string("3"),
+                    Text {
+                        extra: vec![Text::string("!")],
+                        ..Text::string("hi").bold()
+                    },
+                ],
+            },
+            ..Text::string("")
+        };
+        assert_eq!(read_nbt(tag), expected);
+    }
+
+    #[test]
+    fn click_event_serde() {
+        assert_click_round_trip(
+            ClickEvent::open_url("https://example.com"),
+            json!({"action": "open_url", "value": "https://example.com"}),
+        );
+        assert_click_round_trip(
+            ClickEvent::run_command("/help"),
+            json!({"action": "run_command", "value": "/help"}),
+        );
+        assert_click_round_trip(
+            ClickEvent::suggest_command("/msg "),
+            json!({"action": "suggest_command", "value": "/msg "}),
+        );
+        assert_click_round_trip(
+            ClickEvent::change_page(3),
+            json!({"action": "change_page", "value": "3"}),
+        );
+        assert_click_round_trip(
+            ClickEvent::copy_to_clipboard("copied"),
+            json!({"action": "copy_to_clipboard", "value": "copied"}),
+        );
+    }
+
+    #[test]
+    fn change_page_reads_numbers() {
+        let event = json!({"action": "change_page", "value": 3});
+        assert_eq!(
+            serde_json::from_value::<ClickEvent>(event).unwrap(),
+            ClickEvent::change_page(3)
+        );
+    }
+
+    #[cfg(feature = "v1_21_5")]
+    #[test]
+    fn versioned_click_event_serde() {
+        assert_click_round_trip(
+            ClickEvent::custom("example:clicked", Some("payload".into())),
+            json!({
+                "action": "custom",
+                "value": {"id": "example:clicked", "payload": "payload"},
+            }),
+        );
+        assert_click_round_trip(
+            ClickEvent::custom("example:clicked", None),
+            json!({"action": "custom", "value": {"id": "example:clicked"}}),
+        );
+        assert_click_round_trip(
+            ClickEvent::show_dialog("example:rules"),
+            json!({"action": "show_dialog", "value": "example:rules"}),
+        );
+    }
+
+    #[cfg(not(feature = "v1_21_5"))]
+    #[test]
+    fn versioned_click_events_need_the_feature() {
+        let event = json!({"action": "show_dialog", "value": "example:rules"});
+        assert!(serde_json::from_value::<ClickEvent>(event).is_err());
+    }
+
+    #[test]
+    fn open_file_is_rejected() {
+        assert_eq!(ClickEvent::open_file("/etc/passwd"), Err(OpenFileError));
+
+        let event = json!({"action": "open_file", "value": "/etc/passwd"});
+        assert!(serde_json::from_value::<ClickEvent>(event).is_err());
+    }
+
+    #[test]
+    fn deeply_nested_text_is_rejected() {
+        let json = r#"{"text":"","extra":["#.repeat(1000);
+        let mut bytes = Vec::new();
+        json.write(&mut bytes, Default::default()).unwrap();
+
+        assert!(Text::read(bytes.as_slice(), TextArgs::default()).is_err());
+    }
+
+    fn colors(text: &Text) -> Vec<&str> {
+        text.extra
+            .iter()
+            .map(|child| child.style.color.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn gradient_has_one_child_per_char() {
+        let text = Text::gradient("héllo", Color::srgb(1.0, 0.0, 0.0), Color::WHITE);
+
+        let chars: Vec<_> = text
+            .extra
+            .iter()
+            .map(|child| child.content.clone())
+            .collect();
+        let expected: Vec<_> = "héllo".chars().map(TextContent::string).collect();
+        assert_eq!(chars, expected);
+    }
+
+    #[test]
+    fn gradient_endpoints_are_the_given_colors() {
+        let text = Text::gradient("gradient", Color::srgb(1.0, 0.0, 0.0), Color::BLACK);
+        let colors = colors(&text);
+
+        assert_eq!(colors.len(), 8);
+        assert_eq!(colors[0], "#ff0000");
+        assert_eq!(colors[7], "#000000");
+    }
+
+    #[test]
+    fn single_char_gradient_is_the_start_color() {
+        let text = Text::gradient("a", Color::WHITE, Color::BLACK);
+        assert_eq!(colors(&text), ["#ffffff"]);
+    }
+
+    #[test]
+    fn rainbow_starts_red() {
+        let text = Text::rainbow("rainbow");
+        let colors = colors(&text);
+
+        assert_eq!(colors.len(), 7);
+        assert_eq!(colors[0], "#ff0000");
+        assert_ne!(colors[0], colors[6]);
+    }
+
+    #[test]
+    fn inherited_style_keeps_set_fields() {
+        let parent = TextStyle {
+            bold: Some(true),
+            italic: Some(true),
+            color: Some("red".to_owned()),
+            insertion: Some("parent".to_owned()),
+            click: Some(ClickEvent::RunCommand("/spawn".to_owned())),
+            ..TextStyle::default()
+        };
+        let mut child = TextStyle {
+            italic: Some(false),
+            color: Some("blue".to_owned()),
+            ..TextStyle::default()
+        };
+
+        child.inherit_from(&parent);
+
+        assert_eq!(
+            child,
+            TextStyle {
+                // Set on the child.
+                italic: Some(false),
+                color: Some("blue".to_owned()),
+                // Unset on the child, so taken from the parent.
+                bold: Some(true),
+                insertion: Some("parent".to_owned()),
+                click: Some(ClickEvent::RunCommand("/spawn".to_owned())),
+                // Unset on both.
+                ..TextStyle::default()
+            }
+        );
+    }
+
+    #[test]
+    fn inheriting_from_an_empty_style_changes_nothing() {
+        let style = TextStyle {
+            underlined: Some(true),
+            font: Some("minecraft:uniform".to_owned()),
+            ..TextStyle::default()
+        };
+        let mut child = style.clone();
+        child.inherit_from(&TextStyle::default());
+        assert_eq!(child, style);
+    }
+}
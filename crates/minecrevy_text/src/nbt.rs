@@ -0,0 +1,118 @@
+//! Encoding of text components as network NBT, as used in the play state.
+
+use std::io::{self, Read, Write};
+
+use minecrevy_io::{
+    nbt::{Compound, Tag},
+    McRead, McWrite,
+};
+use serde_json::{Map, Number, Value};
+
+/// Writes the given JSON value as a nameless network NBT tag.
+pub(crate) fn write_value(writer: impl Write, value: &Value) -> io::Result<()> {
+    to_tag(value)?.write(writer, ())
+}
+
+/// Converts a JSON value to the NBT tag it's encoded as.
+fn to_tag(value: &Value) -> io::Result<Tag> {
+    Ok(match value {
+        Value::Null => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "null can't be encoded as NBT",
+            ))
+        }
+        Value::Bool(v) => Tag::from(*v),
+        Value::Number(n) => number_tag(n),
+        Value::String(v) => Tag::String(v.clone()),
+        Value::Array(values) => Tag::List(values.iter().map(to_tag).collect::<io::Result<_>>()?),
+        Value::Object(fields) => Tag::Compound(
+            fields
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), to_tag(value)?)))
+                .collect::<io::Result<Compound>>()?,
+        ),
+    })
+}
+
+fn number_tag(n: &Number) -> Tag {
+    match n.as_i64() {
+        Some(v) => i32::try_from(v).map_or(Tag::Long(v), Tag::Int),
+        None => Tag::Double(n.as_f64().unwrap_or_default()),
+    }
+}
+
+/// Reads a nameless network NBT tag as a JSON value.
+///
+/// Bytes of `0` and `1` are read as booleans, as that's what text components
+/// use them for.
+pub(crate) fn read_value(reader: impl Read) -> io::Result<Value> {
+    Ok(to_value(Tag::read(reader, ())?))
+}
+
+/// Converts a JSON value read from NBT to the shape [`Text`] deserializes
+/// from, recursing into child components.
+///
+/// Network NBT encodes a component with only text as a plain string, and
+/// lists can't mix tag types, so each element of a mixed list is wrapped in a
+/// compound with a single empty name. Primitive translation arguments are
+/// converted to text.
+///
+/// [`Text`]: crate::Text
+pub(crate) fn normalize_component(value: Value) -> Value {
+    match value {
+        Value::String(text) => text_object(text),
+        Value::Bool(v) => text_object(v.to_string()),
+        Value::Number(v) => text_object(v.to_string()),
+        Value::Object(mut fields) => {
+            if fields.len() == 1 && fields.contains_key("") {
+                return normalize_component(fields.remove("").unwrap());
+            }
+            for name in ["extra", "with"] {
+                if let Some(Value::Array(children)) = fields.remove(name) {
+                    let children = children.into_iter().map(normalize_component).collect();
+                    fields.insert(name.to_owned(), Value::Array(children));
+                }
+            }
+            if let Some(Value::Object(hover)) = fields.get_mut("hover") {
+                if let Some(shown) = hover.remove("value") {
+                    hover.insert("value".to_owned(), normalize_component(shown));
+                }
+            }
+            Value::Object(fields)
+        }
+        value => value,
+    }
+}
+
+/// Returns a component object with only the given text.
+fn text_object(text: String) -> Value {
+    let mut fields = Map::new();
+    fields.insert("text".to_owned(), Value::String(text));
+    Value::Object(fields)
+}
+
+/// Converts an NBT tag to a JSON value.
+fn to_value(tag: Tag) -> Value {
+    match tag {
+        Tag::Byte(0) => Value::Bool(false),
+        Tag::Byte(1) => Value::Bool(true),
+        Tag::Byte(v) => Value::from(v),
+        Tag::Short(v) => Value::from(v),
+        Tag::Int(v) => Value::from(v),
+        Tag::Long(v) => Value::from(v),
+        Tag::Float(v) => Value::from(v),
+        Tag::Double(v) => Value::from(v),
+        Tag::ByteArray(values) => Value::from(values),
+        Tag::String(v) => Value::String(v),
+        Tag::List(values) => Value::Array(values.into_iter().map(to_value).collect()),
+        Tag::Compound(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, to_value(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        Tag::IntArray(values) => Value::from(values),
+        Tag::LongArray(values) => Value::from(values),
+    }
+}
@@ -0,0 +1,186 @@
+//! Text components that are encoded once and sent many times.
+
+use std::{
+    io::{self, Read, Write},
+    sync::OnceLock,
+};
+
+use minecrevy_io::{args::StringArgs, McRead, McWrite};
+
+use crate::{Text, TextArgs};
+
+/// A [`Text`] component that caches its encoded forms, so it can be sent to
+/// many clients without being serialized again for each of them.
+///
+/// The cache is cleared whenever the text is changed through
+/// [`PreparedText::text_mut`] or [`PreparedText::set`].
+#[derive(Clone, Debug)]
+pub struct PreparedText {
+    /// The source text component.
+    text: Text,
+    /// The text serialized as a JSON string.
+    json: OnceLock<String>,
+    /// The text encoded as network NBT.
+    nbt: OnceLock<Vec<u8>>,
+}
+
+impl PreparedText {
+    /// Creates a new [`PreparedText`] for the given text component.
+    pub fn new(text: Text) -> Self {
+        Self {
+            text,
+            json: OnceLock::new(),
+            nbt: OnceLock::new(),
+        }
+    }
+
+    /// Returns the source text component.
+    pub fn text(&self) -> &Text {
+        &self.text
+    }
+
+    /// Returns the source text component mutably, clearing the cache.
+    pub fn text_mut(&mut self) -> &mut Text {
+        self.json.take();
+        self.nbt.take();
+        &mut self.text
+    }
+
+    /// Replaces the source text component, clearing the cache.
+    pub fn set(&mut self, text: Text) {
+        *self.text_mut() = text;
+    }
+
+    /// Returns the source text component, discarding the cache.
+    pub fn into_text(self) -> Text {
+        self.text
+    }
+
+    /// Returns the text serialized as a JSON string, serializing it if it
+    /// hasn't been yet.
+    fn json(&self) -> io::Result<&String> {
+        if let Some(json) = self.json.get() {
+            return Ok(json);
+        }
+        let json = serde_json::to_string::<Text>(&self.text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(self.json.get_or_init(|| json))
+    }
+
+    /// Returns the text encoded as network NBT, encoding it if it hasn't been
+    /// yet.
+    fn nbt(&self) -> io::Result<&[u8]> {
+        if let Some(nbt) = self.nbt.get() {
+            return Ok(nbt);
+        }
+        let mut nbt = Vec::new();
+        self.text.write(
+            &mut nbt,
+            TextArgs {
+                max_len: None,
+                nbt: true,
+            },
+        )?;
+        Ok(self.nbt.get_or_init(|| nbt))
+    }
+}
+
+impl From<Text> for PreparedText {
+    fn from(text: Text) -> Self {
+        Self::new(text)
+    }
+}
+
+impl PartialEq for PreparedText {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+impl Eq for PreparedText {}
+
+impl McRead for PreparedText {
+    type Args = TextArgs;
+
+    fn read(reader: impl Read, args: Self::Args) -> io::Result<Self> {
+        Text::read(reader, args).map(Self::new)
+    }
+}
+
+impl McWrite for PreparedText {
+    type Args = TextArgs;
+
+    fn write(&self, mut writer: impl Write, args: Self::Args) -> io::Result<()> {
+        if args.nbt {
+            return writer.write_all(self.nbt()?);
+        }
+
+        self.json()?.write(
+            writer,
+            StringArgs {
+                max_len: args.max_len,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::McWrite;
+
+    use super::PreparedText;
+    use crate::{Text, TextArgs};
+
+    fn encode(value: &impl McWrite<Args = TextArgs>, nbt: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let args = TextArgs {
+            nbt,
+            ..TextArgs::default()
+        };
+        value.write(&mut bytes, args).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn sends_match_a_direct_encode() {
+        let text = Text::string("Hello").bold();
+        let prepared = PreparedText::new(text.clone());
+
+        for nbt in [false, true] {
+            let direct = encode(&text, nbt);
+            for _ in 0..100 {
+                assert_eq!(encode(&prepared, nbt), direct);
+            }
+        }
+    }
+
+    #[test]
+    fn text_is_encoded_once() {
+        let mut prepared = PreparedText::new(Text::string("before"));
+        let json = encode(&prepared, false);
+        let nbt = encode(&prepared, true);
+
+        // Changing the source behind the cache's back shows that later sends
+        // reuse the first encoding instead of encoding the text again.
+        prepared.text = Text::string("after");
+        for _ in 0..10 {
+            assert_eq!(encode(&prepared, false), json);
+            assert_eq!(encode(&prepared, true), nbt);
+        }
+    }
+
+    #[test]
+    fn changing_the_text_clears_the_cache() {
+        let mut prepared = PreparedText::new(Text::string("before"));
+        encode(&prepared, false);
+        encode(&prepared, true);
+
+        prepared.set(Text::string("after"));
+        let after = Text::string("after");
+        assert_eq!(encode(&prepared, false), encode(&after, false));
+        assert_eq!(encode(&prepared, true), encode(&after, true));
+
+        prepared.text_mut().extra.push(Text::string("!"));
+        assert_eq!(encode(&prepared, false), encode(prepared.text(), false));
+    }
+}
@@ -0,0 +1,142 @@
+//! Modifiers of entity attributes, such as movement speed or max health.
+
+use std::io;
+
+use minecrevy_io::{error::DecodeError, McRead, McWrite};
+
+use crate::key::Key;
+
+/// A modifier of an entity attribute, such as the speed bonus from sprinting.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AttributeModifier {
+    /// The unique ID of the modifier, such as `minecraft:sprinting`.
+    pub id: Key,
+    /// The amount the modifier changes the attribute by.
+    pub amount: f64,
+    /// How the amount is applied to the attribute.
+    pub operation: Operation,
+}
+
+impl McRead for AttributeModifier {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = Key::read(&mut reader, ())?;
+        let amount = f64::read(&mut reader, ())?;
+        let operation = i8::read(reader, ())?;
+        let operation = Operation::from_id(operation)
+            .ok_or_else(|| DecodeError::invalid_discriminant::<Operation>(operation))?;
+
+        Ok(Self {
+            id,
+            amount,
+            operation,
+        })
+    }
+}
+
+impl McWrite for AttributeModifier {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.id.write(&mut writer, ())?;
+        self.amount.write(&mut writer, ())?;
+        self.operation.to_id().write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+/// How an [`AttributeModifier`]'s amount is applied to an attribute.
+///
+/// Modifiers are applied in the order of their operations.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum Operation {
+    /// Adds the amount to the base value.
+    #[default]
+    AddValue,
+    /// Adds the base value multiplied by the amount.
+    AddMultipliedBase,
+    /// Multiplies the total value by one plus the amount.
+    AddMultipliedTotal,
+}
+
+impl Operation {
+    /// Returns the [`Operation`] with the given protocol ID, or [`None`] if the
+    /// ID is out of range.
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            0 => Some(Self::AddValue),
+            1 => Some(Self::AddMultipliedBase),
+            2 => Some(Self::AddMultipliedTotal),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`Operation`].
+    pub fn to_id(self) -> i8 {
+        match self {
+            Self::AddValue => 0,
+            Self::AddMultipliedBase => 1,
+            Self::AddMultipliedTotal => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::{AttributeModifier, Operation};
+    use crate::key::Key;
+
+    #[test]
+    fn operation_ids() {
+        for (operation, id) in [
+            (Operation::AddValue, 0),
+            (Operation::AddMultipliedBase, 1),
+            (Operation::AddMultipliedTotal, 2),
+        ] {
+            assert_eq!(operation.to_id(), id);
+            assert_eq!(Operation::from_id(id), Some(operation));
+        }
+        assert_eq!(Operation::from_id(3), None);
+        assert_eq!(Operation::from_id(-1), None);
+    }
+
+    #[test]
+    fn modifier_layout() {
+        let modifier = AttributeModifier {
+            id: Key::minecraft("sprinting").unwrap(),
+            amount: 0.5,
+            operation: Operation::AddMultipliedTotal,
+        };
+        let mut bytes = Vec::new();
+        modifier.write(&mut bytes, ()).unwrap();
+
+        let mut expected = vec![19];
+        expected.extend_from_slice(b"minecraft:sprinting");
+        expected.extend_from_slice(&0.5f64.to_be_bytes());
+        expected.push(2);
+        assert_eq!(bytes, expected);
+        assert_eq!(
+            AttributeModifier::read(bytes.as_slice(), ()).unwrap(),
+            modifier
+        );
+    }
+
+    #[test]
+    fn unknown_operation_is_rejected() {
+        let mut bytes = Vec::new();
+        Key::minecraft("sprinting")
+            .unwrap()
+            .write(&mut bytes, ())
+            .unwrap();
+        bytes.extend_from_slice(&0.5f64.to_be_bytes());
+        bytes.push(3);
+
+        let error = AttributeModifier::read(bytes.as_slice(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
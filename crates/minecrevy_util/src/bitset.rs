@@ -0,0 +1,188 @@
+//! A growable set of bits, encoded as a length-prefixed array of longs.
+
+use std::io;
+
+use minecrevy_io::{args::IntArgs, McRead, McWrite};
+
+/// A growable set of bits, encoded as a varint-prefixed array of longs, where
+/// bit `i` is stored in long `i / 64`, at bit `i % 64`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Default)]
+pub struct BitSet(Vec<u64>);
+
+impl BitSet {
+    /// Creates a new, empty [`BitSet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the given bit is set.
+    pub fn get(&self, bit: usize) -> bool {
+        self.0
+            .get(bit / 64)
+            .is_some_and(|word| word & (1 << (bit % 64)) != 0)
+    }
+
+    /// Sets the given bit, growing the set if needed.
+    pub fn set(&mut self, bit: usize) {
+        let word = bit / 64;
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        self.0[word] |= 1 << (bit % 64);
+    }
+
+    /// Clears the given bit.
+    pub fn clear(&mut self, bit: usize) {
+        if let Some(word) = self.0.get_mut(bit / 64) {
+            *word &= !(1 << (bit % 64));
+        }
+        // Keep the encoding minimal, as vanilla does.
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    /// Returns the number of longs the set is encoded as.
+    pub fn len_longs(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if no bits are set.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    /// Returns an iterator over the indices of the set bits, in ascending order.
+    pub fn iter_set(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+
+    /// Creates a new [`BitSet`] with the given bits set.
+    pub fn from_indices(indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut set = Self::new();
+        for bit in indices {
+            set.set(bit);
+        }
+        set
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        Self::from_indices(iter)
+    }
+}
+
+impl McRead for BitSet {
+    type Args = ();
+
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let len = i32::read(&mut reader, IntArgs { varint: true })?;
+        let len = usize::try_from(len).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid bitset length: {len}"),
+            )
+        })?;
+
+        let mut words = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            words.push(i64::read(&mut reader, IntArgs { varint: false })? as u64);
+        }
+        Ok(Self(words))
+    }
+}
+
+impl McWrite for BitSet {
+    type Args = ();
+
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let len = i32::try_from(self.0.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        len.write(&mut writer, IntArgs { varint: true })?;
+        for &word in &self.0 {
+            (word as i64).write(&mut writer, IntArgs { varint: false })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::BitSet;
+
+    #[test]
+    fn set_and_get_across_the_word_boundary() {
+        let mut set = BitSet::new();
+        for bit in [0, 63, 64, 127] {
+            set.set(bit);
+        }
+
+        for bit in [0, 63, 64, 127] {
+            assert!(set.get(bit), "bit {bit} isn't set");
+        }
+        for bit in [1, 62, 65, 126, 128, 1000] {
+            assert!(!set.get(bit), "bit {bit} is set");
+        }
+        assert_eq!(set.len_longs(), 2);
+        assert_eq!(set.count_ones(), 4);
+        assert_eq!(set.iter_set().collect::<Vec<_>>(), [0, 63, 64, 127]);
+    }
+
+    #[test]
+    fn clear_trims_trailing_empty_words() {
+        let mut set = BitSet::from_indices([3, 64]);
+        assert_eq!(set.len_longs(), 2);
+
+        set.clear(64);
+        assert!(!set.get(64));
+        assert_eq!(set.len_longs(), 1);
+
+        set.clear(3);
+        assert!(set.is_empty());
+        assert_eq!(set.len_longs(), 0);
+        assert_eq!(set, BitSet::new());
+    }
+
+    #[test]
+    fn wire_encoding() {
+        let set = BitSet::from_indices([0, 65]);
+        let mut bytes = Vec::new();
+        set.write(&mut bytes, ()).unwrap();
+
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // two longs
+            0x02,
+            // bit 0 of the first long
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            // bit 1 of the second long
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+        ]);
+        assert_eq!(BitSet::read(bytes.as_slice(), ()).unwrap(), set);
+    }
+
+    #[test]
+    fn empty_set_is_a_zero_length() {
+        let mut bytes = Vec::new();
+        BitSet::new().write(&mut bytes, ()).unwrap();
+        assert_eq!(bytes, [0x00]);
+    }
+
+    #[test]
+    fn negative_length_is_rejected() {
+        let error = BitSet::read([0xFF, 0xFF, 0xFF, 0xFF, 0x0F].as_slice(), ()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
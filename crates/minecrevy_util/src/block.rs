@@ -0,0 +1,343 @@
+//! Block states in their textual form, such as `minecraft:oak_stairs[facing=north,half=top]`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::key::{Key, KeyError};
+
+/// A block and the values of its properties, as written in commands and
+/// structure files, such as `minecraft:oak_stairs[facing=north,half=top]`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct BlockState {
+    /// The key of the block, such as `minecraft:oak_stairs`.
+    pub block: Key,
+    /// The values of the block's properties, keyed by property name.
+    pub properties: BTreeMap<String, String>,
+}
+
+impl BlockState {
+    /// Creates a new [`BlockState`] for the given block, without any properties.
+    pub fn new(block: Key) -> Self {
+        Self {
+            block,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Parses a [`BlockState`] in the form `block[name=value,...]`, where the
+    /// bracketed properties are optional.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block key is invalid, or the properties are
+    /// malformed or repeated.
+    pub fn parse(state: &str) -> Result<Self, BlockStateError> {
+        let (block, properties) = match state.split_once('[') {
+            Some((block, rest)) => {
+                // Nothing may follow the closing bracket.
+                let (properties, _) = rest
+                    .split_once(']')
+                    .filter(|(properties, trailing)| {
+                        trailing.is_empty() && !properties.contains('[')
+                    })
+                    .ok_or_else(|| BlockStateError::Malformed(state.to_owned()))?;
+                (block, Some(properties))
+            }
+            None => (state, None),
+        };
+
+        let mut this = Self::new(Key::parse(block)?);
+        for property in properties.into_iter().flat_map(|p| p.split(',')) {
+            // Allow `block[]`, as vanilla does.
+            if property.is_empty() && properties == Some("") {
+                continue;
+            }
+
+            let (name, value) = property
+                .split_once('=')
+                .filter(|(name, value)| !name.is_empty() && !value.is_empty())
+                .ok_or_else(|| BlockStateError::Malformed(state.to_owned()))?;
+            if this
+                .properties
+                .insert(name.to_owned(), value.to_owned())
+                .is_some()
+            {
+                return Err(BlockStateError::DuplicateProperty(name.to_owned()));
+            }
+        }
+
+        Ok(this)
+    }
+
+    /// Returns the value of the given property, if set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+
+    /// Validates this block state against the properties its block declares
+    /// in the given [`BlockDefinitions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block isn't defined, a property isn't declared
+    /// by the block, or a property has a value that isn't allowed.
+    pub fn validate(&self, blocks: &BlockDefinitions) -> Result<(), BlockStateError> {
+        let definition = blocks
+            .get(&self.block)
+            .ok_or_else(|| BlockStateError::UnknownBlock(self.block.clone()))?;
+
+        for (name, value) in &self.properties {
+            let Some(values) = definition.properties.get(name) else {
+                return Err(BlockStateError::UnknownProperty(name.clone()));
+            };
+            if !values.contains(value) {
+                return Err(BlockStateError::InvalidValue {
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for BlockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.block)?;
+        if self.properties.is_empty() {
+            return Ok(());
+        }
+
+        write!(f, "[")?;
+        for (i, (name, value)) in self.properties.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{name}={value}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl FromStr for BlockState {
+    type Err = BlockStateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A block and the properties its states can have, such as `minecraft:oak_stairs`
+/// with a `facing` property of `north`, `south`, `west`, or `east`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct BlockDefinition {
+    /// The key of the block, such as `minecraft:oak_stairs`.
+    pub block: Key,
+    /// The allowed values of each of the block's properties, keyed by
+    /// property name.
+    pub properties: BTreeMap<String, Vec<String>>,
+}
+
+impl BlockDefinition {
+    /// Creates a new [`BlockDefinition`] for the given block, without any
+    /// properties.
+    pub fn new(block: Key) -> Self {
+        Self {
+            block,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Declares a property of the block with its allowed values.
+    pub fn with_property<'a>(
+        mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        self.properties
+            .insert(name.into(), values.into_iter().map(str::to_owned).collect());
+        self
+    }
+}
+
+/// The [`BlockDefinition`]s of all known blocks, keyed by block.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct BlockDefinitions(HashMap<Key, BlockDefinition>);
+
+impl BlockDefinitions {
+    /// Creates a new, empty [`BlockDefinitions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the given [`BlockDefinition`], replacing any previous
+    /// definition of the same block.
+    pub fn insert(&mut self, definition: BlockDefinition) {
+        self.0.insert(definition.block.clone(), definition);
+    }
+
+    /// Returns the [`BlockDefinition`] of the given block, if defined.
+    pub fn get(&self, block: &Key) -> Option<&BlockDefinition> {
+        self.0.get(block)
+    }
+}
+
+impl FromIterator<BlockDefinition> for BlockDefinitions {
+    fn from_iter<T: IntoIterator<Item = BlockDefinition>>(iter: T) -> Self {
+        let mut blocks = Self::new();
+        for definition in iter {
+            blocks.insert(definition);
+        }
+        blocks
+    }
+}
+
+/// Error type for invalid [`BlockState`]s.
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum BlockStateError {
+    /// The block key is invalid.
+    #[error(transparent)]
+    InvalidBlock(#[from] KeyError),
+    /// The bracketed properties are malformed.
+    #[error("malformed block state: {0:?}")]
+    Malformed(String),
+    /// A property was given more than once.
+    #[error("duplicate block state property: {0:?}")]
+    DuplicateProperty(String),
+    /// The block isn't defined.
+    #[error("unknown block: {0}")]
+    UnknownBlock(Key),
+    /// A property isn't declared by the block.
+    #[error("unknown block state property: {0:?}")]
+    UnknownProperty(String),
+    /// A property has a value that isn't allowed by the block.
+    #[error("invalid value {value:?} for block state property {name:?}")]
+    InvalidValue {
+        /// The name of the property.
+        name: String,
+        /// The invalid value.
+        value: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockDefinition, BlockDefinitions, BlockState, BlockStateError};
+    use crate::key::Key;
+
+    fn blocks() -> BlockDefinitions {
+        [
+            BlockDefinition::new(Key::minecraft("stone").unwrap()),
+            BlockDefinition::new(Key::minecraft("oak_stairs").unwrap())
+                .with_property("facing", ["north", "south", "west", "east"])
+                .with_property("half", ["top", "bottom"]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn parse() {
+        let state = BlockState::parse("minecraft:oak_stairs[half=top,facing=north]").unwrap();
+        assert_eq!(state.block, Key::minecraft("oak_stairs").unwrap());
+        assert_eq!(state.get("facing"), Some("north"));
+        assert_eq!(state.get("half"), Some("top"));
+        assert_eq!(state.get("waterlogged"), None);
+
+        let stone = BlockState::new(Key::minecraft("stone").unwrap());
+        assert_eq!(BlockState::parse("stone"), Ok(stone.clone()));
+        assert_eq!(BlockState::parse("minecraft:stone[]"), Ok(stone));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for state in [
+            "minecraft:stone",
+            "minecraft:oak_stairs[facing=north,half=top]",
+            "custom:block[a=1]",
+        ] {
+            let parsed: BlockState = state.parse().unwrap();
+            assert_eq!(parsed.to_string(), state);
+            assert_eq!(BlockState::parse(&parsed.to_string()), Ok(parsed));
+        }
+
+        // Properties are printed in sorted order.
+        let parsed = BlockState::parse("oak_stairs[half=top,facing=north]").unwrap();
+        assert_eq!(
+            parsed.to_string(),
+            "minecraft:oak_stairs[facing=north,half=top]"
+        );
+    }
+
+    #[test]
+    fn parse_malformed() {
+        for state in [
+            "stone[",
+            "stone[facing=north",
+            "stone[facing]",
+            "stone[=north]",
+            "stone[facing=]",
+            "stone[facing=north,]",
+            "stone[facing=north]]",
+            "stone[facing=north]x",
+            "stone[facing=north][half=top]",
+            "stone[facing=[north]",
+        ] {
+            assert_eq!(
+                BlockState::parse(state),
+                Err(BlockStateError::Malformed(state.to_owned())),
+                "{state:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(matches!(
+            BlockState::parse("Stone[facing=north]"),
+            Err(BlockStateError::InvalidBlock(_)),
+        ));
+        assert_eq!(
+            BlockState::parse("stone[facing=north,facing=south]"),
+            Err(BlockStateError::DuplicateProperty("facing".to_owned())),
+        );
+    }
+
+    #[test]
+    fn validate_declared() {
+        let blocks = blocks();
+        for state in ["minecraft:stone", "oak_stairs[facing=west,half=top]"] {
+            assert_eq!(BlockState::parse(state).unwrap().validate(&blocks), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_undeclared() {
+        let blocks = blocks();
+        let validate = |state: &str| BlockState::parse(state).unwrap().validate(&blocks);
+
+        assert_eq!(
+            validate("minecraft:dirt"),
+            Err(BlockStateError::UnknownBlock(
+                Key::minecraft("dirt").unwrap()
+            )),
+        );
+        assert_eq!(
+            validate("stone[facing=north]"),
+            Err(BlockStateError::UnknownProperty("facing".to_owned())),
+        );
+        assert_eq!(
+            validate("oak_stairs[facing=up]"),
+            Err(BlockStateError::InvalidValue {
+                name: "facing".to_owned(),
+                value: "up".to_owned(),
+            }),
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! The appearance of boss bars shown at the top of a player's screen.
+
+/// The color of a boss bar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum BossBarColor {
+    /// A pink bar, as used by default for custom boss bars.
+    #[default]
+    Pink,
+    /// A blue bar.
+    Blue,
+    /// A red bar.
+    Red,
+    /// A green bar.
+    Green,
+    /// A yellow bar.
+    Yellow,
+    /// A purple bar, as used by the wither and the ender dragon.
+    Purple,
+    /// A white bar.
+    White,
+}
+
+impl BossBarColor {
+    /// Returns the [`BossBarColor`] with the given protocol ID, or [`None`] if
+    /// the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Pink),
+            1 => Some(Self::Blue),
+            2 => Some(Self::Red),
+            3 => Some(Self::Green),
+            4 => Some(Self::Yellow),
+            5 => Some(Self::Purple),
+            6 => Some(Self::White),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`BossBarColor`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::Pink => 0,
+            Self::Blue => 1,
+            Self::Red => 2,
+            Self::Green => 3,
+            Self::Yellow => 4,
+            Self::Purple => 5,
+            Self::White => 6,
+        }
+    }
+}
+
+/// The number of notches a boss bar is divided into.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum BossBarDivision {
+    /// A solid bar with no notches.
+    #[default]
+    None,
+    /// A bar divided into 6 notches.
+    Notches6,
+    /// A bar divided into 10 notches.
+    Notches10,
+    /// A bar divided into 12 notches.
+    Notches12,
+    /// A bar divided into 20 notches.
+    Notches20,
+}
+
+impl BossBarDivision {
+    /// Returns the [`BossBarDivision`] with the given protocol ID, or [`None`]
+    /// if the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::None),
+            1 => Some(Self::Notches6),
+            2 => Some(Self::Notches10),
+            3 => Some(Self::Notches12),
+            4 => Some(Self::Notches20),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`BossBarDivision`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::None => 0,
+            Self::Notches6 => 1,
+            Self::Notches10 => 2,
+            Self::Notches12 => 3,
+            Self::Notches20 => 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BossBarColor, BossBarDivision};
+
+    #[test]
+    fn color_ids() {
+        for (color, id) in [
+            (BossBarColor::Pink, 0),
+            (BossBarColor::Blue, 1),
+            (BossBarColor::Red, 2),
+            (BossBarColor::Green, 3),
+            (BossBarColor::Yellow, 4),
+            (BossBarColor::Purple, 5),
+            (BossBarColor::White, 6),
+        ] {
+            assert_eq!(color.to_id(), id);
+            assert_eq!(BossBarColor::from_id(id), Some(color));
+        }
+        assert_eq!(BossBarColor::from_id(7), None);
+    }
+
+    #[test]
+    fn division_ids() {
+        for (division, id) in [
+            (BossBarDivision::None, 0),
+            (BossBarDivision::Notches6, 1),
+            (BossBarDivision::Notches10, 2),
+            (BossBarDivision::Notches12, 3),
+            (BossBarDivision::Notches20, 4),
+        ] {
+            assert_eq!(division.to_id(), id);
+            assert_eq!(BossBarDivision::from_id(id), Some(division));
+        }
+        assert_eq!(BossBarDivision::from_id(5), None);
+    }
+}
@@ -0,0 +1,80 @@
+//! Helpers for charging bows and crossbows.
+
+/// The power of a drawn bow, in the range `0.0..=1.0`.
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+pub struct BowCharge(pub f32);
+
+impl BowCharge {
+    /// A fully drawn bow.
+    pub const FULL: Self = Self(1.0);
+
+    /// The minimum power a bow must be drawn to for it to fire an arrow.
+    pub const MIN_POWER: f32 = 0.1;
+
+    /// Returns the power of a bow that has been drawn for the given number of
+    /// ticks, using the vanilla formula.
+    ///
+    /// Returns `0.0` if the bow hasn't been drawn for long enough to fire.
+    pub fn from_ticks(ticks: i32) -> f32 {
+        let seconds = ticks.max(0) as f32 / 20.0;
+        let power = ((seconds * seconds + seconds * 2.0) / 3.0).min(1.0);
+
+        if power < Self::MIN_POWER {
+            0.0
+        } else {
+            power
+        }
+    }
+
+    /// Returns `true` if the bow is drawn far enough to fire an arrow.
+    pub fn can_fire(self) -> bool {
+        self.0 >= Self::MIN_POWER
+    }
+
+    /// Returns `true` if the bow is fully drawn, which makes arrows critical.
+    pub fn is_full(self) -> bool {
+        self.0 >= Self::FULL.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BowCharge;
+
+    #[test]
+    fn from_ticks_below_min_power() {
+        assert_eq!(BowCharge::from_ticks(0), 0.0);
+        // 2 ticks give a power of 0.07, which is too weak to fire.
+        assert_eq!(BowCharge::from_ticks(2), 0.0);
+        assert!(!BowCharge(BowCharge::from_ticks(2)).can_fire());
+    }
+
+    #[test]
+    fn from_ticks_at_min_power() {
+        // 3 ticks give a power of 0.1075, just past the cutoff.
+        let power = BowCharge::from_ticks(3);
+        assert!((power - 0.1075).abs() < 1e-6, "{power}");
+        assert!(BowCharge(power).can_fire());
+        assert!(!BowCharge(power).is_full());
+    }
+
+    #[test]
+    fn from_ticks_partial() {
+        let power = BowCharge::from_ticks(10);
+        assert!((power - 1.25 / 3.0).abs() < 1e-6, "{power}");
+    }
+
+    #[test]
+    fn from_ticks_full() {
+        assert_eq!(BowCharge::from_ticks(20), 1.0);
+        assert!(BowCharge(BowCharge::from_ticks(20)).is_full());
+    }
+
+    #[test]
+    fn from_ticks_clamps() {
+        assert_eq!(BowCharge::from_ticks(21), 1.0);
+        assert_eq!(BowCharge::from_ticks(i32::MAX), 1.0);
+        assert_eq!(BowCharge::from_ticks(-1), 0.0);
+        assert_eq!(BowCharge::from_ticks(i32::MIN), 0.0);
+    }
+}
@@ -0,0 +1,286 @@
+//! RGB colors and the 16 named chat colors.
+
+use std::fmt;
+
+/// A 24-bit RGB color.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct Color {
+    /// The red component.
+    pub r: u8,
+    /// The green component.
+    pub g: u8,
+    /// The blue component.
+    pub b: u8,
+}
+
+impl Color {
+    /// Creates a new [`Color`] from the given components.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Creates a new [`Color`] from the lower 24 bits of the given integer,
+    /// in `0xRRGGBB` order.
+    pub const fn from_rgb(rgb: u32) -> Self {
+        Self::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    /// Returns this color as an integer in `0xRRGGBB` order.
+    pub const fn to_rgb(self) -> u32 {
+        (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+
+    /// Parses a color from a 6-digit hex string, with or without a leading `#`.
+    ///
+    /// Returns [`None`] if the string is not a valid hex color.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        u32::from_str_radix(hex, 16).ok().map(Self::from_rgb)
+    }
+
+    /// Returns this color as a lowercase hex string with a leading `#`,
+    /// such as `#ff5555`.
+    pub fn to_hex(self) -> String {
+        format!("#{:06x}", self.to_rgb())
+    }
+
+    /// Returns the [`NamedColor`] closest to this color.
+    ///
+    /// Differences in brightness count more than differences in hue, so that
+    /// a saturated color such as `#ff0000` picks the bright [`NamedColor::Red`]
+    /// rather than [`NamedColor::DarkRed`].
+    pub fn nearest_named(self) -> NamedColor {
+        NamedColor::ALL
+            .into_iter()
+            .min_by_key(|named| self.distance(named.color()))
+            .unwrap_or_default()
+    }
+
+    /// Linearly interpolates between this color and `other`.
+    ///
+    /// A `t` of `0.0` returns this color, and a `t` of `1.0` returns `other`.
+    /// Values of `t` outside of `0.0..=1.0` are clamped.
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+
+        Self::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+        )
+    }
+
+    /// Returns the squared euclidean distance between this color and `other`,
+    /// plus twice the squared difference in their brightest components.
+    fn distance(self, other: Color) -> u32 {
+        let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+        let brightness = |c: Color| c.r.max(c.g).max(c.b);
+        d(self.r, other.r)
+            + d(self.g, other.g)
+            + d(self.b, other.b)
+            + 2 * d(brightness(self), brightness(other))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:06x}", self.to_rgb())
+    }
+}
+
+impl From<NamedColor> for Color {
+    fn from(named: NamedColor) -> Self {
+        named.color()
+    }
+}
+
+/// One of the 16 named colors supported by legacy chat formatting.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum NamedColor {
+    /// `#000000`
+    Black,
+    /// `#0000aa`
+    DarkBlue,
+    /// `#00aa00`
+    DarkGreen,
+    /// `#00aaaa`
+    DarkAqua,
+    /// `#aa0000`
+    DarkRed,
+    /// `#aa00aa`
+    DarkPurple,
+    /// `#ffaa00`
+    Gold,
+    /// `#aaaaaa`
+    Gray,
+    /// `#555555`
+    DarkGray,
+    /// `#5555ff`
+    Blue,
+    /// `#55ff55`
+    Green,
+    /// `#55ffff`
+    Aqua,
+    /// `#ff5555`
+    Red,
+    /// `#ff55ff`
+    LightPurple,
+    /// `#ffff55`
+    Yellow,
+    /// `#ffffff`
+    #[default]
+    White,
+}
+
+impl NamedColor {
+    /// All named colors, in the order of their legacy formatting codes.
+    pub const ALL: [NamedColor; 16] = [
+        Self::Black,
+        Self::DarkBlue,
+        Self::DarkGreen,
+        Self::DarkAqua,
+        Self::DarkRed,
+        Self::DarkPurple,
+        Self::Gold,
+        Self::Gray,
+        Self::DarkGray,
+        Self::Blue,
+        Self::Green,
+        Self::Aqua,
+        Self::Red,
+        Self::LightPurple,
+        Self::Yellow,
+        Self::White,
+    ];
+
+    /// Returns the [`Color`] this named color is rendered as.
+    pub const fn color(self) -> Color {
+        Color::from_rgb(match self {
+            Self::Black => 0x000000,
+            Self::DarkBlue => 0x0000AA,
+            Self::DarkGreen => 0x00AA00,
+            Self::DarkAqua => 0x00AAAA,
+            Self::DarkRed => 0xAA0000,
+            Self::DarkPurple => 0xAA00AA,
+            Self::Gold => 0xFFAA00,
+            Self::Gray => 0xAAAAAA,
+            Self::DarkGray => 0x555555,
+            Self::Blue => 0x5555FF,
+            Self::Green => 0x55FF55,
+            Self::Aqua => 0x55FFFF,
+            Self::Red => 0xFF5555,
+            Self::LightPurple => 0xFF55FF,
+            Self::Yellow => 0xFFFF55,
+            Self::White => 0xFFFFFF,
+        })
+    }
+
+    /// Returns the name of this color as used in text components, such as `dark_red`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Black => "black",
+            Self::DarkBlue => "dark_blue",
+            Self::DarkGreen => "dark_green",
+            Self::DarkAqua => "dark_aqua",
+            Self::DarkRed => "dark_red",
+            Self::DarkPurple => "dark_purple",
+            Self::Gold => "gold",
+            Self::Gray => "gray",
+            Self::DarkGray => "dark_gray",
+            Self::Blue => "blue",
+            Self::Green => "green",
+            Self::Aqua => "aqua",
+            Self::Red => "red",
+            Self::LightPurple => "light_purple",
+            Self::Yellow => "yellow",
+            Self::White => "white",
+        }
+    }
+
+    /// Returns the named color with the given text component name, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    /// Returns the legacy formatting code of this color, such as `c` for [`NamedColor::Red`].
+    pub const fn code(self) -> char {
+        match self {
+            Self::Black => '0',
+            Self::DarkBlue => '1',
+            Self::DarkGreen => '2',
+            Self::DarkAqua => '3',
+            Self::DarkRed => '4',
+            Self::DarkPurple => '5',
+            Self::Gold => '6',
+            Self::Gray => '7',
+            Self::DarkGray => '8',
+            Self::Blue => '9',
+            Self::Green => 'a',
+            Self::Aqua => 'b',
+            Self::Red => 'c',
+            Self::LightPurple => 'd',
+            Self::Yellow => 'e',
+            Self::White => 'f',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, NamedColor};
+
+    #[test]
+    fn from_hex() {
+        let color = Color::new(0xFF, 0x55, 0x00);
+        assert_eq!(Color::from_hex("#ff5500"), Some(color));
+        assert_eq!(Color::from_hex("ff5500"), Some(color));
+        assert_eq!(Color::from_hex("#FF5500"), Some(color));
+        assert_eq!(color.to_hex(), "#ff5500");
+    }
+
+    #[test]
+    fn from_hex_rejects_bad_input() {
+        for hex in [
+            "", "#", "#fff", "#ff550", "#ff55000", "#gg5500", "+ff550", "##ff5500",
+        ] {
+            assert_eq!(Color::from_hex(hex), None, "{hex:?}");
+        }
+    }
+
+    #[test]
+    fn named_colors_are_nearest_to_themselves() {
+        for named in NamedColor::ALL {
+            assert_eq!(named.color().nearest_named(), named);
+        }
+    }
+
+    #[test]
+    fn pure_colors_pick_the_bright_variant() {
+        assert_eq!(Color::from_rgb(0xFF0000).nearest_named(), NamedColor::Red);
+        assert_eq!(Color::from_rgb(0x00FF00).nearest_named(), NamedColor::Green);
+        assert_eq!(Color::from_rgb(0x0000FF).nearest_named(), NamedColor::Blue);
+        assert_eq!(
+            Color::from_rgb(0x800000).nearest_named(),
+            NamedColor::DarkRed
+        );
+        assert_eq!(Color::from_rgb(0xFF8000).nearest_named(), NamedColor::Gold);
+    }
+
+    #[test]
+    fn lerp() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        assert_eq!(black.lerp(white, 0.0), black);
+        assert_eq!(black.lerp(white, 0.5), Color::new(128, 128, 128));
+        assert_eq!(black.lerp(white, 1.0), white);
+        assert_eq!(white.lerp(black, 0.5), Color::new(128, 128, 128));
+        // t is clamped.
+        assert_eq!(black.lerp(white, -1.0), black);
+        assert_eq!(black.lerp(white, 2.0), white);
+    }
+}
@@ -0,0 +1,112 @@
+//! Conversions between block, chunk, and region coordinates.
+//!
+//! Every conversion rounds towards negative infinity, so the block at `-1`
+//! belongs to the chunk at `-1` rather than `0`.
+
+use glam::{IVec2, IVec3};
+
+/// The width of a chunk, in blocks.
+pub const CHUNK_WIDTH: i32 = 16;
+/// The width of a region, in chunks.
+pub const REGION_WIDTH: i32 = 32;
+
+/// Returns the X and Z coordinates of the chunk containing the given block.
+pub fn block_to_chunk(block: IVec3) -> IVec2 {
+    IVec2::new(
+        block.x.div_euclid(CHUNK_WIDTH),
+        block.z.div_euclid(CHUNK_WIDTH),
+    )
+}
+
+/// Returns the position of the given block within its 16x16x16 chunk section,
+/// from `0` to `15` on each axis.
+pub fn block_to_local(block: IVec3) -> IVec3 {
+    block.rem_euclid(IVec3::splat(CHUNK_WIDTH))
+}
+
+/// Returns the index from the bottom of the world of the chunk section
+/// containing the given block height, for a world whose lowest block is at
+/// `min_y`.
+pub fn block_to_section(y: i32, min_y: i32) -> i32 {
+    (y - min_y).div_euclid(CHUNK_WIDTH)
+}
+
+/// Returns the position of the block at the lowest corner of the given chunk,
+/// at the given height.
+pub fn chunk_to_block(chunk: IVec2, y: i32) -> IVec3 {
+    IVec3::new(chunk.x * CHUNK_WIDTH, y, chunk.y * CHUNK_WIDTH)
+}
+
+/// Returns the X and Z coordinates of the region containing the given chunk.
+pub fn chunk_to_region(chunk: IVec2) -> IVec2 {
+    chunk.div_euclid(IVec2::splat(REGION_WIDTH))
+}
+
+/// Returns the position of the given chunk within its region, from `0` to
+/// `31` on each axis.
+pub fn chunk_to_local(chunk: IVec2) -> IVec2 {
+    chunk.rem_euclid(IVec2::splat(REGION_WIDTH))
+}
+
+/// Returns the X and Z coordinates of the region containing the given block.
+pub fn block_to_region(block: IVec3) -> IVec2 {
+    chunk_to_region(block_to_chunk(block))
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{IVec2, IVec3};
+
+    use super::{
+        block_to_chunk, block_to_local, block_to_region, block_to_section, chunk_to_block,
+        chunk_to_local, chunk_to_region,
+    };
+
+    #[test]
+    fn block_to_chunk_rounds_down() {
+        assert_eq!(block_to_chunk(IVec3::new(0, 0, 15)), IVec2::new(0, 0));
+        assert_eq!(block_to_chunk(IVec3::new(16, 0, -1)), IVec2::new(1, -1));
+        assert_eq!(block_to_chunk(IVec3::new(-16, 0, -17)), IVec2::new(-1, -2));
+    }
+
+    #[test]
+    fn block_to_local_wraps_negatives() {
+        assert_eq!(block_to_local(IVec3::new(17, 18, 19)), IVec3::new(1, 2, 3));
+        assert_eq!(
+            block_to_local(IVec3::new(-1, -64, -17)),
+            IVec3::new(15, 0, 15)
+        );
+    }
+
+    #[test]
+    fn block_to_section_is_relative_to_min_y() {
+        assert_eq!(block_to_section(-64, -64), 0);
+        assert_eq!(block_to_section(-49, -64), 0);
+        assert_eq!(block_to_section(-48, -64), 1);
+        assert_eq!(block_to_section(0, -64), 4);
+        assert_eq!(block_to_section(-65, -64), -1);
+    }
+
+    #[test]
+    fn chunk_to_block_is_the_lowest_corner() {
+        assert_eq!(
+            chunk_to_block(IVec2::new(2, -3), -64),
+            IVec3::new(32, -64, -48)
+        );
+        for chunk in [IVec2::new(0, 0), IVec2::new(-1, 5), IVec2::new(-7, -8)] {
+            assert_eq!(block_to_chunk(chunk_to_block(chunk, 0)), chunk);
+            assert_eq!(
+                block_to_chunk(chunk_to_block(chunk, 0) - IVec3::new(1, 0, 1)),
+                chunk - IVec2::ONE
+            );
+        }
+    }
+
+    #[test]
+    fn regions_round_down() {
+        assert_eq!(chunk_to_region(IVec2::new(31, 32)), IVec2::new(0, 1));
+        assert_eq!(chunk_to_region(IVec2::new(-1, -33)), IVec2::new(-1, -2));
+        assert_eq!(chunk_to_local(IVec2::new(-1, 33)), IVec2::new(31, 1));
+        assert_eq!(block_to_region(IVec3::new(-1, 0, 512)), IVec2::new(-1, 1));
+    }
+}
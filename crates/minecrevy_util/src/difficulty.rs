@@ -0,0 +1,111 @@
+//! The [`Difficulty`] of a world.
+
+use std::io;
+
+use minecrevy_io::{error::DecodeError, McRead, McWrite};
+
+/// The difficulty of a world.
+///
+/// Encoded as an unsigned byte holding its protocol ID.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum Difficulty {
+    /// Hostile mobs don't spawn and players regain health over time.
+    Peaceful,
+    /// Hostile mobs spawn and deal less damage than normal.
+    Easy,
+    /// Hostile mobs spawn and deal standard damage.
+    #[default]
+    Normal,
+    /// Hostile mobs spawn and deal more damage than normal.
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns the [`Difficulty`] with the given protocol ID, or [`None`] if
+    /// the ID is out of range.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Peaceful),
+            1 => Some(Self::Easy),
+            2 => Some(Self::Normal),
+            3 => Some(Self::Hard),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`Difficulty`].
+    pub fn to_id(self) -> u8 {
+        match self {
+            Self::Peaceful => 0,
+            Self::Easy => 1,
+            Self::Normal => 2,
+            Self::Hard => 3,
+        }
+    }
+}
+
+impl McRead for Difficulty {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = u8::read(reader, ())?;
+        Self::from_id(id).ok_or_else(|| DecodeError::invalid_discriminant::<Self>(id).into())
+    }
+}
+
+impl McWrite for Difficulty {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_id().write(writer, ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::Difficulty;
+
+    const DIFFICULTIES: [(Difficulty, u8); 4] = [
+        (Difficulty::Peaceful, 0),
+        (Difficulty::Easy, 1),
+        (Difficulty::Normal, 2),
+        (Difficulty::Hard, 3),
+    ];
+
+    #[test]
+    fn byte_values() {
+        for (difficulty, byte) in DIFFICULTIES {
+            let mut bytes = Vec::new();
+            difficulty.write(&mut bytes, ()).unwrap();
+            assert_eq!(bytes, [byte]);
+            assert_eq!(Difficulty::read([byte].as_slice(), ()).unwrap(), difficulty);
+        }
+    }
+
+    #[test]
+    fn id_round_trip() {
+        for (difficulty, id) in DIFFICULTIES {
+            assert_eq!(difficulty.to_id(), id);
+            assert_eq!(Difficulty::from_id(id), Some(difficulty));
+        }
+    }
+
+    #[test]
+    fn invalid_ids_are_none() {
+        for id in [4, 5, 0x7F, 0x80, 0xFF] {
+            assert_eq!(Difficulty::from_id(id), None);
+        }
+    }
+
+    #[test]
+    fn out_of_range_is_rejected() {
+        for byte in [4, 0xFF] {
+            let error = Difficulty::read([byte].as_slice(), ()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
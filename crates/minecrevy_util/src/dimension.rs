@@ -0,0 +1,364 @@
+//! Dimension types and biomes, as sent in registry data.
+
+use std::{borrow::Cow, io};
+
+use minecrevy_io::{nbt::CompoundWriter, McWrite};
+
+use crate::color::Color;
+
+/// An entry of the `minecraft:dimension_type` registry, which describes the
+/// rules and appearance of a dimension.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DimensionType {
+    /// The time of day that's always used in the dimension, if any.
+    pub fixed_time: Option<i64>,
+    /// Whether the dimension has sky light.
+    pub has_skylight: bool,
+    /// Whether the dimension has a bedrock ceiling.
+    pub has_ceiling: bool,
+    /// Whether water evaporates and lava spreads faster.
+    pub ultrawarm: bool,
+    /// Whether compasses and clocks work, and sleeping is possible.
+    pub natural: bool,
+    /// The scale of coordinates compared to other dimensions, for portals.
+    pub coordinate_scale: f64,
+    /// Whether players can sleep in beds rather than them exploding.
+    pub bed_works: bool,
+    /// Whether respawn anchors work rather than exploding.
+    pub respawn_anchor_works: bool,
+    /// The lowest Y coordinate of the dimension, a multiple of 16.
+    pub min_y: i32,
+    /// The height of the dimension, a multiple of 16.
+    pub height: i32,
+    /// The height that portals and chorus fruit can teleport players to.
+    pub logical_height: i32,
+    /// The block tag of blocks that burn forever, such as
+    /// `#minecraft:infiniburn_overworld`.
+    pub infiniburn: Cow<'static, str>,
+    /// The sky effects of the dimension, such as `minecraft:overworld`.
+    pub effects: Cow<'static, str>,
+    /// The base light level of the dimension, from `0.0` to `1.0`.
+    pub ambient_light: f32,
+    /// Whether piglins don't zombify.
+    pub piglin_safe: bool,
+    /// Whether raids can start.
+    pub has_raids: bool,
+    /// The light level that monsters can spawn at or below.
+    pub monster_spawn_light_level: LightLevel,
+    /// The block light level that monsters can spawn at or below.
+    pub monster_spawn_block_light_limit: i32,
+}
+
+impl DimensionType {
+    /// The vanilla `minecraft:overworld` dimension type.
+    pub const OVERWORLD: Self = Self {
+        fixed_time: None,
+        has_skylight: true,
+        has_ceiling: false,
+        ultrawarm: false,
+        natural: true,
+        coordinate_scale: 1.,
+        bed_works: true,
+        respawn_anchor_works: false,
+        min_y: -64,
+        height: 384,
+        logical_height: 384,
+        infiniburn: Cow::Borrowed("#minecraft:infiniburn_overworld"),
+        effects: Cow::Borrowed("minecraft:overworld"),
+        ambient_light: 0.,
+        piglin_safe: false,
+        has_raids: true,
+        monster_spawn_light_level: LightLevel::Uniform { min: 0, max: 7 },
+        monster_spawn_block_light_limit: 0,
+    };
+
+    /// The vanilla `minecraft:the_nether` dimension type.
+    pub const THE_NETHER: Self = Self {
+        fixed_time: Some(18000),
+        has_skylight: false,
+        has_ceiling: true,
+        ultrawarm: true,
+        natural: false,
+        coordinate_scale: 8.,
+        bed_works: false,
+        respawn_anchor_works: true,
+        min_y: 0,
+        height: 256,
+        logical_height: 128,
+        infiniburn: Cow::Borrowed("#minecraft:infiniburn_nether"),
+        effects: Cow::Borrowed("minecraft:the_nether"),
+        ambient_light: 0.1,
+        piglin_safe: true,
+        has_raids: false,
+        monster_spawn_light_level: LightLevel::Constant(7),
+        monster_spawn_block_light_limit: 15,
+    };
+
+    /// The vanilla `minecraft:the_end` dimension type.
+    pub const THE_END: Self = Self {
+        fixed_time: Some(6000),
+        has_skylight: false,
+        has_ceiling: false,
+        ultrawarm: false,
+        natural: false,
+        coordinate_scale: 1.,
+        bed_works: false,
+        respawn_anchor_works: false,
+        min_y: 0,
+        height: 256,
+        logical_height: 256,
+        infiniburn: Cow::Borrowed("#minecraft:infiniburn_end"),
+        effects: Cow::Borrowed("minecraft:the_end"),
+        ambient_light: 0.,
+        piglin_safe: false,
+        has_raids: true,
+        monster_spawn_light_level: LightLevel::Uniform { min: 0, max: 7 },
+        monster_spawn_block_light_limit: 0,
+    };
+
+    /// Returns the number of 16-block sections in a chunk column of the
+    /// dimension.
+    pub fn sections(&self) -> usize {
+        (self.height / 16) as usize
+    }
+}
+
+impl McWrite for DimensionType {
+    type Args = ();
+
+    /// Writes the dimension type as a network NBT compound.
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let mut nbt = CompoundWriter::root(writer)?;
+        if let Some(fixed_time) = self.fixed_time {
+            nbt.long("fixed_time", fixed_time)?;
+        }
+        nbt.bool("has_skylight", self.has_skylight)?
+            .bool("has_ceiling", self.has_ceiling)?
+            .bool("ultrawarm", self.ultrawarm)?
+            .bool("natural", self.natural)?
+            .double("coordinate_scale", self.coordinate_scale)?
+            .bool("bed_works", self.bed_works)?
+            .bool("respawn_anchor_works", self.respawn_anchor_works)?
+            .int("min_y", self.min_y)?
+            .int("height", self.height)?
+            .int("logical_height", self.logical_height)?
+            .string("infiniburn", &self.infiniburn)?
+            .string("effects", &self.effects)?
+            .float("ambient_light", self.ambient_light)?
+            .bool("piglin_safe", self.piglin_safe)?
+            .bool("has_raids", self.has_raids)?;
+        match self.monster_spawn_light_level {
+            LightLevel::Constant(level) => {
+                nbt.int("monster_spawn_light_level", level)?;
+            }
+            LightLevel::Uniform { min, max } => {
+                let mut provider = nbt.compound("monster_spawn_light_level")?;
+                provider
+                    .string("type", "minecraft:uniform")?
+                    .int("min_inclusive", min)?
+                    .int("max_inclusive", max)?;
+                provider.end()?;
+            }
+        }
+        nbt.int(
+            "monster_spawn_block_light_limit",
+            self.monster_spawn_block_light_limit,
+        )?;
+        nbt.end()
+    }
+}
+
+/// A light level that's either constant or picked at random.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum LightLevel {
+    /// Always the given light level.
+    Constant(i32),
+    /// A light level picked uniformly from `min..=max`.
+    Uniform {
+        /// The minimum light level, inclusive.
+        min: i32,
+        /// The maximum light level, inclusive.
+        max: i32,
+    },
+}
+
+/// An entry of the `minecraft:worldgen/biome` registry, which describes the
+/// climate and colors of a biome.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Biome {
+    /// Whether it rains or snows in the biome.
+    pub has_precipitation: bool,
+    /// The temperature of the biome, which decides whether it snows.
+    pub temperature: f32,
+    /// The humidity of the biome, which affects grass and foliage colors.
+    pub downfall: f32,
+    /// The colors of the biome.
+    pub effects: BiomeEffects,
+}
+
+impl Biome {
+    /// The vanilla `minecraft:plains` biome.
+    pub const PLAINS: Self = Self {
+        has_precipitation: true,
+        temperature: 0.8,
+        downfall: 0.4,
+        effects: BiomeEffects {
+            fog_color: Color::from_rgb(0xC0D8FF),
+            sky_color: Color::from_rgb(0x78A7FF),
+            water_color: Color::from_rgb(0x3F76E4),
+            water_fog_color: Color::from_rgb(0x050533),
+            foliage_color: None,
+            grass_color: None,
+        },
+    };
+
+    /// The vanilla `minecraft:the_void` biome.
+    pub const THE_VOID: Self = Self {
+        has_precipitation: false,
+        temperature: 0.5,
+        downfall: 0.5,
+        effects: BiomeEffects {
+            fog_color: Color::from_rgb(0xC0D8FF),
+            sky_color: Color::from_rgb(0x7BA4FF),
+            water_color: Color::from_rgb(0x3F76E4),
+            water_fog_color: Color::from_rgb(0x050533),
+            foliage_color: None,
+            grass_color: None,
+        },
+    };
+}
+
+impl McWrite for Biome {
+    type Args = ();
+
+    /// Writes the biome as a network NBT compound.
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        let mut nbt = CompoundWriter::root(writer)?;
+        nbt.bool("has_precipitation", self.has_precipitation)?
+            .float("temperature", self.temperature)?
+            .float("downfall", self.downfall)?;
+
+        let effects = &self.effects;
+        let mut colors = nbt.compound("effects")?;
+        colors
+            .int("fog_color", effects.fog_color.to_rgb() as i32)?
+            .int("sky_color", effects.sky_color.to_rgb() as i32)?
+            .int("water_color", effects.water_color.to_rgb() as i32)?
+            .int("water_fog_color", effects.water_fog_color.to_rgb() as i32)?;
+        if let Some(color) = effects.foliage_color {
+            colors.int("foliage_color", color.to_rgb() as i32)?;
+        }
+        if let Some(color) = effects.grass_color {
+            colors.int("grass_color", color.to_rgb() as i32)?;
+        }
+        colors.end()?;
+
+        nbt.end()
+    }
+}
+
+/// The colors of a [`Biome`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct BiomeEffects {
+    /// The color of the fog.
+    pub fog_color: Color,
+    /// The color of the sky.
+    pub sky_color: Color,
+    /// The color of water.
+    pub water_color: Color,
+    /// The color of the fog underwater.
+    pub water_fog_color: Color,
+    /// The color of leaves, if overridden.
+    pub foliage_color: Option<Color>,
+    /// The color of grass, if overridden.
+    pub grass_color: Option<Color>,
+}
+
+#[cfg(test)]
+mod tests {
+    use minecrevy_io::{
+        nbt::{Compound, Tag},
+        McRead, McWrite,
+    };
+
+    use super::{Biome, DimensionType};
+
+    fn encode(value: &impl McWrite<Args = ()>) -> Compound {
+        let mut bytes = Vec::new();
+        value.write(&mut bytes, ()).unwrap();
+        let mut reader = bytes.as_slice();
+        let Tag::Compound(nbt) = Tag::read(&mut reader, ()).unwrap() else {
+            panic!("expected a compound");
+        };
+        assert!(reader.is_empty(), "trailing bytes");
+        nbt
+    }
+
+    #[test]
+    fn dimension_type_has_required_keys() {
+        let nbt = encode(&DimensionType::OVERWORLD);
+
+        for key in [
+            "has_skylight",
+            "has_ceiling",
+            "ultrawarm",
+            "natural",
+            "coordinate_scale",
+            "bed_works",
+            "respawn_anchor_works",
+            "min_y",
+            "height",
+            "logical_height",
+            "infiniburn",
+            "effects",
+            "ambient_light",
+            "piglin_safe",
+            "has_raids",
+            "monster_spawn_light_level",
+            "monster_spawn_block_light_limit",
+        ] {
+            assert!(nbt.contains_key(key), "missing {key}");
+        }
+        assert!(!nbt.contains_key("fixed_time"));
+
+        assert_eq!(nbt["has_skylight"], Tag::Byte(1));
+        assert_eq!(nbt["coordinate_scale"], Tag::Double(1.));
+        assert_eq!(nbt["min_y"], Tag::Int(-64));
+        assert_eq!(nbt["height"], Tag::Int(384));
+        assert_eq!(
+            nbt["infiniburn"],
+            Tag::from("#minecraft:infiniburn_overworld")
+        );
+        assert_eq!(nbt["ambient_light"], Tag::Float(0.));
+
+        let light = nbt["monster_spawn_light_level"].as_compound().unwrap();
+        assert_eq!(light["type"], Tag::from("minecraft:uniform"));
+        assert_eq!(light["min_inclusive"], Tag::Int(0));
+        assert_eq!(light["max_inclusive"], Tag::Int(7));
+    }
+
+    #[test]
+    fn dimension_type_fixed_time_and_constant_light() {
+        let nbt = encode(&DimensionType::THE_NETHER);
+
+        assert_eq!(nbt["fixed_time"], Tag::Long(18000));
+        assert_eq!(nbt["monster_spawn_light_level"], Tag::Int(7));
+    }
+
+    #[test]
+    fn biome_has_required_keys() {
+        let nbt = encode(&Biome::PLAINS);
+
+        assert_eq!(nbt["has_precipitation"], Tag::Byte(1));
+        assert_eq!(nbt["temperature"], Tag::Float(0.8));
+        assert_eq!(nbt["downfall"], Tag::Float(0.4));
+
+        let effects = nbt["effects"].as_compound().unwrap();
+        assert_eq!(effects["fog_color"], Tag::Int(0xC0D8FF));
+        assert_eq!(effects["sky_color"], Tag::Int(0x78A7FF));
+        assert_eq!(effects["water_color"], Tag::Int(0x3F76E4));
+        assert_eq!(effects["water_fog_color"], Tag::Int(0x050533));
+        assert!(!effects.contains_key("foliage_color"));
+        assert!(!effects.contains_key("grass_color"));
+    }
+}
@@ -0,0 +1,77 @@
+//! The six axis-aligned [`Direction`]s, such as the faces of a block.
+
+use glam::IVec3;
+
+/// One of the six axis-aligned directions, such as the face of a block.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Direction {
+    /// Towards negative Y.
+    Down,
+    /// Towards positive Y.
+    Up,
+    /// Towards negative Z.
+    North,
+    /// Towards positive Z.
+    South,
+    /// Towards negative X.
+    West,
+    /// Towards positive X.
+    East,
+}
+
+impl Direction {
+    /// All directions, in the order of their protocol IDs.
+    pub const ALL: [Direction; 6] = [
+        Self::Down,
+        Self::Up,
+        Self::North,
+        Self::South,
+        Self::West,
+        Self::East,
+    ];
+
+    /// Returns the [`Direction`] with the given protocol ID, or [`None`] if
+    /// the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| Self::ALL.get(id))
+            .copied()
+    }
+
+    /// Returns the protocol ID of this [`Direction`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::Down => 0,
+            Self::Up => 1,
+            Self::North => 2,
+            Self::South => 3,
+            Self::West => 4,
+            Self::East => 5,
+        }
+    }
+
+    /// Returns the opposite direction.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Down => Self::Up,
+            Self::Up => Self::Down,
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::West => Self::East,
+            Self::East => Self::West,
+        }
+    }
+
+    /// Returns the unit offset of this direction.
+    pub fn offset(self) -> IVec3 {
+        match self {
+            Self::Down => IVec3::NEG_Y,
+            Self::Up => IVec3::Y,
+            Self::North => IVec3::NEG_Z,
+            Self::South => IVec3::Z,
+            Self::West => IVec3::NEG_X,
+            Self::East => IVec3::X,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! The [`GameMode`] of a player.
+
+use std::io;
+
+use minecrevy_io::{error::DecodeError, McRead, McWrite};
+
+/// The game mode of a player.
+///
+/// Encoded as an unsigned byte holding its protocol ID, except in the player
+/// info packet, which encodes it as a VarInt.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum GameMode {
+    /// Players can break and place blocks, take damage, and must gather resources.
+    #[default]
+    Survival,
+    /// Players can fly, are invulnerable, and have unlimited resources.
+    Creative,
+    /// Players can interact with the world, but not freely break or place blocks.
+    Adventure,
+    /// Players can fly through blocks and cannot interact with the world.
+    Spectator,
+}
+
+impl GameMode {
+    /// Returns the [`GameMode`] with the given protocol ID, or [`None`] if the
+    /// ID is out of range.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Survival),
+            1 => Some(Self::Creative),
+            2 => Some(Self::Adventure),
+            3 => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`GameMode`].
+    pub fn to_id(self) -> u8 {
+        match self {
+            Self::Survival => 0,
+            Self::Creative => 1,
+            Self::Adventure => 2,
+            Self::Spectator => 3,
+        }
+    }
+}
+
+/// The game mode a player had before their current one, if any.
+///
+/// Unlike [`GameMode`], this can represent the absence of a game mode, which
+/// the protocol encodes as `-1`.
+///
+/// Encoded as a signed byte holding its protocol ID.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum PreviousGameMode {
+    /// The player had no previous game mode.
+    #[default]
+    None,
+    /// The player's previous game mode.
+    Some(GameMode),
+}
+
+impl PreviousGameMode {
+    /// Returns the [`PreviousGameMode`] with the given protocol ID, or [`None`]
+    /// if the ID is out of range.
+    ///
+    /// An ID of `-1` returns [`PreviousGameMode::None`].
+    pub fn from_id(id: i8) -> Option<Self> {
+        match id {
+            -1 => Some(Self::None),
+            _ => u8::try_from(id)
+                .ok()
+                .and_then(GameMode::from_id)
+                .map(Self::Some),
+        }
+    }
+
+    /// Returns the protocol ID of this [`PreviousGameMode`].
+    ///
+    /// [`PreviousGameMode::None`] returns `-1`.
+    pub fn to_id(self) -> i8 {
+        match self {
+            Self::None => -1,
+            // Game mode IDs are always in the range 0..=3.
+            Self::Some(mode) => mode.to_id() as i8,
+        }
+    }
+}
+
+impl McRead for GameMode {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = u8::read(reader, ())?;
+        Self::from_id(id).ok_or_else(|| DecodeError::invalid_discriminant::<Self>(id).into())
+    }
+}
+
+impl McWrite for GameMode {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_id().write(writer, ())
+    }
+}
+
+impl McRead for PreviousGameMode {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = i8::read(reader, ())?;
+        Self::from_id(id).ok_or_else(|| DecodeError::invalid_discriminant::<Self>(id).into())
+    }
+}
+
+impl McWrite for PreviousGameMode {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_id().write(writer, ())
+    }
+}
+
+impl From<GameMode> for PreviousGameMode {
+    fn from(mode: GameMode) -> Self {
+        Self::Some(mode)
+    }
+}
+
+impl From<Option<GameMode>> for PreviousGameMode {
+    fn from(mode: Option<GameMode>) -> Self {
+        mode.map_or(Self::None, Self::Some)
+    }
+}
+
+impl From<PreviousGameMode> for Option<GameMode> {
+    fn from(mode: PreviousGameMode) -> Self {
+        match mode {
+            PreviousGameMode::None => None,
+            PreviousGameMode::Some(mode) => Some(mode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::{GameMode, PreviousGameMode};
+
+    const MODES: [(GameMode, u8); 4] = [
+        (GameMode::Survival, 0),
+        (GameMode::Creative, 1),
+        (GameMode::Adventure, 2),
+        (GameMode::Spectator, 3),
+    ];
+
+    #[test]
+    fn byte_values() {
+        for (mode, byte) in MODES {
+            let mut bytes = Vec::new();
+            mode.write(&mut bytes, ()).unwrap();
+            assert_eq!(bytes, [byte]);
+            assert_eq!(GameMode::read([byte].as_slice(), ()).unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn previous_byte_values() {
+        let modes = MODES.map(|(mode, byte)| (PreviousGameMode::Some(mode), byte));
+        for (mode, byte) in [(PreviousGameMode::None, 0xFF)].into_iter().chain(modes) {
+            let mut bytes = Vec::new();
+            mode.write(&mut bytes, ()).unwrap();
+            assert_eq!(bytes, [byte]);
+            assert_eq!(PreviousGameMode::read([byte].as_slice(), ()).unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn id_round_trip() {
+        for (mode, id) in MODES {
+            assert_eq!(mode.to_id(), id);
+            assert_eq!(GameMode::from_id(id), Some(mode));
+            assert_eq!(PreviousGameMode::from_id(id as i8), Some(mode.into()));
+        }
+        assert_eq!(PreviousGameMode::None.to_id(), -1);
+        assert_eq!(PreviousGameMode::from_id(-1), Some(PreviousGameMode::None));
+    }
+
+    #[test]
+    fn invalid_ids_are_none() {
+        for id in [4, 5, 0x7F, 0x80, 0xFF] {
+            assert_eq!(GameMode::from_id(id), None);
+        }
+        for id in [4, 0x7F, -2, i8::MIN] {
+            assert_eq!(PreviousGameMode::from_id(id), None);
+        }
+    }
+
+    #[test]
+    fn out_of_range_is_rejected() {
+        for byte in [4, 0xFF] {
+            let error = GameMode::read([byte].as_slice(), ()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+        for byte in [4, 0xFE] {
+            let error = PreviousGameMode::read([byte].as_slice(), ()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! The [`Hand`] a player uses to interact with the world.
+
+use std::io;
+
+use minecrevy_io::{args::IntArgs, error::DecodeError, McRead, McWrite};
+
+/// One of the two hands of a player.
+///
+/// Encoded as a VarInt holding its protocol ID.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum Hand {
+    /// The player's main hand.
+    #[default]
+    MainHand,
+    /// The player's off hand.
+    OffHand,
+}
+
+impl Hand {
+    /// Returns the [`Hand`] with the given protocol ID, or [`None`] if the ID
+    /// is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::MainHand),
+            1 => Some(Self::OffHand),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`Hand`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::MainHand => 0,
+            Self::OffHand => 1,
+        }
+    }
+
+    /// Returns the opposite hand.
+    pub fn other(self) -> Self {
+        match self {
+            Self::MainHand => Self::OffHand,
+            Self::OffHand => Self::MainHand,
+        }
+    }
+}
+
+impl McRead for Hand {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = i32::read(reader, IntArgs { varint: true })?;
+        Self::from_id(id).ok_or_else(|| DecodeError::invalid_discriminant::<Self>(id).into())
+    }
+}
+
+impl McWrite for Hand {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_id().write(writer, IntArgs { varint: true })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::Hand;
+
+    #[test]
+    fn byte_values() {
+        for (hand, byte) in [(Hand::MainHand, 0), (Hand::OffHand, 1)] {
+            let mut bytes = Vec::new();
+            hand.write(&mut bytes, ()).unwrap();
+            assert_eq!(bytes, [byte]);
+            assert_eq!(Hand::read([byte].as_slice(), ()).unwrap(), hand);
+        }
+    }
+
+    #[test]
+    fn out_of_range_is_rejected() {
+        // 2, then -1 as a VarInt.
+        for bytes in [&[0x02][..], &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]] {
+            let error = Hand::read(bytes, ()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn other_swaps_hands() {
+        assert_eq!(Hand::MainHand.other(), Hand::OffHand);
+        assert_eq!(Hand::OffHand.other(), Hand::MainHand);
+        assert_eq!(Hand::MainHand.other().other(), Hand::MainHand);
+    }
+}
@@ -0,0 +1,460 @@
+//! Stacks of items, as held in inventory slots.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+};
+
+use minecrevy_io::{args::IntArgs, McRead, McWrite};
+
+/// A stack of items of a single type, as held in an inventory slot.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Default)]
+pub struct ItemStack {
+    /// The protocol ID of the item type.
+    pub item: i32,
+    /// The number of items in the stack.
+    pub count: u8,
+    /// The data components that differ from the item type's defaults.
+    pub components: ItemComponents,
+}
+
+impl ItemStack {
+    /// The maximum stack size of most item types.
+    pub const DEFAULT_MAX_STACK_SIZE: u8 = 64;
+
+    /// Creates a new [`ItemStack`] of the given item type, without any
+    /// component changes.
+    pub fn new(item: i32, count: u8) -> Self {
+        Self {
+            item,
+            count,
+            components: ItemComponents::default(),
+        }
+    }
+
+    /// Returns `true` if the stack has no items.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns `true` if this stack and `other` hold the same item type with
+    /// the same components, so that they can be merged.
+    pub fn can_stack_with(&self, other: &ItemStack) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.item == other.item
+            && self.components == other.components
+    }
+
+    /// Removes up to `amount` items from this stack, returning them as a new
+    /// stack, which holds at most the given maximum stack size of the item type.
+    pub fn split(&mut self, amount: u8, max_stack_size: u8) -> ItemStack {
+        let amount = amount.min(self.count).min(max_stack_size);
+        self.count -= amount;
+
+        ItemStack {
+            count: amount,
+            ..self.clone()
+        }
+    }
+
+    /// Moves as many items as possible from `other` into this stack, without
+    /// exceeding the given maximum stack size of the item type.
+    ///
+    /// Returns the number of items moved, which is `0` if the stacks can't be
+    /// merged.
+    pub fn merge(&mut self, other: &mut ItemStack, max_stack_size: u8) -> u8 {
+        if !self.can_stack_with(other) {
+            return 0;
+        }
+
+        let amount = other.count.min(max_stack_size.saturating_sub(self.count));
+        self.count += amount;
+        other.count -= amount;
+        amount
+    }
+}
+
+impl McRead for ItemStack {
+    type Args = ();
+
+    /// Reads an item stack in the slot format, where an empty stack is encoded
+    /// as a count of `0`.
+    ///
+    /// Only components with a fixed layout can be read, see
+    /// [`ItemComponents::read_data`].
+    fn read(mut reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let count = i32::read(&mut reader, IntArgs { varint: true })?;
+        let count = u8::try_from(count).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid item count: {count}"),
+            )
+        })?;
+        if count == 0 {
+            return Ok(Self::default());
+        }
+
+        let item = i32::read(&mut reader, IntArgs { varint: true })?;
+        let added = i32::read(&mut reader, IntArgs { varint: true })?;
+        let removed = i32::read(&mut reader, IntArgs { varint: true })?;
+
+        let mut components = ItemComponents::default();
+        for _ in 0..added {
+            let ty = i32::read(&mut reader, IntArgs { varint: true })?;
+            let data = ItemComponents::read_data(&mut reader, ty)?;
+            components.added.insert(ty, data);
+        }
+        for _ in 0..removed {
+            components
+                .removed
+                .insert(i32::read(&mut reader, IntArgs { varint: true })?);
+        }
+
+        Ok(Self {
+            item,
+            count,
+            components,
+        })
+    }
+}
+
+impl McWrite for ItemStack {
+    type Args = ();
+
+    /// Writes the item stack in the slot format, where an empty stack is
+    /// encoded as a count of `0`.
+    fn write(&self, mut writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        i32::from(self.count).write(&mut writer, IntArgs { varint: true })?;
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        self.item.write(&mut writer, IntArgs { varint: true })?;
+        let len =
+            |n: usize| i32::try_from(n).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e));
+        len(self.components.added.len())?.write(&mut writer, IntArgs { varint: true })?;
+        len(self.components.removed.len())?.write(&mut writer, IntArgs { varint: true })?;
+        for (ty, data) in &self.components.added {
+            ty.write(&mut writer, IntArgs { varint: true })?;
+            writer.write_all(data)?;
+        }
+        for ty in &self.components.removed {
+            ty.write(&mut writer, IntArgs { varint: true })?;
+        }
+        Ok(())
+    }
+}
+
+/// The data components of an [`ItemStack`] that differ from its item type's
+/// defaults, such as a custom name or enchantments.
+///
+/// Component data is kept in its encoded form, keyed by component type ID.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Default)]
+pub struct ItemComponents {
+    /// The components added to or overridden on the stack.
+    pub added: BTreeMap<i32, Vec<u8>>,
+    /// The default components removed from the stack.
+    pub removed: BTreeSet<i32>,
+}
+
+impl ItemComponents {
+    /// Returns `true` if the stack uses its item type's default components.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Reads the encoded data of a component of the given type.
+    ///
+    /// Only components with a fixed layout are supported: those holding a
+    /// single varint (such as `minecraft:max_stack_size` or `minecraft:damage`),
+    /// a single boolean, or no data at all.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component type isn't supported.
+    pub fn read_data(mut reader: impl io::Read, ty: i32) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        match ty {
+            // max_stack_size, max_damage, damage, rarity, custom_model_data, repair_cost
+            1 | 2 | 3 | 8 | 13 | 16 => {
+                i32::read(&mut reader, IntArgs { varint: true })?
+                    .write(&mut data, IntArgs { varint: true })?;
+            }
+            // enchantment_glint_override
+            18 => bool::read(&mut reader, ())?.write(&mut data, ())?,
+            // hide_additional_tooltip, hide_tooltip, creative_slot_lock
+            14 | 15 | 17 => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("reading item component {ty} is not supported"),
+                ))
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// The contents of an inventory slot, which may be empty.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Default)]
+pub struct Slot(pub Option<ItemStack>);
+
+impl Slot {
+    /// An empty slot.
+    pub const EMPTY: Self = Self(None);
+}
+
+impl From<ItemStack> for Slot {
+    fn from(stack: ItemStack) -> Self {
+        Self((!stack.is_empty()).then_some(stack))
+    }
+}
+
+/// Arguments for reading and writing [`Slot`]s.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub struct SlotArgs {
+    /// Whether to use the layout from before 1.20.5, where a slot is a present
+    /// boolean followed by the item ID, count, and an NBT tag.
+    ///
+    /// Only stacks without components are supported in this layout.
+    pub legacy: bool,
+}
+
+impl McRead for Slot {
+    type Args = SlotArgs;
+
+    fn read(mut reader: impl io::Read, args: Self::Args) -> io::Result<Self> {
+        if !args.legacy {
+            return ItemStack::read(reader, ()).map(Self::from);
+        }
+
+        if !bool::read(&mut reader, ())? {
+            return Ok(Self::EMPTY);
+        }
+        let item = i32::read(&mut reader, IntArgs { varint: true })?;
+        let count = u8::read(&mut reader, ())?;
+        if u8::read(&mut reader, ())? != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "reading legacy item NBT is not supported",
+            ));
+        }
+
+        Ok(Self::from(ItemStack::new(item, count)))
+    }
+}
+
+impl McWrite for Slot {
+    type Args = SlotArgs;
+
+    fn write(&self, mut writer: impl io::Write, args: Self::Args) -> io::Result<()> {
+        let stack = self.0.as_ref().filter(|stack| !stack.is_empty());
+        if !args.legacy {
+            return match stack {
+                Some(stack) => stack.write(writer, ()),
+                None => 0u8.write(writer, ()),
+            };
+        }
+
+        let Some(stack) = stack else {
+            return false.write(writer, ());
+        };
+        if !stack.components.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "writing legacy item NBT is not supported",
+            ));
+        }
+        true.write(&mut writer, ())?;
+        stack.item.write(&mut writer, IntArgs { varint: true })?;
+        stack.count.write(&mut writer, ())?;
+        // An empty NBT tag.
+        0u8.write(&mut writer, ())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::{ItemComponents, ItemStack, Slot, SlotArgs};
+
+    const MODERN: SlotArgs = SlotArgs { legacy: false };
+    const LEGACY: SlotArgs = SlotArgs { legacy: true };
+
+    fn write_slot(slot: &Slot, args: SlotArgs) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        slot.write(&mut bytes, args).unwrap();
+        bytes
+    }
+
+    /// A stack with a `minecraft:damage` component of the given value.
+    fn damaged(count: u8, damage: u8) -> ItemStack {
+        let mut stack = ItemStack::new(1, count);
+        stack.components.added.insert(3, vec![damage]);
+        stack
+    }
+
+    #[test]
+    fn merge_moves_all_items() {
+        let mut stack = ItemStack::new(1, 10);
+        let mut other = ItemStack::new(1, 20);
+        assert_eq!(
+            stack.merge(&mut other, ItemStack::DEFAULT_MAX_STACK_SIZE),
+            20
+        );
+        assert_eq!(stack.count, 30);
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn merge_is_clamped_to_max_stack_size() {
+        let mut stack = ItemStack::new(1, 50);
+        let mut other = ItemStack::new(1, 20);
+        assert_eq!(
+            stack.merge(&mut other, ItemStack::DEFAULT_MAX_STACK_SIZE),
+            14
+        );
+        assert_eq!(stack.count, 64);
+        assert_eq!(other.count, 6);
+
+        // A full or oversized stack accepts nothing.
+        let mut full = ItemStack::new(1, 20);
+        assert_eq!(full.merge(&mut other, 16), 0);
+        assert_eq!((full.count, other.count), (20, 6));
+    }
+
+    #[test]
+    fn merge_requires_matching_stacks() {
+        let mut stack = ItemStack::new(1, 10);
+
+        let mut other_item = ItemStack::new(2, 10);
+        assert_eq!(stack.merge(&mut other_item, 64), 0);
+        assert_eq!(other_item.count, 10);
+
+        let mut empty = ItemStack::new(1, 0);
+        assert_eq!(empty.merge(&mut stack, 64), 0);
+        assert_eq!(stack.count, 10);
+    }
+
+    #[test]
+    fn components_must_be_equal_to_stack() {
+        assert!(damaged(1, 5).can_stack_with(&damaged(1, 5)));
+        assert!(!damaged(1, 5).can_stack_with(&damaged(1, 6)));
+        assert!(!damaged(1, 5).can_stack_with(&ItemStack::new(1, 1)));
+
+        let mut removed = ItemStack::new(1, 1);
+        removed.components.removed.insert(3);
+        assert!(!removed.can_stack_with(&ItemStack::new(1, 1)));
+        assert!(removed.can_stack_with(&removed.clone()));
+
+        let mut stack = damaged(10, 5);
+        let mut other = damaged(10, 6);
+        assert_eq!(stack.merge(&mut other, 64), 0);
+        assert_eq!((stack.count, other.count), (10, 10));
+    }
+
+    #[test]
+    fn split_keeps_components() {
+        let mut stack = damaged(10, 5);
+        let split = stack.split(4, ItemStack::DEFAULT_MAX_STACK_SIZE);
+        assert_eq!(split.components, stack.components);
+        assert_ne!(split.components, ItemComponents::default());
+        assert!(split.can_stack_with(&stack));
+    }
+
+    #[test]
+    fn split_takes_requested_amount() {
+        let mut stack = ItemStack::new(1, 10);
+        let split = stack.split(4, ItemStack::DEFAULT_MAX_STACK_SIZE);
+        assert_eq!(split.count, 4);
+        assert_eq!(stack.count, 6);
+    }
+
+    #[test]
+    fn split_is_clamped_to_count() {
+        let mut stack = ItemStack::new(1, 3);
+        let split = stack.split(10, ItemStack::DEFAULT_MAX_STACK_SIZE);
+        assert_eq!(split.count, 3);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn split_is_clamped_to_max_stack_size() {
+        // An oversized stack of an item that stacks to 16.
+        let mut stack = ItemStack::new(1, 40);
+        let split = stack.split(32, 16);
+        assert_eq!(split.count, 16);
+        assert_eq!(stack.count, 24);
+    }
+
+    #[test]
+    fn empty_slot_layout() {
+        assert_eq!(write_slot(&Slot::EMPTY, MODERN), [0x00]);
+        assert_eq!(write_slot(&Slot::EMPTY, LEGACY), [0x00]);
+        // A stack of zero items is empty too.
+        assert_eq!(
+            write_slot(&Slot(Some(ItemStack::new(1, 0))), MODERN),
+            [0x00]
+        );
+
+        assert_eq!(Slot::read([0x00].as_slice(), MODERN).unwrap(), Slot::EMPTY);
+        assert_eq!(Slot::read([0x00].as_slice(), LEGACY).unwrap(), Slot::EMPTY);
+    }
+
+    #[test]
+    fn slot_without_components_layout() {
+        let slot = Slot::from(ItemStack::new(300, 64));
+
+        // count, item ID as a two-byte varint, no added or removed components.
+        let bytes = write_slot(&slot, MODERN);
+        assert_eq!(bytes, [0x40, 0xAC, 0x02, 0x00, 0x00]);
+        assert_eq!(Slot::read(bytes.as_slice(), MODERN).unwrap(), slot);
+
+        // present, item ID, count, and an empty NBT tag.
+        let bytes = write_slot(&slot, LEGACY);
+        assert_eq!(bytes, [0x01, 0xAC, 0x02, 0x40, 0x00]);
+        assert_eq!(Slot::read(bytes.as_slice(), LEGACY).unwrap(), slot);
+    }
+
+    #[test]
+    fn slot_with_components_layout() {
+        let mut stack = damaged(2, 5);
+        // max_stack_size
+        stack.components.added.insert(1, vec![16]);
+        // hide_tooltip, rarity
+        stack.components.removed.extend([15, 8]);
+        let slot = Slot::from(stack);
+
+        let bytes = write_slot(&slot, MODERN);
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            // count, item ID, 2 added, 2 removed
+            0x02, 0x01, 0x02, 0x02,
+            // added components, in order of type
+            0x01, 0x10,
+            0x03, 0x05,
+            // removed components, in order of type
+            0x08, 0x0F,
+        ]);
+        assert_eq!(Slot::read(bytes.as_slice(), MODERN).unwrap(), slot);
+    }
+
+    #[test]
+    fn legacy_slot_with_components_is_unsupported() {
+        let error = Slot::from(damaged(1, 5))
+            .write(&mut Vec::new(), LEGACY)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn negative_count_is_rejected() {
+        let error = Slot::read([0xFF, 0xFF, 0xFF, 0xFF, 0x0F].as_slice(), MODERN).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,132 @@
+//! Namespaced identifiers, such as `minecraft:stone`.
+
+use std::{fmt, io, str::FromStr};
+
+use minecrevy_io::{args::StringArgs, McRead, McWrite};
+use thiserror::Error;
+
+/// A namespaced identifier, such as `minecraft:stone`.
+///
+/// Also known as a resource location or identifier.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Key {
+    namespace: String,
+    path: String,
+}
+
+impl Key {
+    /// The default namespace, used when parsing keys without one.
+    pub const MINECRAFT: &'static str = "minecraft";
+
+    /// Creates a new [`Key`] from the given namespace and path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the namespace or path contain invalid characters.
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Result<Self, KeyError> {
+        let namespace = namespace.into();
+        let path = path.into();
+
+        if namespace.is_empty() || !namespace.chars().all(is_valid_namespace_char) {
+            return Err(KeyError::InvalidNamespace(namespace));
+        }
+        if path.is_empty() || !path.chars().all(is_valid_path_char) {
+            return Err(KeyError::InvalidPath(path));
+        }
+
+        Ok(Self { namespace, path })
+    }
+
+    /// Creates a new [`Key`] in the [`Key::MINECRAFT`] namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path contains invalid characters.
+    pub fn minecraft(path: impl Into<String>) -> Result<Self, KeyError> {
+        Self::new(Self::MINECRAFT, path)
+    }
+
+    /// Parses a [`Key`] in the form `namespace:path`, or `path` for keys in
+    /// the [`Key::MINECRAFT`] namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the namespace or path contain invalid characters.
+    pub fn parse(key: &str) -> Result<Self, KeyError> {
+        match key.split_once(':') {
+            Some((namespace, path)) => Self::new(namespace, path),
+            None => Self::minecraft(key),
+        }
+    }
+
+    /// Returns the namespace of this key, such as `minecraft`.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the path of this key, such as `stone`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl FromStr for Key {
+    type Err = KeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl McRead for Key {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let key = String::read(
+            reader,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )?;
+
+        Self::parse(&key).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl McWrite for Key {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_string().write(
+            writer,
+            StringArgs {
+                max_len: Some(32767),
+            },
+        )
+    }
+}
+
+/// Error type for invalid [`Key`]s.
+#[derive(Error, Clone, PartialEq, Eq, Debug)]
+pub enum KeyError {
+    /// The namespace contains characters other than `[a-z0-9._-]`, or is empty.
+    #[error("invalid key namespace: {0:?}")]
+    InvalidNamespace(String),
+    /// The path contains characters other than `[a-z0-9._-/]`, or is empty.
+    #[error("invalid key path: {0:?}")]
+    InvalidPath(String),
+}
+
+fn is_valid_namespace_char(c: char) -> bool {
+    matches!(c, 'a'..='z' | '0'..='9' | '.' | '_' | '-')
+}
+
+fn is_valid_path_char(c: char) -> bool {
+    is_valid_namespace_char(c) || c == '/'
+}
@@ -0,0 +1,55 @@
+//! Common Minecraft data types and utilities shared between Minecrevy crates.
+
+#![warn(missing_docs)]
+
+pub mod prelude {
+    //! Re-exports important traits and types.
+
+    pub use crate::{
+        attribute::{AttributeModifier, Operation},
+        bitset::BitSet,
+        block::{BlockDefinition, BlockDefinitions, BlockState},
+        boss_bar::{BossBarColor, BossBarDivision},
+        bow::BowCharge,
+        color::{Color, NamedColor},
+        difficulty::Difficulty,
+        dimension::{Biome, DimensionType},
+        direction::Direction,
+        game_mode::{GameMode, PreviousGameMode},
+        hand::Hand,
+        item::{ItemStack, Slot},
+        key::Key,
+        pose::Pose,
+        potion::PotionEffect,
+        registry::Registry,
+        scoreboard::{DisplaySlot, ObjectiveRenderType},
+        sound::SoundCategory,
+        tag::{TagEntry, Tags},
+        team::{CollisionRule, NameTagVisibility},
+        ticks::{DayPhase, Ticks, TimeOfDay},
+    };
+}
+
+pub mod attribute;
+pub mod bitset;
+pub mod block;
+pub mod boss_bar;
+pub mod bow;
+pub mod color;
+pub mod coords;
+pub mod difficulty;
+pub mod dimension;
+pub mod direction;
+pub mod game_mode;
+pub mod hand;
+pub mod item;
+pub mod key;
+pub mod num;
+pub mod pose;
+pub mod potion;
+pub mod registry;
+pub mod scoreboard;
+pub mod sound;
+pub mod tag;
+pub mod team;
+pub mod ticks;
@@ -0,0 +1,48 @@
+//! Conversions between the fixed-point numbers sent in packets and the
+//! floating-point numbers they represent.
+
+/// The number of fixed-point velocity units in one block per tick.
+pub const VELOCITY_SCALE: f64 = 8000.0;
+/// The largest velocity the client accepts on each axis, in blocks per tick.
+pub const MAX_VELOCITY: f64 = 3.9;
+
+/// Returns the given velocity in blocks per tick as a fixed-point number in
+/// units of 1/8000 block per tick, as sent in packets.
+///
+/// The velocity is clamped to the range the client accepts, from
+/// `-`[`MAX_VELOCITY`] to [`MAX_VELOCITY`], and `NaN` becomes `0`.
+pub fn velocity_to_fixed(velocity: f64) -> i16 {
+    (velocity.clamp(-MAX_VELOCITY, MAX_VELOCITY) * VELOCITY_SCALE) as i16
+}
+
+/// Returns the given fixed-point velocity in units of 1/8000 block per tick
+/// in blocks per tick.
+pub fn fixed_to_velocity(fixed: i16) -> f64 {
+    f64::from(fixed) / VELOCITY_SCALE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fixed_to_velocity, velocity_to_fixed, MAX_VELOCITY};
+
+    #[test]
+    fn velocity_round_trip() {
+        for fixed in [0, 1, -1, 400, -8000, 31200, -31200] {
+            assert_eq!(velocity_to_fixed(fixed_to_velocity(fixed)), fixed);
+        }
+        assert_eq!(velocity_to_fixed(1.0), 8000);
+        assert_eq!(velocity_to_fixed(-0.05), -400);
+        assert_eq!(fixed_to_velocity(4000), 0.5);
+    }
+
+    #[test]
+    fn velocity_is_clamped() {
+        let max = velocity_to_fixed(MAX_VELOCITY);
+        assert_eq!(max, 31200);
+        assert_eq!(velocity_to_fixed(10.0), max);
+        assert_eq!(velocity_to_fixed(f64::INFINITY), max);
+        assert_eq!(velocity_to_fixed(-10.0), -max);
+        assert_eq!(velocity_to_fixed(f64::NEG_INFINITY), -max);
+        assert_eq!(velocity_to_fixed(f64::NAN), 0);
+    }
+}
@@ -0,0 +1,109 @@
+//! The [`Pose`] of an entity, which determines its hitbox and animation.
+
+/// The pose of an entity, which determines its hitbox and animation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum Pose {
+    /// The default pose.
+    #[default]
+    Standing,
+    /// Gliding with an elytra.
+    FallFlying,
+    /// Sleeping in a bed.
+    Sleeping,
+    /// Swimming, or crawling through a one block tall gap.
+    Swimming,
+    /// Spinning from a riptide trident.
+    SpinAttack,
+    /// Sneaking.
+    Sneaking,
+    /// Long jumping, used by goats.
+    LongJumping,
+    /// Dying.
+    Dying,
+    /// Croaking, used by frogs.
+    Croaking,
+    /// Using its tongue, used by frogs.
+    UsingTongue,
+    /// Sitting, used by camels.
+    Sitting,
+    /// Roaring, used by wardens.
+    Roaring,
+    /// Sniffing, used by wardens.
+    Sniffing,
+    /// Emerging from the ground, used by wardens.
+    Emerging,
+    /// Digging into the ground, used by wardens.
+    Digging,
+    /// Sliding, used by breezes.
+    Sliding,
+    /// Shooting, used by breezes.
+    Shooting,
+    /// Inhaling, used by breezes.
+    Inhaling,
+}
+
+impl Pose {
+    /// All poses, in the order of their protocol IDs.
+    pub const ALL: [Pose; 18] = [
+        Self::Standing,
+        Self::FallFlying,
+        Self::Sleeping,
+        Self::Swimming,
+        Self::SpinAttack,
+        Self::Sneaking,
+        Self::LongJumping,
+        Self::Dying,
+        Self::Croaking,
+        Self::UsingTongue,
+        Self::Sitting,
+        Self::Roaring,
+        Self::Sniffing,
+        Self::Emerging,
+        Self::Digging,
+        Self::Sliding,
+        Self::Shooting,
+        Self::Inhaling,
+    ];
+
+    /// Returns the [`Pose`] with the given protocol ID, or [`None`] if the ID
+    /// is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| Self::ALL.get(id))
+            .copied()
+    }
+
+    /// Returns the protocol ID of this [`Pose`].
+    pub fn to_id(self) -> i32 {
+        self as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pose;
+
+    #[test]
+    fn ids_match_their_position() {
+        for (id, pose) in (0..).zip(Pose::ALL) {
+            assert_eq!(pose.to_id(), id);
+            assert_eq!(Pose::from_id(id), Some(pose));
+        }
+    }
+
+    #[test]
+    fn wire_values() {
+        assert_eq!(Pose::Standing.to_id(), 0);
+        assert_eq!(Pose::Sleeping.to_id(), 2);
+        assert_eq!(Pose::Sneaking.to_id(), 5);
+        assert_eq!(Pose::Sitting.to_id(), 10);
+        assert_eq!(Pose::Inhaling.to_id(), 17);
+    }
+
+    #[test]
+    fn out_of_range_is_rejected() {
+        assert_eq!(Pose::from_id(18), None);
+        assert_eq!(Pose::from_id(-1), None);
+    }
+}
@@ -0,0 +1,75 @@
+//! Status effects applied to entities, such as by potions.
+
+use crate::key::Key;
+
+/// A status effect applied to an entity, such as `minecraft:strength`.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PotionEffect {
+    /// The type of the effect.
+    pub id: Key,
+    /// The amplifier of the effect, where `0` is level I.
+    pub amplifier: u8,
+    /// The remaining duration of the effect in ticks, or
+    /// [`PotionEffect::INFINITE`].
+    pub duration: i32,
+    /// Whether the effect comes from a beacon or conduit, which makes its
+    /// particles less visible.
+    pub ambient: bool,
+    /// Whether the effect shows particles around the entity.
+    pub show_particles: bool,
+    /// Whether the effect shows an icon in the inventory and HUD.
+    pub show_icon: bool,
+}
+
+impl PotionEffect {
+    /// The duration of an effect that never runs out.
+    pub const INFINITE: i32 = -1;
+
+    /// Creates a new [`PotionEffect`] of the given type, amplifier, and
+    /// duration in ticks, that shows both particles and an icon.
+    pub fn new(id: Key, amplifier: u8, duration: i32) -> Self {
+        Self {
+            id,
+            amplifier,
+            duration,
+            ambient: false,
+            show_particles: true,
+            show_icon: true,
+        }
+    }
+
+    /// Returns `true` if the effect never runs out.
+    pub fn is_infinite(&self) -> bool {
+        self.duration == Self::INFINITE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PotionEffect;
+    use crate::key::Key;
+
+    #[test]
+    fn new_shows_particles_and_icon() {
+        let effect = PotionEffect::new(Key::minecraft("speed").unwrap(), 1, 600);
+
+        assert_eq!(effect.amplifier, 1);
+        assert_eq!(effect.duration, 600);
+        assert!(!effect.ambient);
+        assert!(effect.show_particles);
+        assert!(effect.show_icon);
+        assert!(!effect.is_infinite());
+    }
+
+    #[test]
+    fn infinite_duration() {
+        let effect = PotionEffect::new(
+            Key::minecraft("night_vision").unwrap(),
+            0,
+            PotionEffect::INFINITE,
+        );
+
+        assert_eq!(effect.duration, -1);
+        assert!(effect.is_infinite());
+    }
+}
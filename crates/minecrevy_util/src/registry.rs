@@ -0,0 +1,132 @@
+//! A bidirectional mapping between numeric protocol IDs and [`Key`]s.
+
+use std::collections::HashMap;
+
+use crate::key::Key;
+
+/// A bidirectional mapping between numeric protocol IDs and [`Key`]s, such as
+/// for blocks or items.
+///
+/// IDs are assigned sequentially from `0`, in insertion order.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Registry {
+    keys: Vec<Key>,
+    ids: HashMap<Key, i32>,
+}
+
+impl Registry {
+    /// Creates a new, empty [`Registry`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the given [`Key`], returning its ID.
+    ///
+    /// If the key is already registered, its existing ID is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registry already contains [`i32::MAX`] entries.
+    pub fn insert(&mut self, key: Key) -> i32 {
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+
+        let id = i32::try_from(self.keys.len()).expect("registry is full");
+        self.keys.push(key.clone());
+        self.ids.insert(key, id);
+        id
+    }
+
+    /// Returns the ID of the given [`Key`], if registered.
+    pub fn id_of(&self, key: &Key) -> Option<i32> {
+        self.ids.get(key).copied()
+    }
+
+    /// Returns the [`Key`] with the given ID, if registered.
+    pub fn key_of(&self, id: i32) -> Option<&Key> {
+        usize::try_from(id).ok().and_then(|id| self.keys.get(id))
+    }
+
+    /// Returns the number of registered keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no keys are registered.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns an iterator over the registered IDs and [`Key`]s, in order of ID.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &Key)> {
+        (0..).zip(&self.keys)
+    }
+}
+
+impl FromIterator<Key> for Registry {
+    fn from_iter<T: IntoIterator<Item = Key>>(iter: T) -> Self {
+        let mut registry = Self::new();
+        for key in iter {
+            registry.insert(key);
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::key::Key;
+
+    fn key(path: &str) -> Key {
+        Key::minecraft(path).unwrap()
+    }
+
+    #[test]
+    fn ids_are_assigned_in_insertion_order() {
+        let mut registry = Registry::new();
+        assert!(registry.is_empty());
+        assert_eq!(registry.insert(key("stone")), 0);
+        assert_eq!(registry.insert(key("dirt")), 1);
+        assert_eq!(registry.insert(key("grass_block")), 2);
+        assert_eq!(registry.len(), 3);
+
+        assert_eq!(
+            registry.iter().collect::<Vec<_>>(),
+            [
+                (0, &key("stone")),
+                (1, &key("dirt")),
+                (2, &key("grass_block"))
+            ]
+        );
+    }
+
+    #[test]
+    fn ids_and_keys_map_both_ways() {
+        let registry: Registry = [key("stone"), key("dirt")].into_iter().collect();
+
+        for (id, key) in registry.iter() {
+            assert_eq!(registry.id_of(key), Some(id));
+            assert_eq!(registry.key_of(id), Some(key));
+        }
+        assert_eq!(registry.id_of(&key("air")), None);
+        assert_eq!(registry.key_of(2), None);
+        assert_eq!(registry.key_of(-1), None);
+    }
+
+    #[test]
+    fn reinserting_keeps_the_existing_id() {
+        let mut registry = Registry::new();
+        registry.insert(key("stone"));
+        registry.insert(key("dirt"));
+
+        assert_eq!(registry.insert(key("stone")), 0);
+        assert_eq!(registry.len(), 2);
+
+        let collected: Registry = [key("stone"), key("dirt"), key("stone")]
+            .into_iter()
+            .collect();
+        assert_eq!(collected, registry);
+    }
+}
@@ -0,0 +1,114 @@
+//! Where and how scoreboard objectives are displayed.
+
+use crate::color::NamedColor;
+
+/// A place on the client's screen that a scoreboard objective can be shown.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum DisplaySlot {
+    /// The player list, shown when holding the tab key.
+    List,
+    /// The sidebar on the right of the screen.
+    Sidebar,
+    /// Below the name tags of players.
+    BelowName,
+    /// The sidebar, shown only to players on a team of the given color.
+    TeamSidebar(NamedColor),
+}
+
+impl DisplaySlot {
+    /// Returns the [`DisplaySlot`] with the given protocol ID, or [`None`] if
+    /// the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::List),
+            1 => Some(Self::Sidebar),
+            2 => Some(Self::BelowName),
+            3..=18 => Some(Self::TeamSidebar(NamedColor::ALL[id as usize - 3])),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`DisplaySlot`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::List => 0,
+            Self::Sidebar => 1,
+            Self::BelowName => 2,
+            Self::TeamSidebar(color) => {
+                3 + NamedColor::ALL.iter().position(|c| *c == color).unwrap() as i32
+            }
+        }
+    }
+}
+
+/// How the scores of an objective are rendered in the player list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum ObjectiveRenderType {
+    /// Scores are shown as numbers.
+    #[default]
+    Integer,
+    /// Scores are shown as rows of hearts.
+    Hearts,
+}
+
+impl ObjectiveRenderType {
+    /// Returns the [`ObjectiveRenderType`] with the given protocol ID, or
+    /// [`None`] if the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Integer),
+            1 => Some(Self::Hearts),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`ObjectiveRenderType`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::Integer => 0,
+            Self::Hearts => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplaySlot, ObjectiveRenderType};
+    use crate::color::NamedColor;
+
+    #[test]
+    fn display_slot_ids() {
+        for (slot, id) in [
+            (DisplaySlot::List, 0),
+            (DisplaySlot::Sidebar, 1),
+            (DisplaySlot::BelowName, 2),
+            (DisplaySlot::TeamSidebar(NamedColor::Black), 3),
+            (DisplaySlot::TeamSidebar(NamedColor::Red), 15),
+            (DisplaySlot::TeamSidebar(NamedColor::White), 18),
+        ] {
+            assert_eq!(slot.to_id(), id);
+            assert_eq!(DisplaySlot::from_id(id), Some(slot));
+        }
+        assert_eq!(DisplaySlot::from_id(19), None);
+        assert_eq!(DisplaySlot::from_id(-1), None);
+    }
+
+    #[test]
+    fn team_sidebar_ids_follow_the_named_colors() {
+        for (id, color) in (3..).zip(NamedColor::ALL) {
+            assert_eq!(DisplaySlot::TeamSidebar(color).to_id(), id);
+        }
+    }
+
+    #[test]
+    fn objective_render_type_ids() {
+        for (render_type, id) in [
+            (ObjectiveRenderType::Integer, 0),
+            (ObjectiveRenderType::Hearts, 1),
+        ] {
+            assert_eq!(render_type.to_id(), id);
+            assert_eq!(ObjectiveRenderType::from_id(id), Some(render_type));
+        }
+        assert_eq!(ObjectiveRenderType::from_id(2), None);
+    }
+}
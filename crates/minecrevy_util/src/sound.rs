@@ -0,0 +1,121 @@
+//! The categories of sounds, which players control the volume of separately.
+
+use std::io;
+
+use minecrevy_io::{args::IntArgs, error::DecodeError, McRead, McWrite};
+
+/// The category of a sound, which decides which volume slider controls it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum SoundCategory {
+    /// Sounds controlled only by the master volume.
+    #[default]
+    Master,
+    /// Background music.
+    Music,
+    /// Music discs played by jukeboxes.
+    Record,
+    /// Rain and thunder.
+    Weather,
+    /// Sounds made by blocks.
+    Block,
+    /// Sounds made by hostile mobs.
+    Hostile,
+    /// Sounds made by passive and neutral mobs.
+    Neutral,
+    /// Sounds made by players.
+    Player,
+    /// Cave and underwater ambience.
+    Ambient,
+    /// Narration and voice chat.
+    Voice,
+}
+
+impl SoundCategory {
+    /// Returns the [`SoundCategory`] with the given protocol ID, or [`None`]
+    /// if the ID is out of range.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Master),
+            1 => Some(Self::Music),
+            2 => Some(Self::Record),
+            3 => Some(Self::Weather),
+            4 => Some(Self::Block),
+            5 => Some(Self::Hostile),
+            6 => Some(Self::Neutral),
+            7 => Some(Self::Player),
+            8 => Some(Self::Ambient),
+            9 => Some(Self::Voice),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol ID of this [`SoundCategory`].
+    pub fn to_id(self) -> i32 {
+        match self {
+            Self::Master => 0,
+            Self::Music => 1,
+            Self::Record => 2,
+            Self::Weather => 3,
+            Self::Block => 4,
+            Self::Hostile => 5,
+            Self::Neutral => 6,
+            Self::Player => 7,
+            Self::Ambient => 8,
+            Self::Voice => 9,
+        }
+    }
+}
+
+impl McRead for SoundCategory {
+    type Args = ();
+
+    fn read(reader: impl io::Read, (): Self::Args) -> io::Result<Self> {
+        let id = i32::read(reader, IntArgs { varint: true })?;
+        Self::from_id(id).ok_or_else(|| DecodeError::invalid_discriminant::<Self>(id).into())
+    }
+}
+
+impl McWrite for SoundCategory {
+    type Args = ();
+
+    fn write(&self, writer: impl io::Write, (): Self::Args) -> io::Result<()> {
+        self.to_id().write(writer, IntArgs { varint: true })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use minecrevy_io::{McRead, McWrite};
+
+    use super::SoundCategory;
+
+    #[test]
+    fn wire_values() {
+        for (category, id) in [
+            (SoundCategory::Master, 0),
+            (SoundCategory::Music, 1),
+            (SoundCategory::Record, 2),
+            (SoundCategory::Weather, 3),
+            (SoundCategory::Block, 4),
+            (SoundCategory::Hostile, 5),
+            (SoundCategory::Neutral, 6),
+            (SoundCategory::Player, 7),
+            (SoundCategory::Ambient, 8),
+            (SoundCategory::Voice, 9),
+        ] {
+            let mut bytes = Vec::new();
+            category.write(&mut bytes, ()).unwrap();
+            assert_eq!(bytes, [id]);
+            assert_eq!(SoundCategory::read([id].as_slice(), ()).unwrap(), category);
+        }
+    }
+
+    #[test]
+    fn out_of_range_is_rejected() {
+        assert_eq!(SoundCategory::from_id(10), None);
+        let error = SoundCategory::read([10].as_slice(), ()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
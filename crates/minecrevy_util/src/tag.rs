@@ -0,0 +1,197 @@
+//! Tags, which group keys such as blocks or items, and may reference other tags.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::key::{Key, KeyError};
+
+/// A single entry of a tag: either a key, or a reference to another tag.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum TagEntry {
+    /// A key, such as `minecraft:oak_log`.
+    Key(Key),
+    /// A reference to another tag, such as `#minecraft:oak_logs`.
+    Tag(Key),
+}
+
+impl TagEntry {
+    /// Parses a [`TagEntry`], where tag references are prefixed with `#`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is invalid.
+    pub fn parse(entry: &str) -> Result<Self, KeyError> {
+        match entry.strip_prefix('#') {
+            Some(tag) => Key::parse(tag).map(Self::Tag),
+            None => Key::parse(entry).map(Self::Key),
+        }
+    }
+}
+
+impl fmt::Display for TagEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{key}"),
+            Self::Tag(tag) => write!(f, "#{tag}"),
+        }
+    }
+}
+
+impl FromStr for TagEntry {
+    type Err = KeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A collection of tags of a single registry, such as the block tags.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Tags(HashMap<Key, Vec<TagEntry>>);
+
+impl Tags {
+    /// Creates a new, empty [`Tags`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the entries of the given tag, returning the previous entries.
+    pub fn insert(&mut self, tag: Key, entries: Vec<TagEntry>) -> Option<Vec<TagEntry>> {
+        self.0.insert(tag, entries)
+    }
+
+    /// Returns the entries of the given tag, without resolving nested tags.
+    pub fn get(&self, tag: &Key) -> Option<&[TagEntry]> {
+        self.0.get(tag).map(Vec::as_slice)
+    }
+
+    /// Returns the keys that are members of the given tag, including the
+    /// members of any tags it references, or [`None`] if the tag doesn't exist.
+    ///
+    /// Each key is only returned once, in the order it is first found.
+    /// References to unknown tags are skipped, as are references that would
+    /// form a cycle.
+    pub fn resolve(&self, tag: &Key) -> Option<Vec<Key>> {
+        let entries = self.0.get(tag)?;
+
+        let mut members = Vec::new();
+        let mut visited = vec![tag];
+        self.resolve_into(entries, &mut visited, &mut members);
+        Some(members)
+    }
+
+    fn resolve_into<'a>(
+        &'a self,
+        entries: &'a [TagEntry],
+        visited: &mut Vec<&'a Key>,
+        members: &mut Vec<Key>,
+    ) {
+        for entry in entries {
+            match entry {
+                TagEntry::Key(key) => {
+                    if !members.contains(key) {
+                        members.push(key.clone());
+                    }
+                }
+                TagEntry::Tag(tag) => {
+                    if visited.contains(&tag) {
+                        continue;
+                    }
+                    let Some(entries) = self.0.get(tag) else {
+                        continue;
+                    };
+
+                    visited.push(tag);
+                    self.resolve_into(entries, visited, members);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TagEntry, Tags};
+    use crate::key::Key;
+
+    fn key(path: &str) -> Key {
+        Key::minecraft(path).unwrap()
+    }
+
+    fn entries(entries: &[&str]) -> Vec<TagEntry> {
+        entries
+            .iter()
+            .map(|e| TagEntry::parse(e).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn entry_parse_and_display() {
+        assert_eq!(
+            TagEntry::parse("oak_log"),
+            Ok(TagEntry::Key(key("oak_log")))
+        );
+        assert_eq!(
+            TagEntry::parse("#minecraft:logs"),
+            Ok(TagEntry::Tag(key("logs")))
+        );
+        assert!(TagEntry::parse("#Not A Key").is_err());
+        assert_eq!(TagEntry::Tag(key("logs")).to_string(), "#minecraft:logs");
+        assert_eq!(
+            TagEntry::Key(key("oak_log")).to_string(),
+            "minecraft:oak_log"
+        );
+    }
+
+    #[test]
+    fn resolve_nested_tags() {
+        let mut tags = Tags::new();
+        tags.insert(key("oak_logs"), entries(&["oak_log", "oak_wood"]));
+        tags.insert(key("birch_logs"), entries(&["birch_log"]));
+        tags.insert(
+            key("logs"),
+            entries(&["#oak_logs", "#birch_logs", "crimson_stem"]),
+        );
+
+        assert_eq!(
+            tags.resolve(&key("logs")),
+            Some(vec![
+                key("oak_log"),
+                key("oak_wood"),
+                key("birch_log"),
+                key("crimson_stem"),
+            ])
+        );
+        // `get` doesn't resolve nested tags.
+        assert_eq!(tags.get(&key("logs")).unwrap().len(), 3);
+        assert_eq!(tags.resolve(&key("unknown")), None);
+    }
+
+    #[test]
+    fn resolve_skips_duplicates_and_unknown_tags() {
+        let mut tags = Tags::new();
+        tags.insert(key("a"), entries(&["stone", "dirt"]));
+        tags.insert(
+            key("b"),
+            entries(&["dirt", "#a", "#missing", "stone", "sand"]),
+        );
+
+        assert_eq!(
+            tags.resolve(&key("b")),
+            Some(vec![key("dirt"), key("stone"), key("sand")])
+        );
+    }
+
+    #[test]
+    fn resolve_terminates_on_cycles() {
+        let mut tags = Tags::new();
+        tags.insert(key("a"), entries(&["stone", "#b"]));
+        tags.insert(key("b"), entries(&["dirt", "#a"]));
+        tags.insert(key("self"), entries(&["#self", "sand"]));
+
+        assert_eq!(
+            tags.resolve(&key("a")),
+            Some(vec![key("stone"), key("dirt")])
+        );
+        assert_eq!(tags.resolve(&key("self")), Some(vec![key("sand")]));
+    }
+}
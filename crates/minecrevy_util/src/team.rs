@@ -0,0 +1,110 @@
+//! Rules for how members of a scoreboard team see and interact with others.
+
+/// Which players can see the name tags of a team's members.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum NameTagVisibility {
+    /// Name tags are shown to everyone.
+    #[default]
+    Always,
+    /// Name tags are hidden from players on other teams.
+    HideForOtherTeams,
+    /// Name tags are hidden from players on the same team.
+    HideForOwnTeam,
+    /// Name tags are hidden from everyone.
+    Never,
+}
+
+impl NameTagVisibility {
+    /// Returns the [`NameTagVisibility`] with the given protocol name, or
+    /// [`None`] if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(Self::Always),
+            "hideForOtherTeams" => Some(Self::HideForOtherTeams),
+            "hideForOwnTeam" => Some(Self::HideForOwnTeam),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol name of this [`NameTagVisibility`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::HideForOtherTeams => "hideForOtherTeams",
+            Self::HideForOwnTeam => "hideForOwnTeam",
+            Self::Never => "never",
+        }
+    }
+}
+
+/// Which entities a team's members push and are pushed by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum CollisionRule {
+    /// Members collide with everyone.
+    #[default]
+    Always,
+    /// Members only collide with entities on other teams.
+    PushOtherTeams,
+    /// Members only collide with entities on the same team.
+    PushOwnTeam,
+    /// Members don't collide with anyone.
+    Never,
+}
+
+impl CollisionRule {
+    /// Returns the [`CollisionRule`] with the given protocol name, or [`None`]
+    /// if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(Self::Always),
+            "pushOtherTeams" => Some(Self::PushOtherTeams),
+            "pushOwnTeam" => Some(Self::PushOwnTeam),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Returns the protocol name of this [`CollisionRule`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::PushOtherTeams => "pushOtherTeams",
+            Self::PushOwnTeam => "pushOwnTeam",
+            Self::Never => "never",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollisionRule, NameTagVisibility};
+
+    #[test]
+    fn name_tag_visibility_names() {
+        for (visibility, name) in [
+            (NameTagVisibility::Always, "always"),
+            (NameTagVisibility::HideForOtherTeams, "hideForOtherTeams"),
+            (NameTagVisibility::HideForOwnTeam, "hideForOwnTeam"),
+            (NameTagVisibility::Never, "never"),
+        ] {
+            assert_eq!(visibility.name(), name);
+            assert_eq!(NameTagVisibility::from_name(name), Some(visibility));
+        }
+        assert_eq!(NameTagVisibility::from_name("hide_for_own_team"), None);
+    }
+
+    #[test]
+    fn collision_rule_names() {
+        for (rule, name) in [
+            (CollisionRule::Always, "always"),
+            (CollisionRule::PushOtherTeams, "pushOtherTeams"),
+            (CollisionRule::PushOwnTeam, "pushOwnTeam"),
+            (CollisionRule::Never, "never"),
+        ] {
+            assert_eq!(rule.name(), name);
+            assert_eq!(CollisionRule::from_name(name), Some(rule));
+        }
+        assert_eq!(CollisionRule::from_name("Never"), None);
+    }
+}
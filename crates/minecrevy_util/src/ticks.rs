@@ -0,0 +1,214 @@
+//! Conversions between game ticks, Minecraft days, and real time.
+
+use std::{ops::Add, time::Duration};
+
+/// A span of game time, measured in ticks.
+///
+/// The game runs at [`Ticks::PER_SECOND`] ticks per second, and a full
+/// Minecraft day lasts [`Ticks::PER_DAY`] ticks.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Default)]
+pub struct Ticks(pub i64);
+
+impl Ticks {
+    /// The number of ticks in one real-time second.
+    pub const PER_SECOND: i64 = 20;
+    /// The number of ticks in one Minecraft day.
+    pub const PER_DAY: i64 = 24000;
+
+    /// Returns the number of ticks that elapse in the given number of seconds,
+    /// rounded to the nearest tick.
+    pub fn from_seconds(seconds: f64) -> Self {
+        Self((seconds * Self::PER_SECOND as f64).round() as i64)
+    }
+
+    /// Returns the number of seconds these ticks take to elapse.
+    pub fn as_seconds(self) -> f64 {
+        self.0 as f64 / Self::PER_SECOND as f64
+    }
+
+    /// Returns the number of ticks that elapse in the given [`Duration`],
+    /// rounded to the nearest tick.
+    pub fn from_duration(duration: Duration) -> Self {
+        Self::from_seconds(duration.as_secs_f64())
+    }
+
+    /// Returns the [`Duration`] these ticks take to elapse, or [`None`] if
+    /// the number of ticks is negative.
+    pub fn as_duration(self) -> Option<Duration> {
+        let ticks = u64::try_from(self.0).ok()?;
+        let per_second = Self::PER_SECOND as u64;
+        // Split into whole seconds first, so large tick counts can't overflow.
+        let seconds = Duration::from_secs(ticks / per_second);
+        let rest = Duration::from_millis(ticks % per_second * 1000 / per_second);
+        Some(seconds + rest)
+    }
+
+    /// Returns the number of ticks in the given number of Minecraft days.
+    pub fn from_minecraft_days(days: i64) -> Self {
+        Self(days * Self::PER_DAY)
+    }
+
+    /// Returns the number of Minecraft days these ticks span.
+    pub fn as_minecraft_days(self) -> f64 {
+        self.0 as f64 / Self::PER_DAY as f64
+    }
+
+    /// Returns the [`TimeOfDay`] these ticks correspond to, when interpreted
+    /// as the age of a world.
+    pub fn time_of_day(self) -> TimeOfDay {
+        TimeOfDay::from_ticks(self)
+    }
+}
+
+impl Add for Ticks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+/// A point in time within a single Minecraft day, in the range `0..24000`.
+///
+/// A time of `0` corresponds to 06:00 on the in-game clock.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Default)]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    /// The time of day at which the sun is at its highest point.
+    pub const NOON: Self = Self(6000);
+    /// The time of day at which the sun begins to set.
+    pub const SUNSET: Self = Self(12000);
+    /// The time of day at which the night begins.
+    pub const NIGHT: Self = Self(13000);
+    /// The time of day at which the moon is at its highest point.
+    pub const MIDNIGHT: Self = Self(18000);
+    /// The time of day at which the sun begins to rise.
+    pub const SUNRISE: Self = Self(23000);
+
+    /// Returns the [`TimeOfDay`] for the given number of [`Ticks`], wrapping
+    /// values outside of a single day.
+    pub fn from_ticks(ticks: Ticks) -> Self {
+        Self(ticks.0.rem_euclid(Ticks::PER_DAY) as u16)
+    }
+
+    /// Returns the number of [`Ticks`] since the start of the day.
+    pub fn ticks(self) -> Ticks {
+        Ticks(i64::from(self.0))
+    }
+
+    /// Returns the [`DayPhase`] this time of day falls into.
+    pub fn phase(self) -> DayPhase {
+        if self < Self::SUNSET {
+            DayPhase::Day
+        } else if self < Self::NIGHT {
+            DayPhase::Sunset
+        } else if self < Self::SUNRISE {
+            DayPhase::Night
+        } else {
+            DayPhase::Sunrise
+        }
+    }
+
+    /// Returns the hour on the in-game clock, in the range `0..24`.
+    pub fn hours(self) -> u8 {
+        ((self.0 / 1000 + 6) % 24) as u8
+    }
+
+    /// Returns the minute on the in-game clock, in the range `0..60`.
+    pub fn minutes(self) -> u8 {
+        (u32::from(self.0 % 1000) * 60 / 1000) as u8
+    }
+}
+
+/// A phase of the Minecraft day-night cycle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum DayPhase {
+    /// The sun is up, from [`TimeOfDay`] `0` until [`TimeOfDay::SUNSET`].
+    Day,
+    /// The sun is setting, from [`TimeOfDay::SUNSET`] until [`TimeOfDay::NIGHT`].
+    Sunset,
+    /// The moon is up, from [`TimeOfDay::NIGHT`] until [`TimeOfDay::SUNRISE`].
+    Night,
+    /// The sun is rising, from [`TimeOfDay::SUNRISE`] until the end of the day.
+    Sunrise,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{DayPhase, Ticks, TimeOfDay};
+
+    #[test]
+    fn seconds() {
+        assert_eq!(Ticks::from_seconds(0.0), Ticks(0));
+        assert_eq!(Ticks::from_seconds(1.0), Ticks(20));
+        assert_eq!(Ticks::from_seconds(2.5), Ticks(50));
+        // Rounded to the nearest tick.
+        assert_eq!(Ticks::from_seconds(0.026), Ticks(1));
+        assert_eq!(Ticks::from_seconds(0.024), Ticks(0));
+        assert_eq!(Ticks(30).as_seconds(), 1.5);
+        assert_eq!(Ticks(-20).as_seconds(), -1.0);
+        assert_eq!(Ticks::from_duration(Duration::from_millis(1250)), Ticks(25));
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(Ticks::from_minecraft_days(0), Ticks(0));
+        assert_eq!(Ticks::from_minecraft_days(3), Ticks(72000));
+        assert_eq!(Ticks(36000).as_minecraft_days(), 1.5);
+        assert_eq!(Ticks(24000) + Ticks(6000), Ticks(30000));
+    }
+
+    #[test]
+    fn time_of_day_wraps() {
+        assert_eq!(Ticks(0).time_of_day(), TimeOfDay::default());
+        assert_eq!(Ticks(30000).time_of_day(), TimeOfDay::NOON);
+        assert_eq!(Ticks(-6000).time_of_day(), TimeOfDay::MIDNIGHT);
+        assert_eq!(TimeOfDay::from_ticks(Ticks(24000)).ticks(), Ticks(0));
+    }
+
+    #[test]
+    fn phases() {
+        let phase = |ticks| Ticks(ticks).time_of_day().phase();
+        assert_eq!(phase(0), DayPhase::Day);
+        assert_eq!(phase(11999), DayPhase::Day);
+        assert_eq!(phase(12000), DayPhase::Sunset);
+        assert_eq!(phase(12999), DayPhase::Sunset);
+        assert_eq!(phase(13000), DayPhase::Night);
+        assert_eq!(phase(22999), DayPhase::Night);
+        assert_eq!(phase(23000), DayPhase::Sunrise);
+        assert_eq!(phase(23999), DayPhase::Sunrise);
+        assert_eq!(phase(24000), DayPhase::Day);
+    }
+
+    #[test]
+    fn clock() {
+        assert_eq!(
+            (TimeOfDay::default().hours(), TimeOfDay::default().minutes()),
+            (6, 0)
+        );
+        assert_eq!(
+            (TimeOfDay::NOON.hours(), TimeOfDay::NOON.minutes()),
+            (12, 0)
+        );
+        assert_eq!(TimeOfDay::MIDNIGHT.hours(), 0);
+        let time = Ticks(18500).time_of_day();
+        assert_eq!((time.hours(), time.minutes()), (0, 30));
+    }
+
+    #[test]
+    fn as_duration() {
+        assert_eq!(Ticks(0).as_duration(), Some(Duration::ZERO));
+        assert_eq!(Ticks(1).as_duration(), Some(Duration::from_millis(50)));
+        assert_eq!(Ticks(25).as_duration(), Some(Duration::from_millis(1250)));
+        assert_eq!(Ticks(-1).as_duration(), None);
+    }
+
+    #[test]
+    fn as_duration_does_not_overflow() {
+        let duration = Ticks(i64::MAX).as_duration().unwrap();
+        assert_eq!(duration.as_secs(), i64::MAX as u64 / 20);
+    }
+}
@@ -0,0 +1,27 @@
+//! Reads the input as nameless network NBT.
+//!
+//! Reading may fail, but must never panic, overflow the stack, or allocate
+//! without bound. A tag that reads successfully must write back to bytes that
+//! read as the same tag, and its serialized length must match those bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minecrevy_io::{nbt::Tag, McRead, McWrite};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(tag) = Tag::read(data, ()) else {
+        return;
+    };
+
+    let mut bytes = Vec::new();
+    tag.write(&mut bytes, ())
+        .expect("a read tag can be written");
+    assert_eq!(tag.serialized_len().unwrap(), bytes.len());
+
+    let reread = Tag::read(bytes.as_slice(), ()).expect("a written tag can be read");
+    // Compare the bytes, as NaN floats aren't equal to themselves.
+    let mut rewritten = Vec::new();
+    reread.write(&mut rewritten, ()).unwrap();
+    assert_eq!(bytes, rewritten);
+});
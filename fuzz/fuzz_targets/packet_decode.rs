@@ -0,0 +1,65 @@
+//! Splits the input into frames with [`RawPacketCodec`], then decodes each
+//! frame's body as every serverbound packet.
+//!
+//! Decoding may fail, but must never panic or allocate without bound.
+
+#![no_main]
+
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use minecrevy_io::{packet::codec::RawPacketCodec, McRead};
+use minecrevy_protocol::{config, handshake, login, play, status};
+use tokio_util::codec::Decoder;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = RawPacketCodec::new(Arc::default());
+    let mut src = BytesMut::from(data);
+    while let Ok(Some(packet)) = codec.decode(&mut src) {
+        decode_all(&packet.body);
+    }
+});
+
+/// Decodes the given packet body as each serverbound packet, ignoring the
+/// results.
+fn decode_all(body: &[u8]) {
+    decode::<handshake::Handshake>(body);
+
+    decode::<status::Request>(body);
+    decode::<status::Ping>(body);
+
+    decode::<login::LoginStart>(body);
+    decode::<login::EncryptionResponse>(body);
+    decode::<login::LoginAcknowledged>(body);
+
+    decode::<config::ServerboundKnownPacks>(body);
+    decode::<config::AcknowledgeFinishConfiguration>(body);
+
+    decode::<play::KeepAlive>(body);
+    decode::<play::Pong>(body);
+    decode::<play::PluginMessage>(body);
+    decode::<play::ConfirmTeleport>(body);
+    decode::<play::SetPlayerPosition>(body);
+    decode::<play::SetPlayerPositionAndRotation>(body);
+    decode::<play::Spectate>(body);
+    decode::<play::SwingArm>(body);
+    decode::<play::PlayerAction>(body);
+    decode::<play::UseItemOn>(body);
+    decode::<play::SetHeldItem>(body);
+    decode::<play::SetCreativeModeSlot>(body);
+    decode::<play::EntityAction>(body);
+    decode::<play::UpdateSign>(body);
+    decode::<play::ProgramCommandBlock>(body);
+    decode::<play::ProgramJigsawBlock>(body);
+    decode::<play::ProgramStructureBlock>(body);
+    decode::<play::ChatCommand>(body);
+    decode::<play::ResourcePackResponse>(body);
+    decode::<play::SetDifficulty>(body);
+    decode::<play::LockDifficulty>(body);
+}
+
+/// Decodes the given packet body as a `T`, ignoring the result.
+fn decode<T: McRead<Args = ()>>(body: &[u8]) {
+    let _ = T::read(body, ());
+}
@@ -0,0 +1,27 @@
+//! Reads the input as a VarInt and a VarLong.
+//!
+//! Reading may fail, but must never panic. A value that reads successfully
+//! must write back to bytes that read as the same value, with the length
+//! [`varint_bytes`] reports.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minecrevy_io::prelude::{varint_bytes, ReadMinecraftExt, WriteMinecraftExt};
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = data;
+    if let Ok(value) = reader.read_var_i32() {
+        let mut bytes = Vec::new();
+        bytes.write_var_i32(value).unwrap();
+        assert_eq!(bytes.len(), varint_bytes(value));
+        assert_eq!(bytes.as_slice().read_var_i32().unwrap(), value);
+    }
+
+    let mut reader = data;
+    if let Ok(value) = reader.read_var_i64() {
+        let mut bytes = Vec::new();
+        bytes.write_var_i64(value).unwrap();
+        assert_eq!(bytes.as_slice().read_var_i64().unwrap(), value);
+    }
+});
@@ -96,7 +96,7 @@ fn main() {
             online: args.online_players,
             max: args.max_players,
         })
-        .insert_resource(AllowLogin(Err(Some(args.deny_login))))
+        .insert_resource(AllowLogin::deny(args.deny_login))
         .add_systems(
             Startup,
             start_server(SocketAddr::new(args.address, args.port)),